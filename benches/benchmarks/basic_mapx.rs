@@ -18,7 +18,7 @@ fn read_write(c: &mut Criterion) {
     group.bench_function(" write ", |b| {
         b.iter(|| {
             let n = i.fetch_add(1, Ordering::SeqCst);
-            db.set_value([n; 2], vec![n; 128]);
+            db.set_value([n; 2], vec![n; 128]).unwrap();
         })
     });
 
@@ -44,7 +44,7 @@ fn random_read_write(c: &mut Criterion) {
         b.iter(|| {
             let n = rng.gen::<usize>();
             let key = [n; 2];
-            db.set_value(key, vec![n; 128]);
+            db.set_value(key, vec![n; 128]).unwrap();
             keys.push(key);
         })
     });
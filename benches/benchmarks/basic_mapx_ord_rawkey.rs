@@ -20,7 +20,7 @@ fn read_write(c: &mut Criterion) {
         b.iter(|| {
             let n = i.fetch_add(1, Ordering::SeqCst);
             let key = <usize as ValueEnDe>::encode(&n);
-            db.set_value(key, n);
+            db.set_value(key, n).unwrap();
         })
     });
 
@@ -48,7 +48,7 @@ fn random_read_write(c: &mut Criterion) {
         b.iter(|| {
             let n = rng.gen::<usize>();
             let key = <usize as ValueEnDe>::encode(&n);
-            db.set_value(key.clone(), n);
+            db.set_value(key.clone(), n).unwrap();
             keys.push(key);
         })
     });
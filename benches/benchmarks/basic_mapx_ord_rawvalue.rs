@@ -20,7 +20,7 @@ fn read_write(c: &mut Criterion) {
         b.iter(|| {
             let n = i.fetch_add(1, Ordering::SeqCst);
             let val = <usize as ValueEnDe>::encode(&n);
-            db.set_value(n, val);
+            db.set_value(n, val).unwrap();
         })
     });
 
@@ -47,7 +47,7 @@ fn random_read_write(c: &mut Criterion) {
         b.iter(|| {
             let n = rng.gen::<usize>();
             let val = <usize as ValueEnDe>::encode(&n);
-            db.set_value(n, val);
+            db.set_value(n, val).unwrap();
             keys.push(n);
         })
     });
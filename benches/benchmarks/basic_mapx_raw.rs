@@ -18,7 +18,7 @@ fn read_write(c: &mut Criterion) {
         b.iter(|| {
             let n = i.fetch_add(1, Ordering::SeqCst);
             let val = n.to_be_bytes();
-            db.insert(&val, &val);
+            db.insert(&val, &val).unwrap();
         })
     });
 
@@ -44,7 +44,7 @@ fn random_read_write(c: &mut Criterion) {
         b.iter(|| {
             let n: usize = rng.gen();
             let key = n.to_be_bytes();
-            db.insert(&key, &key);
+            db.insert(&key, &key).unwrap();
             keys.push(key);
         })
     });
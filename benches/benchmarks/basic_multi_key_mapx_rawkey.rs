@@ -57,11 +57,11 @@ fn random_read_write(c: &mut Criterion) {
     group.bench_function(" random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|i| {
+            if let Some(i) = keys.get(index) {
                 let n = i.to_be_bytes();
                 let key: &[&[u8]] = &[&n, &n];
                 db.get(key);
-            });
+            }
         })
     });
 
@@ -52,7 +52,7 @@ fn random_read_write(c: &mut Criterion) {
     group.bench_function(" random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|key| db.get(&(&key, &key, &key)));
+            keys.get(index).map(|key| db.get(&(key, key, key)));
         })
     });
     group.finish();
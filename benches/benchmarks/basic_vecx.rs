@@ -18,7 +18,7 @@ fn read_write(c: &mut Criterion) {
     group.bench_function(" write ", |b| {
         b.iter(|| {
             let n = i.fetch_add(1, Ordering::SeqCst);
-            db.push(vec![n; 128]);
+            db.push(vec![n; 128]).unwrap();
         })
     });
 
@@ -42,7 +42,7 @@ fn random_read_write(c: &mut Criterion) {
     group.bench_function(" random write ", |b| {
         b.iter(|| {
             let n = rng.gen::<usize>();
-            db.push(vec![n; 128]);
+            db.push(vec![n; 128]).unwrap();
         })
     });
 
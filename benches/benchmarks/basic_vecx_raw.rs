@@ -19,7 +19,7 @@ fn read_write(c: &mut Criterion) {
         b.iter(|| {
             let n = i.fetch_add(1, Ordering::SeqCst);
             let val = <usize as ValueEnDe>::encode(&n);
-            db.push(val);
+            db.push(val).unwrap();
         })
     });
 
@@ -44,7 +44,7 @@ fn random_read_write(c: &mut Criterion) {
         b.iter(|| {
             let n = rng.gen::<usize>();
             let val = <usize as ValueEnDe>::encode(&n);
-            db.push(val);
+            db.push(val).unwrap();
         })
     });
 
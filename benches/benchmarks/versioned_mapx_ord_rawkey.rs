@@ -125,13 +125,13 @@ fn version_random_read_write(c: &mut Criterion) {
     group.bench_function(" version random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|key| {
+            if let Some(key) = keys.get(index) {
                 db.get_by_branch_version(
                     key,
                     db.branch_get_default().as_deref(),
                     VersionName(key),
                 );
-            });
+            }
         })
     });
     group.finish();
@@ -195,10 +195,10 @@ fn branch_version_random_read_write(c: &mut Criterion) {
     group.bench_function(" branch version random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|key| {
+            if let Some(key) = keys.get(index) {
                 let key = key.as_ref();
                 db.get_by_branch_version(key, BranchName(key), VersionName(key));
-            });
+            }
         })
     });
     group.finish();
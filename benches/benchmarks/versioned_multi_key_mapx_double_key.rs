@@ -58,7 +58,7 @@ fn random_read_write(c: &mut Criterion) {
     group.bench_function(" random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|n| db.get(&(&n, &n)));
+            keys.get(index).map(|n| db.get(&(n, n)));
         })
     });
     group.finish();
@@ -120,13 +120,13 @@ fn version_random_read_write(c: &mut Criterion) {
     group.bench_function(" version random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|n| {
+            if let Some(n) = keys.get(index) {
                 db.get_by_branch_version(
-                    &(&n, &n),
+                    &(n, n),
                     db.branch_get_default().as_deref(),
                     VersionName(&n.to_be_bytes()),
                 );
-            });
+            }
         })
     });
     group.finish();
@@ -189,10 +189,10 @@ fn branch_version_random_read_write(c: &mut Criterion) {
     group.bench_function(" branch version random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|n| {
+            if let Some(n) = keys.get(index) {
                 let name = &n.to_be_bytes();
-                db.get_by_branch_version(&(&n, &n), BranchName(name), VersionName(name));
-            });
+                db.get_by_branch_version(&(n, n), BranchName(name), VersionName(name));
+            }
         })
     });
     group.finish();
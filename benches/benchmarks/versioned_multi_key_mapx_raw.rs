@@ -127,13 +127,13 @@ fn version_random_read_write(c: &mut Criterion) {
     group.bench_function(" random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|n| {
+            if let Some(n) = keys.get(index) {
                 db.get_by_branch_version(
                     &[&n.to_be_bytes(), &n.to_be_bytes()],
                     db.branch_get_default().as_deref(),
                     VersionName(&n.to_be_bytes()),
                 );
-            });
+            }
         })
     });
     group.finish();
@@ -199,10 +199,10 @@ fn branch_version_random_read_write(c: &mut Criterion) {
     group.bench_function(" branch version random read ", |b| {
         b.iter(|| {
             let index: usize = rng.gen_range(0..keys.len());
-            keys.get(index).map(|n| {
+            if let Some(n) = keys.get(index) {
                 let key = &n.to_be_bytes();
                 db.get_by_branch_version(&[key, key], BranchName(key), VersionName(key));
-            });
+            }
         })
     });
     group.finish();
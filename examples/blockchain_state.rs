@@ -118,7 +118,7 @@ impl WorldState {
         &mut self,
         tx: &Transaction,
     ) -> Result<()> {
-        if tx.from.get(0).is_some() {
+        if !tx.from.is_empty() {
             Ok(())
         } else {
             // ..........
@@ -52,18 +52,18 @@ impl AnimalHospital {
         };
         self.owners
             .entry(owner)
-            .or_insert(Mapx::new())
-            .insert(new_id, animal);
+            .or_default()
+            .insert(new_id, animal).unwrap();
 
         Ok(new_id)
     }
 
     fn discharged(&mut self, owner: AnimalOwner, id: AnimalID) -> Result<()> {
         if let Some(mut animals) = self.owners.get_mut(&owner) {
-            if animals.remove(&id).is_none() {
+            if animals.remove(&id).unwrap().is_none() {
                 return Err(eg!("animal ID not found"));
             }
-            self.discharge_history.push(id);
+            self.discharge_history.push(id).unwrap();
 
             Ok(())
         } else {
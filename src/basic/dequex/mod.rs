@@ -0,0 +1,368 @@
+//!
+//! A disk-storage replacement for the in-memory VecDeque.
+//!
+//! NOTE:
+//! - Values will be encoded by some `serde`-like methods
+//! - Pushing and popping at both ends cost O(1)
+//!
+//! # Examples
+//!
+//! ```
+//! use vsdb::basic::dequex::Dequex;
+//!
+//! let dir = format!("/tmp/__vsdb__{}", rand::random::<u128>());
+//! vsdb::vsdb_set_base_dir(&dir);
+//!
+//! let mut l = Dequex::new();
+//!
+//! l.push_back(1).unwrap();
+//! l.push_front(0).unwrap();
+//! assert_eq!(l.len(), 2);
+//! assert_eq!(l.iter().collect::<Vec<_>>(), vec![0, 1]);
+//!
+//! assert_eq!(l.pop_front().unwrap(), Some(0));
+//! assert_eq!(l.pop_back().unwrap(), Some(1));
+//! assert!(l.is_empty());
+//! ```
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::mapx_ord_rawkey::{MapxOrdRawKey, ValueMut},
+    common::{
+        ende::ValueEnDe,
+        ensure_writable,
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+        RawKey, RawValue,
+    },
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::ops::{Bound, RangeBounds};
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(bound = "")]
+pub struct Dequex<T> {
+    // the elements, along with their bounds under the `META_KEY`
+    inner: MapxOrdRawKey<T>,
+}
+
+impl<T: ValueEnDe> Dequex<T> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        Dequex {
+            inner: MapxOrdRawKey::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get(&self, idx: usize) -> Option<T> {
+        let (head, tail) = self.bounds();
+        let pos = offset(head, tail, idx)?;
+        self.inner.get(&pos_key(pos))
+    }
+
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, idx: usize) -> VsdbResult<Option<T>> {
+        let (head, tail) = self.try_bounds()?;
+        match offset(head, tail, idx) {
+            Some(pos) => self.inner.try_get(&pos_key(pos)),
+            None => Ok(None),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_mut(&mut self, idx: usize) -> Option<ValueMut<'_, T>> {
+        let (head, tail) = self.bounds();
+        let k = offset(head, tail, idx).map(pos_key)?;
+        self.inner
+            .get(&k)
+            .map(|v| ValueMut::new(&mut self.inner, k, v))
+    }
+
+    #[inline(always)]
+    pub fn front(&self) -> Option<T> {
+        self.get(0)
+    }
+
+    #[inline(always)]
+    pub fn back(&self) -> Option<T> {
+        alt!(self.is_empty(), return None);
+        self.get(self.len() - 1)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        let (head, tail) = self.bounds();
+        (tail - head) as usize
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        0 == self.len()
+    }
+
+    #[inline(always)]
+    pub fn push_front(&mut self, v: T) -> VsdbResult<()> {
+        self.push_front_ref(&v)
+    }
+
+    #[inline(always)]
+    pub fn push_front_ref(&mut self, v: &T) -> VsdbResult<()> {
+        let (head, tail) = self.bounds();
+        let v = self.inner.encode_value(v);
+        self.write(head - 1, Some(v), (head - 1, tail))
+    }
+
+    #[inline(always)]
+    pub fn push_back(&mut self, v: T) -> VsdbResult<()> {
+        self.push_back_ref(&v)
+    }
+
+    #[inline(always)]
+    pub fn push_back_ref(&mut self, v: &T) -> VsdbResult<()> {
+        let (head, tail) = self.bounds();
+        let v = self.inner.encode_value(v);
+        self.write(tail, Some(v), (head, tail + 1))
+    }
+
+    #[inline(always)]
+    pub fn pop_front(&mut self) -> VsdbResult<Option<T>> {
+        let (head, tail) = self.bounds();
+        alt!(head == tail, return Ok(None));
+        let v = self.inner.get(&pos_key(head));
+        self.write(head, None, (head + 1, tail))?;
+        Ok(v)
+    }
+
+    #[inline(always)]
+    pub fn pop_back(&mut self) -> VsdbResult<Option<T>> {
+        let (head, tail) = self.bounds();
+        alt!(head == tail, return Ok(None));
+        let v = self.inner.get(&pos_key(tail - 1));
+        self.write(tail - 1, None, (head, tail - 1))?;
+        Ok(v)
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> DequexIter<'_, T> {
+        self.range(..)
+    }
+
+    /// Iterate over the elements whose positions are within `bounds`,
+    /// the positions beyond the length will be ignored.
+    pub fn range<R: RangeBounds<usize>>(&self, bounds: R) -> DequexIter<'_, T> {
+        let (head, tail) = self.bounds();
+        let (lo, hi) = positions(head, tail, bounds);
+        DequexIter {
+            hdr: &self.inner,
+            lo,
+            hi,
+        }
+    }
+
+    /// Same as `iter`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> VsdbResult<DequexTryIter<'_, T>> {
+        self.try_range(..)
+    }
+
+    /// Same as `range`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    pub fn try_range<R: RangeBounds<usize>>(
+        &self,
+        bounds: R,
+    ) -> VsdbResult<DequexTryIter<'_, T>> {
+        let (head, tail) = self.try_bounds()?;
+        let (lo, hi) = positions(head, tail, bounds);
+        Ok(DequexTryIter {
+            hdr: &self.inner,
+            lo,
+            hi,
+        })
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        self.inner.clear()
+    }
+
+    // `[head, tail)`, the positions of the elements
+    #[inline(always)]
+    fn bounds(&self) -> (i64, i64) {
+        self.inner
+            .get_raw(META_KEY)
+            .map(|b| decode_bounds(&b))
+            .unwrap_or((ORIGIN, ORIGIN))
+    }
+
+    #[inline(always)]
+    fn try_bounds(&self) -> VsdbResult<(i64, i64)> {
+        self.inner
+            .try_get_raw(META_KEY)
+            .map(|b| b.map(|b| decode_bounds(&b)).unwrap_or((ORIGIN, ORIGIN)))
+    }
+
+    // write an element along with the new bounds in one batch
+    #[inline(always)]
+    fn write(
+        &mut self,
+        pos: i64,
+        v: Option<RawValue>,
+        bounds: (i64, i64),
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+        self.inner.write_batch_raw([
+            (pos_key(pos), v),
+            (Box::from(META_KEY), Some(encode_bounds(bounds))),
+        ]);
+        Ok(())
+    }
+}
+
+impl<T> Clone for Dequex<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: ValueEnDe> Default for Dequex<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ValueEnDe + GcMark> GcMark for Dequex<T> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        // the bounds are not a value of `T`
+        if self.inner.gc_mark_instance(marks) && !T::gc_nope() {
+            self.iter().for_each(|v| v.gc_mark(marks));
+        }
+    }
+}
+
+// the elements are read one by one by their positions,
+// which are always contiguous within `[lo, hi)`
+pub struct DequexIter<'a, T: ValueEnDe> {
+    hdr: &'a MapxOrdRawKey<T>,
+    lo: i64,
+    hi: i64,
+}
+
+impl<'a, T: ValueEnDe> Iterator for DequexIter<'a, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+        self.lo += 1;
+        self.hdr.get(&pos_key(self.lo - 1))
+    }
+}
+
+impl<'a, T: ValueEnDe> DoubleEndedIterator for DequexIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+        self.hi -= 1;
+        self.hdr.get(&pos_key(self.hi))
+    }
+}
+
+pub struct DequexTryIter<'a, T: ValueEnDe> {
+    hdr: &'a MapxOrdRawKey<T>,
+    lo: i64,
+    hi: i64,
+}
+
+impl<'a, T: ValueEnDe> Iterator for DequexTryIter<'a, T> {
+    type Item = VsdbResult<T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+        self.lo += 1;
+        self.hdr.try_get(&pos_key(self.lo - 1)).transpose()
+    }
+}
+
+impl<'a, T: ValueEnDe> DoubleEndedIterator for DequexTryIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+        self.hi -= 1;
+        self.hdr.try_get(&pos_key(self.hi)).transpose()
+    }
+}
+
+// the position of the first element pushed into an empty queue
+pub(crate) const ORIGIN: i64 = 0;
+
+// never collides with the keys of the positions
+pub(crate) const META_KEY: &[u8] = &[];
+
+// the sign bit is flipped,
+// so that the negative positions sort before the positive ones
+#[inline(always)]
+pub(crate) fn pos_key(pos: i64) -> RawKey {
+    Box::new(((pos as u64) ^ (1 << 63)).to_be_bytes())
+}
+
+#[inline(always)]
+pub(crate) fn encode_bounds((head, tail): (i64, i64)) -> RawValue {
+    let mut b = head.to_be_bytes().to_vec();
+    b.extend_from_slice(&tail.to_be_bytes());
+    b.into_boxed_slice()
+}
+
+#[inline(always)]
+pub(crate) fn decode_bounds(b: &[u8]) -> (i64, i64) {
+    (
+        crate::parse_int!(b[..8], i64),
+        crate::parse_int!(b[8..], i64),
+    )
+}
+
+// the position of the `idx`-th element within `[head, tail)`
+#[inline(always)]
+pub(crate) fn offset(head: i64, tail: i64, idx: usize) -> Option<i64> {
+    i64::try_from(idx)
+        .ok()
+        .and_then(|i| head.checked_add(i))
+        .filter(|pos| *pos < tail)
+}
+
+// convert `bounds` of indexes to a `[lo, hi)` range of positions within `[head, tail)`
+pub(crate) fn positions<R: RangeBounds<usize>>(
+    head: i64,
+    tail: i64,
+    bounds: R,
+) -> (i64, i64) {
+    let len = (tail - head) as usize;
+    let lo = match bounds.start_bound() {
+        Bound::Included(i) => *i,
+        Bound::Excluded(i) => i.saturating_add(1),
+        Bound::Unbounded => 0,
+    }
+    .min(len);
+    let hi = match bounds.end_bound() {
+        Bound::Included(i) => i.saturating_add(1),
+        Bound::Excluded(i) => *i,
+        Bound::Unbounded => len,
+    }
+    .clamp(lo, len);
+    (head + lo as i64, head + hi as i64)
+}
@@ -0,0 +1,81 @@
+use super::*;
+use std::collections::VecDeque;
+
+#[test]
+fn test_push_pop() {
+    let mut hdr = Dequex::new();
+    let mut model = VecDeque::new();
+
+    for i in 0..500usize {
+        if 0 == i % 3 {
+            hdr.push_front(i);
+            model.push_front(i);
+        } else {
+            hdr.push_back(i);
+            model.push_back(i);
+        }
+    }
+    assert_eq!(hdr.len(), model.len());
+    assert_eq!(hdr.front(), model.front().copied());
+    assert_eq!(hdr.back(), model.back().copied());
+    assert!(hdr.iter().eq(model.iter().copied()));
+    assert!(hdr.iter().rev().eq(model.iter().rev().copied()));
+    (0..model.len()).for_each(|i| assert_eq!(hdr.get(i), Some(model[i])));
+    assert!(hdr.get(model.len()).is_none());
+
+    for i in 0..400 {
+        if 0 == i % 2 {
+            assert_eq!(hdr.pop_front().unwrap(), model.pop_front());
+        } else {
+            assert_eq!(hdr.pop_back().unwrap(), model.pop_back());
+        }
+    }
+    assert!(hdr.iter().eq(model.iter().copied()));
+
+    *hdr.get_mut(3).unwrap() = 0;
+    model[3] = 0;
+    assert_eq!(hdr.get(3), Some(0));
+
+    while let Some(v) = model.pop_back() {
+        assert_eq!(hdr.pop_back().unwrap(), Some(v));
+    }
+    assert!(hdr.is_empty());
+    assert!(hdr.pop_front().unwrap().is_none());
+    assert!(hdr.pop_back().unwrap().is_none());
+    assert!(hdr.front().is_none());
+    assert!(hdr.back().is_none());
+}
+
+#[test]
+fn test_range() {
+    let mut hdr = Dequex::new();
+    (0..100usize).for_each(|i| hdr.push_front(i).unwrap());
+    (100..200usize).for_each(|i| hdr.push_back(i).unwrap());
+    let model = hdr.iter().collect::<Vec<_>>();
+
+    assert!(hdr.range(..).eq(model.iter().copied()));
+    assert!(hdr.range(50..150).eq(model[50..150].iter().copied()));
+    assert!(hdr.range(..=99).eq(model[..=99].iter().copied()));
+    assert!(hdr.range(190..500).eq(model[190..].iter().copied()));
+    assert!(hdr.range(300..).next().is_none());
+    assert!(
+        hdr.range((Bound::Included(20), Bound::Excluded(10)))
+            .next()
+            .is_none()
+    );
+
+    hdr.clear();
+    assert!(hdr.is_empty());
+    assert!(hdr.range(..).next().is_none());
+    hdr.push_front(1);
+    assert_eq!(hdr.iter().collect::<Vec<_>>(), vec![1]);
+}
+
+#[test]
+fn test_valueende() {
+    let mut hdr = Dequex::new();
+    (0..10usize).for_each(|i| hdr.push_front(i).unwrap());
+    let hdr = <Dequex<usize> as ValueEnDe>::decode(&hdr.encode()).unwrap();
+    assert_eq!(hdr.len(), 10);
+    assert!(hdr.iter().eq((0..10).rev()));
+}
@@ -0,0 +1,869 @@
+//!
+//! A counted B+ tree that maps positions to element IDs,
+//! it makes the positional insertion and removal of vectors logarithmic.
+//!
+//! NOTE:
+//! - Leaves hold the IDs of elements in order, the values are stored elsewhere
+//! - Branches record the number of elements under each of their children
+//! - IDs of elements are allocated incrementally and never reused
+//! - A legacy vector is indexed by the positions until its first write,
+//!   then the built index is recorded in its values for its other handles
+//!
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::mapx_raw::MapxRaw,
+    common::{
+        ende::{SimpleVisitor, ValueEn, ValueEnDe},
+        gc::{GcMark, GcMarks},
+        RawKey, RawValue,
+    },
+};
+use ruc::*;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    marker::PhantomData,
+    mem::size_of,
+    result::Result as StdResult,
+};
+
+/// The number of elements written in one round of the bulk insertions.
+pub(crate) const INSERT_BATCH_SIZE: usize = 4096;
+
+// the root always lives here, no matter how the tree grows or shrinks
+const ROOT_ID: u64 = 0;
+
+// the allocators of node IDs and element IDs
+const META_KEY: [u8; 0] = [];
+
+// the handle of the values of the legacy vector indexed by this tree,
+// it is shorter than the keys of nodes, so it will never be overwritten
+const LEGACY_VALUES_KEY: [u8; 1] = [0];
+
+// the handle of the index built for a legacy vector is kept in its values,
+// it is shorter than the keys of elements, so it will never be overwritten,
+// the legacy handles saved elsewhere will find the index by it
+const LEGACY_INDEX_KEY: [u8; 0] = [];
+
+const MAX_ENTRIES: usize = 256;
+
+// a smaller node will be merged with one of its siblings if possible
+const MIN_ENTRIES: usize = MAX_ENTRIES / 4;
+
+// write the cached new nodes to the disk when there are so many cached nodes
+const MAX_DIRTY_NODES: usize = 1024;
+
+const TAG_LEAF: u8 = 0;
+const TAG_BRANCH: u8 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct IndexTree {
+    nodes: MapxRaw,
+    // a legacy vector whose index has not been built,
+    // `nodes` is the instance of its values, whose keys are the positions,
+    // so the positions are used as the IDs of the elements
+    #[serde(skip)]
+    positional: bool,
+}
+
+impl IndexTree {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub(crate) unsafe fn shadow(&self) -> Self {
+        Self {
+            nodes: self.nodes.shadow(),
+            positional: self.positional,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn new() -> Self {
+        let mut hdr = IndexTree {
+            nodes: MapxRaw::new_untracked(),
+            positional: false,
+        };
+        hdr.init();
+        hdr
+    }
+
+    // build an index with the positions adopted as the IDs of the elements
+    fn new_positional(len: usize) -> Self {
+        let mut hdr = Self::new();
+        (0..len).step_by(INSERT_BATCH_SIZE).for_each(|idx| {
+            hdr.insert(idx, INSERT_BATCH_SIZE.min(len - idx), |_| {});
+        });
+        hdr
+    }
+
+    // the index of a legacy vector will be built on its first write,
+    // so loading a legacy handle never writes anything
+    #[inline(always)]
+    fn materialize(&mut self) {
+        if self.positional {
+            self.adopt(Self::new_positional(self.nodes.len()));
+        }
+    }
+
+    // replace the positional index with a built one,
+    // and record the built one in the values of the legacy vector
+    fn adopt(&mut self, index: Self) {
+        let values = std::mem::replace(self, index).nodes;
+        self.record_legacy_values(values);
+    }
+
+    fn record_legacy_values(&mut self, mut values: MapxRaw) {
+        pnk!(values.insert(&LEGACY_INDEX_KEY, &self.nodes.encode()));
+        pnk!(self.nodes.insert(&LEGACY_VALUES_KEY, &values.encode()));
+    }
+
+    fn init(&mut self) {
+        let mut txn = Txn::new(&mut self.nodes);
+        txn.next_node_id = 1 + ROOT_ID;
+        txn.next_elem_id = 0;
+        txn.put(ROOT_ID, Node::Leaf(vec![]));
+        txn.commit();
+    }
+
+    #[inline(always)]
+    pub(crate) fn len(&self) -> usize {
+        if self.positional {
+            return self.nodes.len();
+        }
+        read_node(&self.nodes, ROOT_ID).count() as usize
+    }
+
+    /// The ID of the element at the position `idx`.
+    pub(crate) fn get(&self, idx: usize) -> Option<u64> {
+        if self.positional {
+            return alt!(idx < self.len(), Some(idx as u64), None);
+        }
+
+        let mut idx = idx as u64;
+        let mut node = read_node(&self.nodes, ROOT_ID);
+        loop {
+            match node {
+                Node::Leaf(ids) => return ids.get(idx as usize).copied(),
+                Node::Branch(children) => {
+                    let (slot, offset) = locate(&children, idx);
+                    idx = offset;
+                    node = read_node(&self.nodes, children[slot].0);
+                }
+            }
+        }
+    }
+
+    // the IDs held by the leaf that covers the position `idx`,
+    // along with the position of its first element
+    fn leaf_at(&self, idx: u64) -> Option<(u64, Vec<u64>)> {
+        if self.positional {
+            let len = self.len() as u64;
+            alt!(idx >= len, return None);
+            let start = idx - idx % MAX_ENTRIES as u64;
+            return Some((start, (start..len.min(start + MAX_ENTRIES as u64)).collect()));
+        }
+
+        let mut start = 0;
+        let mut offset = idx;
+        let mut node = read_node(&self.nodes, ROOT_ID);
+        loop {
+            match node {
+                Node::Leaf(ids) => {
+                    return alt!(
+                        (offset as usize) < ids.len(),
+                        Some((start, ids)),
+                        None
+                    );
+                }
+                Node::Branch(children) => {
+                    let (slot, o) = locate(&children, offset);
+                    start += offset - o;
+                    offset = o;
+                    node = read_node(&self.nodes, children[slot].0);
+                }
+            }
+        }
+    }
+
+    /// Insert `n` new elements before the position `idx`,
+    /// their IDs are consecutive and the first one is passed to `write_values`,
+    /// which will be called before the tree is changed.
+    pub(crate) fn insert<F>(&mut self, idx: usize, n: usize, write_values: F)
+    where
+        F: FnOnce(u64),
+    {
+        self.materialize();
+        let mut txn = Txn::new(&mut self.nodes);
+
+        let first = txn.next_elem_id;
+        txn.next_elem_id += n as u64;
+        write_values(first);
+
+        let mut path = txn.path(idx as u64);
+        let (mut id, mut node, pos) = path.pop().unwrap();
+        node.leaf_mut().splice(pos..pos, first..first + n as u64);
+
+        while let Some((parent_id, mut parent, slot)) = path.pop() {
+            let pieces = txn.put_split(id, node);
+            parent.branch_mut().splice(slot..=slot, pieces);
+            id = parent_id;
+            node = parent;
+        }
+
+        txn.put_root(node);
+        txn.commit();
+    }
+
+    /// Remove the element at the position `idx`, and return its ID.
+    pub(crate) fn remove(&mut self, idx: usize) -> Option<u64> {
+        alt!(idx >= self.len(), return None);
+
+        self.materialize();
+        let mut txn = Txn::new(&mut self.nodes);
+
+        let mut path = txn.path(idx as u64);
+        let (mut id, mut node, pos) = path.pop().unwrap();
+        let removed = node.leaf_mut().remove(pos);
+
+        while let Some((parent_id, mut parent, slot)) = path.pop() {
+            txn.put_shrunk(parent.branch_mut(), slot, id, node);
+            id = parent_id;
+            node = parent;
+        }
+
+        txn.put_root(node);
+        txn.commit();
+
+        Some(removed)
+    }
+
+    /// Replace the element at the position `idx` with another ID,
+    /// and return the replaced one.
+    pub(crate) fn set(&mut self, idx: usize, elem_id: u64) -> Option<u64> {
+        alt!(idx >= self.len(), return None);
+
+        self.materialize();
+        let mut txn = Txn::new(&mut self.nodes);
+
+        let (id, mut node, pos) = txn.path(idx as u64).pop().unwrap();
+        let old = std::mem::replace(&mut node.leaf_mut()[pos], elem_id);
+        txn.put(id, node);
+        txn.commit();
+
+        Some(old)
+    }
+
+    /// Keep the first `len` elements, and return the IDs of the others.
+    pub(crate) fn truncate(&mut self, len: usize) -> Vec<u64> {
+        let mut removed = vec![];
+        alt!(len >= self.len(), return removed);
+
+        self.materialize();
+        let mut txn = Txn::new(&mut self.nodes);
+        let mut root = txn.get(ROOT_ID);
+        txn.cut(&mut root, len as u64, &mut removed);
+        txn.put_root(root);
+        txn.commit();
+
+        removed
+    }
+
+    /// Rebuild the whole tree with the IDs in order,
+    /// the current tree must not be changed during the rebuilding,
+    /// so the `ids` can be read from itself.
+    pub(crate) fn rebuild<I>(&mut self, ids: I)
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        // nothing of the positional index should be kept
+        if self.positional {
+            self.adopt(Self::new());
+        }
+
+        let mut txn = Txn::new(&mut self.nodes);
+        let old_node_ceiling = txn.next_node_id;
+
+        let mut level = vec![];
+        let mut ids = ids.into_iter().peekable();
+        while ids.peek().is_some() {
+            let leaf = Node::Leaf(ids.by_ref().take(MAX_ENTRIES).collect());
+            level.push(txn.put_new(leaf));
+        }
+        while level.len() > MAX_ENTRIES {
+            level = level
+                .chunks(MAX_ENTRIES)
+                .map(|c| txn.put_new(Node::Branch(c.to_vec())))
+                .collect();
+        }
+
+        txn.put_root(Node::Branch(level));
+        txn.commit();
+
+        // all the old nodes except the root are dropped in one shot
+        self.nodes.remove_range(
+            &(1 + ROOT_ID).to_be_bytes()[..]..&old_node_ceiling.to_be_bytes()[..],
+        );
+    }
+
+    /// IDs of the elements in the range `[lo, hi)`.
+    #[inline(always)]
+    pub(crate) fn iter_range(&self, lo: usize, hi: usize) -> IndexTreeIter<'_> {
+        IndexTreeIter {
+            hdr: self,
+            lo: lo as u64,
+            hi: hi.min(self.len()) as u64,
+            front: VecDeque::new(),
+            back: VecDeque::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn iter(&self) -> IndexTreeIter<'_> {
+        self.iter_range(0, usize::MAX)
+    }
+
+    /// NOTE: the values should be cleared before,
+    /// or the record of the index in the values of a legacy vector will be lost.
+    pub(crate) fn clear(&mut self) {
+        self.materialize();
+        let legacy_values = self.nodes.get(&LEGACY_VALUES_KEY);
+        pnk!(self.nodes.clear());
+        self.init();
+        if let Some(values) = legacy_values {
+            self.record_legacy_values(pnk!(MapxRaw::decode(&values)));
+        }
+    }
+}
+
+impl Clone for IndexTree {
+    fn clone(&self) -> Self {
+        if self.positional {
+            // the values are copied along with their keys by the vector
+            Self::new_positional(self.len())
+        } else {
+            // the copied values are not the ones of any legacy vector
+            let mut nodes = self.nodes.clone();
+            pnk!(nodes.remove(&LEGACY_VALUES_KEY));
+            Self {
+                nodes,
+                positional: false,
+            }
+        }
+    }
+}
+
+impl GcMark for IndexTree {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.nodes.gc_mark(marks);
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+/////////////////////////////////////////////////////////////////////////////
+
+// Handles of the vectors located by the index tree.
+//
+// A legacy vector stored its values with the positions as keys,
+// its handle is `{ inner: { inner: { inner: <instance config> }, p } }`.
+// The current handles keep the same shape, but the instance config
+// is replaced by `[VECX_TAG, VECX_VERSION]` and the encoded `(index, values)`.
+// A legacy vector keeps its legacy handle until its index is built.
+//
+// An instance config never starts with `VECX_TAG`, see `CFG_TAG` for details.
+const VECX_TAG: u8 = 0xfe;
+const VECX_VERSION: u8 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+// the shape of `MapxOrdRawKey` and `MapxOrdRawValue`
+#[derive(Serialize, Deserialize)]
+struct MapShape {
+    inner: Wrapper<Payload>,
+    p: PhantomData<()>,
+}
+
+struct Payload(Vec<u8>);
+
+impl Serialize for Payload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Payload {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+        deserializer.deserialize_bytes(SimpleVisitor).map(Payload)
+    }
+}
+
+pub(crate) fn serialize_vecx<S, V>(
+    index: &IndexTree,
+    values: &V,
+    serializer: S,
+) -> StdResult<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize,
+{
+    if index.positional {
+        return Wrapper { inner: values }.serialize(serializer);
+    }
+
+    let mut payload = vec![VECX_TAG, VECX_VERSION];
+    payload.extend_from_slice(&(index, values).encode_value());
+
+    Wrapper {
+        inner: MapShape {
+            inner: Wrapper {
+                inner: Payload(payload),
+            },
+            p: PhantomData,
+        },
+    }
+    .serialize(serializer)
+}
+
+// the index of a legacy vector will be built on its first write,
+// its values are adopted as they are, and the built index is recorded in them,
+// so the legacy handles saved before will use the built index
+pub(crate) fn deserialize_vecx<'de, D, V>(
+    deserializer: D,
+) -> StdResult<(IndexTree, V), D::Error>
+where
+    D: Deserializer<'de>,
+    V: ValueEnDe,
+{
+    let shape = Wrapper::<MapShape>::deserialize(deserializer)?.inner;
+
+    match shape.inner.inner.0.as_slice() {
+        [VECX_TAG, VECX_VERSION, hdr @ ..] => {
+            <(IndexTree, V) as ValueEnDe>::decode(hdr).map_err(de::Error::custom)
+        }
+        [VECX_TAG, ver, ..] => Err(de::Error::custom(format!(
+            "Unknown version of the vector handle: {}",
+            ver
+        ))),
+        _ => {
+            // both of them have the same shape as the legacy ones
+            let nodes = <MapxRaw as ValueEnDe>::decode(&shape.inner.encode())
+                .map_err(de::Error::custom)?;
+            let values =
+                <V as ValueEnDe>::decode(&shape.encode()).map_err(de::Error::custom)?;
+
+            let index = match nodes.get(&LEGACY_INDEX_KEY) {
+                Some(hdr) => IndexTree {
+                    nodes: <MapxRaw as ValueEnDe>::decode(&hdr)
+                        .map_err(de::Error::custom)?,
+                    positional: false,
+                },
+                None => IndexTree {
+                    nodes,
+                    positional: true,
+                },
+            };
+            Ok((index, values))
+        }
+    }
+}
+
+pub(crate) struct IndexTreeIter<'a> {
+    hdr: &'a IndexTree,
+    // the next position at the front
+    lo: u64,
+    // the position after the next one at the back
+    hi: u64,
+    // cached IDs of the positions start from `lo`
+    front: VecDeque<u64>,
+    // cached IDs of the positions end with `hi - 1`
+    back: VecDeque<u64>,
+}
+
+impl Iterator for IndexTreeIter<'_> {
+    type Item = u64;
+    fn next(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+
+        if self.front.is_empty() {
+            let (start, ids) = self.hdr.leaf_at(self.lo)?;
+            let end = (ids.len() as u64).min(self.hi - start);
+            self.front
+                .extend(&ids[(self.lo - start) as usize..end as usize]);
+        }
+
+        self.lo += 1;
+        self.front.pop_front()
+    }
+}
+
+impl DoubleEndedIterator for IndexTreeIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+
+        if self.back.is_empty() {
+            let (start, ids) = self.hdr.leaf_at(self.hi - 1)?;
+            let begin = self.lo.max(start) - start;
+            self.back
+                .extend(&ids[begin as usize..(self.hi - start) as usize]);
+        }
+
+        self.hi -= 1;
+        self.back.pop_back()
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+/////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug)]
+enum Node {
+    // IDs of the elements
+    Leaf(Vec<u64>),
+    // IDs of the children, and the number of elements under each of them
+    Branch(Vec<(u64, u64)>),
+}
+
+impl Node {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(ids) => ids.len(),
+            Node::Branch(children) => children.len(),
+        }
+    }
+
+    // the number of elements under this node
+    fn count(&self) -> u64 {
+        match self {
+            Node::Leaf(ids) => ids.len() as u64,
+            Node::Branch(children) => children.iter().map(|(_, n)| n).sum(),
+        }
+    }
+
+    fn leaf_mut(&mut self) -> &mut Vec<u64> {
+        match self {
+            Node::Leaf(ids) => ids,
+            Node::Branch(_) => unreachable!(),
+        }
+    }
+
+    fn branch_mut(&mut self) -> &mut Vec<(u64, u64)> {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!(),
+        }
+    }
+
+    // split into pieces of nearly equal sizes, none of them is too big
+    #[allow(clippy::manual_div_ceil)]
+    fn split(self) -> Vec<Node> {
+        let n = ((self.len() + MAX_ENTRIES - 1) / MAX_ENTRIES).max(1);
+        match self {
+            Node::Leaf(ids) => chunks(ids, n).into_iter().map(Node::Leaf).collect(),
+            Node::Branch(children) => {
+                chunks(children, n).into_iter().map(Node::Branch).collect()
+            }
+        }
+    }
+
+    // `other` must be at the same level
+    fn merge(self, other: Node) -> Node {
+        match (self, other) {
+            (Node::Leaf(mut a), Node::Leaf(b)) => {
+                a.extend(b);
+                Node::Leaf(a)
+            }
+            (Node::Branch(mut a), Node::Branch(b)) => {
+                a.extend(b);
+                Node::Branch(a)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn encode(&self) -> RawValue {
+        let mut bytes = vec![];
+        match self {
+            Node::Leaf(ids) => {
+                bytes.push(TAG_LEAF);
+                ids.iter()
+                    .for_each(|id| bytes.extend_from_slice(&id.to_be_bytes()));
+            }
+            Node::Branch(children) => {
+                bytes.push(TAG_BRANCH);
+                children.iter().for_each(|(id, n)| {
+                    bytes.extend_from_slice(&id.to_be_bytes());
+                    bytes.extend_from_slice(&n.to_be_bytes());
+                });
+            }
+        }
+        bytes.into()
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        let ints = bytes[1..]
+            .chunks_exact(size_of::<u64>())
+            .map(|i| crate::parse_int!(i, u64));
+        match bytes[0] {
+            TAG_LEAF => Node::Leaf(ints.collect()),
+            TAG_BRANCH => {
+                let ints = ints.collect::<Vec<_>>();
+                Node::Branch(ints.chunks_exact(2).map(|i| (i[0], i[1])).collect())
+            }
+            _ => pnk!(Err(eg!("Invalid node"))),
+        }
+    }
+}
+
+// split `v` into `n` pieces of nearly equal sizes
+fn chunks<T>(v: Vec<T>, n: usize) -> Vec<Vec<T>> {
+    let (size, rem) = (v.len() / n, v.len() % n);
+    let mut v = v.into_iter();
+    (0..n)
+        .map(|i| v.by_ref().take(size + usize::from(i < rem)).collect())
+        .collect()
+}
+
+// the slot of the child that covers the position `idx`,
+// and the offset of the position in that child,
+// positions out of the range are covered by the last child
+fn locate(children: &[(u64, u64)], mut idx: u64) -> (usize, u64) {
+    let last = children.len() - 1;
+    for (slot, (_, n)) in children.iter().enumerate() {
+        if idx < *n || slot == last {
+            return (slot, idx);
+        }
+        idx -= n;
+    }
+    unreachable!()
+}
+
+#[inline(always)]
+fn node_key(id: u64) -> RawKey {
+    id.to_be_bytes().to_vec().into()
+}
+
+#[inline(always)]
+fn read_node(nodes: &MapxRaw, id: u64) -> Node {
+    Node::decode(&pnk!(nodes.get(&id.to_be_bytes()).c(d!("Missing node"))))
+}
+
+// Changes of nodes are cached, and written to the disk in one batch on commit.
+//
+// Nodes under the IDs allocated by the transaction are unreachable
+// from the tree on the disk before the commit,
+// so they can be written earlier to bound the memory usage,
+// a crash leaves them as garbage, which will be overwritten by later ones.
+struct Txn<'a> {
+    nodes: &'a mut MapxRaw,
+    // `None` means a removed node
+    dirty: BTreeMap<u64, Option<Node>>,
+    // the first ID allocated by this transaction
+    fresh_node_id: u64,
+    next_node_id: u64,
+    next_elem_id: u64,
+}
+
+impl<'a> Txn<'a> {
+    fn new(nodes: &'a mut MapxRaw) -> Self {
+        let (next_node_id, next_elem_id) = nodes
+            .get(&META_KEY)
+            .map(|m| {
+                (
+                    crate::parse_int!(m[..size_of::<u64>()], u64),
+                    crate::parse_int!(m[size_of::<u64>()..], u64),
+                )
+            })
+            .unwrap_or_default();
+
+        Txn {
+            nodes,
+            dirty: BTreeMap::new(),
+            fresh_node_id: next_node_id,
+            next_node_id,
+            next_elem_id,
+        }
+    }
+
+    fn get(&self, id: u64) -> Node {
+        match self.dirty.get(&id) {
+            Some(node) => pnk!(node.clone().c(d!("Removed node"))),
+            None => read_node(self.nodes, id),
+        }
+    }
+
+    fn put(&mut self, id: u64, node: Node) {
+        self.dirty.insert(id, Some(node));
+        if self.dirty.len() >= MAX_DIRTY_NODES {
+            self.flush_fresh();
+        }
+    }
+
+    // return the new ID and the number of elements under the node
+    fn put_new(&mut self, node: Node) -> (u64, u64) {
+        let id = self.next_node_id;
+        self.next_node_id += 1;
+        let n = node.count();
+        self.put(id, node);
+        (id, n)
+    }
+
+    fn del(&mut self, id: u64) {
+        self.dirty.insert(id, None);
+    }
+
+    // the path from the root to the leaf that covers the position `idx`,
+    // each step is `(node ID, node, slot of the next step)`,
+    // the position after the last element is covered by the last leaf
+    fn path(&self, mut idx: u64) -> Vec<(u64, Node, usize)> {
+        let mut path = vec![];
+        let mut id = ROOT_ID;
+        loop {
+            let node = self.get(id);
+            match &node {
+                Node::Leaf(_) => {
+                    path.push((id, node, idx as usize));
+                    return path;
+                }
+                Node::Branch(children) => {
+                    let (slot, offset) = locate(children, idx);
+                    let child = children[slot].0;
+                    path.push((id, node, slot));
+                    id = child;
+                    idx = offset;
+                }
+            }
+        }
+    }
+
+    // write a child that may be too big,
+    // return the pieces that should replace it in its parent
+    fn put_split(&mut self, id: u64, node: Node) -> Vec<(u64, u64)> {
+        let mut pieces = node.split().into_iter();
+        let first = pieces.next().unwrap();
+        let mut ret = vec![(id, first.count())];
+        self.put(id, first);
+        ret.extend(pieces.map(|p| self.put_new(p)));
+        ret
+    }
+
+    // write a child that has lost some elements,
+    // it will be merged with one of its siblings if it is too small
+    fn put_shrunk(
+        &mut self,
+        children: &mut Vec<(u64, u64)>,
+        slot: usize,
+        id: u64,
+        node: Node,
+    ) {
+        if 0 == node.len() {
+            children.remove(slot);
+            self.del(id);
+            return;
+        }
+
+        if node.len() < MIN_ENTRIES && children.len() > 1 {
+            let (l, r) = alt!(0 < slot, (slot - 1, slot), (slot, slot + 1));
+            let sibling_slot = alt!(l == slot, r, l);
+            let sibling = self.get(children[sibling_slot].0);
+            if sibling.len() + node.len() <= MAX_ENTRIES {
+                let merged = alt!(l == slot, node.merge(sibling), sibling.merge(node));
+                children[l].1 = merged.count();
+                self.put(children[l].0, merged);
+                self.del(children[r].0);
+                children.remove(r);
+                return;
+            }
+        }
+
+        children[slot].1 = node.count();
+        self.put(id, node);
+    }
+
+    // keep the first `keep` elements under the node, collect the others
+    fn cut(&mut self, node: &mut Node, keep: u64, removed: &mut Vec<u64>) {
+        match node {
+            Node::Leaf(ids) => {
+                removed.extend(ids.drain(keep as usize..));
+            }
+            Node::Branch(children) => {
+                let (slot, offset) = locate(children, keep);
+                let dropped = children.drain(slot + 1..).collect::<Vec<_>>();
+
+                let id = children[slot].0;
+                let mut child = self.get(id);
+                self.cut(&mut child, offset, removed);
+                self.put_shrunk(children, slot, id, child);
+
+                dropped
+                    .into_iter()
+                    .for_each(|(id, _)| self.drop_subtree(id, removed));
+            }
+        }
+    }
+
+    fn drop_subtree(&mut self, id: u64, removed: &mut Vec<u64>) {
+        match self.get(id) {
+            Node::Leaf(ids) => removed.extend(ids),
+            Node::Branch(children) => children
+                .into_iter()
+                .for_each(|(id, _)| self.drop_subtree(id, removed)),
+        }
+        self.del(id);
+    }
+
+    // write the root, grow or shrink the tree if needed
+    fn put_root(&mut self, mut root: Node) {
+        while root.len() > MAX_ENTRIES {
+            let pieces = root.split().into_iter().map(|p| self.put_new(p));
+            root = Node::Branch(pieces.collect());
+        }
+
+        while let Node::Branch(children) = &root {
+            match children.len() {
+                0 => root = Node::Leaf(vec![]),
+                1 => {
+                    let id = children[0].0;
+                    root = self.get(id);
+                    self.del(id);
+                }
+                _ => break,
+            }
+        }
+
+        self.put(ROOT_ID, root);
+    }
+
+    // write the cached nodes under the IDs allocated by this transaction
+    fn flush_fresh(&mut self) {
+        let fresh = self.dirty.split_off(&self.fresh_node_id);
+        if !fresh.is_empty() {
+            self.nodes.write_batch(
+                fresh
+                    .into_iter()
+                    .map(|(id, node)| (node_key(id), node.map(|n| n.encode()))),
+            );
+        }
+    }
+
+    // all the changes of the existing nodes and the allocators
+    // are written in one batch, so the tree is never half updated
+    fn commit(self) {
+        let mut meta = self.next_node_id.to_be_bytes().to_vec();
+        meta.extend_from_slice(&self.next_elem_id.to_be_bytes());
+
+        let kvs = self
+            .dirty
+            .into_iter()
+            .map(|(id, node)| (node_key(id), node.map(|n| n.encode())))
+            .chain([(META_KEY.to_vec().into(), Some(meta.into()))]);
+        self.nodes.write_batch_at_once(kvs);
+    }
+}
@@ -0,0 +1,125 @@
+use super::*;
+use rand::Rng;
+
+fn check(hdr: &IndexTree, model: &[u64]) {
+    assert_eq!(hdr.len(), model.len());
+    assert!(hdr.iter().eq(model.iter().copied()));
+    assert!(hdr.iter().rev().eq(model.iter().rev().copied()));
+}
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr = IndexTree::new();
+    let mut model = vec![];
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..3000 {
+        let idx = rng.gen_range(0..=model.len());
+        hdr.insert(idx, 1, |id| model.insert(idx, id));
+    }
+    check(&hdr, &model);
+    (0..model.len()).for_each(|i| assert_eq!(hdr.get(i), Some(model[i])));
+    assert!(hdr.get(model.len()).is_none());
+
+    for _ in 0..2000 {
+        let idx = rng.gen_range(0..model.len());
+        assert_eq!(hdr.remove(idx), Some(model.remove(idx)));
+    }
+    check(&hdr, &model);
+    assert!(hdr.remove(model.len()).is_none());
+
+    while !model.is_empty() {
+        assert_eq!(hdr.remove(0), Some(model.remove(0)));
+    }
+    check(&hdr, &model);
+}
+
+#[test]
+fn test_bulk_insert() {
+    let mut hdr = IndexTree::new();
+    let mut model = vec![];
+
+    for _ in 0..5 {
+        hdr.insert(model.len(), INSERT_BATCH_SIZE, |first| {
+            model.extend(first..first + INSERT_BATCH_SIZE as u64)
+        });
+    }
+    hdr.insert(100, 1000, |first| {
+        model.splice(100..100, first..first + 1000);
+    });
+    check(&hdr, &model);
+
+    let first = model[7];
+    assert_eq!(hdr.set(7, 1 << 40), Some(first));
+    model[7] = 1 << 40;
+    assert_eq!(hdr.get(7), Some(1 << 40));
+    assert!(hdr.set(model.len(), 0).is_none());
+
+    let removed = hdr.truncate(10000);
+    assert_eq!(removed, model.split_off(10000));
+    check(&hdr, &model);
+    assert!(hdr.truncate(10000).is_empty());
+
+    let kept = model
+        .iter()
+        .copied()
+        .filter(|id| id % 3 == 0)
+        .collect::<Vec<_>>();
+    let shadow = unsafe { hdr.shadow() };
+    hdr.rebuild(shadow.iter().filter(|id| id % 3 == 0));
+    check(&hdr, &kept);
+
+    assert_eq!(hdr.truncate(0), kept);
+    check(&hdr, &[]);
+
+    hdr.insert(0, 3, |_| {});
+    assert_eq!(hdr.len(), 3);
+    hdr.clear();
+    check(&hdr, &[]);
+}
+
+#[test]
+fn test_iter_range() {
+    let mut hdr = IndexTree::new();
+    let mut model = vec![];
+    hdr.insert(0, 2000, |first| model.extend(first..first + 2000));
+
+    assert!(
+        hdr.iter_range(300, 1700)
+            .eq(model[300..1700].iter().copied())
+    );
+    assert!(hdr.iter_range(1900, 5000).eq(model[1900..].iter().copied()));
+    assert!(hdr.iter_range(10, 10).next().is_none());
+
+    // consume from both ends
+    let mut it = hdr.iter_range(250, 800);
+    let mut front = vec![];
+    let mut back = vec![];
+    loop {
+        match it.next() {
+            Some(id) => front.push(id),
+            None => break,
+        }
+        match it.next_back() {
+            Some(id) => back.push(id),
+            None => break,
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    assert_eq!(front, model[250..800]);
+}
+
+// the new nodes are written before the commit when there are too many of them
+#[test]
+fn test_big_txn() {
+    let mut hdr = IndexTree::new();
+    let n = MAX_ENTRIES * (MAX_DIRTY_NODES + 10);
+    hdr.rebuild(0..n as u64);
+    assert_eq!(hdr.len(), n);
+    assert_eq!(hdr.get(n - 1), Some(n as u64 - 1));
+
+    let removed = hdr.truncate(100);
+    assert_eq!(removed.len(), n - 100);
+    check(&hdr, &(0..100).collect::<Vec<_>>());
+}
@@ -0,0 +1,679 @@
+//!
+//! A `MapxOrd`-like structure with automatically maintained secondary indexes.
+//!
+//! NOTE:
+//! - Keys will be encoded by `KeyEnDeOrdered`
+//! - Values will be encoded by some `serde`-like methods
+//! - The records and all their index entries are stored in one keyspace,
+//!   each insertion or removal is written in one batch
+//! - Only the names of the indexes are persisted, the extractors must be
+//!   registered again after reloading an instance, writes are refused until
+//!   all the persisted indexes have been registered
+//! - An index that has not been persisted is new to the instance,
+//!   the existing records are indexed in batches when it is registered
+//!
+//! # Examples
+//!
+//! ```
+//! use vsdb::basic::indexed_mapx::IndexedMapx;
+//!
+//! let dir = format!("/tmp/__vsdb__{}", rand::random::<u128>());
+//! vsdb::vsdb_set_base_dir(&dir);
+//!
+//! let mut l = IndexedMapx::new();
+//! l.add_index("len", |v: &String| v.len() as u32).unwrap();
+//!
+//! l.insert(1u64, "a".to_owned()).unwrap();
+//! l.insert(2u64, "bb".to_owned()).unwrap();
+//! l.insert(3u64, "cc".to_owned()).unwrap();
+//!
+//! assert!(l.get_by_index("len", &2u32).map(|(k, _)| k).eq([2, 3]));
+//! assert!(l.range_by_index("len", ..2u32).map(|(k, _)| k).eq([1]));
+//!
+//! l.insert(2u64, "b".to_owned()).unwrap();
+//! assert!(l.get_by_index("len", &1u32).map(|(k, _)| k).eq([1, 2]));
+//!
+//! l.remove(&1).unwrap();
+//! assert!(l.get_by_index("len", &1u32).map(|(k, _)| k).eq([2]));
+//! assert_eq!(l.len(), 2);
+//! ```
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::{
+        mapx_raw::{MapxRaw, MapxRawIter},
+        multi_mapx::{as_slice, prefix_end},
+    },
+    common::{
+        ende::{decode_key_part, encode_key_part, KeyEnDeOrdered, ValueCodec, ValueEnDe},
+        ensure_writable,
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+        RawKey, RawValue,
+    },
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    sync::Arc,
+};
+
+/// A registered index, its name and the extractor of its keys.
+pub(crate) type Index<V> = (String, Extractor<V>);
+
+pub(crate) type Extractor<V> = Arc<dyn Fn(&V) -> RawKey + Send + Sync>;
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct IndexedMapx<K, V> {
+    inner: MapxRaw,
+    #[serde(skip)]
+    indexes: Vec<Index<V>>,
+    p: PhantomData<K>,
+}
+
+impl<K, V> IndexedMapx<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+            indexes: self.indexes.clone(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        IndexedMapx {
+            inner: MapxRaw::new_untracked(),
+            indexes: vec![],
+            p: PhantomData,
+        }
+    }
+
+    /// Register an index named `name`, whose keys are extracted by `f`.
+    ///
+    /// If the index has not been persisted, the existing records are indexed
+    /// in batches, and then the index is persisted.
+    pub fn add_index<I, F>(&mut self, name: &str, f: F) -> VsdbResult<()>
+    where
+        I: KeyEnDeOrdered,
+        F: Fn(&V) -> I + Send + Sync + 'static,
+    {
+        add_index(&mut self.indexes, name, f)?;
+        let ret = self.backfill(name);
+        if ret.is_err() {
+            self.indexes.pop();
+        }
+        Ok(ret?)
+    }
+
+    #[inline(always)]
+    pub fn get(&self, key: &K) -> Option<V> {
+        let k = record_key(&key.to_bytes());
+        self.inner
+            .get(&k)
+            .map(|v| pnk!(self.inner.codec().decode_by_key(&k, &v)))
+    }
+
+    #[inline(always)]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.inner.contains_key(&record_key(&key.to_bytes()))
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner
+            .get(&LEN_KEY)
+            .map(|l| decode_len(&l))
+            .unwrap_or(0)
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        0 == self.len()
+    }
+
+    #[inline(always)]
+    pub fn insert(&mut self, key: K, value: V) -> VsdbResult<Option<V>> {
+        self.insert_ref(&key, &value)
+    }
+
+    /// Insert or update a record, and its index entries along with it,
+    /// an error will be returned if some persisted indexes have not been registered.
+    pub fn insert_ref(&mut self, key: &K, value: &V) -> VsdbResult<Option<V>> {
+        let old = self.get(key);
+        let changes = changes(
+            &self.indexes,
+            self.inner.codec(),
+            key,
+            old.as_ref(),
+            Some(value),
+            self.len(),
+        );
+        self.write(changes)?;
+        Ok(old)
+    }
+
+    /// Remove a record, and its index entries along with it,
+    /// an error will be returned if some persisted indexes have not been registered.
+    pub fn remove(&mut self, key: &K) -> VsdbResult<Option<V>> {
+        let old = match self.get(key) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let changes = changes(
+            &self.indexes,
+            self.inner.codec(),
+            key,
+            Some(&old),
+            None,
+            self.len(),
+        );
+        self.write(changes)?;
+        Ok(Some(old))
+    }
+
+    /// Iterate over the records in the order of their keys.
+    #[inline(always)]
+    pub fn iter(&self) -> IndexedMapxIter<'_, K, V> {
+        IndexedMapxIter {
+            hdr: &self.inner,
+            iter: self.inner.range(&RECORDS[..]..&INDEXES[..]),
+            p: PhantomData,
+        }
+    }
+
+    /// Get the records whose keys of the index `name` equal to `index_key`,
+    /// in the order of their keys.
+    #[inline(always)]
+    pub fn get_by_index<I: KeyEnDeOrdered>(
+        &self,
+        name: &str,
+        index_key: &I,
+    ) -> IndexedMapxIndexIter<'_, K, V> {
+        self.range_by_index::<I, _>(
+            name,
+            (Bound::Included(index_key), Bound::Included(index_key)),
+        )
+    }
+
+    /// Iterate over the records whose keys of the index `name` are within `bounds`,
+    /// in the order of the index keys, and then the keys of the records.
+    pub fn range_by_index<I, R>(
+        &self,
+        name: &str,
+        bounds: R,
+    ) -> IndexedMapxIndexIter<'_, K, V>
+    where
+        I: KeyEnDeOrdered,
+        R: RangeBounds<I>,
+    {
+        let (lo, hi) = index_bounds(name, bounds);
+        IndexedMapxIndexIter {
+            hdr: &self.inner,
+            iter: self.inner.range((as_slice(&lo), as_slice(&hi))),
+            prefix_len: index_prefix(name).len(),
+            index: find_index(&self.indexes, name),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        Ok(self.inner.clear()?)
+    }
+
+    // index the existing records by the newly registered index `name`,
+    // the entries left by an interrupted backfilling are removed first,
+    // and the name is persisted after all the entries have been written
+    fn backfill(&mut self, name: &str) -> Result<()> {
+        if self.inner.contains_key(&name_key(name)) {
+            return Ok(());
+        }
+        let f = find_index(&self.indexes, name).c(d!())?;
+        let codec = self.inner.codec();
+
+        let (lo, hi) = index_entries_bounds(name);
+        loop {
+            let stale = self
+                .inner
+                .range(&lo[..]..&hi[..])
+                .take(BACKFILL_BATCH_SIZE)
+                .map(|(k, _)| (k, None))
+                .collect::<Vec<_>>();
+            if stale.is_empty() {
+                break;
+            }
+            ensure_writable().c(d!())?;
+            self.inner.write_batch(stale);
+        }
+
+        if self.is_empty() {
+            return Ok(());
+        }
+        ensure_writable().c(d!())?;
+
+        let mut lo = Bound::Included(RawKey::from(&RECORDS[..]));
+        loop {
+            let records = self
+                .inner
+                .range((as_slice(&lo), Bound::Excluded(&INDEXES[..])))
+                .take(BACKFILL_BATCH_SIZE)
+                .collect::<Vec<_>>();
+            let last = match records.last() {
+                Some((last, _)) => last,
+                None => break,
+            };
+            let entries = backfill_entries(codec, name, &f, &records).c(d!())?;
+            lo = Bound::Excluded(last.clone());
+            self.inner.write_batch(entries);
+        }
+
+        self.inner.write_batch([(name_key(name), Some(RawValue::default()))]);
+        Ok(())
+    }
+
+    // write all the changes in one batch,
+    // along with the names of the newly registered indexes
+    fn write(&mut self, changes: Vec<(RawKey, Option<RawValue>)>) -> Result<()> {
+        ensure_writable().c(d!())?;
+        let (lo, hi) = index_names_bounds();
+        let persisted = self.inner.range(&lo[..]..&hi[..]).map(|(k, _)| k);
+        let mut kvs = index_name_changes(&self.indexes, persisted).c(d!())?;
+        kvs.extend(changes);
+        self.inner.write_batch(kvs);
+        Ok(())
+    }
+}
+
+impl<K, V> Clone for IndexedMapx<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            indexes: self.indexes.clone(),
+            p: PhantomData,
+        }
+    }
+}
+
+impl<K, V> PartialEq for IndexedMapx<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<K, V> Eq for IndexedMapx<K, V> {}
+
+impl<K, V> fmt::Debug for IndexedMapx<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexedMapx")
+            .field("inner", &self.inner)
+            .field("indexes", &index_names(&self.indexes))
+            .finish()
+    }
+}
+
+impl<K, V> Default for IndexedMapx<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> GcMark for IndexedMapx<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe + GcMark,
+{
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        if self.inner.gc_mark_instance(marks) && !V::gc_nope() {
+            self.iter().for_each(|(_, v)| v.gc_mark(marks));
+        }
+    }
+}
+
+pub struct IndexedMapxIter<'a, K, V> {
+    hdr: &'a MapxRaw,
+    iter: MapxRawIter<'a>,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> Iterator for IndexedMapxIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(k, v)| pnk!(decode_record(self.hdr.codec(), &k, &v)))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IndexedMapxIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|(k, v)| pnk!(decode_record(self.hdr.codec(), &k, &v)))
+    }
+}
+
+pub struct IndexedMapxIndexIter<'a, K, V> {
+    hdr: &'a MapxRaw,
+    iter: MapxRawIter<'a>,
+    prefix_len: usize,
+    // the extractor of the index, `None` if it has not been registered
+    index: Option<Extractor<V>>,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> IndexedMapxIndexIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    // `None` if the entry is stale
+    fn get_record(&self, entry: &[u8]) -> Option<(K, V)> {
+        let (ik, k) = pnk!(split_entry(&entry[self.prefix_len..]));
+        let k = record_key(k);
+        let v = self.hdr.get(&k)?;
+        let (k, v) = pnk!(decode_record(self.hdr.codec(), &k, &v));
+        is_fresh(self.index.as_ref(), &ik, &v).then_some((k, v))
+    }
+}
+
+impl<'a, K, V> Iterator for IndexedMapxIndexIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entry, _) = self.iter.next()?;
+            if let Some(r) = self.get_record(&entry) {
+                return Some(r);
+            }
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IndexedMapxIndexIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entry, _) = self.iter.next_back()?;
+            if let Some(r) = self.get_record(&entry) {
+                return Some(r);
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////
+
+// The keyspace is divided by the first byte:
+// - `RECORDS` + key => the encoded value
+// - `INDEXES` + name + index key + key => empty,
+//   the name and the index key are encoded as the non-last parts of composite keys
+// - `LEN_KEY` => the number of the records
+// - `NAMES` + name => empty, the names of the indexes which have been written
+
+pub(crate) const RECORDS: [u8; 1] = [0];
+pub(crate) const INDEXES: [u8; 1] = [1];
+pub(crate) const LEN_KEY: [u8; 1] = [2];
+pub(crate) const NAMES: [u8; 1] = [3];
+
+// the number of the records indexed in one batch by a backfilling
+pub(crate) const BACKFILL_BATCH_SIZE: usize = 8192;
+
+pub(crate) fn add_index<V, I, F>(
+    indexes: &mut Vec<Index<V>>,
+    name: &str,
+    f: F,
+) -> Result<()>
+where
+    I: KeyEnDeOrdered,
+    F: Fn(&V) -> I + Send + Sync + 'static,
+{
+    if indexes.iter().any(|(n, _)| n == name) {
+        return Err(eg!("index `{}` has been registered", name));
+    }
+    indexes.push((name.to_owned(), Arc::new(move |v| f(v).to_bytes())));
+    Ok(())
+}
+
+pub(crate) fn index_names<V>(indexes: &[Index<V>]) -> Vec<&str> {
+    indexes.iter().map(|(n, _)| n.as_str()).collect()
+}
+
+#[inline(always)]
+pub(crate) fn find_index<V>(indexes: &[Index<V>], name: &str) -> Option<Extractor<V>> {
+    indexes
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, f)| f.clone())
+}
+
+#[inline(always)]
+pub(crate) fn index_names_bounds() -> (RawKey, RawKey) {
+    (NAMES.into(), pnk!(prefix_end(&NAMES).c(d!())))
+}
+
+// all the `persisted` names must have been registered,
+// or their entries would go stale after the writing,
+// the names of the newly registered indexes are returned as the writes
+pub(crate) fn index_name_changes<V, I>(
+    indexes: &[Index<V>],
+    persisted: I,
+) -> Result<Vec<(RawKey, Option<RawValue>)>>
+where
+    I: IntoIterator<Item = RawKey>,
+{
+    let persisted = persisted
+        .into_iter()
+        .map(|k| String::from_utf8_lossy(&k[NAMES.len()..]).into_owned())
+        .collect::<Vec<_>>();
+    if let Some(n) = persisted
+        .iter()
+        .find(|n| !indexes.iter().any(|(name, _)| name == *n))
+    {
+        return Err(eg!("index `{}` must be registered before writing", n));
+    }
+    Ok(indexes
+        .iter()
+        .filter(|(name, _)| !persisted.contains(name))
+        .map(|(name, _)| (name_key(name), Some(RawValue::default())))
+        .collect())
+}
+
+#[inline(always)]
+pub(crate) fn name_key(name: &str) -> RawKey {
+    [&NAMES[..], name.as_bytes()].concat().into_boxed_slice()
+}
+
+#[inline(always)]
+pub(crate) fn record_key(key: &[u8]) -> RawKey {
+    [&RECORDS[..], key].concat().into_boxed_slice()
+}
+
+#[inline(always)]
+pub(crate) fn decode_len(bytes: &[u8]) -> usize {
+    crate::parse_int!(bytes, u64) as usize
+}
+
+#[inline(always)]
+pub(crate) fn decode_record<K, V>(
+    codec: ValueCodec,
+    key: &[u8],
+    value: &[u8],
+) -> Result<(K, V)>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    let k = K::from_slice(&key[RECORDS.len()..]).c(d!())?;
+    let v = codec.decode_by_key(key, value).c(d!())?;
+    Ok((k, v))
+}
+
+#[inline(always)]
+pub(crate) fn index_prefix(name: &str) -> Vec<u8> {
+    let mut buf = INDEXES.to_vec();
+    encode_key_part(&name.to_owned(), false, &mut buf);
+    buf
+}
+
+// the range of all the entries of the index `name`
+#[inline(always)]
+pub(crate) fn index_entries_bounds(name: &str) -> (RawKey, RawKey) {
+    let prefix = index_prefix(name);
+    // the entries always start with `INDEXES`, so there is always an end
+    let end = pnk!(prefix_end(&prefix).c(d!()));
+    (prefix.into_boxed_slice(), end)
+}
+
+// the entries of the index `name` for a batch of the encoded `records`
+pub(crate) fn backfill_entries<V: ValueEnDe>(
+    codec: ValueCodec,
+    name: &str,
+    f: &Extractor<V>,
+    records: &[(RawKey, RawValue)],
+) -> Result<Vec<(RawKey, Option<RawValue>)>> {
+    records
+        .iter()
+        .map(|(k, v)| {
+            let v = codec.decode_by_key::<V>(k, v).c(d!())?;
+            let entry = [&index_entry_prefix(name, &f(&v))[..], &k[RECORDS.len()..]]
+                .concat()
+                .into_boxed_slice();
+            Ok((entry, Some(RawValue::default())))
+        })
+        .collect()
+}
+
+fn index_entry_prefix(name: &str, index_key: &[u8]) -> Vec<u8> {
+    let mut buf = index_prefix(name);
+    encode_key_part(&index_key.to_vec(), false, &mut buf);
+    buf
+}
+
+// split an index entry, whose name part has been stripped,
+// into the index key and the key of the record
+#[inline(always)]
+pub(crate) fn split_entry(mut entry: &[u8]) -> Result<(Vec<u8>, &[u8])> {
+    let ik = decode_key_part::<Vec<u8>>(&mut entry, false).c(d!())?;
+    Ok((ik, entry))
+}
+
+// whether the index key `ik` of an entry is still the one of the record `v`,
+// it can not be verified if the index has not been registered
+#[inline(always)]
+pub(crate) fn is_fresh<V>(index: Option<&Extractor<V>>, ik: &[u8], v: &V) -> bool {
+    match index {
+        Some(f) => *f(v) == *ik,
+        None => true,
+    }
+}
+
+// convert `bounds` of index keys to a range of index entries,
+// every bound covers all the entries of its index key
+pub(crate) fn index_bounds<I, R>(name: &str, bounds: R) -> (Bound<RawKey>, Bound<RawKey>)
+where
+    I: KeyEnDeOrdered,
+    R: RangeBounds<I>,
+{
+    let prefix = |ik: &I| index_entry_prefix(name, &ik.to_bytes());
+    // the entries always start with `INDEXES`, so there is always an end
+    let end = |p: Vec<u8>| pnk!(prefix_end(&p).c(d!()));
+    let lo = match bounds.start_bound() {
+        Bound::Included(ik) => Bound::Included(prefix(ik).into_boxed_slice()),
+        Bound::Excluded(ik) => Bound::Included(end(prefix(ik))),
+        Bound::Unbounded => Bound::Included(index_prefix(name).into_boxed_slice()),
+    };
+    let hi = match bounds.end_bound() {
+        Bound::Included(ik) => Bound::Excluded(end(prefix(ik))),
+        Bound::Excluded(ik) => Bound::Excluded(prefix(ik).into_boxed_slice()),
+        Bound::Unbounded => Bound::Excluded(end(index_prefix(name))),
+    };
+    (lo, hi)
+}
+
+// the writes to replace the record `old` with `new` under `key`,
+// along with the index entries and the number of the records,
+// a `None` value means a removing
+pub(crate) fn changes<K, V>(
+    indexes: &[Index<V>],
+    codec: ValueCodec,
+    key: &K,
+    old: Option<&V>,
+    new: Option<&V>,
+    len: usize,
+) -> Vec<(RawKey, Option<RawValue>)>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    let key = key.to_bytes();
+    let mut ret = vec![];
+
+    for (name, f) in indexes.iter() {
+        let old_ik = old.map(|v| f(v));
+        let new_ik = new.map(|v| f(v));
+        if old_ik == new_ik {
+            continue;
+        }
+        let entry = |ik: &[u8]| {
+            [&index_entry_prefix(name, ik)[..], &key[..]]
+                .concat()
+                .into_boxed_slice()
+        };
+        if let Some(ik) = old_ik {
+            ret.push((entry(&ik), None));
+        }
+        if let Some(ik) = new_ik {
+            ret.push((entry(&ik), Some(RawValue::default())));
+        }
+    }
+
+    let new_len = match (old, new) {
+        (None, Some(_)) => len + 1,
+        (Some(_), None) => len - 1,
+        _ => len,
+    };
+    if new_len != len {
+        let l = (new_len as u64).to_be_bytes();
+        ret.push((LEN_KEY.into(), Some(l.into())));
+    }
+
+    let value = new.map(|v| codec.encode(v));
+    ret.push((record_key(&key), value));
+
+    ret
+}
@@ -0,0 +1,214 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct Record {
+    owner: String,
+    amount: u64,
+}
+
+fn record(owner: &str, amount: u64) -> Record {
+    Record {
+        owner: owner.to_owned(),
+        amount,
+    }
+}
+
+fn indexed() -> IndexedMapx<u32, Record> {
+    let mut hdr = IndexedMapx::new();
+    pnk!(hdr.add_index("owner", |r: &Record| r.owner.clone()));
+    pnk!(hdr.add_index("amount", |r: &Record| r.amount));
+    hdr
+}
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr = indexed();
+    assert!(hdr.add_index("owner", |r: &Record| r.amount).is_err());
+
+    (0..30u32).for_each(|i| {
+        let owner = if 0 == i % 2 { "alice" } else { "bob" };
+        assert!(hdr.insert(i, record(owner, i as u64 / 3)).unwrap().is_none());
+    });
+    assert_eq!(hdr.len(), 30);
+    assert!(hdr.iter().map(|(k, _)| k).eq(0..30));
+    assert!(hdr.iter().rev().map(|(k, _)| k).eq((0..30).rev()));
+
+    let by_owner = |hdr: &IndexedMapx<u32, Record>, owner: &str| {
+        hdr.get_by_index("owner", &owner.to_owned())
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        by_owner(&hdr, "alice"),
+        (0..30).step_by(2).collect::<Vec<_>>()
+    );
+    assert!(hdr.get_by_index("amount", &2u64).map(|(k, _)| k).eq(6..9));
+    assert!(
+        hdr.range_by_index("amount", 8u64..)
+            .map(|(k, r)| (r.amount, k))
+            .eq([(8, 24), (8, 25), (8, 26), (9, 27), (9, 28), (9, 29)])
+    );
+    assert!(
+        hdr.range_by_index("amount", (Bound::Excluded(0u64), Bound::Included(1u64)))
+            .map(|(k, _)| k)
+            .eq(3..6)
+    );
+    assert!(hdr.get_by_index("nonexistent", &0u64).next().is_none());
+
+    // update
+    assert_eq!(
+        hdr.insert(0, record("carol", 100)).unwrap(),
+        Some(record("alice", 0))
+    );
+    assert_eq!(by_owner(&hdr, "carol"), [0]);
+    assert!(!by_owner(&hdr, "alice").contains(&0));
+    assert!(hdr.get_by_index("amount", &0u64).map(|(k, _)| k).eq(1..3));
+    assert!(hdr.get_by_index("amount", &100u64).map(|(k, _)| k).eq([0]));
+    assert_eq!(hdr.len(), 30);
+
+    // remove
+    assert_eq!(hdr.remove(&0).unwrap(), Some(record("carol", 100)));
+    assert!(hdr.remove(&0).unwrap().is_none());
+    assert!(by_owner(&hdr, "carol").is_empty());
+    assert!(hdr.get_by_index("amount", &100u64).next().is_none());
+    assert_eq!(hdr.len(), 29);
+
+    hdr.clear();
+    assert!(hdr.is_empty());
+    assert!(by_owner(&hdr, "bob").is_empty());
+}
+
+#[test]
+fn test_reload() {
+    let mut hdr = indexed();
+    hdr.insert(1, record("alice", 1));
+    hdr.insert(2, record("bob", 1));
+
+    let mut reloaded = pnk!(IndexedMapx::<u32, Record>::decode(&hdr.encode()));
+    assert_eq!(reloaded, hdr);
+    assert!(
+        reloaded
+            .get_by_index("amount", &1u64)
+            .map(|(k, _)| k)
+            .eq([1, 2])
+    );
+
+    // the writes are refused until all the indexes are registered again
+    pnk!(reloaded.add_index("amount", |r: &Record| r.amount));
+    assert!(reloaded.insert_ref(&2, &record("carol", 5)).is_err());
+    assert!(reloaded.remove(&1).is_err());
+    assert_eq!(reloaded.get(&2), Some(record("bob", 1)));
+
+    pnk!(reloaded.add_index("owner", |r: &Record| r.owner.clone()));
+    reloaded.insert(2, record("carol", 5));
+    assert!(hdr.get_by_index("amount", &1u64).map(|(k, _)| k).eq([1]));
+    assert!(hdr.get_by_index("amount", &5u64).map(|(k, _)| k).eq([2]));
+    assert!(
+        hdr.get_by_index("owner", &"bob".to_owned())
+            .next()
+            .is_none()
+    );
+    assert!(
+        hdr.get_by_index("owner", &"carol".to_owned())
+            .map(|(k, _)| k)
+            .eq([2])
+    );
+
+    // a new index is persisted once the existing records have been indexed
+    pnk!(reloaded.add_index("zero", |_: &Record| 0u8));
+    reloaded.insert(3, record("dave", 5));
+    let mut reloaded = pnk!(IndexedMapx::<u32, Record>::decode(&hdr.encode()));
+    pnk!(reloaded.add_index("amount", |r: &Record| r.amount));
+    pnk!(reloaded.add_index("owner", |r: &Record| r.owner.clone()));
+    assert!(reloaded.remove(&3).is_err());
+}
+
+#[test]
+fn test_stale_entries() {
+    let mut hdr = indexed();
+    (0..5u32).for_each(|i| {
+        hdr.insert(i, record("alice", 1));
+    });
+
+    // the record is gone, but its entries are left
+    let k = record_key(&2u32.to_bytes());
+    hdr.inner.write_batch([(k, None)]);
+    assert!(
+        hdr.get_by_index("amount", &1u64)
+            .map(|(k, _)| k)
+            .eq([0, 1, 3, 4])
+    );
+
+    // the record is changed, but its entries are not
+    let k = record_key(&3u32.to_bytes());
+    let v = hdr.inner.codec().encode(&record("bob", 1));
+    hdr.inner.write_batch([(k, Some(v))]);
+    assert!(
+        hdr.get_by_index("owner", &"alice".to_owned())
+            .rev()
+            .map(|(k, _)| k)
+            .eq([4, 1, 0])
+    );
+    assert!(
+        hdr.get_by_index("amount", &1u64)
+            .map(|(k, _)| k)
+            .eq([0, 1, 3, 4])
+    );
+}
+
+#[test]
+fn test_backfill() {
+    let mut hdr = IndexedMapx::new();
+    let n = BACKFILL_BATCH_SIZE as u32 + 10;
+    (0..n).for_each(|i| {
+        hdr.insert(i, record(if 0 == i % 2 { "alice" } else { "bob" }, i as u64));
+    });
+
+    // the entries left by an interrupted backfilling
+    let stale = [&index_entry_prefix("owner", &"carol".to_owned().to_bytes())[..], &[0]]
+        .concat()
+        .into_boxed_slice();
+    hdr.inner.write_batch([(stale, Some(RawValue::default()))]);
+
+    pnk!(hdr.add_index("owner", |r: &Record| r.owner.clone()));
+    pnk!(hdr.add_index("amount", |r: &Record| r.amount));
+    assert_eq!(
+        hdr.get_by_index("owner", &"alice".to_owned()).count(),
+        n as usize / 2
+    );
+    assert!(
+        hdr.get_by_index("owner", &"carol".to_owned())
+            .next()
+            .is_none()
+    );
+    assert!(
+        hdr.range_by_index("amount", 8u64..11)
+            .map(|(k, _)| k)
+            .eq(8..11)
+    );
+    assert_eq!(hdr.get_by_index("amount", &(n as u64 - 1)).count(), 1);
+
+    // the indexes have been persisted, so they are not indexed again
+    let mut reloaded = pnk!(IndexedMapx::<u32, Record>::decode(&hdr.encode()));
+    assert!(reloaded.insert_ref(&0, &record("carol", 0)).is_err());
+    pnk!(reloaded.add_index("owner", |_: &Record| String::new()));
+    assert!(reloaded.get_by_index("owner", &String::new()).next().is_none());
+}
+
+#[test]
+fn test_vec_index_keys() {
+    let mut hdr = IndexedMapx::new();
+    pnk!(hdr.add_index("pair", |r: &Record| vec![r.amount, r.owner.len() as u64]));
+    pnk!(hdr.insert(0u32, record("a", 1)));
+    pnk!(hdr.insert(1u32, record("a", 2)));
+    pnk!(hdr.insert(2u32, record("bb", 1)));
+
+    assert!(hdr.get_by_index("pair", &vec![1u64, 1]).map(|(k, _)| k).eq([0]));
+    assert!(hdr.get_by_index("pair", &vec![1u64, 2]).map(|(k, _)| k).eq([2]));
+    assert!(
+        hdr.range_by_index("pair", vec![2u64]..)
+            .map(|(k, _)| k)
+            .eq([1])
+    );
+}
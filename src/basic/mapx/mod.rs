@@ -16,19 +16,19 @@
 //!
 //! let mut l = Mapx::new();
 //!
-//! l.insert(1, 0);
-//! l.insert_ref(&1, &0);
-//! l.insert(2, 0);
+//! l.insert(1, 0).unwrap();
+//! l.insert_ref(&1, &0).unwrap();
+//! l.insert(2, 0).unwrap();
 //!
 //! l.iter().for_each(|(k, v)| {
 //!     assert!(k >= 1);
 //!     assert_eq!(v, 0);
 //! });
 //!
-//! l.remove(&2);
+//! l.remove(&2).unwrap();
 //! assert_eq!(l.len(), 1);
 //!
-//! l.clear();
+//! l.clear().unwrap();
 //! assert_eq!(l.len(), 0);
 //! ```
 //!
@@ -37,9 +37,19 @@
 mod test;
 
 use crate::{
-    basic::mapx_ord_rawkey::{Entry, MapxOrdRawKey, MapxOrdRawKeyIter, ValueMut},
-    common::ende::{KeyEnDe, ValueEnDe},
+    basic::mapx_ord_rawkey::{
+        MapxOrdRawKey, MapxOrdRawKeyIter, MapxOrdRawKeyIterMut, MapxOrdRawKeyTryIter,
+        ValueIterMut, ValueMut,
+    },
+    common::{
+        ende::{KeyEnDe, ValueEnDe},
+        entry::{Entry, EntryHdr},
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+        InstanceOpts,
+    },
 };
+use ruc::*;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
@@ -55,6 +65,10 @@ where
     K: KeyEnDe,
     V: ValueEnDe,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -71,11 +85,71 @@ where
         }
     }
 
+    /// Same as `new`, but an error will be returned
+    /// instead of a panic in the read-only modes.
+    #[inline(always)]
+    pub fn try_new() -> VsdbResult<Self> {
+        Ok(Self {
+            inner: MapxOrdRawKey::try_new()?,
+            p: PhantomData,
+        })
+    }
+
+    /// Create an instance without tracking its length,
+    /// writes are cheaper, but `len()` will do a full scanning.
+    #[inline(always)]
+    pub fn new_untracked() -> Self {
+        Self {
+            inner: MapxOrdRawKey::new_untracked(),
+            p: PhantomData,
+        }
+    }
+
+    /// Create an instance with the specified options,
+    /// the options are recorded in the serialized handle.
+    #[inline(always)]
+    pub fn new_with_opts(opts: &InstanceOpts) -> Self {
+        Self {
+            inner: MapxOrdRawKey::new_with_opts(opts),
+            p: PhantomData,
+        }
+    }
+
+    /// Rewrite all the values to the latest version of the schema `schema`,
+    /// into a new instance that replaces this one, so the handle should be saved again.
+    ///
+    /// The old instance is left untouched, its handle is still usable
+    /// if the process crashes before the new handle has been saved.
+    ///
+    /// A handle stored as a value of another instance(e.g. the inner maps of
+    /// `MapxOrd<K, Mapx<K2, V>>`) is a copy, which is not updated by this,
+    /// so write the migrated handle back, or the stored one keeps pointing to
+    /// the old instance, and the new instance will be removed by `vsdb_gc`.
+    ///
+    /// The old instance is never removed by the migration, it takes as much space
+    /// as the new one until it is reclaimed by `vsdb_gc`, so run `vsdb_gc` after
+    /// the new handle has been saved, which also reclaims the new instance
+    /// left behind by a crashed migration.
+    ///
+    /// Values stored without a schema are treated as version 0 of it,
+    /// this is an offline routine, the instance should not be used elsewhere meanwhile.
+    #[inline(always)]
+    pub fn migrate_instance(&mut self, schema: &str) -> Result<()> {
+        self.inner.migrate_instance(schema).c(d!())
+    }
+
     #[inline(always)]
     pub fn get(&self, key: &K) -> Option<V> {
         self.inner.get(&key.encode())
     }
 
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, key: &K) -> VsdbResult<Option<V>> {
+        self.inner.try_get(&key.encode())
+    }
+
     #[inline(always)]
     pub fn get_mut(&mut self, key: &K) -> Option<ValueMut<'_, V>> {
         let k = key.encode();
@@ -100,60 +174,99 @@ where
     }
 
     #[inline(always)]
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+    pub fn insert(&mut self, key: K, value: V) -> VsdbResult<Option<V>> {
         self.insert_ref(&key, &value)
     }
 
     #[inline(always)]
-    pub fn insert_ref(&mut self, key: &K, value: &V) -> Option<V> {
+    pub fn insert_ref(&mut self, key: &K, value: &V) -> VsdbResult<Option<V>> {
         self.inner.insert_ref(&key.encode(), value)
     }
 
     #[inline(always)]
-    pub fn set_value(&mut self, key: K, value: V) {
+    pub fn set_value(&mut self, key: K, value: V) -> VsdbResult<()> {
         self.set_value_ref(&key, &value)
     }
 
     #[inline(always)]
-    pub fn set_value_ref(&mut self, key: &K, value: &V) {
-        self.inner.set_value_ref(&key.encode(), value);
+    pub fn set_value_ref(&mut self, key: &K, value: &V) -> VsdbResult<()> {
+        self.inner.set_value_ref(&key.encode(), value)
     }
 
     #[inline(always)]
-    pub fn entry(&mut self, key: K) -> Entry<'_, V> {
-        self.inner.entry(key.encode())
+    pub fn entry(&mut self, key: K) -> Entry<'_, Self> {
+        Entry::new(self, key)
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> MapxIter<K, V> {
+    pub fn iter(&self) -> MapxIter<'_, K, V> {
         MapxIter {
             iter: self.inner.iter(),
             p: PhantomData,
         }
     }
 
+    /// Same as `iter`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxTryIter<'_, K, V> {
+        MapxTryIter {
+            iter: self.inner.try_iter(),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
-    pub fn values(&self) -> MapxValues<K, V> {
+    pub fn values(&self) -> MapxValues<'_, K, V> {
         MapxValues { iter: self.iter() }
     }
 
-    // TODO
-    // pub fn iter_mut
-    // pub fn values_mut
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> MapxIterMut<'_, K, V> {
+        MapxIterMut {
+            inner: self.inner.iter_mut(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn values_mut(&mut self) -> MapxValuesMut<'_, K, V> {
+        MapxValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
 
     #[inline(always)]
-    pub fn remove(&mut self, key: &K) -> Option<V> {
+    pub fn remove(&mut self, key: &K) -> VsdbResult<Option<V>> {
         self.inner.remove(&key.encode())
     }
 
     #[inline(always)]
-    pub fn unset_value(&mut self, key: &K) {
-        self.inner.unset_value(&key.encode());
+    pub fn unset_value(&mut self, key: &K) -> VsdbResult<()> {
+        self.inner.unset_value(&key.encode())
     }
 
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        self.inner.clear()
+    }
+
+    /// Retain only the pairs specified by the predicate,
+    /// the removings are written in batches.
+    #[inline(always)]
+    pub fn retain<F>(&mut self, mut f: F) -> VsdbResult<()>
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        self.inner
+            .retain(|k, v| f(&<K as KeyEnDe>::decode(k).unwrap(), v))
+    }
+
+    /// Move all the pairs of `other` into `self`, leaving `other` empty,
+    /// the existing values of `self` will be overwritten.
+    #[inline(always)]
+    pub fn append(&mut self, other: &mut Self) -> VsdbResult<()> {
+        self.inner.append(&mut other.inner)
     }
 }
 
@@ -176,16 +289,70 @@ where
     }
 }
 
-pub struct MapxIter<K, V>
+impl<K, V> EntryHdr for Mapx<K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Key = K;
+    type Value = V;
+
+    fn entry_get(&self, key: &K) -> Option<V> {
+        self.get(key)
+    }
+
+    fn entry_set(&mut self, key: &K, value: &V) {
+        pnk!(self.set_value_ref(key, value));
+    }
+
+    fn entry_remove(&mut self, key: &K) {
+        pnk!(self.unset_value(key));
+    }
+}
+
+impl<K, V> Extend<(K, V)> for Mapx<K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.inner
+            .extend(iter.into_iter().map(|(k, v)| (k.encode(), v)));
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Mapx<K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
 {
-    iter: MapxOrdRawKeyIter<V>,
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl<K, V> GcMark for Mapx<K, V>
+where
+    V: ValueEnDe + GcMark,
+{
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+pub struct MapxIter<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    iter: MapxOrdRawKeyIter<'a, V>,
     p: PhantomData<K>,
 }
 
-impl<K, V> Iterator for MapxIter<K, V>
+impl<'a, K, V> Iterator for MapxIter<'a, K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
@@ -198,7 +365,7 @@ where
     }
 }
 
-impl<K, V> DoubleEndedIterator for MapxIter<K, V>
+impl<'a, K, V> DoubleEndedIterator for MapxIter<'a, K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
@@ -210,15 +377,15 @@ where
     }
 }
 
-pub struct MapxValues<K, V>
+pub struct MapxValues<'a, K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
 {
-    iter: MapxIter<K, V>,
+    iter: MapxIter<'a, K, V>,
 }
 
-impl<K, V> Iterator for MapxValues<K, V>
+impl<'a, K, V> Iterator for MapxValues<'a, K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
@@ -229,7 +396,7 @@ where
     }
 }
 
-impl<K, V> DoubleEndedIterator for MapxValues<K, V>
+impl<'a, K, V> DoubleEndedIterator for MapxValues<'a, K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
@@ -239,9 +406,106 @@ where
     }
 }
 
-impl<K, V> ExactSizeIterator for MapxValues<K, V>
+impl<'a, K, V> ExactSizeIterator for MapxValues<'a, K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
 {
 }
+
+pub struct MapxTryIter<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    iter: MapxOrdRawKeyTryIter<'a, V>,
+    p: PhantomData<K>,
+}
+
+impl<'a, K, V> Iterator for MapxTryIter<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Item = VsdbResult<(K, V)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|kv| kv.and_then(|(k, v)| Ok((<K as KeyEnDe>::decode(&k)?, v))))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxTryIter<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|kv| kv.and_then(|(k, v)| Ok((<K as KeyEnDe>::decode(&k)?, v))))
+    }
+}
+
+pub struct MapxIterMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    inner: MapxOrdRawKeyIterMut<'a, V>,
+    p: PhantomData<K>,
+}
+
+impl<'a, K, V> Iterator for MapxIterMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Item = (K, ValueIterMut<'a, V>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(k, v)| (<K as KeyEnDe>::decode(&k).unwrap(), v))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxIterMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(k, v)| (<K as KeyEnDe>::decode(&k).unwrap(), v))
+    }
+}
+
+pub struct MapxValuesMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    inner: MapxIterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for MapxValuesMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Item = ValueIterMut<'a, V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxValuesMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
@@ -10,10 +10,10 @@ fn test_insert() {
         .for_each(|(key, value)| {
             assert!(hdr.get(&key).is_none());
             hdr.set_value(key, value);
-            assert!(hdr.insert(key, value).is_some());
+            assert!(hdr.insert(key, value).unwrap().is_some());
             assert!(hdr.contains_key(&key));
             assert_eq!(pnk!(hdr.get(&key)), value);
-            assert_eq!(pnk!(hdr.remove(&key)), value);
+            assert_eq!(pnk!(hdr.remove(&key)).unwrap(), value);
             assert!(hdr.get(&key).is_none());
         });
     hdr.clear();
@@ -30,12 +30,12 @@ fn test_len() {
     (0..max)
         .map(|i: usize| (i, (max + i)))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(key, value).is_none());
+            assert!(hdr.insert(key, value).unwrap().is_none());
         });
     assert_eq!(500, hdr.len());
 
     for key in 0..max {
-        assert!(hdr.remove(&key).is_some());
+        assert!(hdr.remove(&key).unwrap().is_some());
     }
     assert_eq!(0, hdr.len());
 }
@@ -46,7 +46,7 @@ fn test_valueende() {
     let dehdr = {
         let mut hdr: Mapx<usize, usize> = Mapx::new();
         (0..cnt).map(|i: usize| (i, i)).for_each(|(key, value)| {
-            assert!(hdr.insert(key, value).is_none());
+            assert!(hdr.insert(key, value).unwrap().is_none());
         });
         <Mapx<usize, usize> as ValueEnDe>::encode(&hdr)
     };
@@ -62,9 +62,9 @@ fn test_iter() {
     let mut hdr: Mapx<usize, usize> = Mapx::new();
     let max = 500;
     (0..max).map(|i: usize| (i, i)).for_each(|(key, value)| {
-        assert!(hdr.insert(key, value).is_none());
+        assert!(hdr.insert(key, value).unwrap().is_none());
     });
-    for (key, value) in hdr.iter() {
+    for (key, value) in unsafe { hdr.shadow() }.iter() {
         assert_eq!(key, value);
         hdr.unset_value(&key);
     }
@@ -76,7 +76,7 @@ fn test_first_last() {
     let mut hdr: Mapx<usize, usize> = Mapx::new();
     let max = 500;
     (0..max).map(|i: usize| (i, i)).for_each(|(key, value)| {
-        assert!(hdr.insert(key, value).is_none());
+        assert!(hdr.insert(key, value).unwrap().is_none());
     });
     let (key, value) = pnk!(hdr.iter().next());
     assert_eq!(key, value);
@@ -92,7 +92,7 @@ fn test_values() {
     let mut hdr: Mapx<usize, usize> = Mapx::new();
     let max = 500;
     (0..max).map(|i: usize| (i, i)).for_each(|(key, value)| {
-        assert!(hdr.insert(key, value).is_none());
+        assert!(hdr.insert(key, value).unwrap().is_none());
     });
     let mut i = 0;
     for it in hdr.values() {
@@ -106,7 +106,7 @@ fn test_values_first_last() {
     let mut hdr: Mapx<usize, usize> = Mapx::new();
     let max = 500;
     (0..max).map(|i: usize| (i, i)).for_each(|(key, value)| {
-        assert!(hdr.insert(key, value).is_none());
+        assert!(hdr.insert(key, value).unwrap().is_none());
     });
     let value = pnk!(hdr.values().next());
     assert_eq!(0, value);
@@ -18,19 +18,19 @@
 //!
 //! let mut l = MapxOrd::new();
 //!
-//! l.insert(1, 0);
-//! l.insert_ref(&1, &0);
-//! l.insert(2, 0);
+//! l.insert(1, 0).unwrap();
+//! l.insert_ref(&1, &0).unwrap();
+//! l.insert(2, 0).unwrap();
 //!
 //! l.iter().for_each(|(k, v)| {
 //!     assert!(k >= 1);
 //!     assert_eq!(v, 0);
 //! });
 //!
-//! l.remove(&2);
+//! l.remove(&2).unwrap();
 //! assert_eq!(l.len(), 1);
 //!
-//! l.clear();
+//! l.clear().unwrap();
 //! assert_eq!(l.len(), 0);
 //! ```
 //!
@@ -39,8 +39,21 @@
 mod test;
 
 use crate::{
-    basic::mapx_ord_rawkey::{Entry, MapxOrdRawKey, MapxOrdRawKeyIter, ValueMut},
-    common::ende::{KeyEnDeOrdered, ValueEnDe},
+    basic::{
+        mapx_ord_rawkey::{
+            MapxOrdRawKey, MapxOrdRawKeyIter, MapxOrdRawKeyIterMut,
+            MapxOrdRawKeyTryIter, ValueIterMut, ValueMut,
+        },
+        multi_mapx::as_slice,
+    },
+    common::{
+        ende::{KeyEnDeOrdered, KeyEncoding, ValueEnDe},
+        engines::ValueCfg,
+        entry::{Entry, EntryHdr},
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+        ensure_writable, InstanceOpts, RawKey,
+    },
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -61,6 +74,10 @@ where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -77,14 +94,82 @@ where
         }
     }
 
+    /// Same as `new`, but an error will be returned
+    /// instead of a panic in the read-only modes.
+    #[inline(always)]
+    pub fn try_new() -> VsdbResult<Self> {
+        Ok(MapxOrd {
+            inner: MapxOrdRawKey::try_new()?,
+            p: PhantomData,
+        })
+    }
+
+    /// Create an instance without tracking its length,
+    /// writes are cheaper, but `len()` will do a full scanning.
+    #[inline(always)]
+    pub fn new_untracked() -> Self {
+        MapxOrd {
+            inner: MapxOrdRawKey::new_untracked(),
+            p: PhantomData,
+        }
+    }
+
+    /// Create an instance with the specified options,
+    /// the options are recorded in the serialized handle.
+    #[inline(always)]
+    pub fn new_with_opts(opts: &InstanceOpts) -> Self {
+        MapxOrd {
+            inner: MapxOrdRawKey::new_with_opts(opts),
+            p: PhantomData,
+        }
+    }
+
+    /// Rewrite all the values to the latest version of the schema `schema`,
+    /// into a new instance that replaces this one, so the handle should be saved again.
+    ///
+    /// The old instance is left untouched, its handle is still usable
+    /// if the process crashes before the new handle has been saved.
+    ///
+    /// A handle stored as a value of another instance(e.g. the inner maps of
+    /// `MapxOrd<K, Mapx<K2, V>>`) is a copy, which is not updated by this,
+    /// so write the migrated handle back, or the stored one keeps pointing to
+    /// the old instance, and the new instance will be removed by `vsdb_gc`.
+    ///
+    /// The old instance is never removed by the migration, it takes as much space
+    /// as the new one until it is reclaimed by `vsdb_gc`, so run `vsdb_gc` after
+    /// the new handle has been saved, which also reclaims the new instance
+    /// left behind by a crashed migration.
+    ///
+    /// Values stored without a schema are treated as version 0 of it,
+    /// this is an offline routine, the instance should not be used elsewhere meanwhile.
+    #[inline(always)]
+    pub fn migrate_instance(&mut self, schema: &str) -> Result<()> {
+        self.inner.migrate_instance(schema).c(d!())
+    }
+
+    #[inline(always)]
+    pub(crate) fn new_with_cfg(value_cfg: ValueCfg) -> Self {
+        MapxOrd {
+            inner: MapxOrdRawKey::new_with_cfg(value_cfg),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn get(&self, key: &K) -> Option<V> {
-        self.inner.get(&key.to_bytes())
+        self.inner.get(&self.encode_key(key))
+    }
+
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, key: &K) -> VsdbResult<Option<V>> {
+        self.inner.try_get(&self.encode_key(key))
     }
 
     #[inline(always)]
     pub fn get_mut(&mut self, key: &K) -> Option<ValueMut<'_, V>> {
-        let k = key.to_bytes();
+        let k = self.encode_key(key);
         self.inner
             .get(&k)
             .map(|v| ValueMut::new(&mut self.inner, k, v))
@@ -92,21 +177,21 @@ where
 
     #[inline(always)]
     pub fn contains_key(&self, key: &K) -> bool {
-        self.inner.contains_key(&key.to_bytes())
+        self.inner.contains_key(&self.encode_key(key))
     }
 
     #[inline(always)]
     pub fn get_le(&self, key: &K) -> Option<(K, V)> {
         self.inner
-            .get_le(&key.to_bytes())
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_le(&self.encode_key(key))
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
     pub fn get_ge(&self, key: &K) -> Option<(K, V)> {
         self.inner
-            .get_ge(&key.to_bytes())
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_ge(&self.encode_key(key))
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
@@ -120,13 +205,13 @@ where
     }
 
     #[inline(always)]
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+    pub fn insert(&mut self, key: K, value: V) -> VsdbResult<Option<V>> {
         self.insert_ref(&key, &value)
     }
 
     #[inline(always)]
-    pub fn insert_ref(&mut self, key: &K, value: &V) -> Option<V> {
-        self.inner.insert_ref(&key.to_bytes(), value)
+    pub fn insert_ref(&mut self, key: &K, value: &V) -> VsdbResult<Option<V>> {
+        self.inner.insert_ref(&self.encode_key(key), value)
     }
 
     // used to support efficient versioned-implementations
@@ -136,58 +221,59 @@ where
         key: &K,
         value: &[u8],
     ) -> Option<V> {
-        self.inner.insert_ref_encoded_value(&key.to_bytes(), value)
+        self.inner.insert_ref_encoded_value(&self.encode_key(key), value)
     }
 
     #[inline(always)]
-    pub fn set_value(&mut self, key: K, value: V) {
-        self.set_value_ref(&key, &value);
+    pub fn set_value(&mut self, key: K, value: V) -> VsdbResult<()> {
+        self.set_value_ref(&key, &value)
     }
 
     #[inline(always)]
-    pub fn set_value_ref(&mut self, key: &K, value: &V) {
-        self.inner.insert_ref(&key.to_bytes(), value);
+    pub fn set_value_ref(&mut self, key: &K, value: &V) -> VsdbResult<()> {
+        self.inner.set_value_ref(&self.encode_key(key), value)
     }
 
     #[inline(always)]
-    pub fn entry(&mut self, key: K) -> Entry<'_, V> {
-        Entry {
-            key: key.to_bytes(),
-            hdr: &mut self.inner,
-        }
+    pub fn entry(&mut self, key: K) -> Entry<'_, Self> {
+        Entry::new(self, key)
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> MapxOrdIter<K, V> {
+    pub fn iter(&self) -> MapxOrdIter<'_, K, V> {
         MapxOrdIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.iter(),
             p: PhantomData,
         }
     }
 
     #[inline(always)]
-    pub fn values(&self) -> MapxOrdValues<K, V> {
+    pub fn values(&self) -> MapxOrdValues<'_, K, V> {
         MapxOrdValues { iter: self.iter() }
     }
 
     #[inline(always)]
-    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> MapxOrdIter<K, V> {
+    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> MapxOrdIter<'_, K, V> {
         self.range_ref((bounds.start_bound(), bounds.end_bound()))
     }
 
     #[inline(always)]
     pub fn range_ref<'a, R: RangeBounds<&'a K>>(
-        &'a self,
+        &self,
         bounds: R,
-    ) -> MapxOrdIter<K, V> {
+    ) -> MapxOrdIter<'_, K, V>
+    where
+        K: 'a,
+    {
         let ll;
         let l = match bounds.start_bound() {
             Bound::Included(lo) => {
-                ll = lo.to_bytes();
+                ll = self.encode_key(lo);
                 Bound::Included(&ll[..])
             }
             Bound::Excluded(lo) => {
-                ll = lo.to_bytes();
+                ll = self.encode_key(lo);
                 Bound::Excluded(&ll[..])
             }
             Bound::Unbounded => Bound::Unbounded,
@@ -196,27 +282,109 @@ where
         let hh;
         let h = match bounds.end_bound() {
             Bound::Included(hi) => {
-                hh = hi.to_bytes();
+                hh = self.encode_key(hi);
                 Bound::Included(&hh[..])
             }
             Bound::Excluded(hi) => {
-                hh = hi.to_bytes();
+                hh = self.encode_key(hi);
                 Bound::Excluded(&hh[..])
             }
             Bound::Unbounded => Bound::Unbounded,
         };
 
         MapxOrdIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.range_ref((l, h)),
             p: PhantomData,
         }
     }
 
-    // TODO
-    // pub fn iter_mut
-    // pub fn values_mut
-    // pub fn range_mut
-    // pub fn range_ref_mut
+    /// Same as `iter`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxOrdTryIter<'_, K, V> {
+        MapxOrdTryIter {
+            key_encoding: self.inner.key_encoding(),
+            iter: self.inner.try_iter(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `range`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    pub fn try_range<R: RangeBounds<K>>(&self, bounds: R) -> MapxOrdTryIter<'_, K, V> {
+        let (l, h) = self.encode_bounds(&bounds);
+        MapxOrdTryIter {
+            key_encoding: self.inner.key_encoding(),
+            iter: self.inner.try_range((as_slice(&l), as_slice(&h))),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> MapxOrdIterMut<'_, K, V> {
+        MapxOrdIterMut {
+            key_encoding: self.inner.key_encoding(),
+            inner: self.inner.iter_mut(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn values_mut(&mut self) -> MapxOrdValuesMut<'_, K, V> {
+        MapxOrdValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn range_mut<R: RangeBounds<K>>(
+        &mut self,
+        bounds: R,
+    ) -> MapxOrdIterMut<'_, K, V> {
+        self.range_ref_mut((bounds.start_bound(), bounds.end_bound()))
+    }
+
+    #[inline(always)]
+    pub fn range_ref_mut<'a, R: RangeBounds<&'a K>>(
+        &mut self,
+        bounds: R,
+    ) -> MapxOrdIterMut<'_, K, V>
+    where
+        K: 'a,
+    {
+        let ll;
+        let l = match bounds.start_bound() {
+            Bound::Included(lo) => {
+                ll = self.encode_key(lo);
+                Bound::Included(&ll[..])
+            }
+            Bound::Excluded(lo) => {
+                ll = self.encode_key(lo);
+                Bound::Excluded(&ll[..])
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let hh;
+        let h = match bounds.end_bound() {
+            Bound::Included(hi) => {
+                hh = self.encode_key(hi);
+                Bound::Included(&hh[..])
+            }
+            Bound::Excluded(hi) => {
+                hh = self.encode_key(hi);
+                Bound::Excluded(&hh[..])
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        MapxOrdIterMut {
+            key_encoding: self.inner.key_encoding(),
+            inner: self.inner.range_ref_mut((l, h)),
+            p: PhantomData,
+        }
+    }
 
     #[inline(always)]
     pub fn first(&self) -> Option<(K, V)> {
@@ -229,18 +397,117 @@ where
     }
 
     #[inline(always)]
-    pub fn remove(&mut self, key: &K) -> Option<V> {
-        self.inner.remove(&key.to_bytes())
+    pub fn remove(&mut self, key: &K) -> VsdbResult<Option<V>> {
+        self.inner.remove(&self.encode_key(key))
     }
 
     #[inline(always)]
-    pub fn unset_value(&mut self, key: &K) {
-        self.inner.remove(&key.to_bytes());
+    pub fn unset_value(&mut self, key: &K) -> VsdbResult<()> {
+        self.inner.unset_value(&self.encode_key(key))
     }
 
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        self.inner.clear()
+    }
+
+    /// Retain only the pairs specified by the predicate,
+    /// the removings are written in batches.
+    #[inline(always)]
+    pub fn retain<F>(&mut self, mut f: F) -> VsdbResult<()>
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let key_encoding = self.inner.key_encoding();
+        self.inner
+            .retain(|k, v| f(&pnk!(key_encoding.decode_slice(k)), v))
+    }
+
+    /// Remove all the pairs within the `bounds` in one shot,
+    /// and return them in order.
+    ///
+    /// NOTE: the removed pairs are collected into the memory
+    pub fn drain_range<R: RangeBounds<K>>(
+        &mut self,
+        bounds: R,
+    ) -> VsdbResult<Vec<(K, V)>> {
+        self.inner
+            .drain_range(self.encode_bounds(&bounds))?
+            .into_iter()
+            .map(|(k, v)| Ok((self.decode_key(k)?, v)))
+            .collect()
+    }
+
+    /// Split the map into two at the given key,
+    /// return everything after the given key, including the key.
+    #[inline(always)]
+    pub fn split_off(&mut self, key: &K) -> VsdbResult<Self> {
+        Ok(MapxOrd {
+            inner: self.inner.split_off(&self.encode_key(key))?,
+            p: PhantomData,
+        })
+    }
+
+    /// Move all the pairs of `other` into `self`, leaving `other` empty,
+    /// the existing values of `self` will be overwritten.
+    #[inline(always)]
+    pub fn append(&mut self, other: &mut Self) -> VsdbResult<()> {
+        if self.inner.key_encoding() == other.inner.key_encoding() {
+            return self.inner.append(&mut other.inner);
+        }
+
+        // the keys must be re-encoded
+        ensure_writable()?;
+        self.extend(other.iter());
+        other.clear()
+    }
+
+    /// Re-encode all the keys of an instance created by older versions of VSDB,
+    /// in which the negative keys of signed integers sort after the positive ones,
+    /// it is a no-op for the instances created by the current version.
+    ///
+    /// The keys are written into a new instance that replaces this one,
+    /// so the handle should be saved again, the old instance is left untouched
+    /// until it is reclaimed by `vsdb_gc`.
+    ///
+    /// Like `migrate_instance`, a copy of the handle stored in another instance
+    /// is not updated, the migrated handle should be written back into it.
+    ///
+    /// This is an offline routine, the instance should not be used elsewhere meanwhile.
+    pub fn migrate_legacy_keys(&mut self) -> Result<()> {
+        if KeyEncoding::Legacy != self.inner.key_encoding() {
+            return Ok(());
+        }
+        self.inner.rekey(K::upgrade_legacy_bytes).c(d!())
+    }
+
+    #[inline(always)]
+    fn encode_key(&self, key: &K) -> RawKey {
+        self.inner.key_encoding().encode(key)
+    }
+
+    #[inline(always)]
+    fn decode_key(&self, key: RawKey) -> VsdbResult<K> {
+        self.inner.key_encoding().decode(key)
+    }
+
+    // the bounds of the encoded keys
+    fn encode_bounds<R: RangeBounds<K>>(
+        &self,
+        bounds: &R,
+    ) -> (Bound<RawKey>, Bound<RawKey>) {
+        let encode = |b: Bound<&K>| match b {
+            Bound::Included(k) => Bound::Included(self.encode_key(k)),
+            Bound::Excluded(k) => Bound::Excluded(self.encode_key(k)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        (encode(bounds.start_bound()), encode(bounds.end_bound()))
+    }
+
+    // return `false` if the instance has been marked before
+    #[inline(always)]
+    pub(crate) fn gc_mark_instance(&self, marks: &mut GcMarks) -> bool {
+        self.inner.gc_mark_instance(marks)
     }
 }
 
@@ -263,27 +530,85 @@ where
     }
 }
 
-pub struct MapxOrdIter<K, V>
+impl<K, V> EntryHdr for MapxOrd<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Key = K;
+    type Value = V;
+
+    fn entry_get(&self, key: &K) -> Option<V> {
+        self.get(key)
+    }
+
+    fn entry_set(&mut self, key: &K, value: &V) {
+        pnk!(self.set_value_ref(key, value));
+    }
+
+    fn entry_remove(&mut self, key: &K) {
+        pnk!(self.unset_value(key));
+    }
+}
+
+impl<K, V> Extend<(K, V)> for MapxOrd<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let key_encoding = self.inner.key_encoding();
+        self.inner.extend(
+            iter.into_iter()
+                .map(|(k, v)| (key_encoding.encode_owned(k), v)),
+        );
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for MapxOrd<K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
 {
-    iter: MapxOrdRawKeyIter<V>,
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl<K, V> GcMark for MapxOrd<K, V>
+where
+    V: ValueEnDe + GcMark,
+{
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+pub struct MapxOrdIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    iter: MapxOrdRawKeyIter<'a, V>,
+    key_encoding: KeyEncoding,
     p: PhantomData<K>,
 }
 
-impl<K, V> Iterator for MapxOrdIter<K, V>
+impl<'a, K, V> Iterator for MapxOrdIter<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
 {
     type Item = (K, V);
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+        self.iter.next().map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
     }
 }
 
-impl<K, V> DoubleEndedIterator for MapxOrdIter<K, V>
+impl<'a, K, V> DoubleEndedIterator for MapxOrdIter<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
@@ -291,26 +616,26 @@ where
     fn next_back(&mut self) -> Option<Self::Item> {
         self.iter
             .next_back()
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
     }
 }
 
-impl<K, V> ExactSizeIterator for MapxOrdIter<K, V>
+impl<'a, K, V> ExactSizeIterator for MapxOrdIter<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
 {
 }
 
-pub struct MapxOrdValues<K, V>
+pub struct MapxOrdValues<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
 {
-    iter: MapxOrdIter<K, V>,
+    iter: MapxOrdIter<'a, K, V>,
 }
 
-impl<K, V> Iterator for MapxOrdValues<K, V>
+impl<'a, K, V> Iterator for MapxOrdValues<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
@@ -321,7 +646,7 @@ where
     }
 }
 
-impl<K, V> DoubleEndedIterator for MapxOrdValues<K, V>
+impl<'a, K, V> DoubleEndedIterator for MapxOrdValues<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
@@ -331,9 +656,106 @@ where
     }
 }
 
-impl<K, V> ExactSizeIterator for MapxOrdValues<K, V>
+impl<'a, K, V> ExactSizeIterator for MapxOrdValues<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+}
+
+pub struct MapxOrdTryIter<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
 {
+    iter: MapxOrdRawKeyTryIter<'a, V>,
+    key_encoding: KeyEncoding,
+    p: PhantomData<K>,
+}
+
+impl<'a, K, V> Iterator for MapxOrdTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = VsdbResult<(K, V)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|kv| kv.and_then(|(k, v)| Ok((self.key_encoding.decode(k)?, v))))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxOrdTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|kv| kv.and_then(|(k, v)| Ok((self.key_encoding.decode(k)?, v))))
+    }
+}
+
+pub struct MapxOrdIterMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    inner: MapxOrdRawKeyIterMut<'a, V>,
+    key_encoding: KeyEncoding,
+    p: PhantomData<K>,
+}
+
+impl<'a, K, V> Iterator for MapxOrdIterMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = (K, ValueIterMut<'a, V>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxOrdIterMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
+    }
+}
+
+pub struct MapxOrdValuesMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    inner: MapxOrdIterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for MapxOrdValuesMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = ValueIterMut<'a, V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxOrdValuesMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
 }
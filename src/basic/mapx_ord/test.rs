@@ -1,4 +1,5 @@
 use super::*;
+use crate::{common::engines::reload_as_legacy, ValueEnDe};
 use ruc::*;
 
 #[test]
@@ -10,10 +11,10 @@ fn test_insert() {
         .for_each(|(key, value)| {
             assert!(hdr.get(&key).is_none());
             hdr.set_value(key, value);
-            assert!(hdr.insert(key, value).is_some());
+            assert!(hdr.insert(key, value).unwrap().is_some());
             assert!(hdr.contains_key(&key));
             assert_eq!(pnk!(hdr.get(&key)), value);
-            assert_eq!(pnk!(hdr.remove(&key)), value);
+            assert_eq!(pnk!(hdr.remove(&key)).unwrap(), value);
             assert!(hdr.get(&key).is_none());
         });
     hdr.clear();
@@ -23,6 +24,18 @@ fn test_insert() {
     assert!(hdr.is_empty());
 }
 
+#[test]
+fn test_try_write() {
+    let mut hdr: MapxOrd<usize, usize> = pnk!(MapxOrd::try_new());
+    assert_eq!(None, pnk!(hdr.insert_ref(&1, &10)));
+    assert_eq!(Some(10), pnk!(hdr.insert_ref(&1, &11)));
+    assert_eq!(Some(11), pnk!(hdr.remove(&1)));
+    assert_eq!(None, pnk!(hdr.remove(&1)));
+    assert_eq!(None, pnk!(hdr.insert_ref(&2, &20)));
+    pnk!(hdr.clear());
+    assert!(hdr.is_empty());
+}
+
 #[test]
 fn test_len() {
     let mut hdr: MapxOrd<usize, usize> = MapxOrd::new();
@@ -30,23 +43,47 @@ fn test_len() {
     (0..max)
         .map(|i: usize| (i, (max + i)))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(key, value).is_none());
+            assert!(hdr.insert(key, value).unwrap().is_none());
         });
     assert_eq!(500, hdr.len());
 
     for key in 0..max {
-        assert!(hdr.remove(&key).is_some());
+        assert!(hdr.remove(&key).unwrap().is_some());
     }
     assert_eq!(0, hdr.len());
 }
 
+#[test]
+fn test_len_untracked() {
+    let mut hdr: MapxOrd<usize, usize> = MapxOrd::new_untracked();
+    let max = 500;
+    (0..max).for_each(|i| {
+        assert!(hdr.insert(i, i).unwrap().is_none());
+    });
+    assert_eq!(max, hdr.len());
+    assert!(!hdr.is_empty());
+
+    // the option is kept by the decoded handles and the clones
+    let reloaded = pnk!(<MapxOrd<usize, usize> as ValueEnDe>::decode(&hdr.encode()));
+    let mut cloned = reloaded.clone();
+    (0..max).filter(|i| 0 == i % 2).for_each(|i| {
+        assert!(cloned.remove(&i).unwrap().is_some());
+    });
+    assert_eq!(max / 2, cloned.len());
+    assert_eq!(max, hdr.len());
+
+    hdr.clear();
+    assert_eq!(0, hdr.len());
+    assert!(hdr.is_empty());
+}
+
 #[test]
 fn test_valueende() {
     let cnt = 500;
     let dehdr = {
         let mut hdr: MapxOrd<usize, usize> = MapxOrd::new();
         (0..cnt).map(|i: usize| (i, i)).for_each(|(key, value)| {
-            assert!(hdr.insert(key, value).is_none());
+            assert!(hdr.insert(key, value).unwrap().is_none());
         });
         <MapxOrd<usize, usize> as ValueEnDe>::encode(&hdr)
     };
@@ -62,9 +99,9 @@ fn test_iter() {
     let mut hdr: MapxOrd<usize, usize> = MapxOrd::new();
     let max = 500;
     (0..max).map(|i: usize| (i, i)).for_each(|(key, value)| {
-        assert!(hdr.insert(key, value).is_none());
+        assert!(hdr.insert(key, value).unwrap().is_none());
     });
-    for (key, value) in hdr.iter() {
+    for (key, value) in unsafe { hdr.shadow() }.iter() {
         assert_eq!(key, value);
         hdr.unset_value(&key);
     }
@@ -76,7 +113,7 @@ fn test_first_last() {
     let mut hdr: MapxOrd<usize, usize> = MapxOrd::new();
     let max = 500;
     (0..max).map(|i: usize| (i, i)).for_each(|(key, value)| {
-        assert!(hdr.insert(key, value).is_none());
+        assert!(hdr.insert(key, value).unwrap().is_none());
     });
     let (key, value) = pnk!(hdr.first());
     assert_eq!(key, value);
@@ -92,7 +129,7 @@ fn test_values() {
     let mut hdr: MapxOrd<usize, usize> = MapxOrd::new();
     let max = 500;
     (0..max).map(|i: usize| (i, i)).for_each(|(key, value)| {
-        assert!(hdr.insert(key, value).is_none());
+        assert!(hdr.insert(key, value).unwrap().is_none());
     });
     let mut i = 0;
     for it in hdr.values() {
@@ -106,7 +143,7 @@ fn test_values_first_last() {
     let mut hdr: MapxOrd<usize, usize> = MapxOrd::new();
     let max = 500;
     (0..max).map(|i: usize| (i, i)).for_each(|(key, value)| {
-        assert!(hdr.insert(key, value).is_none());
+        assert!(hdr.insert(key, value).unwrap().is_none());
     });
     let value = pnk!(hdr.values().next());
     assert_eq!(0, value);
@@ -114,3 +151,48 @@ fn test_values_first_last() {
     let value = pnk!(hdr.values().next_back());
     assert_eq!(max - 1, value);
 }
+
+#[test]
+fn test_legacy_keys() {
+    let mut hdr: MapxOrd<i64, i64> = reload_as_legacy(&MapxOrd::new());
+    (-10..10).for_each(|i| {
+        pnk!(hdr.insert(i, i));
+    });
+
+    // keys are read back in the same way as they were written,
+    // but the negative ones sort after the positive ones
+    assert_eq!(Some(-1), hdr.get(&-1));
+    assert_eq!(Some((0, 0)), hdr.first());
+    assert!(hdr.iter().map(|(k, _)| k).eq((0..10).chain(-10..0)));
+    assert_eq!(0, hdr.range(..0).count());
+
+    let saved = hdr.encode();
+    pnk!(hdr.migrate_legacy_keys());
+    assert_eq!(20, hdr.len());
+    assert!(hdr.iter().map(|(k, _)| k).eq(-10..10));
+    assert!(hdr.iter().all(|(k, v)| k == v));
+    assert!(hdr.range(..0).map(|(k, _)| k).eq(-10..0));
+
+    // the migrated keys are written into a new instance,
+    // the saved handle still points to the untouched legacy keys
+    let saved = pnk!(<MapxOrd<i64, i64> as ValueEnDe>::decode(&saved));
+    assert_eq!(Some((0, 0)), saved.first());
+
+    // a no-op for the instances created by the current version
+    let mut reloaded = pnk!(<MapxOrd<i64, i64> as ValueEnDe>::decode(&hdr.encode()));
+    pnk!(reloaded.migrate_legacy_keys());
+    assert!(reloaded.iter().map(|(k, _)| k).eq(-10..10));
+
+    let mut hdr: MapxOrd<(Vec<i32>, i8), u8> = reload_as_legacy(&MapxOrd::new());
+    [(vec![-1], 1), (vec![0], -1), (vec![1, -1], 0), (vec![-2, 3], 0)]
+        .into_iter()
+        .for_each(|k| {
+            pnk!(hdr.insert(k.clone(), k.0.len() as u8));
+        });
+    pnk!(hdr.migrate_legacy_keys());
+    assert_eq!(
+        vec![(vec![-2, 3], 0), (vec![-1], 1), (vec![0], -1), (vec![1, -1], 0)],
+        hdr.iter().map(|(k, _)| k).collect::<Vec<_>>()
+    );
+    assert!(hdr.iter().all(|(k, v)| v as usize == k.0.len()));
+}
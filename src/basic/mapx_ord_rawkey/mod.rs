@@ -16,18 +16,18 @@
 //!
 //! let mut l = MapxOrdRawKey::new();
 //!
-//! l.insert_ref(&[1], &0);
-//! l.insert(vec![1].into_boxed_slice(), 0);
-//! l.insert_ref(&[2], &0);
+//! l.insert_ref(&[1], &0).unwrap();
+//! l.insert(vec![1].into_boxed_slice(), 0).unwrap();
+//! l.insert_ref(&[2], &0).unwrap();
 //!
 //! l.iter().for_each(|(_, v)| {
 //!     assert_eq!(v, 0);
 //! });
 //!
-//! l.remove(&[2]);
+//! l.remove(&[2]).unwrap();
 //! assert_eq!(l.len(), 1);
 //!
-//! l.clear();
+//! l.clear().unwrap();
 //! assert_eq!(l.len(), 0);
 //! ```
 //!
@@ -36,8 +36,16 @@
 mod test;
 
 use crate::{
-    basic::mapx_raw::{self, MapxRaw, MapxRawIter},
-    common::{ende::ValueEnDe, RawKey},
+    basic::mapx_raw::{self, MapxRaw, MapxRawIter, MapxRawTryIter},
+    common::{
+        ende::{KeyEncoding, ValueCodec, ValueEnDe},
+        engines::ValueCfg,
+        entry::{Entry, EntryHdr},
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+        schema::schema_id,
+        ensure_writable, InstanceOpts, Pre, RawKey, RawValue,
+    },
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -57,6 +65,10 @@ impl<V> MapxOrdRawKey<V>
 where
     V: ValueEnDe,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -73,19 +85,88 @@ where
         }
     }
 
+    /// Same as `new`, but an error will be returned
+    /// instead of a panic in the read-only modes.
+    #[inline(always)]
+    pub fn try_new() -> VsdbResult<Self> {
+        Ok(MapxOrdRawKey {
+            inner: MapxRaw::try_new()?,
+            p: PhantomData,
+        })
+    }
+
+    /// Create an instance without tracking its length,
+    /// writes are cheaper, but `len()` will do a full scanning.
+    #[inline(always)]
+    pub fn new_untracked() -> Self {
+        MapxOrdRawKey {
+            inner: MapxRaw::new_untracked(),
+            p: PhantomData,
+        }
+    }
+
+    /// Create an instance with the specified options,
+    /// the options are recorded in the serialized handle.
+    #[inline(always)]
+    pub fn new_with_opts(opts: &InstanceOpts) -> Self {
+        MapxOrdRawKey {
+            inner: MapxRaw::new_with_opts(opts),
+            p: PhantomData,
+        }
+    }
+
+    /// Rewrite all the values to the latest version of the schema `schema`,
+    /// into a new instance that replaces this one, so the handle should be saved again.
+    ///
+    /// The old instance is left untouched, its handle is still usable
+    /// if the process crashes before the new handle has been saved.
+    ///
+    /// A handle stored as a value of another instance(e.g. the inner maps of
+    /// `MapxOrd<K, Mapx<K2, V>>`) is a copy, which is not updated by this,
+    /// so write the migrated handle back, or the stored one keeps pointing to
+    /// the old instance, and the new instance will be removed by `vsdb_gc`.
+    ///
+    /// The old instance is never removed by the migration, it takes as much space
+    /// as the new one until it is reclaimed by `vsdb_gc`, so run `vsdb_gc` after
+    /// the new handle has been saved, which also reclaims the new instance
+    /// left behind by a crashed migration.
+    ///
+    /// Values stored without a schema are treated as version 0 of it,
+    /// this is an offline routine, the instance should not be used elsewhere meanwhile.
+    #[inline(always)]
+    pub fn migrate_instance(&mut self, schema: &str) -> Result<()> {
+        self.inner.migrate_schema(schema_id(schema)).c(d!())
+    }
+
+    #[inline(always)]
+    pub(crate) fn new_with_cfg(value_cfg: ValueCfg) -> Self {
+        MapxOrdRawKey {
+            inner: MapxRaw::new_with_cfg(value_cfg),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn get(&self, key: &[u8]) -> Option<V> {
         self.inner
             .get(key)
-            .map(|v| <V as ValueEnDe>::decode(&v).unwrap())
+            .map(|v| self.inner.codec().decode_by_key(key, &v).unwrap())
+    }
+
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, key: &[u8]) -> VsdbResult<Option<V>> {
+        self.inner
+            .try_get(key)?
+            .map(|v| self.inner.codec().decode_by_key(key, &v))
+            .transpose()
     }
 
     #[inline(always)]
     pub fn get_mut(&mut self, key: &[u8]) -> Option<ValueMut<'_, V>> {
-        self.inner.get_mut(key).map(|inner| ValueMut {
-            value: <V as ValueEnDe>::decode(&*inner).unwrap(),
-            inner,
-        })
+        self.get(key)
+            .map(move |v| ValueMut::new(self, key.to_vec().into_boxed_slice(), v))
     }
 
     #[inline(always)]
@@ -95,16 +176,18 @@ where
 
     #[inline(always)]
     pub fn get_le(&self, key: &[u8]) -> Option<(RawKey, V)> {
-        self.inner
-            .get_le(key)
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.inner.get_le(key).map(|(k, v)| {
+            let v = self.inner.codec().decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 
     #[inline(always)]
     pub fn get_ge(&self, key: &[u8]) -> Option<(RawKey, V)> {
-        self.inner
-            .get_ge(key)
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.inner.get_ge(key).map(|(k, v)| {
+            let v = self.inner.codec().decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 
     #[inline(always)]
@@ -118,15 +201,16 @@ where
     }
 
     #[inline(always)]
-    pub fn insert(&mut self, key: RawKey, value: V) -> Option<V> {
+    pub fn insert(&mut self, key: RawKey, value: V) -> VsdbResult<Option<V>> {
         self.insert_ref(&key, &value)
     }
 
     #[inline(always)]
-    pub fn insert_ref(&mut self, key: &[u8], value: &V) -> Option<V> {
+    pub fn insert_ref(&mut self, key: &[u8], value: &V) -> VsdbResult<Option<V>> {
         self.inner
-            .insert(key, &value.encode())
-            .map(|v| <V as ValueEnDe>::decode(&v).unwrap())
+            .insert(key, &self.inner.codec().encode(value))?
+            .map(|v| self.inner.codec().decode_by_key(key, &v))
+            .transpose()
     }
 
     // used to support efficient versioned-implementations
@@ -136,24 +220,24 @@ where
         key: &[u8],
         value: &[u8],
     ) -> Option<V> {
-        self.inner
-            .insert(key, value)
-            .map(|v| <V as ValueEnDe>::decode(&v).unwrap())
+        pnk!(self.inner.insert(key, value))
+            .map(|v| self.inner.codec().decode_by_key(key, &v).unwrap())
     }
 
     #[inline(always)]
-    pub fn set_value(&mut self, key: RawKey, value: V) {
-        self.set_value_ref(&key, &value);
+    pub fn set_value(&mut self, key: RawKey, value: V) -> VsdbResult<()> {
+        self.set_value_ref(&key, &value)
     }
 
     #[inline(always)]
-    pub fn set_value_ref(&mut self, key: &[u8], value: &V) {
-        self.inner.insert(key, &value.encode());
+    pub fn set_value_ref(&mut self, key: &[u8], value: &V) -> VsdbResult<()> {
+        self.inner.insert(key, &self.inner.codec().encode(value))?;
+        Ok(())
     }
 
     #[inline(always)]
-    pub fn entry(&mut self, key: RawKey) -> Entry<'_, V> {
-        Entry { key, hdr: self }
+    pub fn entry(&mut self, key: RawKey) -> Entry<'_, Self> {
+        Entry::new(self, key)
     }
 
     #[inline(always)]
@@ -162,15 +246,16 @@ where
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> MapxOrdRawKeyIter<V> {
+    pub fn iter(&self) -> MapxOrdRawKeyIter<'_, V> {
         MapxOrdRawKeyIter {
             inner: self.inner.iter(),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
 
     #[inline(always)]
-    pub fn range<R: RangeBounds<RawKey>>(&self, bounds: R) -> MapxOrdRawKeyIter<V> {
+    pub fn range<R: RangeBounds<RawKey>>(&self, bounds: R) -> MapxOrdRawKeyIter<'_, V> {
         let start = match bounds.start_bound() {
             Bound::Included(s) => Bound::Included(&s[..]),
             Bound::Excluded(s) => Bound::Excluded(&s[..]),
@@ -188,28 +273,67 @@ where
 
     #[inline(always)]
     pub fn range_ref<'a, R: RangeBounds<&'a [u8]>>(
-        &'a self,
+        &self,
         bounds: R,
-    ) -> MapxOrdRawKeyIter<V> {
+    ) -> MapxOrdRawKeyIter<'_, V> {
         MapxOrdRawKeyIter {
             inner: self.inner.range(bounds),
+            codec: self.inner.codec(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `iter`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxOrdRawKeyTryIter<'_, V> {
+        MapxOrdRawKeyTryIter {
+            inner: self.inner.try_iter(),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
 
+    /// Same as `range_ref`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
     #[inline(always)]
-    pub fn iter_mut(&self) -> MapxOrdRawKeyIterMut<V> {
+    pub fn try_range<'a, R: RangeBounds<&'a [u8]>>(
+        &self,
+        bounds: R,
+    ) -> MapxOrdRawKeyTryIter<'_, V> {
+        MapxOrdRawKeyTryIter {
+            inner: self.inner.try_range(bounds),
+            codec: self.inner.codec(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn values(&self) -> MapxOrdRawKeyValues<'_, V> {
+        MapxOrdRawKeyValues { inner: self.iter() }
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> MapxOrdRawKeyIterMut<'_, V> {
         MapxOrdRawKeyIterMut {
-            inner: self.inner.iter(),
+            codec: self.inner.codec(),
+            inner: self.inner.iter_mut(),
             p: PhantomData,
         }
     }
 
+    #[inline(always)]
+    pub fn values_mut(&mut self) -> MapxOrdRawKeyValuesMut<'_, V> {
+        MapxOrdRawKeyValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
     #[inline(always)]
     pub fn range_mut<R: RangeBounds<RawKey>>(
         &mut self,
         bounds: R,
-    ) -> MapxOrdRawKeyIter<V> {
+    ) -> MapxOrdRawKeyIterMut<'_, V> {
         let start = match bounds.start_bound() {
             Bound::Included(s) => Bound::Included(&s[..]),
             Bound::Excluded(s) => Bound::Excluded(&s[..]),
@@ -227,10 +351,11 @@ where
 
     #[inline(always)]
     pub fn range_ref_mut<'a, R: RangeBounds<&'a [u8]>>(
-        &'a mut self,
+        &mut self,
         bounds: R,
-    ) -> MapxOrdRawKeyIter<V> {
+    ) -> MapxOrdRawKeyIterMut<'_, V> {
         MapxOrdRawKeyIterMut {
+            codec: self.inner.codec(),
             inner: self.inner.range_mut(bounds),
             p: PhantomData,
         }
@@ -247,20 +372,163 @@ where
     }
 
     #[inline(always)]
-    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+    pub fn remove(&mut self, key: &[u8]) -> VsdbResult<Option<V>> {
+        self.inner
+            .remove(key)?
+            .map(|v| self.inner.codec().decode_by_key(key, &v))
+            .transpose()
+    }
+
+    #[inline(always)]
+    pub fn unset_value(&mut self, key: &[u8]) -> VsdbResult<()> {
+        self.inner.remove(key)?;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        self.inner.clear().map_err(From::from)
+    }
+
+    /// Retain only the pairs specified by the predicate,
+    /// the removings are written in batches.
+    #[inline(always)]
+    pub fn retain<F>(&mut self, mut f: F) -> VsdbResult<()>
+    where
+        F: FnMut(&[u8], &V) -> bool,
+    {
+        let codec = self.inner.codec();
         self.inner
-            .remove(key)
-            .map(|v| <V as ValueEnDe>::decode(&v).unwrap())
+            .retain(|k, v| f(k, &codec.decode_by_key(k, v).unwrap()))
+            .map_err(From::from)
+    }
+
+    /// Remove all the pairs within the `bounds` in one shot,
+    /// and return them in order.
+    ///
+    /// NOTE: the removed pairs are collected into the memory
+    pub fn drain_range<R: RangeBounds<RawKey>>(
+        &mut self,
+        bounds: R,
+    ) -> VsdbResult<Vec<(RawKey, V)>> {
+        let start = match bounds.start_bound() {
+            Bound::Included(s) => Bound::Included(&s[..]),
+            Bound::Excluded(s) => Bound::Excluded(&s[..]),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let end = match bounds.end_bound() {
+            Bound::Included(e) => Bound::Included(&e[..]),
+            Bound::Excluded(e) => Bound::Excluded(&e[..]),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let codec = self.inner.codec();
+        self.inner
+            .drain_range((start, end))?
+            .into_iter()
+            .map(|(k, v)| {
+                let v = codec.decode_by_key(&k, &v)?;
+                Ok((k, v))
+            })
+            .collect()
+    }
+
+    /// Split the map into two at the given key,
+    /// return everything after the given key, including the key.
+    #[inline(always)]
+    pub fn split_off(&mut self, key: &[u8]) -> VsdbResult<Self> {
+        Ok(MapxOrdRawKey {
+            inner: self.inner.split_off(key)?,
+            p: PhantomData,
+        })
+    }
+
+    /// Move all the pairs of `other` into `self`, leaving `other` empty,
+    /// the existing values of `self` will be overwritten.
+    pub fn append(&mut self, other: &mut Self) -> VsdbResult<()> {
+        ensure_writable()?;
+        if self.instance_prefix() == other.instance_prefix() {
+            return Ok(());
+        }
+        // the values are re-encoded,
+        // the two instances may have different value configurations
+        self.extend(other.iter());
+        other.clear()
+    }
+
+    // remove all the `keys` in batches
+    #[inline(always)]
+    pub(crate) fn remove_batch<I>(&mut self, keys: I)
+    where
+        I: IntoIterator<Item = RawKey>,
+    {
+        self.inner.write_batch(keys.into_iter().map(|k| (k, None)));
+    }
+
+    // get a value without decoding it
+    #[inline(always)]
+    pub(crate) fn get_raw(&self, key: &[u8]) -> Option<RawValue> {
+        self.inner.get(key)
+    }
+
+    #[inline(always)]
+    pub(crate) fn try_get_raw(&self, key: &[u8]) -> VsdbResult<Option<RawValue>> {
+        Ok(self.inner.try_get(key)?)
+    }
+
+    #[inline(always)]
+    pub(crate) fn encode_value(&self, value: &V) -> RawValue {
+        self.inner.codec().encode(value)
+    }
+
+    // write encoded values in batches, a `None` value means a removing
+    #[inline(always)]
+    pub(crate) fn write_batch_raw<I>(&mut self, kvs: I)
+    where
+        I: IntoIterator<Item = (RawKey, Option<RawValue>)>,
+    {
+        self.inner.write_batch(kvs);
+    }
+
+    // re-encode all the keys into a new instance that replaces this one,
+    // the values are kept as they are
+    #[inline(always)]
+    pub(crate) fn rekey<F>(&mut self, rekey: F) -> Result<()>
+    where
+        F: Fn(&[u8]) -> VsdbResult<RawKey>,
+    {
+        self.inner.rekey(rekey).c(d!())
     }
 
     #[inline(always)]
-    pub fn unset_value(&mut self, key: &[u8]) {
-        self.inner.remove(key);
+    pub(crate) fn key_encoding(&self) -> KeyEncoding {
+        self.inner.key_encoding()
     }
 
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub(crate) fn instance_prefix(&self) -> Pre {
+        self.inner.instance_prefix()
+    }
+
+    // create a new empty instance with the same configurations
+    #[inline(always)]
+    pub(crate) fn new_like(&self) -> Result<Self> {
+        self.inner.new_like().c(d!()).map(|inner| MapxOrdRawKey {
+            inner,
+            p: PhantomData,
+        })
+    }
+
+    // return `false` if the instance has been marked before
+    #[inline(always)]
+    pub(crate) fn gc_mark_instance(&self, marks: &mut GcMarks) -> bool {
+        self.inner.gc_mark_instance(marks)
+    }
+
+    #[inline(always)]
+    pub(crate) fn gc_is_marked(&self, marks: &GcMarks) -> bool {
+        self.inner.gc_is_marked(marks)
     }
 }
 
@@ -282,13 +550,96 @@ where
     }
 }
 
+impl<V> EntryHdr for MapxOrdRawKey<V>
+where
+    V: ValueEnDe,
+{
+    type Key = RawKey;
+    type Value = V;
+
+    fn entry_get(&self, key: &RawKey) -> Option<V> {
+        self.get(key)
+    }
+
+    fn entry_set(&mut self, key: &RawKey, value: &V) {
+        pnk!(self.set_value_ref(key, value));
+    }
+
+    fn entry_remove(&mut self, key: &RawKey) {
+        pnk!(self.unset_value(key));
+    }
+}
+
+impl<V> Extend<(RawKey, V)> for MapxOrdRawKey<V>
+where
+    V: ValueEnDe,
+{
+    fn extend<I: IntoIterator<Item = (RawKey, V)>>(&mut self, iter: I) {
+        let codec = self.inner.codec();
+        self.inner
+            .write_batch(iter.into_iter().map(|(k, v)| (k, Some(codec.encode(&v)))));
+    }
+}
+
+impl<V> FromIterator<(RawKey, V)> for MapxOrdRawKey<V>
+where
+    V: ValueEnDe,
+{
+    fn from_iter<I: IntoIterator<Item = (RawKey, V)>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl<V> GcMark for MapxOrdRawKey<V>
+where
+    V: ValueEnDe + GcMark,
+{
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        if self.gc_mark_instance(marks) && !V::gc_nope() {
+            self.values().for_each(|v| v.gc_mark(marks));
+        }
+    }
+}
+
+// Owns a copy of the handle instead of a `&mut` to it,
+// so the mutable iterators can yield many of them at the same time.
 #[derive(Debug)]
 pub struct ValueMut<'a, V>
 where
     V: ValueEnDe,
 {
+    hdr: MapxOrdRawKey<V>,
+    key: RawKey,
     value: V,
-    inner: mapx_raw::ValueMut<'a>,
+    _p: PhantomData<&'a mut MapxOrdRawKey<V>>,
+}
+
+impl<'a, V> ValueMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    pub(crate) fn new(hdr: &'a mut MapxOrdRawKey<V>, key: RawKey, value: V) -> Self {
+        unsafe { Self::new_shadowed(hdr, key, value) }
+    }
+
+    /// # Safety
+    ///
+    /// `hdr` must be mutably borrowed for `'a` by the caller,
+    /// and no other `ValueMut` of the same key may be alive in the meantime.
+    pub(crate) unsafe fn new_shadowed(
+        hdr: &MapxOrdRawKey<V>,
+        key: RawKey,
+        value: V,
+    ) -> Self {
+        ValueMut {
+            hdr: hdr.shadow(),
+            key,
+            value,
+            _p: PhantomData,
+        }
+    }
 }
 
 impl<'a, V> Drop for ValueMut<'a, V>
@@ -296,7 +647,7 @@ where
     V: ValueEnDe,
 {
     fn drop(&mut self) {
-        *self.inner = self.value.encode();
+        pnk!(self.hdr.set_value_ref(&self.key, &self.value));
     }
 }
 
@@ -319,26 +670,6 @@ where
     }
 }
 
-pub struct Entry<'a, V>
-where
-    V: 'a + ValueEnDe,
-{
-    pub key: RawKey,
-    pub hdr: &'a mut MapxOrdRawKey<V>,
-}
-
-impl<'a, V> Entry<'a, V>
-where
-    V: ValueEnDe,
-{
-    pub fn or_insert(self, default: V) -> ValueMut<'a, V> {
-        if !self.hdr.contains_key(&self.key) {
-            self.hdr.set_value_ref(&self.key, &default);
-        }
-        pnk!(self.hdr.get_mut(&self.key))
-    }
-}
-
 pub struct EntryRef<'a, V>
 where
     V: ValueEnDe,
@@ -353,7 +684,7 @@ where
 {
     pub fn or_insert_ref(self, default: &V) -> ValueMut<'a, V> {
         if !self.hdr.contains_key(self.key) {
-            self.hdr.set_value_ref(self.key, default);
+            pnk!(self.hdr.set_value_ref(self.key, default));
         }
         pnk!(self.hdr.get_mut(self.key))
     }
@@ -364,6 +695,7 @@ where
     V: ValueEnDe,
 {
     inner: MapxRawIter<'a>,
+    codec: ValueCodec,
     p: PhantomData<V>,
 }
 
@@ -373,9 +705,10 @@ where
 {
     type Item = (RawKey, V);
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner
-            .next()
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.inner.next().map(|(k, v)| {
+            let v = self.codec.decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 }
 
@@ -384,14 +717,51 @@ where
     V: ValueEnDe,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.inner
-            .next_back()
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.inner.next_back().map(|(k, v)| {
+            let v = self.codec.decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 }
 
 impl<'a, V> ExactSizeIterator for MapxOrdRawKeyIter<'a, V> where V: ValueEnDe {}
 
+pub struct MapxOrdRawKeyTryIter<'a, V>
+where
+    V: ValueEnDe,
+{
+    inner: MapxRawTryIter<'a>,
+    codec: ValueCodec,
+    p: PhantomData<V>,
+}
+
+impl<'a, V> Iterator for MapxOrdRawKeyTryIter<'a, V>
+where
+    V: ValueEnDe,
+{
+    type Item = VsdbResult<(RawKey, V)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|kv| {
+            let (k, v) = kv?;
+            let v = self.codec.decode_by_key(&k, &v)?;
+            Ok((k, v))
+        })
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for MapxOrdRawKeyTryIter<'a, V>
+where
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|kv| {
+            let (k, v) = kv?;
+            let v = self.codec.decode_by_key(&k, &v)?;
+            Ok((k, v))
+        })
+    }
+}
+
 pub struct MapxOrdRawKeyValues<'a, V>
 where
     V: ValueEnDe,
@@ -428,6 +798,7 @@ where
     V: ValueEnDe,
 {
     inner: mapx_raw::MapxRawIterMut<'a>,
+    codec: ValueCodec,
     p: PhantomData<V>,
 }
 
@@ -438,10 +809,12 @@ where
     type Item = (RawKey, ValueIterMut<'a, V>);
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next().map(|(k, v)| {
+            let value = self.codec.decode_by_key(&k, &v).unwrap();
             (
                 k,
                 ValueIterMut {
-                    value: <V as ValueEnDe>::decode(&*v).unwrap(),
+                    value,
+                    codec: self.codec,
                     inner: v,
                 },
             )
@@ -455,10 +828,12 @@ where
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.inner.next_back().map(|(k, v)| {
+            let value = self.codec.decode_by_key(&k, &v).unwrap();
             (
                 k,
                 ValueIterMut {
-                    value: <V as ValueEnDe>::decode(&*v).unwrap(),
+                    value,
+                    codec: self.codec,
                     inner: v,
                 },
             )
@@ -466,7 +841,31 @@ where
     }
 }
 
-impl<'a, V> ExactSizeIterator for MapxOrdRawKeyIter<'a, V> where V: ValueEnDe {}
+pub struct MapxOrdRawKeyValuesMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    inner: MapxOrdRawKeyIterMut<'a, V>,
+}
+
+impl<'a, V> Iterator for MapxOrdRawKeyValuesMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    type Item = ValueIterMut<'a, V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for MapxOrdRawKeyValuesMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
 
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
@@ -477,15 +876,16 @@ where
     V: ValueEnDe,
 {
     value: V,
+    codec: ValueCodec,
     inner: mapx_raw::ValueIterMut<'a>,
 }
 
-impl<'a, V> Drop for ValueMut<'a, V>
+impl<'a, V> Drop for ValueIterMut<'a, V>
 where
     V: ValueEnDe,
 {
     fn drop(&mut self) {
-        *self.inner = self.value.encode();
+        *self.inner = self.codec.encode(&self.value);
     }
 }
 
@@ -11,12 +11,12 @@ fn test_insert() {
             assert!(hdr.get(&key).is_none());
             hdr.entry_ref(&key).or_insert_ref(&value);
             hdr.set_value(Box::new(key), value);
-            assert!(hdr.insert(Box::new(key), value).is_some());
+            assert!(hdr.insert(Box::new(key), value).unwrap().is_some());
             assert!(hdr.contains_key(&key));
             assert_eq!(pnk!(hdr.get(&key)), value);
-            assert_eq!(pnk!(hdr.remove(&key)), value);
+            assert_eq!(pnk!(hdr.remove(&key)).unwrap(), value);
             assert!(hdr.get(&key).is_none());
-            assert!(hdr.insert(Box::new(key), value).is_none());
+            assert!(hdr.insert(Box::new(key), value).unwrap().is_none());
         });
     hdr.clear();
     (0..max).map(|i: usize| i.to_be_bytes()).for_each(|key| {
@@ -31,12 +31,12 @@ fn test_len() {
     (0..max)
         .map(|i: usize| (i.to_be_bytes(), (max + i).to_be_bytes()))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(Box::new(key), value).is_none());
+            assert!(hdr.insert(Box::new(key), value).unwrap().is_none());
         });
     assert_eq!(500, hdr.len());
 
     for key in 0..max {
-        assert!(hdr.remove(&key.to_be_bytes()).is_some());
+        assert!(hdr.remove(&key.to_be_bytes()).unwrap().is_some());
     }
     assert_eq!(0, hdr.len());
 }
@@ -49,7 +49,7 @@ fn test_valueende() {
         (0..cnt)
             .map(|i: usize| (i.to_be_bytes(), i))
             .for_each(|(key, value)| {
-                assert!(hdr.insert(Box::new(key), value).is_none());
+                assert!(hdr.insert(Box::new(key), value).unwrap().is_none());
             });
         <MapxOrdRawKey<usize> as ValueEnDe>::encode(&hdr)
     };
@@ -67,9 +67,9 @@ fn test_iter() {
     (0..max)
         .map(|i: usize| (i.to_be_bytes(), i))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(Box::new(key), value).is_none());
+            assert!(hdr.insert(Box::new(key), value).unwrap().is_none());
         });
-    for (key, _) in hdr.iter() {
+    for (key, _) in unsafe { hdr.shadow() }.iter() {
         hdr.unset_value(&key);
     }
     assert_eq!(0, hdr.len());
@@ -82,7 +82,7 @@ fn test_first_last() {
     (0..max)
         .map(|i: usize| (i.to_be_bytes(), i))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(Box::new(key), value).is_none());
+            assert!(hdr.insert(Box::new(key), value).unwrap().is_none());
         });
     let (_, value) = pnk!(hdr.first());
     assert_eq!(0, value);
@@ -98,7 +98,7 @@ fn test_values() {
     (0..max)
         .map(|i: usize| (i.to_be_bytes(), i))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(Box::new(key), value).is_none());
+            assert!(hdr.insert(Box::new(key), value).unwrap().is_none());
         });
     let mut i = 0;
     for it in hdr.values() {
@@ -114,7 +114,7 @@ fn test_values_first_last() {
     (0..max)
         .map(|i: usize| (i.to_be_bytes(), i))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(Box::new(key), value).is_none());
+            assert!(hdr.insert(Box::new(key), value).unwrap().is_none());
         });
     let value = pnk!(hdr.values().next());
     assert_eq!(0, value);
@@ -122,3 +122,20 @@ fn test_values_first_last() {
     let value = pnk!(hdr.values().next_back());
     assert_eq!(max - 1, value);
 }
+
+#[test]
+#[cfg(feature = "json_codec")]
+fn test_codec() {
+    let opts = InstanceOpts {
+        codec: crate::Codec::Json,
+        ..Default::default()
+    };
+    let mut hdr = MapxOrdRawKey::new_with_opts(&opts);
+    hdr.insert(Box::new([0]), vec![1u8, 2]);
+    assert_eq!(&b"[1,2]"[..], &pnk!(hdr.inner.get(&[0u8]))[..]);
+
+    let mut hdr = pnk!(<MapxOrdRawKey<Vec<u8>> as ValueEnDe>::decode(&hdr.encode()));
+    assert_eq!(Some(vec![1, 2]), hdr.get(&[0u8]));
+    hdr.values_mut().for_each(|mut v| v.push(3));
+    assert_eq!(&b"[1,2,3]"[..], &pnk!(hdr.inner.get(&[0u8]))[..]);
+}
@@ -16,19 +16,19 @@
 //!
 //! let mut l = MapxOrdRawValue::new();
 //!
-//! l.insert_ref(&1, &[0]);
-//! l.insert(1, Box::new([0]));
-//! l.insert_ref(&2, &[0]);
+//! l.insert_ref(&1, &[0]).unwrap();
+//! l.insert(1, Box::new([0])).unwrap();
+//! l.insert_ref(&2, &[0]).unwrap();
 //!
 //! l.iter().for_each(|(k, v)| {
 //!     assert!(k >= 1);
 //!     assert_eq!(&v[..], &[0]);
 //! });
 //!
-//! l.remove(&2);
+//! l.remove(&2).unwrap();
 //! assert_eq!(l.len(), 1);
 //!
-//! l.clear();
+//! l.clear().unwrap();
 //! assert_eq!(l.len(), 0);
 //! ```
 //!
@@ -37,8 +37,17 @@
 mod test;
 
 use crate::{
-    basic::mapx_raw::{MapxRaw, MapxRawIter},
-    common::{ende::KeyEnDeOrdered, RawValue},
+    basic::{
+        mapx_raw::{MapxRaw, MapxRawIter, MapxRawIterMut, MapxRawTryIter, ValueIterMut},
+        multi_mapx::as_slice,
+    },
+    common::{
+        ende::{KeyEnDeOrdered, KeyEncoding},
+        ensure_writable,
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+        Pre, RawKey, RawValue,
+    },
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -58,6 +67,10 @@ impl<K> MapxOrdRawValue<K>
 where
     K: KeyEnDeOrdered,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -74,35 +87,62 @@ where
         }
     }
 
+    /// Same as `new`, but an error will be returned
+    /// instead of a panic in the read-only modes.
+    #[inline(always)]
+    pub fn try_new() -> Result<Self> {
+        MapxRaw::try_new().c(d!()).map(|inner| MapxOrdRawValue {
+            inner,
+            p: PhantomData,
+        })
+    }
+
+    /// Create an instance without tracking its length,
+    /// writes are cheaper, but `len()` will do a full scanning.
+    #[inline(always)]
+    pub fn new_untracked() -> Self {
+        MapxOrdRawValue {
+            inner: MapxRaw::new_untracked(),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn get(&self, key: &K) -> Option<RawValue> {
-        self.inner.get(&key.to_bytes())
+        self.inner.get(&self.encode_key(key))
+    }
+
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, key: &K) -> Result<Option<RawValue>> {
+        self.inner.try_get(&self.encode_key(key)).c(d!())
     }
 
     #[inline(always)]
     pub fn get_mut(&mut self, key: &K) -> Option<ValueMut<'_, K>> {
         self.inner
-            .get(&key.to_bytes())
+            .get(&self.encode_key(key))
             .map(|v| ValueMut::new(self, key.clone(), v))
     }
 
     #[inline(always)]
     pub fn contains_key(&self, key: &K) -> bool {
-        self.inner.contains_key(&key.to_bytes())
+        self.inner.contains_key(&self.encode_key(key))
     }
 
     #[inline(always)]
     pub fn get_le(&self, key: &K) -> Option<(K, RawValue)> {
         self.inner
-            .get_le(&key.to_bytes())
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_le(&self.encode_key(key))
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
     pub fn get_ge(&self, key: &K) -> Option<(K, RawValue)> {
         self.inner
-            .get_ge(&key.to_bytes())
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_ge(&self.encode_key(key))
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
@@ -116,23 +156,23 @@ where
     }
 
     #[inline(always)]
-    pub fn insert(&mut self, key: K, value: RawValue) -> Option<RawValue> {
+    pub fn insert(&mut self, key: K, value: RawValue) -> Result<Option<RawValue>> {
         self.insert_ref(&key, &value)
     }
 
     #[inline(always)]
-    pub fn insert_ref(&mut self, key: &K, value: &[u8]) -> Option<RawValue> {
-        self.inner.insert(&key.to_bytes(), value)
+    pub fn insert_ref(&mut self, key: &K, value: &[u8]) -> Result<Option<RawValue>> {
+        self.inner.insert(&self.encode_key(key), value).c(d!())
     }
 
     #[inline(always)]
-    pub fn set_value(&mut self, key: K, value: RawValue) {
-        self.set_value_ref(&key, &value);
+    pub fn set_value(&mut self, key: K, value: RawValue) -> Result<()> {
+        self.set_value_ref(&key, &value)
     }
 
     #[inline(always)]
-    pub fn set_value_ref(&mut self, key: &K, value: &[u8]) {
-        self.inner.insert(&key.to_bytes(), value);
+    pub fn set_value_ref(&mut self, key: &K, value: &[u8]) -> Result<()> {
+        self.inner.insert(&self.encode_key(key), value).c(d!()).map(|_| ())
     }
 
     #[inline(always)]
@@ -146,31 +186,60 @@ where
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> MapxOrdRawValueIter<K> {
+    pub fn iter(&self) -> MapxOrdRawValueIter<'_, K> {
         MapxOrdRawValueIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.iter(),
             p: PhantomData,
         }
     }
 
     #[inline(always)]
-    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> MapxOrdRawValueIter<K> {
+    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> MapxOrdRawValueIter<'_, K> {
         self.range_ref((bounds.start_bound(), bounds.end_bound()))
     }
 
+    /// Same as `iter`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxOrdRawValueTryIter<'_, K> {
+        MapxOrdRawValueTryIter {
+            key_encoding: self.inner.key_encoding(),
+            iter: self.inner.try_iter(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `range`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    pub fn try_range<R: RangeBounds<K>>(
+        &self,
+        bounds: R,
+    ) -> MapxOrdRawValueTryIter<'_, K> {
+        let (l, h) = self.encode_bounds(&bounds);
+        MapxOrdRawValueTryIter {
+            key_encoding: self.inner.key_encoding(),
+            iter: self.inner.try_range((as_slice(&l), as_slice(&h))),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn range_ref<'a, R: RangeBounds<&'a K>>(
-        &'a self,
+        &self,
         bounds: R,
-    ) -> MapxOrdRawValueIter<K> {
+    ) -> MapxOrdRawValueIter<'_, K>
+    where
+        K: 'a,
+    {
         let ll;
         let l = match bounds.start_bound() {
             Bound::Included(lo) => {
-                ll = lo.to_bytes();
+                ll = self.encode_key(lo);
                 Bound::Included(&ll[..])
             }
             Bound::Excluded(lo) => {
-                ll = lo.to_bytes();
+                ll = self.encode_key(lo);
                 Bound::Excluded(&ll[..])
             }
             Bound::Unbounded => Bound::Unbounded,
@@ -179,28 +248,92 @@ where
         let hh;
         let h = match bounds.end_bound() {
             Bound::Included(hi) => {
-                hh = hi.to_bytes();
+                hh = self.encode_key(hi);
                 Bound::Included(&hh[..])
             }
             Bound::Excluded(hi) => {
-                hh = hi.to_bytes();
+                hh = self.encode_key(hi);
                 Bound::Excluded(&hh[..])
             }
             Bound::Unbounded => Bound::Unbounded,
         };
 
         MapxOrdRawValueIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.range((l, h)),
             p: PhantomData,
         }
     }
 
-    // TODO
-    // pub fn values
-    // pub fn iter_mut
-    // pub fn values_mut
-    // pub fn range_mut
-    // pub fn range_ref_mut
+    #[inline(always)]
+    pub fn values(&self) -> MapxOrdRawValueValues<'_, K> {
+        MapxOrdRawValueValues { iter: self.iter() }
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> MapxOrdRawValueIterMut<'_, K> {
+        MapxOrdRawValueIterMut {
+            key_encoding: self.inner.key_encoding(),
+            inner: self.inner.iter_mut(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn values_mut(&mut self) -> MapxOrdRawValueValuesMut<'_, K> {
+        MapxOrdRawValueValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn range_mut<R: RangeBounds<K>>(
+        &mut self,
+        bounds: R,
+    ) -> MapxOrdRawValueIterMut<'_, K> {
+        self.range_ref_mut((bounds.start_bound(), bounds.end_bound()))
+    }
+
+    #[inline(always)]
+    pub fn range_ref_mut<'a, R: RangeBounds<&'a K>>(
+        &mut self,
+        bounds: R,
+    ) -> MapxOrdRawValueIterMut<'_, K>
+    where
+        K: 'a,
+    {
+        let ll;
+        let l = match bounds.start_bound() {
+            Bound::Included(lo) => {
+                ll = self.encode_key(lo);
+                Bound::Included(&ll[..])
+            }
+            Bound::Excluded(lo) => {
+                ll = self.encode_key(lo);
+                Bound::Excluded(&ll[..])
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let hh;
+        let h = match bounds.end_bound() {
+            Bound::Included(hi) => {
+                hh = self.encode_key(hi);
+                Bound::Included(&hh[..])
+            }
+            Bound::Excluded(hi) => {
+                hh = self.encode_key(hi);
+                Bound::Excluded(&hh[..])
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        MapxOrdRawValueIterMut {
+            key_encoding: self.inner.key_encoding(),
+            inner: self.inner.range_mut((l, h)),
+            p: PhantomData,
+        }
+    }
 
     #[inline(always)]
     pub fn first(&self) -> Option<(K, RawValue)> {
@@ -213,18 +346,136 @@ where
     }
 
     #[inline(always)]
-    pub fn remove(&mut self, key: &K) -> Option<RawValue> {
-        self.inner.remove(&key.to_bytes())
+    pub fn remove(&mut self, key: &K) -> Result<Option<RawValue>> {
+        self.inner.remove(&self.encode_key(key)).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn unset_value(&mut self, key: &K) -> Result<()> {
+        self.inner.remove(&self.encode_key(key)).c(d!()).map(|_| ())
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> Result<()> {
+        self.inner.clear().c(d!())
     }
 
+    /// Retain only the pairs specified by the predicate,
+    /// the removings are written in batches.
     #[inline(always)]
-    pub fn unset_value(&mut self, key: &K) {
-        self.inner.remove(&key.to_bytes());
+    pub fn retain<F>(&mut self, mut f: F) -> Result<()>
+    where
+        F: FnMut(&K, &[u8]) -> bool,
+    {
+        let key_encoding = self.inner.key_encoding();
+        self.inner
+            .retain(|k, v| f(&pnk!(key_encoding.decode_slice(k)), v))
+            .c(d!())
+    }
+
+    /// Remove all the pairs within the `bounds` in one shot,
+    /// and return them in order.
+    ///
+    /// NOTE: the removed pairs are collected into the memory
+    pub fn drain_range<R: RangeBounds<K>>(
+        &mut self,
+        bounds: R,
+    ) -> Result<Vec<(K, RawValue)>> {
+        let (l, h) = self.encode_bounds(&bounds);
+        self.inner
+            .drain_range((as_slice(&l), as_slice(&h)))
+            .c(d!())?
+            .into_iter()
+            .map(|(k, v)| self.decode_key(k).c(d!()).map(|k| (k, v)))
+            .collect()
     }
 
+    /// Split the map into two at the given key,
+    /// return everything after the given key, including the key.
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn split_off(&mut self, key: &K) -> Result<Self> {
+        self.inner
+            .split_off(&self.encode_key(key))
+            .c(d!())
+            .map(|inner| MapxOrdRawValue {
+                inner,
+                p: PhantomData,
+            })
+    }
+
+    /// Move all the pairs of `other` into `self`, leaving `other` empty,
+    /// the existing values of `self` will be overwritten.
+    #[inline(always)]
+    pub fn append(&mut self, other: &mut Self) -> Result<()> {
+        if self.inner.key_encoding() == other.inner.key_encoding() {
+            return self.inner.append(&mut other.inner).c(d!());
+        }
+
+        // the keys must be re-encoded
+        ensure_writable().c(d!())?;
+        self.extend(other.iter());
+        other.clear().c(d!())
+    }
+
+    /// Re-encode all the keys of an instance created by older versions of VSDB,
+    /// in which the negative keys of signed integers sort after the positive ones,
+    /// it is a no-op for the instances created by the current version.
+    ///
+    /// The keys are written into a new instance that replaces this one,
+    /// so the handle should be saved again, the old instance is left untouched
+    /// until it is reclaimed by `vsdb_gc`.
+    ///
+    /// A copy of the handle stored as a value of another instance is not updated,
+    /// the migrated handle should be written back into it, or the new instance
+    /// will be removed by `vsdb_gc`.
+    ///
+    /// This is an offline routine, the instance should not be used elsewhere meanwhile.
+    pub fn migrate_legacy_keys(&mut self) -> Result<()> {
+        if KeyEncoding::Legacy != self.inner.key_encoding() {
+            return Ok(());
+        }
+        self.inner.rekey(K::upgrade_legacy_bytes).c(d!())
+    }
+
+    // remove all the `keys` in batches
+    #[inline(always)]
+    pub(crate) fn remove_batch<I>(&mut self, keys: I)
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let key_encoding = self.inner.key_encoding();
+        self.inner.write_batch(
+            keys.into_iter()
+                .map(|k| (key_encoding.encode_owned(k), None)),
+        );
+    }
+
+    #[inline(always)]
+    fn encode_key(&self, key: &K) -> RawKey {
+        self.inner.key_encoding().encode(key)
+    }
+
+    #[inline(always)]
+    fn decode_key(&self, key: RawKey) -> VsdbResult<K> {
+        self.inner.key_encoding().decode(key)
+    }
+
+    // the bounds of the encoded keys
+    fn encode_bounds<R: RangeBounds<K>>(
+        &self,
+        bounds: &R,
+    ) -> (Bound<RawKey>, Bound<RawKey>) {
+        let encode = |b: Bound<&K>| match b {
+            Bound::Included(k) => Bound::Included(self.encode_key(k)),
+            Bound::Excluded(k) => Bound::Excluded(self.encode_key(k)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        (encode(bounds.start_bound()), encode(bounds.end_bound()))
+    }
+
+    #[inline(always)]
+    pub(crate) fn instance_prefix(&self) -> Pre {
+        self.inner.instance_prefix()
     }
 }
 
@@ -246,14 +497,48 @@ where
     }
 }
 
+impl<K> Extend<(K, RawValue)> for MapxOrdRawValue<K>
+where
+    K: KeyEnDeOrdered,
+{
+    fn extend<I: IntoIterator<Item = (K, RawValue)>>(&mut self, iter: I) {
+        let key_encoding = self.inner.key_encoding();
+        self.inner.extend(
+            iter.into_iter()
+                .map(|(k, v)| (key_encoding.encode_owned(k), v)),
+        );
+    }
+}
+
+impl<K> FromIterator<(K, RawValue)> for MapxOrdRawValue<K>
+where
+    K: KeyEnDeOrdered,
+{
+    fn from_iter<I: IntoIterator<Item = (K, RawValue)>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl<K> GcMark for MapxOrdRawValue<K> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+// Owns a copy of the handle instead of a `&mut` to it,
+// so the mutable iterators can yield many of them at the same time.
 #[derive(Debug)]
 pub struct ValueMut<'a, K>
 where
     K: KeyEnDeOrdered,
 {
-    hdr: &'a mut MapxOrdRawValue<K>,
+    hdr: MapxOrdRawValue<K>,
     key: K,
     value: RawValue,
+    _p: PhantomData<&'a mut MapxOrdRawValue<K>>,
 }
 
 impl<'a, K> ValueMut<'a, K>
@@ -261,7 +546,24 @@ where
     K: KeyEnDeOrdered,
 {
     pub(crate) fn new(hdr: &'a mut MapxOrdRawValue<K>, key: K, value: RawValue) -> Self {
-        ValueMut { hdr, key, value }
+        unsafe { Self::new_shadowed(hdr, key, value) }
+    }
+
+    /// # Safety
+    ///
+    /// `hdr` must be mutably borrowed for `'a` by the caller,
+    /// and no other `ValueMut` of the same key may be alive in the meantime.
+    pub(crate) unsafe fn new_shadowed(
+        hdr: &MapxOrdRawValue<K>,
+        key: K,
+        value: RawValue,
+    ) -> Self {
+        ValueMut {
+            hdr: hdr.shadow(),
+            key,
+            value,
+            _p: PhantomData,
+        }
     }
 }
 
@@ -270,7 +572,7 @@ where
     K: KeyEnDeOrdered,
 {
     fn drop(&mut self) {
-        self.hdr.set_value_ref(&self.key, &self.value);
+        pnk!(self.hdr.set_value_ref(&self.key, &self.value));
     }
 }
 
@@ -308,7 +610,7 @@ where
 {
     pub fn or_insert(self, default: RawValue) -> ValueMut<'a, K> {
         if !self.hdr.contains_key(&self.key) {
-            self.hdr.set_value_ref(&self.key, &default);
+            pnk!(self.hdr.set_value_ref(&self.key, &default));
         }
         pnk!(self.hdr.get_mut(&self.key))
     }
@@ -328,73 +630,160 @@ where
 {
     pub fn or_insert_ref(self, default: &[u8]) -> ValueMut<'a, K> {
         if !self.hdr.contains_key(self.key) {
-            self.hdr.set_value_ref(self.key, default);
+            pnk!(self.hdr.set_value_ref(self.key, default));
         }
         pnk!(self.hdr.get_mut(self.key))
     }
 }
 
-pub struct MapxOrdRawValueIter<K>
+pub struct MapxOrdRawValueIter<'a, K>
 where
     K: KeyEnDeOrdered,
 {
-    iter: MapxRawIter,
+    iter: MapxRawIter<'a>,
+    key_encoding: KeyEncoding,
     p: PhantomData<K>,
 }
 
-impl<K> Iterator for MapxOrdRawValueIter<K>
+impl<'a, K> Iterator for MapxOrdRawValueIter<'a, K>
 where
     K: KeyEnDeOrdered,
 {
     type Item = (K, RawValue);
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+        self.iter.next().map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
     }
 }
 
-impl<K> DoubleEndedIterator for MapxOrdRawValueIter<K>
+impl<'a, K> DoubleEndedIterator for MapxOrdRawValueIter<'a, K>
 where
     K: KeyEnDeOrdered,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.iter
             .next_back()
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
     }
 }
 
-impl<K> ExactSizeIterator for MapxOrdRawValueIter<K> where K: KeyEnDeOrdered {}
+impl<'a, K> ExactSizeIterator for MapxOrdRawValueIter<'a, K> where K: KeyEnDeOrdered {}
 
-/////////////////////////////////////////////////////////////////////////////
-/////////////////////////////////////////////////////////////////////////////
+pub struct MapxOrdRawValueValues<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    iter: MapxOrdRawValueIter<'a, K>,
+}
 
-pub struct MapxOrdRawKeyValuesMut<'a, V>
+impl<'a, K> Iterator for MapxOrdRawValueValues<'a, K>
 where
-    V: ValueEnDe,
+    K: KeyEnDeOrdered,
 {
-    inner: MapxOrdRawKeyIterMut<'a, V>,
+    type Item = RawValue;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
 }
 
-impl<'a, V> Iterator for MapxOrdRawKeyValuesMut<'a, V>
+impl<'a, K> DoubleEndedIterator for MapxOrdRawValueValues<'a, K>
 where
-    V: ValueEnDe,
+    K: KeyEnDeOrdered,
 {
-    type Item = ValueIterMut<'a, V>;
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K> ExactSizeIterator for MapxOrdRawValueValues<'a, K> where K: KeyEnDeOrdered {}
+
+pub struct MapxOrdRawValueTryIter<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    iter: MapxRawTryIter<'a>,
+    key_encoding: KeyEncoding,
+    p: PhantomData<K>,
+}
+
+impl<'a, K> Iterator for MapxOrdRawValueTryIter<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    type Item = Result<(K, RawValue)>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(_, v)| v)
+        self.iter
+            .next()
+            .map(|kv| {
+                kv.and_then(|(k, v)| Ok((self.key_encoding.decode(k).c(d!())?, v)))
+            })
     }
 }
 
-impl<'a, V> DoubleEndedIterator for MapxOrdRawKeyValuesMut<'a, V>
+impl<'a, K> DoubleEndedIterator for MapxOrdRawValueTryIter<'a, K>
 where
-    V: ValueEnDe,
+    K: KeyEnDeOrdered,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.inner.next_back().map(|(_, v)| v)
+        self.iter
+            .next_back()
+            .map(|kv| {
+                kv.and_then(|(k, v)| Ok((self.key_encoding.decode(k).c(d!())?, v)))
+            })
+    }
+}
+
+pub struct MapxOrdRawValueIterMut<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    inner: MapxRawIterMut<'a>,
+    key_encoding: KeyEncoding,
+    p: PhantomData<K>,
+}
+
+impl<'a, K> Iterator for MapxOrdRawValueIterMut<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    type Item = (K, ValueIterMut<'a>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
+    }
+}
+
+impl<'a, K> DoubleEndedIterator for MapxOrdRawValueIterMut<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
     }
 }
 
-impl<'a, V> ExactSizeIterator for MapxOrdRawKeyValues<'a, V> where V: ValueEnDe {}
+pub struct MapxOrdRawValueValuesMut<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    inner: MapxOrdRawValueIterMut<'a, K>,
+}
 
-/////////////////////////////////////////////////////////////////////////////
-/////////////////////////////////////////////////////////////////////////////
+impl<'a, K> Iterator for MapxOrdRawValueValuesMut<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    type Item = ValueIterMut<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K> DoubleEndedIterator for MapxOrdRawValueValuesMut<'a, K>
+where
+    K: KeyEnDeOrdered,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ValueEnDe;
+use crate::{common::engines::reload_as_legacy, ValueEnDe};
 
 use super::*;
 use ruc::*;
@@ -13,14 +13,14 @@ fn test_insert() {
             assert!(hdr.get(&key).is_none());
             hdr.entry_ref(&key).or_insert_ref(&value);
             hdr.set_value(key, Box::new(value));
-            assert!(hdr.insert(key, Box::new(value)).is_some());
+            assert!(hdr.insert(key, Box::new(value)).unwrap().is_some());
             assert!(hdr.contains_key(&key));
 
             assert_eq!(*pnk!(hdr.get(&key)), value);
-            assert_eq!(*pnk!(hdr.remove(&key)), value);
+            assert_eq!(*pnk!(hdr.remove(&key)).unwrap(), value);
 
             assert!(hdr.get(&key).is_none());
-            assert!(hdr.insert(key, Box::new(value)).is_none());
+            assert!(hdr.insert(key, Box::new(value)).unwrap().is_none());
         });
     hdr.clear();
     (0..max).map(|i: usize| i).for_each(|key| {
@@ -35,12 +35,12 @@ fn test_len() {
     (0..max)
         .map(|i: usize| (i, (max + i).to_be_bytes()))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(key, Box::new(value)).is_none());
+            assert!(hdr.insert(key, Box::new(value)).unwrap().is_none());
         });
     assert_eq!(500, hdr.len());
 
     for key in 0..max {
-        assert!(hdr.remove(&key).is_some());
+        assert!(hdr.remove(&key).unwrap().is_some());
     }
     assert_eq!(0, hdr.len());
 }
@@ -53,7 +53,7 @@ fn test_valueende() {
         (0..cnt)
             .map(|i: usize| (i, <usize as ValueEnDe>::encode(&i)))
             .for_each(|(key, value)| {
-                assert!(hdr.insert(key, value).is_none());
+                assert!(hdr.insert(key, value).unwrap().is_none());
             });
         <MapxOrdRawValue<usize> as ValueEnDe>::encode(&hdr)
     };
@@ -72,9 +72,9 @@ fn test_iter() {
     (0..max)
         .map(|i: usize| (i, i.to_be_bytes()))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(key, Box::new(value)).is_none());
+            assert!(hdr.insert(key, Box::new(value)).unwrap().is_none());
         });
-    for (key, _) in hdr.iter() {
+    for (key, _) in unsafe { hdr.shadow() }.iter() {
         hdr.unset_value(&key);
     }
     assert_eq!(0, hdr.len());
@@ -87,7 +87,7 @@ fn test_first_last() {
     (0..max)
         .map(|i: usize| (i, <usize as ValueEnDe>::encode(&i)))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(key, value).is_none());
+            assert!(hdr.insert(key, value).unwrap().is_none());
         });
 
     let (_, value) = pnk!(hdr.first());
@@ -98,3 +98,16 @@ fn test_first_last() {
     let val = pnk!(<usize as ValueEnDe>::decode(&value));
     assert_eq!(max - 1, val);
 }
+
+#[test]
+fn test_legacy_keys() {
+    let mut hdr: MapxOrdRawValue<i32> = reload_as_legacy(&MapxOrdRawValue::new());
+    (-3..3).for_each(|i: i32| {
+        pnk!(hdr.insert(i, i.to_be_bytes().to_vec().into_boxed_slice()));
+    });
+    assert_eq!(Some(0), hdr.first().map(|(k, _)| k));
+
+    pnk!(hdr.migrate_legacy_keys());
+    assert!(hdr.iter().map(|(k, _)| k).eq(-3..3));
+    assert!(hdr.iter().all(|(k, v)| v[..] == k.to_be_bytes()));
+}
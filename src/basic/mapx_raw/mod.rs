@@ -14,18 +14,18 @@
 //!
 //! let mut l = MapxRaw::new();
 //!
-//! l.insert(&[1], &[0]);
-//! l.insert(&[1], &[0]);
-//! l.insert(&[2], &[0]);
+//! l.insert(&[1], &[0]).unwrap();
+//! l.insert(&[1], &[0]).unwrap();
+//! l.insert(&[2], &[0]).unwrap();
 //!
 //! l.iter().for_each(|(_, v)| {
 //!     assert_eq!(&v[..], &[0]);
 //! });
 //!
-//! l.remove(&[2]);
+//! l.remove(&[2]).unwrap();
 //! assert_eq!(l.len(), 1);
 //!
-//! l.clear();
+//! l.clear().unwrap();
 //! assert_eq!(l.len(), 0);
 //! ```
 //!
@@ -33,12 +33,20 @@
 #[cfg(test)]
 mod test;
 
-use crate::common::{engines, RawKey, RawValue};
+use crate::common::{
+    ende::{KeyEncoding, ValueCodec},
+    engines::{self, ValueCfg},
+    error::VsdbResult,
+    gc::{GcMark, GcMarks},
+    schema::SchemaId,
+    ensure_writable, InstanceOpts, Pre, RawKey, RawValue,
+};
 use ruc::*;
 use serde::{Deserialize, Serialize};
 use std::ops::RangeBounds;
 
 pub(crate) type MapxRawIter<'a> = engines::MapxIter<'a>;
+pub(crate) type MapxRawTryIter<'a> = engines::MapxTryIter<'a>;
 pub(crate) type MapxRawIterMut<'a> = engines::MapxIterMut<'a>;
 pub(crate) type ValueMut<'a> = engines::ValueMut<'a>;
 pub(crate) type ValueIterMut<'a> = engines::ValueIterMut<'a>;
@@ -50,6 +58,10 @@ pub struct MapxRaw {
 }
 
 impl MapxRaw {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -64,11 +76,84 @@ impl MapxRaw {
         }
     }
 
+    /// Same as `new`, but an error will be returned
+    /// instead of a panic in the read-only modes.
     #[inline(always)]
-    pub fn get(&self, key: &[u8]) -> Option<&RawValue> {
+    pub fn try_new() -> Result<Self> {
+        engines::Mapx::try_new()
+            .c(d!())
+            .map(|inner| MapxRaw { inner })
+    }
+
+    /// Create an instance without tracking its length,
+    /// writes are cheaper, but `len()` will do a full scanning.
+    #[inline(always)]
+    pub fn new_untracked() -> Self {
+        MapxRaw {
+            inner: engines::Mapx::new_untracked(),
+        }
+    }
+
+    /// Create an instance with the specified options,
+    /// the options are recorded in the serialized handle.
+    #[inline(always)]
+    pub fn new_with_opts(opts: &InstanceOpts) -> Self {
+        MapxRaw {
+            inner: engines::Mapx::new_with_opts(opts),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn new_with_cfg(value_cfg: ValueCfg) -> Self {
+        MapxRaw {
+            inner: engines::Mapx::new_with_cfg(value_cfg),
+        }
+    }
+
+    // the codec of the typed values built on this instance
+    #[inline(always)]
+    pub(crate) fn codec(&self) -> ValueCodec {
+        self.inner.codec()
+    }
+
+    // the encoding of the typed keys built on this instance
+    #[inline(always)]
+    pub(crate) fn key_encoding(&self) -> KeyEncoding {
+        self.inner.key_encoding()
+    }
+
+    #[inline(always)]
+    pub(crate) fn instance_prefix(&self) -> Pre {
+        self.inner.instance_prefix()
+    }
+
+    // rewrite all the values to the latest version of the schema `id`
+    #[inline(always)]
+    pub(crate) fn migrate_schema(&mut self, id: SchemaId) -> Result<()> {
+        self.inner.migrate_schema(id).c(d!())
+    }
+
+    // re-encode all the keys into a new instance that replaces this one
+    #[inline(always)]
+    pub(crate) fn rekey<F>(&mut self, rekey: F) -> Result<()>
+    where
+        F: Fn(&[u8]) -> VsdbResult<RawKey>,
+    {
+        self.inner.rekey(rekey).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn get(&self, key: &[u8]) -> Option<RawValue> {
         self.inner.get(key)
     }
 
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, key: &[u8]) -> Result<Option<RawValue>> {
+        self.inner.try_get(key).c(d!())
+    }
+
     #[inline(always)]
     pub fn get_mut<'a>(&'a mut self, key: &'a [u8]) -> Option<ValueMut<'a>> {
         self.inner.get_mut(key)
@@ -105,41 +190,149 @@ impl MapxRaw {
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> MapxRawIter {
+    pub fn iter(&self) -> MapxRawIter<'_> {
         self.inner.iter()
     }
 
     #[inline(always)]
-    pub fn range<'a, R: RangeBounds<&'a [u8]>>(&'a self, bounds: R) -> MapxRawIter {
+    pub fn range<'a, R: RangeBounds<&'a [u8]>>(&self, bounds: R) -> MapxRawIter<'_> {
         self.inner.range(bounds)
     }
 
+    /// Same as `iter`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxRawTryIter<'_> {
+        self.inner.try_iter()
+    }
+
+    /// Same as `range`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_range<'a, R: RangeBounds<&'a [u8]>>(
+        &self,
+        bounds: R,
+    ) -> MapxRawTryIter<'_> {
+        self.inner.try_range(bounds)
+    }
+
     #[inline(always)]
-    pub fn iter_mut(&mut self) -> MapxRawIterMut {
+    pub fn iter_mut(&mut self) -> MapxRawIterMut<'_> {
         self.inner.iter_mut()
     }
 
     #[inline(always)]
     pub fn range_mut<'a, R: RangeBounds<&'a [u8]>>(
-        &'a mut self,
+        &mut self,
         bounds: R,
-    ) -> MapxRawIterMut {
+    ) -> MapxRawIterMut<'_> {
         self.inner.range_mut(bounds)
     }
 
     #[inline(always)]
-    pub fn insert(&mut self, key: &[u8], value: &[u8]) -> Option<RawValue> {
-        self.inner.insert(key, value)
+    pub fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<Option<RawValue>> {
+        self.inner.try_insert(key, value).c(d!())
     }
 
     #[inline(always)]
-    pub fn remove(&mut self, key: &[u8]) -> Option<RawValue> {
-        self.inner.remove(key)
+    pub fn remove(&mut self, key: &[u8]) -> Result<Option<RawValue>> {
+        self.inner.try_remove(key).c(d!())
     }
 
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn clear(&mut self) -> Result<()> {
+        self.inner.try_clear().c(d!())
+    }
+
+    /// Retain only the pairs specified by the predicate,
+    /// the removings are written in batches.
+    #[inline(always)]
+    pub fn retain<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnMut(&[u8], &[u8]) -> bool,
+    {
+        ensure_writable().c(d!())?;
+        self.inner.retain(f);
+        Ok(())
+    }
+
+    /// Remove all the pairs within the `bounds` in one shot,
+    /// and return them in order.
+    ///
+    /// NOTE: the removed pairs are collected into the memory
+    pub fn drain_range<'a, R: RangeBounds<&'a [u8]>>(
+        &mut self,
+        bounds: R,
+    ) -> Result<Vec<(RawKey, RawValue)>> {
+        ensure_writable().c(d!())?;
+        let bounds = (bounds.start_bound().cloned(), bounds.end_bound().cloned());
+        let drained = self.range(bounds).collect();
+        self.inner.remove_range(bounds);
+        Ok(drained)
+    }
+
+    /// Split the map into two at the given key,
+    /// return everything after the given key, including the key.
+    pub fn split_off(&mut self, key: &[u8]) -> Result<Self> {
+        let mut other = self.new_like().c(d!())?;
+        other.extend(self.range(key..));
+        self.inner.remove_range(key..);
+        Ok(other)
+    }
+
+    /// Move all the pairs of `other` into `self`, leaving `other` empty,
+    /// the existing values of `self` will be overwritten.
+    pub fn append(&mut self, other: &mut Self) -> Result<()> {
+        ensure_writable().c(d!())?;
+        if self.instance_prefix() == other.instance_prefix() {
+            return Ok(());
+        }
+        self.extend(other.iter());
+        other.clear().c(d!())
+    }
+
+    // remove all the pairs within the `bounds` in one shot
+    #[inline(always)]
+    pub(crate) fn remove_range<'a, R: RangeBounds<&'a [u8]>>(&mut self, bounds: R) {
+        self.inner.remove_range(bounds)
+    }
+
+    // a `None` value means a removing
+    #[inline(always)]
+    pub(crate) fn write_batch<I>(&mut self, kvs: I)
+    where
+        I: IntoIterator<Item = (RawKey, Option<RawValue>)>,
+    {
+        self.inner.write_batch(kvs)
+    }
+
+    // same as `write_batch`, but never split into several batches
+    #[inline(always)]
+    pub(crate) fn write_batch_at_once<I>(&mut self, kvs: I)
+    where
+        I: IntoIterator<Item = (RawKey, Option<RawValue>)>,
+    {
+        self.inner.write_batch_at_once(kvs)
+    }
+
+    // create a new empty instance with the same configurations
+    #[inline(always)]
+    pub(crate) fn new_like(&self) -> Result<Self> {
+        self.inner
+            .try_new_like()
+            .c(d!())
+            .map(|inner| MapxRaw { inner })
+    }
+
+    // return `false` if the instance has been marked before
+    #[inline(always)]
+    pub(crate) fn gc_mark_instance(&self, marks: &mut GcMarks) -> bool {
+        self.inner.gc_mark(marks)
+    }
+
+    #[inline(always)]
+    pub(crate) fn gc_is_marked(&self, marks: &GcMarks) -> bool {
+        self.inner.gc_is_marked(marks)
     }
 }
 
@@ -149,6 +342,28 @@ impl Default for MapxRaw {
     }
 }
 
+impl Extend<(RawKey, RawValue)> for MapxRaw {
+    fn extend<I: IntoIterator<Item = (RawKey, RawValue)>>(&mut self, iter: I) {
+        self.inner
+            .write_batch(iter.into_iter().map(|(k, v)| (k, Some(v))));
+    }
+}
+
+impl FromIterator<(RawKey, RawValue)> for MapxRaw {
+    fn from_iter<I: IntoIterator<Item = (RawKey, RawValue)>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl GcMark for MapxRaw {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.gc_mark_instance(marks);
+    }
+}
+
 pub struct EntryRef<'a> {
     key: &'a [u8],
     hdr: &'a mut MapxRaw,
@@ -157,7 +372,7 @@ pub struct EntryRef<'a> {
 impl<'a> EntryRef<'a> {
     pub fn or_insert_ref(self, default: &'a [u8]) -> ValueMut<'a> {
         if !self.hdr.contains_key(self.key) {
-            self.hdr.insert(self.key, default);
+            pnk!(self.hdr.insert(self.key, default));
         }
         pnk!(self.hdr.get_mut(self.key))
     }
@@ -167,7 +382,7 @@ impl<'a> EntryRef<'a> {
         F: FnOnce() -> RawValue,
     {
         if !self.hdr.contains_key(self.key) {
-            self.hdr.insert(self.key, &f());
+            pnk!(self.hdr.insert(self.key, &f()));
         }
         pnk!(self.hdr.get_mut(self.key))
     }
@@ -1,7 +1,8 @@
-use crate::ValueEnDe;
+use crate::{ValueEn, ValueEnDe};
 
 use super::*;
 use ruc::*;
+use std::ops::Bound;
 
 #[test]
 fn test_insert() {
@@ -12,12 +13,12 @@ fn test_insert() {
         .for_each(|(key, value)| {
             assert!(hdr.get(&key).is_none());
             hdr.entry_ref(&key).or_insert_ref(&value);
-            assert!(hdr.insert(&key, &value).is_some());
+            assert!(hdr.insert(&key, &value).unwrap().is_some());
             assert!(hdr.contains_key(&key));
             assert_eq!(pnk!(hdr.get(&key)), value);
-            assert_eq!(pnk!(hdr.remove(&key)), value);
+            assert_eq!(pnk!(hdr.remove(&key)).unwrap(), value);
             assert!(hdr.get(&key).is_none());
-            assert!(hdr.insert(&key, &value).is_none());
+            assert!(hdr.insert(&key, &value).unwrap().is_none());
         });
     hdr.clear();
     (0..max).map(|i: usize| i.to_be_bytes()).for_each(|key| {
@@ -25,6 +26,33 @@ fn test_insert() {
     });
     assert!(hdr.is_empty());
 }
+
+#[test]
+fn test_clear() {
+    let mut hdr = MapxRaw::new();
+    let mut hdr_neighbor = MapxRaw::new();
+    let max = 500;
+    (0..max).map(|i: usize| i.to_be_bytes()).for_each(|key| {
+        assert!(hdr.insert(&key, &key).unwrap().is_none());
+        assert!(hdr_neighbor.insert(&key, &key).unwrap().is_none());
+    });
+    hdr.insert(&[u8::MAX; 64], &[]);
+    hdr_neighbor.insert(&[u8::MAX; 64], &[]);
+
+    hdr.clear();
+    assert!(hdr.is_empty());
+    assert!(hdr.iter().next().is_none());
+    assert!(hdr.get(&[u8::MAX; 64]).is_none());
+
+    assert_eq!(max + 1, hdr_neighbor.len());
+    assert_eq!(max + 1, hdr_neighbor.iter().count());
+    assert!(hdr_neighbor.get(&[u8::MAX; 64]).is_some());
+
+    hdr.insert(&[0], &[0]);
+    assert_eq!(1, hdr.len());
+    assert_eq!(&[0], &hdr.get(&[0]).unwrap()[..]);
+}
+
 #[test]
 fn test_len() {
     let mut hdr = MapxRaw::new();
@@ -32,12 +60,12 @@ fn test_len() {
     (0..max)
         .map(|i: usize| (i.to_be_bytes(), (max + i).to_be_bytes()))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(&key, &value).is_none());
+            assert!(hdr.insert(&key, &value).unwrap().is_none());
         });
     assert_eq!(500, hdr.len());
 
     for key in 0..max {
-        assert!(hdr.remove(&key.to_be_bytes()).is_some());
+        assert!(hdr.remove(&key.to_be_bytes()).unwrap().is_some());
     }
     assert_eq!(0, hdr.len());
 }
@@ -50,7 +78,7 @@ fn test_valueende() {
         (0..cnt)
             .map(|i: usize| (i.to_be_bytes(), <usize as ValueEnDe>::encode(&i)))
             .for_each(|(key, value)| {
-                assert!(hdr.insert(&key, &value).is_none());
+                assert!(hdr.insert(&key, &value).unwrap().is_none());
             });
         <MapxRaw as ValueEnDe>::encode(&hdr)
     };
@@ -64,6 +92,56 @@ fn test_valueende() {
     });
 }
 
+// the handles written by older versions only contain the prefix
+#[test]
+fn test_legacy_handle() {
+    #[derive(Serialize)]
+    struct LegacyCfg {
+        prefix: [u8; 8],
+    }
+
+    struct LegacyMapx(RawValue);
+
+    impl Serialize for LegacyMapx {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            serializer: S,
+        ) -> std::result::Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+
+    #[derive(Serialize)]
+    struct LegacyMapxRaw {
+        inner: LegacyMapx,
+    }
+
+    let mut hdr = MapxRaw::new();
+    hdr.insert(&[1], &[10]);
+    hdr.insert(&[2], &[20]);
+
+    let cfg = LegacyCfg {
+        prefix: hdr.instance_prefix().to_be_bytes(),
+    };
+    let legacy = LegacyMapxRaw {
+        inner: LegacyMapx(ValueEn::encode_value(&cfg)),
+    };
+
+    let reloaded = pnk!(<MapxRaw as ValueEnDe>::decode(&ValueEn::encode_value(
+        &legacy
+    )));
+    assert_eq!(2, reloaded.len());
+    assert_eq!(&pnk!(reloaded.get(&[2]))[..], &[20]);
+    assert_eq!(hdr.instance_prefix(), reloaded.instance_prefix());
+    assert_eq!(KeyEncoding::Ordered, hdr.key_encoding());
+    assert_eq!(KeyEncoding::Legacy, reloaded.key_encoding());
+
+    // re-encoded with the current format
+    let reloaded = pnk!(<MapxRaw as ValueEnDe>::decode(&reloaded.encode()));
+    assert_eq!(2, reloaded.len());
+    assert_eq!(KeyEncoding::Legacy, reloaded.key_encoding());
+}
+
 #[test]
 fn test_iter() {
     let mut hdr = MapxRaw::new();
@@ -71,10 +149,10 @@ fn test_iter() {
     (0..max)
         .map(|i: usize| (i.to_be_bytes(), i.to_be_bytes()))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(&key, &value).is_none());
+            assert!(hdr.insert(&key, &value).unwrap().is_none());
         });
-    for (key, _) in hdr.iter() {
-        assert!(hdr.remove(&key).is_some());
+    for (key, _) in unsafe { hdr.shadow() }.iter() {
+        assert!(hdr.remove(&key).unwrap().is_some());
     }
     assert_eq!(0, hdr.len());
 }
@@ -86,7 +164,7 @@ fn test_first_last() {
     (0..max)
         .map(|i: usize| (i.to_be_bytes(), <usize as ValueEnDe>::encode(&i)))
         .for_each(|(key, value)| {
-            assert!(hdr.insert(&key, &value).is_none());
+            assert!(hdr.insert(&key, &value).unwrap().is_none());
         });
     let (_, value) = pnk!(hdr.iter().next());
     let val = pnk!(<usize as ValueEnDe>::decode(&value));
@@ -96,3 +174,54 @@ fn test_first_last() {
     let val = pnk!(<usize as ValueEnDe>::decode(&value));
     assert_eq!(max - 1, val);
 }
+
+#[test]
+fn test_range() {
+    let mut hdr = MapxRaw::new();
+    let mut hdr_neighbor = MapxRaw::new();
+    let max = 500usize;
+    (0..max).map(|i| i.to_be_bytes()).for_each(|key| {
+        assert!(hdr.insert(&key, &key).unwrap().is_none());
+        assert!(hdr_neighbor.insert(&key, &key).unwrap().is_none());
+    });
+    hdr.insert(&[], &[]);
+    hdr.insert(&[u8::MAX; 128], &[]);
+    hdr_neighbor.insert(&[u8::MAX; 256], &[]);
+
+    let k = |i: usize| i.to_be_bytes();
+
+    assert_eq!(max + 2, hdr.iter().count());
+    assert_eq!(&[u8::MAX; 128][..], &hdr.iter().next_back().unwrap().0[..]);
+    assert_eq!(&[0u8; 0][..], &hdr.iter().next().unwrap().0[..]);
+
+    assert_eq!(100, hdr.range(&k(100)[..]..&k(200)[..]).count());
+    assert_eq!(101, hdr.range(&k(100)[..]..=&k(200)[..]).count());
+    assert_eq!(
+        99,
+        hdr.range((Bound::Excluded(&k(100)[..]), Bound::Excluded(&k(200)[..])))
+            .count()
+    );
+    assert_eq!(
+        &k(199)[..],
+        &hdr.range(&k(100)[..]..&k(200)[..]).next_back().unwrap().0[..]
+    );
+    assert_eq!(
+        &k(200)[..],
+        &hdr.range(&k(100)[..]..=&k(200)[..]).next_back().unwrap().0[..]
+    );
+    assert_eq!(
+        &k(101)[..],
+        &hdr.range((Bound::Excluded(&k(100)[..]), Bound::Unbounded))
+            .next()
+            .unwrap()
+            .0[..]
+    );
+
+    // keys of the neighbor instance are never touched
+    assert_eq!(
+        &[u8::MAX; 128][..],
+        &hdr.range(&k(100)[..]..).next_back().unwrap().0[..]
+    );
+    assert_eq!(max + 2, hdr.range(..=&[u8::MAX; 256][..]).count());
+    assert_eq!(0, hdr.range(&k(max)[..]..&[u8::MAX; 64][..]).count());
+}
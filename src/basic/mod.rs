@@ -2,11 +2,17 @@
 //! Unversioned functions.
 //!
 
+pub mod dequex;
+pub(crate) mod index_tree;
+pub mod indexed_mapx;
 pub mod mapx;
 pub mod mapx_ord;
 pub mod mapx_ord_rawkey;
 pub mod mapx_ord_rawvalue;
 pub mod mapx_raw;
+pub mod multi_mapx;
 pub mod orphan;
+pub mod setx;
+pub mod setx_ord;
 pub mod vecx;
 pub mod vecx_raw;
@@ -0,0 +1,444 @@
+//!
+//! A one-to-many map storing data in disk,
+//! all the `(key, value)` pairs share one keyspace.
+//!
+//! NOTE:
+//! - Both keys and values will be encoded by `KeyEnDeOrdered`
+//! - The values of a key are kept in ascending order
+//! - No counter is kept for each key, `count` scans all the values of the key
+//!
+//! # Examples
+//!
+//! ```
+//! use vsdb::basic::multi_mapx::MultiMapx;
+//!
+//! let dir = format!("/tmp/__vsdb__{}", rand::random::<u128>());
+//! vsdb::vsdb_set_base_dir(&dir);
+//!
+//! let mut l = MultiMapx::new();
+//!
+//! assert!(l.insert(1u32, 20u64).unwrap());
+//! assert!(l.insert(1u32, 10u64).unwrap());
+//! assert!(!l.insert(1u32, 10u64).unwrap());
+//! assert!(l.insert(2u32, 10u64).unwrap());
+//!
+//! assert!(l.get_all(&1).eq([10, 20]));
+//! assert!(l.get_all_range(&1, 15..).eq([20]));
+//! assert_eq!(l.count(&1), 2);
+//!
+//! assert!(l.remove(&1, &10).unwrap());
+//! l.remove_all(&1).unwrap();
+//! assert_eq!(l.count(&1), 0);
+//! assert_eq!(l.len(), 1);
+//! ```
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::mapx_raw::{MapxRaw, MapxRawIter, MapxRawTryIter},
+    common::{
+        ende::{encode_key_part, KeyEnDeOrdered},
+        ensure_writable,
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+        RawKey, RawValue,
+    },
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(bound = "")]
+pub struct MultiMapx<K, V> {
+    inner: MapxRaw,
+    p: PhantomData<(K, V)>,
+}
+
+impl<K, V> MultiMapx<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        MultiMapx {
+            inner: MapxRaw::new(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, key: &K, value: &V) -> bool {
+        self.inner.contains_key(&pair_key(key, value))
+    }
+
+    #[inline(always)]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_all(key).next().is_some()
+    }
+
+    /// The number of all the pairs.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// The number of the values of `key`.
+    ///
+    /// NOTE: this is O(n) in the number of the values,
+    /// they are scanned from the disk one by one on every call.
+    #[inline(always)]
+    pub fn count(&self, key: &K) -> usize {
+        self.get_all(key).count()
+    }
+
+    /// Return `true` if the pair did not exist before.
+    #[inline(always)]
+    pub fn insert(&mut self, key: K, value: V) -> VsdbResult<bool> {
+        self.insert_ref(&key, &value)
+    }
+
+    #[inline(always)]
+    pub fn insert_ref(&mut self, key: &K, value: &V) -> VsdbResult<bool> {
+        Ok(self.inner.insert(&pair_key(key, value), &[])?.is_none())
+    }
+
+    /// Return `true` if the pair existed before.
+    #[inline(always)]
+    pub fn remove(&mut self, key: &K, value: &V) -> VsdbResult<bool> {
+        Ok(self.inner.remove(&pair_key(key, value))?.is_some())
+    }
+
+    /// Remove all the values of `key` in one shot.
+    ///
+    /// NOTE: the values are still scanned once to keep the length exact
+    #[inline(always)]
+    pub fn remove_all(&mut self, key: &K) -> VsdbResult<()> {
+        ensure_writable()?;
+        let prefix = key_prefix(key);
+        let (lo, hi) = value_bounds::<V, _>(&prefix, ..);
+        self.inner.remove_range((as_slice(&lo), as_slice(&hi)));
+        Ok(())
+    }
+
+    /// Iterate over the values of `key` in ascending order.
+    #[inline(always)]
+    pub fn get_all(&self, key: &K) -> MultiMapxValues<'_, V> {
+        self.get_all_range(key, ..)
+    }
+
+    /// Iterate over the values of `key` within `bounds`,
+    /// for paging, start the next page after the last value of the current one,
+    /// e.g. `(Bound::Excluded(last), Bound::Unbounded)`.
+    pub fn get_all_range<R: RangeBounds<V>>(
+        &self,
+        key: &K,
+        bounds: R,
+    ) -> MultiMapxValues<'_, V> {
+        let prefix = key_prefix(key);
+        let (lo, hi) = value_bounds(&prefix, bounds);
+        MultiMapxValues {
+            iter: self.inner.range((as_slice(&lo), as_slice(&hi))),
+            prefix_len: prefix.len(),
+            p: PhantomData,
+        }
+    }
+
+    /// Iterate over all the pairs, ordered by keys and then values.
+    #[inline(always)]
+    pub fn iter(&self) -> MultiMapxIter<'_, K, V> {
+        MultiMapxIter {
+            iter: self.inner.iter(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `get_all`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_all(&self, key: &K) -> MultiMapxTryValues<'_, V> {
+        self.try_get_all_range(key, ..)
+    }
+
+    /// Same as `get_all_range`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    pub fn try_get_all_range<R: RangeBounds<V>>(
+        &self,
+        key: &K,
+        bounds: R,
+    ) -> MultiMapxTryValues<'_, V> {
+        let prefix = key_prefix(key);
+        let (lo, hi) = value_bounds(&prefix, bounds);
+        MultiMapxTryValues {
+            iter: self.inner.try_range((as_slice(&lo), as_slice(&hi))),
+            prefix_len: prefix.len(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `iter`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MultiMapxTryIter<'_, K, V> {
+        MultiMapxTryIter {
+            iter: self.inner.try_iter(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        Ok(self.inner.clear()?)
+    }
+}
+
+impl<K, V> Clone for MultiMapx<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            p: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Default for MultiMapx<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for MultiMapx<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.inner.extend(
+            iter.into_iter()
+                .map(|(k, v)| (pair_key(&k, &v), RawValue::default())),
+        );
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for MultiMapx<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl<K, V> GcMark for MultiMapx<K, V> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+pub struct MultiMapxIter<'a, K, V> {
+    iter: MapxRawIter<'a>,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> Iterator for MultiMapxIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(k, _)| pnk!(<(K, V)>::from_slice(&k)))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MultiMapxIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|(k, _)| pnk!(<(K, V)>::from_slice(&k)))
+    }
+}
+
+pub struct MultiMapxValues<'a, V> {
+    iter: MapxRawIter<'a>,
+    prefix_len: usize,
+    p: PhantomData<V>,
+}
+
+impl<'a, V: KeyEnDeOrdered> Iterator for MultiMapxValues<'a, V> {
+    type Item = V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(k, _)| pnk!(V::from_slice(&k[self.prefix_len..])))
+    }
+}
+
+impl<'a, V: KeyEnDeOrdered> DoubleEndedIterator for MultiMapxValues<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|(k, _)| pnk!(V::from_slice(&k[self.prefix_len..])))
+    }
+}
+
+pub struct MultiMapxTryIter<'a, K, V> {
+    iter: MapxRawTryIter<'a>,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> Iterator for MultiMapxTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    type Item = VsdbResult<(K, V)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|kv| <(K, V)>::from_slice(&kv?.0))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MultiMapxTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|kv| <(K, V)>::from_slice(&kv?.0))
+    }
+}
+
+pub struct MultiMapxTryValues<'a, V> {
+    iter: MapxRawTryIter<'a>,
+    prefix_len: usize,
+    p: PhantomData<V>,
+}
+
+impl<'a, V: KeyEnDeOrdered> Iterator for MultiMapxTryValues<'a, V> {
+    type Item = VsdbResult<V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|kv| V::from_slice(&kv?.0[self.prefix_len..]))
+    }
+}
+
+impl<'a, V: KeyEnDeOrdered> DoubleEndedIterator for MultiMapxTryValues<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|kv| V::from_slice(&kv?.0[self.prefix_len..]))
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////
+
+// A pair is stored as a composite key `(key, value)` with an empty value,
+// encoded in the same way as the tuple `(K, V)`, the encoded key part is
+// never a prefix of another one, so the values of a key are contiguous.
+
+#[inline(always)]
+pub(crate) fn key_prefix<K: KeyEnDeOrdered>(key: &K) -> Vec<u8> {
+    let mut buf = vec![];
+    encode_key_part(key, false, &mut buf);
+    buf
+}
+
+#[inline(always)]
+pub(crate) fn pair_key<K, V>(key: &K, value: &V) -> RawKey
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    let mut buf = key_prefix(key);
+    encode_key_part(value, true, &mut buf);
+    buf.into_boxed_slice()
+}
+
+// convert `bounds` of values to a range of raw keys under `prefix`
+pub(crate) fn value_bounds<V, R>(
+    prefix: &[u8],
+    bounds: R,
+) -> (Bound<RawKey>, Bound<RawKey>)
+where
+    V: KeyEnDeOrdered,
+    R: RangeBounds<V>,
+{
+    let with_prefix = |v: &V| {
+        let mut buf = prefix.to_vec();
+        encode_key_part(v, true, &mut buf);
+        buf.into_boxed_slice()
+    };
+    let lo = match bounds.start_bound() {
+        Bound::Included(v) => Bound::Included(with_prefix(v)),
+        Bound::Excluded(v) => Bound::Excluded(with_prefix(v)),
+        Bound::Unbounded => Bound::Included(prefix.into()),
+    };
+    let hi = match bounds.end_bound() {
+        Bound::Included(v) => Bound::Included(with_prefix(v)),
+        Bound::Excluded(v) => Bound::Excluded(with_prefix(v)),
+        Bound::Unbounded => prefix_end(prefix)
+            .map(Bound::Excluded)
+            .unwrap_or(Bound::Unbounded),
+    };
+    (lo, hi)
+}
+
+// the smallest bytes which are greater than all the ones starting with `prefix`,
+// `None` if there is no such thing(`prefix` is made of `0xff`)
+pub(crate) fn prefix_end(prefix: &[u8]) -> Option<RawKey> {
+    let idx = prefix.iter().rposition(|b| u8::MAX != *b)?;
+    let mut end = RawKey::from(&prefix[..=idx]);
+    end[idx] += 1;
+    Some(end)
+}
+
+#[inline(always)]
+pub(crate) fn as_slice(b: &Bound<RawKey>) -> Bound<&[u8]> {
+    match b {
+        Bound::Included(b) => Bound::Included(&b[..]),
+        Bound::Excluded(b) => Bound::Excluded(&b[..]),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
@@ -0,0 +1,86 @@
+use super::*;
+use std::collections::BTreeSet;
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr = MultiMapx::new();
+    (0..100u64)
+        .rev()
+        .for_each(|v| assert!(hdr.insert(v % 3, v).unwrap()));
+    assert!(!hdr.insert(0, 0).unwrap());
+    assert_eq!(hdr.len(), 100);
+    assert_eq!(hdr.count(&0), 34);
+    assert!(hdr.get_all(&1).eq((1..100).step_by(3)));
+    assert!(hdr.get_all(&1).rev().eq((1..98).rev().step_by(3)));
+    assert!(hdr.get_all_range(&2, 10..=20).eq([11, 14, 17, 20]));
+    assert!(hdr.contains(&2, &5));
+    assert!(!hdr.contains(&2, &6));
+    assert!(!hdr.contains_key(&3));
+
+    assert!(hdr.remove(&2, &5).unwrap());
+    assert!(!hdr.remove(&2, &5).unwrap());
+    assert_eq!(hdr.count(&2), 32);
+
+    hdr.remove_all(&1);
+    assert!(hdr.get_all(&1).next().is_none());
+    assert_eq!(hdr.count(&0), 34);
+    assert_eq!(hdr.count(&2), 32);
+    assert_eq!(hdr.len(), 66);
+
+    hdr.clear();
+    assert!(hdr.is_empty());
+}
+
+#[test]
+fn test_variable_size_keys() {
+    let model = [
+        (vec![], 3u32),
+        (vec![0u8], 1),
+        (vec![0u8], 2),
+        (vec![0u8, 0], 0),
+        (vec![1u8], 5),
+        (vec![u8::MAX], 7),
+        (vec![u8::MAX, u8::MAX], 9),
+    ]
+    .into_iter()
+    .collect::<BTreeSet<_>>();
+    let hdr = model.iter().cloned().collect::<MultiMapx<Vec<u8>, u32>>();
+
+    assert!(hdr.iter().eq(model.iter().cloned()));
+    for k in model.iter().map(|(k, _)| k) {
+        assert!(
+            hdr.get_all(k)
+                .eq(model.iter().filter(|(kk, _)| kk == k).map(|(_, v)| *v))
+        );
+    }
+    assert!(hdr.get_all(&vec![0u8, 1]).next().is_none());
+}
+
+#[test]
+fn test_paging() {
+    let mut hdr = MultiMapx::new();
+    (0..50u32).for_each(|v| assert!(hdr.insert_ref(&"addr".to_owned(), &v).unwrap()));
+    assert!(hdr.insert("addr0".to_owned(), 100).unwrap());
+
+    let mut pages = vec![];
+    let mut last = None;
+    loop {
+        let page = match last {
+            Some(v) => hdr
+                .get_all_range(
+                    &"addr".to_owned(),
+                    (Bound::Excluded(v), Bound::Unbounded),
+                )
+                .take(8)
+                .collect::<Vec<_>>(),
+            None => hdr.get_all(&"addr".to_owned()).take(8).collect(),
+        };
+        if page.is_empty() {
+            break;
+        }
+        last = page.last().copied();
+        pages.push(page);
+    }
+    assert_eq!(pages.len(), 7);
+    assert!(pages.into_iter().flatten().eq(0..50));
+}
@@ -82,7 +82,15 @@
 #[cfg(test)]
 mod test;
 
-use crate::{basic::mapx_ord_rawkey::MapxOrdRawKey, ValueEnDe};
+use crate::{
+    basic::mapx_ord_rawkey::MapxOrdRawKey,
+    common::{
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+    },
+    ValueEnDe,
+};
+use ruc::*;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
@@ -111,6 +119,10 @@ impl<T> Orphan<T>
 where
     T: ValueEnDe,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -120,7 +132,7 @@ where
 
     pub fn new(v: T) -> Self {
         let mut hdr = MapxOrdRawKey::new();
-        hdr.insert_ref(&[], &v);
+        pnk!(hdr.insert_ref(&[], &v));
         Self { inner: hdr }
     }
 
@@ -129,8 +141,14 @@ where
         self.inner.get(&[]).unwrap()
     }
 
+    /// Same as `get_value`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    pub fn try_get_value(&self) -> VsdbResult<T> {
+        self.inner.try_get(&[]).map(|v| v.unwrap())
+    }
+
     fn set_value_ref(&mut self, v: &T) {
-        self.inner.set_value_ref(&[], v);
+        pnk!(self.inner.set_value_ref(&[], v));
     }
 
     /// Get the mutable handler of the value.
@@ -157,11 +175,18 @@ impl<T> Clone for Orphan<T> {
 impl<T: Default + ValueEnDe> Default for Orphan<T> {
     fn default() -> Self {
         let mut hdr = MapxOrdRawKey::new();
-        hdr.insert_ref(&[], &T::default());
+        pnk!(hdr.insert_ref(&[], &T::default()));
         Self { inner: hdr }
     }
 }
 
+impl<T: ValueEnDe + GcMark> GcMark for Orphan<T> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
 ////////////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////
 
@@ -202,7 +227,7 @@ where
     T: ValueEnDe + Ord,
 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.get_value().partial_cmp(&other.get_value())
+        Some(self.cmp(other))
     }
 }
 
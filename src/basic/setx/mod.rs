@@ -0,0 +1,157 @@
+//!
+//! A `HashSet`-like structure but storing data in disk.
+//!
+//! NOTE:
+//! - Keys will be encoded by some `serde`-like methods
+//!
+//! # Examples
+//!
+//! ```
+//! use vsdb::basic::setx::Setx;
+//!
+//! let dir = format!("/tmp/__vsdb__{}", rand::random::<u128>());
+//! vsdb::vsdb_set_base_dir(&dir);
+//!
+//! let mut l = Setx::new();
+//!
+//! assert!(l.insert(1).unwrap());
+//! assert!(!l.insert(1).unwrap());
+//! assert!(l.contains(&1));
+//!
+//! assert!(l.remove(&1).unwrap());
+//! assert!(!l.contains(&1));
+//! assert!(l.is_empty());
+//! ```
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::mapx::{Mapx, MapxIter},
+    common::{
+        ende::KeyEnDe,
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(bound = "")]
+pub struct Setx<K> {
+    inner: Mapx<K, ()>,
+}
+
+impl<K: KeyEnDe> Setx<K> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        Setx { inner: Mapx::new() }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, key: &K) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Return `true` if the key did not exist before.
+    #[inline(always)]
+    pub fn insert(&mut self, key: K) -> VsdbResult<bool> {
+        self.insert_ref(&key)
+    }
+
+    #[inline(always)]
+    pub fn insert_ref(&mut self, key: &K) -> VsdbResult<bool> {
+        self.inner.insert_ref(key, &()).map(|v| v.is_none())
+    }
+
+    /// Return `true` if the key existed before.
+    #[inline(always)]
+    pub fn remove(&mut self, key: &K) -> VsdbResult<bool> {
+        self.inner.remove(key).map(|v| v.is_some())
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> SetxIter<'_, K> {
+        SetxIter {
+            iter: self.inner.iter(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        self.inner.clear()
+    }
+}
+
+impl<K> Clone for Setx<K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: KeyEnDe> Default for Setx<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyEnDe> Extend<K> for Setx<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        self.inner.extend(iter.into_iter().map(|k| (k, ())));
+    }
+}
+
+impl<K: KeyEnDe> FromIterator<K> for Setx<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl<K> GcMark for Setx<K> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+pub struct SetxIter<'a, K: KeyEnDe> {
+    iter: MapxIter<'a, K, ()>,
+}
+
+impl<'a, K: KeyEnDe> Iterator for SetxIter<'a, K> {
+    type Item = K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K: KeyEnDe> DoubleEndedIterator for SetxIter<'a, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
@@ -0,0 +1,32 @@
+use super::*;
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr = Setx::new();
+    (0..100u32).for_each(|i| assert!(hdr.insert(i).unwrap()));
+    (0..100u32).for_each(|i| assert!(!hdr.insert_ref(&i).unwrap()));
+    assert_eq!(hdr.len(), 100);
+    assert!(hdr.contains(&99));
+    assert!(!hdr.contains(&100));
+
+    (0..50u32).for_each(|i| assert!(hdr.remove(&i).unwrap()));
+    assert!(!hdr.remove(&0).unwrap());
+    assert_eq!(hdr.len(), 50);
+
+    let mut keys = hdr.iter().collect::<Vec<_>>();
+    keys.sort_unstable();
+    assert_eq!(keys, (50..100).collect::<Vec<_>>());
+
+    hdr.clear();
+    assert!(hdr.is_empty());
+}
+
+#[test]
+fn test_collect() {
+    let hdr = ["a", "b", "a"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Setx<String>>();
+    assert_eq!(hdr.len(), 2);
+    assert!(hdr.contains(&"a".to_owned()));
+}
@@ -0,0 +1,335 @@
+//!
+//! A `BTreeSet`-like structure but storing data in disk.
+//!
+//! NOTE:
+//! - Keys will be encoded by `KeyEnDeOrdered`
+//! - It's your duty to ensure that the encoded key keeps a same order with the original key
+//!
+//! # Examples
+//!
+//! ```
+//! use vsdb::basic::setx_ord::SetxOrd;
+//!
+//! let dir = format!("/tmp/__vsdb__{}", rand::random::<u128>());
+//! vsdb::vsdb_set_base_dir(&dir);
+//!
+//! let a = (0..6).collect::<SetxOrd<u32>>();
+//! let b = (3..9).collect::<SetxOrd<u32>>();
+//!
+//! assert!(a.union(&b).eq(0..9));
+//! assert!(a.intersection(&b).eq(3..6));
+//! assert!(a.difference(&b).eq(0..3));
+//! assert!(a.range(2..4).eq(2..4));
+//! ```
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::mapx_ord::{MapxOrd, MapxOrdIter},
+    common::{
+        ende::KeyEnDeOrdered,
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+    },
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, iter::Peekable, ops::RangeBounds};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(bound = "")]
+pub struct SetxOrd<K> {
+    inner: MapxOrd<K, ()>,
+}
+
+impl<K: KeyEnDeOrdered> SetxOrd<K> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        SetxOrd {
+            inner: MapxOrd::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, key: &K) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Return `true` if the key did not exist before.
+    #[inline(always)]
+    pub fn insert(&mut self, key: K) -> VsdbResult<bool> {
+        self.insert_ref(&key)
+    }
+
+    #[inline(always)]
+    pub fn insert_ref(&mut self, key: &K) -> VsdbResult<bool> {
+        self.inner.insert_ref(key, &()).map(|v| v.is_none())
+    }
+
+    /// Return `true` if the key existed before.
+    #[inline(always)]
+    pub fn remove(&mut self, key: &K) -> VsdbResult<bool> {
+        self.inner.remove(key).map(|v| v.is_some())
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> SetxOrdIter<'_, K> {
+        SetxOrdIter {
+            iter: self.inner.iter(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> SetxOrdIter<'_, K> {
+        SetxOrdIter {
+            iter: self.inner.range(bounds),
+        }
+    }
+
+    #[inline(always)]
+    pub fn first(&self) -> Option<K> {
+        self.inner.first().map(|(k, _)| k)
+    }
+
+    #[inline(always)]
+    pub fn last(&self) -> Option<K> {
+        self.inner.last().map(|(k, _)| k)
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        self.inner.clear()
+    }
+
+    /// Re-encode all the keys of a set loaded from the handle of a `MapxOrd<K, ()>`
+    /// created by older versions of VSDB, see `MapxOrd::migrate_legacy_keys`.
+    #[inline(always)]
+    pub fn migrate_legacy_keys(&mut self) -> Result<()> {
+        self.inner.migrate_legacy_keys().c(d!())
+    }
+
+    /// Visit the keys in `self` or `other` in ascending order, without duplicates.
+    #[inline(always)]
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<SetxOrdIter<'a, K>>
+    where
+        K: Ord,
+    {
+        Union::new(self.iter(), other.iter())
+    }
+
+    /// Visit the keys in both `self` and `other` in ascending order.
+    #[inline(always)]
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Intersection<SetxOrdIter<'a, K>>
+    where
+        K: Ord,
+    {
+        Intersection::new(self.iter(), other.iter())
+    }
+
+    /// Visit the keys in `self` but not in `other` in ascending order.
+    #[inline(always)]
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<SetxOrdIter<'a, K>>
+    where
+        K: Ord,
+    {
+        Difference::new(self.iter(), other.iter())
+    }
+}
+
+impl<K> Clone for SetxOrd<K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: KeyEnDeOrdered> Default for SetxOrd<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyEnDeOrdered> Extend<K> for SetxOrd<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        self.inner.extend(iter.into_iter().map(|k| (k, ())));
+    }
+}
+
+impl<K: KeyEnDeOrdered> FromIterator<K> for SetxOrd<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl<K> GcMark for SetxOrd<K> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+pub struct SetxOrdIter<'a, K: KeyEnDeOrdered> {
+    iter: MapxOrdIter<'a, K, ()>,
+}
+
+impl<'a, K: KeyEnDeOrdered> Iterator for SetxOrdIter<'a, K> {
+    type Item = K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K: KeyEnDeOrdered> DoubleEndedIterator for SetxOrdIter<'a, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////
+
+/// A lazy iterator over the union of two ascending key streams,
+/// also used by the versioned sets.
+pub struct Union<I: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<I>,
+}
+
+impl<I: Iterator> Union<I> {
+    pub(crate) fn new(a: I, b: I) -> Self {
+        Self {
+            a: a.peekable(),
+            b: b.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for Union<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        let ord = match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, _) => Ordering::Greater,
+        };
+        match ord {
+            Ordering::Less => self.a.next(),
+            Ordering::Greater => self.b.next(),
+            Ordering::Equal => {
+                self.b.next();
+                self.a.next()
+            }
+        }
+    }
+}
+
+/// A lazy iterator over the intersection of two ascending key streams,
+/// also used by the versioned sets.
+pub struct Intersection<I: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<I>,
+}
+
+impl<I: Iterator> Intersection<I> {
+    pub(crate) fn new(a: I, b: I) -> Self {
+        Self {
+            a: a.peekable(),
+            b: b.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for Intersection<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.a.peek()?.cmp(self.b.peek()?) {
+                Ordering::Less => {
+                    self.a.next();
+                }
+                Ordering::Greater => {
+                    self.b.next();
+                }
+                Ordering::Equal => {
+                    self.b.next();
+                    return self.a.next();
+                }
+            }
+        }
+    }
+}
+
+/// A lazy iterator over the keys of the first ascending key stream
+/// which are not in the second one, also used by the versioned sets.
+pub struct Difference<I: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<I>,
+}
+
+impl<I: Iterator> Difference<I> {
+    pub(crate) fn new(a: I, b: I) -> Self {
+        Self {
+            a: a.peekable(),
+            b: b.peekable(),
+        }
+    }
+}
+
+impl<I> Iterator for Difference<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let a = self.a.peek()?;
+            match self.b.peek().map(|b| a.cmp(b)) {
+                None | Some(Ordering::Less) => return self.a.next(),
+                Some(Ordering::Greater) => {
+                    self.b.next();
+                }
+                Some(Ordering::Equal) => {
+                    self.a.next();
+                    self.b.next();
+                }
+            }
+        }
+    }
+}
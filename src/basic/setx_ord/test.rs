@@ -0,0 +1,62 @@
+use super::*;
+use crate::common::engines::reload_as_legacy;
+use std::collections::BTreeSet;
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr = SetxOrd::new();
+    (0..100i32).rev().for_each(|i| assert!(hdr.insert(i - 50).unwrap()));
+    assert!(!hdr.insert(0).unwrap());
+    assert_eq!(hdr.len(), 100);
+    assert!(hdr.iter().eq(-50..50));
+    assert!(hdr.iter().rev().eq((-50..50).rev()));
+    assert!(hdr.range(-3..=3).eq(-3..=3));
+    assert_eq!(hdr.first(), Some(-50));
+    assert_eq!(hdr.last(), Some(49));
+
+    assert!(hdr.remove(&0).unwrap());
+    assert!(!hdr.remove(&0).unwrap());
+    assert!(!hdr.contains(&0));
+    assert!(hdr.range(-1..2).eq([-1, 1]));
+
+    hdr.clear();
+    assert!(hdr.is_empty());
+    assert!(hdr.first().is_none());
+}
+
+#[test]
+fn test_set_operations() {
+    let a_model = (0..300u64).filter(|i| i % 2 == 0).collect::<BTreeSet<_>>();
+    let b_model = (0..300u64).filter(|i| i % 3 == 0).collect::<BTreeSet<_>>();
+    let a = a_model.iter().copied().collect::<SetxOrd<_>>();
+    let b = b_model.iter().copied().collect::<SetxOrd<_>>();
+    let empty = SetxOrd::new();
+
+    assert!(a.union(&b).eq(a_model.union(&b_model).copied()));
+    assert!(
+        a.intersection(&b)
+            .eq(a_model.intersection(&b_model).copied())
+    );
+    assert!(a.difference(&b).eq(a_model.difference(&b_model).copied()));
+    assert!(b.difference(&a).eq(b_model.difference(&a_model).copied()));
+
+    assert!(a.union(&empty).eq(a_model.iter().copied()));
+    assert!(empty.union(&a).eq(a_model.iter().copied()));
+    assert!(a.intersection(&empty).next().is_none());
+    assert!(a.difference(&empty).eq(a_model.iter().copied()));
+    assert!(empty.difference(&a).next().is_none());
+    assert!(a.difference(&a).next().is_none());
+}
+
+#[test]
+fn test_legacy_keys() {
+    let mut hdr: SetxOrd<i16> = reload_as_legacy(&SetxOrd::new());
+    (-3..3).for_each(|i| {
+        hdr.insert(i).unwrap();
+    });
+    assert!(hdr.contains(&-3));
+    assert_eq!(Some(0), hdr.first());
+
+    hdr.migrate_legacy_keys().unwrap();
+    assert!(hdr.iter().eq(-3..3));
+}
@@ -3,6 +3,8 @@
 //!
 //! NOTE:
 //! - Values will be encoded by some `serde`-like methods
+//! - Positions are kept in a counted B+ tree,
+//!   `insert` and `remove` in the middle cost O(log n)
 //!
 //! # Examples
 //!
@@ -14,18 +16,18 @@
 //!
 //! let mut l = Vecx::new();
 //!
-//! l.push(1);
+//! l.push(1).unwrap();
 //! for i in l.iter() {
 //!     assert_eq!(1, i);
 //! }
 //!
-//! l.pop();
+//! l.pop().unwrap();
 //! assert_eq!(l.len(), 0);
 //!
-//! l.insert(0, 1);
+//! l.insert(0, 1).unwrap();
 //! assert_eq!(l.len(), 1);
 //!
-//! l.clear();
+//! l.clear().unwrap();
 //! assert_eq!(l.len(), 0);
 //! ```
 
@@ -33,201 +35,369 @@
 mod test;
 
 use crate::{
-    basic::mapx_ord_rawkey::{MapxOrdRawKey, MapxOrdRawKeyIter, ValueMut},
-    common::ende::ValueEnDe,
+    basic::{
+        index_tree::{
+            deserialize_vecx, serialize_vecx, IndexTree, IndexTreeIter,
+            INSERT_BATCH_SIZE,
+        },
+        mapx_ord_rawkey::{MapxOrdRawKey, ValueMut},
+    },
+    common::{
+        ende::ValueEnDe,
+        ensure_writable,
+        error::VsdbResult,
+        gc::{GcMark, GcMarks},
+        RawKey,
+    },
 };
 use ruc::*;
-use serde::{Deserialize, Serialize};
-use std::cmp::Ordering;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::result::Result as StdResult;
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
-#[serde(bound = "")]
+// elements are located by a counted B+ tree,
+// and their values are stored with their IDs as keys,
+// so inserting or removing in the middle will not move other values
+#[derive(Debug)]
 pub struct Vecx<T> {
-    inner: MapxOrdRawKey<T>,
+    index: IndexTree,
+    values: MapxOrdRawKey<T>,
 }
 
 impl<T: ValueEnDe> Vecx<T> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
-            inner: self.inner.shadow(),
+            index: self.index.shadow(),
+            values: self.values.shadow(),
         }
     }
 
     #[inline(always)]
     pub fn new() -> Self {
         Vecx {
-            inner: MapxOrdRawKey::new(),
+            index: IndexTree::new(),
+            values: MapxOrdRawKey::new(),
         }
     }
 
     #[inline(always)]
     pub fn get(&self, idx: usize) -> Option<T> {
-        self.inner.get(&(idx as u64).to_be_bytes())
+        let id = self.index.get(idx)?;
+        self.values.get(&id.to_be_bytes())
+    }
+
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, idx: usize) -> VsdbResult<Option<T>> {
+        match self.index.get(idx) {
+            Some(id) => self.values.try_get(&id.to_be_bytes()),
+            None => Ok(None),
+        }
     }
 
     #[inline(always)]
     pub fn get_mut(&mut self, idx: usize) -> Option<ValueMut<'_, T>> {
-        let idx_bytes = (idx as u64).to_be_bytes();
-        self.inner.get(&idx_bytes).map(|v| {
-            ValueMut::new(&mut self.inner, idx_bytes.to_vec().into_boxed_slice(), v)
-        })
+        let id = self.index.get(idx)?;
+        let k = elem_key(id);
+        self.values
+            .get(&k)
+            .map(|v| ValueMut::new(&mut self.values, k, v))
     }
 
     #[inline(always)]
     pub fn last(&self) -> Option<T> {
         alt!(self.is_empty(), return None);
-        Some(
-            self.inner
-                .get(&(self.len() as u64 - 1).to_be_bytes())
-                .unwrap(),
-        )
+        Some(self.get(self.len() - 1).unwrap())
     }
 
     #[inline(always)]
     pub fn len(&self) -> usize {
-        self.inner.len()
+        self.index.len()
     }
 
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
-        self.inner.is_empty()
+        0 == self.len()
     }
 
     #[inline(always)]
-    pub fn push(&mut self, v: T) {
+    pub fn push(&mut self, v: T) -> VsdbResult<()> {
         self.push_ref(&v)
     }
 
     #[inline(always)]
-    pub fn push_ref(&mut self, v: &T) {
-        self.inner.insert_ref(&(self.len() as u64).to_be_bytes(), v);
+    pub fn push_ref(&mut self, v: &T) -> VsdbResult<()> {
+        self.insert_ref(self.len(), v)
     }
 
     #[inline(always)]
-    pub fn insert(&mut self, idx: usize, v: T) {
+    pub fn insert(&mut self, idx: usize, v: T) -> VsdbResult<()> {
         self.insert_ref(idx, &v)
     }
 
+    /// Insert an element at the position `idx`,
+    /// only O(log n) nodes of the index will be rewritten.
     #[inline(always)]
-    pub fn insert_ref(&mut self, idx: usize, v: &T) {
-        let idx = idx as u64;
-        match (self.len() as u64).cmp(&idx) {
-            Ordering::Greater => {
-                self.inner
-                    .range_ref(
-                        &idx.to_be_bytes()[..]..&(self.len() as u64).to_be_bytes()[..],
-                    )
-                    .for_each(|(i, iv)| {
-                        self.inner.insert_ref(
-                            &(crate::parse_int!(i, u64) + 1).to_be_bytes(),
-                            &iv,
-                        );
-                    });
-                self.inner.insert_ref(&idx.to_be_bytes(), v);
-            }
-            Ordering::Equal => {
-                self.push_ref(v);
-            }
-            Ordering::Less => {
-                panic!("out of index");
-            }
+    pub fn insert_ref(&mut self, idx: usize, v: &T) -> VsdbResult<()> {
+        if idx > self.len() {
+            panic!("out of index");
         }
+        ensure_writable()?;
+        let values = &mut self.values;
+        self.index.insert(idx, 1, |id| {
+            pnk!(values.set_value_ref(&id.to_be_bytes(), v));
+        });
+        Ok(())
     }
 
     #[inline(always)]
-    pub fn pop(&mut self) -> Option<T> {
-        alt!(self.is_empty(), return None);
-        self.inner.remove(&(self.len() as u64 - 1).to_be_bytes())
-    }
-
-    #[inline(always)]
-    pub fn remove(&mut self, idx: usize) -> T {
-        let idx = idx as u64;
-        if !self.is_empty() && idx < self.len() as u64 {
-            let last_idx = self.len() as u64 - 1;
-            let ret = self.inner.remove(&idx.to_be_bytes()).unwrap();
-            self.inner
-                .range_ref(&(1 + idx).to_be_bytes()[..]..)
-                .for_each(|(i, v)| {
-                    self.inner
-                        .insert_ref(&(crate::parse_int!(i, u64) - 1).to_be_bytes(), &v);
-                });
-            self.inner.remove(&last_idx.to_be_bytes());
-            return ret;
+    pub fn pop(&mut self) -> VsdbResult<Option<T>> {
+        alt!(self.is_empty(), return Ok(None));
+        self.remove(self.len() - 1).map(Some)
+    }
+
+    /// Remove the element at the position `idx`,
+    /// only O(log n) nodes of the index will be rewritten.
+    #[inline(always)]
+    pub fn remove(&mut self, idx: usize) -> VsdbResult<T> {
+        ensure_writable()?;
+        if let Some(id) = self.index.remove(idx) {
+            return self.values.remove(&id.to_be_bytes()).map(|v| v.unwrap());
         }
         panic!("out of index");
     }
 
     #[inline(always)]
-    pub fn swap_remove(&mut self, idx: usize) -> T {
-        let idx = idx as u64;
-        if !self.is_empty() && idx < self.len() as u64 {
-            let last_idx = self.len() as u64 - 1;
-            let ret = self.inner.remove(&idx.to_be_bytes()).unwrap();
-            if let Some(v) = self.inner.remove(&last_idx.to_be_bytes()) {
-                self.inner.insert_ref(&idx.to_be_bytes(), &v);
-            }
-            return ret;
+    pub fn swap_remove(&mut self, idx: usize) -> VsdbResult<T> {
+        let len = self.len();
+        if idx < len {
+            ensure_writable()?;
+            let last = self.index.remove(len - 1).unwrap();
+            let id = alt!(idx == len - 1, last, self.index.set(idx, last).unwrap());
+            return self.values.remove(&id.to_be_bytes()).map(|v| v.unwrap());
         }
         panic!("out of index");
     }
 
-    pub fn update(&mut self, idx: usize, v: T) -> Option<T> {
+    pub fn update(&mut self, idx: usize, v: T) -> VsdbResult<Option<T>> {
         self.update_ref(idx, &v)
     }
 
     #[inline(always)]
-    pub fn update_ref(&mut self, idx: usize, v: &T) -> Option<T> {
-        if idx < self.len() {
-            return self.inner.insert_ref(&(idx as u64).to_be_bytes(), v);
+    pub fn update_ref(&mut self, idx: usize, v: &T) -> VsdbResult<Option<T>> {
+        if let Some(id) = self.index.get(idx) {
+            return self.values.insert_ref(&id.to_be_bytes(), v);
         }
         panic!("out of index");
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> VecxIter<T> {
+    pub fn iter(&self) -> VecxIter<'_, T> {
         VecxIter {
-            iter: self.inner.iter(),
+            ids: self.index.iter(),
+            values: &self.values,
         }
     }
 
-    // TODO
-    // pub fn iter_mut
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> VecxIterMut<'_, T> {
+        VecxIterMut {
+            ids: self.index.iter(),
+            values: &mut self.values,
+        }
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> VsdbResult<()> {
+        ensure_writable()?;
+        self.values.clear()?;
+        self.index.clear();
+        Ok(())
+    }
 
+    /// Shorten the vector to the first `len` elements,
+    /// no effect if `len` is not less than the current length.
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn truncate(&mut self, len: usize) -> VsdbResult<()> {
+        ensure_writable()?;
+        let removed = self.index.truncate(len);
+        self.values.remove_batch(removed.into_iter().map(elem_key));
+        Ok(())
+    }
+
+    /// Retain only the elements specified by the predicate,
+    /// the index is rebuilt in batches, the kept values are not moved.
+    pub fn retain<F>(&mut self, mut f: F) -> VsdbResult<()>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        ensure_writable()?;
+
+        let mut removed = vec![];
+
+        // the old index will not be changed until the end of the rebuilding
+        let index = unsafe { self.index.shadow() };
+        let values = &self.values;
+        self.index.rebuild(index.iter().filter(|id| {
+            let keep = f(&values.get(&id.to_be_bytes()).unwrap());
+            alt!(!keep, removed.push(*id));
+            keep
+        }));
+
+        self.values.remove_batch(removed.into_iter().map(elem_key));
+        Ok(())
+    }
+
+    /// Split the vector into two at the given index,
+    /// return the elements in the range `[at, len)`.
+    pub fn split_off(&mut self, at: usize) -> VsdbResult<Self> {
+        if at > self.len() {
+            panic!("out of index");
+        }
+        ensure_writable()?;
+        let mut other = Self::new();
+        other.extend(
+            self.index
+                .iter_range(at, self.len())
+                .map(|id| self.values.get(&id.to_be_bytes()).unwrap()),
+        );
+        self.truncate(at)?;
+        Ok(other)
+    }
+
+    /// Move all the elements of `other` into `self`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) -> VsdbResult<()> {
+        ensure_writable()?;
+        if self.values.instance_prefix() == other.values.instance_prefix() {
+            return Ok(());
+        }
+        self.extend(other.iter());
+        other.clear()
     }
 }
 
 impl<T> Clone for Vecx<T> {
     fn clone(&self) -> Self {
         Self {
-            inner: self.inner.clone(),
+            index: self.index.clone(),
+            values: self.values.clone(),
         }
     }
 }
 
+impl<T: ValueEnDe + PartialEq> PartialEq for Vecx<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: ValueEnDe + Eq> Eq for Vecx<T> {}
+
+impl<T> Serialize for Vecx<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        serialize_vecx(&self.index, &self.values, serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Vecx<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+        deserialize_vecx(deserializer).map(|(index, values)| Self { index, values })
+    }
+}
+
 impl<T: ValueEnDe> Default for Vecx<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-pub struct VecxIter<T: ValueEnDe> {
-    iter: MapxOrdRawKeyIter<T>,
+impl<T: ValueEnDe> Extend<T> for Vecx<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().peekable();
+        while iter.peek().is_some() {
+            let batch = iter.by_ref().take(INSERT_BATCH_SIZE).collect::<Vec<_>>();
+            let len = self.len();
+            let values = &mut self.values;
+            self.index.insert(len, batch.len(), |first| {
+                values.extend((first..).map(elem_key).zip(batch));
+            });
+        }
+    }
+}
+
+impl<T: ValueEnDe> FromIterator<T> for Vecx<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl<T: ValueEnDe + GcMark> GcMark for Vecx<T> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.index.gc_mark(marks);
+        self.values.gc_mark(marks);
+    }
+}
+
+#[inline(always)]
+fn elem_key(id: u64) -> RawKey {
+    id.to_be_bytes().to_vec().into_boxed_slice()
 }
 
-impl<T: ValueEnDe> Iterator for VecxIter<T> {
+pub struct VecxIter<'a, T: ValueEnDe> {
+    ids: IndexTreeIter<'a>,
+    values: &'a MapxOrdRawKey<T>,
+}
+
+impl<'a, T: ValueEnDe> Iterator for VecxIter<'a, T> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|v| v.1)
+        self.ids
+            .next()
+            .map(|id| self.values.get(&id.to_be_bytes()).unwrap())
+    }
+}
+
+impl<'a, T: ValueEnDe> DoubleEndedIterator for VecxIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.ids
+            .next_back()
+            .map(|id| self.values.get(&id.to_be_bytes()).unwrap())
+    }
+}
+
+pub struct VecxIterMut<'a, T: ValueEnDe> {
+    ids: IndexTreeIter<'a>,
+    values: &'a mut MapxOrdRawKey<T>,
+}
+
+impl<'a, T: ValueEnDe> VecxIterMut<'a, T> {
+    fn value_mut(&mut self, id: u64) -> ValueMut<'a, T> {
+        let k = elem_key(id);
+        let v = self.values.get(&k).unwrap();
+
+        // each element will be visited only once,
+        // so the write-back handles will never overlap
+        unsafe { ValueMut::new_shadowed(self.values, k, v) }
+    }
+}
+
+impl<'a, T: ValueEnDe> Iterator for VecxIterMut<'a, T> {
+    type Item = ValueMut<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ids.next().map(|id| self.value_mut(id))
     }
 }
 
-impl<T: ValueEnDe> DoubleEndedIterator for VecxIter<T> {
+impl<'a, T: ValueEnDe> DoubleEndedIterator for VecxIterMut<'a, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.iter.next_back().map(|v| v.1)
+        self.ids.next_back().map(|id| self.value_mut(id))
     }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use crate::ValueEn;
 use ruc::*;
 
 #[test]
@@ -49,6 +50,18 @@ fn test_valueende() {
     });
 }
 
+#[test]
+fn test_eq() {
+    let mut hdr: Vecx<usize> = (0..10).collect();
+    let cloned = hdr.clone();
+    assert_eq!(hdr, cloned);
+
+    hdr.insert(3, 100);
+    assert_ne!(hdr, cloned);
+    hdr.remove(3);
+    assert_eq!(hdr, cloned);
+}
+
 #[test]
 fn test_remove() {
     let mut hdr = Vecx::new();
@@ -61,7 +74,7 @@ fn test_remove() {
     assert_eq!(max, hdr.len());
 
     let idx = 400;
-    assert_eq!(max + idx, hdr.remove(idx));
+    assert_eq!(max + idx, hdr.remove(idx).unwrap());
     hdr.clear();
     assert_eq!(0, hdr.len());
 }
@@ -88,7 +101,7 @@ fn test_push_pop() {
         hdr.push(value);
     });
     for val in (0..max).rev() {
-        assert_eq!(val, pnk!(hdr.pop()));
+        assert_eq!(val, pnk!(hdr.pop().unwrap()));
     }
 }
 
@@ -100,7 +113,7 @@ fn test_swap_remove() {
         hdr.push(value);
     });
     for idx in (0..max - 1).rev() {
-        assert_eq!(idx, hdr.swap_remove(idx));
+        assert_eq!(idx, hdr.swap_remove(idx).unwrap());
     }
     assert_eq!(1, hdr.len());
     let value = pnk!(hdr.last());
@@ -180,3 +193,82 @@ fn write_out_of_index_7() {
     hdr.insert(0, 0);
     hdr.swap_remove(100);
 }
+
+// the handles written by older versions store the values with their positions as keys
+#[test]
+fn test_legacy_handle() {
+    #[derive(Serialize)]
+    struct LegacyVecx {
+        inner: MapxOrdRawKey<usize>,
+    }
+
+    let mut values = MapxOrdRawKey::new();
+    (0..10usize).for_each(|i| {
+        values.insert_ref(&(i as u64).to_be_bytes(), &i);
+    });
+    let legacy = LegacyVecx { inner: values };
+
+    let mut hdr = pnk!(<Vecx<usize> as ValueEnDe>::decode(&ValueEn::encode_value(
+        &legacy
+    )));
+    assert_eq!(10, hdr.len());
+    assert!(hdr.iter().eq(0..10));
+    assert!(hdr.iter().rev().eq(hdr.iter().collect::<Vec<_>>().into_iter().rev()));
+
+    // nothing is written before the first change
+    assert_eq!(hdr.encode(), ValueEn::encode_value(&legacy));
+
+    hdr.insert(5, 100);
+    hdr.push(10);
+    assert_eq!(Some(100), hdr.get(5));
+    assert_eq!(Some(10), hdr.last());
+
+    // re-encoded with the current format
+    let reloaded = pnk!(<Vecx<usize> as ValueEnDe>::decode(&hdr.encode()));
+    assert_eq!(hdr, reloaded);
+    assert!(reloaded.iter().eq((0..5).chain([100]).chain(5..11)));
+
+    // the legacy handle saved before uses the built index
+    let load_legacy = || {
+        pnk!(<Vecx<usize> as ValueEnDe>::decode(&ValueEn::encode_value(
+            &legacy
+        )))
+    };
+    assert_eq!(hdr, load_legacy());
+
+    hdr.remove(0).unwrap();
+    assert!(load_legacy().iter().eq((1..5).chain([100]).chain(5..11)));
+
+    hdr.clear().unwrap();
+    hdr.push(1).unwrap();
+    hdr.insert(0, 0).unwrap();
+    assert!(load_legacy().iter().eq(0..2));
+}
+
+#[test]
+fn test_legacy_handle_retain() {
+    #[derive(Serialize)]
+    struct LegacyVecx {
+        inner: MapxOrdRawKey<usize>,
+    }
+
+    let mut values = MapxOrdRawKey::new();
+    (0..10usize).for_each(|i| {
+        values.insert_ref(&(i as u64).to_be_bytes(), &i).unwrap();
+    });
+    let legacy = LegacyVecx { inner: values };
+    let load_legacy = || {
+        pnk!(<Vecx<usize> as ValueEnDe>::decode(&ValueEn::encode_value(
+            &legacy
+        )))
+    };
+
+    // the index is rebuilt by the first write
+    let mut hdr = load_legacy();
+    hdr.retain(|v| 1 == v % 2).unwrap();
+    assert!(hdr.iter().eq((1..10).step_by(2)));
+    assert_eq!(hdr, load_legacy());
+
+    hdr.insert(0, 0).unwrap();
+    assert!(load_legacy().iter().eq([0, 1, 3, 5, 7, 9]));
+}
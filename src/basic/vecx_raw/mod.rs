@@ -3,6 +3,8 @@
 //!
 //! NOTE:
 //! - Values will be encoded by some `serde`-like methods
+//! - Positions are kept in a counted B+ tree,
+//!   `insert` and `remove` in the middle cost O(log n)
 //!
 //! # Examples
 //!
@@ -14,18 +16,18 @@
 //!
 //! let mut l = VecxRaw::new();
 //!
-//! l.push_ref(&1u8.to_be_bytes());
+//! l.push_ref(&1u8.to_be_bytes()).unwrap();
 //! for i in l.iter() {
 //!     assert_eq!(&1u8.to_be_bytes(), &i[..]);
 //! }
 //!
-//! l.pop();
+//! l.pop().unwrap();
 //! assert_eq!(l.len(), 0);
 //!
-//! l.insert_ref(0, &1u8.to_be_bytes());
+//! l.insert_ref(0, &1u8.to_be_bytes()).unwrap();
 //! assert_eq!(l.len(), 1);
 //!
-//! l.clear();
+//! l.clear().unwrap();
 //! assert_eq!(l.len(), 0);
 //! ```
 
@@ -33,159 +35,266 @@
 mod test;
 
 use crate::{
-    basic::mapx_ord_rawvalue::{MapxOrdRawValue, MapxOrdRawValueIter, ValueMut},
-    common::RawValue,
+    basic::{
+        index_tree::{
+            deserialize_vecx, serialize_vecx, IndexTree, IndexTreeIter,
+            INSERT_BATCH_SIZE,
+        },
+        mapx_ord_rawvalue::{MapxOrdRawValue, ValueMut},
+    },
+    common::{
+        ensure_writable,
+        gc::{GcMark, GcMarks},
+        RawValue,
+    },
 };
 use ruc::*;
-use serde::{Deserialize, Serialize};
-use std::cmp::Ordering;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::result::Result as StdResult;
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
-#[serde(bound = "")]
+// elements are located by a counted B+ tree,
+// and their values are stored with their IDs as keys,
+// so inserting or removing in the middle will not move other values
+#[derive(Clone, Debug)]
 pub struct VecxRaw {
-    inner: MapxOrdRawValue<u64>,
+    index: IndexTree,
+    values: MapxOrdRawValue<u64>,
 }
 
 impl VecxRaw {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
-            inner: self.inner.shadow(),
+            index: self.index.shadow(),
+            values: self.values.shadow(),
         }
     }
 
     #[inline(always)]
     pub fn new() -> Self {
         VecxRaw {
-            inner: MapxOrdRawValue::new(),
+            index: IndexTree::new(),
+            values: MapxOrdRawValue::new(),
         }
     }
 
     #[inline(always)]
     pub fn get(&self, idx: usize) -> Option<RawValue> {
-        self.inner.get(&(idx as u64))
+        let id = self.index.get(idx)?;
+        self.values.get(&id)
+    }
+
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, idx: usize) -> Result<Option<RawValue>> {
+        match self.index.get(idx) {
+            Some(id) => self.values.try_get(&id).c(d!()),
+            None => Ok(None),
+        }
     }
 
     #[inline(always)]
     pub fn get_mut(&mut self, idx: usize) -> Option<ValueMut<'_, u64>> {
-        let idx = idx as u64;
-        self.inner
-            .get(&idx)
-            .map(|v| ValueMut::new(&mut self.inner, idx, v))
+        let id = self.index.get(idx)?;
+        self.values
+            .get(&id)
+            .map(|v| ValueMut::new(&mut self.values, id, v))
     }
 
     #[inline(always)]
     pub fn last(&self) -> Option<RawValue> {
         alt!(self.is_empty(), return None);
-        Some(self.inner.get(&(self.len() as u64 - 1)).unwrap())
+        Some(self.get(self.len() - 1).unwrap())
     }
 
     #[inline(always)]
     pub fn len(&self) -> usize {
-        self.inner.len()
+        self.index.len()
     }
 
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
-        self.inner.is_empty()
+        0 == self.len()
     }
 
     #[inline(always)]
-    pub fn push(&mut self, v: RawValue) {
-        self.push_ref(&v)
+    pub fn push(&mut self, v: RawValue) -> Result<()> {
+        self.push_ref(&v).c(d!())
     }
 
     #[inline(always)]
-    pub fn push_ref(&mut self, v: &[u8]) {
-        self.inner.insert_ref(&(self.len() as u64), v);
+    pub fn push_ref(&mut self, v: &[u8]) -> Result<()> {
+        self.insert_ref(self.len(), v).c(d!())
     }
 
     #[inline(always)]
-    pub fn insert(&mut self, idx: usize, v: RawValue) {
-        self.insert_ref(idx, &v)
+    pub fn insert(&mut self, idx: usize, v: RawValue) -> Result<()> {
+        self.insert_ref(idx, &v).c(d!())
     }
 
+    /// Insert an element at the position `idx`,
+    /// only O(log n) nodes of the index will be rewritten.
     #[inline(always)]
-    pub fn insert_ref(&mut self, idx: usize, v: &[u8]) {
-        let idx = idx as u64;
-        match (self.len() as u64).cmp(&idx) {
-            Ordering::Greater => {
-                self.inner
-                    .range_ref(&idx..&(self.len() as u64))
-                    .for_each(|(i, iv)| {
-                        self.inner.insert_ref(&(i + 1), &iv);
-                    });
-                self.inner.insert_ref(&idx, v);
-            }
-            Ordering::Equal => {
-                self.push_ref(v);
-            }
-            Ordering::Less => {
-                panic!("out of index");
-            }
+    pub fn insert_ref(&mut self, idx: usize, v: &[u8]) -> Result<()> {
+        if idx > self.len() {
+            panic!("out of index");
         }
+        ensure_writable().c(d!())?;
+        let values = &mut self.values;
+        self.index.insert(idx, 1, |id| {
+            pnk!(values.set_value_ref(&id, v));
+        });
+        Ok(())
     }
 
     #[inline(always)]
-    pub fn pop(&mut self) -> Option<RawValue> {
-        alt!(self.is_empty(), return None);
-        self.inner.remove(&(self.len() as u64 - 1))
+    pub fn pop(&mut self) -> Result<Option<RawValue>> {
+        alt!(self.is_empty(), return Ok(None));
+        self.remove(self.len() - 1).c(d!()).map(Some)
     }
 
+    /// Remove the element at the position `idx`,
+    /// only O(log n) nodes of the index will be rewritten.
     #[inline(always)]
-    pub fn remove(&mut self, idx: usize) -> RawValue {
-        let idx = idx as u64;
-        if !self.is_empty() && idx < self.len() as u64 {
-            let last_idx = self.len() as u64 - 1;
-            let ret = self.inner.remove(&idx).unwrap();
-            self.inner.range_ref(&(1 + idx)..).for_each(|(i, v)| {
-                self.inner.insert_ref(&(i - 1), &v);
-            });
-            self.inner.remove(&last_idx);
-            return ret;
+    pub fn remove(&mut self, idx: usize) -> Result<RawValue> {
+        ensure_writable().c(d!())?;
+        if let Some(id) = self.index.remove(idx) {
+            return self.values.remove(&id).c(d!()).map(|v| v.unwrap());
         }
         panic!("out of index");
     }
 
     #[inline(always)]
-    pub fn swap_remove(&mut self, idx: usize) -> RawValue {
-        let idx = idx as u64;
-        if !self.is_empty() && idx < self.len() as u64 {
-            let last_idx = self.len() as u64 - 1;
-            let ret = self.inner.remove(&idx).unwrap();
-            if let Some(v) = self.inner.remove(&last_idx) {
-                self.inner.insert_ref(&idx, &v);
-            }
-            return ret;
+    pub fn swap_remove(&mut self, idx: usize) -> Result<RawValue> {
+        let len = self.len();
+        if idx < len {
+            ensure_writable().c(d!())?;
+            let last = self.index.remove(len - 1).unwrap();
+            let id = alt!(idx == len - 1, last, self.index.set(idx, last).unwrap());
+            return self.values.remove(&id).c(d!()).map(|v| v.unwrap());
         }
         panic!("out of index");
     }
 
-    pub fn update(&mut self, idx: usize, v: RawValue) -> Option<RawValue> {
-        self.update_ref(idx, &v)
+    pub fn update(&mut self, idx: usize, v: RawValue) -> Result<Option<RawValue>> {
+        self.update_ref(idx, &v).c(d!())
     }
 
     #[inline(always)]
-    pub fn update_ref(&mut self, idx: usize, v: &[u8]) -> Option<RawValue> {
-        if idx < self.len() {
-            return self.inner.insert_ref(&(idx as u64), v);
+    pub fn update_ref(&mut self, idx: usize, v: &[u8]) -> Result<Option<RawValue>> {
+        if let Some(id) = self.index.get(idx) {
+            return self.values.insert_ref(&id, v).c(d!());
         }
         panic!("out of index");
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> VecxRawIter {
+    pub fn iter(&self) -> VecxRawIter<'_> {
         VecxRawIter {
-            iter: self.inner.iter(),
+            ids: self.index.iter(),
+            values: &self.values,
         }
     }
 
-    // TODO
-    // pub fn iter_mut
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> VecxRawIterMut<'_> {
+        VecxRawIterMut {
+            ids: self.index.iter(),
+            values: &mut self.values,
+        }
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) -> Result<()> {
+        ensure_writable().c(d!())?;
+        self.values.clear().c(d!())?;
+        self.index.clear();
+        Ok(())
+    }
 
+    /// Shorten the vector to the first `len` elements,
+    /// no effect if `len` is not less than the current length.
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn truncate(&mut self, len: usize) -> Result<()> {
+        ensure_writable().c(d!())?;
+        let removed = self.index.truncate(len);
+        self.values.remove_batch(removed);
+        Ok(())
+    }
+
+    /// Retain only the elements specified by the predicate,
+    /// the index is rebuilt in batches, the kept values are not moved.
+    pub fn retain<F>(&mut self, mut f: F) -> Result<()>
+    where
+        F: FnMut(&[u8]) -> bool,
+    {
+        ensure_writable().c(d!())?;
+
+        let mut removed = vec![];
+
+        // the old index will not be changed until the end of the rebuilding
+        let index = unsafe { self.index.shadow() };
+        let values = &self.values;
+        self.index.rebuild(index.iter().filter(|id| {
+            let keep = f(&values.get(id).unwrap());
+            alt!(!keep, removed.push(*id));
+            keep
+        }));
+
+        self.values.remove_batch(removed);
+        Ok(())
+    }
+
+    /// Split the vector into two at the given index,
+    /// return the elements in the range `[at, len)`.
+    pub fn split_off(&mut self, at: usize) -> Result<Self> {
+        if at > self.len() {
+            panic!("out of index");
+        }
+        ensure_writable().c(d!())?;
+        let mut other = Self::new();
+        other.extend(
+            self.index
+                .iter_range(at, self.len())
+                .map(|id| self.values.get(&id).unwrap()),
+        );
+        self.truncate(at).c(d!())?;
+        Ok(other)
+    }
+
+    /// Move all the elements of `other` into `self`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) -> Result<()> {
+        ensure_writable().c(d!())?;
+        if self.values.instance_prefix() == other.values.instance_prefix() {
+            return Ok(());
+        }
+        self.extend(other.iter());
+        other.clear().c(d!())
+    }
+}
+
+impl PartialEq for VecxRaw {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for VecxRaw {}
+
+impl Serialize for VecxRaw {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        serialize_vecx(&self.index, &self.values, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for VecxRaw {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+        deserialize_vecx(deserializer).map(|(index, values)| Self { index, values })
     }
 }
 
@@ -195,19 +304,78 @@ impl Default for VecxRaw {
     }
 }
 
-pub struct VecxRawIter {
-    iter: MapxOrdRawValueIter<u64>,
+impl Extend<RawValue> for VecxRaw {
+    fn extend<I: IntoIterator<Item = RawValue>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().peekable();
+        while iter.peek().is_some() {
+            let batch = iter.by_ref().take(INSERT_BATCH_SIZE).collect::<Vec<_>>();
+            let len = self.len();
+            let values = &mut self.values;
+            self.index.insert(len, batch.len(), |first| {
+                values.extend((first..).zip(batch));
+            });
+        }
+    }
+}
+
+impl FromIterator<RawValue> for VecxRaw {
+    fn from_iter<I: IntoIterator<Item = RawValue>>(iter: I) -> Self {
+        let mut hdr = Self::new();
+        hdr.extend(iter);
+        hdr
+    }
+}
+
+impl GcMark for VecxRaw {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.index.gc_mark(marks);
+        self.values.gc_mark(marks);
+    }
+}
+
+pub struct VecxRawIter<'a> {
+    ids: IndexTreeIter<'a>,
+    values: &'a MapxOrdRawValue<u64>,
 }
 
-impl Iterator for VecxRawIter {
+impl<'a> Iterator for VecxRawIter<'a> {
     type Item = RawValue;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|v| v.1)
+        self.ids.next().map(|id| self.values.get(&id).unwrap())
+    }
+}
+
+impl<'a> DoubleEndedIterator for VecxRawIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.ids.next_back().map(|id| self.values.get(&id).unwrap())
+    }
+}
+
+pub struct VecxRawIterMut<'a> {
+    ids: IndexTreeIter<'a>,
+    values: &'a mut MapxOrdRawValue<u64>,
+}
+
+impl<'a> VecxRawIterMut<'a> {
+    fn value_mut(&mut self, id: u64) -> ValueMut<'a, u64> {
+        let v = self.values.get(&id).unwrap();
+
+        // each element will be visited only once,
+        // so the write-back handles will never overlap
+        unsafe { ValueMut::new_shadowed(self.values, id, v) }
+    }
+}
+
+impl<'a> Iterator for VecxRawIterMut<'a> {
+    type Item = ValueMut<'a, u64>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ids.next().map(|id| self.value_mut(id))
     }
 }
 
-impl DoubleEndedIterator for VecxRawIter {
+impl<'a> DoubleEndedIterator for VecxRawIterMut<'a> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.iter.next_back().map(|v| v.1)
+        self.ids.next_back().map(|id| self.value_mut(id))
     }
 }
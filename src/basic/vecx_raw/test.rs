@@ -1,5 +1,5 @@
 use super::*;
-use crate::ValueEnDe;
+use crate::{ValueEn, ValueEnDe};
 use ruc::*;
 
 fn gen_sample(idx: usize) -> Box<[u8]> {
@@ -70,7 +70,7 @@ fn test_remove() {
     assert_eq!(max, hdr.len());
 
     let idx = 400;
-    let val = pnk!(<usize as ValueEnDe>::decode(&hdr.remove(idx)));
+    let val = pnk!(<usize as ValueEnDe>::decode(&hdr.remove(idx).unwrap()));
     assert_eq!(max + idx, val);
     hdr.clear();
     assert_eq!(0, hdr.len());
@@ -104,7 +104,7 @@ fn test_push_pop() {
             hdr.push(value);
         });
     for idx in (0..max).rev() {
-        let val = pnk!(<usize as ValueEnDe>::decode(&pnk!(hdr.pop())));
+        let val = pnk!(<usize as ValueEnDe>::decode(&pnk!(hdr.pop().unwrap())));
         assert_eq!(idx, val);
     }
 }
@@ -119,7 +119,7 @@ fn test_swap_remove() {
             hdr.push(value);
         });
     for idx in (0..max - 1).rev() {
-        let val = pnk!(<usize as ValueEnDe>::decode(&hdr.swap_remove(idx)));
+        let val = pnk!(<usize as ValueEnDe>::decode(&hdr.swap_remove(idx).unwrap()));
         assert_eq!(val, idx);
     }
     assert_eq!(1, hdr.len());
@@ -204,3 +204,78 @@ fn write_out_of_index_7() {
     hdr.insert(0, gen_sample(0));
     hdr.swap_remove(100);
 }
+
+// the handles written by older versions store the values with their positions as keys
+#[test]
+fn test_legacy_handle() {
+    #[derive(Serialize)]
+    struct LegacyVecxRaw {
+        inner: MapxOrdRawValue<u64>,
+    }
+
+    let mut values = MapxOrdRawValue::new();
+    (0..10).for_each(|i| {
+        values.insert_ref(&(i as u64), &gen_sample(i));
+    });
+    let legacy = LegacyVecxRaw { inner: values };
+
+    let mut hdr = pnk!(<VecxRaw as ValueEnDe>::decode(&ValueEn::encode_value(
+        &legacy
+    )));
+    assert_eq!(10, hdr.len());
+    assert!(hdr.iter().eq((0..10).map(gen_sample)));
+    assert!(hdr.iter().rev().eq(hdr.iter().collect::<Vec<_>>().into_iter().rev()));
+
+    // nothing is written before the first change
+    assert_eq!(hdr.encode(), ValueEn::encode_value(&legacy));
+
+    hdr.remove(0);
+    hdr.push(gen_sample(10));
+
+    // re-encoded with the current format
+    let reloaded = pnk!(<VecxRaw as ValueEnDe>::decode(&hdr.encode()));
+    assert_eq!(hdr, reloaded);
+    assert!(reloaded.iter().eq((1..11).map(gen_sample)));
+
+    // the legacy handle saved before uses the built index
+    let load_legacy = || {
+        pnk!(<VecxRaw as ValueEnDe>::decode(&ValueEn::encode_value(&legacy)))
+    };
+    assert_eq!(hdr, load_legacy());
+
+    hdr.insert(5, gen_sample(100)).unwrap();
+    assert!(load_legacy()
+        .iter()
+        .eq((1..6).chain([100]).chain(6..11).map(gen_sample)));
+
+    hdr.clear().unwrap();
+    hdr.push(gen_sample(1)).unwrap();
+    hdr.insert(0, gen_sample(0)).unwrap();
+    assert!(load_legacy().iter().eq((0..2).map(gen_sample)));
+}
+
+#[test]
+fn test_legacy_handle_retain() {
+    #[derive(Serialize)]
+    struct LegacyVecxRaw {
+        inner: MapxOrdRawValue<u64>,
+    }
+
+    let mut values = MapxOrdRawValue::new();
+    (0..10).for_each(|i| {
+        values.insert_ref(&(i as u64), &gen_sample(i)).unwrap();
+    });
+    let legacy = LegacyVecxRaw { inner: values };
+    let load_legacy = || {
+        pnk!(<VecxRaw as ValueEnDe>::decode(&ValueEn::encode_value(&legacy)))
+    };
+
+    // the index is rebuilt by the first write
+    let mut hdr = load_legacy();
+    hdr.retain(|v| v[v.len() - 1] % 2 == 1).unwrap();
+    assert!(hdr.iter().eq((1..10).step_by(2).map(gen_sample)));
+    assert_eq!(hdr, load_legacy());
+
+    hdr.insert(0, gen_sample(0)).unwrap();
+    assert!(load_legacy().iter().eq([0, 1, 3, 5, 7, 9].map(gen_sample)));
+}
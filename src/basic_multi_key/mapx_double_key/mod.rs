@@ -10,7 +10,11 @@ mod test;
 
 use crate::{
     basic_multi_key::mapx_raw::MapxRawMk,
-    common::ende::{KeyEnDe, ValueEnDe},
+    common::{
+        ende::{KeyEnDe, ValueEnDe},
+        entry::{Entry, EntryHdr},
+        gc::{GcMark, GcMarks},
+    },
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -35,6 +39,10 @@ where
     K2: KeyEnDe,
     V: ValueEnDe,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -78,12 +86,17 @@ where
         self.inner.is_empty()
     }
 
+    #[inline(always)]
+    pub fn entry(&mut self, key: (K1, K2)) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
     #[inline(always)]
     pub fn entry_ref<'a>(
         &'a mut self,
         key: &'a (&'a K1, &'a K2),
-    ) -> Entry<'a, K1, K2, V> {
-        Entry { key, hdr: self }
+    ) -> EntryRef<'a, K1, K2, V> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
@@ -110,8 +123,8 @@ where
     }
 
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn clear(&mut self) -> Result<()> {
+        self.inner.clear().c(d!())
     }
 
     #[inline(always)]
@@ -119,11 +132,90 @@ where
         self.inner.key_size()
     }
 
-    // TODO
-    // pub fn iter_op
-    // pub fn iter_op_with_key_prefix
-    // pub fn iter_mut_op
-    // pub fn iter_mut_op_with_key_prefix
+    #[inline(always)]
+    pub fn iter_op<F>(&self, op: &mut F) -> Result<()>
+    where
+        F: FnMut((K1, K2), V) -> Result<()>,
+    {
+        self.iter_op_with_key_prefix_inner(op, &[]).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn iter_op_with_key_prefix<F>(&self, op: &mut F, key_prefix: &K1) -> Result<()>
+    where
+        F: FnMut((K1, K2), V) -> Result<()>,
+    {
+        self.iter_op_with_key_prefix_inner(op, &[&key_prefix.encode()[..]])
+            .c(d!())
+    }
+
+    fn iter_op_with_key_prefix_inner<F>(
+        &self,
+        op: &mut F,
+        key_prefix: &[&[u8]],
+    ) -> Result<()>
+    where
+        F: FnMut((K1, K2), V) -> Result<()>,
+    {
+        let mut cb = |k: &[&[u8]], v: &[u8]| -> Result<()> {
+            if KEY_SIZE != k.len() {
+                return Err(eg!("key size mismatch"));
+            }
+            let k1 = KeyEnDe::decode(k[0]).c(d!())?;
+            let k2 = KeyEnDe::decode(k[1]).c(d!())?;
+            let v = ValueEnDe::decode(v).c(d!())?;
+            op((k1, k2), v).c(d!())
+        };
+
+        self.inner
+            .iter_op_with_key_prefix(&mut cb, key_prefix)
+            .c(d!())
+    }
+
+    /// The changed values will be written back after each call of the `op`.
+    #[inline(always)]
+    pub fn iter_mut_op<F>(&mut self, op: &mut F) -> Result<()>
+    where
+        F: FnMut((K1, K2), &mut V) -> Result<()>,
+    {
+        self.iter_mut_op_with_key_prefix_inner(op, &[]).c(d!())
+    }
+
+    /// The changed values will be written back after each call of the `op`.
+    #[inline(always)]
+    pub fn iter_mut_op_with_key_prefix<F>(
+        &mut self,
+        op: &mut F,
+        key_prefix: &K1,
+    ) -> Result<()>
+    where
+        F: FnMut((K1, K2), &mut V) -> Result<()>,
+    {
+        self.iter_mut_op_with_key_prefix_inner(op, &[&key_prefix.encode()[..]])
+            .c(d!())
+    }
+
+    fn iter_mut_op_with_key_prefix_inner<F>(
+        &mut self,
+        op: &mut F,
+        key_prefix: &[&[u8]],
+    ) -> Result<()>
+    where
+        F: FnMut((K1, K2), &mut V) -> Result<()>,
+    {
+        let mut cb = |k: &[&[u8]], v: &mut V| -> Result<()> {
+            if KEY_SIZE != k.len() {
+                return Err(eg!("key size mismatch"));
+            }
+            let k1 = KeyEnDe::decode(k[0]).c(d!())?;
+            let k2 = KeyEnDe::decode(k[1]).c(d!())?;
+            op((k1, k2), v).c(d!())
+        };
+
+        self.inner
+            .iter_mut_op_typed_value_with_key_prefix(&mut cb, key_prefix)
+            .c(d!())
+    }
 }
 
 impl<K1, K2, V> Clone for MapxDk<K1, K2, V> {
@@ -146,6 +238,38 @@ where
     }
 }
 
+impl<K1, K2, V> EntryHdr for MapxDk<K1, K2, V>
+where
+    K1: KeyEnDe,
+    K2: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Key = (K1, K2);
+    type Value = V;
+
+    fn entry_get(&self, key: &(K1, K2)) -> Option<V> {
+        self.get(&(&key.0, &key.1))
+    }
+
+    fn entry_set(&mut self, key: &(K1, K2), value: &V) {
+        self.insert(&(&key.0, &key.1), value);
+    }
+
+    fn entry_remove(&mut self, key: &(K1, K2)) {
+        self.remove(&(&key.0, Some(&key.1)));
+    }
+}
+
+impl<K1, K2, V> GcMark for MapxDk<K1, K2, V>
+where
+    V: ValueEnDe + GcMark,
+{
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark_with_typed_value::<V>(marks);
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct ValueMut<'a, K1, K2, V>
 where
@@ -203,12 +327,12 @@ where
     }
 }
 
-pub struct Entry<'a, K1, K2, V> {
+pub struct EntryRef<'a, K1, K2, V> {
     hdr: &'a mut MapxDk<K1, K2, V>,
     key: &'a (&'a K1, &'a K2),
 }
 
-impl<'a, K1, K2, V> Entry<'a, K1, K2, V>
+impl<'a, K1, K2, V> EntryRef<'a, K1, K2, V>
 where
     K1: KeyEnDe,
     K2: KeyEnDe,
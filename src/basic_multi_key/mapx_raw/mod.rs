@@ -10,7 +10,13 @@ mod test;
 
 use crate::{
     basic::mapx_raw::MapxRaw,
-    common::{ende::ValueEnDe, RawValue},
+    common::{
+        ende::ValueEnDe,
+        entry::{Entry, EntryHdr},
+        ensure_writable,
+        gc::{GcMark, GcMarks},
+        RawKey, RawValue,
+    },
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -27,6 +33,10 @@ pub struct MapxRawMk {
 }
 
 impl MapxRawMk {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -52,7 +62,7 @@ impl MapxRawMk {
             return None;
         }
 
-        let mut hdr = self.inner;
+        let mut hdr = unsafe { self.inner.shadow() };
         for (idx, k) in key.iter().enumerate() {
             if let Some(v) = hdr.get(k) {
                 if 1 + idx == self.key_size {
@@ -84,8 +94,13 @@ impl MapxRawMk {
     }
 
     #[inline(always)]
-    pub fn entry_ref<'a>(&'a mut self, key: &'a [&'a [u8]]) -> Entry<'a> {
-        Entry { key, hdr: self }
+    pub fn entry(&mut self, key: Vec<RawKey>) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
+    #[inline(always)]
+    pub fn entry_ref<'a>(&'a mut self, key: &'a [&'a [u8]]) -> EntryRef<'a> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
@@ -93,13 +108,14 @@ impl MapxRawMk {
         if key.len() != self.key_size {
             return Err(eg!("Incorrect key size"));
         }
+        ensure_writable().c(d!())?;
 
         let mut ret = None;
 
-        let mut hdr = self.inner;
+        let mut hdr = unsafe { self.inner.shadow() };
         for (idx, k) in key.iter().enumerate() {
             if 1 + idx == self.key_size {
-                ret = hdr.insert(k, value);
+                ret = hdr.insert(k, value).c(d!())?;
                 break;
             } else {
                 let mut new_hdr = None;
@@ -128,13 +144,14 @@ impl MapxRawMk {
         if key.len() > self.key_size {
             return Err(eg!("Incorrect key size"));
         }
+        ensure_writable().c(d!())?;
 
-        let mut hdr = self.inner;
+        let mut hdr = unsafe { self.inner.shadow() };
         for (idx, k) in key.iter().enumerate() {
             if let Some(v) = hdr.get(k) {
                 // NOTE: use `key.len()` instead of `self.key_size`
                 if 1 + idx == key.len() {
-                    let ret = hdr.remove(k);
+                    let ret = hdr.remove(k).c(d!())?;
                     // NOTE: use `self.key_size` instead of `key.len()`
                     if 1 + idx == self.key_size {
                         return Ok(ret);
@@ -153,8 +170,8 @@ impl MapxRawMk {
     }
 
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn clear(&mut self) -> Result<()> {
+        self.inner.clear().c(d!())
     }
 
     #[inline(always)]
@@ -179,8 +196,8 @@ impl MapxRawMk {
     where
         F: FnMut(&[&[u8]], &[u8]) -> Result<()>,
     {
-        let mut key_buf = vec![&[][..]; self.key_size()];
-        let mut hdr = self.inner;
+        let mut key_buf = vec![RawKey::default(); self.key_size()];
+        let mut hdr = unsafe { self.inner.shadow() };
         let mut depth = self.key_size();
 
         if self.key_size < key_prefix.len() {
@@ -188,7 +205,7 @@ impl MapxRawMk {
         } else {
             for (idx, k) in key_prefix.iter().enumerate() {
                 if let Some(v) = hdr.get(k) {
-                    key_buf[idx] = &k[..];
+                    key_buf[idx] = k.to_vec().into_boxed_slice();
                     if 1 + idx == self.key_size {
                         let key = key_buf
                             .iter()
@@ -213,7 +230,7 @@ impl MapxRawMk {
     fn recursive_walk<F>(
         &self,
         hdr: MapxRaw,
-        key_buf: &mut [&[u8]],
+        key_buf: &mut [RawKey],
         depth: usize,
         op: &mut F,
     ) -> Result<()>
@@ -260,8 +277,8 @@ impl MapxRawMk {
         F: FnMut(&[&[u8]], &V) -> Result<()>,
         V: ValueEnDe,
     {
-        let mut key_buf = vec![&[][..]; self.key_size()];
-        let mut hdr = self.inner;
+        let mut key_buf = vec![RawKey::default(); self.key_size()];
+        let mut hdr = unsafe { self.inner.shadow() };
         let mut depth = self.key_size();
 
         if self.key_size < key_prefix.len() {
@@ -269,7 +286,7 @@ impl MapxRawMk {
         } else {
             for (idx, k) in key_prefix.iter().enumerate() {
                 if let Some(v) = hdr.get(k) {
-                    key_buf[idx] = k;
+                    key_buf[idx] = k.to_vec().into_boxed_slice();
                     if 1 + idx == self.key_size {
                         let key = key_buf
                             .iter()
@@ -294,7 +311,7 @@ impl MapxRawMk {
     fn recursive_walk_typed_value<V, F>(
         &self,
         hdr: MapxRaw,
-        key_buf: &mut [&[u8]],
+        key_buf: &mut [RawKey],
         depth: usize,
         op: &mut F,
     ) -> Result<()>
@@ -324,11 +341,185 @@ impl MapxRawMk {
         Ok(())
     }
 
-    // TODO
-    // pub fn iter_mut_op
-    // pub fn iter_mut_op_with_key_prefix
-    // pub fn iter_mut_op_typed_value
-    // pub fn iter_mut_op_typed_value_with_key_prefix
+    /// The changed values will be written back after each call of the `op`.
+    #[inline(always)]
+    pub fn iter_mut_op<F>(&mut self, op: &mut F) -> Result<()>
+    where
+        F: FnMut(&[&[u8]], &mut RawValue) -> Result<()>,
+    {
+        self.iter_mut_op_with_key_prefix(op, &[]).c(d!())
+    }
+
+    /// The changed values will be written back after each call of the `op`.
+    #[inline(always)]
+    pub fn iter_mut_op_with_key_prefix<F>(
+        &mut self,
+        op: &mut F,
+        key_prefix: &[&[u8]],
+    ) -> Result<()>
+    where
+        F: FnMut(&[&[u8]], &mut RawValue) -> Result<()>,
+    {
+        let mut key_buf = vec![RawKey::default(); self.key_size()];
+        let mut hdr = unsafe { self.inner.shadow() };
+        let mut depth = self.key_size();
+
+        if self.key_size < key_prefix.len() {
+            return Err(eg!("Invalid key size"));
+        } else {
+            for (idx, k) in key_prefix.iter().enumerate() {
+                if let Some(mut v) = hdr.get(k) {
+                    key_buf[idx] = k.to_vec().into_boxed_slice();
+                    if 1 + idx == self.key_size {
+                        let key = key_buf
+                            .iter()
+                            .map(|sub_k| sub_k.as_ref())
+                            .collect::<Vec<_>>();
+                        op(key.as_slice(), &mut v).c(d!())?;
+                        hdr.insert(k, &v).c(d!())?;
+                        return Ok(());
+                    } else {
+                        hdr = pnk!(ValueEnDe::decode(&v));
+                        depth -= 1;
+                    }
+                } else {
+                    // key-prefix does not exist
+                    return Ok(());
+                }
+            }
+        };
+
+        self.recursive_walk_mut(hdr, key_buf.as_mut_slice(), depth, op)
+            .c(d!())
+    }
+
+    fn recursive_walk_mut<F>(
+        &self,
+        mut hdr: MapxRaw,
+        key_buf: &mut [RawKey],
+        depth: usize,
+        op: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(&[&[u8]], &mut RawValue) -> Result<()>,
+    {
+        let idx = self.key_size() - depth;
+        if 1 == depth {
+            for (k, mut v) in hdr.iter_mut() {
+                key_buf[idx] = k;
+                let key = key_buf
+                    .iter()
+                    .map(|sub_k| sub_k.as_ref())
+                    .collect::<Vec<_>>();
+                op(key.as_slice(), &mut v).c(d!())?;
+            }
+        } else {
+            for (k, v) in hdr.iter() {
+                key_buf[idx] = k;
+                let hdr = pnk!(ValueEnDe::decode(&v));
+                self.recursive_walk_mut(hdr, key_buf, depth - 1, op)
+                    .c(d!())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub(super) fn iter_mut_op_typed_value<V, F>(&mut self, op: &mut F) -> Result<()>
+    where
+        F: FnMut(&[&[u8]], &mut V) -> Result<()>,
+        V: ValueEnDe,
+    {
+        self.iter_mut_op_typed_value_with_key_prefix(op, &[])
+            .c(d!())
+    }
+
+    #[inline(always)]
+    pub fn iter_mut_op_typed_value_with_key_prefix<V, F>(
+        &mut self,
+        op: &mut F,
+        key_prefix: &[&[u8]],
+    ) -> Result<()>
+    where
+        F: FnMut(&[&[u8]], &mut V) -> Result<()>,
+        V: ValueEnDe,
+    {
+        let mut cb = |k: &[&[u8]], v: &mut RawValue| -> Result<()> {
+            let mut value = <V as ValueEnDe>::decode(v).c(d!())?;
+            op(k, &mut value).c(d!())?;
+            *v = value.encode();
+            Ok(())
+        };
+
+        self.iter_mut_op_with_key_prefix(&mut cb, key_prefix)
+            .c(d!())
+    }
+
+    #[inline(always)]
+    pub(crate) fn gc_is_marked(&self, marks: &GcMarks) -> bool {
+        self.inner.gc_is_marked(marks)
+    }
+
+    // mark the instance itself, and those nested in the typed values
+    pub(crate) fn gc_mark_with_typed_value<V>(&self, marks: &mut GcMarks)
+    where
+        V: ValueEnDe + GcMark,
+    {
+        if self.gc_is_marked(marks) {
+            return;
+        }
+
+        self.gc_mark(marks);
+
+        if !V::gc_nope() {
+            pnk!(self.iter_op_typed_value(&mut |_, v: &V| {
+                v.gc_mark(marks);
+                Ok(())
+            }));
+        }
+    }
+
+    // all non-leaf values are encoded `MapxRaw`s
+    fn gc_mark_nested(hdr: &MapxRaw, depth: usize, marks: &mut GcMarks) {
+        if 1 < depth {
+            for (_, v) in hdr.iter() {
+                let hdr: MapxRaw = pnk!(ValueEnDe::decode(&v));
+                if hdr.gc_mark_instance(marks) {
+                    Self::gc_mark_nested(&hdr, depth - 1, marks);
+                }
+            }
+        }
+    }
+}
+
+impl GcMark for MapxRawMk {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        if self.inner.gc_mark_instance(marks) {
+            Self::gc_mark_nested(&self.inner, self.key_size, marks);
+        }
+    }
+}
+
+impl EntryHdr for MapxRawMk {
+    type Key = Vec<RawKey>;
+    type Value = RawValue;
+
+    fn entry_get(&self, key: &Vec<RawKey>) -> Option<RawValue> {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        self.get(&key)
+    }
+
+    fn entry_set(&mut self, key: &Vec<RawKey>, value: &RawValue) {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        pnk!(self.insert(&key, value));
+    }
+
+    fn entry_remove(&mut self, key: &Vec<RawKey>) {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        pnk!(self.remove(&key));
+    }
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -363,12 +554,12 @@ impl<'a> DerefMut for ValueMut<'a> {
     }
 }
 
-pub struct Entry<'a> {
+pub struct EntryRef<'a> {
     hdr: &'a mut MapxRawMk,
     key: &'a [&'a [u8]],
 }
 
-impl<'a> Entry<'a> {
+impl<'a> EntryRef<'a> {
     pub fn or_insert_ref(self, default: &'a [u8]) -> Result<ValueMut<'a>> {
         if !self.hdr.contains_key(self.key) {
             self.hdr.insert(self.key, default).c(d!())?;
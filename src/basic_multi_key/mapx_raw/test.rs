@@ -84,7 +84,7 @@ fn test_iter_op_with_key_prefix() {
     );
     assert_eq!(
         map.get(&[&[11], &[12], &[13], &[14]]).unwrap().as_ref(),
-        &[]
+        &[] as &[u8]
     );
     assert!(
         map.entry_ref(&[&[11], &[12], &[13], &[15]])
@@ -100,7 +100,7 @@ fn test_iter_op_with_key_prefix() {
     let mut op = |k: &[&[u8]], v: &[u8]| {
         cnt += 1;
         println!("cnt = {} v = {:?}", cnt, v);
-        if v == &[] {
+        if v.is_empty() {
             assert_eq!(k, &[&[11], &[12], &[13], &[14]]);
         } else {
             assert_eq!(k, &[&[11], &[12], &[13], &[15]]);
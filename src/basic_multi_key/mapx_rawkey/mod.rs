@@ -8,7 +8,15 @@
 #[cfg(test)]
 mod test;
 
-use crate::{basic_multi_key::mapx_raw::MapxRawMk, common::ende::ValueEnDe};
+use crate::{
+    basic_multi_key::mapx_raw::MapxRawMk,
+    common::{
+        ende::ValueEnDe,
+        entry::{Entry, EntryHdr},
+        gc::{GcMark, GcMarks},
+        RawKey,
+    },
+};
 use ruc::*;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -24,6 +32,10 @@ pub struct MapxRawKeyMk<V> {
 }
 
 impl<V: ValueEnDe> MapxRawKeyMk<V> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -63,8 +75,13 @@ impl<V: ValueEnDe> MapxRawKeyMk<V> {
     }
 
     #[inline(always)]
-    pub fn entry_ref<'a>(&'a mut self, key: &'a [&'a [u8]]) -> Entry<'a, V> {
-        Entry { key, hdr: self }
+    pub fn entry(&mut self, key: Vec<RawKey>) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
+    #[inline(always)]
+    pub fn entry_ref<'a>(&'a mut self, key: &'a [&'a [u8]]) -> EntryRef<'a, V> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
@@ -86,8 +103,8 @@ impl<V: ValueEnDe> MapxRawKeyMk<V> {
     }
 
     #[inline(always)]
-    pub fn clear(&mut self) {
-        self.inner.clear();
+    pub fn clear(&mut self) -> Result<()> {
+        self.inner.clear().c(d!())
     }
 
     #[inline(always)]
@@ -117,9 +134,59 @@ impl<V: ValueEnDe> MapxRawKeyMk<V> {
             .c(d!())
     }
 
-    // TODO
-    // pub fn iter_mut_op
-    // pub fn iter_mut_op_with_key_prefix
+    #[inline(always)]
+    pub fn iter_mut_op<F>(&mut self, op: &mut F) -> Result<()>
+    where
+        F: FnMut(&[&[u8]], &mut V) -> Result<()>,
+    {
+        self.inner.iter_mut_op_typed_value(op).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn iter_mut_op_with_key_prefix<F>(
+        &mut self,
+        op: &mut F,
+        key_prefix: &[&[u8]],
+    ) -> Result<()>
+    where
+        F: FnMut(&[&[u8]], &mut V) -> Result<()>,
+    {
+        self.inner
+            .iter_mut_op_typed_value_with_key_prefix(op, key_prefix)
+            .c(d!())
+    }
+
+    #[inline(always)]
+    pub(crate) fn gc_is_marked(&self, marks: &GcMarks) -> bool {
+        self.inner.gc_is_marked(marks)
+    }
+}
+
+impl<V: ValueEnDe> EntryHdr for MapxRawKeyMk<V> {
+    type Key = Vec<RawKey>;
+    type Value = V;
+
+    fn entry_get(&self, key: &Vec<RawKey>) -> Option<V> {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        self.get(&key)
+    }
+
+    fn entry_set(&mut self, key: &Vec<RawKey>, value: &V) {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        pnk!(self.insert(&key, value));
+    }
+
+    fn entry_remove(&mut self, key: &Vec<RawKey>) {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        pnk!(self.remove(&key));
+    }
+}
+
+impl<V: ValueEnDe + GcMark> GcMark for MapxRawKeyMk<V> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark_with_typed_value::<V>(marks);
+    }
 }
 
 impl<V> Clone for MapxRawKeyMk<V> {
@@ -163,12 +230,12 @@ impl<'a, V: ValueEnDe> DerefMut for ValueMut<'a, V> {
     }
 }
 
-pub struct Entry<'a, V> {
+pub struct EntryRef<'a, V> {
     hdr: &'a mut MapxRawKeyMk<V>,
     key: &'a [&'a [u8]],
 }
 
-impl<'a, V: ValueEnDe> Entry<'a, V> {
+impl<'a, V: ValueEnDe> EntryRef<'a, V> {
     pub fn or_insert_ref(self, default: &'a V) -> Result<ValueMut<'a, V>> {
         if !self.hdr.contains_key(self.key) {
             self.hdr.insert(self.key, default).c(d!())?;
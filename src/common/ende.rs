@@ -4,15 +4,21 @@
 //! (en)Encode and (de)Decode
 //!
 
-use super::RawBytes;
+use super::{
+    error::{VsdbError, VsdbResult},
+    schema::{self, SchemaId},
+    Pre, RawBytes,
+};
 use ruc::*;
 use serde::{
     de::{self, DeserializeOwned},
-    Serialize,
+    Deserialize, Serialize,
 };
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
     fmt,
-    mem::{size_of, transmute},
+    mem::size_of,
     result::Result as StdResult,
 };
 
@@ -66,14 +72,14 @@ pub trait KeyDe: DeserializeOwned {
         all(feature = "msgpack_codec", feature = "bcs_codec"),
         all(not(feature = "msgpack_codec"), not(feature = "bcs_codec")),
     ))]
-    fn decode_key(bytes: &[u8]) -> Result<Self> {
-        msgpack::from_slice(bytes).c(d!())
+    fn decode_key(bytes: &[u8]) -> VsdbResult<Self> {
+        msgpack::from_slice(bytes).map_err(VsdbError::codec::<Self>)
     }
 
     /// Decode from bytes to the original key type.
     #[cfg(all(feature = "bcs_codec", not(feature = "msgpack_codec")))]
-    fn decode_key(bytes: &[u8]) -> Result<Self> {
-        bcs::from_bytes(bytes).c(d!())
+    fn decode_key(bytes: &[u8]) -> VsdbResult<Self> {
+        bcs::from_bytes(bytes).map_err(VsdbError::codec::<Self>)
     }
 }
 
@@ -85,7 +91,7 @@ pub trait KeyEnDe: KeyEn + KeyDe {
     }
 
     /// Decode from bytes to the original key type.
-    fn decode(bytes: &[u8]) -> Result<Self> {
+    fn decode(bytes: &[u8]) -> VsdbResult<Self> {
         <Self as KeyDe>::decode_key(bytes)
     }
 }
@@ -117,14 +123,14 @@ pub trait ValueDe: DeserializeOwned {
         all(feature = "msgpack_codec", feature = "bcs_codec"),
         all(not(feature = "msgpack_codec"), not(feature = "bcs_codec")),
     ))]
-    fn decode_value(bytes: &[u8]) -> Result<Self> {
-        msgpack::from_slice(bytes).c(d!())
+    fn decode_value(bytes: &[u8]) -> VsdbResult<Self> {
+        msgpack::from_slice(bytes).map_err(VsdbError::codec::<Self>)
     }
 
     /// Decode from bytes to the original key type.
     #[cfg(all(feature = "bcs_codec", not(feature = "msgpack_codec")))]
-    fn decode_value(bytes: &[u8]) -> Result<Self> {
-        bcs::from_bytes(bytes).c(d!())
+    fn decode_value(bytes: &[u8]) -> VsdbResult<Self> {
+        bcs::from_bytes(bytes).map_err(VsdbError::codec::<Self>)
     }
 }
 
@@ -136,7 +142,7 @@ pub trait ValueEnDe: ValueEn + ValueDe {
     }
 
     /// Decode from bytes to the original key type.
-    fn decode(bytes: &[u8]) -> Result<Self> {
+    fn decode(bytes: &[u8]) -> VsdbResult<Self> {
         <Self as ValueDe>::decode_value(bytes)
     }
 }
@@ -168,8 +174,193 @@ pub(crate) fn encode_optioned_bytes(v: &Option<&[u8]>) -> RawBytes {
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
 
+/// Codecs of the typed values of an instance,
+/// the available ones are decided by the `*_codec` features.
+///
+/// A codec is recorded in the config of an instance by its fixed tag,
+/// so all the variants exist in every build, and opening an instance
+/// whose codec is not enabled will fail.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(into = "u8", try_from = "u8")]
+#[repr(u8)]
+pub enum Codec {
+    /// Use the implementations of `ValueEn/ValueDe`,
+    /// that is, the crate-wide codec.
+    #[default]
+    Default = 0,
+    /// Requires the `msgpack_codec` feature.
+    Msgpack = 1,
+    /// Requires the `bcs_codec` feature.
+    Bcs = 2,
+    /// Requires the `bincode_codec` feature.
+    Bincode = 3,
+    /// Requires the `postcard_codec` feature.
+    Postcard = 4,
+    /// Requires the `json_codec` feature.
+    Json = 5,
+}
+
+impl From<Codec> for u8 {
+    fn from(codec: Codec) -> u8 {
+        codec as u8
+    }
+}
+
+impl TryFrom<u8> for Codec {
+    type Error = VsdbError;
+    fn try_from(tag: u8) -> VsdbResult<Self> {
+        match tag {
+            0 => Ok(Self::Default),
+            1 => Ok(Self::Msgpack),
+            2 => Ok(Self::Bcs),
+            3 => Ok(Self::Bincode),
+            4 => Ok(Self::Postcard),
+            5 => Ok(Self::Json),
+            _ => Err(VsdbError::codec::<Self>(format!("unknown tag: {}", tag))),
+        }
+    }
+}
+
+impl Codec {
+    /// Whether the feature of this codec is enabled.
+    pub fn is_enabled(self) -> bool {
+        match self {
+            Self::Default => true,
+            Self::Msgpack => cfg!(feature = "msgpack_codec"),
+            Self::Bcs => cfg!(feature = "bcs_codec"),
+            Self::Bincode => cfg!(feature = "bincode_codec"),
+            Self::Postcard => cfg!(feature = "postcard_codec"),
+            Self::Json => cfg!(feature = "json_codec"),
+        }
+    }
+
+    pub(crate) fn check_enabled(self) -> VsdbResult<()> {
+        if self.is_enabled() {
+            Ok(())
+        } else {
+            Err(self.disabled())
+        }
+    }
+
+    fn disabled(self) -> VsdbError {
+        VsdbError::codec::<Self>(format!(
+            "the `{:?}` codec is not enabled by its feature",
+            self
+        ))
+    }
+
+    pub(crate) fn encode<V: ValueEnDe>(self, value: &V) -> RawBytes {
+        match self {
+            Self::Default => value.encode(),
+            #[cfg(feature = "msgpack_codec")]
+            Self::Msgpack => msgpack::to_vec(value).unwrap().into_boxed_slice(),
+            #[cfg(feature = "bcs_codec")]
+            Self::Bcs => bcs::to_bytes(value).unwrap().into_boxed_slice(),
+            #[cfg(feature = "bincode_codec")]
+            Self::Bincode => bincode::serialize(value).unwrap().into_boxed_slice(),
+            #[cfg(feature = "postcard_codec")]
+            Self::Postcard => postcard::to_allocvec(value).unwrap().into_boxed_slice(),
+            #[cfg(feature = "json_codec")]
+            Self::Json => serde_json::to_vec(value).unwrap().into_boxed_slice(),
+            // an instance can not be created or opened with a disabled codec
+            #[allow(unreachable_patterns)]
+            _ => panic!("{}", self.disabled()),
+        }
+    }
+
+    pub(crate) fn decode<V: ValueEnDe>(self, bytes: &[u8]) -> VsdbResult<V> {
+        match self {
+            Self::Default => <V as ValueEnDe>::decode(bytes),
+            #[cfg(feature = "msgpack_codec")]
+            Self::Msgpack => msgpack::from_slice(bytes).map_err(VsdbError::codec::<V>),
+            #[cfg(feature = "bcs_codec")]
+            Self::Bcs => bcs::from_bytes(bytes).map_err(VsdbError::codec::<V>),
+            #[cfg(feature = "bincode_codec")]
+            Self::Bincode => bincode::deserialize(bytes).map_err(VsdbError::codec::<V>),
+            #[cfg(feature = "postcard_codec")]
+            Self::Postcard => postcard::from_bytes(bytes).map_err(VsdbError::codec::<V>),
+            #[cfg(feature = "json_codec")]
+            Self::Json => serde_json::from_slice(bytes).map_err(VsdbError::codec::<V>),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.disabled()),
+        }
+    }
+}
+
+// the codec and the schema of the typed values of an instance
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ValueCodec {
+    pub(crate) codec: Codec,
+    pub(crate) schema: Option<SchemaId>,
+    // attached to the decoding errors
+    pub(crate) instance: Pre,
+}
+
+impl ValueCodec {
+    pub(crate) fn encode<V: ValueEnDe>(self, value: &V) -> RawBytes {
+        let value = self.codec.encode(value);
+        if let Some(id) = self.schema {
+            pnk!(schema::seal(id, &value))
+        } else {
+            value
+        }
+    }
+
+    pub(crate) fn decode<V: ValueEnDe>(self, bytes: &[u8]) -> VsdbResult<V> {
+        if let Some(id) = self.schema {
+            schema::open(id, bytes)
+                .map_err(|e| VsdbError::codec::<V>(e.get_lowest_msg()))
+                .and_then(|v| self.codec.decode(&v))
+        } else {
+            self.codec.decode(bytes)
+        }
+        .map_err(|e| e.with_instance(self.instance))
+    }
+
+    // decode the value of a specified key
+    pub(crate) fn decode_by_key<V: ValueEnDe>(
+        self,
+        key: &[u8],
+        bytes: &[u8],
+    ) -> VsdbResult<V> {
+        self.decode(bytes).map_err(|e| e.with_key(key))
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+/////////////////////////////////////////////////////////////////////////////
+
 /// For keys that their serialized order keep consistent with their original format.
 /// When using this kind of keys, we can do some ordered operations, such as: `get_le/get_be ...`
+///
+/// It can be derived for structures whose fields are all ordered keys,
+/// the fields are encoded in their declaration order like a tuple,
+/// so the order of the encoded bytes is consistent with the derived `Ord`.
+///
+/// ```
+/// use vsdb::{KeyEnDeOrdered, MapxOrd};
+///
+/// #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, KeyEnDeOrdered)]
+/// pub struct TxKey {
+///     pub height: u64,
+///     pub sender: String,
+///     pub nonce: i32,
+/// }
+///
+/// let mut m = MapxOrd::new();
+/// let key = |height, sender: &str, nonce| TxKey {
+///     height,
+///     sender: sender.to_owned(),
+///     nonce,
+/// };
+///
+/// m.insert(key(1, "bob", 0), 0);
+/// m.insert(key(1, "alice", -1), 1);
+/// m.insert(key(0, "bob", 1), 2);
+///
+/// assert_eq!(Some(key(0, "bob", 1)), m.first().map(|(k, _)| k));
+/// assert_eq!(Some(key(1, "bob", 0)), m.last().map(|(k, _)| k));
+/// ```
 pub trait KeyEnDeOrdered: Clone + Eq + Ord + fmt::Debug {
     /// &key => bytes
     fn to_bytes(&self) -> RawBytes;
@@ -180,12 +371,72 @@ pub trait KeyEnDeOrdered: Clone + Eq + Ord + fmt::Debug {
     }
 
     /// &bytes => key
-    fn from_slice(b: &[u8]) -> Result<Self>;
+    fn from_slice(b: &[u8]) -> VsdbResult<Self>;
 
     /// bytes => key
-    fn from_bytes(b: RawBytes) -> Result<Self> {
+    fn from_bytes(b: RawBytes) -> VsdbResult<Self> {
         Self::from_slice(&b)
     }
+
+    /// The size of the encoded bytes, `None` for the keys of variable sizes,
+    /// it is used to compose ordered keys, such as tuples.
+    const KEY_SIZE: Option<usize> = None;
+
+    /// legacy bytes => bytes
+    ///
+    /// Re-encode a key written by the legacy encoding,
+    /// in which signed integers are stored as their plain big-endian bytes.
+    ///
+    /// The conversion must be its own inverse,
+    /// it is also used to write the keys of the legacy instances.
+    fn upgrade_legacy_bytes(b: &[u8]) -> VsdbResult<RawBytes> {
+        Ok(b.to_vec().into_boxed_slice())
+    }
+}
+
+/// The version of the key encoding of an instance, recorded in its config,
+/// the instances created by older versions of VSDB use the legacy one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub(crate) enum KeyEncoding {
+    // signed integers are stored as their plain big-endian bytes,
+    // so the negative keys sort after the positive ones
+    Legacy,
+    #[default]
+    Ordered,
+}
+
+impl KeyEncoding {
+    #[inline(always)]
+    pub(crate) fn encode<K: KeyEnDeOrdered>(self, key: &K) -> RawBytes {
+        match self {
+            Self::Legacy => pnk!(K::upgrade_legacy_bytes(&key.to_bytes())),
+            Self::Ordered => key.to_bytes(),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn encode_owned<K: KeyEnDeOrdered>(self, key: K) -> RawBytes {
+        match self {
+            Self::Legacy => pnk!(K::upgrade_legacy_bytes(&key.into_bytes())),
+            Self::Ordered => key.into_bytes(),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn decode<K: KeyEnDeOrdered>(self, b: RawBytes) -> VsdbResult<K> {
+        match self {
+            Self::Legacy => K::upgrade_legacy_bytes(&b).and_then(K::from_bytes),
+            Self::Ordered => K::from_bytes(b),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn decode_slice<K: KeyEnDeOrdered>(self, b: &[u8]) -> VsdbResult<K> {
+        match self {
+            Self::Legacy => K::upgrade_legacy_bytes(b).and_then(K::from_bytes),
+            Self::Ordered => K::from_slice(b),
+        }
+    }
 }
 
 impl KeyEnDeOrdered for Vec<u8> {
@@ -200,12 +451,12 @@ impl KeyEnDeOrdered for Vec<u8> {
     }
 
     #[inline(always)]
-    fn from_slice(b: &[u8]) -> Result<Self> {
+    fn from_slice(b: &[u8]) -> VsdbResult<Self> {
         Ok(b.to_vec())
     }
 
     #[inline(always)]
-    fn from_bytes(b: RawBytes) -> Result<Self> {
+    fn from_bytes(b: RawBytes) -> VsdbResult<Self> {
         Ok(b.to_vec())
     }
 }
@@ -222,12 +473,12 @@ impl KeyEnDeOrdered for RawBytes {
     }
 
     #[inline(always)]
-    fn from_slice(b: &[u8]) -> Result<Self> {
+    fn from_slice(b: &[u8]) -> VsdbResult<Self> {
         Ok(b.to_vec().into_boxed_slice())
     }
 
     #[inline(always)]
-    fn from_bytes(b: RawBytes) -> Result<Self> {
+    fn from_bytes(b: RawBytes) -> VsdbResult<Self> {
         Ok(b)
     }
 }
@@ -244,79 +495,154 @@ impl KeyEnDeOrdered for String {
     }
 
     #[inline(always)]
-    fn from_slice(b: &[u8]) -> Result<Self> {
-        String::from_utf8(b.to_owned()).c(d!())
+    fn from_slice(b: &[u8]) -> VsdbResult<Self> {
+        String::from_utf8(b.to_owned()).map_err(VsdbError::codec::<Self>)
     }
 
     #[inline(always)]
-    fn from_bytes(b: RawBytes) -> Result<Self> {
-        String::from_utf8(b.into()).c(d!())
+    fn from_bytes(b: RawBytes) -> VsdbResult<Self> {
+        String::from_utf8(b.into()).map_err(VsdbError::codec::<Self>)
     }
 }
 
+macro_rules! impl_float {
+    ($name: tt, $float: ty, $bits: ty) => {
+        /// A wrapper of the float type with a total order(`total_cmp`),
+        /// so that it can be used as the key of ordered structures.
+        #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+        #[serde(transparent)]
+        pub struct $name(pub $float);
+
+        impl $name {
+            const SIGN: $bits = !(<$bits>::MAX >> 1);
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other).is_eq()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.total_cmp(&other.0)
+            }
+        }
+
+        impl From<$float> for $name {
+            fn from(f: $float) -> Self {
+                Self(f)
+            }
+        }
+
+        impl KeyEnDeOrdered for $name {
+            const KEY_SIZE: Option<usize> = Some(size_of::<$bits>());
+
+            // the same transformation as `total_cmp`:
+            // flip all bits of negative numbers, and the sign bit of the others
+            #[inline(always)]
+            fn to_bytes(&self) -> RawBytes {
+                let bits = self.0.to_bits();
+                let bits = if 0 == bits & Self::SIGN {
+                    bits | Self::SIGN
+                } else {
+                    !bits
+                };
+                Box::new(bits.to_be_bytes())
+            }
+            #[inline(always)]
+            fn from_slice(b: &[u8]) -> VsdbResult<Self> {
+                let bits = <[u8; size_of::<$bits>()]>::try_from(b)
+                    .map_err(VsdbError::codec::<Self>)
+                    .map(<$bits>::from_be_bytes)?;
+                let bits = if 0 == bits & Self::SIGN {
+                    !bits
+                } else {
+                    bits & !Self::SIGN
+                };
+                Ok(Self(<$float>::from_bits(bits)))
+            }
+        }
+    };
+}
+
+impl_float!(OrderedF32, f32, u32);
+impl_float!(OrderedF64, f64, u64);
+
+// the sign bit of signed integers is flipped(`i ^ MIN`),
+// so that negative integers sort before the positive ones,
+// it is a no-op for unsigned integers(`MIN == 0`)
 macro_rules! impl_type {
     ($int: ty) => {
         impl KeyEnDeOrdered for $int {
+            const KEY_SIZE: Option<usize> = Some(size_of::<$int>());
+
             #[inline(always)]
             fn to_bytes(&self) -> RawBytes {
-                Box::new(self.to_be_bytes())
+                Box::new((self ^ <$int>::MIN).to_be_bytes())
+            }
+            #[inline(always)]
+            fn from_slice(b: &[u8]) -> VsdbResult<Self> {
+                <[u8; size_of::<$int>()]>::try_from(b)
+                    .map_err(VsdbError::codec::<Self>)
+                    .map(<$int>::from_be_bytes)
+                    .map(|i| i ^ <$int>::MIN)
             }
             #[inline(always)]
-            fn from_slice(b: &[u8]) -> Result<Self> {
+            fn upgrade_legacy_bytes(b: &[u8]) -> VsdbResult<RawBytes> {
                 <[u8; size_of::<$int>()]>::try_from(b)
-                    .c(d!())
+                    .map_err(VsdbError::codec::<Self>)
                     .map(<$int>::from_be_bytes)
+                    .map(|i| Box::new((i ^ <$int>::MIN).to_be_bytes()) as RawBytes)
             }
         }
     };
     (@$int: ty) => {
-        #[allow(clippy::unsound_collection_transmute)]
         impl KeyEnDeOrdered for Vec<$int> {
             #[inline(always)]
             fn to_bytes(&self) -> RawBytes {
+                let mask = <$int>::MIN;
                 self.iter()
-                    .map(|i| i.to_be_bytes())
+                    .map(|i| (i ^ mask).to_be_bytes())
                     .flatten()
                     .collect::<Vec<_>>()
                     .into_boxed_slice()
             }
             #[inline(always)]
-            fn into_bytes(mut self) -> RawBytes {
-                for i in 0..self.len() {
-                    self[i] = self[i].to_be();
-                }
-                unsafe {
-                    let v = transmute::<Box<[$int]>, RawBytes>(self.into_boxed_slice());
-                    v
-                }
-            }
-            #[inline(always)]
-            fn from_slice(b: &[u8]) -> Result<Self> {
+            fn from_slice(b: &[u8]) -> VsdbResult<Self> {
                 if 0 != b.len() % size_of::<$int>() {
-                    return Err(eg!("invalid bytes"));
+                    return Err(VsdbError::codec::<Self>("invalid bytes"));
                 }
+                let mask = <$int>::MIN;
                 b.chunks(size_of::<$int>())
                     .map(|i| {
                         <[u8; size_of::<$int>()]>::try_from(i)
-                            .c(d!())
+                            .map_err(VsdbError::codec::<Self>)
                             .map(<$int>::from_be_bytes)
+                            .map(|i| i ^ mask)
                     })
                     .collect()
             }
             #[inline(always)]
-            fn from_bytes(b: RawBytes) -> Result<Self> {
+            fn upgrade_legacy_bytes(b: &[u8]) -> VsdbResult<RawBytes> {
                 if 0 != b.len() % size_of::<$int>() {
-                    return Err(eg!("invalid bytes"));
-                }
-                let mut ret = unsafe {
-                    let mut v = transmute::<Vec<u8>, Vec<$int>>(b.into());
-                    v.set_len(v.len() / size_of::<$int>());
-                    v
-                };
-                for i in 0..ret.len() {
-                    ret[i] = <$int>::from_be(ret[i]);
+                    return Err(VsdbError::codec::<Self>("invalid bytes"));
                 }
-                Ok(ret)
+                Ok(b.chunks(size_of::<$int>())
+                    .flat_map(|i| {
+                        let i = <$int>::from_be_bytes(i.try_into().unwrap());
+                        (i ^ <$int>::MIN).to_be_bytes()
+                    })
+                    .collect::<Vec<_>>()
+                    .into_boxed_slice())
             }
         }
     };
@@ -331,45 +657,61 @@ macro_rules! impl_type {
                 KeyEnDeOrdered::into_bytes(self.to_vec())
             }
             #[inline(always)]
-            fn from_slice(b: &[u8]) -> Result<Self> {
+            fn from_slice(b: &[u8]) -> VsdbResult<Self> {
                 <Vec<$int> as KeyEnDeOrdered>::from_slice(b)
                     .map(|v| v.into_boxed_slice())
             }
             #[inline(always)]
-            fn from_bytes(b: RawBytes) -> Result<Self> {
+            fn from_bytes(b: RawBytes) -> VsdbResult<Self> {
                 <Vec<$int> as KeyEnDeOrdered>::from_bytes(b)
                     .map(|v| v.into_boxed_slice())
             }
+            #[inline(always)]
+            fn upgrade_legacy_bytes(b: &[u8]) -> VsdbResult<RawBytes> {
+                <Vec<$int> as KeyEnDeOrdered>::upgrade_legacy_bytes(b)
+            }
         }
     };
     ($int: ty, $siz: expr) => {
         impl KeyEnDeOrdered for [$int; $siz] {
+            const KEY_SIZE: Option<usize> = Some($siz * size_of::<$int>());
+
             #[inline(always)]
             fn to_bytes(&self) -> RawBytes {
+                let mask = <$int>::MIN;
                 self.iter()
-                    .map(|i| i.to_be_bytes())
+                    .map(|i| (i ^ mask).to_be_bytes())
                     .flatten()
                     .collect::<Vec<_>>()
                     .into_boxed_slice()
             }
             #[inline(always)]
-            fn from_slice(b: &[u8]) -> Result<Self> {
+            fn from_slice(b: &[u8]) -> VsdbResult<Self> {
                 if 0 != b.len() % size_of::<$int>() {
-                    return Err(eg!("invalid bytes"));
+                    return Err(VsdbError::codec::<Self>("invalid bytes"));
                 }
                 if $siz != b.len() / size_of::<$int>() {
-                    return Err(eg!("invalid bytes"));
+                    return Err(VsdbError::codec::<Self>("invalid bytes"));
                 }
+                let mask = <$int>::MIN;
                 let mut res = [0; $siz];
                 b.chunks(size_of::<$int>())
                     .enumerate()
                     .for_each(|(idx, i)| {
                         res[idx] = <[u8; size_of::<$int>()]>::try_from(i)
                             .map(<$int>::from_be_bytes)
-                            .unwrap();
+                            .unwrap()
+                            ^ mask;
                     });
                 Ok(res)
             }
+            #[inline(always)]
+            fn upgrade_legacy_bytes(b: &[u8]) -> VsdbResult<RawBytes> {
+                if $siz * size_of::<$int>() != b.len() {
+                    return Err(VsdbError::codec::<Self>("invalid bytes"));
+                }
+                <Vec<$int> as KeyEnDeOrdered>::upgrade_legacy_bytes(b)
+            }
         }
     };
 }
@@ -444,5 +786,220 @@ impl_repeat!(
     123, 124, 125, 126, 127, 128
 );
 
+macro_rules! impl_uint {
+    ($uint: ty, $siz: expr) => {
+        impl KeyEnDeOrdered for $uint {
+            const KEY_SIZE: Option<usize> = Some($siz);
+
+            #[inline(always)]
+            fn to_bytes(&self) -> RawBytes {
+                let mut b = [0; $siz];
+                self.to_big_endian(&mut b);
+                Box::new(b)
+            }
+            #[inline(always)]
+            fn from_slice(b: &[u8]) -> VsdbResult<Self> {
+                if $siz != b.len() {
+                    return Err(VsdbError::codec::<Self>("invalid bytes"));
+                }
+                Ok(<$uint>::from_big_endian(b))
+            }
+        }
+    };
+}
+
+impl_uint!(primitive_types_0_10::U128, 16);
+impl_uint!(primitive_types_0_10::U256, 32);
+impl_uint!(primitive_types_0_10::U512, 64);
+impl_uint!(primitive_types_0_11::U128, 16);
+impl_uint!(primitive_types_0_11::U256, 32);
+impl_uint!(primitive_types_0_11::U512, 64);
+
+macro_rules! impl_hash {
+    ($hash: ty, $siz: expr) => {
+        impl KeyEnDeOrdered for $hash {
+            const KEY_SIZE: Option<usize> = Some($siz);
+
+            #[inline(always)]
+            fn to_bytes(&self) -> RawBytes {
+                self.as_bytes().to_vec().into_boxed_slice()
+            }
+            #[inline(always)]
+            fn from_slice(b: &[u8]) -> VsdbResult<Self> {
+                if $siz != b.len() {
+                    return Err(VsdbError::codec::<Self>("invalid bytes"));
+                }
+                Ok(<$hash>::from_slice(b))
+            }
+        }
+    };
+}
+
+impl_hash!(primitive_types_0_10::H128, 16);
+impl_hash!(primitive_types_0_10::H160, 20);
+impl_hash!(primitive_types_0_10::H256, 32);
+impl_hash!(primitive_types_0_10::H512, 64);
+impl_hash!(primitive_types_0_11::H128, 16);
+impl_hash!(primitive_types_0_11::H160, 20);
+impl_hash!(primitive_types_0_11::H256, 32);
+impl_hash!(primitive_types_0_11::H512, 64);
+
+/////////////////////////////////////////////////////////////////////////////
+/////////////////////////////////////////////////////////////////////////////
+
+// Composite keys(tuples, derived structures) are encoded part by part:
+// - the parts of fixed sizes are concatenated as they are
+// - the other parts are escaped(`0x00` => `0x00 0xFF`) and terminated by `0x00 0x00`,
+// so a shorter part always sorts before the longer ones that it is a prefix of
+// - the last part needs no terminator, it is always concatenated as it is
+
+const ESCAPE: u8 = 0x00;
+const ESCAPED: u8 = 0xFF;
+const TERMINATOR: u8 = 0x00;
+
+/// Sum the sizes of all parts of a composite key,
+/// `None` if any of them is of variable size.
+#[doc(hidden)]
+pub const fn key_size(sizes: &[Option<usize>]) -> Option<usize> {
+    let mut ret = 0;
+    let mut i = 0;
+    while i < sizes.len() {
+        if let Some(n) = sizes[i] {
+            ret += n;
+        } else {
+            return None;
+        }
+        i += 1;
+    }
+    Some(ret)
+}
+
+/// Append a part of a composite key to `buf`.
+#[doc(hidden)]
+pub fn encode_key_part<T: KeyEnDeOrdered>(part: &T, last: bool, buf: &mut Vec<u8>) {
+    put_key_part::<T>(&part.to_bytes(), last, buf)
+}
+
+/// Take a part of a composite key from the head of `b`.
+#[doc(hidden)]
+pub fn decode_key_part<T: KeyEnDeOrdered>(b: &mut &[u8], last: bool) -> VsdbResult<T> {
+    match take_key_part::<T>(b, last)? {
+        Cow::Borrowed(part) => T::from_slice(part),
+        Cow::Owned(part) => T::from_bytes(part.into_boxed_slice()),
+    }
+}
+
+/// Re-encode a part of a composite key written by the legacy encoding,
+/// take it from the head of `b` and append the new bytes to `buf`.
+#[doc(hidden)]
+pub fn upgrade_legacy_key_part<T: KeyEnDeOrdered>(
+    b: &mut &[u8],
+    last: bool,
+    buf: &mut Vec<u8>,
+) -> VsdbResult<()> {
+    let part = take_key_part::<T>(b, last)?;
+    put_key_part::<T>(&T::upgrade_legacy_bytes(&part)?, last, buf);
+    Ok(())
+}
+
+fn put_key_part<T: KeyEnDeOrdered>(bytes: &[u8], last: bool, buf: &mut Vec<u8>) {
+    if last || T::KEY_SIZE.is_some() {
+        buf.extend_from_slice(bytes);
+        return;
+    }
+
+    bytes.iter().for_each(|b| {
+        buf.push(*b);
+        if ESCAPE == *b {
+            buf.push(ESCAPED);
+        }
+    });
+    buf.extend_from_slice(&[ESCAPE, TERMINATOR]);
+}
+
+fn take_key_part<'a, T: KeyEnDeOrdered>(
+    b: &mut &'a [u8],
+    last: bool,
+) -> VsdbResult<Cow<'a, [u8]>> {
+    if last {
+        let part = *b;
+        *b = &[];
+        return Ok(Cow::Borrowed(part));
+    }
+
+    if let Some(n) = T::KEY_SIZE {
+        if b.len() < n {
+            return Err(VsdbError::codec::<T>("invalid bytes"));
+        }
+        let (part, rest) = b.split_at(n);
+        *b = rest;
+        return Ok(Cow::Borrowed(part));
+    }
+
+    let mut part = vec![];
+    let mut i = 0;
+    loop {
+        match (b.get(i), b.get(i + 1)) {
+            (Some(&ESCAPE), Some(&ESCAPED)) => {
+                part.push(ESCAPE);
+            }
+            (Some(&ESCAPE), Some(&TERMINATOR)) => {
+                *b = &b[i + 2..];
+                return Ok(Cow::Owned(part));
+            }
+            (Some(&ESCAPE), _) | (None, _) => {
+                return Err(VsdbError::codec::<T>("invalid bytes"));
+            }
+            (Some(byte), _) => {
+                part.push(*byte);
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+}
+
+macro_rules! impl_tuple {
+    ($(($t: ident, $i: tt)),+; ($last_t: ident, $last_i: tt)) => {
+        impl<$($t: KeyEnDeOrdered,)+ $last_t: KeyEnDeOrdered> KeyEnDeOrdered
+            for ($($t,)+ $last_t)
+        {
+            const KEY_SIZE: Option<usize> =
+                key_size(&[$($t::KEY_SIZE,)+ $last_t::KEY_SIZE]);
+
+            #[inline(always)]
+            fn to_bytes(&self) -> RawBytes {
+                let mut buf = vec![];
+                $(encode_key_part(&self.$i, false, &mut buf);)+
+                encode_key_part(&self.$last_i, true, &mut buf);
+                buf.into_boxed_slice()
+            }
+            #[inline(always)]
+            fn from_slice(mut b: &[u8]) -> VsdbResult<Self> {
+                Ok((
+                    $(decode_key_part::<$t>(&mut b, false)?,)+
+                    decode_key_part::<$last_t>(&mut b, true)?,
+                ))
+            }
+            #[inline(always)]
+            fn upgrade_legacy_bytes(mut b: &[u8]) -> VsdbResult<RawBytes> {
+                let mut buf = vec![];
+                $(upgrade_legacy_key_part::<$t>(&mut b, false, &mut buf)?;)+
+                upgrade_legacy_key_part::<$last_t>(&mut b, true, &mut buf)?;
+                Ok(buf.into_boxed_slice())
+            }
+        }
+    };
+}
+
+impl_tuple!((A, 0); (B, 1));
+impl_tuple!((A, 0), (B, 1); (C, 2));
+impl_tuple!((A, 0), (B, 1), (C, 2); (D, 3));
+impl_tuple!((A, 0), (B, 1), (C, 2), (D, 3); (E, 4));
+impl_tuple!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4); (F, 5));
+impl_tuple!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5); (G, 6));
+impl_tuple!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5), (G, 6); (H, 7));
+
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
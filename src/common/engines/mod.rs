@@ -4,74 +4,122 @@
 #[cfg(any(
     feature = "rocks_engine",
     all(feature = "rocks_engine", feature = "sled_engine"),
-    all(not(feature = "rocks_engine"), not(feature = "sled_engine")),
+    all(
+        not(feature = "rocks_engine"),
+        not(feature = "sled_engine"),
+        not(feature = "redb_engine")
+    ),
 ))]
 mod rocks_db;
 
-#[cfg(all(feature = "sled_engine", not(feature = "rocks_engine")))]
+#[cfg(all(
+    feature = "sled_engine",
+    not(feature = "rocks_engine"),
+    not(feature = "redb_engine")
+))]
 mod sled_db;
 
+#[cfg(all(feature = "redb_engine", not(feature = "rocks_engine")))]
+mod redb_db;
+
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
 
 #[cfg(any(
     feature = "rocks_engine",
     all(feature = "rocks_engine", feature = "sled_engine"),
-    all(not(feature = "rocks_engine"), not(feature = "sled_engine")),
+    all(
+        not(feature = "rocks_engine"),
+        not(feature = "sled_engine"),
+        not(feature = "redb_engine")
+    ),
 ))]
 pub(crate) use rocks_db::RocksEngine as RocksDB;
 
-#[cfg(all(feature = "sled_engine", not(feature = "rocks_engine")))]
+#[cfg(all(
+    feature = "sled_engine",
+    not(feature = "rocks_engine"),
+    not(feature = "redb_engine")
+))]
 pub(crate) use sled_db::SledEngine as Sled;
 
+#[cfg(all(feature = "redb_engine", not(feature = "rocks_engine")))]
+pub(crate) use redb_db::RedbEngine as Redb;
+
 #[cfg(any(
     feature = "rocks_engine",
     all(feature = "rocks_engine", feature = "sled_engine"),
-    all(not(feature = "rocks_engine"), not(feature = "sled_engine")),
+    all(
+        not(feature = "rocks_engine"),
+        not(feature = "sled_engine"),
+        not(feature = "redb_engine")
+    ),
 ))]
 type EngineIter = rocks_db::RocksIter;
 
-#[cfg(all(feature = "sled_engine", not(feature = "rocks_engine")))]
+#[cfg(all(
+    feature = "sled_engine",
+    not(feature = "rocks_engine"),
+    not(feature = "redb_engine")
+))]
 type EngineIter = sled_db::SledIter;
 
+#[cfg(all(feature = "redb_engine", not(feature = "rocks_engine")))]
+type EngineIter = redb_db::RedbIter;
+
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
 
 use crate::common::{
-    ende::{SimpleVisitor, ValueEnDe},
-    BranchID, Pre, PreBytes, RawKey, RawValue, VersionID, VSDB,
+    ende::{Codec, KeyEncoding, SimpleVisitor, ValueCodec, ValueEnDe},
+    ensure_writable,
+    error::VsdbResult,
+    gc::GcMarks,
+    schema::{self, ensure_registered, schema_id, SchemaId},
+    BranchID, Compression, InstanceOpts, Pre, PreBytes, RawKey, RawValue, VersionID,
+    PREFIX_SIZE, VSDB,
 };
-use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use ruc::*;
+
+#[cfg(feature = "encryption")]
+use crate::common::utils::crypto;
+
+#[cfg(feature = "zstd_compress")]
+use crate::common::utils::compress;
 use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     marker::PhantomData,
-    ops::{Deref, DerefMut, RangeBounds},
+    mem::size_of,
+    ops::{Bound, Deref, DerefMut, RangeBounds},
     result::Result as StdResult,
 };
 
-static LEN_LK: Lazy<Vec<Mutex<()>>> =
-    Lazy::new(|| (0..VSDB.db.area_count()).map(|_| Mutex::new(())).collect());
-
-/////////////////////////////////////////////////////////////////////////////
-/////////////////////////////////////////////////////////////////////////////
-
 /// Low-level database interface.
 pub trait Engine: Sized {
     fn new() -> Result<Self>;
     fn alloc_prefix(&self) -> Pre;
     fn alloc_branch_id(&self) -> BranchID;
     fn alloc_version_id(&self) -> VersionID;
-    fn area_count(&self) -> usize;
+    fn flush(&self) -> Result<()>;
 
-    // NOTE:
-    // do NOT make the number of areas bigger than `u8::MAX`
-    fn area_idx(&self, meta_prefix: PreBytes) -> usize {
-        meta_prefix[0] as usize % self.area_count()
-    }
+    // make all the finished writes durable, cheaper than `flush`
+    fn sync(&self) -> Result<()>;
+
+    // only available in the `OpenMode::Secondary` mode
+    fn try_catch_up_with_primary(&self) -> Result<()>;
 
-    fn flush(&self);
+    // create a consistent copy of the whole database in `path`,
+    // include the meta data(prefix/branch/version allocators, etc.)
+    fn checkpoint(&self, path: &str) -> Result<()>;
+
+    // compact the key range owned by an instance,
+    // the tombstones of the removed keys will be cleaned up
+    fn compact_instance(&self, instance_prefix: PreBytes);
+
+    fn compact_all(&self);
 
     fn iter(&self, meta_prefix: PreBytes) -> EngineIter;
 
@@ -83,56 +131,302 @@ pub trait Engine: Sized {
 
     fn get(&self, meta_prefix: PreBytes, key: &[u8]) -> Option<RawValue>;
 
+    // if `track_len` is `true`, the length record of the instance
+    // will be updated atomically with the data
     fn insert(
         &self,
         meta_prefix: PreBytes,
         key: &[u8],
         value: &[u8],
+        track_len: bool,
     ) -> Option<RawValue>;
 
-    fn remove(&self, meta_prefix: PreBytes, key: &[u8]) -> Option<RawValue>;
+    fn remove(
+        &self,
+        meta_prefix: PreBytes,
+        key: &[u8],
+        track_len: bool,
+    ) -> Option<RawValue>;
+
+    // remove all the keys within the `bounds` in one shot,
+    // if `track_len` is `true`, the length record of the instance
+    // will be reduced by the number of the removed keys,
+    // which are counted by a scanning of the range,
+    // so use `clear_instance` to remove all the keys of a tracked instance
+    fn remove_range<'a, R: RangeBounds<&'a [u8]>>(
+        &'a self,
+        meta_prefix: PreBytes,
+        bounds: R,
+        track_len: bool,
+    );
+
+    // apply a group of writes in one shot, a `None` value means a removing,
+    // if `track_len` is `true`, the length record of the instance
+    // will be updated along with the data
+    fn write_batch(
+        &self,
+        meta_prefix: PreBytes,
+        kvs: &[(&[u8], Option<&[u8]>)],
+        track_len: bool,
+    );
+
+    // remove all the keys of an instance
+    fn remove_prefix(&self, meta_prefix: PreBytes) {
+        self.remove_range(meta_prefix, .., false)
+    }
 
     fn get_instance_len(&self, instance_prefix: PreBytes) -> u64;
 
     fn set_instance_len(&self, instance_prefix: PreBytes, new_len: u64);
 
-    #[allow(unused_variables)]
-    fn increase_instance_len(&self, instance_prefix: PreBytes) {
-        let x = LEN_LK[self.area_idx(instance_prefix)].lock();
+    // remove all the data of an instance, and reset its length record,
+    // the length record must be exact even if the process crashes
+    fn clear_instance(&self, instance_prefix: PreBytes);
+
+    // prefixes of all the instances that have been created
+    fn instance_prefixes(&self) -> Vec<PreBytes>;
+
+    // remove all the data of an instance, include its meta record
+    fn remove_instance(&self, instance_prefix: PreBytes);
 
-        let l = self.get_instance_len(instance_prefix);
-        self.set_instance_len(instance_prefix, l + 1)
+    // the number of bytes occupied by an instance,
+    // include the prefix and the meta record
+    fn instance_size(&self, instance_prefix: PreBytes) -> u64 {
+        let meta_siz = (PREFIX_SIZE + size_of::<u64>()) as u64;
+        self.iter(instance_prefix).fold(meta_siz, |acc, (k, v)| {
+            acc + (PREFIX_SIZE + k.len() + v.len()) as u64
+        })
     }
+}
 
-    #[allow(unused_variables)]
-    fn decrease_instance_len(&self, instance_prefix: PreBytes) {
-        let x = LEN_LK[self.area_idx(instance_prefix)].lock();
+// IDs are reserved from the 'meta' in blocks,
+// the ceiling of a block is persisted before any ID of it is handed out,
+// so no ID will be reused after a crash, the unused IDs of the last block
+// will just be skipped.
+const ID_BLOCK_SIZE: u64 = 1000;
+
+pub(crate) struct IdAllocator {
+    // `[next, ceiling)` of the current block
+    block: Mutex<(u64, u64)>,
+}
 
-        let l = self.get_instance_len(instance_prefix);
-        self.set_instance_len(instance_prefix, l - 1)
+impl IdAllocator {
+    // `start` is the persisted ceiling of the last block
+    fn new(start: u64) -> Self {
+        Self {
+            block: Mutex::new((start, start)),
+        }
+    }
+
+    fn alloc(&self, persist_ceiling: impl FnOnce(u64)) -> u64 {
+        let mut block = self.block.lock();
+        if block.0 == block.1 {
+            block.1 = block.0 + ID_BLOCK_SIZE;
+            persist_ceiling(block.1);
+        }
+        block.0 += 1;
+        block.0 - 1
     }
 }
 
+// Convert the `bounds` of raw keys to a `[from, to)` range of full keys,
+// `key + [0]` is the closest successor of `key` in the bytewise order.
+fn full_key_range<'a, R: RangeBounds<&'a [u8]>>(
+    meta_prefix: PreBytes,
+    bounds: &R,
+) -> (Vec<u8>, Vec<u8>) {
+    let mut from = meta_prefix.to_vec();
+    match bounds.start_bound() {
+        Bound::Included(lo) => {
+            from.extend_from_slice(lo);
+        }
+        Bound::Excluded(lo) => {
+            from.extend_from_slice(lo);
+            from.push(0u8);
+        }
+        Bound::Unbounded => {}
+    }
+
+    let to = match bounds.end_bound() {
+        Bound::Included(hi) => {
+            let mut to = meta_prefix.to_vec();
+            to.extend_from_slice(hi);
+            to.push(0u8);
+            to
+        }
+        Bound::Excluded(hi) => {
+            let mut to = meta_prefix.to_vec();
+            to.extend_from_slice(hi);
+            to
+        }
+        // prefixes are allocated incrementally from a small number,
+        // so it will never overflow
+        Bound::Unbounded => (crate::parse_prefix!(meta_prefix) + 1)
+            .to_be_bytes()
+            .to_vec(),
+    };
+
+    (from, to)
+}
+
+// The change of the length caused by a batch of writes,
+// for the same key, the last write wins.
+//
+// NOTE: not used by redb, whose writes return the old values
+#[allow(dead_code)]
+fn batch_len_delta(
+    kvs: &[(&[u8], Option<&[u8]>)],
+    exists: impl Fn(&[u8]) -> bool,
+) -> i64 {
+    let mut last = HashMap::with_capacity(kvs.len());
+    for (k, v) in kvs {
+        last.insert(*k, v.is_some());
+    }
+    last.into_iter()
+        .map(|(k, present)| present as i64 - exists(k) as i64)
+        .sum()
+}
+
+// the max number of writes in one batch of the bulk operations
+const WRITE_BATCH_SIZE: usize = 8192;
+
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
 
-const LRU_CAP: usize = 10_0000;
-
 #[derive(Debug)]
 pub(crate) struct Mapx {
     // the unique ID of each instance
     prefix: PreBytes,
+    // if `false`, the length record of this instance will not be updated
+    track_len: bool,
+    // how to store the values
+    value_cfg: ValueCfg,
+    // how the typed keys are encoded
+    key_encoding: KeyEncoding,
 }
 
 impl Mapx {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     pub(crate) unsafe fn shadow(&self) -> Self {
         Self {
             prefix: self.prefix,
+            track_len: self.track_len,
+            value_cfg: self.value_cfg,
+            key_encoding: self.key_encoding,
         }
     }
 
     #[inline(always)]
     pub(crate) fn new() -> Self {
+        Self::new_inner(true, ValueCfg::default())
+    }
+
+    // create an instance without tracking its length,
+    // `len()` will fall back to a full scanning
+    #[inline(always)]
+    pub(crate) fn new_untracked() -> Self {
+        Self::new_inner(false, ValueCfg::default())
+    }
+
+    #[inline(always)]
+    pub(crate) fn new_with_opts(opts: &InstanceOpts) -> Self {
+        Self::new_with_cfg(ValueCfg::new(opts))
+    }
+
+    // instances created from the same `ValueCfg` share the same resources,
+    // such as the compression dictionary
+    #[inline(always)]
+    pub(crate) fn new_with_cfg(value_cfg: ValueCfg) -> Self {
+        Self::new_inner(true, value_cfg)
+    }
+
+    #[inline(always)]
+    pub(crate) fn codec(&self) -> ValueCodec {
+        self.value_cfg.codec(self.instance_prefix())
+    }
+
+    #[inline(always)]
+    pub(crate) fn key_encoding(&self) -> KeyEncoding {
+        self.key_encoding
+    }
+
+    #[inline(always)]
+    pub(crate) fn instance_prefix(&self) -> Pre {
+        Pre::from_be_bytes(self.prefix)
+    }
+
+    // rewrite all the values to the latest version of the schema `id`
+    //
+    // the migrated values are written into a new instance,
+    // which replaces `self` only after all of them have been written,
+    // so the old instance is never touched, and its handle stays valid
+    // if the process crashes before the new handle has been saved;
+    // the old instance will be reclaimed by the GC after that
+    pub(crate) fn migrate_schema(&mut self, id: SchemaId) -> Result<()> {
+        ensure_writable().c(d!())?;
+
+        let old_cfg = self.value_cfg;
+
+        // check all the values before any writing,
+        // so a failed migration will leave nothing behind
+        for (_, v) in self.iter() {
+            old_cfg.migrate_value(id, &v).c(d!())?;
+        }
+
+        let mut value_cfg = old_cfg;
+        value_cfg.set_schema(id);
+
+        let mut new_instance = Self::try_new_inner(self.track_len, value_cfg).c(d!())?;
+        new_instance.key_encoding = self.key_encoding;
+        new_instance.write_batch(
+            self.iter()
+                .map(|(k, v)| (k, Some(pnk!(old_cfg.migrate_value(id, &v))))),
+        );
+        *self = new_instance;
+
+        Ok(())
+    }
+
+    // re-encode all the keys into a new instance that replaces this one,
+    // the new instance uses the current key encoding,
+    // the old instance is left untouched until it is reclaimed by `vsdb_gc`
+    pub(crate) fn rekey<F>(&mut self, rekey: F) -> Result<()>
+    where
+        F: Fn(&[u8]) -> VsdbResult<RawKey>,
+    {
+        ensure_writable().c(d!())?;
+
+        // check all the keys before any writing,
+        // so a failed migration will leave nothing behind
+        for (k, _) in self.iter() {
+            rekey(&k).c(d!())?;
+        }
+
+        let mut new_instance =
+            Self::try_new_inner(self.track_len, self.value_cfg).c(d!())?;
+        new_instance.write_batch(self.iter().map(|(k, v)| (pnk!(rekey(&k)), Some(v))));
+        *self = new_instance;
+
+        Ok(())
+    }
+
+    // panic in the read-only modes
+    #[inline(always)]
+    fn new_inner(track_len: bool, value_cfg: ValueCfg) -> Self {
+        pnk!(Self::try_new_inner(track_len, value_cfg))
+    }
+
+    #[inline(always)]
+    pub(crate) fn try_new() -> Result<Self> {
+        Self::try_new_inner(true, ValueCfg::default()).c(d!())
+    }
+
+    fn try_new_inner(track_len: bool, value_cfg: ValueCfg) -> Result<Self> {
+        ensure_writable().c(d!())?;
+
         let prefix = VSDB.db.alloc_prefix();
 
         let prefix_bytes = prefix.to_be_bytes();
@@ -141,23 +435,47 @@ impl Mapx {
 
         VSDB.db.set_instance_len(prefix_bytes, 0);
 
-        Mapx {
+        Ok(Mapx {
             prefix: prefix_bytes,
-        }
+            track_len,
+            value_cfg,
+            key_encoding: KeyEncoding::default(),
+        })
+    }
+
+    // return `false` if the instance has been marked before
+    #[inline(always)]
+    pub(crate) fn gc_mark(&self, marks: &mut GcMarks) -> bool {
+        self.value_cfg.gc_mark(marks);
+        marks.mark(self.prefix)
+    }
+
+    #[inline(always)]
+    pub(crate) fn gc_is_marked(&self, marks: &GcMarks) -> bool {
+        marks.is_marked(self.prefix)
     }
 
     fn get_instance_cfg(&self) -> InstanceCfg {
         InstanceCfg::from(self)
     }
 
+    // panic if the value has been tampered
     #[inline(always)]
     pub(crate) fn get(&self, key: &[u8]) -> Option<RawValue> {
-        VSDB.db.get(self.prefix, key)
+        pnk!(self.try_get(key))
     }
 
     #[inline(always)]
-    pub(crate) fn get_mut(&mut self, key: &[u8]) -> Option<ValueMut> {
-        let v = VSDB.db.get(self.prefix, key)?;
+    pub(crate) fn try_get(&self, key: &[u8]) -> Result<Option<RawValue>> {
+        VSDB.db
+            .get(self.prefix, key)
+            .map(|v| self.open_value(key, v).c(d!()))
+            .transpose()
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_mut(&mut self, key: &[u8]) -> Option<ValueMut<'_>> {
+        let v = self.get(key)?;
 
         Some(ValueMut {
             key: key.to_vec().into(),
@@ -168,31 +486,47 @@ impl Mapx {
 
     #[inline(always)]
     pub(crate) fn len(&self) -> usize {
-        VSDB.db.get_instance_len(self.prefix) as usize
+        if self.track_len {
+            VSDB.db.get_instance_len(self.prefix) as usize
+        } else {
+            VSDB.db.iter(self.prefix).count()
+        }
     }
 
     #[inline(always)]
     pub(crate) fn is_empty(&self) -> bool {
-        0 == self.len()
+        if self.track_len {
+            0 == self.len()
+        } else {
+            VSDB.db.iter(self.prefix).next().is_none()
+        }
     }
 
     #[inline(always)]
-    pub(crate) fn iter(&self) -> MapxIter {
+    pub(crate) fn iter(&self) -> MapxIter<'_> {
         MapxIter {
             db_iter: VSDB.db.iter(self.prefix),
-            hdr: PhantomData,
+            hdr: self,
         }
     }
 
     #[inline(always)]
-    fn iter_mut<'a>(&'a mut self) -> MapxIterMut<'a> {
+    pub(crate) fn try_iter(&self) -> MapxTryIter<'_> {
+        MapxTryIter {
+            db_iter: VSDB.db.iter(self.prefix),
+            hdr: self,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn iter_mut(&mut self) -> MapxIterMut<'_> {
         MapxIterMut {
-            prefix: self.prefix,
             db_iter: VSDB.db.iter(self.prefix),
-            hdr: PhantomData,
+            hdr: self,
         }
     }
 
+    #[allow(dead_code)]
     #[inline(always)]
     pub(crate) fn into_iter(self) -> MapxIntoIter {
         MapxIntoIter {
@@ -203,56 +537,176 @@ impl Mapx {
 
     #[inline(always)]
     pub(crate) fn range<'a, R: RangeBounds<&'a [u8]>>(
-        &'a self,
+        &self,
         bounds: R,
-    ) -> MapxIter<'a> {
+    ) -> MapxIter<'_> {
         MapxIter {
             db_iter: VSDB.db.range(self.prefix, bounds),
-            hdr: PhantomData,
+            hdr: self,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn try_range<'a, R: RangeBounds<&'a [u8]>>(
+        &self,
+        bounds: R,
+    ) -> MapxTryIter<'_> {
+        MapxTryIter {
+            db_iter: VSDB.db.range(self.prefix, bounds),
+            hdr: self,
         }
     }
 
     #[inline(always)]
     pub(crate) fn range_mut<'a, R: RangeBounds<&'a [u8]>>(
-        &'a mut self,
+        &mut self,
         bounds: R,
-    ) -> MapxIterMut<'a> {
+    ) -> MapxIterMut<'_> {
         MapxIterMut {
             db_iter: VSDB.db.range(self.prefix, bounds),
             hdr: self,
         }
     }
 
+    // panic in the read-only modes
     #[inline(always)]
     pub(crate) fn insert(&mut self, key: &[u8], value: &[u8]) -> Option<RawValue> {
-        let ret = VSDB.db.insert(self.prefix, key, value);
-        if ret.is_none() {
-            VSDB.db.increase_instance_len(self.prefix);
-        }
-        ret
+        pnk!(self.try_insert(key, value))
     }
 
     #[inline(always)]
-    pub(crate) fn remove(&mut self, key: &[u8]) -> Option<RawValue> {
-        let ret = VSDB.db.remove(self.prefix, key);
-        if ret.is_some() {
-            VSDB.db.decrease_instance_len(self.prefix);
-        }
-        ret
+    pub(crate) fn try_insert(
+        &mut self,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<RawValue>> {
+        ensure_writable().c(d!())?;
+
+        let value = self.seal_value(key, value);
+
+        VSDB.db
+            .insert(self.prefix, key, &value, self.track_len)
+            .map(|v| self.open_value(key, v).c(d!()))
+            .transpose()
+    }
+
+    #[inline(always)]
+    pub(crate) fn try_remove(&mut self, key: &[u8]) -> Result<Option<RawValue>> {
+        ensure_writable().c(d!())?;
+
+        VSDB.db
+            .remove(self.prefix, key, self.track_len)
+            .map(|v| self.open_value(key, v).c(d!()))
+            .transpose()
     }
 
+    // panic in the read-only modes
     #[inline(always)]
     pub(crate) fn clear(&mut self) {
-        VSDB.db.iter(self.prefix).for_each(|(k, _)| {
-            VSDB.db.remove(self.prefix, &k);
-        });
-        VSDB.db.set_instance_len(self.prefix, 0);
+        pnk!(self.try_clear());
+    }
+
+    #[inline(always)]
+    pub(crate) fn try_clear(&mut self) -> Result<()> {
+        ensure_writable().c(d!())?;
+
+        VSDB.db.clear_instance(self.prefix);
+        Ok(())
+    }
+
+    // create a new empty instance with the same configurations
+    #[inline(always)]
+    pub(crate) fn try_new_like(&self) -> Result<Self> {
+        Self::try_new_inner(self.track_len, self.value_cfg)
+            .c(d!())
+            .map(|mut hdr| {
+                hdr.key_encoding = self.key_encoding;
+                hdr
+            })
+    }
+
+    // a `None` value means a removing,
+    // the pairs are written in batches to limit the memory usage
+    pub(crate) fn write_batch<I>(&mut self, kvs: I)
+    where
+        I: IntoIterator<Item = (RawKey, Option<RawValue>)>,
+    {
+        pnk!(ensure_writable());
+
+        let mut kvs = kvs.into_iter().peekable();
+        while kvs.peek().is_some() {
+            self.write_batch_at_once(kvs.by_ref().take(WRITE_BATCH_SIZE));
+        }
+    }
+
+    // a `None` value means a removing,
+    // all the pairs are written in one batch, so they are applied atomically
+    pub(crate) fn write_batch_at_once<I>(&mut self, kvs: I)
+    where
+        I: IntoIterator<Item = (RawKey, Option<RawValue>)>,
+    {
+        pnk!(ensure_writable());
+
+        let batch = kvs
+            .into_iter()
+            .map(|(k, v)| {
+                let v = v.map(|v| self.seal_value(&k, &v).into_owned());
+                (k, v)
+            })
+            .collect::<Vec<_>>();
+        let batch = batch
+            .iter()
+            .map(|(k, v)| (&k[..], v.as_deref()))
+            .collect::<Vec<_>>();
+        VSDB.db.write_batch(self.prefix, &batch, self.track_len);
+    }
+
+    #[inline(always)]
+    pub(crate) fn remove_range<'a, R: RangeBounds<&'a [u8]>>(&mut self, bounds: R) {
+        pnk!(ensure_writable());
+
+        VSDB.db.remove_range(self.prefix, bounds, self.track_len);
+    }
+
+    // remove all the pairs for which `f` returns `false`
+    pub(crate) fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&[u8], &[u8]) -> bool,
+    {
+        // the removings will never touch the iterator itself
+        let hdr = unsafe { self.shadow() };
+        let removed = hdr.iter().filter(|(k, v)| !f(k, v)).map(|(k, _)| (k, None));
+        self.write_batch(removed);
+    }
+
+    // values are compressed before the encryption,
+    // and they are authenticated along with their full keys
+    #[cfg_attr(not(feature = "encryption"), allow(unused_variables))]
+    #[inline(always)]
+    fn seal_value<'a>(&self, key: &[u8], value: &'a [u8]) -> Cow<'a, [u8]> {
+        let value = self.value_cfg.compression.compress(value);
+
+        #[cfg(feature = "encryption")]
+        let value = Cow::Owned(
+            crypto::seal(&[&self.prefix[..], key].concat(), &value).into_vec(),
+        );
+
+        value
+    }
+
+    #[cfg_attr(not(feature = "encryption"), allow(unused_variables))]
+    #[inline(always)]
+    fn open_value(&self, key: &[u8], value: RawValue) -> Result<RawValue> {
+        #[cfg(feature = "encryption")]
+        let value = crypto::open(&[&self.prefix[..], key].concat(), value).c(d!())?;
+
+        self.value_cfg.compression.decompress(value).c(d!())
     }
 }
 
 impl Clone for Mapx {
     fn clone(&self) -> Self {
-        let mut new_instance = Self::new();
+        let mut new_instance = pnk!(self.try_new_like());
         for (k, v) in self.iter() {
             new_instance.insert(&k, &v);
         }
@@ -275,17 +729,224 @@ impl Eq for Mapx {}
 #[derive(Deserialize, Serialize, Debug)]
 struct InstanceCfg {
     prefix: PreBytes,
+    track_len: bool,
+    value_cfg: ValueCfg,
+    #[serde(default)]
+    key_encoding: KeyEncoding,
+}
+
+// The config written by older versions, without any format tag,
+// their instances are always tracked, and their keys are legacy encoded.
+#[derive(Deserialize, Serialize, Debug)]
+struct InstanceCfgV0 {
+    prefix: PreBytes,
+}
+
+// An encoded `InstanceCfg` starts with `[CFG_TAG, CFG_VERSION]`.
+//
+// A legacy `InstanceCfgV0` never starts with `CFG_TAG` under any codec:
+// it is the top byte of a prefix for `bcs`, which is always zero in practice,
+// and a negative fixint instead of an array or a map for `msgpack`.
+const CFG_TAG: u8 = 0xff;
+const CFG_VERSION: u8 = 1;
+
+impl InstanceCfg {
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![CFG_TAG, CFG_VERSION];
+        buf.extend_from_slice(&<Self as ValueEnDe>::encode(self));
+        buf
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        match bytes {
+            [CFG_TAG, CFG_VERSION, cfg @ ..] => <Self as ValueEnDe>::decode(cfg)
+                .and_then(|cfg| cfg.value_cfg.codec.check_enabled().map(|_| cfg))
+                .c(d!()),
+            [CFG_TAG, ver, ..] => Err(eg!(format!(
+                "Unknown version of the instance config: {}",
+                ver
+            ))),
+            _ => <InstanceCfgV0 as ValueEnDe>::decode(bytes)
+                .c(d!())
+                .map(|cfg| Self {
+                    prefix: cfg.prefix,
+                    track_len: true,
+                    value_cfg: ValueCfg::default(),
+                    key_encoding: KeyEncoding::Legacy,
+                }),
+        }
+    }
 }
 
 impl From<InstanceCfg> for Mapx {
     fn from(cfg: InstanceCfg) -> Self {
-        Self { prefix: cfg.prefix }
+        Self {
+            prefix: cfg.prefix,
+            track_len: cfg.track_len,
+            value_cfg: cfg.value_cfg,
+            key_encoding: cfg.key_encoding,
+        }
     }
 }
 
 impl From<&Mapx> for InstanceCfg {
     fn from(x: &Mapx) -> Self {
-        Self { prefix: x.prefix }
+        Self {
+            prefix: x.prefix,
+            track_len: x.track_len,
+            value_cfg: x.value_cfg,
+            key_encoding: x.key_encoding,
+        }
+    }
+}
+
+// how to store the values of an instance, recorded in its `InstanceCfg`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub(crate) struct ValueCfg {
+    #[serde(default)]
+    compression: CompressionCfg,
+    #[serde(default)]
+    codec: Codec,
+    #[serde(default)]
+    schema: Option<SchemaId>,
+}
+
+impl ValueCfg {
+    pub(crate) fn new(opts: &InstanceOpts) -> Self {
+        let schema = opts.schema.as_deref().map(schema_id);
+        if let Some(id) = schema {
+            pnk!(ensure_registered(id));
+        }
+
+        pnk!(opts.codec.check_enabled());
+
+        Self {
+            compression: CompressionCfg::new(&opts.compression),
+            codec: opts.codec,
+            schema,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn codec(&self, instance: Pre) -> ValueCodec {
+        ValueCodec {
+            codec: self.codec,
+            schema: self.schema,
+            instance,
+        }
+    }
+
+    // for the internal structures, their values are always
+    // encoded by the default codec, without any schema
+    #[inline(always)]
+    pub(crate) fn for_internal(self) -> Self {
+        Self {
+            codec: Codec::Default,
+            schema: None,
+            ..self
+        }
+    }
+
+    // rewrite a value to the latest version of the schema `id`,
+    // and the schema should be recorded after all values have been migrated
+    pub(crate) fn migrate_value(
+        &self,
+        id: SchemaId,
+        value: &[u8],
+    ) -> Result<RawValue> {
+        if matches!(self.schema, Some(old) if old != id) {
+            return Err(eg!("A different schema has been applied"));
+        }
+        schema::migrate(self.schema, id, value).c(d!())
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_schema(&mut self, id: SchemaId) {
+        self.schema = Some(id);
+    }
+
+    // resources shared by the instances, such as the compression dictionary
+    pub(crate) fn gc_mark(&self, marks: &mut GcMarks) {
+        if let Some(dict_prefix) = self.compression.dict_prefix() {
+            marks.mark(dict_prefix);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+enum CompressionCfg {
+    #[default]
+    None,
+    #[cfg(feature = "zstd_compress")]
+    Zstd {
+        level: i32,
+        // prefix of the instance that stores the dictionary
+        dict: Option<PreBytes>,
+    },
+}
+
+#[cfg(feature = "zstd_compress")]
+const ZSTD_DICT_KEY: [u8; 1] = [0];
+
+impl CompressionCfg {
+    fn new(compression: &Compression) -> Self {
+        match compression {
+            Compression::None => Self::None,
+            #[cfg(feature = "zstd_compress")]
+            Compression::Zstd { level, dict } => Self::Zstd {
+                level: *level,
+                dict: dict.as_ref().map(|d| {
+                    let mut hdr = Mapx::new_untracked();
+                    hdr.insert(&ZSTD_DICT_KEY, d);
+                    hdr.prefix
+                }),
+            },
+        }
+    }
+
+    fn dict_prefix(&self) -> Option<PreBytes> {
+        match self {
+            Self::None => None,
+            #[cfg(feature = "zstd_compress")]
+            Self::Zstd { dict, .. } => *dict,
+        }
+    }
+
+    fn compress<'a>(&self, value: &'a [u8]) -> Cow<'a, [u8]> {
+        match self {
+            Self::None => Cow::Borrowed(value),
+            #[cfg(feature = "zstd_compress")]
+            Self::Zstd { level, dict } => Cow::Owned(compress::zstd_compress(
+                *level,
+                dict.map(|d| (d, move || Self::load_dict(d))),
+                value,
+            )),
+        }
+    }
+
+    fn decompress(&self, value: RawValue) -> Result<RawValue> {
+        match self {
+            Self::None => Ok(value),
+            #[cfg(feature = "zstd_compress")]
+            Self::Zstd { level, dict } => compress::zstd_decompress(
+                *level,
+                dict.map(|d| (d, move || Self::load_dict(d))),
+                &value,
+            )
+            .map(|v| v.into_boxed_slice())
+            .c(d!()),
+        }
+    }
+
+    #[cfg(feature = "zstd_compress")]
+    fn load_dict(dict_prefix: PreBytes) -> Vec<u8> {
+        let hdr = Mapx {
+            prefix: dict_prefix,
+            track_len: false,
+            value_cfg: ValueCfg::default(),
+            key_encoding: KeyEncoding::default(),
+        };
+        pnk!(hdr.get(&ZSTD_DICT_KEY), "the dictionary is lost").into_vec()
     }
 }
 
@@ -297,21 +958,44 @@ impl Serialize for Mapx {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_bytes(&<InstanceCfg as ValueEnDe>::encode(
-            &self.get_instance_cfg(),
-        ))
+        #[cfg(test)]
+        if LEGACY_HANDLE.with(|x| x.get()) {
+            let cfg = InstanceCfgV0 {
+                prefix: self.prefix,
+            };
+            return serializer.serialize_bytes(&ValueEnDe::encode(&cfg));
+        }
+        serializer.serialize_bytes(&self.get_instance_cfg().encode())
     }
 }
 
+#[cfg(test)]
+thread_local! {
+    static LEGACY_HANDLE: std::cell::Cell<bool> = std::cell::Cell::new(false);
+}
+
+// Reload a handle as if it was written by older versions,
+// all the instances in it will use the legacy key encoding.
+#[cfg(test)]
+pub(crate) fn reload_as_legacy<T: ValueEnDe>(hdr: &T) -> T {
+    LEGACY_HANDLE.with(|x| x.set(true));
+    let bytes = hdr.encode();
+    LEGACY_HANDLE.with(|x| x.set(false));
+    pnk!(T::decode(&bytes))
+}
+
 impl<'de> Deserialize<'de> for Mapx {
     fn deserialize<D>(deserializer: D) -> StdResult<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_bytes(SimpleVisitor).map(|meta| {
-            let meta = pnk!(<InstanceCfg as ValueEnDe>::decode(&meta));
-            Mapx::from(meta)
-        })
+        deserializer
+            .deserialize_bytes(SimpleVisitor)
+            .and_then(|meta| {
+                InstanceCfg::decode(&meta)
+                    .map(Mapx::from)
+                    .map_err(|e| serde::de::Error::custom(e.get_lowest_msg()))
+            })
     }
 }
 
@@ -320,32 +1004,67 @@ impl<'de> Deserialize<'de> for Mapx {
 
 pub struct MapxIter<'a> {
     db_iter: EngineIter,
-    _hdr: &'a Mapx,
+    hdr: &'a Mapx,
 }
 
 impl<'a> Iterator for MapxIter<'a> {
     type Item = (RawKey, RawValue);
     fn next(&mut self) -> Option<Self::Item> {
-        self.db_iter.next()
+        let (k, v) = self.db_iter.next()?;
+        let v = pnk!(self.hdr.open_value(&k, v));
+        Some((k, v))
     }
 }
 
 impl<'a> DoubleEndedIterator for MapxIter<'a> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.db_iter.next_back()
+        let (k, v) = self.db_iter.next_back()?;
+        let v = pnk!(self.hdr.open_value(&k, v));
+        Some((k, v))
+    }
+}
+
+// a broken value(e.g. tampered on disk) is yielded as an error
+pub struct MapxTryIter<'a> {
+    db_iter: EngineIter,
+    hdr: &'a Mapx,
+}
+
+impl<'a> Iterator for MapxTryIter<'a> {
+    type Item = Result<(RawKey, RawValue)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.db_iter.next()?;
+        Some(self.hdr.open_value(&k, v).c(d!()).map(|v| (k, v)))
+    }
+}
+
+impl<'a> DoubleEndedIterator for MapxTryIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.db_iter.next_back()?;
+        Some(self.hdr.open_value(&k, v).c(d!()).map(|v| (k, v)))
     }
 }
 
+#[allow(dead_code)]
 pub struct MapxIntoIter {
     db_iter: EngineIter,
     hdr: Mapx,
 }
 
-impl IntoIterator for MapxIntoIter {
+impl Iterator for MapxIntoIter {
     type Item = (RawKey, RawValue);
-    type IntoIter = EngineIter;
-    fn into_iter(self) -> Self::IntoIter {
-        self.db_iter
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.db_iter.next()?;
+        let v = pnk!(self.hdr.open_value(&k, v));
+        Some((k, v))
+    }
+}
+
+impl DoubleEndedIterator for MapxIntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.db_iter.next_back()?;
+        let v = pnk!(self.hdr.open_value(&k, v));
+        Some((k, v))
     }
 }
 
@@ -361,16 +1080,13 @@ pub struct MapxIterMut<'a> {
 }
 
 impl<'a> Iterator for MapxIterMut<'a> {
-    type Item = (RawKey, ValueMut<'a>);
+    type Item = (RawKey, ValueIterMut<'a>);
 
     fn next(&mut self) -> Option<Self::Item> {
         let (k, v) = self.db_iter.next()?;
+        let v = pnk!(self.hdr.open_value(&k, v));
 
-        let vmut = ValueIterMut {
-            key: k.clone(),
-            value: v,
-            hdr: self,
-        };
+        let vmut = ValueIterMut::new(self.hdr, k.clone(), v);
 
         Some((k, vmut))
     }
@@ -379,26 +1095,40 @@ impl<'a> Iterator for MapxIterMut<'a> {
 impl<'a> DoubleEndedIterator for MapxIterMut<'a> {
     fn next_back(&mut self) -> Option<Self::Item> {
         let (k, v) = self.db_iter.next_back()?;
+        let v = pnk!(self.hdr.open_value(&k, v));
 
-        let vmut = ValueMut {
-            key: k.clone(),
-            value: v,
-            hdr: self,
-        };
+        let vmut = ValueIterMut::new(self.hdr, k.clone(), v);
 
         Some((k, vmut))
     }
 }
 
-pub(crate) struct ValueIterMut<'a> {
+// Each item owns a copy of the handle instead of a `&mut` to it,
+// so any number of them can be alive at the same time,
+// the borrow of the instance is held by the `PhantomData`.
+#[derive(Debug)]
+pub struct ValueIterMut<'a> {
     key: RawKey,
     value: RawValue,
-    iter_mut: &'a mut MapxIterMut<'a>,
+    hdr: Mapx,
+    _p: PhantomData<&'a mut Mapx>,
+}
+
+impl<'a> ValueIterMut<'a> {
+    fn new(hdr: &Mapx, key: RawKey, value: RawValue) -> Self {
+        ValueIterMut {
+            key,
+            value,
+            // each key will be yielded only once by the iterator
+            hdr: unsafe { hdr.shadow() },
+            _p: PhantomData,
+        }
+    }
 }
 
 impl<'a> Drop for ValueIterMut<'a> {
     fn drop(&mut self) {
-        self.iter_mut.hdr.insert(&self.key[..], &self.value[..]);
+        self.hdr.insert(&self.key[..], &self.value[..]);
     }
 }
 
@@ -418,7 +1148,8 @@ impl<'a> DerefMut for ValueIterMut<'a> {
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
 
-pub(crate) struct ValueMut<'a> {
+#[derive(Debug)]
+pub struct ValueMut<'a> {
     key: RawKey,
     value: RawValue,
     hdr: &'a mut Mapx,
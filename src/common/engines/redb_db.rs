@@ -0,0 +1,478 @@
+use super::{full_key_range, IdAllocator};
+use crate::common::{
+    vsdb_get_base_dir, vsdb_get_open_mode, vsdb_get_sync_policy, vsdb_set_base_dir,
+    BranchID, Engine, OpenMode, Pre, PreBytes, RawKey, RawValue, SyncPolicy,
+    VersionID, INITIAL_BRANCH_ID, PREFIX_SIZE, RESERVED_ID_CNT,
+};
+use redb::{
+    Database, Durability, Range, ReadableTable, Table, TableDefinition,
+    WriteTransaction,
+};
+use ruc::*;
+use std::{
+    fs,
+    ops::RangeBounds,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+const DB_FILE_NAME: &str = "vsdb.redb";
+
+const META: TableDefinition<&[u8], &[u8]> = TableDefinition::new("meta");
+const DATA: TableDefinition<&[u8], &[u8]> = TableDefinition::new("data");
+
+const META_KEY_BRANCH_ID: [u8; 1] = [u8::MAX - 1];
+const META_KEY_VERSION_ID: [u8; 1] = [u8::MAX - 2];
+const META_KEY_PREFIX_ALLOCATOR: [u8; 1] = [u8::MIN];
+
+// Each write is committed without waiting for the disk,
+// and the garbage pages can only be freed by a durable commit,
+// so make a durable commit after so many writes.
+//
+// redb always recovers to the last durable commit, so the data and
+// its length record(updated in the same transaction) are always consistent,
+// but up to `DURABLE_COMMIT_INTERVAL - 1` finished writes may be lost
+// in a crash under `SyncPolicy::Never`.
+const DURABLE_COMMIT_INTERVAL: usize = 1024;
+
+pub(crate) struct RedbEngine {
+    db: Database,
+    prefix_allocator: IdAllocator,
+    branch_id_allocator: IdAllocator,
+    version_id_allocator: IdAllocator,
+    commit_cnt: AtomicUsize,
+    // `SyncPolicy::EveryWrite`
+    sync_every_write: bool,
+}
+
+impl RedbEngine {
+    #[allow(clippy::manual_is_multiple_of)]
+    fn write<T>(
+        &self,
+        op: impl for<'t> FnOnce(&mut RedbTable<'t>, &mut RedbTable<'t>) -> Result<T>,
+    ) -> T {
+        let cnt = 1 + self.commit_cnt.fetch_add(1, Ordering::Relaxed);
+        let durability = if self.sync_every_write {
+            Durability::Immediate
+        } else if 0 == cnt % DURABLE_COMMIT_INTERVAL {
+            Durability::Eventual
+        } else {
+            Durability::None
+        };
+        pnk!(write_with_durability(&self.db, durability, op))
+    }
+
+    // the ceilings of the ID blocks are always committed durably,
+    // or the IDs handed out before a crash could be allocated again
+    fn persist_ceiling(&self, key: &[u8], ceiling: u64) {
+        pnk!(write_with_durability(
+            &self.db,
+            Durability::Immediate,
+            |meta, _| {
+                meta.insert(key, &ceiling.to_be_bytes()[..])
+                    .c(d!())
+                    .map(|_| ())
+            }
+        ))
+    }
+
+    fn get_meta(&self, key: &[u8]) -> Option<RawValue> {
+        let tx = self.db.begin_read().unwrap();
+        let meta = tx.open_table(META).unwrap();
+        meta.get(key)
+            .unwrap()
+            .map(|v| v.value().to_vec().into_boxed_slice())
+    }
+}
+
+type RedbTable<'a> = Table<'a, &'static [u8], &'static [u8]>;
+
+impl Engine for RedbEngine {
+    fn new() -> Result<Self> {
+        let read_only = match vsdb_get_open_mode() {
+            OpenMode::ReadWrite => false,
+            OpenMode::ReadOnly => true,
+            OpenMode::Secondary => {
+                return Err(eg!("The secondary mode is not supported by redb"));
+            }
+        };
+
+        let db = redb_open(read_only).c(d!())?;
+
+        let (prefix_allocator, initial_value) = PreAllocator::init();
+
+        if read_only {
+            let tx = db.begin_read().c(d!())?;
+            let meta = tx.open_table(META).c(d!())?;
+            if meta.get(&prefix_allocator.key[..]).c(d!())?.is_none() {
+                return Err(eg!("Can not open an uninitialized DB in read-only mode"));
+            }
+        } else {
+            init_meta(&db, &prefix_allocator, initial_value).c(d!())?;
+        }
+
+        // allocations start from the ceilings of the last blocks
+        let id_allocator = |key: &[u8]| -> Result<IdAllocator> {
+            let tx = db.begin_read().c(d!())?;
+            let meta = tx.open_table(META).c(d!())?;
+            meta.get(key)
+                .c(d!())?
+                .map(|v| IdAllocator::new(crate::parse_int!(v.value(), u64)))
+                .c(d!())
+        };
+
+        Ok(RedbEngine {
+            prefix_allocator: id_allocator(&prefix_allocator.key).c(d!())?,
+            branch_id_allocator: id_allocator(&META_KEY_BRANCH_ID).c(d!())?,
+            version_id_allocator: id_allocator(&META_KEY_VERSION_ID).c(d!())?,
+            db,
+            commit_cnt: AtomicUsize::new(0),
+            sync_every_write: SyncPolicy::EveryWrite == vsdb_get_sync_policy(),
+        })
+    }
+
+    fn alloc_prefix(&self) -> Pre {
+        self.prefix_allocator
+            .alloc(|ceiling| self.persist_ceiling(&META_KEY_PREFIX_ALLOCATOR, ceiling))
+    }
+
+    fn alloc_branch_id(&self) -> BranchID {
+        self.branch_id_allocator
+            .alloc(|ceiling| self.persist_ceiling(&META_KEY_BRANCH_ID, ceiling))
+    }
+
+    fn alloc_version_id(&self) -> VersionID {
+        self.version_id_allocator
+            .alloc(|ceiling| self.persist_ceiling(&META_KEY_VERSION_ID, ceiling))
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.sync().c(d!())
+    }
+
+    // an empty durable commit makes all the previous commits durable
+    fn sync(&self) -> Result<()> {
+        if OpenMode::ReadWrite != vsdb_get_open_mode() {
+            return Ok(());
+        }
+        write_with_durability(&self.db, Durability::Immediate, |_, _| Ok(()))
+    }
+
+    fn try_catch_up_with_primary(&self) -> Result<()> {
+        Err(eg!("The secondary mode is not supported by redb"))
+    }
+
+    // redb has no native checkpoints,
+    // copy all tables into a newly created DB in one read transaction
+    fn checkpoint(&self, path: &str) -> Result<()> {
+        fs::create_dir_all(path).c(d!())?;
+        let db = Database::create(format!("{}/{}", path, DB_FILE_NAME)).c(d!())?;
+
+        let tx = self.db.begin_read().c(d!())?;
+        write_with_durability(&db, Durability::Immediate, |meta, data| {
+            for (src, dst) in [(META, meta), (DATA, data)] {
+                for kv in tx.open_table(src).c(d!())?.iter().c(d!())? {
+                    let (k, v) = kv.c(d!())?;
+                    dst.insert(k.value(), v.value()).c(d!())?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // the space of removed data is reused by redb itself,
+    // and the manual compaction requires an exclusive access to the DB
+    fn compact_instance(&self, _: PreBytes) {}
+
+    fn compact_all(&self) {}
+
+    fn iter(&self, meta_prefix: PreBytes) -> RedbIter {
+        self.range(meta_prefix, ..)
+    }
+
+    // the keys are bounded natively,
+    // so the scanning never touches any key outside the range
+    fn range<'a, R: RangeBounds<&'a [u8]>>(
+        &'a self,
+        meta_prefix: PreBytes,
+        bounds: R,
+    ) -> RedbIter {
+        let (from, to) = full_key_range(meta_prefix, &bounds);
+
+        let tx = self.db.begin_read().unwrap();
+        let data = tx.open_table(DATA).unwrap();
+
+        RedbIter {
+            inner: data.range(from.as_slice()..to.as_slice()).unwrap(),
+        }
+    }
+
+    fn get(&self, meta_prefix: PreBytes, key: &[u8]) -> Option<RawValue> {
+        let mut k = meta_prefix.to_vec();
+        k.extend_from_slice(key);
+
+        let tx = self.db.begin_read().unwrap();
+        let data = tx.open_table(DATA).unwrap();
+        data.get(k.as_slice())
+            .unwrap()
+            .map(|v| v.value().to_vec().into_boxed_slice())
+    }
+
+    // the data and its length record are updated in one transaction
+    fn insert(
+        &self,
+        meta_prefix: PreBytes,
+        key: &[u8],
+        value: &[u8],
+        track_len: bool,
+    ) -> Option<RawValue> {
+        let mut k = meta_prefix.to_vec();
+        k.extend_from_slice(key);
+
+        self.write(|meta, data| {
+            let old_v = data
+                .insert(k.as_slice(), value)
+                .c(d!())?
+                .map(|v| v.value().to_vec().into_boxed_slice());
+            if track_len && old_v.is_none() {
+                update_instance_len(meta, meta_prefix, 1).c(d!())?;
+            }
+            Ok(old_v)
+        })
+    }
+
+    fn remove(
+        &self,
+        meta_prefix: PreBytes,
+        key: &[u8],
+        track_len: bool,
+    ) -> Option<RawValue> {
+        let mut k = meta_prefix.to_vec();
+        k.extend_from_slice(key);
+
+        self.write(|meta, data| {
+            let old_v = data
+                .remove(k.as_slice())
+                .c(d!())?
+                .map(|v| v.value().to_vec().into_boxed_slice());
+            if track_len && old_v.is_some() {
+                update_instance_len(meta, meta_prefix, -1).c(d!())?;
+            }
+            Ok(old_v)
+        })
+    }
+
+    fn remove_range<'a, R: RangeBounds<&'a [u8]>>(
+        &'a self,
+        meta_prefix: PreBytes,
+        bounds: R,
+        track_len: bool,
+    ) {
+        let (from, to) = full_key_range(meta_prefix, &bounds);
+        self.write(|meta, data| {
+            let mut cnt = 0;
+            data.retain_in(from.as_slice()..to.as_slice(), |_, _| {
+                cnt += 1;
+                false
+            })
+            .c(d!())?;
+            if track_len && 0 < cnt {
+                update_instance_len(meta, meta_prefix, -cnt).c(d!())?;
+            }
+            Ok(())
+        })
+    }
+
+    // all the writes are done in one transaction
+    fn write_batch(
+        &self,
+        meta_prefix: PreBytes,
+        kvs: &[(&[u8], Option<&[u8]>)],
+        track_len: bool,
+    ) {
+        self.write(|meta, data| {
+            let mut delta = 0;
+            for (k, v) in kvs {
+                let mut fk = meta_prefix.to_vec();
+                fk.extend_from_slice(k);
+                let existed = if let Some(v) = v {
+                    delta += 1;
+                    data.insert(fk.as_slice(), *v).c(d!())?.is_some()
+                } else {
+                    data.remove(fk.as_slice()).c(d!())?.is_some()
+                };
+                if existed {
+                    delta -= 1;
+                }
+            }
+            if track_len && 0 != delta {
+                update_instance_len(meta, meta_prefix, delta).c(d!())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn get_instance_len(&self, instance_prefix: PreBytes) -> u64 {
+        crate::parse_int!(self.get_meta(&instance_prefix).unwrap(), u64)
+    }
+
+    fn set_instance_len(&self, instance_prefix: PreBytes, new_len: u64) {
+        self.write(|meta, _| {
+            meta.insert(&instance_prefix[..], &new_len.to_be_bytes()[..])
+                .c(d!())
+                .map(|_| ())
+        })
+    }
+
+    fn clear_instance(&self, instance_prefix: PreBytes) {
+        let (from, to) = full_key_range(instance_prefix, &(..));
+        self.write(|meta, data| {
+            data.retain_in(from.as_slice()..to.as_slice(), |_, _| false)
+                .c(d!())?;
+            meta.insert(&instance_prefix[..], &0u64.to_be_bytes()[..])
+                .c(d!())
+                .map(|_| ())
+        })
+    }
+
+    // the length of each instance is recorded in the 'meta' with its prefix,
+    // all other keys in the 'meta' are shorter than a prefix
+    fn instance_prefixes(&self) -> Vec<PreBytes> {
+        let tx = self.db.begin_read().unwrap();
+        let meta = tx.open_table(META).unwrap();
+        meta.iter()
+            .unwrap()
+            .map(|kv| kv.unwrap().0.value().to_vec())
+            .filter(|k| k.len() == PREFIX_SIZE)
+            .map(|k| k[..].try_into().unwrap())
+            .collect()
+    }
+
+    fn remove_instance(&self, instance_prefix: PreBytes) {
+        self.remove_prefix(instance_prefix);
+        self.write(|meta, _| meta.remove(&instance_prefix[..]).c(d!()).map(|_| ()))
+    }
+}
+
+pub struct RedbIter {
+    inner: Range<'static, &'static [u8], &'static [u8]>,
+}
+
+impl Iterator for RedbIter {
+    type Item = (RawKey, RawValue);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|i| i.unwrap()).map(|(k, v)| {
+            (
+                k.value()[PREFIX_SIZE..].to_vec().into_boxed_slice(),
+                v.value().to_vec().into_boxed_slice(),
+            )
+        })
+    }
+}
+
+impl DoubleEndedIterator for RedbIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|i| i.unwrap()).map(|(k, v)| {
+            (
+                k.value()[PREFIX_SIZE..].to_vec().into_boxed_slice(),
+                v.value().to_vec().into_boxed_slice(),
+            )
+        })
+    }
+}
+
+// key of the prefix allocator in the 'meta'
+struct PreAllocator {
+    key: [u8; 1],
+}
+
+impl PreAllocator {
+    const fn init() -> (Self, PreBytes) {
+        (
+            Self {
+                key: META_KEY_PREFIX_ALLOCATOR,
+            },
+            (RESERVED_ID_CNT + Pre::MIN).to_be_bytes(),
+        )
+    }
+}
+
+fn write_with_durability<T>(
+    db: &Database,
+    durability: Durability,
+    op: impl for<'t> FnOnce(&mut RedbTable<'t>, &mut RedbTable<'t>) -> Result<T>,
+) -> Result<T> {
+    let mut tx: WriteTransaction = db.begin_write().c(d!())?;
+    tx.set_durability(durability);
+    let ret = {
+        let mut meta = tx.open_table(META).c(d!())?;
+        let mut data = tx.open_table(DATA).c(d!())?;
+        op(&mut meta, &mut data).c(d!())?
+    };
+    tx.commit().c(d!())?;
+    Ok(ret)
+}
+
+fn update_instance_len(
+    meta: &mut RedbTable,
+    instance_prefix: PreBytes,
+    delta: i64,
+) -> Result<()> {
+    let l = meta
+        .get(&instance_prefix[..])
+        .c(d!())?
+        .map(|l| crate::parse_int!(l.value(), u64))
+        .c(d!())?;
+    let l = (l as i64 + delta) as u64;
+    meta.insert(&instance_prefix[..], &l.to_be_bytes()[..])
+        .c(d!())
+        .map(|_| ())
+}
+
+fn redb_open(read_only: bool) -> Result<Database> {
+    let dir = vsdb_get_base_dir();
+
+    // avoid setting again on an opened DB
+    info_omit!(vsdb_set_base_dir(&dir));
+
+    let path = format!("{}/{}", dir, DB_FILE_NAME);
+
+    // `Database::create` initializes a missing or empty file,
+    // a read-only open must never create or modify the DB
+    if read_only {
+        if !Path::new(&path).is_file() {
+            return Err(eg!("The DB does not exist: {}", path));
+        }
+        Database::open(&path).c(d!())
+    } else {
+        Database::create(&path).c(d!())
+    }
+}
+
+fn init_meta(
+    db: &Database,
+    prefix_allocator: &PreAllocator,
+    initial_value: PreBytes,
+) -> Result<()> {
+    write_with_durability(db, Durability::Immediate, |meta, _| {
+        if meta.get(&META_KEY_BRANCH_ID[..]).c(d!())?.is_none() {
+            meta.insert(
+                &META_KEY_BRANCH_ID[..],
+                &(1 + INITIAL_BRANCH_ID as usize).to_be_bytes()[..],
+            )
+            .c(d!())?;
+        }
+
+        if meta.get(&META_KEY_VERSION_ID[..]).c(d!())?.is_none() {
+            meta.insert(&META_KEY_VERSION_ID[..], &0_usize.to_be_bytes()[..])
+                .c(d!())?;
+        }
+
+        if meta.get(&prefix_allocator.key[..]).c(d!())?.is_none() {
+            meta.insert(&prefix_allocator.key[..], &initial_value[..])
+                .c(d!())?;
+        }
+
+        Ok(())
+    })
+}
@@ -1,18 +1,22 @@
+use super::{batch_len_delta, full_key_range, IdAllocator};
 use crate::common::{
-    vsdb_get_base_dir, vsdb_set_base_dir, BranchID, Engine, Pre, PreBytes, RawBytes,
-    RawKey, RawValue, VersionID, INITIAL_BRANCH_ID, MB, RESERVED_ID_CNT,
+    vsdb_get_base_dir, vsdb_get_open_mode, vsdb_get_sync_policy, vsdb_set_base_dir,
+    BranchID, Engine, OpenMode, Pre, PreBytes, RawKey, RawValue, SyncPolicy,
+    VersionID, INITIAL_BRANCH_ID, MB, PREFIX_SIZE, RESERVED_ID_CNT,
 };
 use once_cell::sync::Lazy;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, MutexGuard};
 use rocksdb::{
-    ColumnFamily, ColumnFamilyDescriptor, DBCompressionType, DBIterator, Direction,
-    IteratorMode, Options, ReadOptions, SliceTransform, DB,
+    checkpoint::Checkpoint, merge_operator::MergeOperands, ColumnFamily,
+    ColumnFamilyDescriptor, DBCompressionType, DBIterator, Direction, IteratorMode,
+    Options, ReadOptions, SliceTransform, WriteBatch, WriteOptions, DB,
 };
 use ruc::*;
 use std::{
+    fs,
     mem::size_of,
-    ops::{Bound, RangeBounds},
-    sync::atomic::{AtomicUsize, Ordering},
+    ops::RangeBounds,
+    process,
     thread::available_parallelism,
 };
 
@@ -20,7 +24,9 @@ use std::{
 // do NOT make the number of areas bigger than `u8::MAX`
 const DATA_SET_NUM: usize = 4;
 
-const META_KEY_MAX_KEYLEN: [u8; 1] = [u8::MAX];
+// the number of locks that guard the length records of instances
+const LEN_LOCK_NUM: usize = 64;
+
 const META_KEY_BRANCH_ID: [u8; 1] = [u8::MAX - 1];
 const META_KEY_VERSION_ID: [u8; 1] = [u8::MAX - 2];
 const META_KEY_PREFIX_ALLOCATOR: [u8; 1] = [u8::MIN];
@@ -30,8 +36,14 @@ static HDR: Lazy<(DB, Vec<String>)> = Lazy::new(|| rocksdb_open().unwrap());
 pub(crate) struct RocksEngine {
     meta: &'static DB,
     areas: Vec<&'static str>,
-    prefix_allocator: PreAllocator,
-    max_keylen: AtomicUsize,
+    prefix_allocator: IdAllocator,
+    branch_id_allocator: IdAllocator,
+    version_id_allocator: IdAllocator,
+    // the WAL is synced on every write in the `SyncPolicy::EveryWrite` mode
+    write_opts: WriteOptions,
+    // whether a key existed is read before its length delta is written,
+    // so the writes to a tracked instance are serialized by one of these
+    len_locks: Vec<Mutex<()>>,
 }
 
 impl RocksEngine {
@@ -41,32 +53,20 @@ impl RocksEngine {
     }
 
     #[inline(always)]
-    fn get_max_keylen(&self) -> usize {
-        self.max_keylen.load(Ordering::Relaxed)
+    fn area_idx(&self, meta_prefix: PreBytes) -> usize {
+        meta_prefix[0] as usize % DATA_SET_NUM
     }
 
     #[inline(always)]
-    fn set_max_key_len(&self, len: usize) {
-        self.max_keylen.store(len, Ordering::Relaxed);
-        self.meta
-            .put(META_KEY_MAX_KEYLEN, len.to_be_bytes())
-            .unwrap();
-    }
-
-    #[inline(always)]
-    fn get_upper_bound_value(&self, meta_prefix: PreBytes) -> Vec<u8> {
-        static BUF: Lazy<RawBytes> = Lazy::new(|| vec![u8::MAX; 512].into_boxed_slice());
-
-        let mut max_guard = meta_prefix.to_vec();
-
-        let l = self.get_max_keylen();
-        if l < 513 {
-            max_guard.extend_from_slice(&BUF[..l]);
-        } else {
-            max_guard.extend_from_slice(&vec![u8::MAX; l]);
-        }
-
-        max_guard
+    fn lock_len(
+        &self,
+        meta_prefix: PreBytes,
+        track_len: bool,
+    ) -> Option<MutexGuard<'_, ()>> {
+        track_len.then(|| {
+            let idx = crate::parse_prefix!(meta_prefix) as usize % LEN_LOCK_NUM;
+            self.len_locks[idx].lock()
+        })
     }
 }
 
@@ -77,197 +77,136 @@ impl Engine for RocksEngine {
 
         let (prefix_allocator, initial_value) = PreAllocator::init();
 
-        if meta.get(&META_KEY_MAX_KEYLEN).c(d!())?.is_none() {
-            meta.put(META_KEY_MAX_KEYLEN, 0_usize.to_be_bytes())
-                .c(d!())?;
-        }
-
-        if meta.get(&META_KEY_BRANCH_ID).c(d!())?.is_none() {
-            meta.put(
-                META_KEY_BRANCH_ID,
-                (1 + INITIAL_BRANCH_ID as usize).to_be_bytes(),
-            )
-            .c(d!())?;
-        }
-
-        if meta.get(&META_KEY_VERSION_ID).c(d!())?.is_none() {
-            meta.put(META_KEY_VERSION_ID, 0_usize.to_be_bytes())
-                .c(d!())?;
-        }
-
-        if meta.get(prefix_allocator.key).c(d!())?.is_none() {
-            meta.put(prefix_allocator.key, initial_value).c(d!())?;
+        if OpenMode::ReadWrite != vsdb_get_open_mode() {
+            if meta.get(prefix_allocator.key).c(d!())?.is_none() {
+                return Err(eg!("Can not open an uninitialized DB in read-only mode"));
+            }
+        } else {
+            init_meta(meta, &prefix_allocator, initial_value).c(d!())?;
         }
 
-        let max_keylen = AtomicUsize::new(crate::parse_int!(
-            meta.get(&META_KEY_MAX_KEYLEN).unwrap().unwrap(),
-            usize
-        ));
+        // allocations start from the ceilings of the last blocks
+        let id_allocator = |key: &[u8]| -> Result<IdAllocator> {
+            meta.get(key)
+                .c(d!())?
+                .map(|v| IdAllocator::new(crate::parse_int!(v, u64)))
+                .c(d!())
+        };
 
         Ok(RocksEngine {
+            prefix_allocator: id_allocator(&prefix_allocator.key).c(d!())?,
+            branch_id_allocator: id_allocator(&META_KEY_BRANCH_ID).c(d!())?,
+            version_id_allocator: id_allocator(&META_KEY_VERSION_ID).c(d!())?,
             meta,
             areas,
-            prefix_allocator,
-            // length of the raw key, exclude the meta prefix
-            max_keylen,
+            write_opts: write_opts(SyncPolicy::EveryWrite == vsdb_get_sync_policy()),
+            len_locks: (0..LEN_LOCK_NUM).map(|_| Mutex::new(())).collect(),
         })
     }
 
-    // 'step 1' and 'step 2' is not atomic in multi-threads scene,
-    // so we use a `Mutex` lock for thread safe.
-    #[allow(unused_variables)]
     fn alloc_prefix(&self) -> Pre {
-        static LK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
-        let x = LK.lock();
-
-        // step 1
-        let ret = crate::parse_prefix!(
-            self.meta.get(self.prefix_allocator.key).unwrap().unwrap()
-        );
-
-        // step 2
-        self.meta
-            .put(self.prefix_allocator.key, (1 + ret).to_be_bytes())
-            .unwrap();
-
-        ret
+        self.prefix_allocator.alloc(|ceiling| {
+            self.meta
+                .put(META_KEY_PREFIX_ALLOCATOR, ceiling.to_be_bytes())
+                .unwrap();
+        })
     }
 
-    // 'step 1' and 'step 2' is not atomic in multi-threads scene,
-    // so we use a `Mutex` lock for thread safe.
-    #[allow(unused_variables)]
     fn alloc_branch_id(&self) -> BranchID {
-        static LK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
-        let x = LK.lock();
-
-        // step 1
-        let ret = crate::parse_int!(
-            self.meta.get(META_KEY_BRANCH_ID).unwrap().unwrap(),
-            BranchID
-        );
-
-        // step 2
-        self.meta
-            .put(META_KEY_BRANCH_ID, (1 + ret).to_be_bytes())
-            .unwrap();
-
-        ret
+        self.branch_id_allocator.alloc(|ceiling| {
+            self.meta
+                .put(META_KEY_BRANCH_ID, ceiling.to_be_bytes())
+                .unwrap();
+        })
     }
 
-    // 'step 1' and 'step 2' is not atomic in multi-threads scene,
-    // so we use a `Mutex` lock for thread safe.
-    #[allow(unused_variables)]
     fn alloc_version_id(&self) -> VersionID {
-        static LK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
-        let x = LK.lock();
+        self.version_id_allocator.alloc(|ceiling| {
+            self.meta
+                .put(META_KEY_VERSION_ID, ceiling.to_be_bytes())
+                .unwrap();
+        })
+    }
 
-        // step 1
-        let ret = crate::parse_int!(
-            self.meta.get(META_KEY_VERSION_ID).unwrap().unwrap(),
-            VersionID
-        );
+    fn flush(&self) -> Result<()> {
+        self.meta.flush().c(d!())?;
+        for i in 0..DATA_SET_NUM {
+            self.meta.flush_cf(self.cf_hdr(i)).c(d!())?;
+        }
+        Ok(())
+    }
 
-        // step 2
-        self.meta
-            .put(META_KEY_VERSION_ID, (1 + ret).to_be_bytes())
-            .unwrap();
+    // make all the previous writes in the WAL durable
+    fn sync(&self) -> Result<()> {
+        if OpenMode::ReadWrite != vsdb_get_open_mode() {
+            return Ok(());
+        }
+        self.meta.flush_wal(true).c(d!())
+    }
 
-        ret
+    fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.meta.try_catch_up_with_primary().c(d!())
     }
 
-    fn area_count(&self) -> usize {
-        DATA_SET_NUM
+    // SST files are hard-linked if the `path` is on the same filesystem,
+    // so checkpoints are cheap and can be used as incremental backups
+    fn checkpoint(&self, path: &str) -> Result<()> {
+        Checkpoint::new(self.meta)
+            .c(d!())?
+            .create_checkpoint(path)
+            .c(d!())
     }
 
-    fn flush(&self) {
-        self.meta.flush().unwrap();
+    fn compact_instance(&self, instance_prefix: PreBytes) {
+        let area_idx = self.area_idx(instance_prefix);
+        let (from, to) = full_key_range(instance_prefix, &..);
+        self.meta
+            .compact_range_cf(self.cf_hdr(area_idx), Some(from), Some(to));
+    }
+
+    fn compact_all(&self) {
+        self.meta.compact_range(None::<&[u8]>, None::<&[u8]>);
         (0..DATA_SET_NUM).for_each(|i| {
-            self.meta.flush_cf(self.cf_hdr(i)).unwrap();
+            self.meta
+                .compact_range_cf(self.cf_hdr(i), None::<&[u8]>, None::<&[u8]>);
         });
     }
 
     fn iter(&self, meta_prefix: PreBytes) -> RocksIter {
-        let area_idx = self.area_idx(meta_prefix);
-
-        let inner = self
-            .meta
-            .prefix_iterator_cf(self.cf_hdr(area_idx), meta_prefix);
-
-        let mut opt = ReadOptions::default();
-        opt.set_prefix_same_as_start(true);
-
-        let inner_rev = self.meta.iterator_cf_opt(
-            self.cf_hdr(area_idx),
-            opt,
-            IteratorMode::From(
-                &self.get_upper_bound_value(meta_prefix),
-                Direction::Reverse,
-            ),
-        );
-
-        RocksIter { inner, inner_rev }
+        self.range(meta_prefix, ..)
     }
 
+    // the keys are bounded natively,
+    // so the scanning never touches any key outside the range
     fn range<'a, R: RangeBounds<&'a [u8]>>(
         &'a self,
         meta_prefix: PreBytes,
         bounds: R,
     ) -> RocksIter {
         let area_idx = self.area_idx(meta_prefix);
+        let (from, to) = full_key_range(meta_prefix, &bounds);
 
         let mut opt = ReadOptions::default();
-        let mut opt_rev = ReadOptions::default();
-
-        let mut b_lo = meta_prefix.to_vec();
-        let l = match bounds.start_bound() {
-            Bound::Included(lo) => {
-                b_lo.extend_from_slice(lo);
-                opt.set_iterate_lower_bound(b_lo.as_slice());
-                opt_rev.set_iterate_lower_bound(b_lo.as_slice());
-                b_lo.as_slice()
-            }
-            Bound::Excluded(lo) => {
-                b_lo.extend_from_slice(lo);
-                b_lo.push(0u8);
-                opt.set_iterate_lower_bound(b_lo.as_slice());
-                opt_rev.set_iterate_lower_bound(b_lo.as_slice());
-                b_lo.as_slice()
-            }
-            _ => meta_prefix.as_slice(),
-        };
-
-        let mut b_hi = meta_prefix.to_vec();
-        let h = match bounds.end_bound() {
-            Bound::Included(hi) => {
-                b_hi.extend_from_slice(hi);
-                b_hi.push(0u8);
-                opt.set_iterate_upper_bound(b_hi.as_slice());
-                opt_rev.set_iterate_upper_bound(b_hi.as_slice());
-                b_hi
-            }
-            Bound::Excluded(hi) => {
-                b_hi.extend_from_slice(hi);
-                opt.set_iterate_upper_bound(b_hi.as_slice());
-                opt_rev.set_iterate_upper_bound(b_hi.as_slice());
-                b_hi
-            }
-            _ => self.get_upper_bound_value(meta_prefix),
-        };
-
         opt.set_prefix_same_as_start(true);
-        opt_rev.set_prefix_same_as_start(true);
+        opt.set_iterate_lower_bound(from.as_slice());
+        opt.set_iterate_upper_bound(to.as_slice());
+
+        // the upper bound may be the next prefix,
+        // so the reverse scanning can not be done in the prefix mode
+        let mut opt_rev = ReadOptions::default();
+        opt_rev.set_total_order_seek(true);
+        opt_rev.set_iterate_lower_bound(from.as_slice());
+        opt_rev.set_iterate_upper_bound(to);
 
         let inner = self.meta.iterator_cf_opt(
             self.cf_hdr(area_idx),
             opt,
-            IteratorMode::From(l, Direction::Forward),
+            IteratorMode::From(&from, Direction::Forward),
         );
 
-        let inner_rev = self.meta.iterator_cf_opt(
-            self.cf_hdr(area_idx),
-            opt_rev,
-            IteratorMode::From(&h, Direction::Reverse),
-        );
+        let inner_rev =
+            self.meta
+                .iterator_cf_opt(self.cf_hdr(area_idx), opt_rev, IteratorMode::End);
 
         RocksIter { inner, inner_rev }
     }
@@ -288,38 +227,149 @@ impl Engine for RocksEngine {
         meta_prefix: PreBytes,
         key: &[u8],
         value: &[u8],
+        track_len: bool,
     ) -> Option<RawValue> {
         let area_idx = self.area_idx(meta_prefix);
 
         let mut k = meta_prefix.to_vec();
         k.extend_from_slice(key);
 
-        if key.len() > self.get_max_keylen() {
-            self.set_max_key_len(key.len());
+        let x = self.lock_len(meta_prefix, track_len);
+        let old_v = self.meta.get_cf(self.cf_hdr(area_idx), &k).unwrap();
+
+        let mut batch = WriteBatch::default();
+        batch.put_cf(self.cf_hdr(area_idx), k, value);
+        if track_len && old_v.is_none() {
+            batch.merge(meta_prefix, 1i64.to_be_bytes());
         }
+        self.meta.write_opt(batch, &self.write_opts).unwrap();
+        drop(x);
 
-        let old_v = self.meta.get_cf(self.cf_hdr(area_idx), &k).unwrap();
-        self.meta.put_cf(self.cf_hdr(area_idx), k, value).unwrap();
         old_v.map(|v| v.into_boxed_slice())
     }
 
-    fn remove(&self, meta_prefix: PreBytes, key: &[u8]) -> Option<RawValue> {
+    fn remove(
+        &self,
+        meta_prefix: PreBytes,
+        key: &[u8],
+        track_len: bool,
+    ) -> Option<RawValue> {
         let area_idx = self.area_idx(meta_prefix);
 
         let mut k = meta_prefix.to_vec();
         k.extend_from_slice(key);
+
+        let x = self.lock_len(meta_prefix, track_len);
         let old_v = self.meta.get_cf(self.cf_hdr(area_idx), &k).unwrap();
-        self.meta.delete_cf(self.cf_hdr(area_idx), k).unwrap();
+
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(self.cf_hdr(area_idx), k);
+        if track_len && old_v.is_some() {
+            batch.merge(meta_prefix, (-1i64).to_be_bytes());
+        }
+        self.meta.write_opt(batch, &self.write_opts).unwrap();
+        drop(x);
+
         old_v.map(|v| v.into_boxed_slice())
     }
 
+    // NOTE:
+    // the keys are removed natively, but on a tracked instance
+    // they have to be counted by a scanning of the range first
+    fn remove_range<'a, R: RangeBounds<&'a [u8]>>(
+        &'a self,
+        meta_prefix: PreBytes,
+        bounds: R,
+        track_len: bool,
+    ) {
+        let area_idx = self.area_idx(meta_prefix);
+        let (from, to) = full_key_range(meta_prefix, &bounds);
+
+        let x = self.lock_len(meta_prefix, track_len);
+        let mut batch = WriteBatch::default();
+        if track_len {
+            let cnt = self.range(meta_prefix, bounds).count() as i64;
+            batch.merge(meta_prefix, (-cnt).to_be_bytes());
+        }
+        batch.delete_range_cf(self.cf_hdr(area_idx), from, to);
+        self.meta.write_opt(batch, &self.write_opts).unwrap();
+        drop(x);
+    }
+
+    fn write_batch(
+        &self,
+        meta_prefix: PreBytes,
+        kvs: &[(&[u8], Option<&[u8]>)],
+        track_len: bool,
+    ) {
+        let area_idx = self.area_idx(meta_prefix);
+
+        let full_key = |k: &[u8]| [&meta_prefix[..], k].concat();
+
+        let x = self.lock_len(meta_prefix, track_len);
+        let mut batch = WriteBatch::default();
+        for (k, v) in kvs {
+            if let Some(v) = v {
+                batch.put_cf(self.cf_hdr(area_idx), full_key(k), v);
+            } else {
+                batch.delete_cf(self.cf_hdr(area_idx), full_key(k));
+            }
+        }
+        if track_len {
+            let delta = batch_len_delta(kvs, |k| {
+                self.meta
+                    .get_cf(self.cf_hdr(area_idx), full_key(k))
+                    .unwrap()
+                    .is_some()
+            });
+            batch.merge(meta_prefix, delta.to_be_bytes());
+        }
+        self.meta.write_opt(batch, &self.write_opts).unwrap();
+        drop(x);
+    }
+
     fn get_instance_len(&self, instance_prefix: PreBytes) -> u64 {
         crate::parse_int!(self.meta.get(instance_prefix).unwrap().unwrap(), u64)
     }
 
     fn set_instance_len(&self, instance_prefix: PreBytes, new_len: u64) {
+        let x = self.lock_len(instance_prefix, true);
         self.meta
-            .put(instance_prefix, new_len.to_be_bytes())
+            .put_opt(instance_prefix, new_len.to_be_bytes(), &self.write_opts)
+            .unwrap();
+        drop(x);
+    }
+
+    fn clear_instance(&self, instance_prefix: PreBytes) {
+        let area_idx = self.area_idx(instance_prefix);
+        let (from, to) = full_key_range(instance_prefix, &..);
+
+        let x = self.lock_len(instance_prefix, true);
+        let mut batch = WriteBatch::default();
+        batch.delete_range_cf(self.cf_hdr(area_idx), from, to);
+        batch.put(instance_prefix, 0u64.to_be_bytes());
+        self.meta.write_opt(batch, &self.write_opts).unwrap();
+        drop(x);
+    }
+
+    // the length of each instance is recorded in the 'meta' with its prefix,
+    // all other keys in the 'meta' are shorter than a prefix
+    fn instance_prefixes(&self) -> Vec<PreBytes> {
+        let mut opt = ReadOptions::default();
+        opt.set_total_order_seek(true);
+
+        self.meta
+            .iterator_opt(IteratorMode::Start, opt)
+            .map(|(k, _)| k)
+            .filter(|k| k.len() == PREFIX_SIZE)
+            .map(|k| k[..].try_into().unwrap())
+            .collect()
+    }
+
+    fn remove_instance(&self, instance_prefix: PreBytes) {
+        self.remove_prefix(instance_prefix);
+        self.meta
+            .delete_opt(instance_prefix, &self.write_opts)
             .unwrap();
     }
 }
@@ -334,7 +384,7 @@ impl Iterator for RocksIter {
     fn next(&mut self) -> Option<Self::Item> {
         self.inner
             .next()
-            .map(|(ik, iv)| (ik[META_KEY_SIZ..].into(), iv))
+            .map(|(ik, iv)| (ik[PREFIX_SIZE..].into(), iv))
     }
 }
 
@@ -342,7 +392,7 @@ impl DoubleEndedIterator for RocksIter {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.inner_rev
             .next()
-            .map(|(ik, iv)| (ik[META_KEY_SIZ..].into(), iv))
+            .map(|(ik, iv)| (ik[PREFIX_SIZE..].into(), iv))
     }
 }
 
@@ -361,7 +411,7 @@ impl PreAllocator {
         )
     }
 
-    // fn next(base: &[u8]) -> [u8; META_KEY_SIZ] {
+    // fn next(base: &[u8]) -> [u8; PREFIX_SIZE] {
     //     (crate::parse_prefix!(base) + 1).to_be_bytes()
     // }
 }
@@ -385,6 +435,7 @@ fn rocksdb_open() -> Result<(DB, Vec<String>)> {
     // cfg.set_use_direct_io_for_flush_and_compaction(true);
     cfg.set_write_buffer_size(512 * MB as usize);
     cfg.set_max_write_buffer_number(3);
+    cfg.set_merge_operator_associative("vsdb_instance_len", instance_len_merge);
 
     #[cfg(feature = "compress")]
     {
@@ -398,12 +449,70 @@ fn rocksdb_open() -> Result<(DB, Vec<String>)> {
 
     let cfhdrs = (0..DATA_SET_NUM).map(|i| i.to_string()).collect::<Vec<_>>();
 
-    let cfs = cfhdrs
-        .iter()
-        .map(|i| ColumnFamilyDescriptor::new(i, cfg.clone()))
-        .collect::<Vec<_>>();
-
-    let db = DB::open_cf_descriptors(&cfg, &dir, cfs).c(d!())?;
+    let db = match vsdb_get_open_mode() {
+        OpenMode::ReadWrite => {
+            let cfs = cfhdrs
+                .iter()
+                .map(|i| ColumnFamilyDescriptor::new(i, cfg.clone()))
+                .collect::<Vec<_>>();
+            DB::open_cf_descriptors(&cfg, &dir, cfs).c(d!())?
+        }
+        OpenMode::ReadOnly => {
+            DB::open_cf_for_read_only(&cfg, &dir, &cfhdrs, false).c(d!())?
+        }
+        OpenMode::Secondary => {
+            // used to store the info logs of this secondary instance
+            let secondary_dir = format!("{}/__SECONDARY__/{}", dir, process::id());
+            fs::create_dir_all(&secondary_dir).c(d!())?;
+            cfg.set_max_open_files(-1);
+            DB::open_cf_as_secondary(&cfg, &dir, &secondary_dir, &cfhdrs).c(d!())?
+        }
+    };
 
     Ok((db, cfhdrs))
 }
+
+fn write_opts(sync: bool) -> WriteOptions {
+    let mut opts = WriteOptions::default();
+    opts.set_sync(sync);
+    opts
+}
+
+// the length records of instances are updated by merging deltas,
+// which are written in the same batch with the data,
+// so the records themselves are never read before the writes,
+// and they are exact across crashes
+fn instance_len_merge(
+    _: &[u8],
+    existing: Option<&[u8]>,
+    operands: &mut MergeOperands,
+) -> Option<Vec<u8>> {
+    let l = existing.map(|l| crate::parse_int!(l, i64)).unwrap_or(0);
+    let l = operands.fold(l, |acc, delta| acc + crate::parse_int!(delta, i64));
+    Some(l.to_be_bytes().to_vec())
+}
+
+fn init_meta(
+    meta: &DB,
+    prefix_allocator: &PreAllocator,
+    initial_value: PreBytes,
+) -> Result<()> {
+    if meta.get(META_KEY_BRANCH_ID).c(d!())?.is_none() {
+        meta.put(
+            META_KEY_BRANCH_ID,
+            (1 + INITIAL_BRANCH_ID as usize).to_be_bytes(),
+        )
+        .c(d!())?;
+    }
+
+    if meta.get(META_KEY_VERSION_ID).c(d!())?.is_none() {
+        meta.put(META_KEY_VERSION_ID, 0_usize.to_be_bytes())
+            .c(d!())?;
+    }
+
+    if meta.get(prefix_allocator.key).c(d!())?.is_none() {
+        meta.put(prefix_allocator.key, initial_value).c(d!())?;
+    }
+
+    Ok(())
+}
@@ -1,12 +1,18 @@
+use super::{batch_len_delta, full_key_range, IdAllocator};
 use crate::common::{
-    vsdb_get_base_dir, vsdb_set_base_dir, BranchID, Engine, Pre, PreBytes, RawKey,
-    RawValue, VersionID, GB, INITIAL_BRANCH_ID, PREFIX_SIZE, RESERVED_ID_CNT,
+    vsdb_get_base_dir, vsdb_get_open_mode, vsdb_set_base_dir, BranchID, Engine,
+    vsdb_get_sync_policy, OpenMode, Pre, PreBytes, RawKey, RawValue, SyncPolicy,
+    VersionID, GB, INITIAL_BRANCH_ID, PREFIX_SIZE, RESERVED_ID_CNT,
 };
-use once_cell::sync::Lazy;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use ruc::*;
-use sled::{Config, Db, IVec, Iter, Mode, Tree};
-use std::ops::{Bound, RangeBounds};
+use sled::{Batch, Config, Db, Iter, Mode, Tree};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    mem,
+    ops::RangeBounds,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 // the 'prefix search' in sled is just a global scaning,
 // use a relative larger number to sharding the `Tree` pressure.
@@ -18,15 +24,63 @@ const DATA_SET_NUM: usize = u8::MAX as usize;
 const META_KEY_BRANCH_ID: [u8; 1] = [u8::MAX - 1];
 const META_KEY_VERSION_ID: [u8; 1] = [u8::MAX - 2];
 const META_KEY_PREFIX_ALLOCATOR: [u8; 1] = [u8::MIN];
+const META_KEY_LEN_DIRTY_TAG: u8 = u8::MAX - 3;
+
+// flush the changes of instance lengths after so many writes
+const LEN_FLUSH_INTERVAL: usize = 8192;
+
+// sled has no native range deletion,
+// so the keys are removed in batches to avoid a huge memory usage
+const REMOVE_BATCH_SIZE: usize = 8192;
 
 pub(crate) struct SledEngine {
     meta: Db,
     areas: Vec<Tree>,
-    prefix_allocator: PreAllocator,
+    prefix_allocator: IdAllocator,
+    branch_id_allocator: IdAllocator,
+    version_id_allocator: IdAllocator,
+    // unflushed changes of the instance lengths
+    len_deltas: Mutex<HashMap<PreBytes, i64>>,
+    // writers hold the read guard, the flushing holds the write guard
+    len_flush_lk: RwLock<()>,
+    len_op_cnt: AtomicUsize,
+    // writers hold the read guard, the checkpoint holds the write guard,
+    // so the exported data will never contain any half-done write;
+    // the read guard is recursive, `clear_instance` calls `set_instance_len`
+    ckpt_lk: RwLock<()>,
+    // `SyncPolicy::EveryWrite`
+    sync_every_write: bool,
+}
+
+impl SledEngine {
+    #[inline(always)]
+    fn area_idx(&self, meta_prefix: PreBytes) -> usize {
+        meta_prefix[0] as usize % DATA_SET_NUM
+    }
+
+    // all trees share the same log, flushing one of them is enough
+    #[inline(always)]
+    fn sync_if_needed(&self) {
+        if self.sync_every_write {
+            self.meta.flush().unwrap();
+        }
+    }
 }
 
 impl Engine for SledEngine {
     fn new() -> Result<Self> {
+        // sled always takes an exclusive lock of its files and
+        // can not open them without the write permissions
+        match vsdb_get_open_mode() {
+            OpenMode::ReadWrite => {}
+            OpenMode::ReadOnly => {
+                return Err(eg!("The read-only mode is not supported by sled"));
+            }
+            OpenMode::Secondary => {
+                return Err(eg!("The secondary mode is not supported by sled"));
+            }
+        }
+
         let meta = sled_open().c(d!())?;
 
         let areas = (0..DATA_SET_NUM)
@@ -35,155 +89,109 @@ impl Engine for SledEngine {
 
         let (prefix_allocator, initial_value) = PreAllocator::init();
 
-        if meta.get(&META_KEY_BRANCH_ID).c(d!())?.is_none() {
-            meta.insert(
-                META_KEY_BRANCH_ID,
-                (1 + INITIAL_BRANCH_ID as usize).to_be_bytes(),
-            )
-            .c(d!())?;
-        }
-
-        if meta.get(&META_KEY_VERSION_ID).c(d!())?.is_none() {
-            meta.insert(META_KEY_VERSION_ID, 0_usize.to_be_bytes())
-                .c(d!())?;
-        }
+        init_meta(&meta, &prefix_allocator, initial_value).c(d!())?;
 
-        if meta.get(prefix_allocator.key).c(d!())?.is_none() {
-            meta.insert(prefix_allocator.key, initial_value).c(d!())?;
-        }
+        // allocations start from the ceilings of the last blocks
+        let id_allocator = |key: &[u8]| -> Result<IdAllocator> {
+            meta.get(key)
+                .c(d!())?
+                .map(|v| IdAllocator::new(crate::parse_int!(v, u64)))
+                .c(d!())
+        };
 
-        Ok(SledEngine {
+        let engine = SledEngine {
+            prefix_allocator: id_allocator(&prefix_allocator.key).c(d!())?,
+            branch_id_allocator: id_allocator(&META_KEY_BRANCH_ID).c(d!())?,
+            version_id_allocator: id_allocator(&META_KEY_VERSION_ID).c(d!())?,
             meta,
             areas,
-            prefix_allocator,
-        })
+            len_deltas: Mutex::new(HashMap::new()),
+            len_flush_lk: RwLock::new(()),
+            ckpt_lk: RwLock::new(()),
+            len_op_cnt: AtomicUsize::new(0),
+            sync_every_write: SyncPolicy::EveryWrite == vsdb_get_sync_policy(),
+        };
+
+        engine.recover_len_records();
+
+        Ok(engine)
     }
 
-    // 'step 1' and 'step 2' is not atomic in multi-threads scene,
-    // so we use a `Mutex` lock for thread safe.
-    #[allow(unused_variables)]
     fn alloc_prefix(&self) -> Pre {
-        static LK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
-        let x = LK.lock();
-
-        // step 1
-        let ret = crate::parse_prefix!(
+        self.prefix_allocator.alloc(|ceiling| {
             self.meta
-                .get(self.prefix_allocator.key)
-                .unwrap()
-                .unwrap()
-                .as_ref()
-        );
-
-        // step 2
-        self.meta
-            .insert(self.prefix_allocator.key, (1 + ret).to_be_bytes())
-            .unwrap();
-
-        ret
+                .insert(META_KEY_PREFIX_ALLOCATOR, ceiling.to_be_bytes())
+                .unwrap();
+        })
     }
 
-    // 'step 1' and 'step 2' is not atomic in multi-threads scene,
-    // so we use a `Mutex` lock for thread safe.
-    #[allow(unused_variables)]
     fn alloc_branch_id(&self) -> BranchID {
-        static LK: Lazy<Mutex<bool>> = Lazy::new(|| Mutex::new(false));
-        let x = LK.lock();
-
-        // step 1
-        let ret = crate::parse_int!(
-            self.meta.get(META_KEY_BRANCH_ID).unwrap().unwrap().as_ref(),
-            BranchID
-        );
-
-        // step 2
-        self.meta
-            .insert(META_KEY_BRANCH_ID, (1 + ret).to_be_bytes())
-            .unwrap();
-
-        ret
+        self.branch_id_allocator.alloc(|ceiling| {
+            self.meta
+                .insert(META_KEY_BRANCH_ID, ceiling.to_be_bytes())
+                .unwrap();
+        })
     }
 
-    // 'step 1' and 'step 2' is not atomic in multi-threads scene,
-    // so we use a `Mutex` lock for thread safe.
-    #[allow(unused_variables)]
     fn alloc_version_id(&self) -> VersionID {
-        static LK: Lazy<Mutex<bool>> = Lazy::new(|| Mutex::new(false));
-        let x = LK.lock();
-
-        // step 1
-        let ret = crate::parse_int!(
+        self.version_id_allocator.alloc(|ceiling| {
             self.meta
-                .get(META_KEY_VERSION_ID)
-                .unwrap()
-                .unwrap()
-                .as_ref(),
-            VersionID
-        );
-
-        // step 2
-        self.meta
-            .insert(META_KEY_VERSION_ID, (1 + ret).to_be_bytes())
-            .unwrap();
+                .insert(META_KEY_VERSION_ID, ceiling.to_be_bytes())
+                .unwrap();
+        })
+    }
 
-        ret
+    fn flush(&self) -> Result<()> {
+        self.flush_len_deltas();
+        for area in self.areas.iter() {
+            area.flush().c(d!())?;
+        }
+        Ok(())
     }
 
-    fn area_count(&self) -> usize {
-        self.areas.len()
+    // the lost length records will be recovered by the dirty marks
+    fn sync(&self) -> Result<()> {
+        self.meta.flush().c(d!()).map(|_| ())
     }
 
-    fn flush(&self) {
-        (0..self.areas.len()).for_each(|i| {
-            self.areas[i].flush().unwrap();
-        });
+    fn try_catch_up_with_primary(&self) -> Result<()> {
+        Err(eg!("The secondary mode is not supported by sled"))
     }
 
-    fn iter(&self, meta_prefix: PreBytes) -> SledIter {
-        let area_idx = self.area_idx(meta_prefix);
+    // sled has no native checkpoints, export all trees
+    // (include the 'meta') into a newly created DB,
+    // all writes are blocked until the exporting is done
+    #[allow(unused_variables)]
+    fn checkpoint(&self, path: &str) -> Result<()> {
+        let x = self.ckpt_lk.write();
+        self.flush_len_deltas();
+        let db = sled_cfg(path).create_new(true).open().c(d!())?;
+        db.import(self.meta.export());
+        db.flush().c(d!()).map(|_| ())
+    }
 
-        SledIter {
-            inner: self.areas[area_idx].scan_prefix(meta_prefix.as_slice()),
-            bounds: (Bound::Unbounded, Bound::Unbounded),
-        }
+    // sled does not support manual compactions,
+    // the space of removed data is reclaimed by its own GC
+    fn compact_instance(&self, _: PreBytes) {}
+
+    fn compact_all(&self) {}
+
+    fn iter(&self, meta_prefix: PreBytes) -> SledIter {
+        self.range(meta_prefix, ..)
     }
 
+    // the keys are bounded natively,
+    // so the scanning never touches any key outside the range
     fn range<'a, R: RangeBounds<&'a [u8]>>(
         &'a self,
         meta_prefix: PreBytes,
         bounds: R,
     ) -> SledIter {
         let area_idx = self.area_idx(meta_prefix);
-
-        let mut b_lo = meta_prefix.to_vec();
-        let l = match bounds.start_bound() {
-            Bound::Included(lo) => {
-                b_lo.extend_from_slice(lo);
-                Bound::Included(IVec::from(b_lo))
-            }
-            Bound::Excluded(lo) => {
-                b_lo.extend_from_slice(lo);
-                Bound::Excluded(IVec::from(b_lo))
-            }
-            Bound::Unbounded => Bound::Unbounded,
-        };
-
-        let mut b_hi = meta_prefix.to_vec();
-        let h = match bounds.end_bound() {
-            Bound::Included(hi) => {
-                b_hi.extend_from_slice(hi);
-                Bound::Included(IVec::from(b_hi))
-            }
-            Bound::Excluded(hi) => {
-                b_hi.extend_from_slice(hi);
-                Bound::Excluded(IVec::from(b_hi))
-            }
-            Bound::Unbounded => Bound::Unbounded,
-        };
+        let (from, to) = full_key_range(meta_prefix, &bounds);
 
         SledIter {
-            inner: self.areas[area_idx].scan_prefix(meta_prefix.as_slice()),
-            bounds: (l, h),
+            inner: self.areas[area_idx].range(from..to),
         }
     }
 
@@ -200,79 +208,301 @@ impl Engine for SledEngine {
             .map(|iv| iv.to_vec().into_boxed_slice())
     }
 
+    #[allow(unused_variables)]
     fn insert(
         &self,
         meta_prefix: PreBytes,
         key: &[u8],
         value: &[u8],
+        track_len: bool,
     ) -> Option<RawValue> {
+        let ck = self.ckpt_lk.read_recursive();
         let area_idx = self.area_idx(meta_prefix);
 
         let mut k = meta_prefix.to_vec();
         k.extend_from_slice(key);
         let k = k.into_boxed_slice();
 
-        self.areas[area_idx]
-            .insert(k, value)
-            .unwrap()
-            .map(|iv| iv.to_vec().into_boxed_slice())
+        let old_v = if track_len {
+            let x = self.len_flush_lk.read();
+            self.mark_len_dirty(meta_prefix);
+            let old_v = self.areas[area_idx].insert(k, value).unwrap();
+            if old_v.is_none() {
+                self.add_len_delta(meta_prefix, 1);
+            }
+            drop(x);
+            self.try_flush_len_deltas();
+            old_v
+        } else {
+            self.areas[area_idx].insert(k, value).unwrap()
+        };
+        self.sync_if_needed();
+
+        old_v.map(|iv| iv.to_vec().into_boxed_slice())
     }
 
-    fn remove(&self, meta_prefix: PreBytes, key: &[u8]) -> Option<RawValue> {
+    #[allow(unused_variables)]
+    fn remove(
+        &self,
+        meta_prefix: PreBytes,
+        key: &[u8],
+        track_len: bool,
+    ) -> Option<RawValue> {
+        let ck = self.ckpt_lk.read_recursive();
         let area_idx = self.area_idx(meta_prefix);
 
         let mut k = meta_prefix.to_vec();
         k.extend_from_slice(key);
         let k = k.into_boxed_slice();
 
-        self.areas[area_idx]
-            .remove(k)
-            .unwrap()
-            .map(|iv| iv.to_vec().into_boxed_slice())
+        let old_v = if track_len {
+            let x = self.len_flush_lk.read();
+            self.mark_len_dirty(meta_prefix);
+            let old_v = self.areas[area_idx].remove(k).unwrap();
+            if old_v.is_some() {
+                self.add_len_delta(meta_prefix, -1);
+            }
+            drop(x);
+            self.try_flush_len_deltas();
+            old_v
+        } else {
+            self.areas[area_idx].remove(k).unwrap()
+        };
+        self.sync_if_needed();
+
+        old_v.map(|iv| iv.to_vec().into_boxed_slice())
+    }
+
+    #[allow(unused_variables)]
+    fn remove_range<'a, R: RangeBounds<&'a [u8]>>(
+        &'a self,
+        meta_prefix: PreBytes,
+        bounds: R,
+        track_len: bool,
+    ) {
+        let ck = self.ckpt_lk.read_recursive();
+        let area = &self.areas[self.area_idx(meta_prefix)];
+        let (from, to) = full_key_range(meta_prefix, &bounds);
+
+        let x = track_len.then(|| {
+            let x = self.len_flush_lk.read();
+            self.mark_len_dirty(meta_prefix);
+            x
+        });
+
+        let mut batch = Batch::default();
+        let mut cnt = 0;
+        for k in area.range(from..to).keys().map(|k| k.unwrap()) {
+            batch.remove(k);
+            cnt += 1;
+            if 0 == cnt % REMOVE_BATCH_SIZE {
+                area.apply_batch(mem::take(&mut batch)).unwrap();
+            }
+        }
+        area.apply_batch(batch).unwrap();
+
+        if let Some(x) = x {
+            self.add_len_delta(meta_prefix, -(cnt as i64));
+            drop(x);
+            self.try_flush_len_deltas();
+        }
+        self.sync_if_needed();
+    }
+
+    #[allow(unused_variables)]
+    fn write_batch(
+        &self,
+        meta_prefix: PreBytes,
+        kvs: &[(&[u8], Option<&[u8]>)],
+        track_len: bool,
+    ) {
+        let ck = self.ckpt_lk.read_recursive();
+        let area = &self.areas[self.area_idx(meta_prefix)];
+
+        let full_key = |k: &[u8]| [&meta_prefix[..], k].concat();
+
+        let mut batch = Batch::default();
+        for (k, v) in kvs {
+            if let Some(v) = v {
+                batch.insert(full_key(k), *v);
+            } else {
+                batch.remove(full_key(k));
+            }
+        }
+
+        if track_len {
+            let x = self.len_flush_lk.read();
+            self.mark_len_dirty(meta_prefix);
+            let delta =
+                batch_len_delta(kvs, |k| area.contains_key(full_key(k)).unwrap());
+            area.apply_batch(batch).unwrap();
+            self.add_len_delta(meta_prefix, delta);
+            drop(x);
+            self.try_flush_len_deltas();
+        } else {
+            area.apply_batch(batch).unwrap();
+        }
+        self.sync_if_needed();
     }
 
+    // the read guard makes sure that the length record and the delta
+    // are not read in the middle of a flushing
+    #[allow(unused_variables)]
     fn get_instance_len(&self, instance_prefix: PreBytes) -> u64 {
-        crate::parse_int!(self.meta.get(instance_prefix).unwrap().unwrap(), u64)
+        let x = self.len_flush_lk.read();
+        let l = crate::parse_int!(self.meta.get(instance_prefix).unwrap().unwrap(), u64);
+        let delta = self.len_deltas.lock().get(&instance_prefix).copied();
+        (l as i64 + delta.unwrap_or(0)) as u64
     }
 
+    #[allow(unused_variables)]
     fn set_instance_len(&self, instance_prefix: PreBytes, new_len: u64) {
+        let ck = self.ckpt_lk.read_recursive();
+        let mut deltas = self.len_deltas.lock();
+        let mut batch = Batch::default();
+        batch.insert(&instance_prefix[..], &new_len.to_be_bytes()[..]);
+        if deltas.remove(&instance_prefix).is_some() {
+            batch.remove(len_dirty_key(instance_prefix));
+        }
+        self.meta.apply_batch(batch).unwrap();
+        self.sync_if_needed();
+    }
+
+    // a crash in the middle of the removing will be recovered
+    // by recounting the instance at the next startup
+    #[allow(unused_variables)]
+    fn clear_instance(&self, instance_prefix: PreBytes) {
+        let ck = self.ckpt_lk.read_recursive();
+        let x = self.len_flush_lk.read();
+        self.mark_len_dirty(instance_prefix);
+        self.remove_prefix(instance_prefix);
+        self.set_instance_len(instance_prefix, 0);
+        drop(x);
+    }
+
+    // the length of each instance is recorded in the 'meta' with its prefix,
+    // all other keys in the 'meta' have a different length
+    fn instance_prefixes(&self) -> Vec<PreBytes> {
         self.meta
-            .insert(instance_prefix, new_len.to_be_bytes())
-            .unwrap();
+            .iter()
+            .keys()
+            .map(|k| k.unwrap())
+            .filter(|k| k.len() == PREFIX_SIZE)
+            .map(|k| k[..].try_into().unwrap())
+            .collect()
+    }
+
+    #[allow(unused_variables)]
+    fn remove_instance(&self, instance_prefix: PreBytes) {
+        let ck = self.ckpt_lk.read_recursive();
+        self.remove_prefix(instance_prefix);
+
+        let mut deltas = self.len_deltas.lock();
+        let mut batch = Batch::default();
+        batch.remove(&instance_prefix[..]);
+        if deltas.remove(&instance_prefix).is_some() {
+            batch.remove(len_dirty_key(instance_prefix));
+        }
+        self.meta.apply_batch(batch).unwrap();
+        self.sync_if_needed();
+    }
+}
+
+// sled has no merge operators, so the changes of instance lengths
+// are accumulated in memory and written to the 'meta' in batches.
+//
+// Before the first change of an instance after each flushing,
+// a dirty mark is written to the 'meta', it will be removed along with
+// the flushing of the length record; writes of sled are recovered in order,
+// so any instance whose length might be lost in a crash still has
+// its dirty mark, and will be recounted at the next startup.
+impl SledEngine {
+    // must be called before writing the data
+    fn mark_len_dirty(&self, instance_prefix: PreBytes) {
+        if let Entry::Vacant(e) = self.len_deltas.lock().entry(instance_prefix) {
+            self.meta.insert(len_dirty_key(instance_prefix), []).unwrap();
+            e.insert(0);
+        }
+    }
+
+    fn add_len_delta(&self, instance_prefix: PreBytes, delta: i64) {
+        *self.len_deltas.lock().entry(instance_prefix).or_insert(0) += delta;
+    }
+
+    #[allow(clippy::manual_is_multiple_of)]
+    fn try_flush_len_deltas(&self) {
+        let cnt = 1 + self.len_op_cnt.fetch_add(1, Ordering::Relaxed);
+        if 0 == cnt % LEN_FLUSH_INTERVAL {
+            self.flush_len_deltas();
+        }
+    }
+
+    // the write guard makes sure that there are
+    // no half-done writes when the dirty marks are removed
+    #[allow(unused_variables)]
+    fn flush_len_deltas(&self) {
+        let x = self.len_flush_lk.write();
+        let mut deltas = self.len_deltas.lock();
+
+        let mut batch = Batch::default();
+        for (prefix, delta) in deltas.drain() {
+            if let Some(l) = self.meta.get(prefix).unwrap() {
+                let l = (crate::parse_int!(l, u64) as i64 + delta) as u64;
+                batch.insert(&prefix[..], &l.to_be_bytes()[..]);
+            }
+            batch.remove(len_dirty_key(prefix));
+        }
+        self.meta.apply_batch(batch).unwrap();
+    }
+
+    // recount all the instances that have a dirty mark
+    fn recover_len_records(&self) {
+        let dirty = self
+            .meta
+            .scan_prefix([META_KEY_LEN_DIRTY_TAG])
+            .keys()
+            .map(|k| k.unwrap())
+            .filter(|k| k.len() == 1 + PREFIX_SIZE)
+            .map(|k| k[1..].try_into().unwrap())
+            .collect::<Vec<PreBytes>>();
+
+        let mut deltas = self.len_deltas.lock();
+        for prefix in dirty {
+            let l = self.meta.get(prefix).unwrap();
+            let cnt = self.iter(prefix).count() as i64;
+            let delta = l.map(|l| cnt - crate::parse_int!(l, u64) as i64);
+            deltas.insert(prefix, delta.unwrap_or(0));
+        }
+        drop(deltas);
+
+        self.flush_len_deltas();
     }
 }
 
 pub struct SledIter {
     inner: Iter,
-    bounds: (Bound<IVec>, Bound<IVec>),
 }
 
 impl Iterator for SledIter {
     type Item = (RawKey, RawValue);
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some((k, v)) = self.inner.next().map(|i| i.unwrap()) {
-            if self.bounds.contains(&k) {
-                return Some((
-                    k[PREFIX_SIZE..].to_vec().into_boxed_slice(),
-                    v.to_vec().into_boxed_slice(),
-                ));
-            }
-        }
-        None
+        self.inner.next().map(|i| i.unwrap()).map(|(k, v)| {
+            (
+                k[PREFIX_SIZE..].to_vec().into_boxed_slice(),
+                v.to_vec().into_boxed_slice(),
+            )
+        })
     }
 }
 
 impl DoubleEndedIterator for SledIter {
     fn next_back(&mut self) -> Option<Self::Item> {
-        while let Some((k, v)) = self.inner.next_back().map(|i| i.unwrap()) {
-            if self.bounds.contains(&k) {
-                return Some((
-                    k[PREFIX_SIZE..].to_vec().into_boxed_slice(),
-                    v.to_vec().into_boxed_slice(),
-                ));
-            }
-        }
-        None
+        self.inner.next_back().map(|i| i.unwrap()).map(|(k, v)| {
+            (
+                k[PREFIX_SIZE..].to_vec().into_boxed_slice(),
+                v.to_vec().into_boxed_slice(),
+            )
+        })
     }
 }
 
@@ -292,14 +522,49 @@ impl PreAllocator {
     }
 }
 
+fn init_meta(
+    meta: &Db,
+    prefix_allocator: &PreAllocator,
+    initial_value: PreBytes,
+) -> Result<()> {
+    if meta.get(META_KEY_BRANCH_ID).c(d!())?.is_none() {
+        meta.insert(
+            META_KEY_BRANCH_ID,
+            (1 + INITIAL_BRANCH_ID as usize).to_be_bytes(),
+        )
+        .c(d!())?;
+    }
+
+    if meta.get(META_KEY_VERSION_ID).c(d!())?.is_none() {
+        meta.insert(META_KEY_VERSION_ID, 0_usize.to_be_bytes())
+            .c(d!())?;
+    }
+
+    if meta.get(prefix_allocator.key).c(d!())?.is_none() {
+        meta.insert(prefix_allocator.key, initial_value).c(d!())?;
+    }
+
+    Ok(())
+}
+
+fn len_dirty_key(instance_prefix: PreBytes) -> Vec<u8> {
+    let mut k = vec![META_KEY_LEN_DIRTY_TAG];
+    k.extend_from_slice(&instance_prefix);
+    k
+}
+
 fn sled_open() -> Result<Db> {
     let dir = vsdb_get_base_dir();
 
     // avoid setting again on an opened DB
     info_omit!(vsdb_set_base_dir(&dir));
 
+    sled_cfg(&dir).open().c(d!())
+}
+
+fn sled_cfg(dir: &str) -> Config {
     let mut cfg = Config::new()
-        .path(&dir)
+        .path(dir)
         .mode(Mode::HighThroughput)
         .cache_capacity(10 * GB);
 
@@ -313,5 +578,5 @@ fn sled_open() -> Result<Db> {
         cfg = cfg.use_compression(false);
     }
 
-    cfg.open().c(d!())
+    cfg
 }
@@ -0,0 +1,220 @@
+//!
+//! # Entry API
+//!
+//! A `std`-like entry API shared by all the map-like containers,
+//! the existence of the key is checked only once when the entry is created,
+//! so a read-modify-write can be done with a single lookup.
+//!
+
+use std::ops::{Deref, DerefMut};
+
+/// The map-like containers that can offer an [`Entry`].
+pub trait EntryHdr {
+    /// The key as seen by users.
+    type Key;
+    /// The value as seen by users.
+    type Value;
+
+    /// Get the value of an existing key.
+    fn entry_get(&self, key: &Self::Key) -> Option<Self::Value>;
+
+    /// Insert or overwrite the value of a key.
+    fn entry_set(&mut self, key: &Self::Key, value: &Self::Value);
+
+    /// Remove an existing key.
+    fn entry_remove(&mut self, key: &Self::Key);
+}
+
+/// A view into a single entry of a map, which may either be vacant or occupied.
+pub enum Entry<'a, H: EntryHdr> {
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, H>),
+    /// A vacant entry.
+    Vacant(VacantEntry<'a, H>),
+}
+
+impl<'a, H: EntryHdr> Entry<'a, H> {
+    #[inline(always)]
+    pub(crate) fn new(hdr: &'a mut H, key: H::Key) -> Self {
+        if let Some(value) = hdr.entry_get(&key) {
+            Entry::Occupied(OccupiedEntry { hdr, key, value })
+        } else {
+            Entry::Vacant(VacantEntry { hdr, key })
+        }
+    }
+
+    /// The key of this entry.
+    #[inline(always)]
+    pub fn key(&self) -> &H::Key {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Ensure a value is in the entry by inserting the `default` if empty.
+    #[inline(always)]
+    pub fn or_insert(self, default: H::Value) -> EntryMut<'a, H> {
+        self.or_insert_with(|| default)
+    }
+
+    /// Ensure a value is in the entry by inserting the result of `f` if empty.
+    #[inline(always)]
+    pub fn or_insert_with<F>(self, f: F) -> EntryMut<'a, H>
+    where
+        F: FnOnce() -> H::Value,
+    {
+        self.or_insert_with_key(|_| f())
+    }
+
+    /// Same as `or_insert_with`, but the key is passed to `f`.
+    #[inline(always)]
+    pub fn or_insert_with_key<F>(self, f: F) -> EntryMut<'a, H>
+    where
+        F: FnOnce(&H::Key) -> H::Value,
+    {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let v = f(e.key());
+                e.insert(v)
+            }
+        }
+    }
+
+    /// Ensure a value is in the entry by inserting the default value if empty.
+    #[inline(always)]
+    pub fn or_default(self) -> EntryMut<'a, H>
+    where
+        H::Value: Default,
+    {
+        self.or_insert_with(Default::default)
+    }
+
+    /// Modify the value of an occupied entry in place,
+    /// the new value is written back before returning.
+    #[inline(always)]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut H::Value),
+    {
+        match self {
+            Entry::Occupied(mut e) => {
+                f(&mut e.value);
+                e.hdr.entry_set(&e.key, &e.value);
+                Entry::Occupied(e)
+            }
+            Entry::Vacant(e) => Entry::Vacant(e),
+        }
+    }
+}
+
+/// A view into an occupied entry, it holds the current value.
+pub struct OccupiedEntry<'a, H: EntryHdr> {
+    hdr: &'a mut H,
+    key: H::Key,
+    value: H::Value,
+}
+
+impl<'a, H: EntryHdr> OccupiedEntry<'a, H> {
+    /// The key of this entry.
+    #[inline(always)]
+    pub fn key(&self) -> &H::Key {
+        &self.key
+    }
+
+    /// The value of this entry.
+    #[inline(always)]
+    pub fn get(&self) -> &H::Value {
+        &self.value
+    }
+
+    /// Convert the entry into a mutable reference to its value,
+    /// changes will be written back when it is dropped.
+    #[inline(always)]
+    pub fn into_mut(self) -> EntryMut<'a, H> {
+        EntryMut {
+            hdr: self.hdr,
+            key: self.key,
+            value: self.value,
+        }
+    }
+
+    /// Set the value of this entry, and return the old one.
+    #[inline(always)]
+    pub fn insert(&mut self, value: H::Value) -> H::Value {
+        self.hdr.entry_set(&self.key, &value);
+        std::mem::replace(&mut self.value, value)
+    }
+
+    /// Remove the entry from the map, and return its value.
+    #[inline(always)]
+    pub fn remove(self) -> H::Value {
+        self.remove_entry().1
+    }
+
+    /// Remove the entry from the map, and return its key and value.
+    #[inline(always)]
+    pub fn remove_entry(self) -> (H::Key, H::Value) {
+        self.hdr.entry_remove(&self.key);
+        (self.key, self.value)
+    }
+}
+
+/// A view into a vacant entry.
+pub struct VacantEntry<'a, H: EntryHdr> {
+    hdr: &'a mut H,
+    key: H::Key,
+}
+
+impl<'a, H: EntryHdr> VacantEntry<'a, H> {
+    /// The key of this entry.
+    #[inline(always)]
+    pub fn key(&self) -> &H::Key {
+        &self.key
+    }
+
+    /// Take the ownership of the key.
+    #[inline(always)]
+    pub fn into_key(self) -> H::Key {
+        self.key
+    }
+
+    /// Set the value of this entry.
+    #[inline(always)]
+    pub fn insert(self, value: H::Value) -> EntryMut<'a, H> {
+        // the value will be written when the `EntryMut` is dropped
+        EntryMut {
+            hdr: self.hdr,
+            key: self.key,
+            value,
+        }
+    }
+}
+
+/// A mutable reference to the value of an entry,
+/// changes will be written back when it is dropped.
+pub struct EntryMut<'a, H: EntryHdr> {
+    hdr: &'a mut H,
+    key: H::Key,
+    value: H::Value,
+}
+
+impl<'a, H: EntryHdr> Drop for EntryMut<'a, H> {
+    fn drop(&mut self) {
+        self.hdr.entry_set(&self.key, &self.value);
+    }
+}
+
+impl<'a, H: EntryHdr> Deref for EntryMut<'a, H> {
+    type Target = H::Value;
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'a, H: EntryHdr> DerefMut for EntryMut<'a, H> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
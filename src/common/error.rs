@@ -0,0 +1,191 @@
+//!
+//! # Typed errors
+//!
+//! The failure kinds of the ende layer and the version management,
+//! so that callers can match on them instead of parsing the messages.
+//!
+
+use super::Pre;
+use ruc::RucError;
+use std::{any::type_name, error::Error, fmt};
+
+/// `Result` with a `VsdbError`.
+pub type VsdbResult<T> = Result<T, VsdbError>;
+
+/// The failure kinds of VSDB.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VsdbError {
+    /// Failed to decode bytes into the expected type.
+    Codec {
+        /// The prefix of the instance that the bytes belong to.
+        instance: Option<u64>,
+        /// The raw key of the bytes.
+        key: Option<Vec<u8>>,
+        /// The name of the expected type.
+        type_name: &'static str,
+        /// The reason of the failure.
+        msg: String,
+    },
+    /// The key(or the index) does not exist.
+    NotFound {
+        /// The prefix of the instance.
+        instance: Option<u64>,
+        /// The raw key.
+        key: Vec<u8>,
+    },
+    /// The branch does not exist.
+    BranchNotFound {
+        /// The name of the branch.
+        branch: Vec<u8>,
+    },
+    /// The branch already exists.
+    BranchExists {
+        /// The name of the branch.
+        branch: Vec<u8>,
+    },
+    /// The version does not exist, or it is not on the target branch.
+    VersionNotFound {
+        /// The name of the version.
+        version: Vec<u8>,
+    },
+    /// The version already exists.
+    VersionExists {
+        /// The name of the version.
+        version: Vec<u8>,
+    },
+    /// The branch can not be merged into the target branch safely,
+    /// see `VsMgmt::branch_merge_to` for details.
+    UnsafeMerge {
+        /// The name of the branch to merge.
+        branch: Vec<u8>,
+        /// The name of the target branch.
+        target_branch: Vec<u8>,
+    },
+    /// Failures of the underlying storage, and other internal errors.
+    Storage {
+        /// The reason of the failure.
+        msg: String,
+    },
+}
+
+impl VsdbError {
+    pub(crate) fn codec<T: ?Sized>(msg: impl fmt::Display) -> Self {
+        Self::Codec {
+            instance: None,
+            key: None,
+            type_name: type_name::<T>(),
+            msg: msg.to_string(),
+        }
+    }
+
+    pub(crate) fn branch_not_found(branch: impl AsRef<[u8]>) -> Self {
+        Self::BranchNotFound {
+            branch: branch.as_ref().to_vec(),
+        }
+    }
+
+    pub(crate) fn version_not_found(version: impl AsRef<[u8]>) -> Self {
+        Self::VersionNotFound {
+            version: version.as_ref().to_vec(),
+        }
+    }
+
+    /// Attach the instance to the error, if it has not been attached.
+    pub(crate) fn with_instance(mut self, prefix: Pre) -> Self {
+        match &mut self {
+            Self::Codec { instance, .. } | Self::NotFound { instance, .. } => {
+                instance.get_or_insert(prefix);
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Attach the raw key to the error, if it has not been attached.
+    pub(crate) fn with_key(mut self, raw_key: &[u8]) -> Self {
+        if let Self::Codec { key, .. } = &mut self {
+            key.get_or_insert_with(|| raw_key.to_vec());
+        }
+        self
+    }
+}
+
+impl fmt::Display for VsdbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Codec {
+                instance,
+                key,
+                type_name,
+                msg,
+            } => {
+                write!(f, "failed to decode `{}`", type_name)?;
+                if let Some(i) = instance {
+                    write!(f, ", instance: {}", i)?;
+                }
+                if let Some(k) = key {
+                    write!(f, ", key: 0x{}", Hex(k))?;
+                }
+                write!(f, ", reason: {}", msg)
+            }
+            Self::NotFound { instance, key } => {
+                write!(f, "key not found: 0x{}", Hex(key))?;
+                if let Some(i) = instance {
+                    write!(f, ", instance: {}", i)?;
+                }
+                Ok(())
+            }
+            Self::BranchNotFound { branch } => {
+                write!(f, "branch not found: {}", String::from_utf8_lossy(branch))
+            }
+            Self::BranchExists { branch } => {
+                write!(
+                    f,
+                    "branch already exists: {}",
+                    String::from_utf8_lossy(branch)
+                )
+            }
+            Self::VersionNotFound { version } => {
+                write!(f, "version not found: {}", String::from_utf8_lossy(version))
+            }
+            Self::VersionExists { version } => {
+                write!(
+                    f,
+                    "version already exists: {}",
+                    String::from_utf8_lossy(version)
+                )
+            }
+            Self::UnsafeMerge {
+                branch,
+                target_branch,
+            } => {
+                write!(
+                    f,
+                    "unable to merge branch {} into {} safely",
+                    String::from_utf8_lossy(branch),
+                    String::from_utf8_lossy(target_branch)
+                )
+            }
+            Self::Storage { msg } => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for VsdbError {}
+
+impl From<Box<dyn RucError>> for VsdbError {
+    fn from(e: Box<dyn RucError>) -> Self {
+        Self::Storage {
+            msg: e.get_lowest_msg(),
+        }
+    }
+}
+
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
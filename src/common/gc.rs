@@ -0,0 +1,250 @@
+//!
+//! # Garbage collection of orphaned instances
+//!
+//! The meta information(prefix) of an instance is persisted
+//! only when its handle has been serialized by the user,
+//! so the data of an instance whose handle has been lost will never be freed.
+//!
+//! The GC takes a set of root handles, marks all instances reachable from them,
+//! include the instances nested in the stored values, then removes all the others.
+//!
+
+use crate::common::{engines::Engine, PreBytes, VSDB};
+use ruc::*;
+use std::{
+    collections::{
+        BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque,
+    },
+    marker::PhantomData,
+};
+
+/// Prefixes of all the instances that have been marked as reachable.
+#[derive(Debug, Default)]
+pub struct GcMarks {
+    inner: HashSet<PreBytes>,
+}
+
+impl GcMarks {
+    // return `false` if the prefix has been marked before
+    #[inline(always)]
+    pub(crate) fn mark(&mut self, prefix: PreBytes) -> bool {
+        self.inner.insert(prefix)
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_marked(&self, prefix: PreBytes) -> bool {
+        self.inner.contains(&prefix)
+    }
+
+    // compact the key ranges of all the marked instances
+    #[inline(always)]
+    pub(crate) fn compact(&self) {
+        self.inner.iter().for_each(|p| VSDB.db.compact_instance(*p));
+    }
+
+    /// The number of reachable instances.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// Methods used by the GC to find out all reachable instances.
+pub trait GcMark {
+    /// Mark the instances owned by `self`,
+    /// include those nested in the values stored in them.
+    fn gc_mark(&self, marks: &mut GcMarks);
+
+    /// Types that will never hold any VSDB instance should return `true`,
+    /// then the values of this type will not be decoded during the marking.
+    #[inline(always)]
+    fn gc_nope() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+}
+
+/// Result of a GC round.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// The number of unreachable instances.
+    pub instances: usize,
+    /// The number of bytes occupied by the unreachable instances,
+    /// they have been reclaimed if not in the dry-run mode.
+    pub bytes: u64,
+}
+
+/// Remove all instances that can not be reached from the `roots`.
+///
+/// If `dry_run` is `true`, nothing will be removed,
+/// the result only reports how many bytes would be reclaimed.
+///
+/// **NOTE**:
+/// - All living handles must be included in the `roots`(directly or nestedly),
+///   or their data will be lost
+/// - Do NOT create new instances in other threads during the GC
+pub fn vsdb_gc(roots: &[&dyn GcMark], dry_run: bool) -> Result<GcReport> {
+    if roots.is_empty() {
+        return Err(eg!("Empty roots, refuse to clean up everything"));
+    }
+
+    let mut marks = GcMarks::default();
+    roots.iter().for_each(|r| r.gc_mark(&mut marks));
+
+    let mut report = GcReport::default();
+
+    for prefix in VSDB
+        .db
+        .instance_prefixes()
+        .into_iter()
+        .filter(|p| !marks.is_marked(*p))
+    {
+        report.instances += 1;
+        report.bytes += VSDB.db.instance_size(prefix);
+        if !dry_run {
+            VSDB.db.remove_instance(prefix);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Add nope implementations of `GcMark`
+/// for types that are not defined in VSDB.
+#[macro_export]
+macro_rules! impl_gc_mark_nope {
+    () => {
+        #[inline(always)]
+        fn gc_mark(&self, _: &mut $crate::GcMarks) {}
+
+        #[inline(always)]
+        fn gc_nope() -> bool {
+            true
+        }
+    };
+}
+
+impl<T: ?Sized> GcMark for PhantomData<T> {
+    impl_gc_mark_nope!();
+}
+
+impl<T: GcMark> GcMark for Option<T> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        if let Some(i) = self.as_ref() {
+            i.gc_mark(marks);
+        }
+    }
+
+    #[inline(always)]
+    fn gc_nope() -> bool {
+        T::gc_nope()
+    }
+}
+
+impl<T: GcMark> GcMark for Box<T> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.as_ref().gc_mark(marks);
+    }
+
+    #[inline(always)]
+    fn gc_nope() -> bool {
+        T::gc_nope()
+    }
+}
+
+impl<A: GcMark, B: GcMark> GcMark for (A, B) {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.0.gc_mark(marks);
+        self.1.gc_mark(marks);
+    }
+
+    #[inline(always)]
+    fn gc_nope() -> bool {
+        A::gc_nope() && B::gc_nope()
+    }
+}
+
+impl<A: GcMark, B: GcMark, C: GcMark> GcMark for (A, B, C) {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.0.gc_mark(marks);
+        self.1.gc_mark(marks);
+        self.2.gc_mark(marks);
+    }
+
+    #[inline(always)]
+    fn gc_nope() -> bool {
+        A::gc_nope() && B::gc_nope() && C::gc_nope()
+    }
+}
+
+macro_rules! impl_for_primitives {
+    (@nope $ty: ty) => {
+        impl GcMark for $ty {
+            impl_gc_mark_nope!();
+        }
+        impl<K> GcMark for HashMap<K, $ty> {
+            impl_gc_mark_nope!();
+        }
+        impl<K> GcMark for BTreeMap<K, $ty> {
+            impl_gc_mark_nope!();
+        }
+    };
+    ($ty: ty) => {
+        impl_for_primitives!(@nope $ty);
+        impl_for_primitives!(@nope Box<[$ty]>);
+        impl_for_primitives!(@nope Vec<$ty>);
+        impl_for_primitives!(@nope VecDeque<$ty>);
+        impl_for_primitives!(@nope HashSet<$ty>);
+        impl_for_primitives!(@nope BTreeSet<$ty>);
+        impl_for_primitives!(@nope BinaryHeap<$ty>);
+        impl_for_primitives!(@nope LinkedList<$ty>);
+    };
+    ($ty: ty, $($t: ty),+) => {
+        impl_for_primitives!($ty);
+        impl_for_primitives!($($t), +);
+    };
+}
+
+impl_for_primitives!(
+    i8,
+    u8,
+    i16,
+    u16,
+    i32,
+    u32,
+    i64,
+    u64,
+    i128,
+    u128,
+    isize,
+    usize,
+    bool,
+    char,
+    (),
+    String,
+    primitive_types_0_10::U128,
+    primitive_types_0_10::U256,
+    primitive_types_0_10::U512,
+    primitive_types_0_10::H128,
+    primitive_types_0_10::H160,
+    primitive_types_0_10::H256,
+    primitive_types_0_10::H512,
+    primitive_types_0_11::U128,
+    primitive_types_0_11::U256,
+    primitive_types_0_11::U512,
+    primitive_types_0_11::H128,
+    primitive_types_0_11::H160,
+    primitive_types_0_11::H256,
+    primitive_types_0_11::H512
+);
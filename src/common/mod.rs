@@ -3,20 +3,31 @@
 //!
 
 pub(crate) mod ende;
+pub(crate) mod entry;
 pub(crate) mod engines;
+pub(crate) mod error;
+pub(crate) mod gc;
+pub(crate) mod schema;
 
-#[cfg(feature = "hash")]
+#[cfg(any(feature = "hash", feature = "encryption", feature = "zstd_compress"))]
 pub(crate) mod utils;
 
 use {
+    ende::Codec,
     engines::Engine,
+    fs4::FileExt,
+    gc::{GcMark, GcMarks},
     once_cell::sync::Lazy,
     parking_lot::Mutex,
     ruc::*,
     std::{
-        env, fs,
+        env,
+        fs::{self, File, OpenOptions},
         mem::size_of,
-        sync::atomic::{AtomicBool, Ordering},
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicBool, AtomicU8, Ordering},
+        thread,
+        time::Duration,
     },
 };
 
@@ -68,11 +79,19 @@ pub(crate) const RESERVED_VERSION_NUM_DEFAULT: usize = 10;
 /////////////////////////////////////////////////////////////////////////////
 
 const BASE_DIR_VAR: &str = "VSDB_BASE_DIR";
+const CUSTOM_DIR_NAME: &str = "__CUSTOM__";
+const LOCK_FILE_NAME: &str = "VSDB.LOCK";
+
+static VSDB_OPEN_MODE: AtomicU8 = AtomicU8::new(OpenMode::ReadWrite as u8);
+
+static VSDB_SYNC_POLICY: Mutex<SyncPolicy> = Mutex::new(SyncPolicy::Never);
 
 static VSDB_BASE_DIR: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new(gen_data_dir()));
 
+static COMPACT_AFTER_PRUNE: AtomicBool = AtomicBool::new(false);
+
 static VSDB_CUSTOM_DIR: Lazy<String> = Lazy::new(|| {
-    let d = VSDB_BASE_DIR.lock().clone() + "/__CUSTOM__";
+    let d = format!("{}/{}", VSDB_BASE_DIR.lock(), CUSTOM_DIR_NAME);
     fs::create_dir_all(&d).unwrap();
     env::set_var("VSDB_CUSTOM_DIR", &d);
     d
@@ -81,18 +100,29 @@ static VSDB_CUSTOM_DIR: Lazy<String> = Lazy::new(|| {
 #[cfg(any(
     feature = "rocks_engine",
     all(feature = "rocks_engine", feature = "sled_engine"),
-    all(not(feature = "rocks_engine"), not(feature = "sled_engine")),
+    all(
+        not(feature = "rocks_engine"),
+        not(feature = "sled_engine"),
+        not(feature = "redb_engine")
+    ),
 ))]
 pub(crate) static VSDB: Lazy<VsDB<engines::RocksDB>> = Lazy::new(|| pnk!(VsDB::new()));
 
-#[cfg(all(feature = "sled_engine", not(feature = "rocks_engine")))]
+#[cfg(all(
+    feature = "sled_engine",
+    not(feature = "rocks_engine"),
+    not(feature = "redb_engine")
+))]
 pub(crate) static VSDB: Lazy<VsDB<engines::Sled>> = Lazy::new(|| pnk!(VsDB::new()));
 
+#[cfg(all(feature = "redb_engine", not(feature = "rocks_engine")))]
+pub(crate) static VSDB: Lazy<VsDB<engines::Redb>> = Lazy::new(|| pnk!(VsDB::new()));
+
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
 
 /// Parse bytes to a specified integer type.
-#[macro_export(crate)]
+#[macro_export]
 macro_rules! parse_int {
     ($bytes: expr, $ty: ty) => {{
         let array: [u8; std::mem::size_of::<$ty>()] = $bytes[..].try_into().unwrap();
@@ -101,7 +131,7 @@ macro_rules! parse_int {
 }
 
 /// Parse bytes to a `Pre` type.
-#[macro_export(crate)]
+#[macro_export]
 macro_rules! parse_prefix {
     ($bytes: expr) => {
         $crate::parse_int!($bytes, $crate::common::Pre)
@@ -113,14 +143,33 @@ macro_rules! parse_prefix {
 
 pub(crate) struct VsDB<T: Engine> {
     db: T,
+    // held until the process exits
+    _lock: Option<File>,
 }
 
 impl<T: Engine> VsDB<T> {
     #[inline(always)]
     fn new() -> Result<Self> {
-        Ok(Self {
-            db: T::new().c(d!())?,
-        })
+        // must be acquired before opening the engine
+        let lock = alt!(
+            OpenMode::ReadWrite == vsdb_get_open_mode(),
+            Some(lock_base_dir().c(d!())?),
+            None
+        );
+
+        let db = T::new().c(d!())?;
+
+        if let (OpenMode::ReadWrite, SyncPolicy::Interval(ms)) =
+            (vsdb_get_open_mode(), vsdb_get_sync_policy())
+        {
+            // the `VSDB` must have been initialized when the sleeping ends
+            thread::spawn(move || loop {
+                thread::sleep(Duration::from_millis(ms));
+                info_omit!(VSDB.db.sync());
+            });
+        }
+
+        Ok(Self { db, _lock: lock })
     }
 
     #[inline(always)]
@@ -134,14 +183,115 @@ impl<T: Engine> VsDB<T> {
     }
 
     #[inline(always)]
-    fn flush(&self) {
-        self.db.flush()
+    fn flush(&self) -> Result<()> {
+        self.db.flush().c(d!())
     }
 }
 
 /////////////////////////////////////////////////////////////////////////////
 /////////////////////////////////////////////////////////////////////////////
 
+/// How to open the database.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OpenMode {
+    /// Only one process can open a database in this mode.
+    #[default]
+    ReadWrite,
+    /// All write operations will be refused.
+    ///
+    /// NOTE: not supported by the sled engine.
+    ReadOnly,
+    /// Read-only, and can catch up with the writer process
+    /// by `vsdb_try_catch_up_with_primary`.
+    ///
+    /// NOTE: only supported by the rocksdb engine.
+    Secondary,
+}
+
+/// When to make the written data durable, `SyncPolicy::Never` by default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SyncPolicy {
+    /// Never sync explicitly,
+    /// when the data reach the disk is decided by the backend engine,
+    /// use `vsdb_flush` or `vsdb_durable_commit` to sync manually.
+    ///
+    /// NOTE: the redb engine makes only every 1024th write durable in this mode,
+    /// so a crash may lose up to 1023 finished writes however old they are,
+    /// sled and rocksdb do not lose the finished writes in this way.
+    #[default]
+    Never,
+    /// Every write is synced before it returns, the slowest one.
+    EveryWrite,
+    /// All writes are synced every N milliseconds in the background,
+    /// writes of the last N milliseconds may be lost in a crash.
+    Interval(u64),
+}
+
+/// Options of a newly created instance.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InstanceOpts {
+    /// How to compress the values,
+    /// it is recorded in the serialized handle of the instance.
+    pub compression: Compression,
+    /// How to encode the typed values,
+    /// it is recorded in the serialized handle of the instance.
+    pub codec: Codec,
+    /// Name of the schema of the typed values,
+    /// it must have been registered by `vsdb_register_schema`.
+    pub schema: Option<String>,
+}
+
+/// Compression algorithms of the values of an instance.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Compression {
+    /// The values are stored as they are,
+    /// but may still be compressed by the `compress` feature globally.
+    #[default]
+    None,
+    /// Compress the values by zstd,
+    /// a dictionary trained from typical values(e.g. by `zstd::dict::from_samples`)
+    /// can improve the ratio greatly for small values.
+    ///
+    /// The dictionary is stored in the database along with the instance.
+    #[cfg(feature = "zstd_compress")]
+    Zstd {
+        level: i32,
+        dict: Option<Vec<u8>>,
+    },
+}
+
+impl OpenMode {
+    const fn from_u8(n: u8) -> Self {
+        match n {
+            0 => Self::ReadWrite,
+            1 => Self::ReadOnly,
+            _ => Self::Secondary,
+        }
+    }
+}
+
+#[inline(always)]
+fn lock_base_dir() -> Result<File> {
+    let path = format!("{}/{}", vsdb_get_base_dir(), LOCK_FILE_NAME);
+    let f = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .c(d!())?;
+    f.try_lock_exclusive().map_err(|e| {
+        eg!(
+            "Failed to lock {}, the DB is being used by another process: {}",
+            path,
+            e
+        )
+    })?;
+    Ok(f)
+}
+
+/////////////////////////////////////////////////////////////////////////////
+/////////////////////////////////////////////////////////////////////////////
+
 #[inline(always)]
 fn gen_data_dir() -> String {
     // Compatible with Windows OS?
@@ -178,10 +328,258 @@ pub fn vsdb_set_base_dir(dir: &str) -> Result<()> {
     }
 }
 
+/// Set the open mode of the database, `OpenMode::ReadWrite` by default.
+///
+/// In the read-only modes, all write operations of the versioned
+/// structures and the basic structures will return an error,
+/// except the ones that can not return anything, they will panic:
+/// the creations(use `try_new` instead), the `Extend` implementations,
+/// and the writings through the handles of `get_mut`, `entry`, `iter_mut`.
+///
+/// NOTE: must be called before any VSDB instance is used.
+#[inline(always)]
+pub fn vsdb_set_open_mode(mode: OpenMode) -> Result<()> {
+    if Lazy::get(&VSDB).is_some() {
+        return Err(eg!("VSDB has been initialized !!"));
+    }
+    VSDB_OPEN_MODE.store(mode as u8, Ordering::Relaxed);
+    Ok(())
+}
+
+/// The open mode of the database.
+#[inline(always)]
+pub fn vsdb_get_open_mode() -> OpenMode {
+    OpenMode::from_u8(VSDB_OPEN_MODE.load(Ordering::Relaxed))
+}
+
+// write operations should be refused in the read-only modes
+#[inline(always)]
+pub(crate) fn ensure_writable() -> Result<()> {
+    if OpenMode::ReadWrite == vsdb_get_open_mode() {
+        Ok(())
+    } else {
+        Err(eg!("VSDB is opened in a read-only mode"))
+    }
+}
+
+/// Set the sync policy of the database, `SyncPolicy::Never` by default.
+///
+/// NOTE: must be called before any VSDB instance is used.
+#[inline(always)]
+pub fn vsdb_set_sync_policy(policy: SyncPolicy) -> Result<()> {
+    if Lazy::get(&VSDB).is_some() {
+        return Err(eg!("VSDB has been initialized !!"));
+    }
+    if SyncPolicy::Interval(0) == policy {
+        return Err(eg!("The sync interval must be bigger than zero"));
+    }
+    *VSDB_SYNC_POLICY.lock() = policy;
+    Ok(())
+}
+
+/// The sync policy of the database.
+#[inline(always)]
+pub fn vsdb_get_sync_policy() -> SyncPolicy {
+    *VSDB_SYNC_POLICY.lock()
+}
+
+/// Set the key to encrypt all values at rest.
+///
+/// Values are authenticated along with their keys, any tampered value
+/// will be reported as an error by the `try_*` read APIs, such as `try_get`,
+/// `try_iter` and `try_range`, which are provided by the maps, the vectors,
+/// `Orphan`, `Dequex` and `MultiMapx`, including their versioned ones.
+/// Keys are not encrypted, so that range scans still work.
+///
+/// All the other APIs will panic on a tampered value, including:
+/// - the sets, `IndexedMapx`, `IndexTree` and the multi-key maps,
+///   which have no `try_*` read APIs
+/// - the writing APIs, which read the old values, such as `insert` and `remove`
+/// - the version management APIs of the versioned containers
+///
+/// NOTE:
+/// - must be called before any VSDB instance is used
+/// - a database must always be opened with the same key
+#[cfg(feature = "encryption")]
+#[inline(always)]
+pub fn vsdb_set_encryption_key(key: &utils::crypto::EncryptionKey) -> Result<()> {
+    if Lazy::get(&VSDB).is_some() {
+        return Err(eg!("VSDB has been initialized !!"));
+    }
+    utils::crypto::set_key(key).c(d!())
+}
+
+/// Catch up with the writer process,
+/// only available in the `OpenMode::Secondary` mode.
+#[inline(always)]
+pub fn vsdb_try_catch_up_with_primary() -> Result<()> {
+    if OpenMode::Secondary != vsdb_get_open_mode() {
+        return Err(eg!("VSDB is not opened in the secondary mode"));
+    }
+    VSDB.db.try_catch_up_with_primary().c(d!())
+}
+
 /// Flush data to disk, may take a long time.
 #[inline(always)]
-pub fn vsdb_flush() {
-    VSDB.flush();
+pub fn vsdb_flush() -> Result<()> {
+    VSDB.flush().c(d!())
+}
+
+/// Make all the finished writes durable,
+/// it is much cheaper than `vsdb_flush`.
+///
+/// Call it after `version_create`(or any other write)
+/// to make sure that the new version is on disk,
+/// no matter which `SyncPolicy` is used.
+#[inline(always)]
+pub fn vsdb_durable_commit() -> Result<()> {
+    VSDB.db.sync().c(d!())
+}
+
+/// Create a consistent copy of the whole database in `path`,
+/// which must not exist yet.
+///
+/// The copy includes the meta data(prefix/branch/version allocators, etc.),
+/// so it can be used to restore the database by `vsdb_restore`.
+///
+/// **NOTE**:
+/// - On the rocksdb engine, data files are hard-linked
+///   if the `path` is on the same filesystem, so it is cheap enough
+///   to be used as an incremental backup
+/// - On the sled engine, all data will be copied,
+///   writes in other threads are blocked until the checkpoint is done
+pub fn vsdb_checkpoint(path: &str) -> Result<()> {
+    if Path::new(path).exists() {
+        return Err(eg!("{} already exists", path));
+    }
+    VSDB.db.checkpoint(path).c(d!())
+}
+
+/// Restore the database from a checkpoint created by `vsdb_checkpoint`,
+/// all existing data in ${VSDB_BASE_DIR} will be replaced.
+///
+/// The checkpoint is copied into a sibling directory of ${VSDB_BASE_DIR}
+/// and synced to disk, then renamed into place, so the existing data
+/// are kept as is if the copying fails.
+///
+/// **NOTE**:
+/// - Must be called before any VSDB instance is used,
+///   and after `vsdb_set_base_dir` if it is needed
+/// - The data in ${VSDB_CUSTOM_DIR} will be kept as is
+/// - If the process crashes between the two renamings,
+///   the replaced data are left in `.<base dir name>.replaced`
+pub fn vsdb_restore(path: &str) -> Result<()> {
+    if Lazy::get(&VSDB).is_some() {
+        return Err(eg!("VSDB has been initialized !!"));
+    }
+
+    let src = Path::new(path);
+    if !src.is_dir() {
+        return Err(eg!("{} is not a valid checkpoint", path));
+    }
+
+    // refuse to restore when the DB is being used by another process
+    let _lock = lock_base_dir().c(d!())?;
+
+    let base = PathBuf::from(vsdb_get_base_dir());
+    let name = base.file_name().c(d!())?.to_string_lossy().into_owned();
+    let tmp = base.with_file_name(format!(".{}.restoring", name));
+    let old = base.with_file_name(format!(".{}.replaced", name));
+
+    // clean up the leftovers of an interrupted restoring,
+    // the custom dir may have been moved into the new base dir
+    if tmp.exists() {
+        let custom = tmp.join(CUSTOM_DIR_NAME);
+        if custom.exists() && !base.join(CUSTOM_DIR_NAME).exists() {
+            fs::rename(custom, base.join(CUSTOM_DIR_NAME)).c(d!())?;
+        }
+        fs::remove_dir_all(&tmp).c(d!())?;
+    }
+    if old.exists() {
+        fs::remove_dir_all(&old).c(d!())?;
+    }
+
+    // the current base dir is untouched until all data are on disk
+    copy_dir_all(src, &tmp).c(d!())?;
+    sync_dir_all(&tmp).c(d!())?;
+
+    // the hard link keeps the lock held by us
+    fs::hard_link(base.join(LOCK_FILE_NAME), tmp.join(LOCK_FILE_NAME)).c(d!())?;
+    let custom = base.join(CUSTOM_DIR_NAME);
+    if custom.exists() {
+        fs::rename(custom, tmp.join(CUSTOM_DIR_NAME)).c(d!())?;
+    }
+    File::open(&tmp).c(d!())?.sync_all().c(d!())?;
+
+    fs::rename(&base, &old).c(d!())?;
+    fs::rename(&tmp, &base).c(d!())?;
+    let parent = base.parent().filter(|p| !p.as_os_str().is_empty());
+    File::open(parent.unwrap_or_else(|| Path::new(".")))
+        .c(d!())?
+        .sync_all()
+        .c(d!())?;
+
+    fs::remove_dir_all(&old).c(d!())
+}
+
+fn copy_dir_all(src: &Path, dst: &Path) -> Result<()> {
+    fs::create_dir_all(dst).c(d!())?;
+    for entry in fs::read_dir(src).c(d!())? {
+        let entry = entry.c(d!())?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type().c(d!())?.is_dir() {
+            copy_dir_all(&entry.path(), &target).c(d!())?;
+        } else {
+            fs::copy(entry.path(), target).c(d!())?;
+        }
+    }
+    Ok(())
+}
+
+// sync all the files in `dir` recursively, include the dirs themselves
+fn sync_dir_all(dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(dir).c(d!())? {
+        let entry = entry.c(d!())?;
+        if entry.file_type().c(d!())?.is_dir() {
+            sync_dir_all(&entry.path()).c(d!())?;
+        } else {
+            File::open(entry.path()).c(d!())?.sync_all().c(d!())?;
+        }
+    }
+    File::open(dir).c(d!())?.sync_all().c(d!())
+}
+
+/// Compact the key ranges of all instances owned by `hdr`,
+/// include those nested in the values stored in them.
+///
+/// Useful after mass removals, the tombstones will be cleaned up
+/// and the range scans over these instances will be fast again.
+/// This is a no-op on the sled engine.
+#[inline(always)]
+pub fn vsdb_compact_instance(hdr: &dyn GcMark) {
+    let mut marks = GcMarks::default();
+    hdr.gc_mark(&mut marks);
+    marks.compact();
+}
+
+/// Compact the whole database, may take a long time.
+///
+/// This is a no-op on the sled engine.
+#[inline(always)]
+pub fn vsdb_compact_all() {
+    VSDB.db.compact_all();
+}
+
+/// Compact the instances touched by `prune` after it has finished,
+/// it is disabled by default.
+#[inline(always)]
+pub fn vsdb_set_compact_after_prune(enable: bool) {
+    COMPACT_AFTER_PRUNE.store(enable, Ordering::Relaxed);
+}
+
+#[inline(always)]
+pub(crate) fn vsdb_compact_after_prune() -> bool {
+    COMPACT_AFTER_PRUNE.load(Ordering::Relaxed)
 }
 
 macro_rules! impl_from_for_name {
@@ -223,21 +621,21 @@ impl Default for BranchName<'static> {
 
 impl BranchNameOwned {
     #[inline(always)]
-    pub fn as_deref(&self) -> BranchName {
+    pub fn as_deref(&self) -> BranchName<'_> {
         BranchName(&self.0)
     }
 }
 
 impl ParentBranchNameOwned {
     #[inline(always)]
-    pub fn as_deref(&self) -> ParentBranchName {
+    pub fn as_deref(&self) -> ParentBranchName<'_> {
         ParentBranchName(&self.0)
     }
 }
 
 impl VersionNameOwned {
     #[inline(always)]
-    pub fn as_deref(&self) -> VersionName {
+    pub fn as_deref(&self) -> VersionName<'_> {
         VersionName(&self.0)
     }
 }
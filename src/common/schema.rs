@@ -0,0 +1,134 @@
+//!
+//! # Schema evolution of the stored values
+//!
+//! The typed values of an instance with a schema are stored in envelopes,
+//! each envelope records the schema version that the value was encoded by,
+//! and the values of older versions will be upgraded on decoding,
+//! by the upgrade functions registered with `vsdb_register_schema`.
+//!
+
+use crate::common::RawBytes;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use ruc::*;
+use std::{borrow::Cow, collections::HashMap, mem::size_of, sync::Arc};
+
+pub(crate) type SchemaId = u64;
+
+type SchemaVersion = u32;
+const VERSION_SIZE: usize = size_of::<SchemaVersion>();
+
+/// Upgrade an encoded value of the schema version `n` to the version `n + 1`.
+pub type SchemaUpgrade = fn(&[u8]) -> Result<Vec<u8>>;
+
+static SCHEMAS: Lazy<RwLock<HashMap<SchemaId, Arc<[SchemaUpgrade]>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Register the upgrade functions of the schema `name`,
+/// `upgrades[n]` upgrades the values of version `n` to version `n + 1`,
+/// so the latest version of the schema is `upgrades.len()`.
+///
+/// Values that were stored before a schema is applied to their instance,
+/// are treated as version 0 by `migrate_instance`.
+///
+/// NOTE: the schemas must be registered again after every restarting,
+/// before any instances with a schema are used.
+pub fn vsdb_register_schema(name: &str, upgrades: &[SchemaUpgrade]) -> Result<()> {
+    let mut schemas = SCHEMAS.write();
+    let id = schema_id(name);
+
+    if let Some(old) = schemas.get(&id) {
+        if old.len() > upgrades.len() {
+            return Err(eg!("The schema can not be downgraded !!"));
+        }
+    }
+
+    schemas.insert(id, Arc::from(upgrades));
+    Ok(())
+}
+
+// a stable hash(FNV-1a) of the schema name,
+// it is recorded in the serialized handles of the instances
+pub(crate) fn schema_id(name: &str) -> SchemaId {
+    name.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub(crate) fn ensure_registered(id: SchemaId) -> Result<()> {
+    if SCHEMAS.read().contains_key(&id) {
+        Ok(())
+    } else {
+        Err(eg!("The schema has not been registered !!"))
+    }
+}
+
+// wrap a value encoded by the latest version in an envelope
+pub(crate) fn seal(id: SchemaId, value: &[u8]) -> Result<RawBytes> {
+    let version = latest_version(id).c(d!())?;
+    Ok([&version.to_be_bytes()[..], value].concat().into_boxed_slice())
+}
+
+// unwrap a value from its envelope, and upgrade it to the latest version
+pub(crate) fn open(id: SchemaId, sealed: &[u8]) -> Result<Cow<'_, [u8]>> {
+    if sealed.len() < VERSION_SIZE {
+        return Err(eg!("Invalid envelope of the value"));
+    }
+
+    let (version, value) = sealed.split_at(VERSION_SIZE);
+    let version = SchemaVersion::from_be_bytes(version.try_into().unwrap());
+
+    upgrade(id, version, value).c(d!())
+}
+
+// rewrite a value to the latest version of the schema `id`,
+// `old` is the schema that the value has been stored with
+pub(crate) fn migrate(
+    old: Option<SchemaId>,
+    id: SchemaId,
+    value: &[u8],
+) -> Result<RawBytes> {
+    let value = if let Some(old) = old {
+        open(old, value).c(d!())?
+    } else {
+        upgrade(id, 0, value).c(d!())?
+    };
+    seal(id, &value).c(d!())
+}
+
+fn upgrade(
+    id: SchemaId,
+    version: SchemaVersion,
+    value: &[u8],
+) -> Result<Cow<'_, [u8]>> {
+    // the lock is released before the upgrading,
+    // in case the upgrade functions access other instances,
+    // only the reference count is touched under it
+    let upgrades = SCHEMAS
+        .read()
+        .get(&id)
+        .cloned()
+        .c(d!("The schema has not been registered !!"))?;
+
+    if version as usize > upgrades.len() {
+        return Err(eg!(
+            "Unknown schema version {}, the latest one is {}",
+            version,
+            upgrades.len()
+        ));
+    }
+
+    upgrades[version as usize..]
+        .iter()
+        .try_fold(Cow::Borrowed(value), |v, upgrade| {
+            upgrade(&v).c(d!()).map(Cow::Owned)
+        })
+}
+
+fn latest_version(id: SchemaId) -> Result<SchemaVersion> {
+    SCHEMAS
+        .read()
+        .get(&id)
+        .map(|upgrades| upgrades.len() as SchemaVersion)
+        .c(d!("The schema has not been registered !!"))
+}
@@ -18,3 +18,145 @@ pub(crate) mod hash {
         hasher.finalize().into()
     }
 }
+
+// Values are sealed as `nonce || ciphertext || tag`,
+// the full key(instance prefix + raw key) is authenticated along with them,
+// so a value moved to another key or instance will also be detected.
+//
+// Keys are left as plaintext to keep the order of range scans.
+#[cfg(feature = "encryption")]
+pub(crate) mod crypto {
+    use crate::common::RawValue;
+    use chacha20poly1305::{
+        aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+        XChaCha20Poly1305, XNonce,
+    };
+    use once_cell::sync::OnceCell;
+    use ruc::*;
+
+    pub type EncryptionKey = [u8; 32];
+
+    // a random nonce of 24 bytes is safe enough to be generated for each write
+    const NONCE_SIZE: usize = 24;
+
+    static CIPHER: OnceCell<XChaCha20Poly1305> = OnceCell::new();
+
+    pub(crate) fn set_key(key: &EncryptionKey) -> Result<()> {
+        CIPHER
+            .set(XChaCha20Poly1305::new(key.into()))
+            .map_err(|_| eg!("The encryption key has been set !!"))
+    }
+
+    // values are stored as they are if no key is set
+    pub(crate) fn seal(full_key: &[u8], value: &[u8]) -> RawValue {
+        let cipher = if let Some(c) = CIPHER.get() {
+            c
+        } else {
+            return value.to_vec().into_boxed_slice();
+        };
+
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ct = pnk!(cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: value,
+                    aad: full_key,
+                },
+            )
+            .map_err(|e| eg!(e)));
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ct);
+        sealed.into_boxed_slice()
+    }
+
+    pub(crate) fn open(full_key: &[u8], sealed: RawValue) -> Result<RawValue> {
+        let cipher = if let Some(c) = CIPHER.get() {
+            c
+        } else {
+            return Ok(sealed);
+        };
+
+        if sealed.len() < NONCE_SIZE {
+            return Err(eg!("The value has been tampered !!"));
+        }
+
+        let (nonce, ct) = sealed.split_at(NONCE_SIZE);
+        cipher
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ct,
+                    aad: full_key,
+                },
+            )
+            .map(|v| v.into_boxed_slice())
+            .map_err(|_| eg!("The value has been tampered, or the key is wrong !!"))
+    }
+}
+
+// Prepared dictionaries are cached by the prefixes of the instances
+// that store them, the preparation is much heavier than the compression.
+#[cfg(feature = "zstd_compress")]
+pub(crate) mod compress {
+    use crate::common::PreBytes;
+    use once_cell::sync::Lazy;
+    use parking_lot::Mutex;
+    use ruc::*;
+    use std::{collections::HashMap, io::Read, sync::Arc};
+    use zstd::{
+        bulk::Compressor,
+        dict::{DecoderDictionary, EncoderDictionary},
+        stream::read::Decoder,
+    };
+
+    type Dicts = (EncoderDictionary<'static>, DecoderDictionary<'static>);
+
+    static DICTS: Lazy<Mutex<HashMap<PreBytes, Arc<Dicts>>>> =
+        Lazy::new(|| Mutex::new(HashMap::new()));
+
+    fn get_dicts(
+        level: i32,
+        dict_prefix: PreBytes,
+        load_dict: impl FnOnce() -> Vec<u8>,
+    ) -> Arc<Dicts> {
+        Arc::clone(DICTS.lock().entry(dict_prefix).or_insert_with(|| {
+            let dict = load_dict();
+            Arc::new((
+                EncoderDictionary::copy(&dict, level),
+                DecoderDictionary::copy(&dict),
+            ))
+        }))
+    }
+
+    pub(crate) fn zstd_compress(
+        level: i32,
+        dict: Option<(PreBytes, impl FnOnce() -> Vec<u8>)>,
+        value: &[u8],
+    ) -> Vec<u8> {
+        if let Some((dict_prefix, load_dict)) = dict {
+            let dicts = get_dicts(level, dict_prefix, load_dict);
+            pnk!(Compressor::with_prepared_dictionary(&dicts.0)
+                .and_then(|mut c| c.compress(value)))
+        } else {
+            pnk!(zstd::bulk::compress(value, level))
+        }
+    }
+
+    pub(crate) fn zstd_decompress(
+        level: i32,
+        dict: Option<(PreBytes, impl FnOnce() -> Vec<u8>)>,
+        value: &[u8],
+    ) -> Result<Vec<u8>> {
+        if let Some((dict_prefix, load_dict)) = dict {
+            let dicts = get_dicts(level, dict_prefix, load_dict);
+            let mut decoder = Decoder::with_prepared_dictionary(value, &dicts.1).c(d!())?;
+            let mut ret = vec![];
+            decoder.read_to_end(&mut ret).c(d!())?;
+            Ok(ret)
+        } else {
+            zstd::stream::decode_all(value).c(d!())
+        }
+    }
+}
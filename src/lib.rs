@@ -151,15 +151,23 @@
 //! - `rocks_engine`, use rocksdb as the backend database
 //!     - Faster running speed
 //!     - Can not be compiled into a statically linked object
+//! - `redb_engine`, use redb as the backend database
+//!     - Pure rust, support for compiling into a statically linked object
 //! - \[**default**] `msgpack_codec`, use msgpack as the codec
 //!     - Faster running speed
 //! - `bcs_codec`, use bcs as the codec
 //!     - Created by the 'Libre' project of Facebook
 //!     - Security reinforcement for blockchain scenarios
-//! - \[**default**] `derive`, enable the `Vs` procedural macro
+//! - `bincode_codec`, `postcard_codec`, `json_codec`, enable more codecs
+//!     - Can be selected per instance, see `InstanceOpts`
+//! - \[**default**] `derive`, enable the `Vs` and `KeyEnDeOrdered` procedural macros
 //! - `compress`, enable compression in the backend database
 //! - `hash`, enable an optional hash function
 //!     - Based on the 'blake3' crate
+//! - `encryption`, enable the encryption of values at rest
+//!     - Based on the 'chacha20poly1305' crate(XChaCha20-Poly1305)
+//! - `zstd_compress`, enable the per-instance zstd compression of values
+//!     - Optional dictionaries, see `InstanceOpts`
 //!
 //! ## Low-level design
 //!
@@ -186,8 +194,15 @@ mod common;
 pub mod versioned;
 pub mod versioned_multi_key;
 
-pub use basic::{mapx::Mapx, mapx_ord::MapxOrd, vecx::Vecx};
-pub use versioned::{mapx::MapxVs, mapx_ord::MapxOrdVs, orphan::OrphanVs, vecx::VecxVs};
+pub use basic::{
+    dequex::Dequex, indexed_mapx::IndexedMapx, mapx::Mapx, mapx_ord::MapxOrd,
+    multi_mapx::MultiMapx, setx::Setx, setx_ord::SetxOrd, vecx::Vecx,
+};
+pub use versioned::{
+    dequex::DequexVs, indexed_mapx::IndexedMapxVs, mapx::MapxVs, mapx_ord::MapxOrdVs,
+    multi_mapx::MultiMapxVs, orphan::OrphanVs, setx::SetxVs, setx_ord::SetxOrdVs,
+    vecx::VecxVs,
+};
 
 pub use versioned_multi_key::{
     mapx_double_key::MapxDkVs, mapx_raw::MapxRawMkVs, mapx_triple_key::MapxTkVs,
@@ -196,14 +211,32 @@ pub use versioned_multi_key::{
 pub use versioned::VsMgmt;
 
 #[cfg(feature = "derive")]
-pub use vsdb_derive::Vs;
+pub use vsdb_derive::{KeyEnDeOrdered, Vs};
+
+#[doc(hidden)]
+pub use common::ende::{
+    decode_key_part, encode_key_part, key_size, upgrade_legacy_key_part,
+};
 
 #[cfg(feature = "hash")]
 pub use common::utils::hash::{hash, Hash, HASH_SIZ};
 
+#[cfg(feature = "encryption")]
+pub use common::{utils::crypto::EncryptionKey, vsdb_set_encryption_key};
+
 pub use common::{
-    ende::{KeyDe, KeyEn, KeyEnDe, KeyEnDeOrdered, ValueDe, ValueEn, ValueEnDe},
-    vsdb_flush, vsdb_get_base_dir, vsdb_get_custom_dir, vsdb_set_base_dir, BranchName,
-    BranchNameOwned, ParentBranchName, ParentBranchNameOwned, VersionName,
-    VersionNameOwned, GB, KB, MB,
+    ende::{
+        Codec, KeyDe, KeyEn, KeyEnDe, KeyEnDeOrdered, OrderedF32, OrderedF64, ValueDe,
+        ValueEn, ValueEnDe,
+    },
+    entry::{Entry, EntryHdr, EntryMut, OccupiedEntry, VacantEntry},
+    error::{VsdbError, VsdbResult},
+    gc::{vsdb_gc, GcMark, GcMarks, GcReport},
+    schema::{vsdb_register_schema, SchemaUpgrade},
+    vsdb_checkpoint, vsdb_compact_all, vsdb_compact_instance, vsdb_durable_commit,
+    vsdb_flush, vsdb_get_base_dir, vsdb_get_custom_dir, vsdb_get_open_mode,
+    vsdb_get_sync_policy, vsdb_restore, vsdb_set_base_dir, vsdb_set_compact_after_prune,
+    vsdb_set_open_mode, vsdb_set_sync_policy, vsdb_try_catch_up_with_primary, BranchName,
+    BranchNameOwned, Compression, InstanceOpts, OpenMode, ParentBranchName,
+    ParentBranchNameOwned, SyncPolicy, VersionName, VersionNameOwned, GB, KB, MB,
 };
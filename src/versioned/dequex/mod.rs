@@ -0,0 +1,492 @@
+//!
+//! Documents => [MapxRawVs](crate::versioned::mapx_raw)
+//!
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::dequex::{
+        decode_bounds, encode_bounds, offset, pos_key, positions, META_KEY, ORIGIN,
+    },
+    common::{error::VsdbResult, RawValue},
+    versioned::mapx_raw::{MapxRawVs, View},
+    BranchName, GcMark, GcMarks, ValueEnDe, VersionName, VsMgmt,
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{marker::PhantomData, ops::RangeBounds};
+
+/// A double-ended queue, pushing and popping at both ends cost O(1).
+///
+/// The head and the tail are stored as a versioned value along with the elements,
+/// so they are always switched and popped together with the versions.
+///
+/// Documents => [MapxRawVs](crate::versioned::mapx_raw::MapxRawVs)
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(bound = "")]
+pub struct DequexVs<T> {
+    inner: MapxRawVs,
+    p: PhantomData<T>,
+}
+
+impl<T> Clone for DequexVs<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            p: PhantomData,
+        }
+    }
+}
+
+impl<T: ValueEnDe> Default for DequexVs<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ValueEnDe> DequexVs<T> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        DequexVs {
+            inner: MapxRawVs::new(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get(&self, idx: usize) -> Option<T> {
+        self.get_by_view(idx, View::Default)
+    }
+
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, idx: usize) -> VsdbResult<Option<T>> {
+        self.try_get_by_view(idx, View::Default)
+    }
+
+    #[inline(always)]
+    pub fn front(&self) -> Option<T> {
+        self.get(0)
+    }
+
+    #[inline(always)]
+    pub fn back(&self) -> Option<T> {
+        self.len().checked_sub(1).and_then(|idx| self.get(idx))
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len_by_view(View::Default)
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        0 == self.len()
+    }
+
+    #[inline(always)]
+    pub fn push_front(&mut self, v: T) {
+        self.push_front_ref(&v)
+    }
+
+    #[inline(always)]
+    pub fn push_front_ref(&mut self, v: &T) {
+        pnk!(self.push(v, true, None));
+    }
+
+    #[inline(always)]
+    pub fn push_back(&mut self, v: T) {
+        self.push_back_ref(&v)
+    }
+
+    #[inline(always)]
+    pub fn push_back_ref(&mut self, v: &T) {
+        pnk!(self.push(v, false, None));
+    }
+
+    #[inline(always)]
+    pub fn pop_front(&mut self) -> Result<Option<T>> {
+        self.pop(true, None).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn pop_back(&mut self) -> Result<Option<T>> {
+        self.pop(false, None).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> DequexVsIter<'_, T> {
+        self.range(..)
+    }
+
+    /// Iterate over the elements whose positions are within `bounds`,
+    /// the positions beyond the length will be ignored.
+    #[inline(always)]
+    pub fn range<R: RangeBounds<usize>>(&self, bounds: R) -> DequexVsIter<'_, T> {
+        self.range_by_view(bounds, View::Default)
+    }
+
+    /// Same as `iter`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> VsdbResult<DequexVsTryIter<'_, T>> {
+        self.try_range_by_view(.., View::Default)
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline(always)]
+    pub fn get_by_branch(&self, idx: usize, branch_name: BranchName) -> Option<T> {
+        self.get_by_view(idx, View::Branch(branch_name))
+    }
+
+    /// Same as `get_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch(
+        &self,
+        idx: usize,
+        branch_name: BranchName,
+    ) -> VsdbResult<Option<T>> {
+        self.try_get_by_view(idx, View::Branch(branch_name))
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch(&self, branch_name: BranchName) -> usize {
+        self.len_by_view(View::Branch(branch_name))
+    }
+
+    #[inline(always)]
+    pub fn push_front_by_branch(&mut self, v: T, branch_name: BranchName) {
+        self.push_front_ref_by_branch(&v, branch_name)
+    }
+
+    #[inline(always)]
+    pub fn push_front_ref_by_branch(&mut self, v: &T, branch_name: BranchName) {
+        pnk!(self.push(v, true, Some(branch_name)));
+    }
+
+    #[inline(always)]
+    pub fn push_back_by_branch(&mut self, v: T, branch_name: BranchName) {
+        self.push_back_ref_by_branch(&v, branch_name)
+    }
+
+    #[inline(always)]
+    pub fn push_back_ref_by_branch(&mut self, v: &T, branch_name: BranchName) {
+        pnk!(self.push(v, false, Some(branch_name)));
+    }
+
+    #[inline(always)]
+    pub fn pop_front_by_branch(&mut self, branch_name: BranchName) -> Result<Option<T>> {
+        self.pop(true, Some(branch_name)).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn pop_back_by_branch(&mut self, branch_name: BranchName) -> Result<Option<T>> {
+        self.pop(false, Some(branch_name)).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch<'a>(
+        &'a self,
+        branch_name: BranchName<'a>,
+    ) -> DequexVsIter<'a, T> {
+        self.range_by_view(.., View::Branch(branch_name))
+    }
+
+    /// Same as `iter_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch<'a>(
+        &'a self,
+        branch_name: BranchName<'a>,
+    ) -> VsdbResult<DequexVsTryIter<'a, T>> {
+        self.try_range_by_view(.., View::Branch(branch_name))
+    }
+
+    #[inline(always)]
+    pub fn get_by_branch_version(
+        &self,
+        idx: usize,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> Option<T> {
+        self.get_by_view(idx, View::BranchVersion(branch_name, version_name))
+    }
+
+    /// Same as `get_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch_version(
+        &self,
+        idx: usize,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> VsdbResult<Option<T>> {
+        self.try_get_by_view(idx, View::BranchVersion(branch_name, version_name))
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> usize {
+        self.len_by_view(View::BranchVersion(branch_name, version_name))
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch_version<'a>(
+        &'a self,
+        branch_name: BranchName<'a>,
+        version_name: VersionName<'a>,
+    ) -> DequexVsIter<'a, T> {
+        self.range_by_view(.., View::BranchVersion(branch_name, version_name))
+    }
+
+    /// Same as `iter_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch_version<'a>(
+        &'a self,
+        branch_name: BranchName<'a>,
+        version_name: VersionName<'a>,
+    ) -> VsdbResult<DequexVsTryIter<'a, T>> {
+        self.try_range_by_view(.., View::BranchVersion(branch_name, version_name))
+    }
+
+    #[inline(always)]
+    fn get_by_view(&self, idx: usize, view: View) -> Option<T> {
+        let (head, tail) = bounds(view.get(&self.inner, META_KEY));
+        let k = pos_key(offset(head, tail, idx)?);
+        view.get(&self.inner, &k).map(|v| self.decode(&k, &v))
+    }
+
+    #[inline(always)]
+    fn len_by_view(&self, view: View) -> usize {
+        let (head, tail) = bounds(view.get(&self.inner, META_KEY));
+        (tail - head) as usize
+    }
+
+    #[inline(always)]
+    fn range_by_view<'a, R: RangeBounds<usize>>(
+        &'a self,
+        bounds: R,
+        view: View<'a>,
+    ) -> DequexVsIter<'a, T> {
+        let (head, tail) = self::bounds(view.get(&self.inner, META_KEY));
+        let (lo, hi) = positions(head, tail, bounds);
+        DequexVsIter {
+            hdr: &self.inner,
+            view,
+            lo,
+            hi,
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    fn try_get_by_view(&self, idx: usize, view: View) -> VsdbResult<Option<T>> {
+        let (head, tail) = bounds(view.try_get(&self.inner, META_KEY)?);
+        match offset(head, tail, idx).map(pos_key) {
+            Some(k) => view
+                .try_get(&self.inner, &k)?
+                .map(|v| self.inner.codec().decode_by_key(&k, &v))
+                .transpose(),
+            None => Ok(None),
+        }
+    }
+
+    #[inline(always)]
+    fn try_range_by_view<'a, R: RangeBounds<usize>>(
+        &'a self,
+        bounds: R,
+        view: View<'a>,
+    ) -> VsdbResult<DequexVsTryIter<'a, T>> {
+        let (head, tail) = self::bounds(view.try_get(&self.inner, META_KEY)?);
+        let (lo, hi) = positions(head, tail, bounds);
+        Ok(DequexVsTryIter {
+            hdr: &self.inner,
+            view,
+            lo,
+            hi,
+            p: PhantomData,
+        })
+    }
+
+    // push an element to the head version,
+    // of the default branch if `branch_name` is `None`
+    fn push(
+        &mut self,
+        v: &T,
+        front: bool,
+        branch_name: Option<BranchName>,
+    ) -> Result<()> {
+        let view = branch_name.map_or(View::Default, View::Branch);
+        let (head, tail) = bounds(view.get(&self.inner, META_KEY));
+        let (pos, bounds) = if front {
+            (head - 1, (head - 1, tail))
+        } else {
+            (tail, (head, tail + 1))
+        };
+        let v = self.inner.codec().encode(v);
+        self.write(&pos_key(pos), Some(&v), bounds, branch_name)
+            .c(d!())
+    }
+
+    // pop an element from the head version,
+    // of the default branch if `branch_name` is `None`
+    fn pop(
+        &mut self,
+        front: bool,
+        branch_name: Option<BranchName>,
+    ) -> Result<Option<T>> {
+        let view = branch_name.map_or(View::Default, View::Branch);
+        let (head, tail) = bounds(view.get(&self.inner, META_KEY));
+        alt!(head == tail, return Ok(None));
+        let (pos, bounds) = if front {
+            (head, (head + 1, tail))
+        } else {
+            (tail - 1, (head, tail - 1))
+        };
+        let k = pos_key(pos);
+        let v = view.get(&self.inner, &k).map(|v| self.decode(&k, &v));
+        self.write(&k, None, bounds, branch_name).c(d!())?;
+        Ok(v)
+    }
+
+    // write an element along with the new bounds in one batch
+    fn write(
+        &mut self,
+        k: &[u8],
+        v: Option<&[u8]>,
+        bounds: (i64, i64),
+        branch_name: Option<BranchName>,
+    ) -> Result<()> {
+        let bounds = encode_bounds(bounds);
+        let kvs = [(k, v), (META_KEY, Some(&bounds[..]))];
+        match branch_name {
+            Some(b) => self.inner.write_batch_by_branch(&kvs, b),
+            None => self.inner.write_batch(&kvs),
+        }
+        .c(d!())
+    }
+
+    #[inline(always)]
+    fn decode(&self, k: &[u8], v: &[u8]) -> T {
+        pnk!(self.inner.codec().decode_by_key(k, v))
+    }
+}
+
+impl<T: ValueEnDe> VsMgmt for DequexVs<T> {
+    crate::impl_vs_methods!();
+}
+
+impl<T: ValueEnDe + GcMark> GcMark for DequexVs<T> {
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        if self.inner.gc_mark_instance(marks) && !T::gc_nope() {
+            self.inner.for_each_kv_globally(|k, v| {
+                // the bounds are not a value of `T`
+                if META_KEY != k {
+                    pnk!(self.inner.codec().decode::<T>(&v)).gc_mark(marks);
+                }
+            });
+        }
+    }
+}
+
+// `[head, tail)`, the positions of the elements
+#[inline(always)]
+fn bounds(meta: Option<RawValue>) -> (i64, i64) {
+    meta.map(|b| decode_bounds(&b)).unwrap_or((ORIGIN, ORIGIN))
+}
+
+// the elements are read one by one by their positions,
+// which are always contiguous within `[lo, hi)`
+pub struct DequexVsIter<'a, T: ValueEnDe> {
+    hdr: &'a MapxRawVs,
+    view: View<'a>,
+    lo: i64,
+    hi: i64,
+    p: PhantomData<T>,
+}
+
+impl<'a, T: ValueEnDe> DequexVsIter<'a, T> {
+    #[inline(always)]
+    fn get(&self, pos: i64) -> Option<T> {
+        let k = pos_key(pos);
+        self.view
+            .get(self.hdr, &k)
+            .map(|v| pnk!(self.hdr.codec().decode_by_key(&k, &v)))
+    }
+}
+
+impl<'a, T: ValueEnDe> Iterator for DequexVsIter<'a, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+        self.lo += 1;
+        self.get(self.lo - 1)
+    }
+}
+
+impl<'a, T: ValueEnDe> DoubleEndedIterator for DequexVsIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+        self.hi -= 1;
+        self.get(self.hi)
+    }
+}
+
+pub struct DequexVsTryIter<'a, T: ValueEnDe> {
+    hdr: &'a MapxRawVs,
+    view: View<'a>,
+    lo: i64,
+    hi: i64,
+    p: PhantomData<T>,
+}
+
+impl<'a, T: ValueEnDe> DequexVsTryIter<'a, T> {
+    #[inline(always)]
+    fn get(&self, pos: i64) -> Option<VsdbResult<T>> {
+        let k = pos_key(pos);
+        match self.view.try_get(self.hdr, &k) {
+            Ok(v) => v.map(|v| self.hdr.codec().decode_by_key(&k, &v)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl<'a, T: ValueEnDe> Iterator for DequexVsTryIter<'a, T> {
+    type Item = VsdbResult<T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+        self.lo += 1;
+        self.get(self.lo - 1)
+    }
+}
+
+impl<'a, T: ValueEnDe> DoubleEndedIterator for DequexVsTryIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        alt!(self.lo >= self.hi, return None);
+        self.hi -= 1;
+        self.get(self.hi)
+    }
+}
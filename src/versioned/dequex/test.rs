@@ -0,0 +1,93 @@
+use super::*;
+use crate::common::INITIAL_BRANCH_NAME;
+
+#[test]
+fn test_push_pop() {
+    let mut hdr: DequexVs<usize> = DequexVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+
+    assert!(hdr.front().is_none());
+    assert!(pnk!(hdr.pop_front()).is_none());
+    assert!(pnk!(hdr.pop_back()).is_none());
+
+    (0..50).for_each(|i| hdr.push_front(i));
+    (50..100).for_each(|i| hdr.push_back(i));
+    assert_eq!(hdr.len(), 100);
+    assert_eq!(hdr.front(), Some(49));
+    assert_eq!(hdr.back(), Some(99));
+    assert_eq!(hdr.get(0), Some(49));
+    assert_eq!(hdr.get(50), Some(50));
+    assert!(hdr.get(100).is_none());
+
+    let model = (0..50).rev().chain(50..100).collect::<Vec<_>>();
+    assert!(hdr.iter().eq(model.iter().copied()));
+    assert!(hdr.range(40..60).eq(model[40..60].iter().copied()));
+    assert!(hdr.range(90..).eq(model[90..].iter().copied()));
+
+    assert_eq!(pnk!(hdr.pop_front()), Some(49));
+    assert_eq!(pnk!(hdr.pop_back()), Some(99));
+    assert_eq!(hdr.len(), 98);
+    assert_eq!(hdr.get(0), Some(48));
+
+    hdr.clear();
+    assert!(hdr.is_empty());
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    hdr.push_front(7);
+    assert_eq!(hdr.get(0), Some(7));
+}
+
+#[test]
+fn test_version_pop() {
+    let mut hdr: DequexVs<usize> = DequexVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    (0..10).for_each(|i| hdr.push_back(i));
+
+    pnk!(hdr.version_create(VersionName(b"v1")));
+    (10..20).for_each(|i| hdr.push_front(i));
+    pnk!(hdr.pop_back());
+    assert_eq!(hdr.len(), 19);
+    assert_eq!(hdr.front(), Some(19));
+    assert_eq!(
+        hdr.len_by_branch_version(INITIAL_BRANCH_NAME, VersionName(b"v0")),
+        10
+    );
+    assert!(
+        hdr.iter_by_branch_version(INITIAL_BRANCH_NAME, VersionName(b"v0"))
+            .rev()
+            .eq((0..10).rev())
+    );
+    assert_eq!(
+        hdr.get_by_branch_version(0, INITIAL_BRANCH_NAME, VersionName(b"v0")),
+        Some(0)
+    );
+
+    // the head and the tail are rolled back with the elements
+    pnk!(hdr.version_pop());
+    assert_eq!(hdr.len(), 10);
+    assert!(hdr.iter().eq(0..10));
+    hdr.push_front(100);
+    assert_eq!(hdr.get(0), Some(100));
+    assert_eq!(hdr.get(10), Some(9));
+}
+
+#[test]
+fn test_branch() {
+    let mut hdr: DequexVs<usize> = DequexVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    hdr.push_back(0);
+
+    let bn = BranchName(b"test");
+    pnk!(hdr.branch_create(bn, VersionName(b"v1"), false));
+    hdr.push_front_by_branch(1, bn);
+    hdr.push_back_by_branch(2, bn);
+    assert_eq!(hdr.len_by_branch(bn), 3);
+    assert!(hdr.iter_by_branch(bn).eq([1, 0, 2]));
+    assert_eq!(hdr.get_by_branch(0, bn), Some(1));
+
+    assert_eq!(hdr.len_by_branch(INITIAL_BRANCH_NAME), 1);
+    assert_eq!(hdr.get_by_branch(0, INITIAL_BRANCH_NAME), Some(0));
+
+    assert_eq!(pnk!(hdr.pop_front_by_branch(bn)), Some(1));
+    assert_eq!(pnk!(hdr.pop_back_by_branch(bn)), Some(2));
+    assert_eq!(hdr.len_by_branch(bn), 1);
+}
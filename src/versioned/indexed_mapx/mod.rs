@@ -0,0 +1,675 @@
+//!
+//! Documents => [MapxRawVs](crate::versioned::mapx_raw)
+//!
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::indexed_mapx::{
+        add_index, backfill_entries, changes, decode_len, decode_record, find_index,
+        index_bounds, index_entries_bounds, index_name_changes, index_names,
+        index_names_bounds, index_prefix, is_fresh, name_key, record_key, split_entry,
+        Extractor, Index, BACKFILL_BATCH_SIZE, INDEXES, LEN_KEY, RECORDS,
+    },
+    common::{
+        ende::{KeyEnDeOrdered, ValueEnDe},
+        ensure_writable, RawKey, RawValue,
+    },
+    versioned::mapx_raw::{MapxRawVs, MapxRawVsIter, View},
+    BranchName, GcMark, GcMarks, VersionName, VsMgmt,
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+/// A `MapxOrdVs`-like structure with automatically maintained secondary indexes.
+///
+/// The records and their index entries are stored in one versioned keyspace,
+/// so they are always switched and popped together with the versions.
+///
+/// NOTE: only the names of the indexes are persisted, the extractors must be
+/// registered again after reloading an instance, writes are refused until
+/// all the persisted indexes have been registered.
+///
+/// Documents => [MapxRawVs](crate::versioned::mapx_raw::MapxRawVs)
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct IndexedMapxVs<K, V> {
+    inner: MapxRawVs,
+    #[serde(skip)]
+    indexes: Vec<Index<V>>,
+    p: PhantomData<K>,
+}
+
+impl<K, V> Clone for IndexedMapxVs<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            indexes: self.indexes.clone(),
+            p: PhantomData,
+        }
+    }
+}
+
+impl<K, V> PartialEq for IndexedMapxVs<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<K, V> Eq for IndexedMapxVs<K, V> {}
+
+impl<K, V> fmt::Debug for IndexedMapxVs<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexedMapxVs")
+            .field("inner", &self.inner)
+            .field("indexes", &index_names(&self.indexes))
+            .finish()
+    }
+}
+
+impl<K, V> Default for IndexedMapxVs<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IndexedMapxVs<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+            indexes: self.indexes.clone(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        IndexedMapxVs {
+            inner: MapxRawVs::new(),
+            indexes: vec![],
+            p: PhantomData,
+        }
+    }
+
+    /// Register an index named `name`, whose keys are extracted by `f`.
+    ///
+    /// On each branch where the index has not been persisted, the existing records
+    /// are indexed in batches in the head version, and then the index is persisted,
+    /// the records of the older versions are not covered.
+    pub fn add_index<I, F>(&mut self, name: &str, f: F) -> Result<()>
+    where
+        I: KeyEnDeOrdered,
+        F: Fn(&V) -> I + Send + Sync + 'static,
+    {
+        add_index(&mut self.indexes, name, f).c(d!())?;
+        let ret = self
+            .inner
+            .branch_list()
+            .iter()
+            .try_for_each(|b| self.backfill(name, b.as_deref()));
+        if ret.is_err() {
+            self.indexes.pop();
+        }
+        ret.c(d!())
+    }
+
+    #[inline(always)]
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.get_by_view(key, View::Default)
+    }
+
+    #[inline(always)]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.inner.contains_key(&record_key(&key.to_bytes()))
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len_by_view(View::Default)
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        0 == self.len()
+    }
+
+    #[inline(always)]
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>> {
+        self.insert_ref(&key, &value).c(d!())
+    }
+
+    /// Insert or update a record, and its index entries along with it.
+    pub fn insert_ref(&mut self, key: &K, value: &V) -> Result<Option<V>> {
+        let old = self.get(key);
+        let changes = changes(
+            &self.indexes,
+            self.inner.codec(),
+            key,
+            old.as_ref(),
+            Some(value),
+            self.len(),
+        );
+        self.write(changes, None).c(d!())?;
+        Ok(old)
+    }
+
+    /// Remove a record, and its index entries along with it.
+    pub fn remove(&mut self, key: &K) -> Result<Option<V>> {
+        let old = match self.get(key) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let changes = changes(
+            &self.indexes,
+            self.inner.codec(),
+            key,
+            Some(&old),
+            None,
+            self.len(),
+        );
+        self.write(changes, None).c(d!())?;
+        Ok(Some(old))
+    }
+
+    /// Iterate over the records in the order of their keys.
+    #[inline(always)]
+    pub fn iter(&self) -> IndexedMapxVsIter<'_, K, V> {
+        IndexedMapxVsIter {
+            hdr: &self.inner,
+            iter: self.inner.range(records()),
+            p: PhantomData,
+        }
+    }
+
+    /// Get the records whose keys of the index `name` equal to `index_key`,
+    /// in the order of their keys.
+    #[inline(always)]
+    pub fn get_by_index<I: KeyEnDeOrdered>(
+        &self,
+        name: &str,
+        index_key: &I,
+    ) -> IndexedMapxVsIndexIter<'_, K, V> {
+        self.range_by_index::<I, _>(
+            name,
+            (Bound::Included(index_key), Bound::Included(index_key)),
+        )
+    }
+
+    /// Iterate over the records whose keys of the index `name` are within `bounds`,
+    /// in the order of the index keys, and then the keys of the records.
+    #[inline(always)]
+    pub fn range_by_index<I, R>(
+        &self,
+        name: &str,
+        bounds: R,
+    ) -> IndexedMapxVsIndexIter<'_, K, V>
+    where
+        I: KeyEnDeOrdered,
+        R: RangeBounds<I>,
+    {
+        IndexedMapxVsIndexIter {
+            hdr: &self.inner,
+            iter: self.inner.range(index_bounds(name, bounds)),
+            prefix_len: index_prefix(name).len(),
+            view: View::Default,
+            index: find_index(&self.indexes, name),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline(always)]
+    pub fn get_by_branch(&self, key: &K, branch_name: BranchName) -> Option<V> {
+        self.get_by_view(key, View::Branch(branch_name))
+    }
+
+    #[inline(always)]
+    pub fn contains_key_by_branch(&self, key: &K, branch_name: BranchName) -> bool {
+        self.inner
+            .contains_key_by_branch(&record_key(&key.to_bytes()), branch_name)
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch(&self, branch_name: BranchName) -> usize {
+        self.len_by_view(View::Branch(branch_name))
+    }
+
+    #[inline(always)]
+    pub fn insert_by_branch(
+        &mut self,
+        key: K,
+        value: V,
+        branch_name: BranchName,
+    ) -> Result<Option<V>> {
+        self.insert_ref_by_branch(&key, &value, branch_name).c(d!())
+    }
+
+    pub fn insert_ref_by_branch(
+        &mut self,
+        key: &K,
+        value: &V,
+        branch_name: BranchName,
+    ) -> Result<Option<V>> {
+        let old = self.get_by_branch(key, branch_name);
+        let changes = changes(
+            &self.indexes,
+            self.inner.codec(),
+            key,
+            old.as_ref(),
+            Some(value),
+            self.len_by_branch(branch_name),
+        );
+        self.write(changes, Some(branch_name)).c(d!())?;
+        Ok(old)
+    }
+
+    pub fn remove_by_branch(
+        &mut self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> Result<Option<V>> {
+        let old = match self.get_by_branch(key, branch_name) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let changes = changes(
+            &self.indexes,
+            self.inner.codec(),
+            key,
+            Some(&old),
+            None,
+            self.len_by_branch(branch_name),
+        );
+        self.write(changes, Some(branch_name)).c(d!())?;
+        Ok(Some(old))
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch(
+        &self,
+        branch_name: BranchName,
+    ) -> IndexedMapxVsIter<'_, K, V> {
+        IndexedMapxVsIter {
+            hdr: &self.inner,
+            iter: self.inner.range_by_branch(branch_name, records()),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_by_index_by_branch<'a, I: KeyEnDeOrdered>(
+        &'a self,
+        name: &str,
+        index_key: &I,
+        branch_name: BranchName<'a>,
+    ) -> IndexedMapxVsIndexIter<'a, K, V> {
+        self.range_by_index_by_branch::<I, _>(
+            name,
+            branch_name,
+            (Bound::Included(index_key), Bound::Included(index_key)),
+        )
+    }
+
+    #[inline(always)]
+    pub fn range_by_index_by_branch<'a, I, R>(
+        &'a self,
+        name: &str,
+        branch_name: BranchName<'a>,
+        bounds: R,
+    ) -> IndexedMapxVsIndexIter<'a, K, V>
+    where
+        I: KeyEnDeOrdered,
+        R: RangeBounds<I>,
+    {
+        IndexedMapxVsIndexIter {
+            hdr: &self.inner,
+            iter: self
+                .inner
+                .range_by_branch(branch_name, index_bounds(name, bounds)),
+            prefix_len: index_prefix(name).len(),
+            view: View::Branch(branch_name),
+            index: find_index(&self.indexes, name),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> Option<V> {
+        self.get_by_view(key, View::BranchVersion(branch_name, version_name))
+    }
+
+    #[inline(always)]
+    pub fn contains_key_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> bool {
+        self.inner.contains_key_by_branch_version(
+            &record_key(&key.to_bytes()),
+            branch_name,
+            version_name,
+        )
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> usize {
+        self.len_by_view(View::BranchVersion(branch_name, version_name))
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> IndexedMapxVsIter<'_, K, V> {
+        IndexedMapxVsIter {
+            hdr: &self.inner,
+            iter: self.inner.range_by_branch_version(
+                branch_name,
+                version_name,
+                records(),
+            ),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_by_index_by_branch_version<'a, I: KeyEnDeOrdered>(
+        &'a self,
+        name: &str,
+        index_key: &I,
+        branch_name: BranchName<'a>,
+        version_name: VersionName<'a>,
+    ) -> IndexedMapxVsIndexIter<'a, K, V> {
+        self.range_by_index_by_branch_version::<I, _>(
+            name,
+            branch_name,
+            version_name,
+            (Bound::Included(index_key), Bound::Included(index_key)),
+        )
+    }
+
+    #[inline(always)]
+    pub fn range_by_index_by_branch_version<'a, I, R>(
+        &'a self,
+        name: &str,
+        branch_name: BranchName<'a>,
+        version_name: VersionName<'a>,
+        bounds: R,
+    ) -> IndexedMapxVsIndexIter<'a, K, V>
+    where
+        I: KeyEnDeOrdered,
+        R: RangeBounds<I>,
+    {
+        IndexedMapxVsIndexIter {
+            hdr: &self.inner,
+            iter: self.inner.range_by_branch_version(
+                branch_name,
+                version_name,
+                index_bounds(name, bounds),
+            ),
+            prefix_len: index_prefix(name).len(),
+            view: View::BranchVersion(branch_name, version_name),
+            index: find_index(&self.indexes, name),
+            p: PhantomData,
+        }
+    }
+
+    // index the existing records of a branch by the newly registered index `name`,
+    // the entries left by an interrupted backfilling are removed first,
+    // and the name is persisted after all the entries have been written
+    fn backfill(&mut self, name: &str, branch_name: BranchName) -> Result<()> {
+        if self.inner.contains_key_by_branch(&name_key(name), branch_name) {
+            return Ok(());
+        }
+        let f = find_index(&self.indexes, name).c(d!())?;
+        let codec = self.inner.codec();
+
+        let (lo, hi) = index_entries_bounds(name);
+        loop {
+            let stale = self
+                .inner
+                .range_by_branch(branch_name, lo.clone()..hi.clone())
+                .take(BACKFILL_BATCH_SIZE)
+                .map(|(k, _)| (k, None))
+                .collect::<Vec<_>>();
+            if stale.is_empty() {
+                break;
+            }
+            ensure_writable().c(d!())?;
+            self.write_by_branch(&stale, branch_name).c(d!())?;
+        }
+
+        if 0 == self.len_by_branch(branch_name) {
+            return Ok(());
+        }
+        ensure_writable().c(d!())?;
+
+        let mut lo = Bound::Included(RawKey::from(&RECORDS[..]));
+        loop {
+            let hi = Bound::Excluded(RawKey::from(&INDEXES[..]));
+            let records = self
+                .inner
+                .range_by_branch(branch_name, (lo.clone(), hi))
+                .take(BACKFILL_BATCH_SIZE)
+                .collect::<Vec<_>>();
+            let last = match records.last() {
+                Some((last, _)) => last,
+                None => break,
+            };
+            let entries = backfill_entries(codec, name, &f, &records).c(d!())?;
+            lo = Bound::Excluded(last.clone());
+            self.write_by_branch(&entries, branch_name).c(d!())?;
+        }
+
+        let kvs = [(name_key(name), Some(RawValue::default()))];
+        self.write_by_branch(&kvs, branch_name).c(d!())
+    }
+
+    // write the KVs to the head version of a branch as they are
+    fn write_by_branch(
+        &mut self,
+        kvs: &[(RawKey, Option<RawValue>)],
+        branch_name: BranchName,
+    ) -> Result<()> {
+        let kvs = kvs
+            .iter()
+            .map(|(k, v)| (&k[..], v.as_deref()))
+            .collect::<Vec<_>>();
+        self.inner.write_batch_by_branch(&kvs, branch_name).c(d!())
+    }
+
+    // write all the changes to the head version in one batch,
+    // along with the names of the newly registered indexes,
+    // of the default branch if `branch_name` is `None`
+    fn write(
+        &mut self,
+        changes: Vec<(RawKey, Option<RawValue>)>,
+        branch_name: Option<BranchName>,
+    ) -> Result<()> {
+        let (lo, hi) = index_names_bounds();
+        let persisted = match branch_name {
+            Some(b) => self.inner.range_by_branch(b, lo..hi),
+            None => self.inner.range(lo..hi),
+        }
+        .map(|(k, _)| k);
+        let mut kvs = index_name_changes(&self.indexes, persisted).c(d!())?;
+        kvs.extend(changes);
+
+        let kvs = kvs
+            .iter()
+            .map(|(k, v)| (&k[..], v.as_deref()))
+            .collect::<Vec<_>>();
+        match branch_name {
+            Some(b) => self.inner.write_batch_by_branch(&kvs, b),
+            None => self.inner.write_batch(&kvs),
+        }
+        .c(d!())
+    }
+
+    #[inline(always)]
+    fn get_by_view(&self, key: &K, view: View) -> Option<V> {
+        let k = record_key(&key.to_bytes());
+        view.get(&self.inner, &k)
+            .map(|v| pnk!(self.inner.codec().decode_by_key(&k, &v)))
+    }
+
+    #[inline(always)]
+    fn len_by_view(&self, view: View) -> usize {
+        view.get(&self.inner, &LEN_KEY)
+            .map(|l| decode_len(&l))
+            .unwrap_or(0)
+    }
+}
+
+impl<K, V> VsMgmt for IndexedMapxVs<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    crate::impl_vs_methods!();
+}
+
+impl<K, V> GcMark for IndexedMapxVs<K, V>
+where
+    V: ValueEnDe + GcMark,
+{
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        if self.inner.gc_mark_instance(marks) && !V::gc_nope() {
+            self.inner.for_each_kv_globally(|k, v| {
+                if k.starts_with(&RECORDS) {
+                    pnk!(self.inner.codec().decode::<V>(&v)).gc_mark(marks);
+                }
+            });
+        }
+    }
+}
+
+pub struct IndexedMapxVsIter<'a, K, V> {
+    hdr: &'a MapxRawVs,
+    iter: MapxRawVsIter<'a>,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> Iterator for IndexedMapxVsIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(k, v)| pnk!(decode_record(self.hdr.codec(), &k, &v)))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IndexedMapxVsIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|(k, v)| pnk!(decode_record(self.hdr.codec(), &k, &v)))
+    }
+}
+
+pub struct IndexedMapxVsIndexIter<'a, K, V> {
+    hdr: &'a MapxRawVs,
+    iter: MapxRawVsIter<'a>,
+    prefix_len: usize,
+    // the records are read from the same view as the index entries
+    view: View<'a>,
+    // the extractor of the index, `None` if it has not been registered
+    index: Option<Extractor<V>>,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> IndexedMapxVsIndexIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    // `None` if the entry is stale
+    fn get_record(&self, entry: &[u8]) -> Option<(K, V)> {
+        let (ik, k) = pnk!(split_entry(&entry[self.prefix_len..]));
+        let k = record_key(k);
+        let v = self.view.get(self.hdr, &k)?;
+        let (k, v) = pnk!(decode_record(self.hdr.codec(), &k, &v));
+        is_fresh(self.index.as_ref(), &ik, &v).then_some((k, v))
+    }
+}
+
+impl<'a, K, V> Iterator for IndexedMapxVsIndexIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entry, _) = self.iter.next()?;
+            if let Some(r) = self.get_record(&entry) {
+                return Some(r);
+            }
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IndexedMapxVsIndexIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let (entry, _) = self.iter.next_back()?;
+            if let Some(r) = self.get_record(&entry) {
+                return Some(r);
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn records() -> (Bound<RawKey>, Bound<RawKey>) {
+    (
+        Bound::Included(RECORDS.into()),
+        Bound::Excluded(INDEXES.into()),
+    )
+}
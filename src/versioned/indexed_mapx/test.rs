@@ -0,0 +1,155 @@
+use super::*;
+use crate::common::INITIAL_BRANCH_NAME;
+
+fn keys_of<I: Iterator<Item = (u32, String)>>(iter: I) -> Vec<u32> {
+    iter.map(|(k, _)| k).collect()
+}
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr: IndexedMapxVs<u32, String> = IndexedMapxVs::new();
+    pnk!(hdr.add_index("len", |v: &String| v.len() as u64));
+    pnk!(hdr.version_create(VersionName(b"v0")));
+
+    (0..10u32).for_each(|i| {
+        assert!(pnk!(hdr.insert(i, "x".repeat(1 + i as usize % 3))).is_none());
+    });
+    assert_eq!(hdr.len(), 10);
+    assert_eq!(keys_of(hdr.get_by_index("len", &1u64)), [0, 3, 6, 9]);
+    assert_eq!(keys_of(hdr.range_by_index("len", 2u64..)).len(), 6);
+
+    pnk!(hdr.version_create(VersionName(b"v1")));
+    assert_eq!(
+        pnk!(hdr.insert(0, "xxxxx".to_owned())),
+        Some("x".to_owned())
+    );
+    assert_eq!(pnk!(hdr.remove(&3)), Some("x".to_owned()));
+    assert!(pnk!(hdr.remove(&3)).is_none());
+    assert_eq!(keys_of(hdr.get_by_index("len", &1u64)), [6, 9]);
+    assert_eq!(keys_of(hdr.get_by_index("len", &5u64)), [0]);
+    assert_eq!(hdr.len(), 9);
+    assert_eq!(
+        keys_of(hdr.get_by_index_by_branch_version(
+            "len",
+            &1u64,
+            INITIAL_BRANCH_NAME,
+            VersionName(b"v0")
+        )),
+        [0, 3, 6, 9]
+    );
+    assert_eq!(
+        hdr.len_by_branch_version(INITIAL_BRANCH_NAME, VersionName(b"v0")),
+        10
+    );
+
+    // the index entries are popped along with the records
+    pnk!(hdr.version_pop());
+    assert_eq!(keys_of(hdr.get_by_index("len", &1u64)), [0, 3, 6, 9]);
+    assert!(hdr.get_by_index("len", &5u64).next().is_none());
+    assert_eq!(hdr.get(&0), Some("x".to_owned()));
+    assert_eq!(hdr.len(), 10);
+    assert_eq!(keys_of(hdr.iter()), (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_branch() {
+    let mut hdr: IndexedMapxVs<u32, String> = IndexedMapxVs::new();
+    pnk!(hdr.add_index("value", |v: &String| v.clone()));
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    pnk!(hdr.insert(1, "a".to_owned()));
+    pnk!(hdr.insert(2, "b".to_owned()));
+
+    let bn = BranchName(b"test");
+    pnk!(hdr.branch_create(bn, VersionName(b"v1"), false));
+    pnk!(hdr.insert_by_branch(1, "c".to_owned(), bn));
+    pnk!(hdr.insert_by_branch(3, "a".to_owned(), bn));
+    pnk!(hdr.remove_by_branch(&2, bn));
+
+    let a = "a".to_owned();
+    assert_eq!(keys_of(hdr.get_by_index_by_branch("value", &a, bn)), [3]);
+    assert_eq!(keys_of(hdr.get_by_index("value", &a)), [1]);
+    assert!(
+        hdr.range_by_index_by_branch::<String, _>("value", bn, ..)
+            .eq([(3, "a".to_owned()), (1, "c".to_owned())])
+    );
+    assert_eq!(keys_of(hdr.iter_by_branch(bn)), [1, 3]);
+    assert_eq!(hdr.len_by_branch(bn), 2);
+    assert_eq!(hdr.len(), 2);
+
+    pnk!(hdr.branch_remove(bn));
+    assert_eq!(
+        keys_of(hdr.range_by_index::<String, _>("value", ..)),
+        [1, 2]
+    );
+}
+
+#[test]
+fn test_reload() {
+    let mut hdr: IndexedMapxVs<u32, String> = IndexedMapxVs::new();
+    pnk!(hdr.add_index("len", |v: &String| v.len() as u64));
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    pnk!(hdr.insert(1, "a".to_owned()));
+
+    // the writes are refused until all the indexes are registered again
+    let mut reloaded = pnk!(IndexedMapxVs::<u32, String>::decode(&hdr.encode()));
+    assert!(reloaded.insert(2, "bb".to_owned()).is_err());
+    assert!(reloaded.remove(&1).is_err());
+    assert!(reloaded.get(&2).is_none());
+    assert_eq!(reloaded.len(), 1);
+
+    pnk!(reloaded.add_index("len", |v: &String| v.len() as u64));
+    pnk!(reloaded.insert(1, "bb".to_owned()));
+    assert_eq!(keys_of(hdr.get_by_index("len", &2u64)), [1]);
+    assert!(hdr.get_by_index("len", &1u64).next().is_none());
+
+    // the names are popped along with the versions,
+    // the existing records are indexed in the head version
+    pnk!(reloaded.version_create(VersionName(b"v1")));
+    pnk!(reloaded.add_index("bytes", |v: &String| v.as_bytes().to_vec()));
+    pnk!(reloaded.insert(2, "cc".to_owned()));
+    let mut hdr = pnk!(IndexedMapxVs::<u32, String>::decode(&hdr.encode()));
+    pnk!(hdr.add_index("len", |v: &String| v.len() as u64));
+    assert!(hdr.insert(3, "d".to_owned()).is_err());
+    pnk!(hdr.version_pop());
+    pnk!(hdr.insert(3, "d".to_owned()));
+}
+
+#[test]
+fn test_backfill() {
+    let mut hdr: IndexedMapxVs<u32, String> = IndexedMapxVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    pnk!(hdr.insert(1, "a".to_owned()));
+    pnk!(hdr.insert(2, "bb".to_owned()));
+
+    let bn = BranchName(b"test");
+    pnk!(hdr.branch_create(bn, VersionName(b"v1"), false));
+    pnk!(hdr.insert_by_branch(3, "cc".to_owned(), bn));
+    pnk!(hdr.version_create(VersionName(b"v2")));
+    pnk!(hdr.insert(4, "d".to_owned()));
+
+    pnk!(hdr.add_index("len", |v: &String| v.len() as u64));
+    assert_eq!(keys_of(hdr.get_by_index("len", &1u64)), [1, 4]);
+    assert_eq!(keys_of(hdr.get_by_index("len", &2u64)), [2]);
+    assert_eq!(keys_of(hdr.get_by_index_by_branch("len", &2u64, bn)), [2, 3]);
+    assert_eq!(
+        keys_of(hdr.get_by_index_by_branch("len", &1u64, bn)),
+        [1]
+    );
+
+    // the older versions are not covered
+    assert!(
+        hdr.get_by_index_by_branch_version(
+            "len",
+            &1u64,
+            INITIAL_BRANCH_NAME,
+            VersionName(b"v0")
+        )
+        .next()
+        .is_none()
+    );
+
+    // the indexes have been persisted on all the branches
+    let mut reloaded = pnk!(IndexedMapxVs::<u32, String>::decode(&hdr.encode()));
+    assert!(reloaded.insert(5, "e".to_owned()).is_err());
+    assert!(reloaded.insert_by_branch(5, "e".to_owned(), bn).is_err());
+}
@@ -6,15 +6,21 @@
 mod test;
 
 use crate::{
-    common::ende::{KeyEnDe, ValueEnDe},
-    versioned::mapx_ord_rawkey::{MapxOrdRawKeyVs, MapxOrdRawKeyVsIter},
-    BranchName, VersionName, VsMgmt,
+    common::{
+        ende::{KeyEnDe, ValueEnDe},
+        error::VsdbResult,
+    },
+    versioned::mapx_ord_rawkey::{
+        MapxOrdRawKeyVs, MapxOrdRawKeyVsIter, MapxOrdRawKeyVsIterMut,
+        MapxOrdRawKeyVsTryIter, ValueIterMut,
+    },
+    BranchName, Entry, EntryHdr, GcMark, GcMarks, InstanceOpts, VersionName, VsMgmt,
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
 use std::{
     marker::PhantomData,
-    ops::{Bound, Deref, DerefMut, RangeBounds},
+    ops::{Deref, DerefMut},
 };
 
 /// Documents => [MapxRawVs](crate::versioned::mapx_raw::MapxRawVs)
@@ -49,6 +55,10 @@ where
     K: KeyEnDe,
     V: ValueEnDe,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -65,19 +75,65 @@ where
         }
     }
 
+    /// Create an instance with the specified options,
+    /// the options are applied to the stored values of all versions.
+    #[inline(always)]
+    pub fn new_with_opts(opts: &InstanceOpts) -> Self {
+        MapxVs {
+            inner: MapxOrdRawKeyVs::new_with_opts(opts),
+            p: PhantomData,
+        }
+    }
+
+    /// Rewrite the values of all branches and versions to the latest version
+    /// of the schema `schema`, into new storages that replace the old ones,
+    /// so the handle should be saved again.
+    ///
+    /// The old storages are left untouched, the old handle is still usable
+    /// if the process crashes before the new handle has been saved.
+    ///
+    /// A handle stored as a value of another instance(e.g. the inner maps of
+    /// `MapxOrd<K, MapxVs<K2, V>>`) is a copy, which is not updated by this,
+    /// so write the migrated handle back, or the stored one keeps pointing to
+    /// the old storages, and the new storages will be removed by `vsdb_gc`.
+    ///
+    /// The old storages are never removed by the migration, they take as much space
+    /// as the new ones until they are reclaimed by `vsdb_gc`, so run `vsdb_gc` after
+    /// the new handle has been saved, which also reclaims the new storages
+    /// left behind by a crashed migration.
+    ///
+    /// Values stored without a schema are treated as version 0 of it,
+    /// this is an offline routine, the instance should not be used elsewhere meanwhile.
+    #[inline(always)]
+    pub fn migrate_instance(&mut self, schema: &str) -> Result<()> {
+        self.inner.migrate_instance(schema).c(d!())
+    }
+
     #[inline(always)]
     pub fn get(&self, key: &K) -> Option<V> {
         self.inner.get(&key.encode())
     }
 
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, key: &K) -> VsdbResult<Option<V>> {
+        self.inner.try_get(&key.encode())
+    }
+
     #[inline(always)]
     pub fn get_mut<'a>(&'a mut self, key: &'a K) -> Option<ValueMut<'a, K, V>> {
         self.get(key).map(move |v| ValueMut::new(self, key, v))
     }
 
     #[inline(always)]
-    pub fn entry_ref<'a>(&'a mut self, key: &'a K) -> Entry<'a, K, V> {
-        Entry { key, hdr: self }
+    pub fn entry(&mut self, key: K) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
+    #[inline(always)]
+    pub fn entry_ref<'a>(&'a mut self, key: &'a K) -> EntryRef<'a, K, V> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
@@ -115,15 +171,30 @@ where
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> MapxVsIter<K, V> {
+    pub fn iter(&self) -> MapxVsIter<'_, K, V> {
         MapxVsIter {
             iter: self.inner.iter(),
             p: PhantomData,
         }
     }
 
-    // TODO
-    // pub fn iter_mut
+    /// Same as `iter`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxVsTryIter<'_, K, V> {
+        MapxVsTryIter {
+            iter: self.inner.try_iter(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> MapxVsIterMut<'_, K, V> {
+        MapxVsIterMut {
+            iter: self.inner.iter_mut(),
+            p: PhantomData,
+        }
+    }
 
     #[inline(always)]
     pub fn first(&self) -> Option<(K, V)> {
@@ -155,6 +226,17 @@ where
         self.inner.get_by_branch(&key.encode(), branch_name)
     }
 
+    /// Same as `get_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> VsdbResult<Option<V>> {
+        self.inner.try_get_by_branch(&key.encode(), branch_name)
+    }
+
     #[inline(always)]
     pub fn get_le_by_branch(&self, key: &K, branch_name: BranchName) -> Option<(K, V)> {
         self.inner
@@ -202,13 +284,26 @@ where
     }
 
     #[inline(always)]
-    pub fn iter_by_branch(&self, branch_name: BranchName) -> MapxVsIter<K, V> {
+    pub fn iter_by_branch(&self, branch_name: BranchName) -> MapxVsIter<'_, K, V> {
         MapxVsIter {
             iter: self.inner.iter_by_branch(branch_name),
             p: PhantomData,
         }
     }
 
+    /// Same as `iter_by_branch`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch(
+        &self,
+        branch_name: BranchName,
+    ) -> MapxVsTryIter<'_, K, V> {
+        MapxVsTryIter {
+            iter: self.inner.try_iter_by_branch(branch_name),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn first_by_branch(&self, branch_name: BranchName) -> Option<(K, V)> {
         self.iter_by_branch(branch_name).next()
@@ -247,6 +342,19 @@ where
             .get_by_branch_version(&key.encode(), branch_name, version_name)
     }
 
+    /// Same as `get_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> VsdbResult<Option<V>> {
+        self.inner
+            .try_get_by_branch_version(&key.encode(), branch_name, version_name)
+    }
+
     #[inline(always)]
     pub fn get_le_by_branch_version(
         &self,
@@ -295,13 +403,29 @@ where
         &self,
         branch_name: BranchName,
         version_name: VersionName,
-    ) -> MapxVsIter<K, V> {
+    ) -> MapxVsIter<'_, K, V> {
         MapxVsIter {
             iter: self.inner.iter_by_branch_version(branch_name, version_name),
             p: PhantomData,
         }
     }
 
+    /// Same as `iter_by_branch_version`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> MapxVsTryIter<'_, K, V> {
+        MapxVsTryIter {
+            iter: self
+                .inner
+                .try_iter_by_branch_version(branch_name, version_name),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn first_by_branch_version(
         &self,
@@ -345,6 +469,37 @@ where
     crate::impl_vs_methods!();
 }
 
+impl<K, V> EntryHdr for MapxVs<K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Key = K;
+    type Value = V;
+
+    fn entry_get(&self, key: &K) -> Option<V> {
+        self.get(key)
+    }
+
+    fn entry_set(&mut self, key: &K, value: &V) {
+        pnk!(self.insert_ref(key, value));
+    }
+
+    fn entry_remove(&mut self, key: &K) {
+        pnk!(self.remove(key));
+    }
+}
+
+impl<K, V> GcMark for MapxVs<K, V>
+where
+    V: ValueEnDe + GcMark,
+{
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
 pub struct MapxVsIter<'a, K, V>
 where
     K: KeyEnDe,
@@ -386,6 +541,74 @@ where
 {
 }
 
+pub struct MapxVsTryIter<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    iter: MapxOrdRawKeyVsTryIter<'a, V>,
+    p: PhantomData<K>,
+}
+
+impl<'a, K, V> Iterator for MapxVsTryIter<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Item = VsdbResult<(K, V)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|kv| kv.and_then(|(k, v)| Ok((<K as KeyEnDe>::decode(&k)?, v))))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxVsTryIter<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|kv| kv.and_then(|(k, v)| Ok((<K as KeyEnDe>::decode(&k)?, v))))
+    }
+}
+
+pub struct MapxVsIterMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    iter: MapxOrdRawKeyVsIterMut<'a, V>,
+    p: PhantomData<K>,
+}
+
+impl<'a, K, V> Iterator for MapxVsIterMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Item = (K, ValueIterMut<'a, V>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(k, v)| (pnk!(<K as KeyEnDe>::decode(&k)), v))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxVsIterMut<'a, K, V>
+where
+    K: KeyEnDe,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|(k, v)| (pnk!(<K as KeyEnDe>::decode(&k)), v))
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct ValueMut<'a, K, V>
 where
@@ -438,7 +661,7 @@ where
     }
 }
 
-pub struct Entry<'a, K, V>
+pub struct EntryRef<'a, K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
@@ -447,7 +670,7 @@ where
     key: &'a K,
 }
 
-impl<'a, K, V> Entry<'a, K, V>
+impl<'a, K, V> EntryRef<'a, K, V>
 where
     K: KeyEnDe,
     V: ValueEnDe,
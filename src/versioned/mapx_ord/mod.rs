@@ -5,9 +5,16 @@
 mod test;
 
 use crate::{
-    common::ende::{KeyEnDeOrdered, ValueEnDe},
-    versioned::mapx_ord_rawkey::{MapxOrdRawKeyVs, MapxOrdRawKeyVsIter},
-    BranchName, VersionName, VsMgmt,
+    common::{
+        ende::{KeyEnDeOrdered, KeyEncoding, ValueEnDe},
+        error::VsdbResult,
+        RawKey,
+    },
+    versioned::mapx_ord_rawkey::{
+        MapxOrdRawKeyVs, MapxOrdRawKeyVsIter, MapxOrdRawKeyVsIterMut,
+        MapxOrdRawKeyVsTryIter, ValueIterMut,
+    },
+    BranchName, Entry, EntryHdr, GcMark, GcMarks, InstanceOpts, VersionName, VsMgmt,
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -48,6 +55,10 @@ where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -64,9 +75,80 @@ where
         }
     }
 
+    /// Create an instance with the specified options,
+    /// the options are applied to the stored values of all versions.
+    #[inline(always)]
+    pub fn new_with_opts(opts: &InstanceOpts) -> Self {
+        MapxOrdVs {
+            inner: MapxOrdRawKeyVs::new_with_opts(opts),
+            p: PhantomData,
+        }
+    }
+
+    /// Rewrite the values of all branches and versions to the latest version
+    /// of the schema `schema`, into new storages that replace the old ones,
+    /// so the handle should be saved again.
+    ///
+    /// The old storages are left untouched, the old handle is still usable
+    /// if the process crashes before the new handle has been saved.
+    ///
+    /// A handle stored as a value of another instance(e.g. the inner maps of
+    /// `MapxOrd<K, MapxVs<K2, V>>`) is a copy, which is not updated by this,
+    /// so write the migrated handle back, or the stored one keeps pointing to
+    /// the old storages, and the new storages will be removed by `vsdb_gc`.
+    ///
+    /// The old storages are never removed by the migration, they take as much space
+    /// as the new ones until they are reclaimed by `vsdb_gc`, so run `vsdb_gc` after
+    /// the new handle has been saved, which also reclaims the new storages
+    /// left behind by a crashed migration.
+    ///
+    /// Values stored without a schema are treated as version 0 of it,
+    /// this is an offline routine, the instance should not be used elsewhere meanwhile.
+    #[inline(always)]
+    pub fn migrate_instance(&mut self, schema: &str) -> Result<()> {
+        self.inner.migrate_instance(schema).c(d!())
+    }
+
+    /// Re-encode all the keys of an instance created by older versions of VSDB,
+    /// on all branches and versions, in which the negative keys of signed integers
+    /// sort after the positive ones, it is a no-op for the instances
+    /// created by the current version.
+    ///
+    /// The keys are written into new storages that replace the old ones,
+    /// so the handle should be saved again, the old storages are left untouched
+    /// until they are reclaimed by `vsdb_gc`.
+    ///
+    /// Like `migrate_instance`, a copy of the handle stored in another instance
+    /// is not updated, the migrated handle should be written back into it.
+    ///
+    /// This is an offline routine, the instance should not be used elsewhere meanwhile.
+    pub fn migrate_legacy_keys(&mut self) -> Result<()> {
+        if KeyEncoding::Legacy != self.inner.key_encoding() {
+            return Ok(());
+        }
+        self.inner.rekey(K::upgrade_legacy_bytes).c(d!())
+    }
+
+    #[inline(always)]
+    fn encode_key(&self, key: &K) -> RawKey {
+        self.inner.key_encoding().encode(key)
+    }
+
+    #[inline(always)]
+    fn decode_key(&self, key: RawKey) -> VsdbResult<K> {
+        self.inner.key_encoding().decode(key)
+    }
+
     #[inline(always)]
     pub fn get(&self, key: &K) -> Option<V> {
-        self.inner.get(&key.to_bytes())
+        self.inner.get(&self.encode_key(key))
+    }
+
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, key: &K) -> VsdbResult<Option<V>> {
+        self.inner.try_get(&self.encode_key(key))
     }
 
     #[inline(always)]
@@ -75,22 +157,27 @@ where
     }
 
     #[inline(always)]
-    pub fn entry_ref<'a>(&'a mut self, key: &'a K) -> Entry<'a, K, V> {
-        Entry { key, hdr: self }
+    pub fn entry(&mut self, key: K) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
+    #[inline(always)]
+    pub fn entry_ref<'a>(&'a mut self, key: &'a K) -> EntryRef<'a, K, V> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
     pub fn get_le(&self, key: &K) -> Option<(K, V)> {
         self.inner
-            .get_le(&key.to_bytes())
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_le(&self.encode_key(key))
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
     pub fn get_ge(&self, key: &K) -> Option<(K, V)> {
         self.inner
-            .get_ge(&key.to_bytes())
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_ge(&self.encode_key(key))
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
@@ -110,20 +197,37 @@ where
 
     #[inline(always)]
     pub fn insert_ref(&mut self, key: &K, value: &V) -> Result<Option<V>> {
-        self.inner.insert_ref(&key.to_bytes(), value).c(d!())
+        self.inner.insert_ref(&self.encode_key(key), value).c(d!())
     }
 
     #[inline(always)]
-    pub fn iter(&self) -> MapxOrdVsIter<K, V> {
+    pub fn iter(&self) -> MapxOrdVsIter<'_, K, V> {
         MapxOrdVsIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.iter(),
             p: PhantomData,
         }
     }
 
-    // TODO
-    // pub fn iter_mut
-    // pub fn range_mut
+    /// Same as `iter`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxOrdVsTryIter<'_, K, V> {
+        MapxOrdVsTryIter {
+            key_encoding: self.inner.key_encoding(),
+            iter: self.inner.try_iter(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> MapxOrdVsIterMut<'_, K, V> {
+        MapxOrdVsIterMut {
+            key_encoding: self.inner.key_encoding(),
+            iter: self.inner.iter_mut(),
+            p: PhantomData,
+        }
+    }
 
     #[inline(always)]
     pub fn range<'a, R: 'a + RangeBounds<K>>(
@@ -131,22 +235,46 @@ where
         bounds: R,
     ) -> MapxOrdVsIter<'a, K, V> {
         let l = match bounds.start_bound() {
-            Bound::Included(i) => Bound::Included(i.to_bytes()),
-            Bound::Excluded(i) => Bound::Excluded(i.to_bytes()),
+            Bound::Included(i) => Bound::Included(self.encode_key(i)),
+            Bound::Excluded(i) => Bound::Excluded(self.encode_key(i)),
             _ => Bound::Unbounded,
         };
         let h = match bounds.end_bound() {
-            Bound::Included(i) => Bound::Included(i.to_bytes()),
-            Bound::Excluded(i) => Bound::Excluded(i.to_bytes()),
+            Bound::Included(i) => Bound::Included(self.encode_key(i)),
+            Bound::Excluded(i) => Bound::Excluded(self.encode_key(i)),
             _ => Bound::Unbounded,
         };
 
         MapxOrdVsIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.range((l, h)),
             p: PhantomData,
         }
     }
 
+    #[inline(always)]
+    pub fn range_mut<'a, R: 'a + RangeBounds<K>>(
+        &'a mut self,
+        bounds: R,
+    ) -> MapxOrdVsIterMut<'a, K, V> {
+        let l = match bounds.start_bound() {
+            Bound::Included(i) => Bound::Included(self.encode_key(i)),
+            Bound::Excluded(i) => Bound::Excluded(self.encode_key(i)),
+            _ => Bound::Unbounded,
+        };
+        let h = match bounds.end_bound() {
+            Bound::Included(i) => Bound::Included(self.encode_key(i)),
+            Bound::Excluded(i) => Bound::Excluded(self.encode_key(i)),
+            _ => Bound::Unbounded,
+        };
+
+        MapxOrdVsIterMut {
+            key_encoding: self.inner.key_encoding(),
+            iter: self.inner.range_mut((l, h)),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn first(&self) -> Option<(K, V)> {
         self.iter().next()
@@ -159,12 +287,12 @@ where
 
     #[inline(always)]
     pub fn contains_key(&self, key: &K) -> bool {
-        self.inner.contains_key(&key.to_bytes())
+        self.inner.contains_key(&self.encode_key(key))
     }
 
     #[inline(always)]
     pub fn remove(&mut self, key: &K) -> Result<Option<V>> {
-        self.inner.remove(&key.to_bytes()).c(d!())
+        self.inner.remove(&self.encode_key(key)).c(d!())
     }
 
     #[inline(always)]
@@ -174,21 +302,32 @@ where
 
     #[inline(always)]
     pub fn get_by_branch(&self, key: &K, branch_name: BranchName) -> Option<V> {
-        self.inner.get_by_branch(&key.to_bytes(), branch_name)
+        self.inner.get_by_branch(&self.encode_key(key), branch_name)
+    }
+
+    /// Same as `get_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> VsdbResult<Option<V>> {
+        self.inner.try_get_by_branch(&self.encode_key(key), branch_name)
     }
 
     #[inline(always)]
     pub fn get_le_by_branch(&self, key: &K, branch_name: BranchName) -> Option<(K, V)> {
         self.inner
-            .get_le_by_branch(&key.to_bytes(), branch_name)
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_le_by_branch(&self.encode_key(key), branch_name)
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
     pub fn get_ge_by_branch(&self, key: &K, branch_name: BranchName) -> Option<(K, V)> {
         self.inner
-            .get_ge_by_branch(&key.to_bytes(), branch_name)
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_ge_by_branch(&self.encode_key(key), branch_name)
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
@@ -219,18 +358,33 @@ where
         branch_name: BranchName,
     ) -> Result<Option<V>> {
         self.inner
-            .insert_ref_by_branch(&key.to_bytes(), value, branch_name)
+            .insert_ref_by_branch(&self.encode_key(key), value, branch_name)
             .c(d!())
     }
 
     #[inline(always)]
-    pub fn iter_by_branch(&self, branch_name: BranchName) -> MapxOrdVsIter<K, V> {
+    pub fn iter_by_branch(&self, branch_name: BranchName) -> MapxOrdVsIter<'_, K, V> {
         MapxOrdVsIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.iter_by_branch(branch_name),
             p: PhantomData,
         }
     }
 
+    /// Same as `iter_by_branch`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch(
+        &self,
+        branch_name: BranchName,
+    ) -> MapxOrdVsTryIter<'_, K, V> {
+        MapxOrdVsTryIter {
+            key_encoding: self.inner.key_encoding(),
+            iter: self.inner.try_iter_by_branch(branch_name),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn range_by_branch<'a, R: 'a + RangeBounds<K>>(
         &'a self,
@@ -238,17 +392,18 @@ where
         bounds: R,
     ) -> MapxOrdVsIter<'a, K, V> {
         let l = match bounds.start_bound() {
-            Bound::Included(i) => Bound::Included(i.to_bytes()),
-            Bound::Excluded(i) => Bound::Excluded(i.to_bytes()),
+            Bound::Included(i) => Bound::Included(self.encode_key(i)),
+            Bound::Excluded(i) => Bound::Excluded(self.encode_key(i)),
             _ => Bound::Unbounded,
         };
         let h = match bounds.end_bound() {
-            Bound::Included(i) => Bound::Included(i.to_bytes()),
-            Bound::Excluded(i) => Bound::Excluded(i.to_bytes()),
+            Bound::Included(i) => Bound::Included(self.encode_key(i)),
+            Bound::Excluded(i) => Bound::Excluded(self.encode_key(i)),
             _ => Bound::Unbounded,
         };
 
         MapxOrdVsIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.range_by_branch(branch_name, (l, h)),
             p: PhantomData,
         }
@@ -267,7 +422,7 @@ where
     #[inline(always)]
     pub fn contains_key_by_branch(&self, key: &K, branch_name: BranchName) -> bool {
         self.inner
-            .contains_key_by_branch(&key.to_bytes(), branch_name)
+            .contains_key_by_branch(&self.encode_key(key), branch_name)
     }
 
     #[inline(always)]
@@ -277,7 +432,7 @@ where
         branch_name: BranchName,
     ) -> Result<Option<V>> {
         self.inner
-            .remove_by_branch(&key.to_bytes(), branch_name)
+            .remove_by_branch(&self.encode_key(key), branch_name)
             .c(d!())
     }
 
@@ -289,7 +444,20 @@ where
         version_name: VersionName,
     ) -> Option<V> {
         self.inner
-            .get_by_branch_version(&key.to_bytes(), branch_name, version_name)
+            .get_by_branch_version(&self.encode_key(key), branch_name, version_name)
+    }
+
+    /// Same as `get_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> VsdbResult<Option<V>> {
+        self.inner
+            .try_get_by_branch_version(&self.encode_key(key), branch_name, version_name)
     }
 
     #[inline(always)]
@@ -300,8 +468,8 @@ where
         version_name: VersionName,
     ) -> Option<(K, V)> {
         self.inner
-            .get_le_by_branch_version(&key.to_bytes(), branch_name, version_name)
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_le_by_branch_version(&self.encode_key(key), branch_name, version_name)
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
@@ -312,8 +480,8 @@ where
         version_name: VersionName,
     ) -> Option<(K, V)> {
         self.inner
-            .get_ge_by_branch_version(&key.to_bytes(), branch_name, version_name)
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .get_ge_by_branch_version(&self.encode_key(key), branch_name, version_name)
+            .map(|(k, v)| (pnk!(self.decode_key(k)), v))
     }
 
     #[inline(always)]
@@ -340,13 +508,31 @@ where
         &self,
         branch_name: BranchName,
         version_name: VersionName,
-    ) -> MapxOrdVsIter<K, V> {
+    ) -> MapxOrdVsIter<'_, K, V> {
         MapxOrdVsIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self.inner.iter_by_branch_version(branch_name, version_name),
             p: PhantomData,
         }
     }
 
+    /// Same as `iter_by_branch_version`, but a broken key or value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> MapxOrdVsTryIter<'_, K, V> {
+        MapxOrdVsTryIter {
+            key_encoding: self.inner.key_encoding(),
+            iter: self
+                .inner
+                .try_iter_by_branch_version(branch_name, version_name),
+            p: PhantomData,
+        }
+    }
+
     #[inline(always)]
     pub fn range_by_branch_version<'a, R: 'a + RangeBounds<K>>(
         &'a self,
@@ -355,17 +541,18 @@ where
         bounds: R,
     ) -> MapxOrdVsIter<'a, K, V> {
         let l = match bounds.start_bound() {
-            Bound::Included(i) => Bound::Included(i.to_bytes()),
-            Bound::Excluded(i) => Bound::Excluded(i.to_bytes()),
+            Bound::Included(i) => Bound::Included(self.encode_key(i)),
+            Bound::Excluded(i) => Bound::Excluded(self.encode_key(i)),
             _ => Bound::Unbounded,
         };
         let h = match bounds.end_bound() {
-            Bound::Included(i) => Bound::Included(i.to_bytes()),
-            Bound::Excluded(i) => Bound::Excluded(i.to_bytes()),
+            Bound::Included(i) => Bound::Included(self.encode_key(i)),
+            Bound::Excluded(i) => Bound::Excluded(self.encode_key(i)),
             _ => Bound::Unbounded,
         };
 
         MapxOrdVsIter {
+            key_encoding: self.inner.key_encoding(),
             iter: self
                 .inner
                 .range_by_branch_version(branch_name, version_name, (l, h)),
@@ -401,7 +588,7 @@ where
         version_name: VersionName,
     ) -> bool {
         self.inner.contains_key_by_branch_version(
-            &key.to_bytes(),
+            &self.encode_key(key),
             branch_name,
             version_name,
         )
@@ -416,12 +603,44 @@ where
     crate::impl_vs_methods!();
 }
 
+impl<K, V> EntryHdr for MapxOrdVs<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Key = K;
+    type Value = V;
+
+    fn entry_get(&self, key: &K) -> Option<V> {
+        self.get(key)
+    }
+
+    fn entry_set(&mut self, key: &K, value: &V) {
+        pnk!(self.insert_ref(key, value));
+    }
+
+    fn entry_remove(&mut self, key: &K) {
+        pnk!(self.remove(key));
+    }
+}
+
+impl<K, V> GcMark for MapxOrdVs<K, V>
+where
+    V: ValueEnDe + GcMark,
+{
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
 pub struct MapxOrdVsIter<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
 {
     iter: MapxOrdRawKeyVsIter<'a, V>,
+    key_encoding: KeyEncoding,
     p: PhantomData<K>,
 }
 
@@ -432,7 +651,7 @@ where
 {
     type Item = (K, V);
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+        self.iter.next().map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
     }
 }
 
@@ -444,7 +663,7 @@ where
     fn next_back(&mut self) -> Option<Self::Item> {
         self.iter
             .next_back()
-            .map(|(k, v)| (pnk!(K::from_bytes(k)), v))
+            .map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
     }
 }
 
@@ -455,6 +674,74 @@ where
 {
 }
 
+pub struct MapxOrdVsTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    iter: MapxOrdRawKeyVsTryIter<'a, V>,
+    key_encoding: KeyEncoding,
+    p: PhantomData<K>,
+}
+
+impl<'a, K, V> Iterator for MapxOrdVsTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = VsdbResult<(K, V)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|kv| kv.and_then(|(k, v)| Ok((self.key_encoding.decode(k)?, v))))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxOrdVsTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|kv| kv.and_then(|(k, v)| Ok((self.key_encoding.decode(k)?, v))))
+    }
+}
+
+pub struct MapxOrdVsIterMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    iter: MapxOrdRawKeyVsIterMut<'a, V>,
+    key_encoding: KeyEncoding,
+    p: PhantomData<K>,
+}
+
+impl<'a, K, V> Iterator for MapxOrdVsIterMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    type Item = (K, ValueIterMut<'a, V>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapxOrdVsIterMut<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|(k, v)| (pnk!(self.key_encoding.decode(k)), v))
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct ValueMut<'a, K, V>
 where
@@ -507,7 +794,7 @@ where
     }
 }
 
-pub struct Entry<'a, K, V>
+pub struct EntryRef<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
@@ -516,7 +803,7 @@ where
     key: &'a K,
 }
 
-impl<'a, K, V> Entry<'a, K, V>
+impl<'a, K, V> EntryRef<'a, K, V>
 where
     K: KeyEnDeOrdered,
     V: ValueEnDe,
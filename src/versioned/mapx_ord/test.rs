@@ -1,6 +1,8 @@
 use super::*;
 use crate::{
-    common::{BranchName, ParentBranchName, INITIAL_BRANCH_NAME},
+    common::{
+        engines::reload_as_legacy, BranchName, ParentBranchName, INITIAL_BRANCH_NAME,
+    },
     ValueEnDe, VsMgmt,
 };
 
@@ -466,3 +468,33 @@ fn test_by_branch() {
     assert_eq!(100, pnk!(hdr.get_ge_by_branch(&100, INITIAL_BRANCH_NAME)).0);
     assert_eq!(100, pnk!(hdr.get_le_by_branch(&100, INITIAL_BRANCH_NAME)).0);
 }
+
+#[test]
+fn test_legacy_keys() {
+    let mut hdr: MapxOrdVs<i64, i64> = reload_as_legacy(&MapxOrdVs::new());
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    (-1..2).for_each(|i| {
+        pnk!(hdr.insert(i, i));
+    });
+    pnk!(hdr.branch_create(BranchName(b"b"), VersionName(b"b0"), false));
+    pnk!(hdr.insert_by_branch(-3, -2, BranchName(b"b")));
+    pnk!(hdr.version_create(VersionName(b"v1")));
+    (-3..-1).for_each(|i| {
+        pnk!(hdr.insert(i, i));
+    });
+    assert_eq!(Some((0, 0)), hdr.first());
+
+    pnk!(hdr.migrate_legacy_keys());
+    assert!(hdr.iter().map(|(k, _)| k).eq(-3..2));
+    assert!(hdr.iter().all(|(k, v)| k == v));
+    assert_eq!(Some(-2), hdr.get_by_branch(&-3, BranchName(b"b")));
+    assert_eq!(Some(-3), hdr.get_by_branch(&-3, INITIAL_BRANCH_NAME));
+
+    // the change sets are re-encoded along with the keys
+    pnk!(hdr.version_pop());
+    assert!(hdr.iter().map(|(k, _)| k).eq(-1..2));
+    assert!(hdr
+        .iter_by_branch(BranchName(b"b"))
+        .map(|(k, _)| k)
+        .eq([-3, -1, 0, 1]));
+}
@@ -6,9 +6,16 @@
 mod test;
 
 use crate::{
-    common::{ende::ValueEnDe, BranchName, RawKey, VersionName},
-    versioned::mapx_raw::{MapxRawVs, MapxRawVsIter},
-    VsMgmt,
+    common::{
+        ende::{KeyEncoding, ValueCodec, ValueEnDe},
+        error::VsdbResult,
+        schema::schema_id,
+        BranchName, InstanceOpts, RawKey, VersionName,
+    },
+    versioned::mapx_raw::{
+        self, MapxRawVs, MapxRawVsIter, MapxRawVsIterMut, MapxRawVsTryIter,
+    },
+    Entry, EntryHdr, GcMark, GcMarks, VsMgmt,
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -47,6 +54,10 @@ impl<V> MapxOrdRawKeyVs<V>
 where
     V: ValueEnDe,
 {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -63,28 +74,81 @@ where
         }
     }
 
+    /// Create an instance with the specified options,
+    /// the options are applied to the stored values of all versions.
+    #[inline(always)]
+    pub fn new_with_opts(opts: &InstanceOpts) -> Self {
+        MapxOrdRawKeyVs {
+            inner: MapxRawVs::new_with_opts(opts),
+            p: PhantomData,
+        }
+    }
+
+    /// Rewrite the values of all branches and versions to the latest version
+    /// of the schema `schema`, into new storages that replace the old ones,
+    /// so the handle should be saved again.
+    ///
+    /// The old storages are left untouched, the old handle is still usable
+    /// if the process crashes before the new handle has been saved.
+    ///
+    /// A handle stored as a value of another instance(e.g. the inner maps of
+    /// `MapxOrd<K, MapxVs<K2, V>>`) is a copy, which is not updated by this,
+    /// so write the migrated handle back, or the stored one keeps pointing to
+    /// the old storages, and the new storages will be removed by `vsdb_gc`.
+    ///
+    /// The old storages are never removed by the migration, they take as much space
+    /// as the new ones until they are reclaimed by `vsdb_gc`, so run `vsdb_gc` after
+    /// the new handle has been saved, which also reclaims the new storages
+    /// left behind by a crashed migration.
+    ///
+    /// Values stored without a schema are treated as version 0 of it,
+    /// this is an offline routine, the instance should not be used elsewhere meanwhile.
+    #[inline(always)]
+    pub fn migrate_instance(&mut self, schema: &str) -> Result<()> {
+        self.inner.migrate_schema(schema_id(schema)).c(d!())
+    }
+
+    // re-encode all the keys, on all branches and versions
+    #[inline(always)]
+    pub(crate) fn rekey<F>(&mut self, rekey: F) -> Result<()>
+    where
+        F: Fn(&[u8]) -> VsdbResult<RawKey>,
+    {
+        self.inner.rekey(rekey).c(d!())
+    }
+
+    #[inline(always)]
+    pub(crate) fn key_encoding(&self) -> KeyEncoding {
+        self.inner.key_encoding()
+    }
+
     #[inline(always)]
     pub fn get(&self, key: &[u8]) -> Option<V> {
         self.inner
             .get(key)
-            .map(|v| <V as ValueEnDe>::decode(&v).unwrap())
+            .map(|v| self.inner.codec().decode_by_key(key, &v).unwrap())
     }
 
     #[inline(always)]
-    pub fn get_mut<'a>(&'a mut self, key: &'a [u8]) -> Option<ValueMut<'_, V>> {
+    pub fn get_mut<'a>(&'a mut self, key: &'a [u8]) -> Option<ValueMut<'a, V>> {
         self.get(key).map(move |v| ValueMut::new(self, key, v))
     }
 
     #[inline(always)]
-    pub fn entry_ref<'a>(&'a mut self, key: &'a [u8]) -> Entry<'a, V> {
-        Entry { key, hdr: self }
+    pub fn entry(&mut self, key: RawKey) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
+    #[inline(always)]
+    pub fn entry_ref<'a>(&'a mut self, key: &'a [u8]) -> EntryRef<'a, V> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
     pub fn get_by_branch(&self, key: &[u8], branch_name: BranchName) -> Option<V> {
         self.inner
             .get_by_branch(key, branch_name)
-            .map(|v| <V as ValueEnDe>::decode(&v).unwrap())
+            .map(|v| self.inner.codec().decode_by_key(key, &v).unwrap())
     }
 
     #[inline(always)]
@@ -96,14 +160,54 @@ where
     ) -> Option<V> {
         self.inner
             .get_by_branch_version(key, branch_name, version_name)
-            .map(|v| <V as ValueEnDe>::decode(&v).unwrap())
+            .map(|v| self.inner.codec().decode_by_key(key, &v).unwrap())
     }
 
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
     #[inline(always)]
-    pub fn get_le(&self, key: &[u8]) -> Option<(RawKey, V)> {
+    pub fn try_get(&self, key: &[u8]) -> VsdbResult<Option<V>> {
+        self.inner
+            .try_get(key)?
+            .map(|v| self.inner.codec().decode_by_key(key, &v))
+            .transpose()
+    }
+
+    /// Same as `get_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch(
+        &self,
+        key: &[u8],
+        branch_name: BranchName,
+    ) -> VsdbResult<Option<V>> {
         self.inner
-            .get_le(key)
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+            .try_get_by_branch(key, branch_name)?
+            .map(|v| self.inner.codec().decode_by_key(key, &v))
+            .transpose()
+    }
+
+    /// Same as `get_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch_version(
+        &self,
+        key: &[u8],
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> VsdbResult<Option<V>> {
+        self.inner
+            .try_get_by_branch_version(key, branch_name, version_name)?
+            .map(|v| self.inner.codec().decode_by_key(key, &v))
+            .transpose()
+    }
+
+    #[inline(always)]
+    pub fn get_le(&self, key: &[u8]) -> Option<(RawKey, V)> {
+        self.inner.get_le(key).map(|(k, v)| {
+            let v = self.inner.codec().decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 
     #[inline(always)]
@@ -112,9 +216,10 @@ where
         key: &[u8],
         branch_name: BranchName,
     ) -> Option<(RawKey, V)> {
-        self.inner
-            .get_le_by_branch(key, branch_name)
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.inner.get_le_by_branch(key, branch_name).map(|(k, v)| {
+            let v = self.inner.codec().decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 
     #[inline(always)]
@@ -126,14 +231,18 @@ where
     ) -> Option<(RawKey, V)> {
         self.inner
             .get_le_by_branch_version(key, branch_name, version_name)
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+            .map(|(k, v)| {
+                let v = self.inner.codec().decode_by_key(&k, &v).unwrap();
+                (k, v)
+            })
     }
 
     #[inline(always)]
     pub fn get_ge(&self, key: &[u8]) -> Option<(RawKey, V)> {
-        self.inner
-            .get_ge(key)
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.inner.get_ge(key).map(|(k, v)| {
+            let v = self.inner.codec().decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 
     #[inline(always)]
@@ -142,9 +251,10 @@ where
         key: &[u8],
         branch_name: BranchName,
     ) -> Option<(RawKey, V)> {
-        self.inner
-            .get_ge_by_branch(key, branch_name)
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.inner.get_ge_by_branch(key, branch_name).map(|(k, v)| {
+            let v = self.inner.codec().decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 
     #[inline(always)]
@@ -156,7 +266,10 @@ where
     ) -> Option<(RawKey, V)> {
         self.inner
             .get_ge_by_branch_version(key, branch_name, version_name)
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+            .map(|(k, v)| {
+                let v = self.inner.codec().decode_by_key(&k, &v).unwrap();
+                (k, v)
+            })
     }
 
     #[inline(always)]
@@ -216,9 +329,9 @@ where
     #[inline(always)]
     pub fn insert_ref(&mut self, key: &[u8], value: &V) -> Result<Option<V>> {
         self.inner
-            .insert(key, &value.encode())
+            .insert(key, &self.inner.codec().encode(value))
             .c(d!())
-            .map(|v| v.map(|v| <V as ValueEnDe>::decode(&v).unwrap()))
+            .map(|v| v.map(|v| self.inner.codec().decode_by_key(key, &v).unwrap()))
     }
 
     #[inline(always)]
@@ -229,27 +342,100 @@ where
         branch_name: BranchName,
     ) -> Result<Option<V>> {
         self.inner
-            .insert_by_branch(key, &value.encode(), branch_name)
+            .insert_by_branch(key, &self.inner.codec().encode(value), branch_name)
             .c(d!())
-            .map(|v| v.map(|v| <V as ValueEnDe>::decode(&v).unwrap()))
+            .map(|v| v.map(|v| self.inner.codec().decode_by_key(key, &v).unwrap()))
     }
 
     #[inline(always)]
     pub fn iter(&self) -> MapxOrdRawKeyVsIter<'_, V> {
         MapxOrdRawKeyVsIter {
             iter: self.inner.iter(),
+            codec: self.inner.codec(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> MapxOrdRawKeyVsIterMut<'_, V> {
+        MapxOrdRawKeyVsIterMut {
+            codec: self.inner.codec(),
+            iter: self.inner.iter_mut(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn range_mut<'a, R: 'a + RangeBounds<RawKey>>(
+        &'a mut self,
+        bounds: R,
+    ) -> MapxOrdRawKeyVsIterMut<'a, V> {
+        MapxOrdRawKeyVsIterMut {
+            codec: self.inner.codec(),
+            iter: self.inner.range_mut(bounds),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn range_ref_mut<'a, R: RangeBounds<&'a [u8]>>(
+        &'a mut self,
+        bounds: R,
+    ) -> MapxOrdRawKeyVsIterMut<'a, V> {
+        MapxOrdRawKeyVsIterMut {
+            codec: self.inner.codec(),
+            iter: self.inner.range_ref_mut(bounds),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `iter`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxOrdRawKeyVsTryIter<'_, V> {
+        MapxOrdRawKeyVsTryIter {
+            iter: self.inner.try_iter(),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
 
-    // TODO
-    // pub fn iter_mut
-    // pub fn range_mut
+    /// Same as `iter_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch(
+        &self,
+        branch_name: BranchName,
+    ) -> MapxOrdRawKeyVsTryIter<'_, V> {
+        MapxOrdRawKeyVsTryIter {
+            iter: self.inner.try_iter_by_branch(branch_name),
+            codec: self.inner.codec(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `iter_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> MapxOrdRawKeyVsTryIter<'_, V> {
+        MapxOrdRawKeyVsTryIter {
+            iter: self
+                .inner
+                .try_iter_by_branch_version(branch_name, version_name),
+            codec: self.inner.codec(),
+            p: PhantomData,
+        }
+    }
 
     #[inline(always)]
     pub fn iter_by_branch(&self, branch_name: BranchName) -> MapxOrdRawKeyVsIter<'_, V> {
         MapxOrdRawKeyVsIter {
             iter: self.inner.iter_by_branch(branch_name),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
@@ -262,6 +448,7 @@ where
     ) -> MapxOrdRawKeyVsIter<'_, V> {
         MapxOrdRawKeyVsIter {
             iter: self.inner.iter_by_branch_version(branch_name, version_name),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
@@ -273,6 +460,7 @@ where
     ) -> MapxOrdRawKeyVsIter<'a, V> {
         MapxOrdRawKeyVsIter {
             iter: self.inner.range(bounds),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
@@ -285,6 +473,7 @@ where
     ) -> MapxOrdRawKeyVsIter<'a, V> {
         MapxOrdRawKeyVsIter {
             iter: self.inner.range_by_branch(branch_name, bounds),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
@@ -300,6 +489,7 @@ where
             iter: self
                 .inner
                 .range_by_branch_version(branch_name, version_name, bounds),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
@@ -311,6 +501,7 @@ where
     ) -> MapxOrdRawKeyVsIter<'a, V> {
         MapxOrdRawKeyVsIter {
             iter: self.inner.range_ref(bounds),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
@@ -323,6 +514,7 @@ where
     ) -> MapxOrdRawKeyVsIter<'a, V> {
         MapxOrdRawKeyVsIter {
             iter: self.inner.range_ref_by_branch(branch_name, bounds),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
@@ -340,6 +532,7 @@ where
                 version_name,
                 bounds,
             ),
+            codec: self.inner.codec(),
             p: PhantomData,
         }
     }
@@ -410,7 +603,7 @@ where
         self.inner
             .remove(key)
             .c(d!())
-            .map(|v| v.map(|v| <V as ValueEnDe>::decode(&v).unwrap()))
+            .map(|v| v.map(|v| self.inner.codec().decode_by_key(key, &v).unwrap()))
     }
 
     #[inline(always)]
@@ -422,7 +615,7 @@ where
         self.inner
             .remove_by_branch(key, branch_name)
             .c(d!())
-            .map(|v| v.map(|v| <V as ValueEnDe>::decode(&v).unwrap()))
+            .map(|v| v.map(|v| self.inner.codec().decode_by_key(key, &v).unwrap()))
     }
 
     #[inline(always)]
@@ -438,11 +631,45 @@ where
     crate::impl_vs_methods!();
 }
 
+impl<V> EntryHdr for MapxOrdRawKeyVs<V>
+where
+    V: ValueEnDe,
+{
+    type Key = RawKey;
+    type Value = V;
+
+    fn entry_get(&self, key: &RawKey) -> Option<V> {
+        self.get(key)
+    }
+
+    fn entry_set(&mut self, key: &RawKey, value: &V) {
+        pnk!(self.insert_ref(key, value));
+    }
+
+    fn entry_remove(&mut self, key: &RawKey) {
+        pnk!(self.remove(key));
+    }
+}
+
+impl<V> GcMark for MapxOrdRawKeyVs<V>
+where
+    V: ValueEnDe + GcMark,
+{
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        if self.inner.gc_mark_instance(marks) && !V::gc_nope() {
+            self.inner.for_each_value_globally(|v| {
+                pnk!(self.inner.codec().decode::<V>(&v)).gc_mark(marks);
+            });
+        }
+    }
+}
+
 pub struct MapxOrdRawKeyVsIter<'a, V>
 where
     V: ValueEnDe,
 {
     iter: MapxRawVsIter<'a>,
+    codec: ValueCodec,
     p: PhantomData<V>,
 }
 
@@ -452,9 +679,10 @@ where
 {
     type Item = (RawKey, V);
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter
-            .next()
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.iter.next().map(|(k, v)| {
+            let v = self.codec.decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 }
 
@@ -463,14 +691,137 @@ where
     V: ValueEnDe,
 {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.iter
-            .next_back()
-            .map(|(k, v)| (k, <V as ValueEnDe>::decode(&v).unwrap()))
+        self.iter.next_back().map(|(k, v)| {
+            let v = self.codec.decode_by_key(&k, &v).unwrap();
+            (k, v)
+        })
     }
 }
 
 impl<'a, V> ExactSizeIterator for MapxOrdRawKeyVsIter<'a, V> where V: ValueEnDe {}
 
+pub struct MapxOrdRawKeyVsTryIter<'a, V>
+where
+    V: ValueEnDe,
+{
+    iter: MapxRawVsTryIter<'a>,
+    codec: ValueCodec,
+    p: PhantomData<V>,
+}
+
+impl<'a, V> Iterator for MapxOrdRawKeyVsTryIter<'a, V>
+where
+    V: ValueEnDe,
+{
+    type Item = VsdbResult<(RawKey, V)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|kv| {
+            let (k, v) = kv?;
+            let v = self.codec.decode_by_key(&k, &v)?;
+            Ok((k, v))
+        })
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for MapxOrdRawKeyVsTryIter<'a, V>
+where
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|kv| {
+            let (k, v) = kv?;
+            let v = self.codec.decode_by_key(&k, &v)?;
+            Ok((k, v))
+        })
+    }
+}
+
+pub struct MapxOrdRawKeyVsIterMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    iter: MapxRawVsIterMut<'a>,
+    codec: ValueCodec,
+    p: PhantomData<V>,
+}
+
+impl<'a, V> Iterator for MapxOrdRawKeyVsIterMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    type Item = (RawKey, ValueIterMut<'a, V>);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, v)| {
+            let value = self.codec.decode_by_key(&k, &v).unwrap();
+            (
+                k,
+                ValueIterMut {
+                    value,
+                    codec: self.codec,
+                    inner: v,
+                },
+            )
+        })
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for MapxOrdRawKeyVsIterMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, v)| {
+            let value = self.codec.decode_by_key(&k, &v).unwrap();
+            (
+                k,
+                ValueIterMut {
+                    value,
+                    codec: self.codec,
+                    inner: v,
+                },
+            )
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ValueIterMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    value: V,
+    codec: ValueCodec,
+    inner: mapx_raw::ValueIterMut<'a>,
+}
+
+impl<'a, V> Drop for ValueIterMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    fn drop(&mut self) {
+        *self.inner = self.codec.encode(&self.value);
+    }
+}
+
+impl<'a, V> Deref for ValueIterMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    type Target = V;
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'a, V> DerefMut for ValueIterMut<'a, V>
+where
+    V: ValueEnDe,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct ValueMut<'a, V: ValueEnDe> {
     hdr: &'a mut MapxOrdRawKeyVs<V>,
@@ -515,12 +866,12 @@ where
     }
 }
 
-pub struct Entry<'a, V: ValueEnDe> {
+pub struct EntryRef<'a, V: ValueEnDe> {
     hdr: &'a mut MapxOrdRawKeyVs<V>,
     key: &'a [u8],
 }
 
-impl<'a, V> Entry<'a, V>
+impl<'a, V> EntryRef<'a, V>
 where
     V: ValueEnDe,
 {
@@ -5,19 +5,30 @@
 use crate::{
     basic::{
         mapx_ord::MapxOrd,
-        mapx_ord_rawkey::{MapxOrdRawKey, MapxOrdRawKeyIter},
+        mapx_ord_rawkey::{MapxOrdRawKey, MapxOrdRawKeyIter, MapxOrdRawKeyTryIter},
         mapx_ord_rawvalue::MapxOrdRawValue,
         mapx_raw::MapxRaw,
     },
     common::{
-        ende::encode_optioned_bytes, BranchID, BranchName, BranchNameOwned, RawKey,
-        RawValue, VersionID, VersionName, VersionNameOwned, INITIAL_BRANCH_ID,
+        ende::{encode_optioned_bytes, KeyEncoding, ValueCodec},
+        engines::ValueCfg,
+        ensure_writable,
+        error::{VsdbError, VsdbResult},
+        gc::{GcMark, GcMarks},
+        schema::SchemaId,
+        vsdb_compact_after_prune, BranchID, BranchName, BranchNameOwned, RawKey,
+        InstanceOpts, RawValue, VersionID, VersionName, VersionNameOwned,
+        INITIAL_BRANCH_ID,
         INITIAL_BRANCH_NAME, NULL, RESERVED_VERSION_NUM_DEFAULT, VSDB,
     },
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, collections::HashSet, ops::RangeBounds};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    ops::RangeBounds,
+};
 
 ////////////////////////////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////////
@@ -41,12 +52,20 @@ pub(super) struct MapxRawVs {
 
     // key -> multi-version(globally unique) -> multi-value
     layered_kv: MapxOrdRawKey<MapxOrd<VersionID, Option<RawValue>>>,
+
+    // applied to the multi-value layer of each key
+    #[serde(default)]
+    value_cfg: ValueCfg,
 }
 
 ////////////////////////////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////////
 
 impl MapxRawVs {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -58,11 +77,112 @@ impl MapxRawVs {
             branch_to_its_versions: self.branch_to_its_versions.shadow(),
             version_to_change_set: self.version_to_change_set.shadow(),
             layered_kv: self.layered_kv.shadow(),
+            value_cfg: self.value_cfg,
         }
     }
 
     #[inline(always)]
     pub(super) fn new() -> Self {
+        Self::new_with_cfg(ValueCfg::default())
+    }
+
+    #[inline(always)]
+    pub(super) fn new_with_opts(opts: &InstanceOpts) -> Self {
+        Self::new_with_cfg(ValueCfg::new(opts))
+    }
+
+    #[inline(always)]
+    pub(super) fn codec(&self) -> ValueCodec {
+        // the versioned instance is identified by its `layered_kv`
+        self.value_cfg.codec(self.layered_kv.instance_prefix())
+    }
+
+    #[inline(always)]
+    pub(super) fn key_encoding(&self) -> KeyEncoding {
+        // the keys of the change sets are always encoded in the same way
+        self.layered_kv.key_encoding()
+    }
+
+    // rewrite all the values, on all branches and versions,
+    // to the latest version of the schema `id`
+    //
+    // the migrated values are written into a new `layered_kv`,
+    // which replaces the old one only after all of them have been written,
+    // so the old handle stays valid until the new one has been saved;
+    // the old storages will be reclaimed by the GC after that
+    pub(super) fn migrate_schema(&mut self, id: SchemaId) -> Result<()> {
+        ensure_writable().c(d!())?;
+
+        let value_cfg = self.value_cfg;
+
+        // check all the values before any writing,
+        // so a failed migration will leave nothing behind
+        for vers in self.layered_kv.values() {
+            for v in vers.values().flatten() {
+                value_cfg.migrate_value(id, &v).c(d!())?;
+            }
+        }
+
+        let mut layered_kv = self.layered_kv.new_like().c(d!())?;
+        for (k, vers) in self.layered_kv.iter() {
+            let mut new_vers = MapxOrd::new_with_cfg(value_cfg.for_internal());
+            for (ver, v) in vers.iter() {
+                let v = v.map(|v| pnk!(value_cfg.migrate_value(id, &v)));
+                new_vers.insert_ref_encoded_value(
+                    &ver,
+                    &encode_optioned_bytes(&v.as_deref())[..],
+                );
+            }
+            layered_kv.insert_ref(&k, &new_vers).c(d!())?;
+        }
+
+        self.layered_kv = layered_kv;
+        self.value_cfg.set_schema(id);
+
+        Ok(())
+    }
+
+    // re-encode all the keys, on all branches and versions
+    //
+    // the keys are written into a new `layered_kv` and new change sets,
+    // which replace the old ones only after all of them have been written,
+    // so the old handle stays valid until the new one has been saved;
+    // the old storages will be reclaimed by the GC after that
+    pub(super) fn rekey<F>(&mut self, rekey: F) -> Result<()>
+    where
+        F: Fn(&[u8]) -> VsdbResult<RawKey>,
+    {
+        ensure_writable().c(d!())?;
+
+        // check all the keys before any writing,
+        // so a failed migration will leave nothing behind
+        for (k, _) in self.layered_kv.iter() {
+            rekey(&k).c(d!())?;
+        }
+        for chgset in self.version_to_change_set.values() {
+            for (k, _) in chgset.iter() {
+                rekey(&k).c(d!())?;
+            }
+        }
+
+        // the old handle is kept in `self` until all the writings are done
+        let mut layered_kv = unsafe { self.layered_kv.shadow() };
+        layered_kv.rekey(&rekey).c(d!())?;
+
+        let mut version_to_change_set = MapxOrd::new();
+        for (ver, mut chgset) in self.version_to_change_set.iter() {
+            chgset.rekey(&rekey).c(d!())?;
+            version_to_change_set.insert(ver, chgset).c(d!())?;
+        }
+
+        self.layered_kv = layered_kv;
+        self.version_to_change_set = version_to_change_set;
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn new_with_cfg(value_cfg: ValueCfg) -> Self {
         let mut ret = Self {
             default_branch: BranchID::default(),
             branch_name_to_branch_id: MapxOrdRawKey::new(),
@@ -72,6 +192,7 @@ impl MapxRawVs {
             branch_to_its_versions: MapxOrd::new(),
             version_to_change_set: MapxOrd::new(),
             layered_kv: MapxOrdRawKey::new(),
+            value_cfg,
         };
         ret.init();
         ret
@@ -80,12 +201,15 @@ impl MapxRawVs {
     #[inline(always)]
     fn init(&mut self) {
         self.default_branch = INITIAL_BRANCH_ID;
-        self.branch_name_to_branch_id
-            .insert_ref(INITIAL_BRANCH_NAME.0, &INITIAL_BRANCH_ID);
-        self.branch_id_to_branch_name
-            .insert_ref(&INITIAL_BRANCH_ID, INITIAL_BRANCH_NAME.0);
-        self.branch_to_its_versions
-            .insert(INITIAL_BRANCH_ID, MapxOrd::new());
+        pnk!(self
+            .branch_name_to_branch_id
+            .insert_ref(INITIAL_BRANCH_NAME.0, &INITIAL_BRANCH_ID));
+        pnk!(self
+            .branch_id_to_branch_name
+            .insert_ref(&INITIAL_BRANCH_ID, INITIAL_BRANCH_NAME.0));
+        pnk!(self
+            .branch_to_its_versions
+            .insert(INITIAL_BRANCH_ID, MapxOrd::new()));
     }
 
     #[inline(always)]
@@ -182,6 +306,8 @@ impl MapxRawVs {
         branch_id: BranchID,
         version_id: VersionID,
     ) -> Result<Option<RawValue>> {
+        ensure_writable().c(d!())?;
+
         let ret = self.get_by_branch_version(key, branch_id, version_id);
 
         // remove a non-existing value
@@ -193,16 +319,71 @@ impl MapxRawVs {
         self.version_to_change_set
             .get_mut(&version_id)
             .c(d!())?
-            .insert(key, &[]);
+            .insert(key, &[])
+            .c(d!())?;
 
         self.layered_kv
             .entry_ref(key)
-            .or_insert_ref(&MapxOrd::new())
+            .or_insert_ref(&MapxOrd::new_with_cfg(
+                self.value_cfg.for_internal(),
+            ))
             .insert_ref_encoded_value(&version_id, &encode_optioned_bytes(&value)[..]);
 
         Ok(ret)
     }
 
+    // Write a batch of KVs to the head version of a branch,
+    // a `None` value means a removing.
+    //
+    // All the checks are done before any writing,
+    // and the change set of the version is updated in one batch.
+    pub(super) fn write_batch_by_branch(
+        &mut self,
+        kvs: &[(&[u8], Option<&[u8]>)],
+        branch_id: BranchID,
+    ) -> Result<()> {
+        ensure_writable().c(d!())?;
+
+        let version_id = self
+            .branch_to_its_versions
+            .get(&branch_id)
+            .c(d!("branch not found"))?
+            .last()
+            .c(d!("no version on this branch, create a version first"))?
+            .0;
+        let mut change_set = self.version_to_change_set.get(&version_id).c(d!())?;
+
+        // removings of non-existing values are skipped,
+        // the earlier pairs of the same batch are taken into account
+        let mut existing = HashMap::new();
+        let kvs = kvs
+            .iter()
+            .filter(|(k, v)| {
+                let exists = *existing.entry(*k).or_insert_with(|| {
+                    self.get_by_branch_version(k, branch_id, version_id)
+                        .is_some()
+                });
+                existing.insert(*k, v.is_some());
+                v.is_some() || exists
+            })
+            .collect::<Vec<_>>();
+
+        // NOTE: the values need not to be stored here
+        change_set.write_batch(
+            kvs.iter()
+                .map(|(k, _)| (k.to_vec().into_boxed_slice(), Some(Box::from([])))),
+        );
+
+        for (k, v) in kvs {
+            self.layered_kv
+                .entry_ref(k)
+                .or_insert_ref(&MapxOrd::new_with_cfg(self.value_cfg.for_internal()))
+                .insert_ref_encoded_value(&version_id, &encode_optioned_bytes(v)[..]);
+        }
+
+        Ok(())
+    }
+
     #[inline(always)]
     pub(super) fn get(&self, key: &[u8]) -> Option<RawValue> {
         self.get_by_branch(key, self.branch_get_default())
@@ -238,6 +419,70 @@ impl MapxRawVs {
             .and_then(|(_, value)| value)
     }
 
+    // same as `get_by_branch`, but a broken value(e.g. tampered on disk)
+    // is returned as an error instead of a panic
+    #[inline(always)]
+    pub(super) fn try_get_by_branch(
+        &self,
+        key: &[u8],
+        branch_id: BranchID,
+    ) -> VsdbResult<Option<RawValue>> {
+        match self.try_branch_head(branch_id)? {
+            Some(version_id) => {
+                self.try_get_by_branch_version(key, branch_id, version_id)
+            }
+            None => Ok(None),
+        }
+    }
+
+    // same as `get_by_branch_version`, but a broken value(e.g. tampered on disk)
+    // is returned as an error instead of a panic
+    #[inline(always)]
+    pub(super) fn try_get_by_branch_version(
+        &self,
+        key: &[u8],
+        branch_id: BranchID,
+        version_id: VersionID,
+    ) -> VsdbResult<Option<RawValue>> {
+        match self.layered_kv.try_get(key)? {
+            Some(values) => self.try_get_from_layer(&values, branch_id, version_id),
+            None => Ok(None),
+        }
+    }
+
+    // the value in the multi-value layer of a key
+    // that is visible to the specified version of the branch
+    fn try_get_from_layer(
+        &self,
+        values: &MapxOrd<VersionID, Option<RawValue>>,
+        branch_id: BranchID,
+        version_id: VersionID,
+    ) -> VsdbResult<Option<RawValue>> {
+        let vers = match self.branch_to_its_versions.try_get(&branch_id)? {
+            Some(vers) => vers,
+            None => return Ok(None),
+        };
+        for kv in values.try_range(..=version_id).rev() {
+            let (ver, value) = kv?;
+            if vers.try_get(&ver)?.is_some() {
+                return Ok(value);
+            }
+        }
+        Ok(None)
+    }
+
+    // the latest version of a branch
+    fn try_branch_head(&self, branch_id: BranchID) -> VsdbResult<Option<VersionID>> {
+        match self.branch_to_its_versions.try_get(&branch_id)? {
+            Some(vers) => vers
+                .try_iter()
+                .next_back()
+                .transpose()
+                .map(|v| v.map(|v| v.0)),
+            None => Ok(None),
+        }
+    }
+
     #[inline(always)]
     pub(super) fn get_ge(&self, key: &[u8]) -> Option<(RawKey, RawValue)> {
         self.range_ref(key..).next()
@@ -289,12 +534,12 @@ impl MapxRawVs {
     }
 
     #[inline(always)]
-    pub(super) fn iter(&self) -> MapxRawVsIter {
+    pub(super) fn iter(&self) -> MapxRawVsIter<'_> {
         self.iter_by_branch(self.branch_get_default())
     }
 
     #[inline(always)]
-    pub(super) fn iter_by_branch(&self, branch_id: BranchID) -> MapxRawVsIter {
+    pub(super) fn iter_by_branch(&self, branch_id: BranchID) -> MapxRawVsIter<'_> {
         if let Some(vers) = self.branch_to_its_versions.get(&branch_id) {
             if let Some((version_id, _)) = vers.last() {
                 return self.iter_by_branch_version(branch_id, version_id);
@@ -314,7 +559,7 @@ impl MapxRawVs {
         &self,
         branch_id: BranchID,
         version_id: VersionID,
-    ) -> MapxRawVsIter {
+    ) -> MapxRawVsIter<'_> {
         MapxRawVsIter {
             hdr: self,
             iter: self.layered_kv.iter(),
@@ -323,6 +568,42 @@ impl MapxRawVs {
         }
     }
 
+    // same as `range_ref_by_branch`, but a broken value(e.g. tampered on disk)
+    // is yielded as an error instead of a panic
+    #[inline(always)]
+    pub(super) fn try_range_ref_by_branch<'a, R: RangeBounds<&'a [u8]>>(
+        &self,
+        branch_id: BranchID,
+        bounds: R,
+    ) -> MapxRawVsTryIter<'_> {
+        match self.try_branch_head(branch_id) {
+            Ok(version_id) => self.try_range_ref_by_branch_version(
+                branch_id,
+                version_id.unwrap_or(NULL),
+                bounds,
+            ),
+            Err(e) => MapxRawVsTryIter::from_err(self, e),
+        }
+    }
+
+    // same as `range_ref_by_branch_version`, but a broken value(e.g. tampered on disk)
+    // is yielded as an error instead of a panic
+    #[inline(always)]
+    pub(super) fn try_range_ref_by_branch_version<'a, R: RangeBounds<&'a [u8]>>(
+        &self,
+        branch_id: BranchID,
+        version_id: VersionID,
+        bounds: R,
+    ) -> MapxRawVsTryIter<'_> {
+        MapxRawVsTryIter {
+            hdr: self,
+            iter: self.layered_kv.try_range(bounds),
+            branch_id,
+            version_id,
+            err: None,
+        }
+    }
+
     #[inline(always)]
     pub(super) fn range<'a, R: 'a + RangeBounds<RawKey>>(
         &'a self,
@@ -436,46 +717,49 @@ impl MapxRawVs {
     // Clear all data, for testing purpose.
     #[inline(always)]
     pub(super) fn clear(&mut self) {
-        self.branch_name_to_branch_id.clear();
-        self.version_name_to_version_id.clear();
-        self.branch_id_to_branch_name.clear();
-        self.version_id_to_version_name.clear();
-        self.branch_to_its_versions.clear();
-        self.version_to_change_set.clear();
-        self.layered_kv.clear();
+        pnk!(self.branch_name_to_branch_id.clear());
+        pnk!(self.version_name_to_version_id.clear());
+        pnk!(self.branch_id_to_branch_name.clear());
+        pnk!(self.version_id_to_version_name.clear());
+        pnk!(self.branch_to_its_versions.clear());
+        pnk!(self.version_to_change_set.clear());
+        pnk!(self.layered_kv.clear());
 
         self.init();
     }
 
     #[inline(always)]
-    pub(super) fn version_create(&mut self, version_name: &[u8]) -> Result<()> {
+    pub(super) fn version_create(&mut self, version_name: &[u8]) -> VsdbResult<()> {
         self.version_create_by_branch(version_name, self.branch_get_default())
-            .c(d!())
     }
 
     pub(super) fn version_create_by_branch(
         &mut self,
         version_name: &[u8],
         branch_id: BranchID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+
         if self.version_name_to_version_id.get(version_name).is_some() {
-            return Err(eg!("version already exists"));
+            return Err(VsdbError::VersionExists {
+                version: version_name.to_vec(),
+            });
         }
 
-        let mut vers = self
-            .branch_to_its_versions
-            .get_mut(&branch_id)
-            .c(d!("branch not found"))?;
+        if !self.branch_to_its_versions.contains_key(&branch_id) {
+            return Err(self.branch_not_found(branch_id));
+        }
+        let mut vers = self.branch_to_its_versions.get_mut(&branch_id).unwrap();
 
         let version_id = VSDB.alloc_version_id();
-        vers.insert(version_id, ());
+        vers.insert(version_id, ())?;
 
         self.version_name_to_version_id
-            .insert_ref(version_name, &version_id);
+            .insert_ref(version_name, &version_id)?;
         self.version_id_to_version_name
-            .insert_ref(&version_id, version_name);
+            .insert_ref(&version_id, version_name)?;
         self.version_to_change_set
-            .insert(version_id, MapxRaw::new());
+            .insert(version_id, MapxRaw::new())?;
 
         Ok(())
     }
@@ -523,12 +807,15 @@ impl MapxRawVs {
     // and should not do any tracing.
     #[inline(always)]
     pub(super) fn version_pop_by_branch(&mut self, branch_id: BranchID) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         let mut vers = self
             .branch_to_its_versions
             .get(&branch_id)
             .c(d!("branch not found"))?;
         if let Some((version_id, _)) = vers.iter().next_back() {
             vers.remove(&version_id)
+                .c(d!())?
                 .c(d!("version is not on this branch"))
         } else {
             Ok(())
@@ -544,9 +831,8 @@ impl MapxRawVs {
     pub(super) unsafe fn version_rebase(
         &mut self,
         base_version: VersionID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.version_rebase_by_branch(base_version, self.branch_get_default())
-            .c(d!())
     }
 
     // # Safety
@@ -558,43 +844,44 @@ impl MapxRawVs {
         &mut self,
         base_version: VersionID,
         branch_id: BranchID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+
         let mut vers_hdr = self
             .branch_to_its_versions
             .get(&branch_id)
-            .c(d!("branch not found"))?;
+            .ok_or_else(|| self.branch_not_found(branch_id))?;
         let mut vers = vers_hdr.range(base_version..).map(|(ver, _)| ver);
 
-        if let Some(ver) = vers.next() {
-            if base_version != ver {
-                return Err(eg!("base version is not on this branch"));
-            }
-        } else {
-            return Err(eg!("base version is not on this branch"));
-        };
+        // the base version must be on this branch
+        if vers.next() != Some(base_version) {
+            return Err(self.version_not_found(base_version));
+        }
 
         let mut base_ver_chg_set =
             self.version_to_change_set.get(&base_version).c(d!())?;
         let vers_to_be_merged = vers.collect::<Vec<_>>();
 
         for verid in vers_to_be_merged.iter() {
-            // we do not call `clear()` on the discarded instance for performance reason.
-            for (k, _) in self.version_to_change_set.remove(verid).c(d!())?.iter() {
-                base_ver_chg_set.insert(&k, &[]);
-                self.layered_kv.get(&k).c(d!()).and_then(|mut hdr| {
-                    hdr.remove(verid)
-                        .c(d!())
-                        .map(|v| hdr.insert(base_version, v))
-                })?;
+            let mut chgset = self.version_to_change_set.remove(verid).c(d!())?.c(d!())?;
+            for (k, _) in chgset.iter() {
+                base_ver_chg_set.insert(&k, &[]).c(d!())?;
+                let mut hdr = self.layered_kv.get(&k).c(d!())?;
+                let v = hdr.remove(verid).c(d!())?.c(d!())?;
+                hdr.insert(base_version, v).c(d!())?;
             }
+            chgset.clear().c(d!())?;
 
-            self.version_id_to_version_name
+            let vername = self
+                .version_id_to_version_name
                 .remove(verid)
-                .c(d!())
-                .and_then(|vername| {
-                    self.version_name_to_version_id.remove(&vername).c(d!())
-                })
-                .and_then(|_| vers_hdr.remove(verid).c(d!()))?;
+                .c(d!())?
+                .c(d!())?;
+            self.version_name_to_version_id
+                .remove(&vername)
+                .c(d!())?
+                .c(d!())?;
+            vers_hdr.remove(verid).c(d!())?.c(d!())?;
         }
 
         Ok(())
@@ -608,6 +895,14 @@ impl MapxRawVs {
         self.version_name_to_version_id.get(version_name.0)
     }
 
+    #[inline(always)]
+    pub(super) fn try_version_get_id_by_name(
+        &self,
+        version_name: VersionName,
+    ) -> VsdbResult<Option<VersionID>> {
+        self.version_name_to_version_id.try_get(version_name.0)
+    }
+
     #[inline(always)]
     pub(super) fn version_list(&self) -> Result<Vec<VersionNameOwned>> {
         self.version_list_by_branch(self.branch_get_default())
@@ -655,51 +950,79 @@ impl MapxRawVs {
         &mut self,
         version_id: VersionID,
     ) -> Result<()> {
-        let chgset = self.version_to_change_set.remove(&version_id).c(d!())?;
+        ensure_writable().c(d!())?;
+
+        let mut chgset = self
+            .version_to_change_set
+            .remove(&version_id)
+            .c(d!())?
+            .c(d!())?;
         for (key, _) in chgset.iter() {
             self.layered_kv
                 .get(&key)
                 .c(d!())?
                 .remove(&version_id)
+                .c(d!())?
                 .c(d!())?;
         }
+        chgset.clear().c(d!())?;
 
-        self.branch_to_its_versions
-            .iter()
-            .for_each(|(_, mut vers)| {
-                vers.remove(&version_id);
-            });
+        for (_, mut vers) in self.branch_to_its_versions.iter() {
+            vers.remove(&version_id).c(d!())?;
+        }
 
-        self.version_id_to_version_name
+        let vername = self
+            .version_id_to_version_name
             .remove(&version_id)
+            .c(d!())?
+            .c(d!())?;
+        self.version_name_to_version_id
+            .remove(&vername)
+            .c(d!())?
             .c(d!())
-            .and_then(|vername| self.version_name_to_version_id.remove(&vername).c(d!()))
             .map(|_| ())
     }
 
     // clean up all orphaned versions in the global scope
     pub(super) fn version_clean_up_globally(&mut self) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         let valid_vers = self
             .branch_to_its_versions
             .iter()
-            .flat_map(|(_, vers)| vers.iter().map(|(ver, _)| ver))
+            .flat_map(|(_, vers)| vers.iter().map(|(ver, _)| ver).collect::<Vec<_>>())
             .collect::<HashSet<_>>();
 
-        for (ver, chgset) in self
+        let orphaned_vers = self
             .version_to_change_set
             .iter()
             .filter(|(ver, _)| !valid_vers.contains(ver))
-        {
+            .collect::<Vec<_>>();
+
+        for (ver, chgset) in orphaned_vers {
             for (k, _) in chgset.iter() {
-                self.layered_kv.get(&k).c(d!())?.remove(&ver).c(d!())?;
+                self.layered_kv
+                    .get(&k)
+                    .c(d!())?
+                    .remove(&ver)
+                    .c(d!())?
+                    .c(d!())?;
             }
-            self.version_id_to_version_name
+            let vername = self
+                .version_id_to_version_name
                 .remove(&ver)
-                .c(d!())
-                .and_then(|vername| {
-                    self.version_name_to_version_id.remove(&vername).c(d!())
-                })
-                .and_then(|_| self.version_to_change_set.remove(&ver).c(d!()))?;
+                .c(d!())?
+                .c(d!())?;
+            self.version_name_to_version_id
+                .remove(&vername)
+                .c(d!())?
+                .c(d!())?;
+            self.version_to_change_set
+                .remove(&ver)
+                .c(d!())?
+                .c(d!())?
+                .clear()
+                .c(d!())?;
         }
 
         Ok(())
@@ -711,14 +1034,13 @@ impl MapxRawVs {
         branch_name: &[u8],
         version_name: &[u8],
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.branch_create_by_base_branch(
             branch_name,
             version_name,
             self.branch_get_default(),
             force,
         )
-        .c(d!())
     }
 
     #[inline(always)]
@@ -728,15 +1050,17 @@ impl MapxRawVs {
         version_name: &[u8],
         base_branch_id: BranchID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if self.version_name_to_version_id.contains_key(version_name) {
-            return Err(eg!("this version already exists"));
+            return Err(VsdbError::VersionExists {
+                version: version_name.to_vec(),
+            });
         }
 
         let base_version_id = self
             .branch_to_its_versions
             .get(&base_branch_id)
-            .c(d!("base branch not found"))?
+            .ok_or_else(|| self.branch_not_found(base_branch_id))?
             .last()
             .map(|(version_id, _)| version_id);
 
@@ -748,7 +1072,6 @@ impl MapxRawVs {
                 base_version_id,
                 force,
             )
-            .c(d!())
         }
     }
 
@@ -760,9 +1083,11 @@ impl MapxRawVs {
         base_branch_id: BranchID,
         base_version_id: VersionID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if self.version_name_to_version_id.contains_key(version_name) {
-            return Err(eg!("this version already exists"));
+            return Err(VsdbError::VersionExists {
+                version: version_name.to_vec(),
+            });
         }
 
         unsafe {
@@ -773,7 +1098,6 @@ impl MapxRawVs {
                 Some(base_version_id),
                 force,
             )
-            .c(d!())
         }
     }
 
@@ -782,13 +1106,12 @@ impl MapxRawVs {
         &mut self,
         branch_name: &[u8],
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.branch_create_by_base_branch_without_new_version(
             branch_name,
             self.branch_get_default(),
             force,
         )
-        .c(d!())
     }
 
     #[inline(always)]
@@ -797,11 +1120,11 @@ impl MapxRawVs {
         branch_name: &[u8],
         base_branch_id: BranchID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         let base_version_id = self
             .branch_to_its_versions
             .get(&base_branch_id)
-            .c(d!("base branch not found"))?
+            .ok_or_else(|| self.branch_not_found(base_branch_id))?
             .last()
             .map(|(version_id, _)| version_id);
 
@@ -812,7 +1135,6 @@ impl MapxRawVs {
             base_version_id,
             force,
         )
-        .c(d!())
     }
 
     #[inline(always)]
@@ -822,7 +1144,7 @@ impl MapxRawVs {
         base_branch_id: BranchID,
         base_version_id: VersionID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.do_branch_create_by_base_branch_version(
             branch_name,
             None,
@@ -830,7 +1152,6 @@ impl MapxRawVs {
             Some(base_version_id),
             force,
         )
-        .c(d!())
     }
 
     // param 'force':
@@ -842,30 +1163,34 @@ impl MapxRawVs {
         base_branch_id: BranchID,
         base_version_id: Option<VersionID>,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+
         if force {
             if let Some(brid) = self.branch_name_to_branch_id.get(branch_name) {
-                self.branch_remove(brid).c(d!())?;
+                self.branch_remove(brid)?;
             }
         }
 
         if self.branch_name_to_branch_id.contains_key(branch_name) {
-            return Err(eg!("branch already exists"));
+            return Err(VsdbError::BranchExists {
+                branch: branch_name.to_vec(),
+            });
         }
 
         let vers = self
             .branch_to_its_versions
             .get(&base_branch_id)
-            .c(d!("base branch not exist"))?;
+            .ok_or_else(|| self.branch_not_found(base_branch_id))?;
 
         let vers_copied = if let Some(bv) = base_version_id {
             if !vers.contains_key(&bv) {
-                return Err(eg!("version is not on the base branch"));
+                return Err(self.version_not_found(bv));
             }
-            vers.range(..=bv).fold(MapxOrd::new(), |mut acc, (k, v)| {
-                acc.insert(k, v);
-                acc
-            })
+            vers.range(..=bv)
+                .try_fold(MapxOrd::new(), |mut acc, (k, v)| {
+                    acc.insert(k, v).map(|_| acc)
+                })?
         } else {
             MapxOrd::new()
         };
@@ -873,14 +1198,14 @@ impl MapxRawVs {
         let branch_id = VSDB.alloc_branch_id();
 
         self.branch_name_to_branch_id
-            .insert_ref(branch_name, &branch_id);
+            .insert_ref(branch_name, &branch_id)?;
         self.branch_id_to_branch_name
-            .insert_ref(&branch_id, branch_name);
-        self.branch_to_its_versions.insert(branch_id, vers_copied);
+            .insert_ref(&branch_id, branch_name)?;
+        self.branch_to_its_versions.insert(branch_id, vers_copied)?;
 
         if let Some(vername) = version_name {
             // create the first version of the new branch
-            self.version_create_by_branch(vername, branch_id).c(d!())?;
+            self.version_create_by_branch(vername, branch_id)?;
         }
 
         Ok(())
@@ -912,16 +1237,23 @@ impl MapxRawVs {
     // and should not do any tracing.
     #[inline(always)]
     pub(super) fn branch_remove(&mut self, branch_id: BranchID) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         // if self.branch_get_default() == branch_id {
         //     return Err(eg!("the default branch can NOT be removed"));
         // }
 
         self.branch_truncate(branch_id).c(d!())?;
 
-        self.branch_id_to_branch_name
+        let brname = self
+            .branch_id_to_branch_name
             .remove(&branch_id)
-            .c(d!())
-            .and_then(|brname| self.branch_name_to_branch_id.remove(&brname).c(d!()))?;
+            .c(d!())?
+            .c(d!())?;
+        self.branch_name_to_branch_id
+            .remove(&brname)
+            .c(d!())?
+            .c(d!())?;
 
         self.branch_to_its_versions
             .remove(&branch_id)
@@ -931,12 +1263,14 @@ impl MapxRawVs {
 
     #[inline(always)]
     pub(super) fn branch_keep_only(&mut self, branch_ids: &[BranchID]) -> Result<()> {
-        for brid in self
+        let brids_to_remove = self
             .branch_id_to_branch_name
             .iter()
             .map(|(brid, _)| brid)
             .filter(|brid| !branch_ids.contains(brid))
-        {
+            .collect::<Vec<_>>();
+
+        for brid in brids_to_remove {
             self.branch_remove(brid).c(d!())?;
         }
         self.version_clean_up_globally().c(d!())
@@ -951,9 +1285,10 @@ impl MapxRawVs {
     // and should not do any tracing.
     #[inline(always)]
     pub(super) fn branch_truncate(&mut self, branch_id: BranchID) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         if let Some(mut vers) = self.branch_to_its_versions.get(&branch_id) {
-            vers.clear();
-            Ok(())
+            vers.clear().c(d!())
         } else {
             Err(eg!("branch not found: {}", branch_id))
         }
@@ -971,9 +1306,11 @@ impl MapxRawVs {
         branch_id: BranchID,
         last_version_id: VersionID,
     ) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         if let Some(mut vers) = self.branch_to_its_versions.get(&branch_id) {
             // version id must be in descending order
-            let vers_shadow = vers;
+            let vers_shadow = unsafe { vers.shadow() };
             for (version_id, _) in vers_shadow.range((1 + last_version_id)..).rev() {
                 vers.remove(&version_id)
                     .c(d!("version is not on this branch"))?;
@@ -999,7 +1336,7 @@ impl MapxRawVs {
         &mut self,
         branch_id: BranchID,
         target_branch_id: BranchID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         unsafe { self.do_branch_merge_to(branch_id, target_branch_id, false) }
     }
 
@@ -1012,7 +1349,7 @@ impl MapxRawVs {
         &mut self,
         branch_id: BranchID,
         target_branch_id: BranchID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.do_branch_merge_to(branch_id, target_branch_id, true)
     }
 
@@ -1028,21 +1365,26 @@ impl MapxRawVs {
         branch_id: BranchID,
         target_branch_id: BranchID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+
         let vers = self
             .branch_to_its_versions
             .get(&branch_id)
-            .c(d!("branch not found"))?;
+            .ok_or_else(|| self.branch_not_found(branch_id))?;
         let mut target_vers = self
             .branch_to_its_versions
             .get(&target_branch_id)
-            .c(d!("target branch not found"))?;
+            .ok_or_else(|| self.branch_not_found(target_branch_id))?;
 
         if !force {
             if let Some((ver, _)) = target_vers.last() {
                 if !vers.contains_key(&ver) {
                     // Some new versions have been generated on the target branch
-                    return Err(eg!("unable to merge safely"));
+                    return Err(VsdbError::UnsafeMerge {
+                        branch: self.branch_name_of(branch_id),
+                        target_branch: self.branch_name_of(target_branch_id),
+                    });
                 }
             }
         }
@@ -1052,9 +1394,9 @@ impl MapxRawVs {
             .zip(target_vers.iter())
             .find(|(a, b)| a.0 != b.0)
         {
-            vers.range(fork_point.0.0..).for_each(|(ver, _)| {
-                target_vers.insert(ver, ());
-            });
+            for (ver, _) in vers.range(fork_point.0.0..) {
+                target_vers.insert(ver, ())?;
+            }
         } else if let Some((latest_ver, _)) = vers.last() {
             if let Some((target_latest_ver, _)) = target_vers.last() {
                 match latest_ver.cmp(&target_latest_ver) {
@@ -1063,19 +1405,17 @@ impl MapxRawVs {
                         return Ok(());
                     }
                     Ordering::Greater => {
-                        vers.range((1 + target_latest_ver)..)
-                            .map(|(ver, _)| ver)
-                            .for_each(|ver| {
-                                target_vers.insert(ver, ());
-                            });
+                        for (ver, _) in vers.range((1 + target_latest_ver)..) {
+                            target_vers.insert(ver, ())?;
+                        }
                     }
                     _ => {}
                 }
             } else {
                 // target branch is empty, copy all versions to it
-                vers.iter().for_each(|(ver, _)| {
-                    target_vers.insert(ver, ());
-                });
+                for (ver, _) in vers.iter() {
+                    target_vers.insert(ver, ())?;
+                }
             }
         } else {
             // nothing to be merges
@@ -1140,6 +1480,8 @@ impl MapxRawVs {
         branch_1: &[u8],
         branch_2: &[u8],
     ) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         let brid_1 = self.branch_name_to_branch_id.get(branch_1).c(d!())?;
         let brid_2 = self.branch_name_to_branch_id.get(branch_2).c(d!())?;
 
@@ -1166,6 +1508,26 @@ impl MapxRawVs {
         Ok(())
     }
 
+    fn branch_name_of(&self, branch_id: BranchID) -> Vec<u8> {
+        self.branch_id_to_branch_name
+            .get(&branch_id)
+            .map(|br| br.to_vec())
+            .unwrap_or_else(|| branch_id.to_string().into_bytes())
+    }
+
+    fn branch_not_found(&self, branch_id: BranchID) -> VsdbError {
+        VsdbError::branch_not_found(self.branch_name_of(branch_id))
+    }
+
+    fn version_not_found(&self, version_id: VersionID) -> VsdbError {
+        let vername = self
+            .version_id_to_version_name
+            .get(&version_id)
+            .map(|ver| ver.to_vec())
+            .unwrap_or_else(|| version_id.to_string().into_bytes());
+        VsdbError::version_not_found(vername)
+    }
+
     #[inline(always)]
     pub(super) fn branch_get_id_by_name(
         &self,
@@ -1174,8 +1536,18 @@ impl MapxRawVs {
         self.branch_name_to_branch_id.get(branch_name.0)
     }
 
+    #[inline(always)]
+    pub(super) fn try_branch_get_id_by_name(
+        &self,
+        branch_name: BranchName,
+    ) -> VsdbResult<Option<BranchID>> {
+        self.branch_name_to_branch_id.try_get(branch_name.0)
+    }
+
     #[inline(always)]
     pub(super) fn prune(&mut self, reserved_ver_num: Option<usize>) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         self.version_clean_up_globally().c(d!())?;
 
         let reserved_ver_num = reserved_ver_num.unwrap_or(RESERVED_VERSION_NUM_DEFAULT);
@@ -1183,11 +1555,15 @@ impl MapxRawVs {
             return Err(eg!("reserved version number should NOT be zero"));
         }
 
-        let mut br_vers = self
+        let br_vers_hdrs = self
             .branch_to_its_versions
             .iter()
             .filter(|(_, vers)| !vers.is_empty())
-            .map(|(_, vers)| vers.iter())
+            .map(|(_, vers)| vers)
+            .collect::<Vec<_>>();
+        let mut br_vers = br_vers_hdrs
+            .iter()
+            .map(|vers| vers.iter())
             .collect::<Vec<_>>();
         alt!(br_vers.is_empty(), return Ok(()));
 
@@ -1222,6 +1598,10 @@ impl MapxRawVs {
         let mut rewrite_ver_chgset =
             self.version_to_change_set.get(rewrite_ver).c(d!())?;
 
+        // instances touched by the pruning, they will be compacted at the end
+        let compact = vsdb_compact_after_prune();
+        let mut touched = GcMarks::default();
+
         for (_, mut vers) in self
             .branch_to_its_versions
             .iter()
@@ -1230,29 +1610,83 @@ impl MapxRawVs {
             for ver in vers_to_be_merged.iter() {
                 vers.remove(ver).c(d!())?;
             }
+            alt!(compact, vers.gc_mark(&mut touched));
         }
 
         for ver in vers_to_be_merged.iter() {
-            self.version_id_to_version_name
+            let vername = self
+                .version_id_to_version_name
                 .remove(ver)
-                .c(d!())
-                .and_then(|vername| {
-                    self.version_name_to_version_id.remove(&vername).c(d!())
-                })?;
-            for (k, _) in self.version_to_change_set.remove(ver).c(d!())?.iter() {
+                .c(d!())?
+                .c(d!())?;
+            self.version_name_to_version_id
+                .remove(&vername)
+                .c(d!())?
+                .c(d!())?;
+            let mut chgset = self.version_to_change_set.remove(ver).c(d!())?.c(d!())?;
+            for (k, _) in chgset.iter() {
                 let mut k_vers = self.layered_kv.get(&k).c(d!())?;
-                let value = k_vers.remove(ver).c(d!())?;
+                let value = k_vers.remove(ver).c(d!())?.c(d!())?;
 
                 // keep at least one version
                 if k_vers.range(..=rewrite_ver).next().is_none() {
-                    assert!(rewrite_ver_chgset.insert(&k, &[]).is_none());
-                    assert!(k_vers.insert_ref(rewrite_ver, &value).is_none());
+                    assert!(rewrite_ver_chgset.insert(&k, &[]).c(d!())?.is_none());
+                    assert!(k_vers.insert_ref(rewrite_ver, &value).c(d!())?.is_none());
                 }
+                alt!(compact, k_vers.gc_mark(&mut touched));
             }
+            chgset.clear().c(d!())?;
+            alt!(compact, chgset.gc_mark(&mut touched));
+        }
+
+        if compact {
+            self.version_id_to_version_name.gc_mark(&mut touched);
+            self.version_name_to_version_id.gc_mark(&mut touched);
+            self.version_to_change_set.gc_mark_instance(&mut touched);
+            rewrite_ver_chgset.gc_mark(&mut touched);
+            touched.compact();
         }
 
         Ok(())
     }
+
+    // return `false` if the instance has been marked before
+    pub(super) fn gc_mark(&self, marks: &mut GcMarks) -> bool {
+        if self.layered_kv.gc_is_marked(marks) {
+            return false;
+        }
+
+        self.branch_name_to_branch_id.gc_mark(marks);
+        self.version_name_to_version_id.gc_mark(marks);
+        self.branch_id_to_branch_name.gc_mark(marks);
+        self.version_id_to_version_name.gc_mark(marks);
+        self.branch_to_its_versions.gc_mark(marks);
+        self.version_to_change_set.gc_mark(marks);
+        self.layered_kv.gc_mark(marks);
+        self.value_cfg.gc_mark(marks);
+
+        true
+    }
+
+    // all values that have ever been written, on all branches and versions
+    pub(super) fn for_each_value_globally<F>(&self, mut op: F)
+    where
+        F: FnMut(RawValue),
+    {
+        self.layered_kv.values().for_each(|vers| {
+            vers.values().flatten().for_each(&mut op);
+        });
+    }
+
+    // same as `for_each_value_globally`, along with the keys
+    pub(super) fn for_each_kv_globally<F>(&self, mut op: F)
+    where
+        F: FnMut(&[u8], RawValue),
+    {
+        self.layered_kv.iter().for_each(|(k, vers)| {
+            vers.values().flatten().for_each(|v| op(&k, v));
+        });
+    }
 }
 
 impl Default for MapxRawVs {
@@ -1266,7 +1700,7 @@ impl Default for MapxRawVs {
 
 pub struct MapxRawVsIter<'a> {
     hdr: &'a MapxRawVs,
-    iter: MapxOrdRawKeyIter<MapxOrd<VersionID, Option<RawValue>>>,
+    iter: MapxOrdRawKeyIter<'a, MapxOrd<VersionID, Option<RawValue>>>,
     branch_id: BranchID,
     version_id: VersionID,
 }
@@ -1315,5 +1749,80 @@ impl DoubleEndedIterator for MapxRawVsIter<'_> {
 
 impl ExactSizeIterator for MapxRawVsIter<'_> {}
 
+// a broken value(e.g. tampered on disk) is yielded as an error
+pub struct MapxRawVsTryIter<'a> {
+    hdr: &'a MapxRawVs,
+    iter: MapxOrdRawKeyTryIter<'a, MapxOrd<VersionID, Option<RawValue>>>,
+    branch_id: BranchID,
+    version_id: VersionID,
+    // failed before the iteration, yielded as the only item
+    err: Option<VsdbError>,
+}
+
+impl<'a> MapxRawVsTryIter<'a> {
+    pub(super) fn from_err(hdr: &'a MapxRawVs, e: VsdbError) -> Self {
+        MapxRawVsTryIter {
+            hdr,
+            iter: hdr.layered_kv.try_iter(),
+            branch_id: NULL,
+            version_id: NULL,
+            err: Some(e),
+        }
+    }
+
+    fn visible(
+        &self,
+        kv: VsdbResult<(RawKey, MapxOrd<VersionID, Option<RawValue>>)>,
+    ) -> VsdbResult<Option<(RawKey, RawValue)>> {
+        let (k, values) = kv?;
+        let v = self
+            .hdr
+            .try_get_from_layer(&values, self.branch_id, self.version_id)?;
+        Ok(v.map(|v| (k, v)))
+    }
+}
+
+impl<'a> Iterator for MapxRawVsTryIter<'a> {
+    type Item = VsdbResult<(RawKey, RawValue)>;
+
+    #[allow(clippy::while_let_on_iterator)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.err.take() {
+            return Some(Err(e));
+        }
+        if NULL == self.branch_id || NULL == self.version_id {
+            return None;
+        }
+
+        while let Some(kv) = self.iter.next() {
+            if let Some(kv) = self.visible(kv).transpose() {
+                return Some(kv);
+            }
+        }
+
+        None
+    }
+}
+
+impl DoubleEndedIterator for MapxRawVsTryIter<'_> {
+    #[allow(clippy::while_let_on_iterator)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.err.take() {
+            return Some(Err(e));
+        }
+        if NULL == self.branch_id || NULL == self.version_id {
+            return None;
+        }
+
+        while let Some(kv) = self.iter.next_back() {
+            if let Some(kv) = self.visible(kv).transpose() {
+                return Some(kv);
+            }
+        }
+
+        None
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////////
 ////////////////////////////////////////////////////////////////////////////////////
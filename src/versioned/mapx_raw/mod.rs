@@ -38,17 +38,26 @@ mod backend;
 mod test;
 
 use crate::{
-    common::{BranchName, ParentBranchName, RawKey, RawValue, VersionName, NULL},
+    common::{
+        ende::{KeyEncoding, ValueCodec},
+        entry::{Entry, EntryHdr},
+        error::{VsdbError, VsdbResult},
+        gc::{GcMark, GcMarks},
+        schema::SchemaId,
+        BranchName, InstanceOpts, ParentBranchName, RawKey, RawValue, VersionName,
+        NULL,
+    },
     BranchNameOwned, VersionNameOwned, VsMgmt,
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeSet,
+    marker::PhantomData,
     ops::{Deref, DerefMut, RangeBounds},
 };
 
-pub(crate) use backend::MapxRawVsIter;
+pub(crate) use backend::{MapxRawVsIter, MapxRawVsTryIter};
 
 /// Advanced `MapxRaw`, with versioned feature.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -63,6 +72,10 @@ impl Default for MapxRawVs {
 }
 
 impl MapxRawVs {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -78,6 +91,42 @@ impl MapxRawVs {
         }
     }
 
+    /// Create an instance with the specified options,
+    /// the options are applied to the stored values of all versions.
+    #[inline(always)]
+    pub fn new_with_opts(opts: &InstanceOpts) -> Self {
+        Self {
+            inner: backend::MapxRawVs::new_with_opts(opts),
+        }
+    }
+
+    // the codec of the typed values built on this instance
+    #[inline(always)]
+    pub(crate) fn codec(&self) -> ValueCodec {
+        self.inner.codec()
+    }
+
+    // the encoding of the typed keys built on this instance
+    #[inline(always)]
+    pub(crate) fn key_encoding(&self) -> KeyEncoding {
+        self.inner.key_encoding()
+    }
+
+    // rewrite all the values to the latest version of the schema `id`
+    #[inline(always)]
+    pub(crate) fn migrate_schema(&mut self, id: SchemaId) -> Result<()> {
+        self.inner.migrate_schema(id).c(d!())
+    }
+
+    // re-encode all the keys, on all branches and versions
+    #[inline(always)]
+    pub(crate) fn rekey<F>(&mut self, rekey: F) -> Result<()>
+    where
+        F: Fn(&[u8]) -> VsdbResult<RawKey>,
+    {
+        self.inner.rekey(rekey).c(d!())
+    }
+
     /// Insert a KV to the head version of the default branch.
     #[inline(always)]
     pub fn insert(&mut self, key: &[u8], value: &[u8]) -> Result<Option<RawValue>> {
@@ -113,6 +162,26 @@ impl MapxRawVs {
         self.inner.remove_by_branch(key, branch_id).c(d!())
     }
 
+    // write a batch of KVs to the head version of the default branch,
+    // a `None` value means a removing
+    #[inline(always)]
+    pub(crate) fn write_batch(&mut self, kvs: &[(&[u8], Option<&[u8]>)]) -> Result<()> {
+        let branch_id = self.inner.branch_get_default();
+        self.inner.write_batch_by_branch(kvs, branch_id).c(d!())
+    }
+
+    // write a batch of KVs to the head version of a specified branch,
+    // a `None` value means a removing
+    #[inline(always)]
+    pub(crate) fn write_batch_by_branch(
+        &mut self,
+        kvs: &[(&[u8], Option<&[u8]>)],
+        branch_name: BranchName,
+    ) -> Result<()> {
+        let branch_id = self.inner.branch_get_id_by_name(branch_name).c(d!())?;
+        self.inner.write_batch_by_branch(kvs, branch_id).c(d!())
+    }
+
     /// Get the value of a key from the default branch.
     #[inline(always)]
     pub fn get(&self, key: &[u8]) -> Option<RawValue> {
@@ -125,8 +194,13 @@ impl MapxRawVs {
     }
 
     #[inline(always)]
-    pub fn entry_ref<'a>(&'a mut self, key: &'a [u8]) -> Entry<'a> {
-        Entry { key, hdr: self }
+    pub fn entry(&mut self, key: RawKey) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
+    #[inline(always)]
+    pub fn entry_ref<'a>(&'a mut self, key: &'a [u8]) -> EntryRef<'a> {
+        EntryRef { key, hdr: self }
     }
 
     /// Get the value of a key from the head of a specified branch.
@@ -153,6 +227,47 @@ impl MapxRawVs {
         self.inner.get_by_branch_version(key, branch_id, version_id)
     }
 
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, key: &[u8]) -> VsdbResult<Option<RawValue>> {
+        self.inner
+            .try_get_by_branch(key, self.inner.branch_get_default())
+    }
+
+    /// Same as `get_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch(
+        &self,
+        key: &[u8],
+        branch_name: BranchName,
+    ) -> VsdbResult<Option<RawValue>> {
+        match self.inner.try_branch_get_id_by_name(branch_name)? {
+            Some(branch_id) => self.inner.try_get_by_branch(key, branch_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Same as `get_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch_version(
+        &self,
+        key: &[u8],
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> VsdbResult<Option<RawValue>> {
+        let branch_id = self.inner.try_branch_get_id_by_name(branch_name)?;
+        let version_id = self.inner.try_version_get_id_by_name(version_name)?;
+        match (branch_id, version_id) {
+            (Some(branch_id), Some(version_id)) => self
+                .inner
+                .try_get_by_branch_version(key, branch_id, version_id),
+            _ => Ok(None),
+        }
+    }
+
     /// Get the value of a key from the default branch,
     /// if the target key does not exist, will try to
     /// search a closest value bigger than the target key.
@@ -229,16 +344,20 @@ impl MapxRawVs {
 
     /// Create an iterator over the default branch.
     #[inline(always)]
-    pub fn iter(&self) -> MapxRawVsIter {
+    pub fn iter(&self) -> MapxRawVsIter<'_> {
         self.inner.iter()
     }
 
-    // TODO
-    // pub fn iter_mut
+    /// Create a mutable iterator over the default branch,
+    /// the changed values will be written to the latest version of it.
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> MapxRawVsIterMut<'_> {
+        self.to_iter_mut(|hdr| hdr.iter())
+    }
 
     /// Create an iterator over a specified branch.
     #[inline(always)]
-    pub fn iter_by_branch(&self, branch_name: BranchName) -> MapxRawVsIter {
+    pub fn iter_by_branch(&self, branch_name: BranchName) -> MapxRawVsIter<'_> {
         let branch_id = self
             .inner
             .branch_get_id_by_name(branch_name)
@@ -252,7 +371,7 @@ impl MapxRawVs {
         &self,
         branch_name: BranchName,
         version_name: VersionName,
-    ) -> MapxRawVsIter {
+    ) -> MapxRawVsIter<'_> {
         let branch_id = self
             .inner
             .branch_get_id_by_name(branch_name)
@@ -264,6 +383,82 @@ impl MapxRawVs {
         self.inner.iter_by_branch_version(branch_id, version_id)
     }
 
+    /// Same as `iter`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MapxRawVsTryIter<'_> {
+        self.try_range_ref(..)
+    }
+
+    /// Same as `iter_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch(&self, branch_name: BranchName) -> MapxRawVsTryIter<'_> {
+        self.try_range_ref_by_branch(branch_name, ..)
+    }
+
+    /// Same as `iter_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> MapxRawVsTryIter<'_> {
+        self.try_range_ref_by_branch_version(branch_name, version_name, ..)
+    }
+
+    /// Same as `range_ref`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_range_ref<'a, R: RangeBounds<&'a [u8]>>(
+        &self,
+        bounds: R,
+    ) -> MapxRawVsTryIter<'_> {
+        self.inner
+            .try_range_ref_by_branch(self.inner.branch_get_default(), bounds)
+    }
+
+    /// Same as `range_ref_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_range_ref_by_branch<'a, R: RangeBounds<&'a [u8]>>(
+        &self,
+        branch_name: BranchName,
+        bounds: R,
+    ) -> MapxRawVsTryIter<'_> {
+        match self.inner.try_branch_get_id_by_name(branch_name) {
+            Ok(branch_id) => self
+                .inner
+                .try_range_ref_by_branch(branch_id.unwrap_or(NULL), bounds),
+            Err(e) => MapxRawVsTryIter::from_err(&self.inner, e),
+        }
+    }
+
+    /// Same as `range_ref_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_range_ref_by_branch_version<'a, R: RangeBounds<&'a [u8]>>(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+        bounds: R,
+    ) -> MapxRawVsTryIter<'_> {
+        let ids = self
+            .inner
+            .try_branch_get_id_by_name(branch_name)
+            .and_then(|b| {
+                let v = self.inner.try_version_get_id_by_name(version_name)?;
+                Ok((b.unwrap_or(NULL), v.unwrap_or(NULL)))
+            });
+        match ids {
+            Ok((branch_id, version_id)) => self
+                .inner
+                .try_range_ref_by_branch_version(branch_id, version_id, bounds),
+            Err(e) => MapxRawVsTryIter::from_err(&self.inner, e),
+        }
+    }
+
     /// Create a range iterator over the default branch.
     #[inline(always)]
     pub fn range<'a, R: 'a + RangeBounds<RawKey>>(
@@ -350,6 +545,39 @@ impl MapxRawVs {
             .range_ref_by_branch_version(branch_id, version_id, bounds)
     }
 
+    /// Create a mutable range iterator over the default branch,
+    /// the changed values will be written to the latest version of it.
+    #[inline(always)]
+    pub fn range_mut<'a, R: 'a + RangeBounds<RawKey>>(
+        &'a mut self,
+        bounds: R,
+    ) -> MapxRawVsIterMut<'a> {
+        self.to_iter_mut(|hdr| hdr.range(bounds))
+    }
+
+    /// Create a mutable range iterator over the default branch,
+    /// the changed values will be written to the latest version of it.
+    #[inline(always)]
+    pub fn range_ref_mut<'a, R: RangeBounds<&'a [u8]>>(
+        &'a mut self,
+        bounds: R,
+    ) -> MapxRawVsIterMut<'a> {
+        self.to_iter_mut(|hdr| hdr.range_ref(bounds))
+    }
+
+    #[inline(always)]
+    fn to_iter_mut<'a, F>(&'a mut self, f: F) -> MapxRawVsIterMut<'a>
+    where
+        F: FnOnce(&'a Self) -> MapxRawVsIter<'a>,
+    {
+        // the items write back through their own copies of the handle
+        let hdr = unsafe { self.shadow() };
+        MapxRawVsIterMut {
+            iter: f(self),
+            hdr,
+        }
+    }
+
     /// Check if a key exist on the default branch.
     #[inline(always)]
     pub fn contains_key(&self, key: &[u8]) -> bool {
@@ -441,13 +669,59 @@ impl MapxRawVs {
     pub fn clear(&mut self) {
         self.inner.clear();
     }
+
+    // return `false` if the instance has been marked before
+    #[inline(always)]
+    pub(crate) fn gc_mark_instance(&self, marks: &mut GcMarks) -> bool {
+        self.inner.gc_mark(marks)
+    }
+
+    #[inline(always)]
+    pub(crate) fn for_each_value_globally<F>(&self, op: F)
+    where
+        F: FnMut(RawValue),
+    {
+        self.inner.for_each_value_globally(op)
+    }
+
+    #[inline(always)]
+    pub(crate) fn for_each_kv_globally<F>(&self, op: F)
+    where
+        F: FnMut(&[u8], RawValue),
+    {
+        self.inner.for_each_kv_globally(op)
+    }
+}
+
+impl EntryHdr for MapxRawVs {
+    type Key = RawKey;
+    type Value = RawValue;
+
+    fn entry_get(&self, key: &RawKey) -> Option<RawValue> {
+        self.get(key)
+    }
+
+    fn entry_set(&mut self, key: &RawKey, value: &RawValue) {
+        pnk!(self.insert(key, value));
+    }
+
+    fn entry_remove(&mut self, key: &RawKey) {
+        pnk!(self.remove(key));
+    }
+}
+
+impl GcMark for MapxRawVs {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.gc_mark_instance(marks);
+    }
 }
 
 impl VsMgmt for MapxRawVs {
     /// Create a new version on the default branch.
     #[inline(always)]
-    fn version_create(&mut self, version_name: VersionName) -> Result<()> {
-        self.inner.version_create(version_name.0).c(d!())
+    fn version_create(&mut self, version_name: VersionName) -> VsdbResult<()> {
+        self.inner.version_create(version_name.0)
     }
 
     /// Create a new version on a specified branch,
@@ -457,15 +731,11 @@ impl VsMgmt for MapxRawVs {
         &mut self,
         version_name: VersionName,
         branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|br_id| {
-                self.inner
-                    .version_create_by_branch(version_name.0, br_id)
-                    .c(d!())
-            })
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|br_id| self.inner.version_create_by_branch(version_name.0, br_id))
     }
 
     #[inline(always)]
@@ -510,8 +780,8 @@ impl VsMgmt for MapxRawVs {
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
     #[inline(always)]
-    fn version_pop(&mut self) -> Result<()> {
-        self.inner.version_pop().c(d!())
+    fn version_pop(&mut self) -> VsdbResult<()> {
+        self.inner.version_pop().map_err(From::from)
     }
 
     /// Remove the newest version on a specified branch.
@@ -522,11 +792,13 @@ impl VsMgmt for MapxRawVs {
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
     #[inline(always)]
-    fn version_pop_by_branch(&mut self, branch_name: BranchName) -> Result<()> {
+    fn version_pop_by_branch(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|br_id| self.inner.version_pop_by_branch(br_id).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|br_id| {
+                self.inner.version_pop_by_branch(br_id).map_err(From::from)
+            })
     }
 
     /// Merge all changes made by new versions after the base version into the base version.
@@ -537,11 +809,11 @@ impl VsMgmt for MapxRawVs {
     /// the `base_version` was created directly by the `branch_id`,
     /// or the data records of other branches may be corrupted.
     #[inline(always)]
-    unsafe fn version_rebase(&mut self, base_version: VersionName) -> Result<()> {
+    unsafe fn version_rebase(&mut self, base_version: VersionName) -> VsdbResult<()> {
         self.inner
             .version_get_id_by_name(base_version)
-            .c(d!())
-            .and_then(|bv| self.inner.version_rebase(bv).c(d!()))
+            .ok_or_else(|| VsdbError::version_not_found(base_version.0))
+            .and_then(|bv| self.inner.version_rebase(bv))
     }
 
     /// Merge all changes made by new versions after the base version into the base version.
@@ -556,26 +828,32 @@ impl VsMgmt for MapxRawVs {
         &mut self,
         base_version: VersionName,
         branch_name: BranchName,
-    ) -> Result<()> {
-        let bv = self.inner.version_get_id_by_name(base_version).c(d!())?;
-        let brid = self.inner.branch_get_id_by_name(branch_name).c(d!())?;
-        self.inner.version_rebase_by_branch(bv, brid).c(d!())
+    ) -> VsdbResult<()> {
+        let bv = self
+            .inner
+            .version_get_id_by_name(base_version)
+            .ok_or_else(|| VsdbError::version_not_found(base_version.0))?;
+        let brid = self
+            .inner
+            .branch_get_id_by_name(branch_name)
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))?;
+        self.inner.version_rebase_by_branch(bv, brid)
     }
 
     #[inline(always)]
-    fn version_list(&self) -> Result<Vec<VersionNameOwned>> {
-        self.inner.version_list().c(d!())
+    fn version_list(&self) -> VsdbResult<Vec<VersionNameOwned>> {
+        self.inner.version_list().map_err(From::from)
     }
 
     #[inline(always)]
     fn version_list_by_branch(
         &self,
         branch_name: BranchName,
-    ) -> Result<Vec<VersionNameOwned>> {
+    ) -> VsdbResult<Vec<VersionNameOwned>> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|brid| self.inner.version_list_by_branch(brid).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|brid| self.inner.version_list_by_branch(brid).map_err(From::from))
     }
 
     #[inline(always)]
@@ -584,27 +862,33 @@ impl VsMgmt for MapxRawVs {
     }
 
     #[inline(always)]
-    fn version_has_change_set(&self, version_name: VersionName) -> Result<bool> {
+    fn version_has_change_set(&self, version_name: VersionName) -> VsdbResult<bool> {
         self.inner
             .version_get_id_by_name(version_name)
-            .c(d!("version not found"))
-            .and_then(|verid| self.inner.version_has_change_set(verid).c(d!()))
+            .ok_or_else(|| VsdbError::version_not_found(version_name.0))
+            .and_then(|verid| {
+                self.inner.version_has_change_set(verid).map_err(From::from)
+            })
     }
 
     #[inline(always)]
-    fn version_clean_up_globally(&mut self) -> Result<()> {
-        self.inner.version_clean_up_globally().c(d!())
+    fn version_clean_up_globally(&mut self) -> VsdbResult<()> {
+        self.inner.version_clean_up_globally().map_err(From::from)
     }
 
     #[inline(always)]
     unsafe fn version_revert_globally(
         &mut self,
         version_name: VersionName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .version_get_id_by_name(version_name)
-            .c(d!("version not found"))
-            .and_then(|verid| self.inner.version_revert_globally(verid).c(d!()))
+            .ok_or_else(|| VsdbError::version_not_found(version_name.0))
+            .and_then(|verid| {
+                self.inner
+                    .version_revert_globally(verid)
+                    .map_err(From::from)
+            })
     }
 
     /// Create a new branch based on the head of the default branch.
@@ -614,10 +898,9 @@ impl VsMgmt for MapxRawVs {
         branch_name: BranchName,
         version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_create(branch_name.0, version_name.0, force)
-            .c(d!())
     }
 
     /// Create a new branch based on the head of a specified branch.
@@ -628,19 +911,17 @@ impl VsMgmt for MapxRawVs {
         version_name: VersionName,
         base_branch_name: ParentBranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(BranchName(base_branch_name.0))
-            .c(d!("base branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(base_branch_name.0))
             .and_then(|base_br_id| {
-                self.inner
-                    .branch_create_by_base_branch(
-                        branch_name.0,
-                        version_name.0,
-                        base_br_id,
-                        force,
-                    )
-                    .c(d!())
+                self.inner.branch_create_by_base_branch(
+                    branch_name.0,
+                    version_name.0,
+                    base_br_id,
+                    force,
+                )
             })
     }
 
@@ -653,24 +934,22 @@ impl VsMgmt for MapxRawVs {
         base_branch_name: ParentBranchName,
         base_version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         let base_br_id = self
             .inner
             .branch_get_id_by_name(BranchName(base_branch_name.0))
-            .c(d!("base branch not found"))?;
+            .ok_or_else(|| VsdbError::branch_not_found(base_branch_name.0))?;
         let base_ver_id = self
             .inner
             .version_get_id_by_name(base_version_name)
-            .c(d!("base vesion not found"))?;
-        self.inner
-            .branch_create_by_base_branch_version(
-                branch_name.0,
-                version_name.0,
-                base_br_id,
-                base_ver_id,
-                force,
-            )
-            .c(d!())
+            .ok_or_else(|| VsdbError::version_not_found(base_version_name.0))?;
+        self.inner.branch_create_by_base_branch_version(
+            branch_name.0,
+            version_name.0,
+            base_br_id,
+            base_ver_id,
+            force,
+        )
     }
 
     /// # Safety
@@ -682,10 +961,9 @@ impl VsMgmt for MapxRawVs {
         &mut self,
         branch_name: BranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_create_without_new_version(branch_name.0, force)
-            .c(d!())
     }
 
     /// # Safety
@@ -698,18 +976,16 @@ impl VsMgmt for MapxRawVs {
         branch_name: BranchName,
         base_branch_name: ParentBranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(BranchName(base_branch_name.0))
-            .c(d!("base branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(base_branch_name.0))
             .and_then(|base_br_id| {
-                self.inner
-                    .branch_create_by_base_branch_without_new_version(
-                        branch_name.0,
-                        base_br_id,
-                        force,
-                    )
-                    .c(d!())
+                self.inner.branch_create_by_base_branch_without_new_version(
+                    branch_name.0,
+                    base_br_id,
+                    force,
+                )
             })
     }
 
@@ -724,15 +1000,15 @@ impl VsMgmt for MapxRawVs {
         base_branch_name: ParentBranchName,
         base_version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         let base_br_id = self
             .inner
             .branch_get_id_by_name(BranchName(base_branch_name.0))
-            .c(d!("base branch not found"))?;
+            .ok_or_else(|| VsdbError::branch_not_found(base_branch_name.0))?;
         let base_ver_id = self
             .inner
             .version_get_id_by_name(base_version_name)
-            .c(d!("base vesion not found"))?;
+            .ok_or_else(|| VsdbError::version_not_found(base_version_name.0))?;
         self.inner
             .branch_create_by_base_branch_version_without_new_version(
                 branch_name.0,
@@ -740,7 +1016,6 @@ impl VsMgmt for MapxRawVs {
                 base_ver_id,
                 force,
             )
-            .c(d!())
     }
 
     /// Check if a branch exists or not.
@@ -772,29 +1047,29 @@ impl VsMgmt for MapxRawVs {
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
     #[inline(always)]
-    fn branch_remove(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_remove(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         if let Some(branch_id) = self.inner.branch_get_id_by_name(branch_name) {
-            self.inner.branch_remove(branch_id).c(d!())
+            self.inner.branch_remove(branch_id).map_err(From::from)
         } else {
-            Err(eg!("branch not found"))
+            Err(VsdbError::branch_not_found(branch_name.0))
         }
     }
 
     /// Clean up all other branches not in the list.
     #[inline(always)]
-    fn branch_keep_only(&mut self, branch_names: &[BranchName]) -> Result<()> {
+    fn branch_keep_only(&mut self, branch_names: &[BranchName]) -> VsdbResult<()> {
         let br_ids = branch_names
             .iter()
             .copied()
             .map(|brname| {
                 self.inner
                     .branch_get_id_by_name(brname)
-                    .c(d!("version not found"))
+                    .ok_or_else(|| VsdbError::branch_not_found(brname.0))
             })
-            .collect::<Result<BTreeSet<_>>>()?
+            .collect::<VsdbResult<BTreeSet<_>>>()?
             .into_iter()
             .collect::<Vec<_>>();
-        self.inner.branch_keep_only(&br_ids).c(d!())
+        self.inner.branch_keep_only(&br_ids).map_err(From::from)
     }
 
     /// Remove all changes directly made by versions(bigger than `last_version_id`) of this branch.
@@ -805,11 +1080,11 @@ impl VsMgmt for MapxRawVs {
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
     #[inline(always)]
-    fn branch_truncate(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_truncate(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|br_id| self.inner.branch_truncate(br_id).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|br_id| self.inner.branch_truncate(br_id).map_err(From::from))
     }
 
     /// Remove all changes directly made by versions(bigger than `last_version_id`) of this branch.
@@ -824,16 +1099,18 @@ impl VsMgmt for MapxRawVs {
         &mut self,
         branch_name: BranchName,
         last_version_name: VersionName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
             .and_then(|br_id| {
                 self.inner
                     .version_get_id_by_name(last_version_name)
-                    .c(d!("version not found"))
+                    .ok_or_else(|| VsdbError::version_not_found(last_version_name.0))
                     .and_then(|last_ver_id| {
-                        self.inner.branch_truncate_to(br_id, last_ver_id).c(d!())
+                        self.inner
+                            .branch_truncate_to(br_id, last_ver_id)
+                            .map_err(From::from)
                     })
             })
     }
@@ -846,11 +1123,11 @@ impl VsMgmt for MapxRawVs {
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
     #[inline(always)]
-    fn branch_pop_version(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_pop_version(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|id| self.inner.branch_pop_version(id).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|id| self.inner.branch_pop_version(id).map_err(From::from))
     }
 
     /// Merge a branch into another.
@@ -859,16 +1136,18 @@ impl VsMgmt for MapxRawVs {
         &mut self,
         branch_name: BranchName,
         target_branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
             .and_then(|brid| {
                 let target_brid = self
                     .inner
                     .branch_get_id_by_name(target_branch_name)
-                    .c(d!("target branch not found"))?;
-                self.inner.branch_merge_to(brid, target_brid).c(d!())
+                    .ok_or_else(|| {
+                        VsdbError::branch_not_found(target_branch_name.0)
+                    })?;
+                self.inner.branch_merge_to(brid, target_brid)
             })
     }
 
@@ -884,35 +1163,37 @@ impl VsMgmt for MapxRawVs {
         &mut self,
         branch_name: BranchName,
         target_branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
             .and_then(|brid| {
                 let target_brid = self
                     .inner
                     .branch_get_id_by_name(target_branch_name)
-                    .c(d!("target branch not found"))?;
-                self.inner.branch_merge_to_force(brid, target_brid).c(d!())
+                    .ok_or_else(|| {
+                        VsdbError::branch_not_found(target_branch_name.0)
+                    })?;
+                self.inner.branch_merge_to_force(brid, target_brid)
             })
     }
 
     /// Make a branch to be default,
     /// all default operations will be applied to it.
     #[inline(always)]
-    fn branch_set_default(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_set_default(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|brid| self.inner.branch_set_default(brid).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|brid| self.inner.branch_set_default(brid).map_err(From::from))
     }
 
     #[inline(always)]
-    fn branch_is_empty(&self, branch_name: BranchName) -> Result<bool> {
+    fn branch_is_empty(&self, branch_name: BranchName) -> VsdbResult<bool> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|brid| self.inner.branch_is_empty(brid).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|brid| self.inner.branch_is_empty(brid).map_err(From::from))
     }
 
     #[inline(always)]
@@ -930,14 +1211,48 @@ impl VsMgmt for MapxRawVs {
         &mut self,
         branch_1: BranchName,
         branch_2: BranchName,
-    ) -> Result<()> {
-        self.inner.branch_swap(branch_1.0, branch_2.0).c(d!())
+    ) -> VsdbResult<()> {
+        self.inner
+            .branch_swap(branch_1.0, branch_2.0)
+            .map_err(From::from)
     }
 
     /// Clean outdated versions out of the default reserved number.
     #[inline(always)]
-    fn prune(&mut self, reserved_ver_num: Option<usize>) -> Result<()> {
-        self.inner.prune(reserved_ver_num).c(d!())
+    fn prune(&mut self, reserved_ver_num: Option<usize>) -> VsdbResult<()> {
+        self.inner.prune(reserved_ver_num).map_err(From::from)
+    }
+}
+
+// where the data is read from
+#[derive(Clone, Copy)]
+pub(crate) enum View<'a> {
+    Default,
+    Branch(BranchName<'a>),
+    BranchVersion(BranchName<'a>, VersionName<'a>),
+}
+
+impl<'a> View<'a> {
+    #[inline(always)]
+    pub(crate) fn get(self, hdr: &MapxRawVs, key: &[u8]) -> Option<RawValue> {
+        match self {
+            View::Default => hdr.get(key),
+            View::Branch(b) => hdr.get_by_branch(key, b),
+            View::BranchVersion(b, v) => hdr.get_by_branch_version(key, b, v),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn try_get(
+        self,
+        hdr: &MapxRawVs,
+        key: &[u8],
+    ) -> VsdbResult<Option<RawValue>> {
+        match self {
+            View::Default => hdr.try_get(key),
+            View::Branch(b) => hdr.try_get_by_branch(key, b),
+            View::BranchVersion(b, v) => hdr.try_get_by_branch_version(key, b, v),
+        }
     }
 }
 
@@ -974,12 +1289,77 @@ impl<'a> DerefMut for ValueMut<'a> {
     }
 }
 
-pub struct Entry<'a> {
+pub struct MapxRawVsIterMut<'a> {
+    hdr: MapxRawVs,
+    iter: MapxRawVsIter<'a>,
+}
+
+impl<'a> Iterator for MapxRawVsIterMut<'a> {
+    type Item = (RawKey, ValueIterMut<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.iter.next()?;
+        let vmut = ValueIterMut::new(&self.hdr, k.clone(), v);
+        Some((k, vmut))
+    }
+}
+
+impl<'a> DoubleEndedIterator for MapxRawVsIterMut<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (k, v) = self.iter.next_back()?;
+        let vmut = ValueIterMut::new(&self.hdr, k.clone(), v);
+        Some((k, vmut))
+    }
+}
+
+// Each item owns a copy of the handle instead of a `&mut` to it,
+// so any number of them can be alive at the same time.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ValueIterMut<'a> {
+    hdr: MapxRawVs,
+    key: RawKey,
+    value: RawValue,
+    _p: PhantomData<&'a mut MapxRawVs>,
+}
+
+impl<'a> ValueIterMut<'a> {
+    fn new(hdr: &MapxRawVs, key: RawKey, value: RawValue) -> Self {
+        ValueIterMut {
+            // each key will be yielded only once by the iterator
+            hdr: unsafe { hdr.shadow() },
+            key,
+            value,
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<'a> Drop for ValueIterMut<'a> {
+    fn drop(&mut self) {
+        pnk!(self.hdr.insert(&self.key, &self.value));
+    }
+}
+
+impl<'a> Deref for ValueIterMut<'a> {
+    type Target = RawValue;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'a> DerefMut for ValueIterMut<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+pub struct EntryRef<'a> {
     hdr: &'a mut MapxRawVs,
     key: &'a [u8],
 }
 
-impl<'a> Entry<'a> {
+impl<'a> EntryRef<'a> {
     pub fn or_insert_ref(self, default: &'a [u8]) -> ValueMut<'a> {
         if !self.hdr.contains_key(self.key) {
             pnk!(self.hdr.insert(self.key, default));
@@ -193,6 +193,34 @@ fn test_len() {
     assert_eq!(0, hdr.len());
 }
 
+#[test]
+fn test_write_batch() {
+    let mut hdr = MapxRawVs::new();
+    assert!(hdr.write_batch(&[(&[0], Some(&[0]))]).is_err());
+    assert!(hdr.get(&[0]).is_none());
+
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    pnk!(hdr.write_batch(&[
+        (&[0], Some(&[0])),
+        (&[1], Some(&[1])),
+        (&[0], None),
+        (&[2], None),
+    ]));
+    assert!(hdr.get(&[0]).is_none());
+    assert_eq!(&pnk!(hdr.get(&[1]))[..], &[1]);
+    assert_eq!(1, hdr.len());
+
+    pnk!(hdr.version_create(VersionName(b"v1")));
+    pnk!(hdr.write_batch(&[(&[2], None)]));
+    assert!(!pnk!(hdr.version_has_change_set(VersionName(b"v1"))));
+
+    let bn = BranchName(b"test");
+    pnk!(hdr.branch_create(bn, VersionName(b"v2"), false));
+    pnk!(hdr.write_batch_by_branch(&[(&[1], None), (&[2], Some(&[2]))], bn));
+    assert!(hdr.iter_by_branch(bn).map(|(k, _)| k).eq([Box::from([2])]));
+    assert_eq!(&pnk!(hdr.get(&[1]))[..], &[1]);
+}
+
 #[test]
 fn test_valueende() {
     let cnt = 500;
@@ -2,22 +2,28 @@
 //! Versioned functions.
 //!
 
+pub mod dequex;
+pub mod indexed_mapx;
 pub mod mapx;
 pub mod mapx_ord;
 pub mod mapx_ord_rawkey;
 pub mod mapx_raw;
+pub mod multi_mapx;
 pub mod orphan;
+pub mod setx;
+pub mod setx_ord;
 pub mod vecx;
 
 use crate::{
     basic::{
-        mapx::Mapx, mapx_ord::MapxOrd, mapx_ord_rawkey::MapxOrdRawKey,
-        mapx_ord_rawvalue::MapxOrdRawValue, mapx_raw::MapxRaw, orphan::Orphan,
-        vecx::Vecx, vecx_raw::VecxRaw,
+        dequex::Dequex, indexed_mapx::IndexedMapx, mapx::Mapx, mapx_ord::MapxOrd,
+        mapx_ord_rawkey::MapxOrdRawKey, mapx_ord_rawvalue::MapxOrdRawValue,
+        mapx_raw::MapxRaw, multi_mapx::MultiMapx, orphan::Orphan, setx::Setx,
+        setx_ord::SetxOrd, vecx::Vecx, vecx_raw::VecxRaw,
     },
     BranchName, BranchNameOwned, ParentBranchName, VersionName, VersionNameOwned,
+    VsdbResult,
 };
-use ruc::*;
 use std::{
     collections::{
         BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque,
@@ -32,7 +38,7 @@ use std::{
 /// Methods collection of version management.
 pub trait VsMgmt {
     /// Create a new version on the default branch.
-    fn version_create(&mut self, version_name: VersionName) -> Result<()>;
+    fn version_create(&mut self, version_name: VersionName) -> VsdbResult<()>;
 
     /// Create a new version on a specified branch,
     /// NOTE: the branch must has been created.
@@ -40,7 +46,7 @@ pub trait VsMgmt {
         &mut self,
         version_name: VersionName,
         branch_name: BranchName,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Check if a verison exists on default branch.
     fn version_exists(&self, version_name: VersionName) -> bool;
@@ -59,7 +65,7 @@ pub trait VsMgmt {
     /// 'Write'-like operations on data require recursive tracing of all parent nodes,
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
-    fn version_pop(&mut self) -> Result<()>;
+    fn version_pop(&mut self) -> VsdbResult<()>;
 
     /// Remove the newest version on a specified branch.
     ///
@@ -68,7 +74,7 @@ pub trait VsMgmt {
     /// 'Write'-like operations on data require recursive tracing of all parent nodes,
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
-    fn version_pop_by_branch(&mut self, branch_name: BranchName) -> Result<()>;
+    fn version_pop_by_branch(&mut self, branch_name: BranchName) -> VsdbResult<()>;
 
     /// Merge all changes made by new versions after the base version into the base version.
     ///
@@ -77,7 +83,7 @@ pub trait VsMgmt {
     /// It's the caller's duty to ensure that
     /// the `base_version` was created directly by the `branch_id`,
     /// or the data records of other branches may be corrupted.
-    unsafe fn version_rebase(&mut self, base_version: VersionName) -> Result<()>;
+    unsafe fn version_rebase(&mut self, base_version: VersionName) -> VsdbResult<()>;
 
     /// Merge all changes made by new versions after the base version into the base version.
     ///
@@ -90,7 +96,7 @@ pub trait VsMgmt {
         &mut self,
         base_version: VersionName,
         branch_name: BranchName,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Check if a version exists.
     fn version_exists_globally(&self, version_name: VersionName) -> bool;
@@ -104,13 +110,13 @@ pub trait VsMgmt {
     /// ```rust,no_run
     /// struct Vs0(Vs1, Vs2);
     /// struct Vs1;
-    /// struct Vs2
+    /// struct Vs2;
     /// ```
     /// the caller of `Vs0` can NOT guarantee that
     /// other callers have never created new branches and versions on `Vs1` or `Vs2`,
     /// so the results returned by `Vs1` and `Vs2` may be different,
     /// so `Vs0` can NOT guarantee that it can get a completely consistent result.
-    fn version_list(&self) -> Result<Vec<VersionNameOwned>>;
+    fn version_list(&self) -> VsdbResult<Vec<VersionNameOwned>>;
 
     /// # NOTE
     ///
@@ -121,7 +127,7 @@ pub trait VsMgmt {
     /// ```rust,no_run
     /// struct Vs0(Vs1, Vs2);
     /// struct Vs1;
-    /// struct Vs2
+    /// struct Vs2;
     /// ```
     /// the caller of `Vs0` can NOT guarantee that
     /// other callers have never created new branches and versions on `Vs1` or `Vs2`,
@@ -130,7 +136,7 @@ pub trait VsMgmt {
     fn version_list_by_branch(
         &self,
         branch_name: BranchName,
-    ) -> Result<Vec<VersionNameOwned>>;
+    ) -> VsdbResult<Vec<VersionNameOwned>>;
 
     /// # NOTE
     ///
@@ -141,7 +147,7 @@ pub trait VsMgmt {
     /// ```rust,no_run
     /// struct Vs0(Vs1, Vs2);
     /// struct Vs1;
-    /// struct Vs2
+    /// struct Vs2;
     /// ```
     /// the caller of `Vs0` can NOT guarantee that
     /// other callers have never created new branches and versions on `Vs1` or `Vs2`,
@@ -150,10 +156,10 @@ pub trait VsMgmt {
     fn version_list_globally(&self) -> Vec<VersionNameOwned>;
 
     /// Check if some changes have been make on the version.
-    fn version_has_change_set(&self, version_name: VersionName) -> Result<bool>;
+    fn version_has_change_set(&self, version_name: VersionName) -> VsdbResult<bool>;
 
     /// Clean up all orphan versions, versions not belong to any branch.
-    fn version_clean_up_globally(&mut self) -> Result<()>;
+    fn version_clean_up_globally(&mut self) -> VsdbResult<()>;
 
     /// # Safety
     ///
@@ -161,7 +167,7 @@ pub trait VsMgmt {
     unsafe fn version_revert_globally(
         &mut self,
         version_name: VersionName,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Create a new branch based on the head of the default branch.
     fn branch_create(
@@ -169,7 +175,7 @@ pub trait VsMgmt {
         branch_name: BranchName,
         version_name: VersionName,
         force: bool,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Create a new branch based on the head of a specified branch.
     fn branch_create_by_base_branch(
@@ -178,7 +184,7 @@ pub trait VsMgmt {
         version_name: VersionName,
         base_branch_name: ParentBranchName,
         force: bool,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Create a new branch based on a specified version of a specified branch.
     fn branch_create_by_base_branch_version(
@@ -188,7 +194,7 @@ pub trait VsMgmt {
         base_branch_name: ParentBranchName,
         base_version_name: VersionName,
         force: bool,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// # Safety
     ///
@@ -198,7 +204,7 @@ pub trait VsMgmt {
         &mut self,
         branch_name: BranchName,
         force: bool,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// # Safety
     ///
@@ -209,7 +215,7 @@ pub trait VsMgmt {
         branch_name: BranchName,
         base_branch_name: ParentBranchName,
         force: bool,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// # Safety
     ///
@@ -221,7 +227,7 @@ pub trait VsMgmt {
         base_branch_name: ParentBranchName,
         base_version_name: VersionName,
         force: bool,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Check if a branch exists or not.
     fn branch_exists(&self, branch_name: BranchName) -> bool;
@@ -236,11 +242,11 @@ pub trait VsMgmt {
     /// 'Write'-like operations on data require recursive tracing of all parent nodes,
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
-    fn branch_remove(&mut self, branch_name: BranchName) -> Result<()>;
+    fn branch_remove(&mut self, branch_name: BranchName) -> VsdbResult<()>;
 
     /// Clean up all other branches not in the list,
     /// will also clean up all orphan versions.
-    fn branch_keep_only(&mut self, branch_names: &[BranchName]) -> Result<()>;
+    fn branch_keep_only(&mut self, branch_names: &[BranchName]) -> VsdbResult<()>;
 
     /// Remove all changes directly made by versions(bigger than `last_version_id`) of this branch.
     ///
@@ -249,7 +255,7 @@ pub trait VsMgmt {
     /// 'Write'-like operations on data require recursive tracing of all parent nodes,
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
-    fn branch_truncate(&mut self, branch_name: BranchName) -> Result<()>;
+    fn branch_truncate(&mut self, branch_name: BranchName) -> VsdbResult<()>;
 
     /// Remove all changes directly made by versions(bigger than `last_version_id`) of this branch.
     ///
@@ -262,7 +268,7 @@ pub trait VsMgmt {
         &mut self,
         branch_name: BranchName,
         last_version_name: VersionName,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Remove the newest version on a specified branch.
     ///
@@ -271,14 +277,14 @@ pub trait VsMgmt {
     /// 'Write'-like operations on data require recursive tracing of all parent nodes,
     /// while operations on branches and versions are limited to their own perspective,
     /// and should not do any tracing.
-    fn branch_pop_version(&mut self, branch_name: BranchName) -> Result<()>;
+    fn branch_pop_version(&mut self, branch_name: BranchName) -> VsdbResult<()>;
 
     /// Merge a branch into another.
     fn branch_merge_to(
         &mut self,
         branch_name: BranchName,
         target_branch_name: BranchName,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Merge a branch into another,
     /// even if new different versions have been created on the target branch.
@@ -291,14 +297,14 @@ pub trait VsMgmt {
         &mut self,
         branch_name: BranchName,
         target_branch_name: BranchName,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Make a branch to be default,
     /// all default operations will be applied to it.
-    fn branch_set_default(&mut self, branch_name: BranchName) -> Result<()>;
+    fn branch_set_default(&mut self, branch_name: BranchName) -> VsdbResult<()>;
 
     /// Check if the branch has no versions or only empty versions.
-    fn branch_is_empty(&self, branch_name: BranchName) -> Result<bool>;
+    fn branch_is_empty(&self, branch_name: BranchName) -> VsdbResult<bool>;
 
     /// # NOTE
     ///
@@ -309,7 +315,7 @@ pub trait VsMgmt {
     /// ```rust,no_run
     /// struct Vs0(Vs1, Vs2);
     /// struct Vs1;
-    /// struct Vs2
+    /// struct Vs2;
     /// ```
     /// the caller of `Vs0` can NOT guarantee that
     /// other callers have never created new branches and versions on `Vs1` or `Vs2`,
@@ -332,13 +338,13 @@ pub trait VsMgmt {
         &mut self,
         branch_1: BranchName,
         branch_2: BranchName,
-    ) -> Result<()>;
+    ) -> VsdbResult<()>;
 
     /// Clean outdated versions out of the default branch.
-    fn prune(&mut self, reserved_ver_num: Option<usize>) -> Result<()>;
+    fn prune(&mut self, reserved_ver_num: Option<usize>) -> VsdbResult<()>;
 }
 
-#[macro_export(super)]
+#[macro_export]
 macro_rules! impl_vs_methods {
     () => {
         /// Create a new version on the default branch.
@@ -346,8 +352,8 @@ macro_rules! impl_vs_methods {
         fn version_create(
             &mut self,
             version_name: $crate::VersionName,
-        ) -> ruc::Result<()> {
-            self.inner.version_create(version_name).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.version_create(version_name)
         }
 
         /// Create a new version on a specified branch,
@@ -357,10 +363,9 @@ macro_rules! impl_vs_methods {
             &mut self,
             version_name: $crate::VersionName,
             branch_name: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .version_create_by_branch(version_name, branch_name)
-                .c(d!())
         }
 
         /// Check if a verison exists on default branch.
@@ -388,8 +393,8 @@ macro_rules! impl_vs_methods {
         /// while operations on branches and versions are limited to their own perspective,
         /// and should not do any tracing.
         #[inline(always)]
-        fn version_pop(&mut self) -> ruc::Result<()> {
-            self.inner.version_pop().c(d!())
+        fn version_pop(&mut self) -> $crate::VsdbResult<()> {
+            self.inner.version_pop()
         }
 
         /// Remove the newest version on a specified branch.
@@ -403,8 +408,8 @@ macro_rules! impl_vs_methods {
         fn version_pop_by_branch(
             &mut self,
             branch_name: $crate::BranchName,
-        ) -> ruc::Result<()> {
-            self.inner.version_pop_by_branch(branch_name).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.version_pop_by_branch(branch_name)
         }
 
         /// Merge all changes made by new versions after the base version into the base version.
@@ -418,8 +423,8 @@ macro_rules! impl_vs_methods {
         unsafe fn version_rebase(
             &mut self,
             base_version: $crate::VersionName,
-        ) -> ruc::Result<()> {
-            self.inner.version_rebase(base_version).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.version_rebase(base_version)
         }
 
         /// Merge all changes made by new versions after the base version into the base version.
@@ -434,10 +439,9 @@ macro_rules! impl_vs_methods {
             &mut self,
             base_version: $crate::VersionName,
             branch_name: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .version_rebase_by_branch(base_version, branch_name)
-                .c(d!())
         }
 
         #[inline(always)]
@@ -446,16 +450,16 @@ macro_rules! impl_vs_methods {
         }
 
         #[inline(always)]
-        fn version_list(&self) -> ruc::Result<Vec<$crate::VersionNameOwned>> {
-            self.inner.version_list().c(d!())
+        fn version_list(&self) -> $crate::VsdbResult<Vec<$crate::VersionNameOwned>> {
+            self.inner.version_list()
         }
 
         #[inline(always)]
         fn version_list_by_branch(
             &self,
             branch_name: $crate::BranchName,
-        ) -> ruc::Result<Vec<$crate::VersionNameOwned>> {
-            self.inner.version_list_by_branch(branch_name).c(d!())
+        ) -> $crate::VsdbResult<Vec<$crate::VersionNameOwned>> {
+            self.inner.version_list_by_branch(branch_name)
         }
 
         #[inline(always)]
@@ -467,21 +471,21 @@ macro_rules! impl_vs_methods {
         fn version_has_change_set(
             &self,
             version_name: $crate::VersionName,
-        ) -> ruc::Result<bool> {
-            self.inner.version_has_change_set(version_name).c(d!())
+        ) -> $crate::VsdbResult<bool> {
+            self.inner.version_has_change_set(version_name)
         }
 
         #[inline(always)]
-        fn version_clean_up_globally(&mut self) -> ruc::Result<()> {
-            self.inner.version_clean_up_globally().c(d!())
+        fn version_clean_up_globally(&mut self) -> $crate::VsdbResult<()> {
+            self.inner.version_clean_up_globally()
         }
 
         #[inline(always)]
         unsafe fn version_revert_globally(
             &mut self,
             version_name: $crate::VersionName,
-        ) -> ruc::Result<()> {
-            self.inner.version_revert_globally(version_name).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.version_revert_globally(version_name)
         }
 
         /// Create a new branch based on the head of the default branch.
@@ -491,10 +495,9 @@ macro_rules! impl_vs_methods {
             branch_name: $crate::BranchName,
             version_name: $crate::VersionName,
             force: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_create(branch_name, version_name, force)
-                .c(d!())
         }
 
         /// Create a new branch based on the head of a specified branch.
@@ -505,7 +508,7 @@ macro_rules! impl_vs_methods {
             version_name: $crate::VersionName,
             base_branch_name: $crate::ParentBranchName,
             force: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_create_by_base_branch(
                     branch_name,
@@ -513,7 +516,6 @@ macro_rules! impl_vs_methods {
                     base_branch_name,
                     force,
                 )
-                .c(d!())
         }
 
         /// Create a new branch based on a specified version of a specified branch.
@@ -525,7 +527,7 @@ macro_rules! impl_vs_methods {
             base_branch_name: $crate::ParentBranchName,
             base_version_name: $crate::VersionName,
             force: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_create_by_base_branch_version(
                     branch_name,
@@ -534,7 +536,6 @@ macro_rules! impl_vs_methods {
                     base_version_name,
                     force,
                 )
-                .c(d!())
         }
 
         /// # Safety
@@ -546,10 +547,9 @@ macro_rules! impl_vs_methods {
             &mut self,
             branch_name: $crate::BranchName,
             force: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_create_without_new_version(branch_name, force)
-                .c(d!())
         }
 
         /// # Safety
@@ -562,14 +562,13 @@ macro_rules! impl_vs_methods {
             branch_name: $crate::BranchName,
             base_branch_name: $crate::ParentBranchName,
             force: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_create_by_base_branch_without_new_version(
                     branch_name,
                     base_branch_name,
                     force,
                 )
-                .c(d!())
         }
 
         /// # Safety
@@ -583,7 +582,7 @@ macro_rules! impl_vs_methods {
             base_branch_name: $crate::ParentBranchName,
             base_version_name: $crate::VersionName,
             force: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_create_by_base_branch_version_without_new_version(
                     branch_name,
@@ -591,7 +590,6 @@ macro_rules! impl_vs_methods {
                     base_version_name,
                     force,
                 )
-                .c(d!())
         }
 
         /// Check if a branch exists or not.
@@ -613,8 +611,11 @@ macro_rules! impl_vs_methods {
         /// while operations on branches and versions are limited to their own perspective,
         /// and should not do any tracing.
         #[inline(always)]
-        fn branch_remove(&mut self, branch_name: $crate::BranchName) -> ruc::Result<()> {
-            self.inner.branch_remove(branch_name).c(d!())
+        fn branch_remove(
+            &mut self,
+            branch_name: $crate::BranchName,
+        ) -> $crate::VsdbResult<()> {
+            self.inner.branch_remove(branch_name)
         }
 
         /// Clean up all other branches not in the list.
@@ -622,8 +623,8 @@ macro_rules! impl_vs_methods {
         fn branch_keep_only(
             &mut self,
             branch_names: &[$crate::BranchName],
-        ) -> ruc::Result<()> {
-            self.inner.branch_keep_only(branch_names).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.branch_keep_only(branch_names)
         }
 
         /// Remove all changes directly made by versions(bigger than `last_version_id`) of this branch.
@@ -637,8 +638,8 @@ macro_rules! impl_vs_methods {
         fn branch_truncate(
             &mut self,
             branch_name: $crate::BranchName,
-        ) -> ruc::Result<()> {
-            self.inner.branch_truncate(branch_name).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.branch_truncate(branch_name)
         }
 
         /// Remove all changes directly made by versions(bigger than `last_version_id`) of this branch.
@@ -653,10 +654,9 @@ macro_rules! impl_vs_methods {
             &mut self,
             branch_name: $crate::BranchName,
             last_version_name: $crate::VersionName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_truncate_to(branch_name, last_version_name)
-                .c(d!())
         }
 
         /// Remove the newest version on a specified branch.
@@ -670,8 +670,8 @@ macro_rules! impl_vs_methods {
         fn branch_pop_version(
             &mut self,
             branch_name: $crate::BranchName,
-        ) -> ruc::Result<()> {
-            self.inner.branch_pop_version(branch_name).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.branch_pop_version(branch_name)
         }
 
         /// Merge a branch into another
@@ -680,10 +680,9 @@ macro_rules! impl_vs_methods {
             &mut self,
             branch_name: $crate::BranchName,
             target_branch_name: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_merge_to(branch_name, target_branch_name)
-                .c(d!())
         }
 
         /// Merge a branch into another,
@@ -697,10 +696,9 @@ macro_rules! impl_vs_methods {
             &mut self,
             branch_name: $crate::BranchName,
             target_branch_name: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             self.inner
                 .branch_merge_to_force(branch_name, target_branch_name)
-                .c(d!())
         }
 
         /// Make a branch to be default,
@@ -709,12 +707,15 @@ macro_rules! impl_vs_methods {
         fn branch_set_default(
             &mut self,
             branch_name: $crate::BranchName,
-        ) -> ruc::Result<()> {
-            self.inner.branch_set_default(branch_name).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.branch_set_default(branch_name)
         }
 
-        fn branch_is_empty(&self, branch_name: $crate::BranchName) -> ruc::Result<bool> {
-            self.inner.branch_is_empty(branch_name).c(d!())
+        fn branch_is_empty(
+            &self,
+            branch_name: $crate::BranchName,
+        ) -> $crate::VsdbResult<bool> {
+            self.inner.branch_is_empty(branch_name)
         }
 
         fn branch_list(&self) -> Vec<$crate::BranchNameOwned> {
@@ -729,14 +730,14 @@ macro_rules! impl_vs_methods {
             &mut self,
             branch_1: $crate::BranchName,
             branch_2: $crate::BranchName,
-        ) -> ruc::Result<()> {
-            self.inner.branch_swap(branch_1, branch_2).c(d!())
+        ) -> $crate::VsdbResult<()> {
+            self.inner.branch_swap(branch_1, branch_2)
         }
 
         /// Clean outdated versions out of the default reserved number.
         #[inline(always)]
-        fn prune(&mut self, reserved_ver_num: Option<usize>) -> ruc::Result<()> {
-            self.inner.prune(reserved_ver_num).c(d!())
+        fn prune(&mut self, reserved_ver_num: Option<usize>) -> $crate::VsdbResult<()> {
+            self.inner.prune(reserved_ver_num)
         }
     };
 }
@@ -747,7 +748,7 @@ macro_rules! impl_vs_methods {
 macro_rules! impl_vs_methods_nope {
     () => {
         #[inline(always)]
-        fn version_create(&mut self, _: $crate::VersionName) -> ruc::Result<()> {
+        fn version_create(&mut self, _: $crate::VersionName) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -756,7 +757,7 @@ macro_rules! impl_vs_methods_nope {
             &mut self,
             _: $crate::VersionName,
             __: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -775,17 +776,23 @@ macro_rules! impl_vs_methods_nope {
         }
 
         #[inline(always)]
-        fn version_pop(&mut self) -> ruc::Result<()> {
+        fn version_pop(&mut self) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
         #[inline(always)]
-        fn version_pop_by_branch(&mut self, _: $crate::BranchName) -> ruc::Result<()> {
+        fn version_pop_by_branch(
+            &mut self,
+            _: $crate::BranchName,
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
         #[inline(always)]
-        unsafe fn version_rebase(&mut self, _: $crate::VersionName) -> ruc::Result<()> {
+        unsafe fn version_rebase(
+            &mut self,
+            _: $crate::VersionName,
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -794,7 +801,7 @@ macro_rules! impl_vs_methods_nope {
             &mut self,
             _: $crate::VersionName,
             _: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -802,14 +809,14 @@ macro_rules! impl_vs_methods_nope {
             true
         }
 
-        fn version_list(&self) -> ruc::Result<Vec<$crate::VersionNameOwned>> {
+        fn version_list(&self) -> $crate::VsdbResult<Vec<$crate::VersionNameOwned>> {
             Ok(Default::default())
         }
 
         fn version_list_by_branch(
             &self,
             _: $crate::BranchName,
-        ) -> ruc::Result<Vec<$crate::VersionNameOwned>> {
+        ) -> $crate::VsdbResult<Vec<$crate::VersionNameOwned>> {
             Ok(Default::default())
         }
 
@@ -817,18 +824,21 @@ macro_rules! impl_vs_methods_nope {
             Default::default()
         }
 
-        fn version_has_change_set(&self, _: $crate::VersionName) -> ruc::Result<bool> {
+        fn version_has_change_set(
+            &self,
+            _: $crate::VersionName,
+        ) -> $crate::VsdbResult<bool> {
             Ok(true)
         }
 
-        fn version_clean_up_globally(&mut self) -> ruc::Result<()> {
+        fn version_clean_up_globally(&mut self) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
         unsafe fn version_revert_globally(
             &mut self,
             _: $crate::VersionName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -838,7 +848,7 @@ macro_rules! impl_vs_methods_nope {
             _: $crate::BranchName,
             _: $crate::VersionName,
             _: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -849,7 +859,7 @@ macro_rules! impl_vs_methods_nope {
             _: $crate::VersionName,
             _: $crate::ParentBranchName,
             _: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -861,7 +871,7 @@ macro_rules! impl_vs_methods_nope {
             _: $crate::ParentBranchName,
             _: $crate::VersionName,
             _: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -869,7 +879,7 @@ macro_rules! impl_vs_methods_nope {
             &mut self,
             _: $crate::BranchName,
             _: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -878,7 +888,7 @@ macro_rules! impl_vs_methods_nope {
             _: $crate::BranchName,
             _: $crate::ParentBranchName,
             _: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -888,7 +898,7 @@ macro_rules! impl_vs_methods_nope {
             _: $crate::ParentBranchName,
             _: $crate::VersionName,
             _: bool,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -903,17 +913,20 @@ macro_rules! impl_vs_methods_nope {
         }
 
         #[inline(always)]
-        fn branch_remove(&mut self, _: $crate::BranchName) -> ruc::Result<()> {
+        fn branch_remove(&mut self, _: $crate::BranchName) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
         #[inline(always)]
-        fn branch_keep_only(&mut self, _: &[$crate::BranchName]) -> ruc::Result<()> {
+        fn branch_keep_only(
+            &mut self,
+            _: &[$crate::BranchName],
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
         #[inline(always)]
-        fn branch_truncate(&mut self, _: $crate::BranchName) -> ruc::Result<()> {
+        fn branch_truncate(&mut self, _: $crate::BranchName) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -922,12 +935,15 @@ macro_rules! impl_vs_methods_nope {
             &mut self,
             _: $crate::BranchName,
             _: $crate::VersionName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
         #[inline(always)]
-        fn branch_pop_version(&mut self, _: $crate::BranchName) -> ruc::Result<()> {
+        fn branch_pop_version(
+            &mut self,
+            _: $crate::BranchName,
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -936,7 +952,7 @@ macro_rules! impl_vs_methods_nope {
             &mut self,
             _: $crate::BranchName,
             _: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
@@ -944,16 +960,19 @@ macro_rules! impl_vs_methods_nope {
             &mut self,
             _: $crate::BranchName,
             _: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
         #[inline(always)]
-        fn branch_set_default(&mut self, _: $crate::BranchName) -> ruc::Result<()> {
+        fn branch_set_default(
+            &mut self,
+            _: $crate::BranchName,
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
-        fn branch_is_empty(&self, _: $crate::BranchName) -> ruc::Result<bool> {
+        fn branch_is_empty(&self, _: $crate::BranchName) -> $crate::VsdbResult<bool> {
             Ok(true)
         }
 
@@ -969,12 +988,12 @@ macro_rules! impl_vs_methods_nope {
             &mut self,
             _: $crate::BranchName,
             _: $crate::BranchName,
-        ) -> ruc::Result<()> {
+        ) -> $crate::VsdbResult<()> {
             Ok(())
         }
 
         #[inline(always)]
-        fn prune(&mut self, _: Option<usize>) -> ruc::Result<()> {
+        fn prune(&mut self, _: Option<usize>) -> $crate::VsdbResult<()> {
             Ok(())
         }
     };
@@ -984,6 +1003,10 @@ impl<T: ?Sized> VsMgmt for PhantomData<T> {
     impl_vs_methods_nope!();
 }
 
+impl<K, V> VsMgmt for IndexedMapx<K, V> {
+    impl_vs_methods_nope!();
+}
+
 impl<K, V> VsMgmt for Mapx<K, V> {
     impl_vs_methods_nope!();
 }
@@ -1004,10 +1027,22 @@ impl VsMgmt for MapxRaw {
     impl_vs_methods_nope!();
 }
 
+impl<K, V> VsMgmt for MultiMapx<K, V> {
+    impl_vs_methods_nope!();
+}
+
 impl<T> VsMgmt for Orphan<T> {
     impl_vs_methods_nope!();
 }
 
+impl<K> VsMgmt for Setx<K> {
+    impl_vs_methods_nope!();
+}
+
+impl<K> VsMgmt for SetxOrd<K> {
+    impl_vs_methods_nope!();
+}
+
 impl<V> VsMgmt for Vecx<V> {
     impl_vs_methods_nope!();
 }
@@ -1016,6 +1051,10 @@ impl VsMgmt for VecxRaw {
     impl_vs_methods_nope!();
 }
 
+impl<T> VsMgmt for Dequex<T> {
+    impl_vs_methods_nope!();
+}
+
 macro_rules! impl_for_primitives {
     ($ty: ty) => {
         impl VsMgmt for $ty {
@@ -1094,9 +1133,9 @@ impl_for_primitives!(
 );
 
 impl<T: VsMgmt> VsMgmt for Option<T> {
-    fn version_create(&mut self, version_name: VersionName) -> Result<()> {
+    fn version_create(&mut self, version_name: VersionName) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.version_create(version_name).c(d!())?;
+            i.version_create(version_name)?;
         }
         Ok(())
     }
@@ -1106,10 +1145,9 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         &mut self,
         version_name: VersionName,
         branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.version_create_by_branch(version_name, branch_name)
-                .c(d!())?;
+            i.version_create_by_branch(version_name, branch_name)?;
         }
         Ok(())
     }
@@ -1135,25 +1173,25 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
     }
 
     #[inline(always)]
-    fn version_pop(&mut self) -> Result<()> {
+    fn version_pop(&mut self) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.version_pop().c(d!())?;
+            i.version_pop()?;
         }
         Ok(())
     }
 
     #[inline(always)]
-    fn version_pop_by_branch(&mut self, branch_name: BranchName) -> Result<()> {
+    fn version_pop_by_branch(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.version_pop_by_branch(branch_name).c(d!())?;
+            i.version_pop_by_branch(branch_name)?;
         }
         Ok(())
     }
 
     #[inline(always)]
-    unsafe fn version_rebase(&mut self, base_version: VersionName) -> Result<()> {
+    unsafe fn version_rebase(&mut self, base_version: VersionName) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.version_rebase(base_version).c(d!())?;
+            i.version_rebase(base_version)?;
         }
         Ok(())
     }
@@ -1163,10 +1201,9 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         &mut self,
         base_version: VersionName,
         branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.version_rebase_by_branch(base_version, branch_name)
-                .c(d!())?;
+            i.version_rebase_by_branch(base_version, branch_name)?;
         }
         Ok(())
     }
@@ -1178,9 +1215,9 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         true
     }
 
-    fn version_list(&self) -> Result<Vec<VersionNameOwned>> {
+    fn version_list(&self) -> VsdbResult<Vec<VersionNameOwned>> {
         if let Some(i) = self.as_ref() {
-            i.version_list().c(d!())?;
+            i.version_list()?;
         }
         Ok(Default::default())
     }
@@ -1188,9 +1225,9 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
     fn version_list_by_branch(
         &self,
         branch_name: BranchName,
-    ) -> Result<Vec<VersionNameOwned>> {
+    ) -> VsdbResult<Vec<VersionNameOwned>> {
         if let Some(i) = self.as_ref() {
-            i.version_list_by_branch(branch_name).c(d!())?;
+            i.version_list_by_branch(branch_name)?;
         }
         Ok(Default::default())
     }
@@ -1202,16 +1239,16 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         Default::default()
     }
 
-    fn version_has_change_set(&self, version_name: VersionName) -> Result<bool> {
+    fn version_has_change_set(&self, version_name: VersionName) -> VsdbResult<bool> {
         if let Some(i) = self.as_ref() {
-            i.version_has_change_set(version_name).c(d!())?;
+            i.version_has_change_set(version_name)?;
         }
         Ok(true)
     }
 
-    fn version_clean_up_globally(&mut self) -> Result<()> {
+    fn version_clean_up_globally(&mut self) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.version_clean_up_globally().c(d!())?;
+            i.version_clean_up_globally()?;
         }
         Ok(())
     }
@@ -1219,9 +1256,9 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
     unsafe fn version_revert_globally(
         &mut self,
         version_name: VersionName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.version_revert_globally(version_name).c(d!())?;
+            i.version_revert_globally(version_name)?;
         }
         Ok(())
     }
@@ -1232,9 +1269,9 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         branch_name: BranchName,
         version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_create(branch_name, version_name, force).c(d!())?;
+            i.branch_create(branch_name, version_name, force)?;
         }
         Ok(())
     }
@@ -1246,15 +1283,14 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         version_name: VersionName,
         base_branch_name: ParentBranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
             i.branch_create_by_base_branch(
                 branch_name,
                 version_name,
                 base_branch_name,
                 force,
-            )
-            .c(d!())?;
+            )?;
         }
         Ok(())
     }
@@ -1267,7 +1303,7 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         base_branch_name: ParentBranchName,
         base_version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
             i.branch_create_by_base_branch_version(
                 branch_name,
@@ -1275,8 +1311,7 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
                 base_branch_name,
                 base_version_name,
                 force,
-            )
-            .c(d!())?;
+            )?;
         }
         Ok(())
     }
@@ -1285,10 +1320,9 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         &mut self,
         branch_name: BranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_create_without_new_version(branch_name, force)
-                .c(d!())?;
+            i.branch_create_without_new_version(branch_name, force)?;
         }
         Ok(())
     }
@@ -1298,14 +1332,13 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         branch_name: BranchName,
         base_branch_name: ParentBranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
             i.branch_create_by_base_branch_without_new_version(
                 branch_name,
                 base_branch_name,
                 force,
-            )
-            .c(d!())?;
+            )?;
         }
         Ok(())
     }
@@ -1316,15 +1349,14 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         base_branch_name: ParentBranchName,
         base_version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
             i.branch_create_by_base_branch_version_without_new_version(
                 branch_name,
                 base_branch_name,
                 base_version_name,
                 force,
-            )
-            .c(d!())?;
+            )?;
         }
         Ok(())
     }
@@ -1346,25 +1378,25 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
     }
 
     #[inline(always)]
-    fn branch_remove(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_remove(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_remove(branch_name).c(d!())?;
+            i.branch_remove(branch_name)?;
         }
         Ok(())
     }
 
     #[inline(always)]
-    fn branch_keep_only(&mut self, branch_names: &[BranchName]) -> Result<()> {
+    fn branch_keep_only(&mut self, branch_names: &[BranchName]) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_keep_only(branch_names).c(d!())?;
+            i.branch_keep_only(branch_names)?;
         }
         Ok(())
     }
 
     #[inline(always)]
-    fn branch_truncate(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_truncate(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_truncate(branch_name).c(d!())?;
+            i.branch_truncate(branch_name)?;
         }
         Ok(())
     }
@@ -1374,18 +1406,17 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         &mut self,
         branch_name: BranchName,
         last_version_name: VersionName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_truncate_to(branch_name, last_version_name)
-                .c(d!())?;
+            i.branch_truncate_to(branch_name, last_version_name)?;
         }
         Ok(())
     }
 
     #[inline(always)]
-    fn branch_pop_version(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_pop_version(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_pop_version(branch_name).c(d!())?;
+            i.branch_pop_version(branch_name)?;
         }
         Ok(())
     }
@@ -1395,9 +1426,9 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         &mut self,
         branch_name: BranchName,
         target_branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_merge_to(branch_name, target_branch_name).c(d!())?;
+            i.branch_merge_to(branch_name, target_branch_name)?;
         }
         Ok(())
     }
@@ -1407,25 +1438,24 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         &mut self,
         branch_name: BranchName,
         target_branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_merge_to_force(branch_name, target_branch_name)
-                .c(d!())?;
+            i.branch_merge_to_force(branch_name, target_branch_name)?;
         }
         Ok(())
     }
 
     #[inline(always)]
-    fn branch_set_default(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_set_default(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_set_default(branch_name).c(d!())?;
+            i.branch_set_default(branch_name)?;
         }
         Ok(())
     }
 
-    fn branch_is_empty(&self, branch_name: BranchName) -> Result<bool> {
+    fn branch_is_empty(&self, branch_name: BranchName) -> VsdbResult<bool> {
         if let Some(i) = self.as_ref() {
-            i.branch_is_empty(branch_name).c(d!())?;
+            i.branch_is_empty(branch_name)?;
         }
         Ok(true)
     }
@@ -1448,17 +1478,17 @@ impl<T: VsMgmt> VsMgmt for Option<T> {
         &mut self,
         branch_1: BranchName,
         branch_2: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.branch_swap(branch_1, branch_2).c(d!())?;
+            i.branch_swap(branch_1, branch_2)?;
         }
         Ok(())
     }
 
     #[inline(always)]
-    fn prune(&mut self, reserved_ver_num: Option<usize>) -> Result<()> {
+    fn prune(&mut self, reserved_ver_num: Option<usize>) -> VsdbResult<()> {
         if let Some(i) = self.as_mut() {
-            i.prune(reserved_ver_num).c(d!())?;
+            i.prune(reserved_ver_num)?;
         }
         Ok(())
     }
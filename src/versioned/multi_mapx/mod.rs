@@ -0,0 +1,602 @@
+//!
+//! Documents => [MapxRawVs](crate::versioned::mapx_raw)
+//!
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::multi_mapx::{as_slice, key_prefix, pair_key, value_bounds},
+    common::{ende::KeyEnDeOrdered, error::VsdbResult},
+    versioned::mapx_raw::{MapxRawVs, MapxRawVsIter, MapxRawVsTryIter},
+    BranchName, GcMark, GcMarks, VersionName, VsMgmt,
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{marker::PhantomData, ops::RangeBounds};
+
+/// A one-to-many map, all the `(key, value)` pairs share one keyspace.
+///
+/// Documents => [MapxRawVs](crate::versioned::mapx_raw::MapxRawVs)
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(bound = "")]
+pub struct MultiMapxVs<K, V> {
+    inner: MapxRawVs,
+    p: PhantomData<(K, V)>,
+}
+
+impl<K, V> Clone for MultiMapxVs<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            p: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Default for MultiMapxVs<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> MultiMapxVs<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        MultiMapxVs {
+            inner: MapxRawVs::new(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, key: &K, value: &V) -> bool {
+        self.inner.contains_key(&pair_key(key, value))
+    }
+
+    #[inline(always)]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_all(key).next().is_some()
+    }
+
+    /// The number of all the pairs.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// The number of the values of `key`.
+    ///
+    /// NOTE: this is O(n) in the number of the values,
+    /// they are scanned from the disk one by one on every call.
+    #[inline(always)]
+    pub fn count(&self, key: &K) -> usize {
+        self.get_all(key).count()
+    }
+
+    /// Return `true` if the pair did not exist before.
+    #[inline(always)]
+    pub fn insert(&mut self, key: K, value: V) -> Result<bool> {
+        self.insert_ref(&key, &value).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn insert_ref(&mut self, key: &K, value: &V) -> Result<bool> {
+        self.inner
+            .insert(&pair_key(key, value), &[])
+            .c(d!())
+            .map(|v| v.is_none())
+    }
+
+    /// Return `true` if the pair existed before.
+    #[inline(always)]
+    pub fn remove(&mut self, key: &K, value: &V) -> Result<bool> {
+        self.inner
+            .remove(&pair_key(key, value))
+            .c(d!())
+            .map(|v| v.is_some())
+    }
+
+    /// Remove all the values of `key` in one batch.
+    pub fn remove_all(&mut self, key: &K) -> Result<()> {
+        let prefix = key_prefix(key);
+        let keys = self
+            .inner
+            .range(value_bounds::<V, _>(&prefix, ..))
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        let kvs = keys.iter().map(|k| (&k[..], None)).collect::<Vec<_>>();
+        self.inner.write_batch(&kvs).c(d!())
+    }
+
+    /// Iterate over the values of `key` in ascending order.
+    #[inline(always)]
+    pub fn get_all(&self, key: &K) -> MultiMapxVsValues<'_, V> {
+        self.get_all_range(key, ..)
+    }
+
+    /// Iterate over the values of `key` within `bounds`,
+    /// for paging, start the next page after the last value of the current one,
+    /// e.g. `(Bound::Excluded(last), Bound::Unbounded)`.
+    pub fn get_all_range<R: RangeBounds<V>>(
+        &self,
+        key: &K,
+        bounds: R,
+    ) -> MultiMapxVsValues<'_, V> {
+        let prefix = key_prefix(key);
+        MultiMapxVsValues {
+            iter: self.inner.range(value_bounds(&prefix, bounds)),
+            prefix_len: prefix.len(),
+            p: PhantomData,
+        }
+    }
+
+    /// Iterate over all the pairs, ordered by keys and then values.
+    #[inline(always)]
+    pub fn iter(&self) -> MultiMapxVsIter<'_, K, V> {
+        MultiMapxVsIter {
+            iter: self.inner.iter(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `get_all`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_all(&self, key: &K) -> MultiMapxVsTryValues<'_, V> {
+        self.try_get_all_range(key, ..)
+    }
+
+    /// Same as `get_all_range`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    pub fn try_get_all_range<R: RangeBounds<V>>(
+        &self,
+        key: &K,
+        bounds: R,
+    ) -> MultiMapxVsTryValues<'_, V> {
+        let prefix = key_prefix(key);
+        let (lo, hi) = value_bounds(&prefix, bounds);
+        MultiMapxVsTryValues {
+            iter: self.inner.try_range_ref((as_slice(&lo), as_slice(&hi))),
+            prefix_len: prefix.len(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `iter`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> MultiMapxVsTryIter<'_, K, V> {
+        MultiMapxVsTryIter {
+            iter: self.inner.try_iter(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline(always)]
+    pub fn contains_by_branch(
+        &self,
+        key: &K,
+        value: &V,
+        branch_name: BranchName,
+    ) -> bool {
+        self.inner
+            .contains_key_by_branch(&pair_key(key, value), branch_name)
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch(&self, branch_name: BranchName) -> usize {
+        self.inner.len_by_branch(branch_name)
+    }
+
+    /// Same as `count`, O(n) in the number of the values.
+    #[inline(always)]
+    pub fn count_by_branch(&self, key: &K, branch_name: BranchName) -> usize {
+        self.get_all_by_branch(key, branch_name).count()
+    }
+
+    #[inline(always)]
+    pub fn insert_by_branch(
+        &mut self,
+        key: K,
+        value: V,
+        branch_name: BranchName,
+    ) -> Result<bool> {
+        self.insert_ref_by_branch(&key, &value, branch_name).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn insert_ref_by_branch(
+        &mut self,
+        key: &K,
+        value: &V,
+        branch_name: BranchName,
+    ) -> Result<bool> {
+        self.inner
+            .insert_by_branch(&pair_key(key, value), &[], branch_name)
+            .c(d!())
+            .map(|v| v.is_none())
+    }
+
+    #[inline(always)]
+    pub fn remove_by_branch(
+        &mut self,
+        key: &K,
+        value: &V,
+        branch_name: BranchName,
+    ) -> Result<bool> {
+        self.inner
+            .remove_by_branch(&pair_key(key, value), branch_name)
+            .c(d!())
+            .map(|v| v.is_some())
+    }
+
+    pub fn remove_all_by_branch(
+        &mut self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> Result<()> {
+        let prefix = key_prefix(key);
+        let keys = self
+            .inner
+            .range_by_branch(branch_name, value_bounds::<V, _>(&prefix, ..))
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        let kvs = keys.iter().map(|k| (&k[..], None)).collect::<Vec<_>>();
+        self.inner.write_batch_by_branch(&kvs, branch_name).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn get_all_by_branch(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> MultiMapxVsValues<'_, V> {
+        self.get_all_range_by_branch(key, branch_name, ..)
+    }
+
+    pub fn get_all_range_by_branch<R: RangeBounds<V>>(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        bounds: R,
+    ) -> MultiMapxVsValues<'_, V> {
+        let prefix = key_prefix(key);
+        MultiMapxVsValues {
+            iter: self
+                .inner
+                .range_by_branch(branch_name, value_bounds(&prefix, bounds)),
+            prefix_len: prefix.len(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch(&self, branch_name: BranchName) -> MultiMapxVsIter<'_, K, V> {
+        MultiMapxVsIter {
+            iter: self.inner.iter_by_branch(branch_name),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `get_all_by_branch`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_all_by_branch(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> MultiMapxVsTryValues<'_, V> {
+        self.try_get_all_range_by_branch(key, branch_name, ..)
+    }
+
+    /// Same as `get_all_range_by_branch`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    pub fn try_get_all_range_by_branch<R: RangeBounds<V>>(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        bounds: R,
+    ) -> MultiMapxVsTryValues<'_, V> {
+        let prefix = key_prefix(key);
+        let (lo, hi) = value_bounds(&prefix, bounds);
+        MultiMapxVsTryValues {
+            iter: self
+                .inner
+                .try_range_ref_by_branch(branch_name, (as_slice(&lo), as_slice(&hi))),
+            prefix_len: prefix.len(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `iter_by_branch`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch(
+        &self,
+        branch_name: BranchName,
+    ) -> MultiMapxVsTryIter<'_, K, V> {
+        MultiMapxVsTryIter {
+            iter: self.inner.try_iter_by_branch(branch_name),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains_by_branch_version(
+        &self,
+        key: &K,
+        value: &V,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> bool {
+        self.inner.contains_key_by_branch_version(
+            &pair_key(key, value),
+            branch_name,
+            version_name,
+        )
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> usize {
+        self.inner.len_by_branch_version(branch_name, version_name)
+    }
+
+    /// Same as `count`, O(n) in the number of the values.
+    #[inline(always)]
+    pub fn count_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> usize {
+        self.get_all_by_branch_version(key, branch_name, version_name)
+            .count()
+    }
+
+    #[inline(always)]
+    pub fn get_all_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> MultiMapxVsValues<'_, V> {
+        self.get_all_range_by_branch_version(key, branch_name, version_name, ..)
+    }
+
+    pub fn get_all_range_by_branch_version<R: RangeBounds<V>>(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+        bounds: R,
+    ) -> MultiMapxVsValues<'_, V> {
+        let prefix = key_prefix(key);
+        MultiMapxVsValues {
+            iter: self.inner.range_by_branch_version(
+                branch_name,
+                version_name,
+                value_bounds(&prefix, bounds),
+            ),
+            prefix_len: prefix.len(),
+            p: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> MultiMapxVsIter<'_, K, V> {
+        MultiMapxVsIter {
+            iter: self.inner.iter_by_branch_version(branch_name, version_name),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `get_all_by_branch_version`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_all_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> MultiMapxVsTryValues<'_, V> {
+        self.try_get_all_range_by_branch_version(key, branch_name, version_name, ..)
+    }
+
+    /// Same as `get_all_range_by_branch_version`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    pub fn try_get_all_range_by_branch_version<R: RangeBounds<V>>(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+        bounds: R,
+    ) -> MultiMapxVsTryValues<'_, V> {
+        let prefix = key_prefix(key);
+        let (lo, hi) = value_bounds(&prefix, bounds);
+        MultiMapxVsTryValues {
+            iter: self.inner.try_range_ref_by_branch_version(
+                branch_name,
+                version_name,
+                (as_slice(&lo), as_slice(&hi)),
+            ),
+            prefix_len: prefix.len(),
+            p: PhantomData,
+        }
+    }
+
+    /// Same as `iter_by_branch_version`, but a broken pair(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> MultiMapxVsTryIter<'_, K, V> {
+        MultiMapxVsTryIter {
+            iter: self
+                .inner
+                .try_iter_by_branch_version(branch_name, version_name),
+            p: PhantomData,
+        }
+    }
+}
+
+impl<K, V> VsMgmt for MultiMapxVs<K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    crate::impl_vs_methods!();
+}
+
+impl<K, V> GcMark for MultiMapxVs<K, V> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+pub struct MultiMapxVsIter<'a, K, V> {
+    iter: MapxRawVsIter<'a>,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> Iterator for MultiMapxVsIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(k, _)| pnk!(<(K, V)>::from_slice(&k)))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MultiMapxVsIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|(k, _)| pnk!(<(K, V)>::from_slice(&k)))
+    }
+}
+
+pub struct MultiMapxVsValues<'a, V> {
+    iter: MapxRawVsIter<'a>,
+    prefix_len: usize,
+    p: PhantomData<V>,
+}
+
+impl<'a, V: KeyEnDeOrdered> Iterator for MultiMapxVsValues<'a, V> {
+    type Item = V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|(k, _)| pnk!(V::from_slice(&k[self.prefix_len..])))
+    }
+}
+
+impl<'a, V: KeyEnDeOrdered> DoubleEndedIterator for MultiMapxVsValues<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|(k, _)| pnk!(V::from_slice(&k[self.prefix_len..])))
+    }
+}
+
+pub struct MultiMapxVsTryIter<'a, K, V> {
+    iter: MapxRawVsTryIter<'a>,
+    p: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V> Iterator for MultiMapxVsTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    type Item = VsdbResult<(K, V)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|kv| <(K, V)>::from_slice(&kv?.0))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MultiMapxVsTryIter<'a, K, V>
+where
+    K: KeyEnDeOrdered,
+    V: KeyEnDeOrdered,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|kv| <(K, V)>::from_slice(&kv?.0))
+    }
+}
+
+pub struct MultiMapxVsTryValues<'a, V> {
+    iter: MapxRawVsTryIter<'a>,
+    prefix_len: usize,
+    p: PhantomData<V>,
+}
+
+impl<'a, V: KeyEnDeOrdered> Iterator for MultiMapxVsTryValues<'a, V> {
+    type Item = VsdbResult<V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|kv| V::from_slice(&kv?.0[self.prefix_len..]))
+    }
+}
+
+impl<'a, V: KeyEnDeOrdered> DoubleEndedIterator for MultiMapxVsTryValues<'a, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|kv| V::from_slice(&kv?.0[self.prefix_len..]))
+    }
+}
@@ -0,0 +1,53 @@
+use super::*;
+use crate::common::INITIAL_BRANCH_NAME;
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr: MultiMapxVs<u32, u64> = MultiMapxVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+
+    (0..30u64).for_each(|v| assert!(pnk!(hdr.insert(v as u32 % 3, v))));
+    assert!(!pnk!(hdr.insert(0, 0)));
+    assert_eq!(hdr.len(), 30);
+    assert_eq!(hdr.count(&1), 10);
+    assert!(hdr.get_all(&1).eq((1..30).step_by(3)));
+    assert!(hdr.get_all_range(&2, ..10).eq([2, 5, 8]));
+
+    pnk!(hdr.version_create(VersionName(b"v1")));
+    assert!(pnk!(hdr.remove(&2, &5)));
+    assert!(!pnk!(hdr.remove(&2, &5)));
+    pnk!(hdr.remove_all(&1));
+    assert_eq!(hdr.count(&1), 0);
+    assert_eq!(hdr.len(), 19);
+    assert_eq!(
+        hdr.count_by_branch_version(&1, INITIAL_BRANCH_NAME, VersionName(b"v0")),
+        10
+    );
+
+    pnk!(hdr.version_pop());
+    assert!(hdr.contains(&2, &5));
+    assert_eq!(hdr.count(&1), 10);
+    assert_eq!(hdr.len(), 30);
+}
+
+#[test]
+fn test_branch() {
+    let mut hdr: MultiMapxVs<String, u32> = MultiMapxVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    let k = "addr".to_owned();
+    (0..5).for_each(|v| assert!(pnk!(hdr.insert_ref(&k, &v))));
+
+    let bn = BranchName(b"test");
+    pnk!(hdr.branch_create(bn, VersionName(b"v1"), false));
+    assert!(pnk!(hdr.insert_by_branch(k.clone(), 100, bn)));
+    assert!(pnk!(hdr.remove_by_branch(&k, &0, bn)));
+    assert!(hdr.get_all_by_branch(&k, bn).eq([1, 2, 3, 4, 100]));
+    assert!(hdr.get_all_by_branch(&k, INITIAL_BRANCH_NAME).eq(0..5));
+    assert!(hdr.contains_by_branch(&k, &100, bn));
+    assert!(!hdr.contains(&k, &100));
+
+    pnk!(hdr.remove_all_by_branch(&k, bn));
+    assert_eq!(hdr.count_by_branch(&k, bn), 0);
+    assert_eq!(hdr.count(&k), 5);
+    assert!(hdr.iter().eq((0..5).map(|v| (k.clone(), v))));
+}
@@ -6,8 +6,8 @@
 mod test;
 
 use crate::{
-    versioned::mapx_ord_rawkey::MapxOrdRawKeyVs, BranchName, ValueEnDe, VersionName,
-    VsMgmt,
+    common::error::VsdbResult, versioned::mapx_ord_rawkey::MapxOrdRawKeyVs, BranchName,
+    GcMark, GcMarks, ValueEnDe, VersionName, VsMgmt,
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -32,6 +32,10 @@ impl<T> Clone for OrphanVs<T> {
 }
 
 impl<T: ValueEnDe> OrphanVs<T> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -52,6 +56,13 @@ impl<T: ValueEnDe> OrphanVs<T> {
         self.inner.get(&[])
     }
 
+    /// Same as `get_value`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_value(&self) -> VsdbResult<Option<T>> {
+        self.inner.try_get(&[])
+    }
+
     /// Get the mutable handler of the value.
     ///
     /// NOTE:
@@ -79,6 +90,16 @@ impl<T: ValueEnDe> OrphanVs<T> {
         self.inner.get_by_branch(&[], branch_name)
     }
 
+    /// Same as `get_value_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_value_by_branch(
+        &self,
+        branch_name: BranchName,
+    ) -> VsdbResult<Option<T>> {
+        self.inner.try_get_by_branch(&[], branch_name)
+    }
+
     #[inline(always)]
     pub fn set_value_by_branch(
         &mut self,
@@ -106,6 +127,18 @@ impl<T: ValueEnDe> OrphanVs<T> {
         self.inner
             .get_by_branch_version(&[], branch_name, version_name)
     }
+
+    /// Same as `get_value_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_value_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> VsdbResult<Option<T>> {
+        self.inner
+            .try_get_by_branch_version(&[], branch_name, version_name)
+    }
 }
 
 impl<T: ValueEnDe> Default for OrphanVs<T> {
@@ -121,6 +154,16 @@ where
     crate::impl_vs_methods!();
 }
 
+impl<T> GcMark for OrphanVs<T>
+where
+    T: ValueEnDe + GcMark,
+{
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
 /// A type returned by `get_mut()`.
 pub struct ValueMut<'a, T>
 where
@@ -0,0 +1,204 @@
+//!
+//! Documents => [MapxRawVs](crate::versioned::mapx_raw)
+//!
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    common::ende::KeyEnDe,
+    versioned::mapx::{MapxVs, MapxVsIter},
+    BranchName, GcMark, GcMarks, VersionName, VsMgmt,
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+
+/// Documents => [MapxRawVs](crate::versioned::mapx_raw::MapxRawVs)
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(bound = "")]
+pub struct SetxVs<K> {
+    inner: MapxVs<K, ()>,
+}
+
+impl<K> Clone for SetxVs<K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: KeyEnDe> Default for SetxVs<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyEnDe> SetxVs<K> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        SetxVs {
+            inner: MapxVs::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, key: &K) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Return `true` if the key did not exist before.
+    #[inline(always)]
+    pub fn insert(&mut self, key: K) -> Result<bool> {
+        self.insert_ref(&key).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn insert_ref(&mut self, key: &K) -> Result<bool> {
+        self.inner.insert_ref(key, &()).c(d!()).map(|v| v.is_none())
+    }
+
+    /// Return `true` if the key existed before.
+    #[inline(always)]
+    pub fn remove(&mut self, key: &K) -> Result<bool> {
+        self.inner.remove(key).c(d!()).map(|v| v.is_some())
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> SetxVsIter<'_, K> {
+        SetxVsIter {
+            iter: self.inner.iter(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline(always)]
+    pub fn contains_by_branch(&self, key: &K, branch_name: BranchName) -> bool {
+        self.inner.contains_key_by_branch(key, branch_name)
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch(&self, branch_name: BranchName) -> usize {
+        self.inner.len_by_branch(branch_name)
+    }
+
+    #[inline(always)]
+    pub fn insert_by_branch(&mut self, key: K, branch_name: BranchName) -> Result<bool> {
+        self.insert_ref_by_branch(&key, branch_name).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn insert_ref_by_branch(
+        &mut self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> Result<bool> {
+        self.inner
+            .insert_ref_by_branch(key, &(), branch_name)
+            .c(d!())
+            .map(|v| v.is_none())
+    }
+
+    #[inline(always)]
+    pub fn remove_by_branch(
+        &mut self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> Result<bool> {
+        self.inner
+            .remove_by_branch(key, branch_name)
+            .c(d!())
+            .map(|v| v.is_some())
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch(&self, branch_name: BranchName) -> SetxVsIter<'_, K> {
+        SetxVsIter {
+            iter: self.inner.iter_by_branch(branch_name),
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> bool {
+        self.inner
+            .contains_key_by_branch_version(key, branch_name, version_name)
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> usize {
+        self.inner.len_by_branch_version(branch_name, version_name)
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> SetxVsIter<'_, K> {
+        SetxVsIter {
+            iter: self.inner.iter_by_branch_version(branch_name, version_name),
+        }
+    }
+}
+
+impl<K: KeyEnDe> VsMgmt for SetxVs<K> {
+    crate::impl_vs_methods!();
+}
+
+impl<K> GcMark for SetxVs<K> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+pub struct SetxVsIter<'a, K: KeyEnDe> {
+    iter: MapxVsIter<'a, K, ()>,
+}
+
+impl<'a, K: KeyEnDe> Iterator for SetxVsIter<'a, K> {
+    type Item = K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K: KeyEnDe> DoubleEndedIterator for SetxVsIter<'a, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
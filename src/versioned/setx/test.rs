@@ -0,0 +1,47 @@
+use super::*;
+use crate::common::INITIAL_BRANCH_NAME;
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr: SetxVs<u32> = SetxVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+
+    (0..10).for_each(|i| assert!(pnk!(hdr.insert(i))));
+    assert!(!pnk!(hdr.insert_ref(&0)));
+    assert_eq!(hdr.len(), 10);
+    assert!(hdr.contains(&9));
+
+    pnk!(hdr.version_create(VersionName(b"v1")));
+    assert!(pnk!(hdr.remove(&9)));
+    assert!(!pnk!(hdr.remove(&9)));
+    assert!(!hdr.contains(&9));
+    assert!(hdr.contains_by_branch_version(&9, INITIAL_BRANCH_NAME, VersionName(b"v0")));
+    assert_eq!(
+        hdr.len_by_branch_version(INITIAL_BRANCH_NAME, VersionName(b"v0")),
+        10
+    );
+
+    pnk!(hdr.version_pop());
+    assert!(hdr.contains(&9));
+    let mut keys = hdr.iter().collect::<Vec<_>>();
+    keys.sort_unstable();
+    assert_eq!(keys, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_branch() {
+    let mut hdr: SetxVs<u32> = SetxVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    pnk!(hdr.insert(0));
+
+    let bn = BranchName(b"test");
+    pnk!(hdr.branch_create(bn, VersionName(b"v1"), false));
+    assert!(pnk!(hdr.insert_by_branch(1, bn)));
+    assert!(hdr.contains_by_branch(&1, bn));
+    assert!(!hdr.contains_by_branch(&1, INITIAL_BRANCH_NAME));
+    assert_eq!(hdr.len_by_branch(bn), 2);
+    assert_eq!(hdr.iter_by_branch(INITIAL_BRANCH_NAME).count(), 1);
+
+    assert!(pnk!(hdr.remove_by_branch(&0, bn)));
+    assert!(hdr.contains_by_branch(&0, INITIAL_BRANCH_NAME));
+}
@@ -0,0 +1,288 @@
+//!
+//! Documents => [MapxRawVs](crate::versioned::mapx_raw)
+//!
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    basic::setx_ord::{Difference, Intersection, Union},
+    common::ende::KeyEnDeOrdered,
+    versioned::mapx_ord::{MapxOrdVs, MapxOrdVsIter},
+    BranchName, GcMark, GcMarks, VersionName, VsMgmt,
+};
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeBounds;
+
+/// Documents => [MapxRawVs](crate::versioned::mapx_raw::MapxRawVs)
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(bound = "")]
+pub struct SetxOrdVs<K> {
+    inner: MapxOrdVs<K, ()>,
+}
+
+impl<K> Clone for SetxOrdVs<K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: KeyEnDeOrdered> Default for SetxOrdVs<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyEnDeOrdered> SetxOrdVs<K> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        SetxOrdVs {
+            inner: MapxOrdVs::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, key: &K) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Return `true` if the key did not exist before.
+    #[inline(always)]
+    pub fn insert(&mut self, key: K) -> Result<bool> {
+        self.insert_ref(&key).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn insert_ref(&mut self, key: &K) -> Result<bool> {
+        self.inner.insert_ref(key, &()).c(d!()).map(|v| v.is_none())
+    }
+
+    /// Return `true` if the key existed before.
+    #[inline(always)]
+    pub fn remove(&mut self, key: &K) -> Result<bool> {
+        self.inner.remove(key).c(d!()).map(|v| v.is_some())
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> SetxOrdVsIter<'_, K> {
+        SetxOrdVsIter {
+            iter: self.inner.iter(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn range<'a, R: 'a + RangeBounds<K>>(
+        &'a self,
+        bounds: R,
+    ) -> SetxOrdVsIter<'a, K> {
+        SetxOrdVsIter {
+            iter: self.inner.range(bounds),
+        }
+    }
+
+    #[inline(always)]
+    pub fn first(&self) -> Option<K> {
+        self.inner.first().map(|(k, _)| k)
+    }
+
+    #[inline(always)]
+    pub fn last(&self) -> Option<K> {
+        self.inner.last().map(|(k, _)| k)
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Re-encode all the keys of a set loaded from the handle of a `MapxOrdVs<K, ()>`
+    /// created by older versions of VSDB, see `MapxOrdVs::migrate_legacy_keys`.
+    #[inline(always)]
+    pub fn migrate_legacy_keys(&mut self) -> Result<()> {
+        self.inner.migrate_legacy_keys().c(d!())
+    }
+
+    /// Visit the keys in `self` or `other` in ascending order, without duplicates.
+    #[inline(always)]
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<SetxOrdVsIter<'a, K>>
+    where
+        K: Ord,
+    {
+        Union::new(self.iter(), other.iter())
+    }
+
+    /// Visit the keys in both `self` and `other` in ascending order.
+    #[inline(always)]
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Intersection<SetxOrdVsIter<'a, K>>
+    where
+        K: Ord,
+    {
+        Intersection::new(self.iter(), other.iter())
+    }
+
+    /// Visit the keys in `self` but not in `other` in ascending order.
+    #[inline(always)]
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<SetxOrdVsIter<'a, K>>
+    where
+        K: Ord,
+    {
+        Difference::new(self.iter(), other.iter())
+    }
+
+    #[inline(always)]
+    pub fn contains_by_branch(&self, key: &K, branch_name: BranchName) -> bool {
+        self.inner.contains_key_by_branch(key, branch_name)
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch(&self, branch_name: BranchName) -> usize {
+        self.inner.len_by_branch(branch_name)
+    }
+
+    #[inline(always)]
+    pub fn insert_by_branch(&mut self, key: K, branch_name: BranchName) -> Result<bool> {
+        self.insert_ref_by_branch(&key, branch_name).c(d!())
+    }
+
+    #[inline(always)]
+    pub fn insert_ref_by_branch(
+        &mut self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> Result<bool> {
+        self.inner
+            .insert_ref_by_branch(key, &(), branch_name)
+            .c(d!())
+            .map(|v| v.is_none())
+    }
+
+    #[inline(always)]
+    pub fn remove_by_branch(
+        &mut self,
+        key: &K,
+        branch_name: BranchName,
+    ) -> Result<bool> {
+        self.inner
+            .remove_by_branch(key, branch_name)
+            .c(d!())
+            .map(|v| v.is_some())
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch(&self, branch_name: BranchName) -> SetxOrdVsIter<'_, K> {
+        SetxOrdVsIter {
+            iter: self.inner.iter_by_branch(branch_name),
+        }
+    }
+
+    #[inline(always)]
+    pub fn range_by_branch<'a, R: 'a + RangeBounds<K>>(
+        &'a self,
+        branch_name: BranchName,
+        bounds: R,
+    ) -> SetxOrdVsIter<'a, K> {
+        SetxOrdVsIter {
+            iter: self.inner.range_by_branch(branch_name, bounds),
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains_by_branch_version(
+        &self,
+        key: &K,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> bool {
+        self.inner
+            .contains_key_by_branch_version(key, branch_name, version_name)
+    }
+
+    #[inline(always)]
+    pub fn len_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> usize {
+        self.inner.len_by_branch_version(branch_name, version_name)
+    }
+
+    #[inline(always)]
+    pub fn iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> SetxOrdVsIter<'_, K> {
+        SetxOrdVsIter {
+            iter: self.inner.iter_by_branch_version(branch_name, version_name),
+        }
+    }
+
+    #[inline(always)]
+    pub fn range_by_branch_version<'a, R: 'a + RangeBounds<K>>(
+        &'a self,
+        branch_name: BranchName,
+        version_name: VersionName,
+        bounds: R,
+    ) -> SetxOrdVsIter<'a, K> {
+        SetxOrdVsIter {
+            iter: self
+                .inner
+                .range_by_branch_version(branch_name, version_name, bounds),
+        }
+    }
+}
+
+impl<K: KeyEnDeOrdered> VsMgmt for SetxOrdVs<K> {
+    crate::impl_vs_methods!();
+}
+
+impl<K> GcMark for SetxOrdVs<K> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
+pub struct SetxOrdVsIter<'a, K: KeyEnDeOrdered> {
+    iter: MapxOrdVsIter<'a, K, ()>,
+}
+
+impl<'a, K: KeyEnDeOrdered> Iterator for SetxOrdVsIter<'a, K> {
+    type Item = K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K: KeyEnDeOrdered> DoubleEndedIterator for SetxOrdVsIter<'a, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
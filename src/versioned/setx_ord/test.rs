@@ -0,0 +1,61 @@
+use super::*;
+use crate::common::{engines::reload_as_legacy, INITIAL_BRANCH_NAME};
+
+#[test]
+fn test_insert_remove() {
+    let mut hdr: SetxOrdVs<i32> = SetxOrdVs::new();
+    pnk!(hdr.version_create(VersionName(b"v0")));
+
+    (-5..5).for_each(|i| assert!(pnk!(hdr.insert(i))));
+    assert!(!pnk!(hdr.insert(0)));
+    assert!(hdr.iter().eq(-5..5));
+    assert!(hdr.range(-1..=1).eq(-1..=1));
+    assert_eq!(hdr.first(), Some(-5));
+    assert_eq!(hdr.last(), Some(4));
+
+    pnk!(hdr.version_create(VersionName(b"v1")));
+    assert!(pnk!(hdr.remove(&0)));
+    assert!(!hdr.contains(&0));
+    assert!(
+        hdr.range_by_branch_version(INITIAL_BRANCH_NAME, VersionName(b"v0"), 0..2)
+            .eq(0..2)
+    );
+
+    pnk!(hdr.version_pop());
+    assert!(hdr.contains(&0));
+    assert_eq!(hdr.len(), 10);
+}
+
+#[test]
+fn test_set_operations() {
+    let mut a: SetxOrdVs<u32> = SetxOrdVs::new();
+    let mut b: SetxOrdVs<u32> = SetxOrdVs::new();
+    pnk!(a.version_create(VersionName(b"v0")));
+    pnk!(b.version_create(VersionName(b"v0")));
+    (0..6).for_each(|i| assert!(pnk!(a.insert(i))));
+    (3..9).for_each(|i| assert!(pnk!(b.insert(i))));
+
+    assert!(a.union(&b).eq(0..9));
+    assert!(a.intersection(&b).eq(3..6));
+    assert!(a.difference(&b).eq(0..3));
+    assert!(b.difference(&a).eq(6..9));
+
+    let bn = BranchName(b"test");
+    pnk!(a.branch_create(bn, VersionName(b"v1"), false));
+    assert!(pnk!(a.insert_by_branch(100, bn)));
+    assert!(a.range_by_branch(bn, 5..).eq([5, 100]));
+    assert!(a.iter_by_branch(INITIAL_BRANCH_NAME).eq(0..6));
+}
+
+#[test]
+fn test_legacy_keys() {
+    let mut hdr: SetxOrdVs<i64> = reload_as_legacy(&SetxOrdVs::new());
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    (-3..3).for_each(|i| {
+        pnk!(hdr.insert(i));
+    });
+    assert_eq!(Some(0), hdr.first());
+
+    pnk!(hdr.migrate_legacy_keys());
+    assert!(hdr.iter().eq(-3..3));
+}
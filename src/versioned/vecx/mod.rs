@@ -6,8 +6,12 @@
 mod test;
 
 use crate::{
-    versioned::mapx_ord_rawkey::{MapxOrdRawKeyVs, MapxOrdRawKeyVsIter},
-    BranchName, ValueEnDe, VersionName, VsMgmt,
+    common::error::VsdbResult,
+    versioned::mapx_ord_rawkey::{
+        MapxOrdRawKeyVs, MapxOrdRawKeyVsIter, MapxOrdRawKeyVsIterMut,
+        MapxOrdRawKeyVsTryIter, ValueIterMut,
+    },
+    BranchName, GcMark, GcMarks, ValueEnDe, VersionName, VsMgmt,
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -35,6 +39,10 @@ impl<T: ValueEnDe> Default for VecxVs<T> {
 }
 
 impl<T: ValueEnDe> VecxVs<T> {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
     #[inline(always)]
     pub unsafe fn shadow(&self) -> Self {
         Self {
@@ -54,6 +62,13 @@ impl<T: ValueEnDe> VecxVs<T> {
         self.inner.get(&(idx as u64).to_be_bytes())
     }
 
+    /// Same as `get`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get(&self, idx: usize) -> VsdbResult<Option<T>> {
+        self.inner.try_get(&(idx as u64).to_be_bytes())
+    }
+
     #[inline(always)]
     pub fn get_mut(&mut self, idx: usize) -> Option<ValueMut<'_, T>> {
         self.get(idx)
@@ -120,8 +135,21 @@ impl<T: ValueEnDe> VecxVs<T> {
         }
     }
 
-    // TODO
-    // pub fn iter_mut
+    /// Same as `iter`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter(&self) -> VecxVsTryIter<'_, T> {
+        VecxVsTryIter {
+            iter: self.inner.try_iter(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> VecxVsIterMut<'_, T> {
+        VecxVsIterMut {
+            iter: self.inner.iter_mut(),
+        }
+    }
 
     #[inline(always)]
     pub fn clear(&mut self) {
@@ -134,6 +162,18 @@ impl<T: ValueEnDe> VecxVs<T> {
             .get_by_branch(&(idx as u64).to_be_bytes(), branch_name)
     }
 
+    /// Same as `get_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch(
+        &self,
+        idx: usize,
+        branch_name: BranchName,
+    ) -> VsdbResult<Option<T>> {
+        self.inner
+            .try_get_by_branch(&(idx as u64).to_be_bytes(), branch_name)
+    }
+
     #[inline(always)]
     pub fn last_by_branch(&self, branch_name: BranchName) -> Option<T> {
         alt!(self.is_empty(), return None);
@@ -206,6 +246,15 @@ impl<T: ValueEnDe> VecxVs<T> {
         }
     }
 
+    /// Same as `iter_by_branch`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch(&self, branch_name: BranchName) -> VecxVsTryIter<'_, T> {
+        VecxVsTryIter {
+            iter: self.inner.try_iter_by_branch(branch_name),
+        }
+    }
+
     #[inline(always)]
     pub fn get_by_branch_version(
         &self,
@@ -220,6 +269,22 @@ impl<T: ValueEnDe> VecxVs<T> {
         )
     }
 
+    /// Same as `get_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be reported as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_get_by_branch_version(
+        &self,
+        idx: usize,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> VsdbResult<Option<T>> {
+        self.inner.try_get_by_branch_version(
+            &(idx as u64).to_be_bytes(),
+            branch_name,
+            version_name,
+        )
+    }
+
     #[inline(always)]
     pub fn last_by_branch_version(
         &self,
@@ -267,12 +332,34 @@ impl<T: ValueEnDe> VecxVs<T> {
             iter: self.inner.iter_by_branch_version(branch_name, version_name),
         }
     }
+
+    /// Same as `iter_by_branch_version`, but a broken value(e.g. tampered on disk)
+    /// will be yielded as an error instead of a panic.
+    #[inline(always)]
+    pub fn try_iter_by_branch_version(
+        &self,
+        branch_name: BranchName,
+        version_name: VersionName,
+    ) -> VecxVsTryIter<'_, T> {
+        VecxVsTryIter {
+            iter: self
+                .inner
+                .try_iter_by_branch_version(branch_name, version_name),
+        }
+    }
 }
 
 impl<T: ValueEnDe> VsMgmt for VecxVs<T> {
     crate::impl_vs_methods!();
 }
 
+impl<T: ValueEnDe + GcMark> GcMark for VecxVs<T> {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark(marks);
+    }
+}
+
 pub struct VecxVsIter<'a, T: ValueEnDe> {
     iter: MapxOrdRawKeyVsIter<'a, T>,
 }
@@ -290,6 +377,40 @@ impl<'a, T: ValueEnDe> DoubleEndedIterator for VecxVsIter<'a, T> {
     }
 }
 
+pub struct VecxVsTryIter<'a, T: ValueEnDe> {
+    iter: MapxOrdRawKeyVsTryIter<'a, T>,
+}
+
+impl<'a, T: ValueEnDe> Iterator for VecxVsTryIter<'a, T> {
+    type Item = VsdbResult<T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|v| v.map(|v| v.1))
+    }
+}
+
+impl<'a, T: ValueEnDe> DoubleEndedIterator for VecxVsTryIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|v| v.map(|v| v.1))
+    }
+}
+
+pub struct VecxVsIterMut<'a, T: ValueEnDe> {
+    iter: MapxOrdRawKeyVsIterMut<'a, T>,
+}
+
+impl<'a, T: ValueEnDe> Iterator for VecxVsIterMut<'a, T> {
+    type Item = ValueIterMut<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|v| v.1)
+    }
+}
+
+impl<'a, T: ValueEnDe> DoubleEndedIterator for VecxVsIterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|v| v.1)
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct ValueMut<'a, V: ValueEnDe> {
     hdr: &'a mut MapxOrdRawKeyVs<V>,
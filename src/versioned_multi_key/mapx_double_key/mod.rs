@@ -7,7 +7,7 @@ use crate::{
         RawValue,
     },
     versioned_multi_key::mapx_raw::MapxRawMkVs,
-    BranchName, VersionName, VsMgmt,
+    BranchName, Entry, EntryHdr, GcMark, GcMarks, VersionName, VsMgmt,
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -65,12 +65,17 @@ where
         self.get(key).map(move |v| ValueMut::new(self, key, v))
     }
 
+    #[inline(always)]
+    pub fn entry(&mut self, key: (K1, K2)) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
     #[inline(always)]
     pub fn entry_ref<'a>(
         &'a mut self,
         key: &'a (&'a K1, &'a K2),
-    ) -> Entry<'a, K1, K2, V> {
-        Entry { key, hdr: self }
+    ) -> EntryRef<'a, K1, K2, V> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
@@ -372,6 +377,38 @@ where
     crate::impl_vs_methods!();
 }
 
+impl<K1, K2, V> EntryHdr for MapxDkVs<K1, K2, V>
+where
+    K1: KeyEnDe,
+    K2: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Key = (K1, K2);
+    type Value = V;
+
+    fn entry_get(&self, key: &(K1, K2)) -> Option<V> {
+        self.get(&(&key.0, &key.1))
+    }
+
+    fn entry_set(&mut self, key: &(K1, K2), value: &V) {
+        pnk!(self.insert_ref(&(&key.0, &key.1), value));
+    }
+
+    fn entry_remove(&mut self, key: &(K1, K2)) {
+        pnk!(self.remove(&(&key.0, Some(&key.1))));
+    }
+}
+
+impl<K1, K2, V> GcMark for MapxDkVs<K1, K2, V>
+where
+    V: ValueEnDe + GcMark,
+{
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark_with_typed_value::<V>(marks);
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct ValueMut<'a, K1, K2, V>
 where
@@ -433,7 +470,7 @@ where
     }
 }
 
-pub struct Entry<'a, K1, K2, V>
+pub struct EntryRef<'a, K1, K2, V>
 where
     K1: KeyEnDe,
     K2: KeyEnDe,
@@ -443,7 +480,7 @@ where
     key: &'a (&'a K1, &'a K2),
 }
 
-impl<'a, K1, K2, V> Entry<'a, K1, K2, V>
+impl<'a, K1, K2, V> EntryRef<'a, K1, K2, V>
 where
     K1: KeyEnDe,
     K2: KeyEnDe,
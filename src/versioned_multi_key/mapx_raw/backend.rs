@@ -5,7 +5,11 @@ use crate::{
     },
     basic_multi_key::{mapx_raw::MapxRawMk, mapx_rawkey::MapxRawKeyMk},
     common::{
-        ende::encode_optioned_bytes, BranchID, BranchName, BranchNameOwned, RawValue,
+        ende::encode_optioned_bytes,
+        ensure_writable,
+        error::{VsdbError, VsdbResult},
+        gc::{GcMark, GcMarks},
+        vsdb_compact_after_prune, BranchID, BranchName, BranchNameOwned, RawValue,
         VersionID, VersionName, VersionNameOwned, INITIAL_BRANCH_ID,
         INITIAL_BRANCH_NAME, RESERVED_VERSION_NUM_DEFAULT, VSDB,
     },
@@ -39,6 +43,25 @@ pub(super) struct MapxRawMkVs {
 ////////////////////////////////////////////////////////////////////////////////////
 
 impl MapxRawMkVs {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub(super) unsafe fn shadow(&self) -> Self {
+        Self {
+            default_branch: self.default_branch,
+            key_size: self.key_size,
+            branch_name_to_branch_id: self.branch_name_to_branch_id.shadow(),
+            version_name_to_version_id: self.version_name_to_version_id.shadow(),
+            branch_id_to_branch_name: self.branch_id_to_branch_name.shadow(),
+            version_id_to_version_name: self.version_id_to_version_name.shadow(),
+            branch_to_its_versions: self.branch_to_its_versions.shadow(),
+            version_to_change_set: self.version_to_change_set.shadow(),
+            layered_kv: self.layered_kv.shadow(),
+        }
+    }
+
     #[inline(always)]
     pub(super) fn new(key_size: usize) -> Self {
         let mut ret = Self {
@@ -59,12 +82,15 @@ impl MapxRawMkVs {
     #[inline(always)]
     fn init(&mut self) {
         self.default_branch = INITIAL_BRANCH_ID;
-        self.branch_name_to_branch_id
-            .insert_ref(INITIAL_BRANCH_NAME.0, &INITIAL_BRANCH_ID);
-        self.branch_id_to_branch_name
-            .insert_ref(&INITIAL_BRANCH_ID, INITIAL_BRANCH_NAME.0);
-        self.branch_to_its_versions
-            .insert(INITIAL_BRANCH_ID, MapxOrd::new());
+        pnk!(self
+            .branch_name_to_branch_id
+            .insert_ref(INITIAL_BRANCH_NAME.0, &INITIAL_BRANCH_ID));
+        pnk!(self
+            .branch_id_to_branch_name
+            .insert_ref(&INITIAL_BRANCH_ID, INITIAL_BRANCH_NAME.0));
+        pnk!(self
+            .branch_to_its_versions
+            .insert(INITIAL_BRANCH_ID, MapxOrd::new()));
     }
 
     #[inline(always)]
@@ -147,6 +173,8 @@ impl MapxRawMkVs {
         branch_id: BranchID,
         version_id: VersionID,
     ) -> Result<Option<RawValue>> {
+        ensure_writable().c(d!())?;
+
         if key.len() < self.key_size {
             return self
                 .batch_remove_by_branch_version(key, value, version_id)
@@ -180,12 +208,14 @@ impl MapxRawMkVs {
         value: Option<&[u8]>,
         version_id: VersionID,
     ) -> Result<Option<RawValue>> {
+        ensure_writable().c(d!())?;
+
         let mut hdr = self.version_to_change_set.get(&version_id).c(d!())?;
-        let hdr_shadow = hdr; // hack~
+        let hdr_shadow = unsafe { hdr.shadow() };
         let mut op = |k: &[&[u8]], _: &[u8]| hdr.insert(k, &[]).c(d!()).map(|_| ());
         hdr_shadow.iter_op_with_key_prefix(&mut op, key).c(d!())?;
 
-        let layered_kv_shadow = self.layered_kv; // hack~
+        let layered_kv_shadow = unsafe { self.layered_kv.shadow() };
         let mut op = |k: &[&[u8]], _: &MapxOrd<VersionID, Option<RawValue>>| {
             self.layered_kv
                 .entry_ref(k)
@@ -340,46 +370,49 @@ impl MapxRawMkVs {
 
     #[inline(always)]
     pub(super) fn clear(&mut self) {
-        self.branch_name_to_branch_id.clear();
-        self.version_name_to_version_id.clear();
-        self.branch_id_to_branch_name.clear();
-        self.version_id_to_version_name.clear();
-        self.branch_to_its_versions.clear();
-        self.version_to_change_set.clear();
-        self.layered_kv.clear();
+        pnk!(self.branch_name_to_branch_id.clear());
+        pnk!(self.version_name_to_version_id.clear());
+        pnk!(self.branch_id_to_branch_name.clear());
+        pnk!(self.version_id_to_version_name.clear());
+        pnk!(self.branch_to_its_versions.clear());
+        pnk!(self.version_to_change_set.clear());
+        pnk!(self.layered_kv.clear());
 
         self.init();
     }
 
     #[inline(always)]
-    pub(super) fn version_create(&mut self, version_name: &[u8]) -> Result<()> {
+    pub(super) fn version_create(&mut self, version_name: &[u8]) -> VsdbResult<()> {
         self.version_create_by_branch(version_name, self.branch_get_default())
-            .c(d!())
     }
 
     pub(super) fn version_create_by_branch(
         &mut self,
         version_name: &[u8],
         branch_id: BranchID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+
         if self.version_name_to_version_id.get(version_name).is_some() {
-            return Err(eg!("version already exists"));
+            return Err(VsdbError::VersionExists {
+                version: version_name.to_vec(),
+            });
         }
 
-        let mut vers = self
-            .branch_to_its_versions
-            .get_mut(&branch_id)
-            .c(d!("branch not found"))?;
+        if !self.branch_to_its_versions.contains_key(&branch_id) {
+            return Err(self.branch_not_found(branch_id));
+        }
+        let mut vers = self.branch_to_its_versions.get_mut(&branch_id).unwrap();
 
         let version_id = VSDB.alloc_version_id();
-        vers.insert(version_id, ());
+        vers.insert(version_id, ())?;
 
         self.version_name_to_version_id
-            .insert_ref(version_name, &version_id);
+            .insert_ref(version_name, &version_id)?;
         self.version_id_to_version_name
-            .insert_ref(&version_id, version_name);
+            .insert_ref(&version_id, version_name)?;
         self.version_to_change_set
-            .insert(version_id, MapxRawMk::new(self.key_size));
+            .insert(version_id, MapxRawMk::new(self.key_size))?;
 
         Ok(())
     }
@@ -415,13 +448,16 @@ impl MapxRawMkVs {
 
     #[inline(always)]
     pub(super) fn version_pop_by_branch(&mut self, branch_id: BranchID) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         let mut vers = self
             .branch_to_its_versions
             .get(&branch_id)
             .c(d!("branch not found"))?;
-        let vers_shadow = vers;
+        let vers_shadow = unsafe { vers.shadow() };
         if let Some((version_id, _)) = vers_shadow.iter().last() {
             vers.remove(&version_id)
+                .c(d!())?
                 .c(d!("version is not on this branch"))
         } else {
             Ok(())
@@ -432,28 +468,26 @@ impl MapxRawMkVs {
     pub(super) unsafe fn version_rebase(
         &mut self,
         base_version: VersionID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.version_rebase_by_branch(base_version, self.branch_get_default())
-            .c(d!())
     }
     pub(super) unsafe fn version_rebase_by_branch(
         &mut self,
         base_version: VersionID,
         branch_id: BranchID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+
         let mut vers_hdr = self
             .branch_to_its_versions
             .get(&branch_id)
-            .c(d!("branch not found"))?;
+            .ok_or_else(|| self.branch_not_found(branch_id))?;
         let mut vers = vers_hdr.range(base_version..).map(|(ver, _)| ver);
 
-        if let Some(ver) = vers.next() {
-            if base_version != ver {
-                return Err(eg!("base version is not on this branch"));
-            }
-        } else {
-            return Err(eg!("base version is not on this branch"));
-        };
+        // the base version must be on this branch
+        if vers.next() != Some(base_version) {
+            return Err(self.version_not_found(base_version));
+        }
 
         let mut base_ver_chg_set =
             self.version_to_change_set.get(&base_version).c(d!())?;
@@ -462,30 +496,28 @@ impl MapxRawMkVs {
         for verid in vers_to_be_merged.iter() {
             let mut chgset_ops = |k: &[&[u8]], _: &[u8]| {
                 base_ver_chg_set.insert(k, &[]).c(d!())?;
-                self.layered_kv
-                    .get(k)
-                    .c(d!())
-                    .and_then(|mut hdr| {
-                        hdr.remove(verid)
-                            .c(d!())
-                            .map(|v| hdr.insert(base_version, v))
-                    })
-                    .map(|_| ())
+                let mut hdr = self.layered_kv.get(k).c(d!())?;
+                let v = hdr.remove(verid).c(d!())?.c(d!())?;
+                hdr.insert(base_version, v).c(d!()).map(|_| ())
             };
 
             self.version_to_change_set
                 .remove(verid)
                 .c(d!())?
+                .c(d!())?
                 .iter_op(&mut chgset_ops)
                 .c(d!())?;
 
-            self.version_id_to_version_name
+            let vername = self
+                .version_id_to_version_name
                 .remove(verid)
-                .c(d!())
-                .and_then(|vername| {
-                    self.version_name_to_version_id.remove(&vername).c(d!())
-                })
-                .and_then(|_| vers_hdr.remove(verid).c(d!()))?;
+                .c(d!())?
+                .c(d!())?;
+            self.version_name_to_version_id
+                .remove(&vername)
+                .c(d!())?
+                .c(d!())?;
+            vers_hdr.remove(verid).c(d!())?.c(d!())?;
         }
 
         Ok(())
@@ -546,61 +578,85 @@ impl MapxRawMkVs {
         &mut self,
         version_id: VersionID,
     ) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         let mut chgset_ops = |key: &[&[u8]], _: &[u8]| {
             self.layered_kv
                 .get(key)
                 .c(d!())?
                 .remove(&version_id)
+                .c(d!())?
                 .c(d!())
                 .map(|_| ())
         };
-        let chgset = self.version_to_change_set.remove(&version_id).c(d!())?;
+        let mut chgset = self
+            .version_to_change_set
+            .remove(&version_id)
+            .c(d!())?
+            .c(d!())?;
         chgset.iter_op(&mut chgset_ops).c(d!())?;
+        chgset.clear().c(d!())?;
 
-        self.branch_to_its_versions
-            .iter()
-            .for_each(|(_, mut vers)| {
-                vers.remove(&version_id);
-            });
+        for (_, mut vers) in self.branch_to_its_versions.iter() {
+            vers.remove(&version_id).c(d!())?;
+        }
 
-        self.version_id_to_version_name
+        let vername = self
+            .version_id_to_version_name
             .remove(&version_id)
+            .c(d!())?
+            .c(d!())?;
+        self.version_name_to_version_id
+            .remove(&vername)
+            .c(d!())?
             .c(d!())
-            .and_then(|vername| self.version_name_to_version_id.remove(&vername).c(d!()))
             .map(|_| ())
     }
 
     // clean up all orphaned versions in the global scope
     #[inline(always)]
     pub(super) fn version_clean_up_globally(&mut self) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         let valid_vers = self
             .branch_to_its_versions
             .iter()
-            .flat_map(|(_, vers)| vers.iter().map(|(ver, _)| ver))
+            .flat_map(|(_, vers)| vers.iter().map(|(ver, _)| ver).collect::<Vec<_>>())
             .collect::<HashSet<_>>();
 
-        for (ver, chgset) in self
+        let orphaned_vers = self
             .version_to_change_set
             .iter()
             .filter(|(ver, _)| !valid_vers.contains(ver))
-        {
+            .collect::<Vec<_>>();
+
+        for (ver, chgset) in orphaned_vers {
             let mut chgset_ops = |key: &[&[u8]], _: &[u8]| {
                 self.layered_kv
                     .get(key)
                     .c(d!())?
                     .remove(&ver)
+                    .c(d!())?
                     .c(d!())
                     .map(|_| ())
             };
             chgset.iter_op(&mut chgset_ops).c(d!())?;
 
-            self.version_id_to_version_name
+            let vername = self
+                .version_id_to_version_name
                 .remove(&ver)
-                .c(d!())
-                .and_then(|vername| {
-                    self.version_name_to_version_id.remove(&vername).c(d!())
-                })
-                .and_then(|_| self.version_to_change_set.remove(&ver).c(d!()))?;
+                .c(d!())?
+                .c(d!())?;
+            self.version_name_to_version_id
+                .remove(&vername)
+                .c(d!())?
+                .c(d!())?;
+            self.version_to_change_set
+                .remove(&ver)
+                .c(d!())?
+                .c(d!())?
+                .clear()
+                .c(d!())?;
         }
 
         Ok(())
@@ -612,14 +668,13 @@ impl MapxRawMkVs {
         branch_name: &[u8],
         version_name: &[u8],
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.branch_create_by_base_branch(
             branch_name,
             version_name,
             self.branch_get_default(),
             force,
         )
-        .c(d!())
     }
 
     #[inline(always)]
@@ -629,15 +684,17 @@ impl MapxRawMkVs {
         version_name: &[u8],
         base_branch_id: BranchID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if self.version_name_to_version_id.contains_key(version_name) {
-            return Err(eg!("this version already exists"));
+            return Err(VsdbError::VersionExists {
+                version: version_name.to_vec(),
+            });
         }
 
         let base_version_id = self
             .branch_to_its_versions
             .get(&base_branch_id)
-            .c(d!("base branch not found"))?
+            .ok_or_else(|| self.branch_not_found(base_branch_id))?
             .last()
             .map(|(version_id, _)| version_id);
 
@@ -649,7 +706,6 @@ impl MapxRawMkVs {
                 base_version_id,
                 force,
             )
-            .c(d!())
         }
     }
 
@@ -661,9 +717,11 @@ impl MapxRawMkVs {
         base_branch_id: BranchID,
         base_version_id: VersionID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         if self.version_name_to_version_id.contains_key(version_name) {
-            return Err(eg!("this version already exists"));
+            return Err(VsdbError::VersionExists {
+                version: version_name.to_vec(),
+            });
         }
 
         unsafe {
@@ -674,7 +732,6 @@ impl MapxRawMkVs {
                 Some(base_version_id),
                 force,
             )
-            .c(d!())
         }
     }
 
@@ -683,13 +740,12 @@ impl MapxRawMkVs {
         &mut self,
         branch_name: &[u8],
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.branch_create_by_base_branch_without_new_version(
             branch_name,
             self.branch_get_default(),
             force,
         )
-        .c(d!())
     }
 
     #[inline(always)]
@@ -698,11 +754,11 @@ impl MapxRawMkVs {
         branch_name: &[u8],
         base_branch_id: BranchID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         let base_version_id = self
             .branch_to_its_versions
             .get(&base_branch_id)
-            .c(d!("base branch not found"))?
+            .ok_or_else(|| self.branch_not_found(base_branch_id))?
             .last()
             .map(|(version_id, _)| version_id);
 
@@ -713,7 +769,6 @@ impl MapxRawMkVs {
             base_version_id,
             force,
         )
-        .c(d!())
     }
 
     #[inline(always)]
@@ -723,7 +778,7 @@ impl MapxRawMkVs {
         base_branch_id: BranchID,
         base_version_id: VersionID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.do_branch_create_by_base_branch_version(
             branch_name,
             None,
@@ -731,7 +786,6 @@ impl MapxRawMkVs {
             Some(base_version_id),
             force,
         )
-        .c(d!())
     }
 
     unsafe fn do_branch_create_by_base_branch_version(
@@ -741,30 +795,34 @@ impl MapxRawMkVs {
         base_branch_id: BranchID,
         base_version_id: Option<VersionID>,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+
         if force {
             if let Some(brid) = self.branch_name_to_branch_id.get(branch_name) {
-                self.branch_remove(brid).c(d!())?;
+                self.branch_remove(brid)?;
             }
         }
 
         if self.branch_name_to_branch_id.contains_key(branch_name) {
-            return Err(eg!("branch already exists"));
+            return Err(VsdbError::BranchExists {
+                branch: branch_name.to_vec(),
+            });
         }
 
         let vers = self
             .branch_to_its_versions
             .get(&base_branch_id)
-            .c(d!("base branch not exist"))?;
+            .ok_or_else(|| self.branch_not_found(base_branch_id))?;
 
         let vers_copied = if let Some(bv) = base_version_id {
             if !vers.contains_key(&bv) {
-                return Err(eg!("version is not on the base branch"));
+                return Err(self.version_not_found(bv));
             }
-            vers.range(..=bv).fold(MapxOrd::new(), |mut acc, (k, v)| {
-                acc.insert(k, v);
-                acc
-            })
+            vers.range(..=bv)
+                .try_fold(MapxOrd::new(), |mut acc, (k, v)| {
+                    acc.insert(k, v).map(|_| acc)
+                })?
         } else {
             MapxOrd::new()
         };
@@ -772,13 +830,13 @@ impl MapxRawMkVs {
         let branch_id = VSDB.alloc_branch_id();
 
         self.branch_name_to_branch_id
-            .insert_ref(branch_name, &branch_id);
+            .insert_ref(branch_name, &branch_id)?;
         self.branch_id_to_branch_name
-            .insert_ref(&branch_id, branch_name);
-        self.branch_to_its_versions.insert(branch_id, vers_copied);
+            .insert_ref(&branch_id, branch_name)?;
+        self.branch_to_its_versions.insert(branch_id, vers_copied)?;
 
         if let Some(vername) = version_name {
-            self.version_create_by_branch(vername, branch_id).c(d!())?;
+            self.version_create_by_branch(vername, branch_id)?;
         }
 
         Ok(())
@@ -804,12 +862,19 @@ impl MapxRawMkVs {
 
     #[inline(always)]
     pub(super) fn branch_remove(&mut self, branch_id: BranchID) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         self.branch_truncate(branch_id).c(d!())?;
 
-        self.branch_id_to_branch_name
+        let brname = self
+            .branch_id_to_branch_name
             .remove(&branch_id)
-            .c(d!())
-            .and_then(|brname| self.branch_name_to_branch_id.remove(&brname).c(d!()))?;
+            .c(d!())?
+            .c(d!())?;
+        self.branch_name_to_branch_id
+            .remove(&brname)
+            .c(d!())?
+            .c(d!())?;
 
         self.branch_to_its_versions
             .remove(&branch_id)
@@ -819,12 +884,14 @@ impl MapxRawMkVs {
 
     #[inline(always)]
     pub(super) fn branch_keep_only(&mut self, branch_ids: &[BranchID]) -> Result<()> {
-        for brid in self
+        let brids_to_remove = self
             .branch_id_to_branch_name
             .iter()
             .map(|(brid, _)| brid)
             .filter(|brid| !branch_ids.contains(brid))
-        {
+            .collect::<Vec<_>>();
+
+        for brid in brids_to_remove {
             self.branch_remove(brid).c(d!())?;
         }
         self.version_clean_up_globally().c(d!())
@@ -832,9 +899,10 @@ impl MapxRawMkVs {
 
     #[inline(always)]
     pub(super) fn branch_truncate(&mut self, branch_id: BranchID) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         if let Some(mut vers) = self.branch_to_its_versions.get(&branch_id) {
-            vers.clear();
-            Ok(())
+            vers.clear().c(d!())
         } else {
             Err(eg!("branch not found: {}", branch_id))
         }
@@ -845,8 +913,10 @@ impl MapxRawMkVs {
         branch_id: BranchID,
         last_version_id: VersionID,
     ) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         if let Some(mut vers) = self.branch_to_its_versions.get(&branch_id) {
-            let vers_shadow = vers;
+            let vers_shadow = unsafe { vers.shadow() };
             for (version_id, _) in vers_shadow.range((1 + last_version_id)..).rev() {
                 vers.remove(&version_id)
                     .c(d!("version is not on this branch"))?;
@@ -867,7 +937,7 @@ impl MapxRawMkVs {
         &mut self,
         branch_id: BranchID,
         target_branch_id: BranchID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         unsafe { self.do_branch_merge_to(branch_id, target_branch_id, false) }
     }
 
@@ -876,7 +946,7 @@ impl MapxRawMkVs {
         &mut self,
         branch_id: BranchID,
         target_branch_id: BranchID,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.do_branch_merge_to(branch_id, target_branch_id, true)
     }
 
@@ -885,20 +955,25 @@ impl MapxRawMkVs {
         branch_id: BranchID,
         target_branch_id: BranchID,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
+        ensure_writable()?;
+
         let vers = self
             .branch_to_its_versions
             .get(&branch_id)
-            .c(d!("branch not found"))?;
+            .ok_or_else(|| self.branch_not_found(branch_id))?;
         let mut target_vers = self
             .branch_to_its_versions
             .get(&target_branch_id)
-            .c(d!("target branch not found"))?;
+            .ok_or_else(|| self.branch_not_found(target_branch_id))?;
 
         if !force {
             if let Some((ver, _)) = target_vers.last() {
                 if !vers.contains_key(&ver) {
-                    return Err(eg!("unable to merge safely"));
+                    return Err(VsdbError::UnsafeMerge {
+                        branch: self.branch_name_of(branch_id),
+                        target_branch: self.branch_name_of(target_branch_id),
+                    });
                 }
             }
         }
@@ -908,9 +983,9 @@ impl MapxRawMkVs {
             .zip(target_vers.iter())
             .find(|(a, b)| a.0 != b.0)
         {
-            vers.range(fork_point.0.0..).for_each(|(ver, _)| {
-                target_vers.insert(ver, ());
-            });
+            for (ver, _) in vers.range(fork_point.0.0..) {
+                target_vers.insert(ver, ())?;
+            }
         } else if let Some((latest_ver, _)) = vers.last() {
             if let Some((target_latest_ver, _)) = target_vers.last() {
                 match latest_ver.cmp(&target_latest_ver) {
@@ -918,18 +993,16 @@ impl MapxRawMkVs {
                         return Ok(());
                     }
                     Ordering::Greater => {
-                        vers.range((1 + target_latest_ver)..)
-                            .map(|(ver, _)| ver)
-                            .for_each(|ver| {
-                                target_vers.insert(ver, ());
-                            });
+                        for (ver, _) in vers.range((1 + target_latest_ver)..) {
+                            target_vers.insert(ver, ())?;
+                        }
                     }
                     _ => {}
                 }
             } else {
-                vers.iter().for_each(|(ver, _)| {
-                    target_vers.insert(ver, ());
-                });
+                for (ver, _) in vers.iter() {
+                    target_vers.insert(ver, ())?;
+                }
             }
         } else {
             return Ok(());
@@ -993,6 +1066,8 @@ impl MapxRawMkVs {
         branch_1: &[u8],
         branch_2: &[u8],
     ) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         let brid_1 = self.branch_name_to_branch_id.get(branch_1).c(d!())?;
         let brid_2 = self.branch_name_to_branch_id.get(branch_2).c(d!())?;
 
@@ -1019,6 +1094,26 @@ impl MapxRawMkVs {
         Ok(())
     }
 
+    fn branch_name_of(&self, branch_id: BranchID) -> Vec<u8> {
+        self.branch_id_to_branch_name
+            .get(&branch_id)
+            .map(|br| br.to_vec())
+            .unwrap_or_else(|| branch_id.to_string().into_bytes())
+    }
+
+    fn branch_not_found(&self, branch_id: BranchID) -> VsdbError {
+        VsdbError::branch_not_found(self.branch_name_of(branch_id))
+    }
+
+    fn version_not_found(&self, version_id: VersionID) -> VsdbError {
+        let vername = self
+            .version_id_to_version_name
+            .get(&version_id)
+            .map(|ver| ver.to_vec())
+            .unwrap_or_else(|| version_id.to_string().into_bytes());
+        VsdbError::version_not_found(vername)
+    }
+
     #[inline(always)]
     pub(super) fn branch_get_id_by_name(
         &self,
@@ -1029,6 +1124,8 @@ impl MapxRawMkVs {
 
     #[inline(always)]
     pub(super) fn prune(&mut self, reserved_ver_num: Option<usize>) -> Result<()> {
+        ensure_writable().c(d!())?;
+
         self.version_clean_up_globally().c(d!())?;
 
         let reserved_ver_num = reserved_ver_num.unwrap_or(RESERVED_VERSION_NUM_DEFAULT);
@@ -1036,11 +1133,15 @@ impl MapxRawMkVs {
             return Err(eg!("reserved version number should NOT be zero"));
         }
 
-        let mut br_vers = self
+        let br_vers_hdrs = self
             .branch_to_its_versions
             .iter()
             .filter(|(_, vers)| !vers.is_empty())
-            .map(|(_, vers)| vers.iter())
+            .map(|(_, vers)| vers)
+            .collect::<Vec<_>>();
+        let mut br_vers = br_vers_hdrs
+            .iter()
+            .map(|vers| vers.iter())
             .collect::<Vec<_>>();
         alt!(br_vers.is_empty(), return Ok(()));
 
@@ -1074,6 +1175,10 @@ impl MapxRawMkVs {
         let mut rewrite_ver_chgset =
             self.version_to_change_set.get(rewrite_ver).c(d!())?;
 
+        // instances touched by the pruning, they will be compacted at the end
+        let compact = vsdb_compact_after_prune();
+        let mut touched = GcMarks::default();
+
         for (_, mut vers) in self
             .branch_to_its_versions
             .iter()
@@ -1082,31 +1187,71 @@ impl MapxRawMkVs {
             for ver in vers_to_be_merged.iter() {
                 vers.remove(ver).c(d!())?;
             }
+            alt!(compact, vers.gc_mark(&mut touched));
         }
 
         for ver in vers_to_be_merged.iter() {
-            self.version_id_to_version_name
+            let vername = self
+                .version_id_to_version_name
                 .remove(ver)
-                .c(d!())
-                .and_then(|vername| {
-                    self.version_name_to_version_id.remove(&vername).c(d!())
-                })?;
+                .c(d!())?
+                .c(d!())?;
+            self.version_name_to_version_id
+                .remove(&vername)
+                .c(d!())?
+                .c(d!())?;
             let mut chgset_ops = |k: &[&[u8]], _: &[u8]| {
                 let mut k_vers = self.layered_kv.get(k).c(d!())?;
-                let value = k_vers.remove(ver).c(d!())?;
+                let value = k_vers.remove(ver).c(d!())?.c(d!())?;
                 if k_vers.range(..=rewrite_ver).next().is_none() {
                     assert!(rewrite_ver_chgset.insert(k, &[]).c(d!())?.is_none());
-                    assert!(k_vers.insert_ref(rewrite_ver, &value).is_none());
+                    assert!(k_vers.insert_ref(rewrite_ver, &value).c(d!())?.is_none());
                 }
+                alt!(compact, k_vers.gc_mark(&mut touched));
                 Ok(())
             };
-            self.version_to_change_set
-                .remove(ver)
-                .c(d!())?
-                .iter_op(&mut chgset_ops)
-                .c(d!())?;
+            let mut chgset = self.version_to_change_set.remove(ver).c(d!())?.c(d!())?;
+            chgset.iter_op(&mut chgset_ops).c(d!())?;
+            chgset.clear().c(d!())?;
+            alt!(compact, chgset.gc_mark(&mut touched));
+        }
+
+        if compact {
+            self.version_id_to_version_name.gc_mark(&mut touched);
+            self.version_name_to_version_id.gc_mark(&mut touched);
+            self.version_to_change_set.gc_mark_instance(&mut touched);
+            rewrite_ver_chgset.gc_mark(&mut touched);
+            touched.compact();
         }
 
         Ok(())
     }
+
+    // return `false` if the instance has been marked before
+    pub(super) fn gc_mark(&self, marks: &mut GcMarks) -> bool {
+        if self.layered_kv.gc_is_marked(marks) {
+            return false;
+        }
+
+        self.branch_name_to_branch_id.gc_mark(marks);
+        self.version_name_to_version_id.gc_mark(marks);
+        self.branch_id_to_branch_name.gc_mark(marks);
+        self.version_id_to_version_name.gc_mark(marks);
+        self.branch_to_its_versions.gc_mark(marks);
+        self.version_to_change_set.gc_mark(marks);
+        self.layered_kv.gc_mark(marks);
+
+        true
+    }
+
+    // all values that have ever been written, on all branches and versions
+    pub(super) fn for_each_value_globally<F>(&self, mut op: F)
+    where
+        F: FnMut(RawValue),
+    {
+        pnk!(self.layered_kv.iter_op(&mut |_, vers| {
+            vers.values().flatten().for_each(&mut op);
+            Ok(())
+        }));
+    }
 }
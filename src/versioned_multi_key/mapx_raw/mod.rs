@@ -8,7 +8,13 @@ mod backend;
 mod test;
 
 use crate::{
-    common::{BranchName, ParentBranchName, RawValue, VersionName},
+    common::{
+        ende::ValueEnDe,
+        entry::{Entry, EntryHdr},
+        error::{VsdbError, VsdbResult},
+        gc::{GcMark, GcMarks},
+        BranchName, ParentBranchName, RawKey, RawValue, VersionName,
+    },
     BranchNameOwned, VersionNameOwned, VsMgmt,
 };
 use ruc::*;
@@ -24,6 +30,17 @@ pub struct MapxRawMkVs {
 }
 
 impl MapxRawMkVs {
+    /// # Safety
+    ///
+    /// This API breaks the semantic safety guarantees,
+    /// but it is safe to use in a race-free environment.
+    #[inline(always)]
+    pub unsafe fn shadow(&self) -> Self {
+        Self {
+            inner: self.inner.shadow(),
+        }
+    }
+
     #[inline(always)]
     #[allow(missing_docs)]
     pub fn new(key_size: usize) -> Self {
@@ -74,8 +91,13 @@ impl MapxRawMkVs {
     }
 
     #[inline(always)]
-    pub fn entry_ref<'a>(&'a mut self, key: &'a [&'a [u8]]) -> Entry<'a> {
-        Entry { key, hdr: self }
+    pub fn entry(&mut self, key: Vec<RawKey>) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
+    #[inline(always)]
+    pub fn entry_ref<'a>(&'a mut self, key: &'a [&'a [u8]]) -> EntryRef<'a> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
@@ -211,12 +233,57 @@ impl MapxRawMkVs {
             )
             .c(d!())
     }
+
+    // return `false` if the instance has been marked before
+    #[inline(always)]
+    pub(crate) fn gc_mark_instance(&self, marks: &mut GcMarks) -> bool {
+        self.inner.gc_mark(marks)
+    }
+
+    // mark the instance itself, and those nested in the typed values
+    pub(crate) fn gc_mark_with_typed_value<V>(&self, marks: &mut GcMarks)
+    where
+        V: ValueEnDe + GcMark,
+    {
+        if self.gc_mark_instance(marks) && !V::gc_nope() {
+            self.inner.for_each_value_globally(|v| {
+                pnk!(<V as ValueEnDe>::decode(&v)).gc_mark(marks);
+            });
+        }
+    }
+}
+
+impl EntryHdr for MapxRawMkVs {
+    type Key = Vec<RawKey>;
+    type Value = RawValue;
+
+    fn entry_get(&self, key: &Vec<RawKey>) -> Option<RawValue> {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        self.get(&key)
+    }
+
+    fn entry_set(&mut self, key: &Vec<RawKey>, value: &RawValue) {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        pnk!(self.insert(&key, value));
+    }
+
+    fn entry_remove(&mut self, key: &Vec<RawKey>) {
+        let key = key.iter().map(|k| &k[..]).collect::<Vec<_>>();
+        pnk!(self.remove(&key));
+    }
+}
+
+impl GcMark for MapxRawMkVs {
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.gc_mark_instance(marks);
+    }
 }
 
 impl VsMgmt for MapxRawMkVs {
     #[inline(always)]
-    fn version_create(&mut self, version_name: VersionName) -> Result<()> {
-        self.inner.version_create(version_name.0).c(d!())
+    fn version_create(&mut self, version_name: VersionName) -> VsdbResult<()> {
+        self.inner.version_create(version_name.0)
     }
 
     #[inline(always)]
@@ -224,15 +291,11 @@ impl VsMgmt for MapxRawMkVs {
         &mut self,
         version_name: VersionName,
         branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|br_id| {
-                self.inner
-                    .version_create_by_branch(version_name.0, br_id)
-                    .c(d!())
-            })
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|br_id| self.inner.version_create_by_branch(version_name.0, br_id))
     }
 
     #[inline(always)]
@@ -260,24 +323,26 @@ impl VsMgmt for MapxRawMkVs {
     }
 
     #[inline(always)]
-    fn version_pop(&mut self) -> Result<()> {
-        self.inner.version_pop().c(d!())
+    fn version_pop(&mut self) -> VsdbResult<()> {
+        self.inner.version_pop().map_err(From::from)
     }
 
     #[inline(always)]
-    fn version_pop_by_branch(&mut self, branch_name: BranchName) -> Result<()> {
+    fn version_pop_by_branch(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|br_id| self.inner.version_pop_by_branch(br_id).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|br_id| {
+                self.inner.version_pop_by_branch(br_id).map_err(From::from)
+            })
     }
 
     #[inline(always)]
-    unsafe fn version_rebase(&mut self, base_version: VersionName) -> Result<()> {
+    unsafe fn version_rebase(&mut self, base_version: VersionName) -> VsdbResult<()> {
         self.inner
             .version_get_id_by_name(base_version)
-            .c(d!())
-            .and_then(|bv| self.inner.version_rebase(bv).c(d!()))
+            .ok_or_else(|| VsdbError::version_not_found(base_version.0))
+            .and_then(|bv| self.inner.version_rebase(bv))
     }
 
     #[inline(always)]
@@ -285,10 +350,16 @@ impl VsMgmt for MapxRawMkVs {
         &mut self,
         base_version: VersionName,
         branch_name: BranchName,
-    ) -> Result<()> {
-        let bv = self.inner.version_get_id_by_name(base_version).c(d!())?;
-        let brid = self.inner.branch_get_id_by_name(branch_name).c(d!())?;
-        self.inner.version_rebase_by_branch(bv, brid).c(d!())
+    ) -> VsdbResult<()> {
+        let bv = self
+            .inner
+            .version_get_id_by_name(base_version)
+            .ok_or_else(|| VsdbError::version_not_found(base_version.0))?;
+        let brid = self
+            .inner
+            .branch_get_id_by_name(branch_name)
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))?;
+        self.inner.version_rebase_by_branch(bv, brid)
     }
 
     #[inline(always)]
@@ -300,19 +371,19 @@ impl VsMgmt for MapxRawMkVs {
     }
 
     #[inline(always)]
-    fn version_list(&self) -> Result<Vec<VersionNameOwned>> {
-        self.inner.version_list().c(d!())
+    fn version_list(&self) -> VsdbResult<Vec<VersionNameOwned>> {
+        self.inner.version_list().map_err(From::from)
     }
 
     #[inline(always)]
     fn version_list_by_branch(
         &self,
         branch_name: BranchName,
-    ) -> Result<Vec<VersionNameOwned>> {
+    ) -> VsdbResult<Vec<VersionNameOwned>> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|brid| self.inner.version_list_by_branch(brid).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|brid| self.inner.version_list_by_branch(brid).map_err(From::from))
     }
 
     #[inline(always)]
@@ -321,27 +392,33 @@ impl VsMgmt for MapxRawMkVs {
     }
 
     #[inline(always)]
-    fn version_has_change_set(&self, version_name: VersionName) -> Result<bool> {
+    fn version_has_change_set(&self, version_name: VersionName) -> VsdbResult<bool> {
         self.inner
             .version_get_id_by_name(version_name)
-            .c(d!("version not found"))
-            .and_then(|verid| self.inner.version_has_change_set(verid).c(d!()))
+            .ok_or_else(|| VsdbError::version_not_found(version_name.0))
+            .and_then(|verid| {
+                self.inner.version_has_change_set(verid).map_err(From::from)
+            })
     }
 
     #[inline(always)]
-    fn version_clean_up_globally(&mut self) -> Result<()> {
-        self.inner.version_clean_up_globally().c(d!())
+    fn version_clean_up_globally(&mut self) -> VsdbResult<()> {
+        self.inner.version_clean_up_globally().map_err(From::from)
     }
 
     #[inline(always)]
     unsafe fn version_revert_globally(
         &mut self,
         version_name: VersionName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .version_get_id_by_name(version_name)
-            .c(d!("version not found"))
-            .and_then(|verid| self.inner.version_revert_globally(verid).c(d!()))
+            .ok_or_else(|| VsdbError::version_not_found(version_name.0))
+            .and_then(|verid| {
+                self.inner
+                    .version_revert_globally(verid)
+                    .map_err(From::from)
+            })
     }
 
     #[inline(always)]
@@ -350,10 +427,9 @@ impl VsMgmt for MapxRawMkVs {
         branch_name: BranchName,
         version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_create(branch_name.0, version_name.0, force)
-            .c(d!())
     }
 
     #[inline(always)]
@@ -363,19 +439,17 @@ impl VsMgmt for MapxRawMkVs {
         version_name: VersionName,
         base_branch_name: ParentBranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(BranchName(base_branch_name.0))
-            .c(d!("base branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(base_branch_name.0))
             .and_then(|base_br_id| {
-                self.inner
-                    .branch_create_by_base_branch(
-                        branch_name.0,
-                        version_name.0,
-                        base_br_id,
-                        force,
-                    )
-                    .c(d!())
+                self.inner.branch_create_by_base_branch(
+                    branch_name.0,
+                    version_name.0,
+                    base_br_id,
+                    force,
+                )
             })
     }
 
@@ -387,24 +461,22 @@ impl VsMgmt for MapxRawMkVs {
         base_branch_name: ParentBranchName,
         base_version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         let base_br_id = self
             .inner
             .branch_get_id_by_name(BranchName(base_branch_name.0))
-            .c(d!("base branch not found"))?;
+            .ok_or_else(|| VsdbError::branch_not_found(base_branch_name.0))?;
         let base_ver_id = self
             .inner
             .version_get_id_by_name(base_version_name)
-            .c(d!("base vesion not found"))?;
-        self.inner
-            .branch_create_by_base_branch_version(
-                branch_name.0,
-                version_name.0,
-                base_br_id,
-                base_ver_id,
-                force,
-            )
-            .c(d!())
+            .ok_or_else(|| VsdbError::version_not_found(base_version_name.0))?;
+        self.inner.branch_create_by_base_branch_version(
+            branch_name.0,
+            version_name.0,
+            base_br_id,
+            base_ver_id,
+            force,
+        )
     }
 
     #[inline(always)]
@@ -412,10 +484,9 @@ impl VsMgmt for MapxRawMkVs {
         &mut self,
         branch_name: BranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_create_without_new_version(branch_name.0, force)
-            .c(d!())
     }
 
     #[inline(always)]
@@ -424,18 +495,16 @@ impl VsMgmt for MapxRawMkVs {
         branch_name: BranchName,
         base_branch_name: ParentBranchName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(BranchName(base_branch_name.0))
-            .c(d!("base branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(base_branch_name.0))
             .and_then(|base_br_id| {
-                self.inner
-                    .branch_create_by_base_branch_without_new_version(
-                        branch_name.0,
-                        base_br_id,
-                        force,
-                    )
-                    .c(d!())
+                self.inner.branch_create_by_base_branch_without_new_version(
+                    branch_name.0,
+                    base_br_id,
+                    force,
+                )
             })
     }
 
@@ -446,15 +515,15 @@ impl VsMgmt for MapxRawMkVs {
         base_branch_name: ParentBranchName,
         base_version_name: VersionName,
         force: bool,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         let base_br_id = self
             .inner
             .branch_get_id_by_name(BranchName(base_branch_name.0))
-            .c(d!("base branch not found"))?;
+            .ok_or_else(|| VsdbError::branch_not_found(base_branch_name.0))?;
         let base_ver_id = self
             .inner
             .version_get_id_by_name(base_version_name)
-            .c(d!("base vesion not found"))?;
+            .ok_or_else(|| VsdbError::version_not_found(base_version_name.0))?;
         self.inner
             .branch_create_by_base_branch_version_without_new_version(
                 branch_name.0,
@@ -462,7 +531,6 @@ impl VsMgmt for MapxRawMkVs {
                 base_ver_id,
                 force,
             )
-            .c(d!())
     }
 
     #[inline(always)]
@@ -482,37 +550,37 @@ impl VsMgmt for MapxRawMkVs {
     }
 
     #[inline(always)]
-    fn branch_remove(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_remove(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         if let Some(branch_id) = self.inner.branch_get_id_by_name(branch_name) {
-            self.inner.branch_remove(branch_id).c(d!())
+            self.inner.branch_remove(branch_id).map_err(From::from)
         } else {
-            Err(eg!("branch not found"))
+            Err(VsdbError::branch_not_found(branch_name.0))
         }
     }
 
     /// Clean up all other branches not in the list.
     #[inline(always)]
-    fn branch_keep_only(&mut self, branch_names: &[BranchName]) -> Result<()> {
+    fn branch_keep_only(&mut self, branch_names: &[BranchName]) -> VsdbResult<()> {
         let br_ids = branch_names
             .iter()
             .copied()
             .map(|brname| {
                 self.inner
                     .branch_get_id_by_name(brname)
-                    .c(d!("version not found"))
+                    .ok_or_else(|| VsdbError::branch_not_found(brname.0))
             })
-            .collect::<Result<BTreeSet<_>>>()?
+            .collect::<VsdbResult<BTreeSet<_>>>()?
             .into_iter()
             .collect::<Vec<_>>();
-        self.inner.branch_keep_only(&br_ids).c(d!())
+        self.inner.branch_keep_only(&br_ids).map_err(From::from)
     }
 
     #[inline(always)]
-    fn branch_truncate(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_truncate(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|br_id| self.inner.branch_truncate(br_id).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|br_id| self.inner.branch_truncate(br_id).map_err(From::from))
     }
 
     #[inline(always)]
@@ -520,26 +588,28 @@ impl VsMgmt for MapxRawMkVs {
         &mut self,
         branch_name: BranchName,
         last_version_name: VersionName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
             .and_then(|br_id| {
                 self.inner
                     .version_get_id_by_name(last_version_name)
-                    .c(d!("version not found"))
+                    .ok_or_else(|| VsdbError::version_not_found(last_version_name.0))
                     .and_then(|last_ver_id| {
-                        self.inner.branch_truncate_to(br_id, last_ver_id).c(d!())
+                        self.inner
+                            .branch_truncate_to(br_id, last_ver_id)
+                            .map_err(From::from)
                     })
             })
     }
 
     #[inline(always)]
-    fn branch_pop_version(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_pop_version(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|id| self.inner.branch_pop_version(id).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|id| self.inner.branch_pop_version(id).map_err(From::from))
     }
 
     #[inline(always)]
@@ -547,16 +617,18 @@ impl VsMgmt for MapxRawMkVs {
         &mut self,
         branch_name: BranchName,
         target_branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
             .and_then(|brid| {
                 let target_brid = self
                     .inner
                     .branch_get_id_by_name(target_branch_name)
-                    .c(d!("target branch not found"))?;
-                self.inner.branch_merge_to(brid, target_brid).c(d!())
+                    .ok_or_else(|| {
+                        VsdbError::branch_not_found(target_branch_name.0)
+                    })?;
+                self.inner.branch_merge_to(brid, target_brid)
             })
     }
 
@@ -565,33 +637,35 @@ impl VsMgmt for MapxRawMkVs {
         &mut self,
         branch_name: BranchName,
         target_branch_name: BranchName,
-    ) -> Result<()> {
+    ) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
             .and_then(|brid| {
                 let target_brid = self
                     .inner
                     .branch_get_id_by_name(target_branch_name)
-                    .c(d!("target branch not found"))?;
-                self.inner.branch_merge_to_force(brid, target_brid).c(d!())
+                    .ok_or_else(|| {
+                        VsdbError::branch_not_found(target_branch_name.0)
+                    })?;
+                self.inner.branch_merge_to_force(brid, target_brid)
             })
     }
 
     #[inline(always)]
-    fn branch_set_default(&mut self, branch_name: BranchName) -> Result<()> {
+    fn branch_set_default(&mut self, branch_name: BranchName) -> VsdbResult<()> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|brid| self.inner.branch_set_default(brid).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|brid| self.inner.branch_set_default(brid).map_err(From::from))
     }
 
     #[inline(always)]
-    fn branch_is_empty(&self, branch_name: BranchName) -> Result<bool> {
+    fn branch_is_empty(&self, branch_name: BranchName) -> VsdbResult<bool> {
         self.inner
             .branch_get_id_by_name(branch_name)
-            .c(d!("branch not found"))
-            .and_then(|brid| self.inner.branch_is_empty(brid).c(d!()))
+            .ok_or_else(|| VsdbError::branch_not_found(branch_name.0))
+            .and_then(|brid| self.inner.branch_is_empty(brid).map_err(From::from))
     }
 
     #[inline(always)]
@@ -609,13 +683,15 @@ impl VsMgmt for MapxRawMkVs {
         &mut self,
         branch_1: BranchName,
         branch_2: BranchName,
-    ) -> Result<()> {
-        self.inner.branch_swap(branch_1.0, branch_2.0).c(d!())
+    ) -> VsdbResult<()> {
+        self.inner
+            .branch_swap(branch_1.0, branch_2.0)
+            .map_err(From::from)
     }
 
     #[inline(always)]
-    fn prune(&mut self, reserved_ver_num: Option<usize>) -> Result<()> {
-        self.inner.prune(reserved_ver_num).c(d!())
+    fn prune(&mut self, reserved_ver_num: Option<usize>) -> VsdbResult<()> {
+        self.inner.prune(reserved_ver_num).map_err(From::from)
     }
 }
 
@@ -651,12 +727,12 @@ impl<'a> DerefMut for ValueMut<'a> {
     }
 }
 
-pub struct Entry<'a> {
+pub struct EntryRef<'a> {
     hdr: &'a mut MapxRawMkVs,
     key: &'a [&'a [u8]],
 }
 
-impl<'a> Entry<'a> {
+impl<'a> EntryRef<'a> {
     pub fn or_insert_ref(self, default: &'a [u8]) -> ValueMut<'a> {
         if !self.hdr.contains_key(self.key) {
             pnk!(self.hdr.insert(self.key, default));
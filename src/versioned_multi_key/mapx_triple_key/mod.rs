@@ -7,7 +7,7 @@ use crate::{
         RawValue,
     },
     versioned_multi_key::mapx_raw::MapxRawMkVs,
-    BranchName, VersionName, VsMgmt,
+    BranchName, Entry, EntryHdr, GcMark, GcMarks, VersionName, VsMgmt,
 };
 use ruc::*;
 use serde::{Deserialize, Serialize};
@@ -66,12 +66,17 @@ where
         self.get(key).map(move |v| ValueMut::new(self, key, v))
     }
 
+    #[inline(always)]
+    pub fn entry(&mut self, key: (K1, K2, K3)) -> Entry<'_, Self> {
+        Entry::new(self, key)
+    }
+
     #[inline(always)]
     pub fn entry_ref<'a>(
         &'a mut self,
         key: &'a (&'a K1, &'a K2, &'a K3),
-    ) -> Entry<'a, K1, K2, K3, V> {
-        Entry { key, hdr: self }
+    ) -> EntryRef<'a, K1, K2, K3, V> {
+        EntryRef { key, hdr: self }
     }
 
     #[inline(always)]
@@ -428,6 +433,39 @@ where
     crate::impl_vs_methods!();
 }
 
+impl<K1, K2, K3, V> EntryHdr for MapxTkVs<K1, K2, K3, V>
+where
+    K1: KeyEnDe,
+    K2: KeyEnDe,
+    K3: KeyEnDe,
+    V: ValueEnDe,
+{
+    type Key = (K1, K2, K3);
+    type Value = V;
+
+    fn entry_get(&self, key: &(K1, K2, K3)) -> Option<V> {
+        self.get(&(&key.0, &key.1, &key.2))
+    }
+
+    fn entry_set(&mut self, key: &(K1, K2, K3), value: &V) {
+        pnk!(self.insert_ref(&(&key.0, &key.1, &key.2), value));
+    }
+
+    fn entry_remove(&mut self, key: &(K1, K2, K3)) {
+        pnk!(self.remove(&(&key.0, Some((&key.1, Some(&key.2))))));
+    }
+}
+
+impl<K1, K2, K3, V> GcMark for MapxTkVs<K1, K2, K3, V>
+where
+    V: ValueEnDe + GcMark,
+{
+    #[inline(always)]
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.inner.gc_mark_with_typed_value::<V>(marks);
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct ValueMut<'a, K1, K2, K3, V>
 where
@@ -494,7 +532,7 @@ where
     }
 }
 
-pub struct Entry<'a, K1, K2, K3, V>
+pub struct EntryRef<'a, K1, K2, K3, V>
 where
     K1: KeyEnDe,
     K2: KeyEnDe,
@@ -505,7 +543,7 @@ where
     key: &'a (&'a K1, &'a K2, &'a K3),
 }
 
-impl<'a, K1, K2, K3, V> Entry<'a, K1, K2, K3, V>
+impl<'a, K1, K2, K3, V> EntryRef<'a, K1, K2, K3, V>
 where
     K1: KeyEnDe,
     K2: KeyEnDe,
@@ -31,10 +31,10 @@ fn basic_cases() {
             .for_each(|(i, b)| {
                 hdr_i.entry_ref(&i).or_insert_ref(&b);
                 assert_eq!(&hdr_i.get(&i).unwrap().data, &i);
-                assert_eq!(hdr_i.remove(&i), Some(b.clone()));
+                assert_eq!(hdr_i.remove(&i).unwrap(), Some(b.clone()));
                 assert!(hdr_i.get(&i).is_none());
-                assert!(hdr_i.insert_ref(&i, &b).is_none());
-                assert!(hdr_i.insert_ref(&i, &b).is_some());
+                assert!(hdr_i.insert_ref(&i, &b).unwrap().is_none());
+                assert!(hdr_i.insert_ref(&i, &b).unwrap().is_some());
             });
 
         assert_eq!(cnt, hdr_i.len());
@@ -51,18 +51,18 @@ fn basic_cases() {
     });
 
     (1usize..cnt).for_each(|i| {
-        (*reloaded.get_mut(&i.to_be_bytes()).unwrap()).data =
+        reloaded.get_mut(&i.to_be_bytes()).unwrap().data =
             (1 + i).to_be_bytes().to_vec();
         assert_eq!(
             &reloaded.get(&i.to_be_bytes()).unwrap().data,
             &(1 + i).to_be_bytes()[..]
         );
         assert!(reloaded.contains_key(&i.to_be_bytes()));
-        assert!(reloaded.remove(&i.to_be_bytes()).is_some());
+        assert!(reloaded.remove(&i.to_be_bytes()).unwrap().is_some());
         assert!(!reloaded.contains_key(&i.to_be_bytes()));
     });
 
     assert_eq!(1, reloaded.len());
-    reloaded.clear();
+    reloaded.clear().unwrap();
     assert!(reloaded.is_empty());
 }
@@ -18,13 +18,16 @@ fn basic_cases() {
             .map(|i| (i, i.to_be_bytes()))
             .for_each(|(i, b)| {
                 hdr_i.entry_ref(&i).or_insert_ref(&b);
-                assert_eq!(1 + i as usize, hdr_i.len());
+                assert_eq!(1 + i, hdr_i.len());
                 assert_eq!(&hdr_i.get(&i).unwrap()[..], &b[..]);
-                assert_eq!(&hdr_i.remove(&i).unwrap()[..], &b);
-                assert_eq!(i as usize, hdr_i.len());
+                assert_eq!(&hdr_i.remove(&i).unwrap().unwrap()[..], &b);
+                assert_eq!(i, hdr_i.len());
                 assert!(hdr_i.get(&i).is_none());
-                assert!(hdr_i.insert_ref(&i, &b).is_none());
-                assert!(hdr_i.insert(i, b.to_vec().into_boxed_slice()).is_some());
+                assert!(hdr_i.insert_ref(&i, &b).unwrap().is_none());
+                assert!(hdr_i
+                    .insert(i, b.to_vec().into_boxed_slice())
+                    .unwrap()
+                    .is_some());
             });
 
         assert_eq!(cnt, hdr_i.len());
@@ -45,18 +48,18 @@ fn basic_cases() {
             (1 + i).to_be_bytes().to_vec().into_boxed_slice();
         assert_eq!(&reloaded.get(&i).unwrap()[..], &(1 + i).to_be_bytes());
         assert!(reloaded.contains_key(&i));
-        assert!(reloaded.remove(&i).is_some());
+        assert!(reloaded.remove(&i).unwrap().is_some());
         assert!(!reloaded.contains_key(&i));
     });
 
     assert_eq!(1, reloaded.len());
-    reloaded.clear();
+    reloaded.clear().unwrap();
     assert!(reloaded.is_empty());
 
-    reloaded.insert_ref(&1, &1usize.to_be_bytes());
-    reloaded.insert_ref(&10, &10usize.to_be_bytes());
-    reloaded.insert_ref(&100, &100usize.to_be_bytes());
-    reloaded.insert_ref(&1000, &1000usize.to_be_bytes());
+    reloaded.insert_ref(&1, &1usize.to_be_bytes()).unwrap();
+    reloaded.insert_ref(&10, &10usize.to_be_bytes()).unwrap();
+    reloaded.insert_ref(&100, &100usize.to_be_bytes()).unwrap();
+    reloaded.insert_ref(&1000, &1000usize.to_be_bytes()).unwrap();
 
     assert!(reloaded.range(0..1).next().is_none());
 
@@ -18,10 +18,10 @@ fn basic_cases() {
             .for_each(|(i, b)| {
                 hdr_i.entry_ref(&i).or_insert_ref(&b);
                 assert_eq!(&hdr_i.get(&i).unwrap()[..], &i[..]);
-                assert_eq!(&hdr_i.remove(&i).unwrap()[..], &b[..]);
+                assert_eq!(&hdr_i.remove(&i).unwrap().unwrap()[..], &b[..]);
                 assert!(hdr_i.get(&i).is_none());
-                assert!(hdr_i.insert(&i, &b).is_none());
-                assert!(hdr_i.insert(&i, &b).is_some());
+                assert!(hdr_i.insert(&i, &b).unwrap().is_none());
+                assert!(hdr_i.insert(&i, &b).unwrap().is_some());
             });
 
         assert_eq!(cnt, hdr_i.len());
@@ -41,18 +41,18 @@ fn basic_cases() {
         *reloaded.get_mut(&i).unwrap() = i.to_vec().into_boxed_slice();
         assert_eq!(&reloaded.get(&i).unwrap()[..], &i[..]);
         assert!(reloaded.contains_key(&i));
-        assert!(reloaded.remove(&i).is_some());
+        assert!(reloaded.remove(&i).unwrap().is_some());
         assert!(!reloaded.contains_key(&i));
     });
 
     assert_eq!(1, reloaded.len());
-    reloaded.clear();
+    reloaded.clear().unwrap();
     assert!(reloaded.is_empty());
 
-    reloaded.insert(&[1], &[1]);
-    reloaded.insert(&[4], &[4]);
-    reloaded.insert(&[6], &[6]);
-    reloaded.insert(&[80], &[80]);
+    reloaded.insert(&[1], &[1]).unwrap();
+    reloaded.insert(&[4], &[4]).unwrap();
+    reloaded.insert(&[6], &[6]).unwrap();
+    reloaded.insert(&[80], &[80]).unwrap();
 
     assert!(reloaded.range(&[][..]..&[1][..]).next().is_none());
     assert_eq!(
@@ -29,13 +29,13 @@ fn basic_cases() {
 
         (0..cnt).map(|i| (i, gen_sample(i))).for_each(|(i, b)| {
             hdr_i.entry(i).or_insert(b.clone());
-            assert_eq!(1 + i as usize, hdr_i.len());
+            assert_eq!(1 + i, hdr_i.len());
             assert_eq!(pnk!(hdr_i.get(&i)).idx, i);
-            assert_eq!(hdr_i.remove(&i), Some(b.clone()));
-            assert_eq!(i as usize, hdr_i.len());
+            assert_eq!(hdr_i.remove(&i).unwrap(), Some(b.clone()));
+            assert_eq!(i, hdr_i.len());
             assert!(hdr_i.get(&i).is_none());
-            assert!(hdr_i.insert_ref(&i, &b).is_none());
-            assert!(hdr_i.insert(i, b).is_some());
+            assert!(hdr_i.insert_ref(&i, &b).unwrap().is_none());
+            assert!(hdr_i.insert(i, b).unwrap().is_some());
         });
 
         assert_eq!(cnt, hdr_i.len());
@@ -55,11 +55,11 @@ fn basic_cases() {
         pnk!(reloaded.get_mut(&i)).idx = 1 + i;
         assert_eq!(pnk!(reloaded.get(&i)).idx, 1 + i);
         assert!(reloaded.contains_key(&i));
-        assert!(reloaded.remove(&i).is_some());
+        assert!(reloaded.remove(&i).unwrap().is_some());
         assert!(!reloaded.contains_key(&i));
     });
 
     assert_eq!(1, reloaded.len());
-    reloaded.clear();
+    reloaded.clear().unwrap();
     assert!(reloaded.is_empty());
 }
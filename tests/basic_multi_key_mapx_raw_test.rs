@@ -55,14 +55,14 @@ fn basic_cases() {
     );
     assert_eq!(
         map.get(&[&[11], &[12], &[13], &[14]]).unwrap().as_ref(),
-        &[]
+        &[] as &[u8]
     );
 
     let mut cnt = 0;
     let mut op = |k: &[&[u8]], v: &[u8]| {
         cnt += 1;
         assert_eq!(k, &[&[11], &[12], &[13], &[14]]);
-        assert_eq!(v, &[]);
+        assert!(v.is_empty());
         Ok(())
     };
 
@@ -82,7 +82,7 @@ fn basic_cases() {
     let mut cnt = 0;
     let mut op = |k: &[&[u8]], v: &[u8]| {
         cnt += 1;
-        if v == &[] {
+        if v.is_empty() {
             assert_eq!(k, &[&[11], &[12], &[13], &[14]]);
         } else {
             assert_eq!(k, &[&[11], &[12], &[13], &[15]]);
@@ -114,6 +114,5 @@ fn basic_cases() {
     // cnt += 0
     pnk!(map.iter_op_with_key_prefix(&mut op, &[&[111], &[12], &[13], &[15]]));
 
-    drop(op);
     assert_eq!(cnt, 10);
 }
@@ -108,6 +108,5 @@ fn basic_cases() {
     // cnt += 0
     pnk!(map.iter_op_with_key_prefix(&mut op, &[&[111], &[12], &[13], &[15]]));
 
-    drop(op);
     assert_eq!(cnt, 10);
 }
@@ -18,9 +18,9 @@ fn basic_cases() {
         });
 
         (0..cnt).map(|i| (i, gen_sample(i))).for_each(|(i, b)| {
-            hdr.push_ref(&b);
-            assert_eq!(1 + i as usize, hdr.len());
-            assert_eq!(pnk!(hdr.get(i as usize)), b);
+            hdr.push_ref(&b).unwrap();
+            assert_eq!(1 + i, hdr.len());
+            assert_eq!(pnk!(hdr.get(i)), b);
             assert_eq!(pnk!(hdr.last()), b);
         });
 
@@ -42,10 +42,10 @@ fn basic_cases() {
     *reloaded.get_mut(0).unwrap() = gen_sample(999 * cnt);
     assert_eq!(reloaded.get(0).unwrap(), gen_sample(999 * cnt));
 
-    reloaded.pop();
+    reloaded.pop().unwrap();
     assert_eq!(cnt - 1, reloaded.len());
 
-    reloaded.clear();
+    reloaded.clear().unwrap();
     assert!(reloaded.is_empty());
 }
 
@@ -58,23 +58,23 @@ fn write() {
 
     let mut hdr = VecxRaw::new();
 
-    hdr.insert(0, gen_sample(0));
+    hdr.insert(0, gen_sample(0)).unwrap();
     assert_eq!(1, hdr.len());
-    hdr.insert(0, gen_sample(0));
+    hdr.insert(0, gen_sample(0)).unwrap();
     assert_eq!(2, hdr.len());
 
-    hdr.update_ref(0, &gen_sample(1));
+    hdr.update_ref(0, &gen_sample(1)).unwrap();
     assert_eq!(gen_sample(1), hdr.get(0).unwrap());
-    hdr.update_ref(1, &gen_sample(1));
+    hdr.update_ref(1, &gen_sample(1)).unwrap();
     assert_eq!(gen_sample(1), hdr.get(1).unwrap());
 
-    hdr.push(gen_sample(2));
-    assert_eq!(gen_sample(1), hdr.swap_remove(0));
+    hdr.push(gen_sample(2)).unwrap();
+    assert_eq!(gen_sample(1), hdr.swap_remove(0).unwrap());
     assert_eq!(2, hdr.len());
     assert_eq!(gen_sample(2), hdr.get(0).unwrap());
 
-    hdr.push_ref(&gen_sample(3));
-    assert_eq!(gen_sample(2), hdr.remove(0));
+    hdr.push_ref(&gen_sample(3)).unwrap();
+    assert_eq!(gen_sample(2), hdr.remove(0).unwrap());
     assert_eq!(2, hdr.len());
     assert_eq!(gen_sample(3), hdr.get(1).unwrap());
 }
@@ -28,9 +28,9 @@ fn basic_cases() {
         });
 
         (0..cnt).map(|i| (i, gen_sample(i))).for_each(|(i, b)| {
-            hdr.push_ref(&b);
-            assert_eq!(1 + i as usize, hdr.len());
-            assert_eq!(pnk!(hdr.get(i as usize)), b);
+            hdr.push_ref(&b).unwrap();
+            assert_eq!(1 + i, hdr.len());
+            assert_eq!(pnk!(hdr.get(i)), b);
             assert_eq!(pnk!(hdr.last()), b);
         });
 
@@ -52,10 +52,10 @@ fn basic_cases() {
     *reloaded.get_mut(0).unwrap() = gen_sample(999 * cnt);
     assert_eq!(reloaded.get(0).unwrap(), gen_sample(999 * cnt));
 
-    reloaded.pop();
+    reloaded.pop().unwrap();
     assert_eq!(cnt - 1, reloaded.len());
 
-    reloaded.clear();
+    reloaded.clear().unwrap();
     assert!(reloaded.is_empty());
 }
 
@@ -68,23 +68,23 @@ fn write() {
 
     let mut hdr = Vecx::new();
 
-    hdr.insert(0, 0);
+    hdr.insert(0, 0).unwrap();
     assert_eq!(1, hdr.len());
-    hdr.insert(0, 0);
+    hdr.insert(0, 0).unwrap();
     assert_eq!(2, hdr.len());
 
-    hdr.update_ref(0, &1);
+    hdr.update_ref(0, &1).unwrap();
     assert_eq!(1, hdr.get(0).unwrap());
-    hdr.update_ref(1, &1);
+    hdr.update_ref(1, &1).unwrap();
     assert_eq!(1, hdr.get(1).unwrap());
 
-    hdr.push(2);
-    assert_eq!(1, hdr.swap_remove(0));
+    hdr.push(2).unwrap();
+    assert_eq!(1, hdr.swap_remove(0).unwrap());
     assert_eq!(2, hdr.len());
     assert_eq!(2, hdr.get(0).unwrap());
 
-    hdr.push_ref(&3);
-    assert_eq!(2, hdr.remove(0));
+    hdr.push_ref(&3).unwrap();
+    assert_eq!(2, hdr.remove(0).unwrap());
     assert_eq!(2, hdr.len());
     assert_eq!(3, hdr.get(1).unwrap());
 }
@@ -93,61 +93,61 @@ fn write() {
 #[should_panic]
 fn write_out_of_index_0() {
     let mut hdr = Vecx::new();
-    hdr.insert_ref(100, &0);
+    hdr.insert_ref(100, &0).unwrap();
 }
 
 #[test]
 #[should_panic]
 fn write_out_of_index_1() {
     let mut hdr = Vecx::new();
-    hdr.insert(0, 0);
-    hdr.insert_ref(100, &0);
+    hdr.insert(0, 0).unwrap();
+    hdr.insert_ref(100, &0).unwrap();
 }
 
 #[test]
 #[should_panic]
 fn write_out_of_index_2() {
     let mut hdr = Vecx::new();
-    hdr.update_ref(100, &0);
-    hdr.insert(0, 0);
+    hdr.update_ref(100, &0).unwrap();
+    hdr.insert(0, 0).unwrap();
 }
 
 #[test]
 #[should_panic]
 fn write_out_of_index_3() {
     let mut hdr = Vecx::new();
-    hdr.insert(0, 0);
-    hdr.update_ref(100, &0);
+    hdr.insert(0, 0).unwrap();
+    hdr.update_ref(100, &0).unwrap();
 }
 
 #[test]
 #[should_panic]
 fn write_out_of_index_4() {
     let mut hdr = Vecx::new();
-    hdr.remove(100);
-    hdr.insert(0, 0);
+    hdr.remove(100).unwrap();
+    hdr.insert(0, 0).unwrap();
 }
 
 #[test]
 #[should_panic]
 fn write_out_of_index_5() {
     let mut hdr = Vecx::new();
-    hdr.insert(0, 0);
-    hdr.remove(100);
+    hdr.insert(0, 0).unwrap();
+    hdr.remove(100).unwrap();
 }
 
 #[test]
 #[should_panic]
 fn write_out_of_index_6() {
     let mut hdr = Vecx::new();
-    hdr.swap_remove(100);
-    hdr.insert(0, 0);
+    hdr.swap_remove(100).unwrap();
+    hdr.insert(0, 0).unwrap();
 }
 
 #[test]
 #[should_panic]
 fn write_out_of_index_7() {
     let mut hdr = Vecx::new();
-    hdr.insert(0, 0);
-    hdr.swap_remove(100);
+    hdr.insert(0, 0).unwrap();
+    hdr.swap_remove(100).unwrap();
 }
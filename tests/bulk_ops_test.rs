@@ -0,0 +1,158 @@
+use vsdb::{
+    basic::{mapx_ord_rawkey::MapxOrdRawKey, mapx_raw::MapxRaw, vecx_raw::VecxRaw},
+    vsdb_set_base_dir, Mapx, MapxOrd, Vecx,
+};
+
+#[test]
+fn bulk_operations() {
+    vsdb_set_base_dir("/tmp/.vsdb/bulk_ops_test").unwrap();
+
+    raw_maps();
+    typed_maps();
+    vecs();
+}
+
+fn raw_maps() {
+    let mut m: MapxRaw = (0u32..100)
+        .map(|i| (i.to_be_bytes().into(), i.to_be_bytes().into()))
+        .collect();
+    assert_eq!(m.len(), 100);
+
+    // overwrites and duplicate keys in one batch
+    m.extend([
+        (1u32.to_be_bytes().into(), vec![0].into()),
+        (200u32.to_be_bytes().into(), vec![0].into()),
+        (200u32.to_be_bytes().into(), vec![1].into()),
+    ]);
+    assert_eq!(m.len(), 101);
+    assert_eq!(m.get(&1u32.to_be_bytes()).unwrap().to_vec(), vec![0]);
+    assert_eq!(m.get(&200u32.to_be_bytes()).unwrap().to_vec(), vec![1]);
+
+    m.retain(|k, _| k[3] % 2 == 0).unwrap();
+    assert_eq!(m.len(), 51);
+    assert!(m.iter().all(|(k, _)| k[3] % 2 == 0));
+
+    let drained = m
+        .drain_range(&10u32.to_be_bytes()[..]..&20u32.to_be_bytes()[..])
+        .unwrap();
+    assert_eq!(drained.len(), 5);
+    assert_eq!(drained[0].0.to_vec(), 10u32.to_be_bytes().to_vec());
+    assert_eq!(m.len(), 46);
+    assert!(m.get(&12u32.to_be_bytes()).is_none());
+
+    let mut tail = m.split_off(&50u32.to_be_bytes()).unwrap();
+    assert_eq!(m.len(), 20);
+    assert_eq!(tail.len(), 26);
+    assert!(m.iter().all(|(k, _)| k[..] < 50u32.to_be_bytes()[..]));
+    assert!(tail.iter().all(|(k, _)| k[..] >= 50u32.to_be_bytes()[..]));
+
+    m.append(&mut tail).unwrap();
+    assert_eq!(m.len(), 46);
+    assert!(tail.is_empty());
+
+    let mut untracked = MapxRaw::new_untracked();
+    untracked.extend(m.iter());
+    untracked.retain(|k, _| k[3] < 50).unwrap();
+    assert_eq!(untracked.len(), 20);
+
+    let mut m: MapxOrdRawKey<String> =
+        (0u8..10).map(|i| (vec![i].into(), i.to_string())).collect();
+    m.retain(|_, v| v != "3").unwrap();
+    assert_eq!(m.len(), 9);
+    let lo: Box<[u8]> = vec![5].into();
+    let drained = m.drain_range(lo..).unwrap();
+    assert_eq!(drained.len(), 5);
+    assert_eq!(drained[0].1, "5");
+    let tail = m.split_off(&[2]).unwrap();
+    assert_eq!(m.len(), 2);
+    assert_eq!(tail.len(), 2);
+}
+
+fn typed_maps() {
+    let mut m: MapxOrd<i32, String> = (-10..10).map(|i| (i, i.to_string())).collect();
+    assert_eq!(m.len(), 20);
+
+    m.retain(|k, _| *k != 0).unwrap();
+    assert_eq!(m.len(), 19);
+    assert!(!m.contains_key(&0));
+
+    let drained = m.drain_range(-5..=5).unwrap();
+    assert_eq!(drained.len(), 10);
+    assert_eq!(drained[0], (-5, "-5".to_owned()));
+    assert_eq!(m.len(), 9);
+
+    let mut tail = m.split_off(&0).unwrap();
+    assert_eq!(
+        m.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+        (-10..-5).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        tail.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+        (6..10).collect::<Vec<_>>()
+    );
+
+    tail.insert(-10, "x".to_owned()).unwrap();
+    m.append(&mut tail).unwrap();
+    assert!(tail.is_empty());
+    assert_eq!(m.len(), 9);
+    assert_eq!(m.get(&-10).unwrap(), "x");
+
+    // more pairs than one write batch
+    let mut m: Mapx<u32, u32> = Mapx::new();
+    m.extend((0..20000).map(|i| (i, i)));
+    assert_eq!(m.len(), 20000);
+    m.retain(|k, v| k == v && k % 4 == 0).unwrap();
+    assert_eq!(m.len(), 5000);
+
+    let mut other: Mapx<u32, u32> = (0..10).map(|i| (i, 0)).collect();
+    m.append(&mut other).unwrap();
+    assert!(other.is_empty());
+    assert_eq!(m.len(), 5007);
+    assert_eq!(m.get(&4), Some(0));
+    assert_eq!(m.get(&5), Some(0));
+}
+
+fn vecs() {
+    let mut v: Vecx<u64> = (0..100).collect();
+    assert_eq!(v.len(), 100);
+
+    v.extend(100..110);
+    assert_eq!(v.len(), 110);
+    assert_eq!(v.last(), Some(109));
+
+    v.retain(|i| i % 3 == 0).unwrap();
+    assert_eq!(v.len(), 37);
+    assert!(v.iter().zip((0..110).step_by(3)).all(|(a, b)| a == b));
+
+    v.truncate(30).unwrap();
+    assert_eq!(v.len(), 30);
+    assert_eq!(v.last(), Some(87));
+    v.truncate(100).unwrap();
+    assert_eq!(v.len(), 30);
+
+    let mut tail = v.split_off(20).unwrap();
+    assert_eq!(v.len(), 20);
+    assert_eq!(tail.len(), 10);
+    assert_eq!(tail.get(0), Some(60));
+
+    v.append(&mut tail).unwrap();
+    assert!(tail.is_empty());
+    assert_eq!(v.len(), 30);
+    assert_eq!(v.get(20), Some(60));
+
+    let mut v: VecxRaw = (0u8..10).map(|i| vec![i].into()).collect();
+    v.retain(|i| i[0] >= 5).unwrap();
+    assert_eq!(v.len(), 5);
+    assert_eq!(v.get(0).unwrap().to_vec(), vec![5]);
+
+    let mut tail = v.split_off(2).unwrap();
+    assert_eq!(v.len(), 2);
+    assert_eq!(tail.get(0).unwrap().to_vec(), vec![7]);
+    v.append(&mut tail).unwrap();
+    assert!(tail.is_empty());
+    v.truncate(1).unwrap();
+    assert_eq!(
+        v.iter().map(|i| i.to_vec()).collect::<Vec<_>>(),
+        vec![vec![5]]
+    );
+}
@@ -0,0 +1,86 @@
+use ruc::*;
+use std::{env, fs, path::Path, process::Command};
+use vsdb::{
+    vsdb_checkpoint, vsdb_get_custom_dir, vsdb_restore, vsdb_set_base_dir, MapxOrd,
+    MapxVs, ValueEnDe, VersionName, VsMgmt,
+};
+
+const CHECKPOINT_DIR: &str = "/tmp/.vsdb/checkpoint_test_ckpt";
+
+// the restoring must be done in a new process
+const ENV_ENCODED_HDR: &str = "VSDB_CHECKPOINT_TEST_HDR";
+
+#[test]
+fn checkpoint_and_restore() {
+    if let Ok(hdr) = env::var(ENV_ENCODED_HDR) {
+        restore_and_check(&hdr);
+        return;
+    }
+
+    vsdb_set_base_dir("/tmp/.vsdb/checkpoint_test").unwrap();
+    info_omit!(fs::remove_dir_all(CHECKPOINT_DIR));
+
+    let mut hdr: MapxVs<u64, MapxOrd<u64, u64>> = MapxVs::new();
+    (0..10u64).for_each(|i| {
+        pnk!(hdr.version_create(VersionName(&i.to_be_bytes())));
+        let mut m = MapxOrd::new();
+        m.insert(i, i).unwrap();
+        pnk!(hdr.insert(i, m));
+    });
+
+    pnk!(vsdb_checkpoint(CHECKPOINT_DIR));
+    assert!(vsdb_checkpoint(CHECKPOINT_DIR).is_err());
+
+    // changes after the checkpoint will be lost after restoring
+    pnk!(hdr.version_create(VersionName(b"lost")));
+    pnk!(hdr.insert(10, MapxOrd::new()));
+    hdr.get(&0).unwrap().insert(0, 100).unwrap();
+
+    // the DB has been opened
+    assert!(vsdb_restore(CHECKPOINT_DIR).is_err());
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["checkpoint_and_restore", "--exact"])
+        .env(ENV_ENCODED_HDR, hex::encode(hdr.encode()))
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn restore_and_check(hdr: &str) {
+    vsdb_set_base_dir("/tmp/.vsdb/checkpoint_test_restored").unwrap();
+    let custom_file = format!("{}/kept", vsdb_get_custom_dir());
+    pnk!(fs::write(&custom_file, b"kept"));
+
+    assert!(vsdb_restore("/tmp/.vsdb/checkpoint_test_nonexist").is_err());
+    pnk!(vsdb_restore(CHECKPOINT_DIR));
+
+    // the custom dir is kept, and no temporary dirs are left
+    assert_eq!(b"kept", &pnk!(fs::read(&custom_file))[..]);
+    assert!(!Path::new("/tmp/.vsdb/.checkpoint_test_restored.restoring").exists());
+    assert!(!Path::new("/tmp/.vsdb/.checkpoint_test_restored.replaced").exists());
+
+    let mut hdr = pnk!(<MapxVs<u64, MapxOrd<u64, u64>> as ValueEnDe>::decode(
+        &hex::decode(hdr).unwrap()
+    ));
+
+    assert_eq!(10, hdr.len());
+    assert!(!hdr.version_exists(VersionName(b"lost")));
+    (0..10u64).for_each(|i| {
+        assert!(hdr.version_exists(VersionName(&i.to_be_bytes())));
+        assert_eq!(Some(i), hdr.get(&i).unwrap().get(&i));
+    });
+
+    // the allocators have been restored,
+    // so new instances will never overwrite the existing ones
+    pnk!(hdr.version_create(VersionName(b"new")));
+    (0..10u64).for_each(|i| {
+        let mut m = MapxOrd::new();
+        m.insert(i, i + 1).unwrap();
+        pnk!(hdr.insert(i + 10, m));
+    });
+    (0..10u64).for_each(|i| {
+        assert_eq!(Some(i), hdr.get(&i).unwrap().get(&i));
+        assert_eq!(Some(i + 1), hdr.get(&(i + 10)).unwrap().get(&i));
+    });
+}
@@ -0,0 +1,134 @@
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::panic;
+use vsdb::{
+    vsdb_set_base_dir, Codec, InstanceOpts, Mapx, MapxOrd, MapxOrdVs, MapxVs,
+    ValueEnDe, VersionName, VsMgmt,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+struct Account {
+    id: u64,
+    name: String,
+    balances: Vec<u128>,
+    frozen: Option<bool>,
+}
+
+#[test]
+fn value_codecs() {
+    vsdb_set_base_dir("/tmp/.vsdb/codec_test").unwrap();
+
+    let (codecs, disabled): (Vec<_>, Vec<_>) =
+        CODECS.iter().map(|(c, _)| *c).partition(|c| c.is_enabled());
+    assert!(codecs.contains(&Codec::Default));
+
+    // an instance can not be created with a disabled codec
+    disabled.into_iter().for_each(|codec| {
+        let opts = InstanceOpts {
+            codec,
+            ..Default::default()
+        };
+        assert!(panic::catch_unwind(|| MapxOrd::<u64, u64>::new_with_opts(&opts)).is_err());
+    });
+
+    codecs.iter().copied().for_each(basic);
+    codecs.into_iter().for_each(versioned);
+}
+
+// the tags are recorded in the configs of the instances,
+// so they must never be changed
+const CODECS: [(Codec, u8); 6] = [
+    (Codec::Default, 0),
+    (Codec::Msgpack, 1),
+    (Codec::Bcs, 2),
+    (Codec::Bincode, 3),
+    (Codec::Postcard, 4),
+    (Codec::Json, 5),
+];
+
+#[test]
+fn codec_tags() {
+    CODECS.into_iter().for_each(|(codec, tag)| {
+        assert_eq!(tag, u8::from(codec));
+        assert_eq!(codec, pnk!(Codec::try_from(tag)));
+        assert_eq!(tag.encode(), codec.encode());
+        assert_eq!(codec, pnk!(<Codec as ValueEnDe>::decode(&tag.encode())));
+    });
+    assert!(Codec::try_from(CODECS.len() as u8).is_err());
+    assert!(<Codec as ValueEnDe>::decode(&(CODECS.len() as u8).encode()).is_err());
+}
+
+fn basic(codec: Codec) {
+    let opts = InstanceOpts {
+        codec,
+        ..Default::default()
+    };
+
+    let mut m = Mapx::new_with_opts(&opts);
+    (0..100u64).for_each(|i| {
+        assert!(m.insert(i, account(i)).unwrap().is_none());
+    });
+    assert_eq!(Some(account(0)), m.insert(0, account(0)).unwrap());
+
+    let mut m = pnk!(<Mapx<u64, Account> as ValueEnDe>::decode(&m.encode()));
+    (0..100u64).for_each(|i| {
+        assert_eq!(Some(account(i)), m.get(&i));
+    });
+    m.get_mut(&1).unwrap().frozen = None;
+    assert_eq!(None, m.get(&1).unwrap().frozen);
+
+    let mut m = MapxOrd::new_with_opts(&opts);
+    (0..100u64).for_each(|i| {
+        m.insert(i, account(i)).unwrap();
+    });
+    (0..100u64).for_each(|i| {
+        m.get_mut(&i).unwrap().balances.push(0);
+    });
+
+    let m = pnk!(<MapxOrd<u64, Account> as ValueEnDe>::decode(&m.encode()));
+    assert!(m.iter().all(|(i, v)| {
+        let mut a = account(i);
+        a.balances.push(0);
+        a == v
+    }));
+    assert_eq!(Some(3), m.range(3..).next().map(|(_, v)| v.id));
+}
+
+fn versioned(codec: Codec) {
+    let opts = InstanceOpts {
+        codec,
+        ..Default::default()
+    };
+
+    let mut m = MapxVs::new_with_opts(&opts);
+    pnk!(m.version_create(VersionName(b"v0")));
+    (0..10u64).for_each(|i| {
+        pnk!(m.insert(i, account(i)));
+    });
+    pnk!(m.version_create(VersionName(b"v1")));
+    assert_eq!(Some(account(1)), pnk!(m.remove(&1)));
+
+    let m = pnk!(<MapxVs<u64, Account> as ValueEnDe>::decode(&m.encode()));
+    assert!(m.get(&1).is_none());
+    assert_eq!(9, m.iter().count());
+    assert!(m.iter().all(|(i, v)| v == account(i)));
+
+    let mut m = MapxOrdVs::new_with_opts(&opts);
+    pnk!(m.version_create(VersionName(b"v0")));
+    (0..10u64).for_each(|i| {
+        pnk!(m.insert(i, account(i)));
+    });
+
+    let m = pnk!(<MapxOrdVs<u64, Account> as ValueEnDe>::decode(&m.encode()));
+    assert_eq!(Some(account(9)), m.last().map(|(_, v)| v));
+    assert!(m.range(5..).all(|(i, v)| v == account(i)));
+}
+
+fn account(i: u64) -> Account {
+    Account {
+        id: i,
+        name: format!("account-{}", i),
+        balances: vec![i as u128, u128::MAX - i as u128],
+        frozen: if i < 50 { Some(true) } else { None },
+    }
+}
@@ -0,0 +1,41 @@
+use ruc::*;
+use vsdb::{
+    vsdb_compact_all, vsdb_compact_instance, vsdb_set_base_dir,
+    vsdb_set_compact_after_prune, MapxOrd, MapxVs, VersionName, VsMgmt,
+};
+
+#[test]
+fn compact_after_removals() {
+    vsdb_set_base_dir("/tmp/.vsdb/compact_test").unwrap();
+    vsdb_set_compact_after_prune(true);
+
+    let cnt = 1000u64;
+
+    let mut hdr = MapxOrd::new();
+    (0..cnt).for_each(|i| {
+        hdr.insert(i, i).unwrap();
+    });
+    (0..cnt).filter(|i| 0 != i % 10).for_each(|i| {
+        hdr.remove(&i).unwrap();
+    });
+    vsdb_compact_instance(&hdr);
+    assert_eq!((cnt / 10) as usize, hdr.len());
+    assert!(hdr.iter().all(|(k, v)| 0 == k % 10 && k == v));
+
+    let mut hdr_vs = MapxVs::new();
+    (0..cnt).for_each(|i| {
+        pnk!(hdr_vs.version_create(VersionName(&i.to_be_bytes())));
+        pnk!(hdr_vs.insert(i % 10, i));
+    });
+    pnk!(hdr_vs.prune(Some(10)));
+    assert_eq!(10, pnk!(hdr_vs.version_list()).len());
+    (0..10).for_each(|i| {
+        assert_eq!(cnt - 10 + i, hdr_vs.get(&i).unwrap());
+    });
+
+    hdr.clear().unwrap();
+    vsdb_compact_all();
+    assert!(hdr.is_empty());
+    assert!(hdr.iter().next().is_none());
+    assert_eq!(10, hdr_vs.len());
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "zstd_compress")]
+
+use ruc::*;
+use vsdb::{
+    basic::mapx_raw::MapxRaw, vsdb_gc, vsdb_set_base_dir, BranchName, Compression,
+    GcMark, InstanceOpts, Mapx, MapxVs, ValueEnDe, VersionName, VsMgmt,
+};
+
+#[test]
+fn zstd_compression() {
+    vsdb_set_base_dir("/tmp/.vsdb/compression_test").unwrap();
+
+    let plain = InstanceOpts {
+        compression: Compression::Zstd {
+            level: 3,
+            dict: None,
+        },
+        ..Default::default()
+    };
+    let with_dict = InstanceOpts {
+        compression: Compression::Zstd {
+            level: 3,
+            dict: Some(val(0).repeat(4).into_bytes()),
+        },
+        ..Default::default()
+    };
+
+    // raw values
+    let mut hdr = MapxRaw::new_with_opts(&plain);
+    (0..100u64).for_each(|i| {
+        hdr.insert(&i.to_be_bytes(), val(i).as_bytes()).unwrap();
+    });
+    let hdr = pnk!(<MapxRaw as ValueEnDe>::decode(&hdr.encode()));
+    (0..100u64).for_each(|i| {
+        assert_eq!(val(i).as_bytes(), &hdr.get(&i.to_be_bytes()).unwrap()[..]);
+    });
+    assert_eq!(100, hdr.iter().count());
+
+    // typed values with a dictionary
+    let mut m = Mapx::new_with_opts(&with_dict);
+    (0..100u64).for_each(|i| {
+        m.insert(i, val(i)).unwrap();
+    });
+    let m = pnk!(<Mapx<u64, String> as ValueEnDe>::decode(&m.encode()));
+    (0..100u64).for_each(|i| {
+        assert_eq!(Some(val(i)), m.get(&i));
+    });
+    assert!(m.iter().all(|(i, v)| v == val(i)));
+
+    // versioned values with a dictionary
+    let mut vs: MapxVs<u64, String> = MapxVs::new_with_opts(&with_dict);
+    pnk!(vs.version_create(VersionName(b"v0")));
+    (0..10u64).for_each(|i| {
+        pnk!(vs.insert(i, val(i)));
+    });
+    pnk!(vs.version_create(VersionName(b"v1")));
+    (0..10u64).for_each(|i| {
+        pnk!(vs.insert(i, val(i + 1)));
+    });
+
+    // the dictionaries are reachable from their instances
+    let roots: [&dyn GcMark; 3] = [&hdr, &m, &vs];
+    pnk!(vsdb_gc(&roots, false));
+
+    let vs = pnk!(<MapxVs<u64, String> as ValueEnDe>::decode(&vs.encode()));
+    (0..10u64).for_each(|i| {
+        assert_eq!(Some(val(i + 1)), vs.get(&i));
+        assert_eq!(
+            Some(val(i)),
+            vs.get_by_branch_version(&i, BranchName(b"master"), VersionName(b"v0"))
+        );
+    });
+    assert_eq!(Some(val(5)), m.get(&5));
+}
+
+fn val(i: u64) -> String {
+    format!(
+        "{{\"id\":{},\"name\":\"account-{}\",\"balance\":{},\"frozen\":false}}",
+        i,
+        i,
+        i * 1000
+    )
+}
@@ -0,0 +1,215 @@
+#![cfg(feature = "encryption")]
+
+use ruc::*;
+use std::{env, fs, process::Command};
+use vsdb::{
+    basic::{mapx_raw::MapxRaw, orphan::Orphan},
+    vsdb_flush, vsdb_set_base_dir, vsdb_set_encryption_key, Dequex, DequexVs,
+    EncryptionKey, MapxOrd, MapxOrdVs, MapxVs, MultiMapx, MultiMapxVs, OrphanVs,
+    ValueEnDe, VecxVs, VsMgmt,
+};
+
+const BASE_DIR: &str = "/tmp/.vsdb/encryption_test";
+const HDR_PATH: &str = "/tmp/.vsdb/encryption_test_hdr";
+const PLAIN_HDRS_PATH: &str = "/tmp/.vsdb/encryption_test_plain_hdrs";
+
+// each role must be played in a new process
+const ENV_ROLE: &str = "VSDB_ENCRYPTION_TEST_ROLE";
+
+const KEY: EncryptionKey = [9; 32];
+
+#[test]
+fn encryption() {
+    vsdb_set_base_dir(BASE_DIR).unwrap();
+
+    match env::var(ENV_ROLE).as_deref() {
+        Ok("writer") => return write(),
+        Ok("tamperer") => return tamper(),
+        Ok("plain_writer") => return write_plain(),
+        _ => {}
+    }
+
+    assert!(spawn("writer"));
+    assert!(spawn("tamperer"));
+    assert!(spawn("plain_writer"));
+
+    pnk!(vsdb_set_encryption_key(&KEY));
+    let hdr = load_hdr();
+
+    assert_eq!(Some(val(0)), hdr.get(&key(0)));
+    assert_eq!(Some(val(0)), pnk!(hdr.try_get(&key(0))));
+    assert_eq!(None, pnk!(hdr.try_get(&key(999))));
+
+    // overwritten by a plaintext value
+    assert!(hdr.try_get(&key(1)).is_err());
+
+    // moved from another key, the ciphertext itself is valid
+    assert!(hdr.try_get(&key(2)).is_err());
+
+    // other values are intact, and still in order
+    let lo = key(3);
+    assert!(hdr
+        .range(&lo[..]..)
+        .zip(3..100u64)
+        .all(|((k, v), i)| k[..] == key(i) && v == val(i)));
+    assert_eq!(97, hdr.range(&lo[..]..).count());
+
+    // the broken values are yielded as errors
+    let items = hdr.try_iter().collect::<Vec<_>>();
+    assert_eq!(100, items.len());
+    assert!(items[1].is_err() && items[2].is_err());
+    assert!(items
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| ![1, 2].contains(i))
+        .all(|(i, kv)| pnk!(kv) == (key(i as u64).into(), val(i as u64))));
+    let hi = key(2);
+    assert!(hdr.try_range(..=&hi[..]).next_back().unwrap().is_err());
+    assert_eq!(1, hdr.try_range(..=&hi[..]).filter(|kv| kv.is_ok()).count());
+
+    // typed values
+    let mut m = MapxOrd::new();
+    m.insert(1u64, "a".to_owned()).unwrap();
+    assert_eq!(Some("a".to_owned()), pnk!(m.try_get(&1)));
+    assert_eq!((1, "a".to_owned()), pnk!(pnk!(m.try_iter().next())));
+    assert_eq!(0, m.try_range(2..).count());
+
+    // written without the key, so none of the values can be opened
+    let (mv, mov, vv, ov, dv, mmv, o, d, mm) = load_plain_hdrs();
+    let b = "main".into();
+    let v = "v0".into();
+
+    assert!(mv.try_get(&1).is_err());
+    assert!(mv.try_get_by_branch(&1, b).is_err());
+    assert!(mv.try_get_by_branch_version(&1, b, v).is_err());
+    assert!(mv.try_iter().all(|kv| kv.is_err()));
+    assert!(mv.try_iter_by_branch(b).next().unwrap().is_err());
+    assert!(mv.try_iter_by_branch_version(b, v).next_back().unwrap().is_err());
+
+    assert!(mov.try_get(&1).is_err());
+    assert!(mov.try_iter().next().unwrap().is_err());
+    assert!(vv.try_get(0).is_err());
+    assert!(vv.try_iter().next().unwrap().is_err());
+    assert!(ov.try_get_value().is_err());
+    assert!(ov.try_get_value_by_branch(b).is_err());
+
+    // the bounds of the queue can not be opened either
+    assert!(dv.try_get(0).is_err());
+    assert!(dv.try_iter().is_err());
+    assert!(d.try_get(0).is_err());
+    assert!(d.try_iter().is_err());
+
+    assert!(mmv.try_iter().next().unwrap().is_err());
+    assert!(mmv.try_get_all(&1).next().unwrap().is_err());
+    assert!(mm.try_iter().next().unwrap().is_err());
+    assert!(mm.try_get_all(&1).next_back().unwrap().is_err());
+
+    assert!(o.try_get_value().is_err());
+
+    // the DB has been opened
+    assert!(vsdb_set_encryption_key(&KEY).is_err());
+}
+
+fn write() {
+    pnk!(vsdb_set_encryption_key(&KEY));
+    assert!(vsdb_set_encryption_key(&KEY).is_err());
+
+    let mut hdr = MapxRaw::new();
+    (0..100u64).for_each(|i| {
+        hdr.insert(&key(i), &val(i)).unwrap();
+    });
+    pnk!(vsdb_flush());
+    pnk!(fs::write(HDR_PATH, hdr.encode()));
+}
+
+// open the DB without the key, and modify the values directly
+fn tamper() {
+    let mut hdr = load_hdr();
+
+    // the values are not stored as plaintext
+    let v0 = hdr.get(&key(0)).unwrap();
+    assert_ne!(v0, val(0));
+
+    hdr.insert(&key(1), &val(1)).unwrap();
+    hdr.insert(&key(2), &v0).unwrap();
+    pnk!(vsdb_flush());
+}
+
+type PlainHdrs = (
+    MapxVs<u64, u64>,
+    MapxOrdVs<u64, u64>,
+    VecxVs<u64>,
+    OrphanVs<u64>,
+    DequexVs<u64>,
+    MultiMapxVs<u64, u64>,
+    Orphan<u64>,
+    Dequex<u64>,
+    MultiMapx<u64, u64>,
+);
+
+// write some containers without the key, and read them with the key
+fn write_plain() {
+    let b = "main".into();
+    let v = "v0".into();
+
+    let mut mv = MapxVs::new();
+    pnk!(mv.version_create(v));
+    pnk!(mv.insert(1, 1));
+    pnk!(mv.branch_create(b, "v1".into(), false));
+
+    let mut mov = MapxOrdVs::new();
+    pnk!(mov.version_create(v));
+    pnk!(mov.insert(1, 1));
+
+    let mut vv = VecxVs::new();
+    pnk!(vv.version_create(v));
+    vv.push(1);
+
+    let mut ov = OrphanVs::new();
+    pnk!(ov.version_create(v));
+    pnk!(ov.set_value(1));
+    pnk!(ov.branch_create(b, "v1".into(), false));
+
+    let mut dv = DequexVs::new();
+    pnk!(dv.version_create(v));
+    dv.push_back(1);
+
+    let mut mmv = MultiMapxVs::new();
+    pnk!(mmv.version_create(v));
+    pnk!(mmv.insert(1, 1));
+
+    let mut d = Dequex::new();
+    d.push_back(1).unwrap();
+
+    let mut mm = MultiMapx::new();
+    mm.insert(1, 1).unwrap();
+
+    let hdrs: PlainHdrs = (mv, mov, vv, ov, dv, mmv, Orphan::new(1), d, mm);
+    pnk!(vsdb_flush());
+    pnk!(fs::write(PLAIN_HDRS_PATH, hdrs.encode()));
+}
+
+fn load_plain_hdrs() -> PlainHdrs {
+    pnk!(<PlainHdrs as ValueEnDe>::decode(&pnk!(fs::read(PLAIN_HDRS_PATH))))
+}
+
+fn load_hdr() -> MapxRaw {
+    pnk!(<MapxRaw as ValueEnDe>::decode(&pnk!(fs::read(HDR_PATH))))
+}
+
+fn key(i: u64) -> [u8; 8] {
+    i.to_be_bytes()
+}
+
+fn val(i: u64) -> Box<[u8]> {
+    Box::new(i.to_le_bytes())
+}
+
+fn spawn(role: &str) -> bool {
+    Command::new(env::current_exe().unwrap())
+        .args(["encryption", "--exact"])
+        .env(ENV_ROLE, role)
+        .status()
+        .unwrap()
+        .success()
+}
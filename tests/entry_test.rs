@@ -0,0 +1,107 @@
+use vsdb::{
+    basic::mapx_ord_rawkey::MapxOrdRawKey, basic_multi_key::mapx_double_key::MapxDk,
+    versioned::mapx::MapxVs, versioned_multi_key::mapx_raw::MapxRawMkVs,
+    vsdb_set_base_dir, BranchName, Entry, Mapx, MapxOrd, VersionName, VsMgmt,
+};
+
+#[test]
+fn entry_api() {
+    vsdb_set_base_dir("/tmp/.vsdb/entry_test").unwrap();
+
+    basic_maps();
+    multi_key_maps();
+    versioned_maps();
+}
+
+fn basic_maps() {
+    let mut m: Mapx<u32, u32> = Mapx::new();
+
+    *m.entry(1).or_insert(10) += 1;
+    assert_eq!(m.get(&1), Some(11));
+    *m.entry(1).or_insert(100) += 1;
+    assert_eq!(m.get(&1), Some(12));
+
+    assert_eq!(*m.entry(2).or_default(), 0);
+    assert_eq!(m.get(&2), Some(0));
+    assert_eq!(*m.entry(3).or_insert_with_key(|k| k * 3), 9);
+
+    m.entry(1).and_modify(|v| *v = 0).or_insert(7);
+    assert_eq!(m.get(&1), Some(0));
+    m.entry(4).and_modify(|v| *v = 0).or_insert(7);
+    assert_eq!(m.get(&4), Some(7));
+
+    match m.entry(5) {
+        Entry::Vacant(e) => {
+            assert_eq!(e.key(), &5);
+            assert_eq!(e.into_key(), 5);
+        }
+        Entry::Occupied(_) => panic!("should be vacant"),
+    }
+    assert!(!m.contains_key(&5));
+
+    match m.entry(3) {
+        Entry::Occupied(mut e) => {
+            assert_eq!(e.get(), &9);
+            assert_eq!(e.insert(90), 9);
+            assert_eq!(e.get(), &90);
+        }
+        Entry::Vacant(_) => panic!("should be occupied"),
+    }
+    assert_eq!(m.get(&3), Some(90));
+
+    match m.entry(3) {
+        Entry::Occupied(e) => assert_eq!(e.remove_entry(), (3, 90)),
+        Entry::Vacant(_) => panic!("should be occupied"),
+    }
+    assert!(!m.contains_key(&3));
+
+    let mut m: MapxOrd<u32, String> = MapxOrd::new();
+    m.entry(1).or_insert_with(|| "a".to_owned()).push('b');
+    assert_eq!(m.get(&1).unwrap(), "ab");
+    if let Entry::Occupied(e) = m.entry(1) {
+        assert_eq!(e.remove(), "ab");
+    }
+    assert!(m.is_empty());
+
+    let mut m: MapxOrdRawKey<u32> = MapxOrdRawKey::new();
+    assert_eq!(&m.entry(vec![1].into()).key()[..], &[1]);
+    *m.entry(vec![1].into()).or_insert(1) += 1;
+    assert_eq!(m.get(&[1]), Some(2));
+}
+
+fn multi_key_maps() {
+    let mut m: MapxDk<u32, u32, u32> = MapxDk::new();
+    *m.entry((1, 2)).or_insert(0) += 5;
+    *m.entry((1, 2)).or_insert(0) += 5;
+    assert_eq!(m.get(&(&1, &2)), Some(10));
+    if let Entry::Occupied(e) = m.entry((1, 2)) {
+        e.remove();
+    }
+    assert!(m.get(&(&1, &2)).is_none());
+}
+
+fn versioned_maps() {
+    let mut m: MapxVs<u32, u32> = MapxVs::new();
+    m.version_create(VersionName(b"v0")).unwrap();
+    *m.entry(1).or_insert(1) += 1;
+    assert_eq!(m.get(&1), Some(2));
+
+    m.version_create(VersionName(b"v1")).unwrap();
+    m.entry(1).and_modify(|v| *v *= 10);
+    assert_eq!(m.get(&1), Some(20));
+    let main = m.branch_get_default();
+    assert_eq!(
+        m.get_by_branch_version(&1, BranchName(&main.0), VersionName(b"v0")),
+        Some(2)
+    );
+
+    let mut m = MapxRawMkVs::new(2);
+    m.version_create(VersionName(b"v0")).unwrap();
+    let key = vec![vec![1].into(), vec![2].into()];
+    m.entry(key.clone()).or_insert(vec![3].into());
+    assert_eq!(m.get(&[&[1], &[2]]).unwrap().to_vec(), vec![3]);
+    if let Entry::Occupied(e) = m.entry(key) {
+        assert_eq!(e.remove().to_vec(), vec![3]);
+    }
+    assert!(m.get(&[&[1], &[2]]).is_none());
+}
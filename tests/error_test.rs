@@ -0,0 +1,110 @@
+use vsdb::{
+    versioned::mapx_raw::MapxRawVs, vsdb_set_base_dir, BranchName, MapxOrd,
+    ParentBranchName, ValueEnDe, VersionName, VsMgmt, VsdbError,
+};
+
+#[test]
+fn typed_errors() {
+    vsdb_set_base_dir("/tmp/.vsdb/error_test").unwrap();
+
+    codec_errors();
+    mgmt_errors();
+}
+
+fn codec_errors() {
+    let mut m: MapxOrd<u32, u64> = MapxOrd::new();
+    m.insert(7, 123).unwrap();
+
+    // a handle of the same instance, but with a mistyped value
+    let mistyped: MapxOrd<u32, String> = ValueEnDe::decode(&m.encode()).unwrap();
+
+    match mistyped.try_get(&7) {
+        Err(VsdbError::Codec {
+            instance,
+            key,
+            type_name,
+            ..
+        }) => {
+            assert!(instance.is_some());
+            assert_eq!(key, Some(7u32.to_be_bytes().to_vec()));
+            assert!(type_name.contains("String"));
+        }
+        e => panic!("unexpected: {:?}", e),
+    }
+    assert!(matches!(mistyped.try_get(&8), Ok(None)));
+
+    assert!(matches!(
+        <u64 as ValueEnDe>::decode(&[0xc1]),
+        Err(VsdbError::Codec { .. })
+    ));
+}
+
+fn mgmt_errors() {
+    let mut hdr = MapxRawVs::new();
+    hdr.version_create(VersionName(b"v0")).unwrap();
+
+    assert_eq!(
+        hdr.version_create(VersionName(b"v0")),
+        Err(VsdbError::VersionExists {
+            version: b"v0".to_vec()
+        })
+    );
+    assert_eq!(
+        hdr.version_create_by_branch(VersionName(b"v1"), BranchName(b"b0")),
+        Err(VsdbError::BranchNotFound {
+            branch: b"b0".to_vec()
+        })
+    );
+    assert_eq!(
+        hdr.version_has_change_set(VersionName(b"v1")),
+        Err(VsdbError::VersionNotFound {
+            version: b"v1".to_vec()
+        })
+    );
+
+    hdr.branch_create(BranchName(b"b0"), VersionName(b"v1"), false)
+        .unwrap();
+    assert_eq!(
+        hdr.branch_create(BranchName(b"b0"), VersionName(b"v2"), false),
+        Err(VsdbError::BranchExists {
+            branch: b"b0".to_vec()
+        })
+    );
+    assert_eq!(
+        hdr.branch_create_by_base_branch_version(
+            BranchName(b"b1"),
+            VersionName(b"v2"),
+            ParentBranchName(b"b0"),
+            VersionName(b"v9"),
+            false,
+        ),
+        Err(VsdbError::VersionNotFound {
+            version: b"v9".to_vec()
+        })
+    );
+
+    // a new version on the target branch makes the merge unsafe
+    hdr.version_create(VersionName(b"v2")).unwrap();
+    let main = hdr.branch_get_default();
+    assert_eq!(
+        hdr.branch_merge_to(BranchName(b"b0"), BranchName(&main.0)),
+        Err(VsdbError::UnsafeMerge {
+            branch: b"b0".to_vec(),
+            target_branch: main.0.clone(),
+        })
+    );
+
+    // the base version is not on the branch
+    assert_eq!(
+        unsafe {
+            hdr.version_rebase_by_branch(VersionName(b"v1"), BranchName(&main.0))
+        },
+        Err(VsdbError::VersionNotFound {
+            version: b"v1".to_vec()
+        })
+    );
+
+    // the messages stay readable
+    let e = hdr.branch_remove(BranchName(b"b9")).unwrap_err();
+    assert_eq!(e.to_string(), "branch not found: b9");
+}
@@ -0,0 +1,108 @@
+use primitive_types_0_11::H512;
+use ruc::*;
+use vsdb::{
+    basic::{mapx_raw::MapxRaw, vecx_raw::VecxRaw},
+    basic_multi_key::mapx_double_key::MapxDk,
+    vsdb_gc, vsdb_set_base_dir, GcMark, GcMarks, Mapx, MapxOrd, MapxVs, Vecx,
+    VersionName, VsMgmt,
+};
+
+struct Roots {
+    a: MapxOrd<u64, MapxOrd<u64, Vecx<u8>>>,
+    b: MapxVs<u32, Mapx<u32, u32>>,
+    c: MapxDk<u8, u8, VecxRaw>,
+    d: Vec<H512>,
+}
+
+impl GcMark for Roots {
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.a.gc_mark(marks);
+        self.b.gc_mark(marks);
+        self.c.gc_mark(marks);
+        self.d.gc_mark(marks);
+    }
+}
+
+#[test]
+fn gc_orphaned_prefixes() {
+    vsdb_set_base_dir("/tmp/.vsdb/gc_test").unwrap();
+
+    let mut roots = Roots {
+        a: MapxOrd::new(),
+        b: MapxVs::new(),
+        c: MapxDk::new(),
+        d: vec![H512::zero()],
+    };
+
+    (0..10u64).for_each(|i| {
+        let mut l1 = MapxOrd::new();
+        (0..10u64).for_each(|j| {
+            let mut l2 = Vecx::new();
+            l2.push(j as u8).unwrap();
+            l1.insert(j, l2).unwrap();
+        });
+        roots.a.insert(i, l1).unwrap();
+    });
+
+    pnk!(roots.b.version_create(VersionName(b"v0")));
+    (0..10u32).for_each(|i| {
+        let mut m = Mapx::new();
+        m.insert(i, i).unwrap();
+        pnk!(roots.b.insert(i, m));
+    });
+    // the old value is still reachable from the history
+    pnk!(roots.b.version_create(VersionName(b"v1")));
+    pnk!(roots.b.insert(0, Mapx::new()));
+
+    let mut v = VecxRaw::new();
+    v.push(Box::new([0; 100])).unwrap();
+    roots.c.insert(&(&0, &0), &v);
+
+    // handles lost
+    {
+        let mut lost = MapxRaw::new();
+        lost.insert(&[0; 10], &[0; 100]).unwrap();
+        let mut lost = Mapx::new();
+        lost.insert(0u8, 0u8).unwrap();
+    }
+    let nested_cnt = 1 + roots.a.remove(&9).unwrap().unwrap().len();
+
+    let expected = 2 + nested_cnt;
+
+    let report = pnk!(vsdb_gc(&[&roots], true));
+    assert!(report.instances >= expected);
+    assert!(report.bytes > 100);
+
+    // nothing was removed in the dry-run mode
+    assert_eq!(report, pnk!(vsdb_gc(&[&roots], true)));
+
+    assert_eq!(report, pnk!(vsdb_gc(&[&roots], false)));
+    let report = pnk!(vsdb_gc(&[&roots], true));
+    assert_eq!(0, report.instances);
+    assert_eq!(0, report.bytes);
+
+    // all reachable data are intact
+    (0..9u64).for_each(|i| {
+        let l1 = roots.a.get(&i).unwrap();
+        (0..10u64).for_each(|j| {
+            assert_eq!(Some(j as u8), l1.get(&j).unwrap().get(0));
+        });
+    });
+    (1..10u32).for_each(|i| {
+        assert_eq!(Some(i), roots.b.get(&i).unwrap().get(&i));
+    });
+    assert_eq!(
+        Some(0),
+        roots
+            .b
+            .get_by_branch_version(&0, Default::default(), VersionName(b"v0"))
+            .unwrap()
+            .get(&0)
+    );
+    assert_eq!(
+        &[0; 100][..],
+        &roots.c.get(&(&0, &0)).unwrap().get(0).unwrap()[..]
+    );
+
+    assert!(vsdb_gc(&[], false).is_err());
+}
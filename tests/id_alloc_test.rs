@@ -0,0 +1,83 @@
+use ruc::*;
+use std::{env, fs, process, process::Command, thread};
+use vsdb::{
+    vsdb_flush, vsdb_set_base_dir, MapxOrd, MapxVs, ValueEnDe, VersionName, VsMgmt,
+};
+
+const BASE_DIR: &str = "/tmp/.vsdb/id_alloc_test";
+const HDR_PATH: &str = "/tmp/.vsdb/id_alloc_test_hdr";
+
+// the crashing must happen in a new process
+const ENV_CRASH: &str = "VSDB_ID_ALLOC_TEST_CRASH";
+
+type Hdr = MapxVs<u64, MapxOrd<u64, u64>>;
+
+#[test]
+fn ids_after_crash() {
+    vsdb_set_base_dir(BASE_DIR).unwrap();
+
+    if env::var(ENV_CRASH).is_ok() {
+        write_and_crash();
+    }
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["ids_after_crash", "--exact"])
+        .env(ENV_CRASH, "")
+        .status()
+        .unwrap();
+    assert!(!status.success());
+
+    let mut hdr = pnk!(<Hdr as ValueEnDe>::decode(&pnk!(fs::read(HDR_PATH))));
+
+    // new IDs never collide with the ones allocated before the crash
+    pnk!(hdr.version_create(VersionName(b"new")));
+    (10..20u64).for_each(|i| {
+        let mut m = MapxOrd::new();
+        m.insert(i, i).unwrap();
+        pnk!(hdr.insert(i, m));
+    });
+
+    let new_maps = thread::scope(|s| {
+        (0..4)
+            .map(|_| {
+                s.spawn(|| {
+                    (0..1000u64)
+                        .map(|i| {
+                            let mut m = MapxOrd::new();
+                            m.insert(i, i + 1).unwrap();
+                            m
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|t| t.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    (0..20u64).for_each(|i| {
+        let m = hdr.get(&i).unwrap();
+        assert_eq!(1, m.len());
+        assert_eq!(Some(i), m.get(&i));
+    });
+    new_maps.iter().enumerate().for_each(|(i, m)| {
+        let i = (i % 1000) as u64;
+        assert_eq!(1, m.len());
+        assert_eq!(Some(i + 1), m.get(&i));
+    });
+}
+
+fn write_and_crash() -> ! {
+    let mut hdr: Hdr = MapxVs::new();
+    (0..10u64).for_each(|i| {
+        pnk!(hdr.version_create(VersionName(&i.to_be_bytes())));
+        let mut m = MapxOrd::new();
+        m.insert(i, i).unwrap();
+        pnk!(hdr.insert(i, m));
+    });
+    pnk!(fs::write(HDR_PATH, hdr.encode()));
+    pnk!(vsdb_flush());
+
+    process::abort();
+}
@@ -0,0 +1,248 @@
+use vsdb::{
+    basic::{mapx_ord_rawvalue::MapxOrdRawValue, vecx_raw::VecxRaw},
+    basic_multi_key::{
+        mapx_double_key::MapxDk, mapx_raw::MapxRawMk, mapx_triple_key::MapxTk,
+    },
+    vsdb_set_base_dir, BranchName, Mapx, MapxOrd, MapxOrdVs, MapxVs, Vecx, VecxVs,
+    VersionName, VsMgmt,
+};
+
+#[test]
+fn mutable_iteration() {
+    vsdb_set_base_dir("/tmp/.vsdb/iter_mut_test").unwrap();
+
+    basic_maps();
+    basic_vecs();
+    multi_key_maps();
+    versioned_maps();
+    held_together();
+}
+
+fn basic_maps() {
+    let mut m: Mapx<u32, u32> = Mapx::new();
+    (0..10).for_each(|i| {
+        m.insert(i, i).unwrap();
+    });
+
+    for (k, mut v) in m.iter_mut() {
+        *v += k;
+    }
+    (0..10).for_each(|i| assert_eq!(m.get(&i), Some(2 * i)));
+
+    m.values_mut().for_each(|mut v| *v = 0);
+    assert!(m.values().all(|v| 0 == v));
+
+    let mut m: MapxOrd<u32, String> = MapxOrd::new();
+    (0..10).for_each(|i| {
+        m.insert(i, i.to_string()).unwrap();
+    });
+
+    m.range_mut(3..6).for_each(|(_, mut v)| v.push('x'));
+    for (k, v) in m.iter() {
+        if (3..6).contains(&k) {
+            assert_eq!(v, format!("{}x", k));
+        } else {
+            assert_eq!(v, k.to_string());
+        }
+    }
+
+    m.range_ref_mut(..=&1)
+        .rev()
+        .for_each(|(_, mut v)| v.clear());
+    assert!(m.get(&0).unwrap().is_empty());
+    assert!(m.get(&1).unwrap().is_empty());
+    assert_eq!(m.get(&2).unwrap(), "2");
+
+    m.values_mut().for_each(|mut v| *v = "y".to_owned());
+    assert!(m.values().all(|v| "y" == v));
+
+    let mut m: MapxOrdRawValue<u32> = MapxOrdRawValue::new();
+    (0..10u32).for_each(|i| {
+        m.insert(i, i.to_be_bytes().to_vec().into()).unwrap();
+    });
+    m.range_mut(5..).for_each(|(_, mut v)| *v = vec![0].into());
+    assert_eq!(m.values().filter(|v| v[..] == [0]).count(), 5);
+    m.values_mut().for_each(|mut v| *v = vec![1].into());
+    assert!(m.values().all(|v| v[..] == [1]));
+}
+
+fn basic_vecs() {
+    let mut v: Vecx<u64> = Vecx::new();
+    (0..10).for_each(|i| v.push(i).unwrap());
+    v.iter_mut().for_each(|mut i| *i *= 10);
+    assert!(v.iter().enumerate().all(|(idx, i)| i == 10 * idx as u64));
+
+    let mut v = VecxRaw::new();
+    (0..10u8).for_each(|i| v.push(vec![i].into()).unwrap());
+    v.iter_mut()
+        .rev()
+        .take(5)
+        .for_each(|mut i| *i = vec![0].into());
+    assert_eq!(v.iter().filter(|i| i[..] == [0]).count(), 6);
+}
+
+fn multi_key_maps() {
+    let mut m = MapxRawMk::new(2);
+    m.insert(&[&[1], &[1]], &[1]).unwrap();
+    m.insert(&[&[1], &[2]], &[2]).unwrap();
+    m.insert(&[&[2], &[1]], &[3]).unwrap();
+
+    m.iter_mut_op_with_key_prefix(
+        &mut |_, v| {
+            *v = vec![0].into();
+            Ok(())
+        },
+        &[&[1]],
+    )
+    .unwrap();
+    assert_eq!(&m.get(&[&[1], &[1]]).unwrap()[..], &[0]);
+    assert_eq!(&m.get(&[&[1], &[2]]).unwrap()[..], &[0]);
+    assert_eq!(&m.get(&[&[2], &[1]]).unwrap()[..], &[3]);
+
+    m.iter_mut_op_with_key_prefix(
+        &mut |_, v| {
+            *v = vec![9].into();
+            Ok(())
+        },
+        &[&[2], &[1]],
+    )
+    .unwrap();
+    assert_eq!(&m.get(&[&[2], &[1]]).unwrap()[..], &[9]);
+
+    let mut m: MapxDk<u32, u32, u32> = MapxDk::new();
+    for i in 0..3 {
+        for j in 0..3 {
+            m.insert(&(&i, &j), &(i * j));
+        }
+    }
+    m.iter_mut_op(&mut |(i, j), v| {
+        assert_eq!(*v, i * j);
+        *v += 1;
+        Ok(())
+    })
+    .unwrap();
+    m.iter_mut_op_with_key_prefix(
+        &mut |_, v| {
+            *v = 0;
+            Ok(())
+        },
+        &2,
+    )
+    .unwrap();
+    let mut cnt = 0;
+    m.iter_op(&mut |(i, j), v| {
+        cnt += 1;
+        if 2 == i {
+            assert_eq!(v, 0);
+        } else {
+            assert_eq!(v, i * j + 1);
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(cnt, 9);
+
+    let mut m: MapxTk<u32, u32, u32, u32> = MapxTk::new();
+    m.insert(&(&1, &1, &1), &1);
+    m.insert(&(&1, &2, &1), &1);
+    m.insert(&(&2, &1, &1), &1);
+    m.iter_mut_op_with_key_prefix(
+        &mut |(_, _, k3), v| {
+            *v += k3;
+            Ok(())
+        },
+        (&1, Some(&2)),
+    )
+    .unwrap();
+    assert_eq!(m.get(&(&1, &1, &1)), Some(1));
+    assert_eq!(m.get(&(&1, &2, &1)), Some(2));
+    m.iter_mut_op(&mut |_, v| {
+        *v = 7;
+        Ok(())
+    })
+    .unwrap();
+    m.iter_op(&mut |_, v| {
+        assert_eq!(v, 7);
+        Ok(())
+    })
+    .unwrap();
+}
+
+fn versioned_maps() {
+    let mut m: MapxVs<u32, u32> = MapxVs::new();
+    m.version_create(VersionName(b"v0")).unwrap();
+    (0..10).for_each(|i| {
+        m.insert(i, i).unwrap();
+    });
+
+    m.version_create(VersionName(b"v1")).unwrap();
+    for (k, mut v) in m.iter_mut() {
+        *v += k;
+    }
+    (0..10).for_each(|i| assert_eq!(m.get(&i), Some(2 * i)));
+
+    // the history stays unchanged
+    let main = m.branch_get_default();
+    (0..10).for_each(|i| {
+        assert_eq!(
+            m.get_by_branch_version(&i, BranchName(&main.0), VersionName(b"v0")),
+            Some(i)
+        )
+    });
+
+    let mut m: MapxOrdVs<u32, u32> = MapxOrdVs::new();
+    m.version_create(VersionName(b"v0")).unwrap();
+    (0..10).for_each(|i| {
+        m.insert(i, i).unwrap();
+    });
+    m.range_mut(5..).for_each(|(_, mut v)| *v = 0);
+    assert_eq!(m.iter().filter(|(_, v)| 0 == *v).count(), 6);
+    m.iter_mut().rev().take(1).for_each(|(k, mut v)| {
+        assert_eq!(k, 9);
+        *v = 99;
+    });
+    assert_eq!(m.get(&9), Some(99));
+
+    let mut v: VecxVs<u32> = VecxVs::new();
+    v.version_create(VersionName(b"v0")).unwrap();
+    (0..10).for_each(|i| v.push(i));
+    v.iter_mut().for_each(|mut i| *i += 1);
+    assert!(v.iter().enumerate().all(|(idx, i)| i == 1 + idx as u32));
+}
+
+// the yielded items do not borrow each other,
+// so they can be collected and written back all at once
+fn held_together() {
+    let mut m: Mapx<u32, u32> = Mapx::new();
+    (0..10).for_each(|i| {
+        m.insert(i, i).unwrap();
+    });
+    let mut items = m.iter_mut().collect::<Vec<_>>();
+    items.iter_mut().for_each(|(_, v)| **v += 1);
+    drop(items);
+    (0..10).for_each(|i| assert_eq!(m.get(&i), Some(1 + i)));
+
+    let mut v: Vecx<u64> = Vecx::new();
+    (0..10).for_each(|i| v.push(i).unwrap());
+    let mut items = v.iter_mut().collect::<Vec<_>>();
+    items.iter_mut().for_each(|i| **i *= 2);
+    drop(items);
+    assert!(v.iter().enumerate().all(|(idx, i)| i == 2 * idx as u64));
+
+    let mut v = VecxRaw::new();
+    (0..10u8).for_each(|i| v.push(vec![i].into()).unwrap());
+    let mut items = v.iter_mut().collect::<Vec<_>>();
+    items.iter_mut().for_each(|i| **i = vec![0].into());
+    drop(items);
+    assert!(v.iter().all(|i| i[..] == [0]));
+
+    let mut m: MapxVs<u32, u32> = MapxVs::new();
+    m.version_create(VersionName(b"v0")).unwrap();
+    (0..10).for_each(|i| {
+        m.insert(i, i).unwrap();
+    });
+    let mut items = m.iter_mut().collect::<Vec<_>>();
+    items.iter_mut().for_each(|(_, v)| **v *= 3);
+    drop(items);
+    (0..10).for_each(|i| assert_eq!(m.get(&i), Some(3 * i)));
+}
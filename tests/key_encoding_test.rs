@@ -0,0 +1,272 @@
+use primitive_types_0_11::{H160, H256, U256};
+use ruc::*;
+use vsdb::{vsdb_set_base_dir, KeyEnDeOrdered, MapxOrd, OrderedF32, OrderedF64};
+
+#[test]
+fn key_encoding() {
+    vsdb_set_base_dir("/tmp/.vsdb/key_encoding_test").unwrap();
+
+    signed_ints();
+    floats();
+    tuples();
+    big_ints();
+    structs();
+    legacy_bytes();
+}
+
+fn signed_ints() {
+    let mut m = MapxOrd::new();
+    (-100..100i64).for_each(|i| {
+        m.insert(i * 10, i).unwrap();
+    });
+
+    assert_eq!(Some((-1000, -100)), m.first());
+    assert_eq!(Some((990, 99)), m.last());
+    assert_eq!(Some((-10, -1)), m.get_le(&-5));
+    assert_eq!(Some((0, 0)), m.get_ge(&-5));
+    assert_eq!(Some((-1000, -100)), m.get_ge(&i64::MIN));
+    assert!(m.get_le(&-1001).is_none());
+    assert_eq!(
+        (-3..3).collect::<Vec<_>>(),
+        m.range(-30..30).map(|(_, v)| v).collect::<Vec<_>>()
+    );
+    let ks = m.iter().map(|(k, _)| k).collect::<Vec<_>>();
+    assert!(ks.windows(2).all(|w| w[0] < w[1]));
+
+    let mut m = MapxOrd::new();
+    [vec![-1i8, 0], vec![-1], vec![i8::MIN], vec![1], vec![0, 5]]
+        .into_iter()
+        .for_each(|k| {
+            m.insert(k, ()).unwrap();
+        });
+    assert_eq!(
+        vec![vec![i8::MIN], vec![-1], vec![-1, 0], vec![0, 5], vec![1]],
+        m.iter().map(|(k, _)| k).collect::<Vec<_>>()
+    );
+
+    let mut m = MapxOrd::new();
+    m.insert([-1i128, 1], ()).unwrap();
+    m.insert([-2, 1], ()).unwrap();
+    m.insert([1, -1], ()).unwrap();
+    assert_eq!(Some([-2, 1]), m.first().map(|(k, _)| k));
+    assert_eq!(Some([1, -1]), m.last().map(|(k, _)| k));
+
+    // the owned conversions are the same as the borrowed ones
+    let v = vec![-2i64, 0, 3];
+    let bytes = v.to_bytes();
+    assert_eq!(24, bytes.len());
+    assert_eq!(bytes, v.clone().into_bytes());
+    assert_eq!(v, pnk!(Vec::<i64>::from_bytes(bytes)));
+    let v = vec![1u32, u32::MAX].into_boxed_slice();
+    assert_eq!(v.to_bytes(), v.clone().into_bytes());
+    assert_eq!(v, pnk!(Box::<[u32]>::from_bytes(v.to_bytes())));
+    assert!(Vec::<u16>::from_bytes(Box::new([0; 3])).is_err());
+}
+
+fn floats() {
+    let fs = [
+        f64::NEG_INFINITY,
+        f64::MIN,
+        -1.5,
+        -f64::MIN_POSITIVE,
+        -0.0,
+        0.0,
+        f64::MIN_POSITIVE,
+        1.5,
+        f64::MAX,
+        f64::INFINITY,
+    ];
+
+    let mut m = MapxOrd::new();
+    fs.iter().rev().for_each(|f| {
+        m.insert(OrderedF64(*f), *f).unwrap();
+    });
+    assert_eq!(fs.len(), m.len());
+    assert!(
+        m.iter().zip(fs.iter()).all(
+            |((k, v), f)| k.0.to_bits() == f.to_bits() && v.to_bits() == f.to_bits()
+        )
+    );
+    assert_eq!(Some(-1.5), m.get_le(&OrderedF64(-1.0)).map(|(k, _)| k.0));
+    assert_eq!(Some(1.5), m.get_ge(&OrderedF64(1.0)).map(|(k, _)| k.0));
+    assert_eq!(5, m.range(OrderedF64(-1.5)..OrderedF64(0.1)).count());
+
+    let nan = OrderedF32(f32::NAN);
+    assert_eq!(nan, pnk!(OrderedF32::from_bytes(nan.to_bytes())));
+    assert!(OrderedF32(f32::INFINITY).to_bytes() < nan.to_bytes());
+    assert!(OrderedF32(-0.0).to_bytes() < OrderedF32(0.0).to_bytes());
+}
+
+fn tuples() {
+    let mut m = MapxOrd::new();
+    (0..10u64).rev().for_each(|i| {
+        m.insert((i / 3, [i as u8; 32]), i).unwrap();
+    });
+    assert_eq!(Some(40), <(u64, [u8; 32]) as KeyEnDeOrdered>::KEY_SIZE);
+    assert!(m.iter().map(|(_, v)| v).eq(0..10));
+    assert_eq!(Some(6), m.get_ge(&(2, [0; 32])).map(|(_, v)| v));
+    assert_eq!(Some(5), m.get_le(&(1, [u8::MAX; 32])).map(|(_, v)| v));
+
+    // the variable-size parts are escaped
+    let keys = [
+        ("".to_owned(), vec![0u8], -1i64),
+        ("".to_owned(), vec![0, 0], i64::MIN),
+        ("".to_owned(), vec![0, 1], 0),
+        ("a".to_owned(), vec![], 5),
+        ("a".to_owned(), vec![], 6),
+        ("a\0".to_owned(), vec![], 0),
+        ("ab".to_owned(), vec![0xff, 0], 0),
+    ];
+    assert!(<(String, Vec<u8>, i64) as KeyEnDeOrdered>::KEY_SIZE.is_none());
+
+    let mut m = MapxOrd::new();
+    keys.iter().rev().for_each(|k| {
+        m.insert(k.clone(), ()).unwrap();
+    });
+    assert!(m.iter().map(|(k, _)| k).eq(keys.iter().cloned()));
+    keys.iter().for_each(|k| {
+        assert_eq!(*k, pnk!(KeyEnDeOrdered::from_bytes(k.to_bytes())));
+    });
+
+    // the last part is not escaped
+    let mut m = MapxOrd::new();
+    m.insert((1u8, b"\0\0".to_vec()), ()).unwrap();
+    m.insert((0u8, vec![]), ()).unwrap();
+    assert_eq!(Some((1, vec![0, 0])), m.last().map(|(k, _)| k));
+    assert_eq!(3, (1u8, vec![0u8, 0]).to_bytes().len());
+
+    assert!(<(String, u8)>::from_slice(b"abc").is_err());
+    assert!(<(String, u8)>::from_slice(b"a\0\x01\0\0\x01").is_err());
+}
+
+fn big_ints() {
+    let mut m = MapxOrd::new();
+    [
+        U256::MAX,
+        U256::from(256),
+        U256::zero(),
+        U256::from(u128::MAX),
+        U256::one(),
+    ]
+    .into_iter()
+    .for_each(|i| {
+        m.insert(i, ()).unwrap();
+    });
+    assert_eq!(
+        vec![
+            U256::zero(),
+            U256::one(),
+            U256::from(256),
+            U256::from(u128::MAX),
+            U256::MAX
+        ],
+        m.iter().map(|(k, _)| k).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        Some(U256::from(256)),
+        m.get_ge(&U256::from(2)).map(|(k, _)| k)
+    );
+    assert!(U256::from_slice(&[0; 31]).is_err());
+
+    let mut m = MapxOrd::new();
+    (0..10u8).for_each(|i| {
+        m.insert((H160::repeat_byte(i % 2), H256::repeat_byte(i)), i).unwrap();
+    });
+    assert_eq!(
+        vec![0, 2, 4, 6, 8, 1, 3, 5, 7, 9],
+        m.iter().map(|(_, v)| v).collect::<Vec<_>>()
+    );
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, KeyEnDeOrdered)]
+pub struct Fixed {
+    height: u64,
+    hash: [u8; 4],
+}
+
+/// A key with parts of variable sizes.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, KeyEnDeOrdered)]
+pub struct Composite {
+    pub owner: String,
+    pub fixed: Fixed,
+    pub nonce: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, KeyEnDeOrdered)]
+struct Pair<T>(T, String);
+
+fn structs() {
+    assert_eq!(Some(12), Fixed::KEY_SIZE);
+    assert!(Composite::KEY_SIZE.is_none());
+
+    let key = |owner: &str, height, nonce| Composite {
+        owner: owner.to_owned(),
+        fixed: Fixed {
+            height,
+            hash: [height as u8; 4],
+        },
+        nonce,
+    };
+    let mut keys = vec![];
+    ["", "a", "ab", "b"].iter().for_each(|owner| {
+        (0..3).for_each(|height| {
+            (-2..2).for_each(|nonce| {
+                keys.push(key(owner, height, nonce));
+            });
+        });
+    });
+    assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+
+    let mut m = MapxOrd::new();
+    keys.iter().enumerate().rev().for_each(|(i, k)| {
+        m.insert(k.clone(), i).unwrap();
+    });
+    assert!(m.iter().map(|(_, i)| i).eq(0..keys.len()));
+    assert!(m.iter().all(|(k, i)| k == keys[i]));
+    assert_eq!(
+        Some(key("ab", 0, -2)),
+        m.get_ge(&key("a", 3, 0)).map(|(k, _)| k)
+    );
+    assert_eq!(6, m.range(key("a", 1, 0)..key("a", 2, 2)).count());
+
+    // tuple structures with generics
+    assert_eq!(None, Pair::<u8>::KEY_SIZE);
+    let pairs = [
+        Pair(-1i8, "b".to_owned()),
+        Pair(0, "".to_owned()),
+        Pair(0, "a".to_owned()),
+    ];
+    let mut m = MapxOrd::new();
+    pairs.iter().rev().for_each(|k| {
+        m.insert(k.clone(), ()).unwrap();
+    });
+    assert!(m.iter().map(|(k, _)| k).eq(pairs.iter().cloned()));
+}
+
+// the keys of the instances created by older versions
+fn legacy_bytes() {
+    fn check<K>(key: K, legacy: &[u8])
+    where
+        K: KeyEnDeOrdered + PartialEq + std::fmt::Debug,
+    {
+        let bytes = key.to_bytes();
+        assert_eq!(legacy, &pnk!(K::upgrade_legacy_bytes(&bytes))[..]);
+        assert_eq!(bytes, pnk!(K::upgrade_legacy_bytes(legacy)));
+        assert_eq!(key, pnk!(K::from_bytes(pnk!(K::upgrade_legacy_bytes(legacy)))));
+    }
+
+    check(-2i32, &(-2i32).to_be_bytes());
+    check(vec![-1i16, 2], &[0xff, 0xff, 0, 2]);
+
+    // the parts of composite keys are converted one by one
+    let pair = Pair(-1i64, "x".to_owned());
+    let mut legacy = pair.to_bytes().to_vec();
+    legacy[..8].copy_from_slice(&(-1i64).to_be_bytes());
+    check(pair, &legacy);
+    let mut legacy = (1u8, -1i8).to_bytes().to_vec();
+    legacy[1] = 0xff;
+    check((1u8, -1i8), &legacy);
+
+    // only the signed integers are touched
+    check("a".to_owned(), b"a");
+}
@@ -0,0 +1,56 @@
+use ruc::*;
+use std::{env, fs, process, process::Command};
+use vsdb::{vsdb_flush, vsdb_set_base_dir, MapxOrd, ValueEnDe};
+
+const BASE_DIR: &str = "/tmp/.vsdb/len_crash_test";
+const HDR_PATH: &str = "/tmp/.vsdb/len_crash_test_hdr";
+
+// the crashing must happen in a new process
+const ENV_CRASH: &str = "VSDB_LEN_CRASH_TEST_CRASH";
+
+const FLUSHED_CNT: u64 = 5000;
+
+#[test]
+fn len_after_crash() {
+    vsdb_set_base_dir(BASE_DIR).unwrap();
+
+    if env::var(ENV_CRASH).is_ok() {
+        write_and_crash();
+    }
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["len_after_crash", "--exact"])
+        .env(ENV_CRASH, "")
+        .status()
+        .unwrap();
+    assert!(!status.success());
+
+    let hdr = pnk!(<MapxOrd<u64, u64> as ValueEnDe>::decode(&pnk!(fs::read(
+        HDR_PATH
+    ))));
+
+    // some unflushed data may be lost,
+    // but the length record is always consistent with the data
+    assert!(FLUSHED_CNT as usize <= hdr.len());
+    assert_eq!(hdr.len(), hdr.iter().count());
+}
+
+fn write_and_crash() -> ! {
+    let mut hdr = MapxOrd::new();
+    pnk!(fs::write(HDR_PATH, hdr.encode()));
+
+    (0..FLUSHED_CNT).for_each(|i| {
+        hdr.insert(i, i).unwrap();
+    });
+    (0..FLUSHED_CNT).filter(|i| 0 == i % 3).for_each(|i| {
+        hdr.remove(&i).unwrap();
+        hdr.insert(i, i).unwrap();
+    });
+    pnk!(vsdb_flush());
+
+    (FLUSHED_CNT..2 * FLUSHED_CNT).for_each(|i| {
+        hdr.insert(i, i).unwrap();
+    });
+
+    process::abort();
+}
@@ -0,0 +1,103 @@
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use std::{env, fs, process, process::Command};
+use vsdb::{
+    vsdb_flush, vsdb_gc, vsdb_register_schema, vsdb_set_base_dir, GcMark, GcMarks,
+    MapxOrd, MapxVs, ValueEnDe, VersionName, VsMgmt,
+};
+
+// `vsdb_gc` removes everything that is not reachable from the roots,
+// so this test must own its DB
+const BASE_DIR: &str = "/tmp/.vsdb/migration_crash_test";
+const HDR_PATH: &str = "/tmp/.vsdb/migration_crash_test_hdr";
+
+// the crashing must happen in a new process
+const ENV_CRASH: &str = "VSDB_MIGRATION_CRASH_TEST_CRASH";
+
+const SCHEMA: &str = "u64_to_string";
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct Roots<V> {
+    m: MapxOrd<u64, V>,
+    vs: MapxVs<u64, V>,
+}
+
+impl<V: ValueEnDe + GcMark> GcMark for Roots<V> {
+    fn gc_mark(&self, marks: &mut GcMarks) {
+        self.m.gc_mark(marks);
+        self.vs.gc_mark(marks);
+    }
+}
+
+fn u64_to_string(v: &[u8]) -> Result<Vec<u8>> {
+    let v = <u64 as ValueEnDe>::decode(v).c(d!())?;
+    Ok(v.to_string().encode().into_vec())
+}
+
+#[test]
+fn migration_crash() {
+    vsdb_set_base_dir(BASE_DIR).unwrap();
+    pnk!(vsdb_register_schema(SCHEMA, &[u64_to_string]));
+
+    if env::var(ENV_CRASH).is_ok() {
+        migrate_and_crash();
+    }
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["migration_crash", "--exact"])
+        .env(ENV_CRASH, "")
+        .status()
+        .unwrap();
+    assert!(!status.success());
+
+    // the new handles have not been saved, the old ones are still usable
+    let Roots { mut m, mut vs } = load::<u64>();
+    assert_eq!(100, m.len());
+    assert!(m.iter().all(|(i, v)| i == v));
+    assert_eq!(100, vs.len());
+    assert!(vs.iter().all(|(i, v)| i == v));
+
+    pnk!(m.migrate_instance(SCHEMA));
+    pnk!(vs.migrate_instance(SCHEMA));
+    pnk!(fs::write(HDR_PATH, Roots { m, vs }.encode()));
+
+    let roots = load::<String>();
+
+    // the old instances and those left by the crashed migration
+    let report = pnk!(vsdb_gc(&[&roots], true));
+    assert!(0 < report.instances);
+    assert!(0 < report.bytes);
+
+    assert_eq!(report, pnk!(vsdb_gc(&[&roots], false)));
+    assert_eq!(0, pnk!(vsdb_gc(&[&roots], true)).instances);
+
+    let Roots { m, vs } = roots;
+    assert_eq!(100, m.len());
+    assert!(m.iter().all(|(i, v)| i.to_string() == v));
+    assert_eq!(100, vs.len());
+    assert!(vs.iter().all(|(i, v)| i.to_string() == v));
+}
+
+fn migrate_and_crash() -> ! {
+    let mut m = MapxOrd::new();
+    let mut vs = MapxVs::new();
+    pnk!(vs.version_create(VersionName(b"v0")));
+    (0..100u64).for_each(|i| {
+        m.insert(i, i).unwrap();
+        pnk!(vs.insert(i, i));
+    });
+    let mut roots = Roots { m, vs };
+    pnk!(fs::write(HDR_PATH, roots.encode()));
+
+    pnk!(roots.m.migrate_instance(SCHEMA));
+    pnk!(roots.vs.migrate_instance(SCHEMA));
+    pnk!(vsdb_flush());
+
+    // crash before the new handles have been saved
+    process::abort();
+}
+
+fn load<V: ValueEnDe>() -> Roots<V> {
+    pnk!(<Roots<V> as ValueEnDe>::decode(&pnk!(fs::read(HDR_PATH))))
+}
@@ -0,0 +1,119 @@
+use ruc::*;
+use std::{env, fs, panic, process::Command};
+use vsdb::{
+    vsdb_flush, vsdb_set_base_dir, vsdb_set_open_mode,
+    vsdb_try_catch_up_with_primary, MapxOrd, MapxVs, OpenMode, ValueEnDe,
+    VersionName, VsMgmt,
+};
+
+const BASE_DIR: &str = "/tmp/.vsdb/open_mode_test";
+const HDR_PATH: &str = "/tmp/.vsdb/open_mode_test_hdr";
+const MISSING_DIR: &str = "/tmp/.vsdb/open_mode_test_missing";
+
+// each role must be played in a new process
+const ENV_ROLE: &str = "VSDB_OPEN_MODE_TEST_ROLE";
+
+#[test]
+fn open_modes() {
+    match env::var(ENV_ROLE).as_deref() {
+        Ok("writer") => return write(),
+        Ok("second_writer") => return second_writer(),
+        Ok("missing_reader") => return missing_reader(),
+        _ => {}
+    }
+
+    // a read-only open never creates the DB
+    info_omit!(fs::remove_dir_all(MISSING_DIR));
+    pnk!(fs::create_dir_all(MISSING_DIR));
+    assert!(spawn("missing_reader"));
+    #[cfg(feature = "redb_engine")]
+    assert!(fs::read_dir(MISSING_DIR).unwrap().next().is_none());
+
+    assert!(spawn("writer"));
+
+    vsdb_set_base_dir(BASE_DIR).unwrap();
+    pnk!(vsdb_set_open_mode(OpenMode::ReadOnly));
+
+    // sled can not be opened in the read-only mode
+    #[cfg(all(
+        feature = "sled_engine",
+        not(feature = "rocks_engine"),
+        not(feature = "redb_engine")
+    ))]
+    {
+        assert!(panic::catch_unwind(vsdb_flush).is_err());
+        return;
+    }
+
+    #[allow(unreachable_code)]
+    let mut hdr = pnk!(<MapxVs<u64, MapxOrd<u64, u64>> as ValueEnDe>::decode(
+        &pnk!(fs::read(HDR_PATH))
+    ));
+
+    assert_eq!(10, hdr.len());
+    (0..10u64).for_each(|i| {
+        assert!(hdr.version_exists(VersionName(&i.to_be_bytes())));
+        assert_eq!(Some(i), hdr.get(&i).unwrap().get(&i));
+    });
+
+    // the DB has been opened
+    assert!(vsdb_set_open_mode(OpenMode::ReadWrite).is_err());
+    assert!(vsdb_try_catch_up_with_primary().is_err());
+
+    // write operations of the versioned structures are refused
+    assert!(hdr.version_create(VersionName(b"new")).is_err());
+    assert!(hdr.insert(10, hdr.get(&1).unwrap()).is_err());
+    assert!(hdr.remove(&0).is_err());
+    assert!(hdr.prune(None).is_err());
+
+    // so are the write operations of the basic structures
+    let mut m = hdr.get(&0).unwrap();
+    assert!(m.insert(0, 100).is_err());
+    assert!(m.remove(&0).is_err());
+    assert!(m.retain(|_, _| false).is_err());
+    assert!(m.clear().is_err());
+    assert!(MapxOrd::<u64, u64>::try_new().is_err());
+
+    // the creations without errors returned panic
+    assert!(panic::catch_unwind(MapxOrd::<u64, u64>::new).is_err());
+
+    assert_eq!(Some(0), hdr.get(&0).unwrap().get(&0));
+}
+
+fn write() {
+    vsdb_set_base_dir(BASE_DIR).unwrap();
+
+    let mut hdr: MapxVs<u64, MapxOrd<u64, u64>> = MapxVs::new();
+    (0..10u64).for_each(|i| {
+        pnk!(hdr.version_create(VersionName(&i.to_be_bytes())));
+        let mut m = MapxOrd::new();
+        pnk!(m.insert(i, i));
+        pnk!(hdr.insert(i, m));
+    });
+    pnk!(vsdb_flush());
+
+    // only one writer can open the DB at the same time
+    assert!(spawn("second_writer"));
+
+    pnk!(fs::write(HDR_PATH, hdr.encode()));
+}
+
+fn second_writer() {
+    vsdb_set_base_dir(BASE_DIR).unwrap();
+    assert!(panic::catch_unwind(MapxOrd::<u64, u64>::new).is_err());
+}
+
+fn missing_reader() {
+    vsdb_set_base_dir(MISSING_DIR).unwrap();
+    pnk!(vsdb_set_open_mode(OpenMode::ReadOnly));
+    assert!(panic::catch_unwind(vsdb_flush).is_err());
+}
+
+fn spawn(role: &str) -> bool {
+    Command::new(env::current_exe().unwrap())
+        .args(["open_modes", "--exact"])
+        .env(ENV_ROLE, role)
+        .status()
+        .unwrap()
+        .success()
+}
@@ -0,0 +1,192 @@
+use ruc::*;
+use serde::{Deserialize, Serialize};
+use vsdb::{
+    vsdb_register_schema, vsdb_set_base_dir, BranchName, InstanceOpts, Mapx, MapxOrd,
+    MapxVs, ValueEnDe, VersionName, VsMgmt,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct AccountV0 {
+    id: u64,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct AccountV1 {
+    id: u64,
+    name: String,
+    email: Option<String>,
+}
+
+fn v0_to_v1(v: &[u8]) -> Result<Vec<u8>> {
+    let a = <AccountV0 as ValueEnDe>::decode(v).c(d!())?;
+    Ok(AccountV1 {
+        id: a.id,
+        name: a.name,
+        email: None,
+    }
+    .encode()
+    .into_vec())
+}
+
+fn broken(_: &[u8]) -> Result<Vec<u8>> {
+    Err(eg!("broken"))
+}
+
+#[test]
+fn schema_evolution() {
+    vsdb_set_base_dir("/tmp/.vsdb/schema_test").unwrap();
+
+    legacy_values();
+    lazy_upgrading();
+    failed_migration();
+    nested_handles();
+}
+
+// values stored before any schema is applied
+fn legacy_values() {
+    let mut m = MapxOrd::new();
+    (0..100u64).for_each(|i| {
+        m.insert(i, v0(i)).unwrap();
+    });
+    let hdr = m.encode();
+
+    let mut m = pnk!(<MapxOrd<u64, AccountV1> as ValueEnDe>::decode(&hdr));
+    assert!(m.try_get(&0).is_err());
+
+    assert!(m.migrate_instance("legacy").is_err());
+    pnk!(vsdb_register_schema("legacy", &[v0_to_v1]));
+    pnk!(m.migrate_instance("legacy"));
+    assert!(m.iter().all(|(i, v)| v == v1(i)));
+
+    // the old instance is untouched, as if the new handle had not been saved
+    let old = pnk!(<MapxOrd<u64, AccountV0> as ValueEnDe>::decode(&hdr));
+    assert_eq!(100, old.iter().count());
+    assert!(old.iter().all(|(i, v)| v == v0(i)));
+
+    // the schema is recorded in the new handle
+    let mut m = pnk!(<MapxOrd<u64, AccountV1> as ValueEnDe>::decode(&m.encode()));
+    (0..100u64).for_each(|i| {
+        assert_eq!(Some(v1(i)), m.get(&i));
+    });
+    m.insert(100, v1(100)).unwrap();
+    assert_eq!(Some(v1(100)), m.get(&100));
+
+    // migrating again is a no-op
+    pnk!(m.migrate_instance("legacy"));
+    assert_eq!(101, m.iter().count());
+    assert!(m.iter().all(|(i, v)| v == v1(i)));
+
+    // only one schema can be applied
+    pnk!(vsdb_register_schema("other", &[]));
+    assert!(m.migrate_instance("other").is_err());
+
+    assert!(vsdb_register_schema("legacy", &[]).is_err());
+}
+
+// values of older versions are upgraded on decoding
+fn lazy_upgrading() {
+    pnk!(vsdb_register_schema("lazy", &[]));
+    let opts = InstanceOpts {
+        schema: Some("lazy".to_owned()),
+        ..Default::default()
+    };
+
+    let mut m = Mapx::new_with_opts(&opts);
+    let mut vs = MapxVs::new_with_opts(&opts);
+    pnk!(vs.version_create(VersionName(b"v0")));
+    (0..10u64).for_each(|i| {
+        m.insert(i, v0(i)).unwrap();
+        pnk!(vs.insert(i, v0(i)));
+    });
+    pnk!(vs.version_create(VersionName(b"v1")));
+    pnk!(vs.insert(0, v0(1)));
+
+    pnk!(vsdb_register_schema("lazy", &[v0_to_v1]));
+
+    let m = pnk!(<Mapx<u64, AccountV1> as ValueEnDe>::decode(&m.encode()));
+    assert!(m.iter().all(|(i, v)| v == v1(i)));
+
+    let old_hdr = vs.encode();
+    let mut vs = pnk!(<MapxVs<u64, AccountV1> as ValueEnDe>::decode(&old_hdr));
+    assert_eq!(Some(v1(1)), vs.get(&0));
+    assert_eq!(
+        Some(v1(0)),
+        vs.get_by_branch_version(&0, BranchName(b"master"), VersionName(b"v0"))
+    );
+    pnk!(vs.insert(1, v1(100)));
+    assert_eq!(Some(v1(100)), vs.get(&1));
+
+    pnk!(vs.migrate_instance("lazy"));
+    assert_eq!(Some(v1(1)), vs.get(&0));
+    assert_eq!(
+        Some(v1(0)),
+        vs.get_by_branch_version(&0, BranchName(b"master"), VersionName(b"v0"))
+    );
+    assert_eq!(10, vs.iter().count());
+
+    // the old handle still works, it is upgraded lazily
+    let old = pnk!(<MapxVs<u64, AccountV1> as ValueEnDe>::decode(&old_hdr));
+    assert_eq!(Some(v1(1)), old.get(&0));
+    assert_eq!(10, old.iter().count());
+}
+
+// a failed migration leaves the instance untouched
+fn failed_migration() {
+    pnk!(vsdb_register_schema("broken", &[broken]));
+
+    let mut vs = MapxVs::new();
+    pnk!(vs.version_create(VersionName(b"v0")));
+    (0..10u64).for_each(|i| {
+        pnk!(vs.insert(i, v0(i)));
+    });
+
+    assert!(vs.migrate_instance("broken").is_err());
+    assert!(vs.iter().all(|(i, v)| v == v0(i)));
+
+    let vs = pnk!(<MapxVs<u64, AccountV0> as ValueEnDe>::decode(&vs.encode()));
+    assert_eq!(10, vs.iter().count());
+}
+
+// the handles stored in other instances are copies
+fn nested_handles() {
+    let mut outer = MapxOrd::new();
+    let mut inner = Mapx::new();
+    (0..10u64).for_each(|i| {
+        inner.insert(i, v0(i)).unwrap();
+    });
+    outer.insert(0u64, inner).unwrap();
+
+    let hdr = outer.encode();
+    let mut outer =
+        pnk!(<MapxOrd<u64, Mapx<u64, AccountV1>> as ValueEnDe>::decode(&hdr));
+
+    pnk!(vsdb_register_schema("nested", &[v0_to_v1]));
+    let mut inner = outer.get(&0).unwrap();
+    pnk!(inner.migrate_instance("nested"));
+    assert!(inner.iter().all(|(i, v)| v == v1(i)));
+
+    // the stored copy still points to the old instance
+    assert!(outer.get(&0).unwrap().try_get(&0).is_err());
+
+    outer.insert(0, inner).unwrap();
+    let outer = pnk!(<MapxOrd<u64, Mapx<u64, AccountV1>> as ValueEnDe>::decode(
+        &outer.encode()
+    ));
+    assert!(outer.get(&0).unwrap().iter().all(|(i, v)| v == v1(i)));
+}
+
+fn v0(i: u64) -> AccountV0 {
+    AccountV0 {
+        id: i,
+        name: format!("account-{}", i),
+    }
+}
+
+fn v1(i: u64) -> AccountV1 {
+    AccountV1 {
+        id: i,
+        name: format!("account-{}", i),
+        email: None,
+    }
+}
@@ -0,0 +1,95 @@
+use ruc::*;
+use std::{env, fs, process, process::Command, thread, time::Duration};
+use vsdb::{
+    vsdb_durable_commit, vsdb_set_base_dir, vsdb_set_sync_policy, MapxOrd, MapxVs,
+    SyncPolicy, ValueEnDe, VersionName, VsMgmt,
+};
+
+const BASE_DIR: &str = "/tmp/.vsdb/sync_policy_test";
+const HDR_PATH: &str = "/tmp/.vsdb/sync_policy_test_hdr";
+
+// each role must be played in a new process
+const ENV_ROLE: &str = "VSDB_SYNC_POLICY_TEST_ROLE";
+
+const CNT: u64 = 100;
+
+#[test]
+fn sync_policies() {
+    vsdb_set_base_dir(BASE_DIR).unwrap();
+
+    match env::var(ENV_ROLE).as_deref() {
+        Ok("every_write") => every_write_and_crash(),
+        Ok("interval") => interval_and_crash(),
+        Ok("durable_commit") => durable_commit_and_crash(),
+        _ => {}
+    }
+
+    assert!(vsdb_set_sync_policy(SyncPolicy::Interval(0)).is_err());
+
+    for role in ["every_write", "interval", "durable_commit"] {
+        let status = Command::new(env::current_exe().unwrap())
+            .args(["sync_policies", "--exact"])
+            .env(ENV_ROLE, role)
+            .status()
+            .unwrap();
+        assert!(!status.success());
+    }
+
+    // no synced data is lost in the crashes
+    let hdr = pnk!(<MapxVs<u64, MapxOrd<u64, u64>> as ValueEnDe>::decode(
+        &pnk!(fs::read(HDR_PATH))
+    ));
+    assert!(hdr.version_exists(VersionName(b"v0")));
+    assert_eq!(3, hdr.len());
+    (0..3u64).for_each(|i| {
+        let m = hdr.get(&i).unwrap();
+        assert_eq!(CNT as usize, m.len());
+        assert_eq!(CNT as usize, m.iter().count());
+    });
+
+    // the DB has been opened
+    assert!(vsdb_set_sync_policy(SyncPolicy::EveryWrite).is_err());
+}
+
+fn every_write_and_crash() -> ! {
+    pnk!(vsdb_set_sync_policy(SyncPolicy::EveryWrite));
+
+    let mut hdr: MapxVs<u64, MapxOrd<u64, u64>> = MapxVs::new();
+    pnk!(fs::write(HDR_PATH, hdr.encode()));
+    pnk!(hdr.version_create(VersionName(b"v0")));
+    pnk!(hdr.insert(0, write_cnt()));
+
+    process::abort();
+}
+
+fn interval_and_crash() -> ! {
+    pnk!(vsdb_set_sync_policy(SyncPolicy::Interval(10)));
+
+    let mut hdr = load_hdr();
+    pnk!(hdr.insert(1, write_cnt()));
+    thread::sleep(Duration::from_millis(200));
+
+    process::abort();
+}
+
+fn durable_commit_and_crash() -> ! {
+    let mut hdr = load_hdr();
+    pnk!(hdr.insert(2, write_cnt()));
+    pnk!(vsdb_durable_commit());
+
+    process::abort();
+}
+
+fn load_hdr() -> MapxVs<u64, MapxOrd<u64, u64>> {
+    pnk!(<MapxVs<u64, MapxOrd<u64, u64>> as ValueEnDe>::decode(
+        &pnk!(fs::read(HDR_PATH))
+    ))
+}
+
+fn write_cnt() -> MapxOrd<u64, u64> {
+    let mut m = MapxOrd::new();
+    (0..CNT).for_each(|i| {
+        m.insert(i, i).unwrap();
+    });
+    m
+}
@@ -0,0 +1,71 @@
+use rand::Rng;
+use vsdb::{basic::vecx_raw::VecxRaw, vsdb_set_base_dir, ValueEnDe, Vecx};
+
+#[test]
+fn middle_insert_remove() {
+    vsdb_set_base_dir("/tmp/.vsdb/vecx_index_test").unwrap();
+
+    typed();
+    raw();
+}
+
+fn typed() {
+    let mut rng = rand::thread_rng();
+    let mut v: Vecx<u64> = (0..10000).collect();
+    let mut model = (0..10000).collect::<Vec<u64>>();
+
+    for i in 0..2000 {
+        let idx = rng.gen_range(0..=model.len());
+        v.insert(idx, 100000 + i).unwrap();
+        model.insert(idx, 100000 + i);
+
+        let idx = rng.gen_range(0..model.len());
+        assert_eq!(v.remove(idx).unwrap(), model.remove(idx));
+    }
+    assert_eq!(v.len(), model.len());
+    for _ in 0..500 {
+        let idx = rng.gen_range(0..model.len());
+        assert_eq!(v.get(idx), Some(model[idx]));
+    }
+
+    assert_eq!(v.swap_remove(3).unwrap(), model.swap_remove(3));
+    v.update(5, 7).unwrap();
+    model[5] = 7;
+    *v.get_mut(6).unwrap() = 8;
+    model[6] = 8;
+    assert!(v.iter().eq(model.iter().copied()));
+    assert!(v.iter().rev().eq(model.iter().rev().copied()));
+
+    v.iter_mut().for_each(|mut i| *i += 1);
+    model.iter_mut().for_each(|i| *i += 1);
+    assert!(v.iter().eq(model.iter().copied()));
+
+    v.retain(|i| i % 2 == 0).unwrap();
+    model.retain(|i| i % 2 == 0);
+    assert!(v.iter().eq(model.iter().copied()));
+
+    // the positional index survives the round trip
+    let v = <Vecx<u64> as ValueEnDe>::decode(&v.encode()).unwrap();
+    assert_eq!(v.len(), model.len());
+    assert!(v.iter().eq(model.iter().copied()));
+}
+
+fn raw() {
+    let mut v = VecxRaw::new();
+    let mut model = vec![];
+    for i in 0..3000u32 {
+        let idx = i as usize / 2;
+        v.insert_ref(idx, &i.to_be_bytes()).unwrap();
+        model.insert(idx, i.to_be_bytes().to_vec());
+    }
+    for idx in (0..1000).rev() {
+        assert_eq!(v.remove(idx * 2).unwrap().to_vec(), model.remove(idx * 2));
+    }
+    assert_eq!(v.len(), model.len());
+    assert!(v.iter().map(|i| i.to_vec()).eq(model.iter().cloned()));
+
+    let tail = v.split_off(1000).unwrap();
+    assert_eq!(tail.get(0).unwrap().to_vec(), model[1000]);
+    v.truncate(10).unwrap();
+    assert_eq!(v.last().unwrap().to_vec(), model[9]);
+}
@@ -604,7 +604,7 @@ fn default_branch_operations(hdr: &mut MapxRawVs) {
     let (s, r) = channel();
     for i in (u64::MAX - 10)..u64::MAX {
         let ss = s.clone();
-        let mut h = hdr.clone();
+        let mut h = unsafe { hdr.shadow() };
         thread::spawn(move || {
             pnk!(h.branch_create(
                 BranchName(&i.to_be_bytes()),